@@ -0,0 +1,96 @@
+//! AI 蛇并行决策的扩展性
+//!
+//! 所有蛇同时到达决策时间，测一轮 `update_thinking` 的耗时。
+//! 同一批蛇分别在 1、2、4、8 个线程的 rayon 线程池里决策（不超过本机核数），
+//! 蛇越多、线程越多，耗时应该越接近按线程数线性下降。
+//!
+//! - `ai_thinking`: 游戏棋盘（32×24）上放满寻路 AI 蛇，最多一百条左右
+//! - `swarm_thinking`: 蜂群模式的大棋盘（160×120）上 200 和 256 条按性格权重生成的 AI 蛇
+//!
+//! 运行：`cargo bench --bench ai_thinking`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use macroquad::prelude::*;
+use ::rand::SeedableRng;
+use rtest::fruits::create_fruit_registry;
+use rtest::game::{AIManager, BrainKind, GameRng, HazardManager, PlayerView, Snake, Swarm, SwarmConfig, SWARM_MAX_SNAKES};
+use rtest::types::{BuffState, Fruit, Topology};
+
+/// 生成最多 `count` 条寻路 AI 蛇
+fn crowd(count: usize) -> AIManager {
+    let mut rng = GameRng::seed_from_u64(42);
+    let mut manager = AIManager::new();
+    manager.max_snakes = count;
+    while manager.spawn_snake_of(BrainKind::Pathfinding, &[], &mut rng) {}
+    manager
+}
+
+fn bench_think(c: &mut Criterion) {
+    let registry = create_fruit_registry();
+    let mut player = Snake::new();
+    player.set_body([ivec2(0, 0)]);
+    let fruits: Vec<Fruit> = (0..8).map(|i| Fruit::new(ivec2(2 + i * 3, 1), "normal", 0.0, 0.0)).collect();
+    let buffs = BuffState::default();
+    let players = [PlayerView { snake: &player, buffs: &buffs }];
+    let hazards = HazardManager::new();
+
+    let mut group = c.benchmark_group("ai_thinking");
+    for count in [16usize, 48, 96] {
+        let mut manager = crowd(count);
+        let snakes = manager.snakes.len();
+        for threads in thread_counts() {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            let id = BenchmarkId::new(format!("{snakes}_snakes"), threads);
+            group.bench_function(id, |b| {
+                let mut rng = GameRng::seed_from_u64(7);
+                b.iter(|| {
+                    // dt 足够大，每条蛇都要决策
+                    pool.install(|| {
+                        manager.update_thinking(
+                            ivec2(30, 22), &fruits, &registry, &players, &hazards, &[],
+                            Topology::Bounded, 0.0, 1.0, &mut rng,
+                        )
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_swarm_think(c: &mut Criterion) {
+    let registry = create_fruit_registry();
+    let hazards = HazardManager::new();
+
+    let mut group = c.benchmark_group("swarm_thinking");
+    group.sample_size(10);
+    for count in [200usize, SWARM_MAX_SNAKES] {
+        let mut swarm = Swarm::new(SwarmConfig::default().with_snakes(count));
+        let snakes = swarm.ai.snakes.len();
+        for threads in thread_counts() {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            let id = BenchmarkId::new(format!("{snakes}_snakes"), threads);
+            group.bench_function(id, |b| {
+                let mut rng = GameRng::seed_from_u64(7);
+                b.iter(|| {
+                    pool.install(|| {
+                        swarm.ai.update_thinking(
+                            swarm.food, &swarm.fruits, &registry, &[], &hazards, &[],
+                            swarm.config.topology, 0.0, 1.0, &mut rng,
+                        )
+                    })
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+/// 测试的线程数：1、2、4、8，不超过本机核数
+fn thread_counts() -> impl Iterator<Item = usize> {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    [1usize, 2, 4, 8].into_iter().filter(move |&t| t <= cores)
+}
+
+criterion_group!(benches, bench_think, bench_swarm_think);
+criterion_main!(benches);
@@ -0,0 +1,108 @@
+//! 蛇移动与碰撞检测的性能
+//!
+//! 直接测游戏里真正走的两条路径：
+//!
+//! - `snake_move`: 玩家蛇 [`Snake::move_forward_on`]，蛇沿一条经过所有格子的回路前进，
+//!   不会撞到自己，每一步都要完整地做一遍自撞检测
+//! - `ai_movement`: 棋盘上放满 AI 蛇，测一帧 [`AIManager::update_movement`]
+//!   （所有蛇各走一步，和玩家、彼此之间做碰撞检测，撞死的蛇掉落食物）
+//!
+//! 运行：`cargo bench --bench occupancy`
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use macroquad::prelude::*;
+use ::rand::SeedableRng;
+use rtest::constants::{GRID_H, GRID_W};
+use rtest::fruits::create_fruit_registry;
+use rtest::game::{AIManager, BrainKind, GameRng, HazardManager, PlayerView, Snake};
+use rtest::types::{BuffState, Topology};
+
+const STEPS: usize = 100;
+
+/// 经过棋盘每一格的回路：第 0 列作为回程，其余按行蛇形扫描
+fn board_cycle() -> Vec<IVec2> {
+    let mut path = Vec::with_capacity((GRID_W * GRID_H) as usize);
+    for y in 0..GRID_H {
+        if y % 2 == 0 {
+            path.extend((1..GRID_W).map(|x| ivec2(x, y)));
+        } else {
+            path.extend((1..GRID_W).rev().map(|x| ivec2(x, y)));
+        }
+    }
+    path.extend((0..GRID_H).rev().map(|y| ivec2(0, y)));
+    path
+}
+
+/// 蛇头在回路第 `len` 格、蛇身沿回路向后排开的玩家蛇
+fn snake_on_cycle(cycle: &[IVec2], len: usize) -> Snake {
+    let mut snake = Snake::new();
+    snake.set_body((0..len).map(|k| cycle[len - k]));
+    snake.dir = cycle[len] - cycle[len - 1];
+    snake
+}
+
+fn bench_snake_move(c: &mut Criterion) {
+    let cycle = board_cycle();
+    let mut group = c.benchmark_group("snake_move");
+    for len in [10usize, 150, 600] {
+        group.bench_with_input(BenchmarkId::from_parameter(len), &len, |b, &len| {
+            b.iter_batched(
+                || snake_on_cycle(&cycle, len),
+                |mut snake| {
+                    for step in 1..=STEPS {
+                        snake.set_direction(cycle[(len + step) % cycle.len()] - snake.head());
+                        black_box(snake.move_forward_on(Topology::Bounded, false));
+                    }
+                    snake
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// 生成最多 `count` 条寻路 AI 蛇，全部到了该移动的时间
+fn crowd(count: usize) -> AIManager {
+    let mut rng = GameRng::seed_from_u64(42);
+    let mut manager = AIManager::new();
+    manager.max_snakes = count;
+    while manager.spawn_snake_of(BrainKind::Pathfinding, &[], &mut rng) {}
+    for snake in &mut manager.snakes {
+        snake.move_accumulator = 1.0;
+    }
+    manager
+}
+
+fn bench_ai_movement(c: &mut Criterion) {
+    let registry = create_fruit_registry();
+    let mut player = Snake::new();
+    player.set_body((0..8).map(|x| ivec2(8 - x, GRID_H / 2)));
+    let buffs = BuffState::default();
+    let players = [PlayerView { snake: &player, buffs: &buffs }];
+    let hazards = HazardManager::new();
+
+    let mut group = c.benchmark_group("ai_movement");
+    for count in [16usize, 48, 96] {
+        let snakes = crowd(count).snakes.len();
+        group.bench_with_input(BenchmarkId::from_parameter(snakes), &count, |b, &count| {
+            let mut rng = GameRng::seed_from_u64(7);
+            b.iter_batched(
+                || crowd(count),
+                |mut manager| {
+                    let mut food = ivec2(GRID_W - 2, GRID_H - 2);
+                    let result = manager.update_movement(
+                        &mut food, &mut vec![], &players, &hazards, &[], &mut vec![],
+                        &registry, Topology::Torus, 0.0, 0.0, &mut rng,
+                    );
+                    (manager, result)
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_snake_move, bench_ai_movement);
+criterion_main!(benches);
@@ -26,5 +26,7 @@
 //! - 编码: PCM
 
 pub mod sound_manager;
+pub mod sound_effects;
 
 pub use sound_manager::*;
+pub use sound_effects::*;
@@ -0,0 +1,61 @@
+//! 游戏逻辑触发的音效
+//!
+//! 游戏逻辑只通过 [`SoundEffects`] 播放音效：游戏窗口里是加载好的 [`SoundManager`]，
+//! 没有音频设备的无界面模拟（例如排行榜服务器重放对局）用 [`Silence`]。
+
+use super::SoundManager;
+
+/// 游戏逻辑会播放的音效
+pub trait SoundEffects {
+    /// 吃到食物
+    fn play_eat(&self);
+    /// 吃到陷阱、受到威胁
+    fn play_trap(&self);
+    /// 获得能力、援军
+    fn play_power(&self);
+    /// 游戏结束
+    fn play_game_over(&self);
+    /// 完美通关、击败 Boss
+    fn play_victory(&self);
+    /// 切断蛇身
+    fn play_cut(&self);
+}
+
+impl SoundEffects for SoundManager {
+    fn play_eat(&self) {
+        SoundManager::play_eat(self);
+    }
+
+    fn play_trap(&self) {
+        SoundManager::play_trap(self);
+    }
+
+    fn play_power(&self) {
+        SoundManager::play_power(self);
+    }
+
+    fn play_game_over(&self) {
+        SoundManager::play_game_over(self);
+    }
+
+    fn play_victory(&self) {
+        SoundManager::play_victory(self);
+    }
+
+    fn play_cut(&self) {
+        SoundManager::play_cut(self);
+    }
+}
+
+/// 不发声（无界面模拟用）
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Silence;
+
+impl SoundEffects for Silence {
+    fn play_eat(&self) {}
+    fn play_trap(&self) {}
+    fn play_power(&self) {}
+    fn play_game_over(&self) {}
+    fn play_victory(&self) {}
+    fn play_cut(&self) {}
+}
@@ -32,6 +32,8 @@ use macroquad::audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound
 /// - `trap_sound`: 低频较长音，表示负面效果
 /// - `power_sound`: 中频音，表示获得能力
 /// - `over_sound`: 最低频长音，表示游戏结束
+/// - `victory_sound`: 最高频长音，表示完美通关
+/// - `cut_sound`: 中低频短音，表示蛇身被切断
 pub struct SoundManager {
     /// 吃食物音效 (880Hz, 0.08s)
     pub eat_sound: Sound,
@@ -41,6 +43,12 @@ pub struct SoundManager {
     pub power_sound: Sound,
     /// 游戏结束音效 (220Hz, 0.3s)
     pub over_sound: Sound,
+    /// 完美通关音效 (1320Hz, 0.5s)
+    pub victory_sound: Sound,
+    /// 切断蛇身音效 (440Hz, 0.06s)
+    pub cut_sound: Sound,
+    /// 主音量 (0.0-1.0)，与每种音效自身的音量相乘
+    pub volume: f32,
 }
 
 impl SoundManager {
@@ -50,33 +58,60 @@ impl SoundManager {
         let trap_sound = load_sound_from_bytes(&make_tone_wav(330.0, 0.15, 0.5)).await.unwrap();
         let power_sound = load_sound_from_bytes(&make_tone_wav(660.0, 0.2, 0.6)).await.unwrap();
         let over_sound = load_sound_from_bytes(&make_tone_wav(220.0, 0.30, 0.5)).await.unwrap();
+        let victory_sound = load_sound_from_bytes(&make_tone_wav(1320.0, 0.5, 0.5)).await.unwrap();
+        let cut_sound = load_sound_from_bytes(&make_tone_wav(440.0, 0.06, 0.6)).await.unwrap();
 
         SoundManager {
             eat_sound,
             trap_sound,
             power_sound,
             over_sound,
+            victory_sound,
+            cut_sound,
+            volume: 1.0,
+        }
+    }
+
+    /// 设置主音量（clamp 到 0.0-1.0）
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    /// 按主音量播放音效（静音时不播放）
+    fn play(&self, sound: &Sound, volume: f32) {
+        if self.volume > 0.0 {
+            play_sound(sound, PlaySoundParams { looped: false, volume: volume * self.volume });
         }
     }
 
     /// 播放吃食物音效
     pub fn play_eat(&self) {
-        play_sound(&self.eat_sound, PlaySoundParams { looped: false, volume: 0.5 });
+        self.play(&self.eat_sound, 0.5);
     }
 
     /// 播放陷阱音效
     pub fn play_trap(&self) {
-        play_sound(&self.trap_sound, PlaySoundParams { looped: false, volume: 0.6 });
+        self.play(&self.trap_sound, 0.6);
     }
 
     /// 播放功能果实音效
     pub fn play_power(&self) {
-        play_sound(&self.power_sound, PlaySoundParams { looped: false, volume: 0.7 });
+        self.play(&self.power_sound, 0.7);
     }
 
     /// 播放游戏结束音效
     pub fn play_game_over(&self) {
-        play_sound(&self.over_sound, PlaySoundParams { looped: false, volume: 0.7 });
+        self.play(&self.over_sound, 0.7);
+    }
+
+    /// 播放完美通关音效
+    pub fn play_victory(&self) {
+        self.play(&self.victory_sound, 0.7);
+    }
+
+    /// 播放切断蛇身音效
+    pub fn play_cut(&self) {
+        self.play(&self.cut_sound, 0.6);
     }
 }
 
@@ -0,0 +1,107 @@
+//! 机器人 HTTP 客户端
+//!
+//! 基于 reqwest 阻塞客户端，只在每个机器人自己的后台线程（见 [`super::BotController`]）中调用。
+
+use std::fmt;
+use std::time::Duration;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use super::protocol::{BotInfo, GameRequest, MoveResponse};
+
+/// 机器人请求错误
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BotError {
+    /// 连接失败、超时等网络错误
+    Network(String),
+    /// 机器人返回非 2xx 状态码
+    Http(u16),
+    /// 响应内容无法解析
+    Parse(String),
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Network(msg) => write!(f, "network error: {}", msg),
+            BotError::Http(status) => write!(f, "bot returned HTTP {}", status),
+            BotError::Parse(msg) => write!(f, "invalid response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+/// 一个机器人的客户端
+#[derive(Clone, Debug)]
+pub struct BotClient {
+    base_url: String,
+    http: Client,
+}
+
+impl BotClient {
+    /// 创建客户端，`base_url` 形如 `http://127.0.0.1:8000`；`timeout` 是单次请求的超时
+    pub fn new(base_url: &str, timeout: Duration) -> Result<Self, BotError> {
+        let http = Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| BotError::Network(e.to_string()))?;
+        Ok(BotClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        })
+    }
+
+    /// 机器人地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `GET /`：外观和版本
+    pub fn info(&self) -> Result<BotInfo, BotError> {
+        let response = self
+            .http
+            .get(format!("{}/", self.base_url))
+            .send()
+            .map_err(|e| BotError::Network(e.to_string()))?;
+        let text = Self::read_text(response)?;
+        serde_json::from_str(&text).map_err(|e| BotError::Parse(e.to_string()))
+    }
+
+    /// `POST /start`：一局开始
+    pub fn start(&self, request: &GameRequest) -> Result<(), BotError> {
+        self.post("start", request).map(|_| ())
+    }
+
+    /// `POST /move`：这一回合往哪走
+    pub fn request_move(&self, request: &GameRequest) -> Result<MoveResponse, BotError> {
+        let text = self.post("move", request)?;
+        serde_json::from_str(&text).map_err(|e| BotError::Parse(e.to_string()))
+    }
+
+    /// `POST /end`：一局结束
+    pub fn end(&self, request: &GameRequest) -> Result<(), BotError> {
+        self.post("end", request).map(|_| ())
+    }
+
+    /// 发送请求体，返回响应文本
+    fn post(&self, endpoint: &str, request: &GameRequest) -> Result<String, BotError> {
+        let body = serde_json::to_string(request).map_err(|e| BotError::Parse(e.to_string()))?;
+        let response = self
+            .http
+            .post(format!("{}/{}", self.base_url, endpoint))
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .map_err(|e| BotError::Network(e.to_string()))?;
+        Self::read_text(response)
+    }
+
+    /// 检查状态码并读出响应体
+    fn read_text(response: reqwest::blocking::Response) -> Result<String, BotError> {
+        let status = response.status();
+        if !status.is_success() {
+            return Err(BotError::Http(status.as_u16()));
+        }
+        response.text().map_err(|e| BotError::Network(e.to_string()))
+    }
+}
@@ -0,0 +1,375 @@
+//! 机器人控制器
+//!
+//! 每个机器人一个后台线程，HTTP 请求不会卡住游戏主循环。游戏在机器人的蛇每走一步之后
+//! 调用 [`BotController::request_move`] 发出下一回合的 `/move`，轮到它走时调用
+//! [`BotController::decide`]：回复到了就按回复转向；超过时限、出错或回复非法方向时
+//! 按原方向继续走（记一次回退），迟到的回复直接丢弃。机器人慢的时候后台线程只发最新的 `/move`，
+//! 不会在它恢复后把积压的旧回合一股脑发过去。
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+use macroquad::prelude::Color;
+use crate::broadcast::take_value;
+use crate::game::MAX_PLAYERS;
+use super::client::{BotClient, BotError};
+use super::protocol::{parse_hex_color, BotInfo, Direction, GameRequest, MoveResponse};
+
+/// 默认每回合的回复时限
+pub const DEFAULT_MOVE_TIMEOUT: Duration = Duration::from_millis(500);
+/// 一局最多的机器人数（至少留一个本地玩家）
+pub const MAX_BOTS: usize = MAX_PLAYERS - 1;
+
+/// 命令行指定的机器人
+#[derive(Clone, PartialEq, Debug)]
+pub struct BotOptions {
+    /// 每个机器人的地址，按顺序坐在最后几个玩家位置上
+    pub urls: Vec<String>,
+    /// 每回合的回复时限
+    pub timeout: Duration,
+}
+
+impl BotOptions {
+    /// 从参数里取出 `--bot URL`（可重复）和 `--bot-timeout MS`，其余参数留给别的解析
+    ///
+    /// 没有 `--bot` 时返回 `Ok(None)`
+    pub fn take(args: &mut Vec<String>) -> Result<Option<Self>, String> {
+        let mut urls = Vec::new();
+        while let Some(url) = take_value(args, "--bot")? {
+            urls.push(url);
+        }
+        let timeout = match take_value(args, "--bot-timeout")? {
+            Some(ms) => match ms.parse::<u64>() {
+                Ok(ms) if ms > 0 => Duration::from_millis(ms),
+                _ => return Err(format!("--bot-timeout expects milliseconds, got {:?}", ms)),
+            },
+            None => DEFAULT_MOVE_TIMEOUT,
+        };
+        if urls.is_empty() {
+            return Ok(None);
+        }
+        if urls.len() > MAX_BOTS {
+            return Err(format!("at most {} bots can play, got {}", MAX_BOTS, urls.len()));
+        }
+        Ok(Some(BotOptions { urls, timeout }))
+    }
+}
+
+/// 发给后台线程的请求
+#[derive(Debug)]
+enum BotCall {
+    Start(GameRequest),
+    Move(GameRequest),
+    End(GameRequest),
+}
+
+/// 后台线程的结果
+#[derive(Debug)]
+enum BotReply {
+    Info(Result<BotInfo, BotError>),
+    Move { turn: u32, result: Result<MoveResponse, BotError>, latency: Duration },
+}
+
+/// 轮到机器人走时的决定
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BotDecision {
+    /// 没有在等的请求（还没发出这一回合的 `/move`）
+    Idle,
+    /// 请求还在路上，还没到时限
+    Wait,
+    /// 机器人选的方向
+    Move(Direction),
+    /// 超时或出错，按原方向继续走
+    Fallback,
+}
+
+/// 一个 HTTP 机器人
+#[derive(Debug)]
+pub struct BotController {
+    url: String,
+    timeout: Duration,
+    calls: Sender<BotCall>,
+    replies: Receiver<BotReply>,
+    info: Option<BotInfo>,
+    /// 下一个 `/move` 的回合数
+    turn: u32,
+    /// 在等的回合和发出时间
+    pending: Option<(u32, Instant)>,
+    /// 已经到了、还没用上的回复
+    arrived: Option<(Result<MoveResponse, BotError>, Duration)>,
+    latency: Option<Duration>,
+    fallbacks: u32,
+    last_error: Option<BotError>,
+}
+
+impl BotController {
+    /// 连接 `url` 上的机器人：后台线程先取一次外观（`GET /`），再按顺序处理请求
+    pub fn spawn(url: &str, timeout: Duration) -> Result<Self, BotError> {
+        // 客户端的超时放宽一倍，迟到的回复由回合数过滤，线程不会一直卡在一个请求上
+        let client = BotClient::new(url, timeout * 2)?;
+        let url = client.base_url().to_string();
+        let (call_tx, call_rx) = mpsc::channel::<BotCall>();
+        let (reply_tx, reply_rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            if reply_tx.send(BotReply::Info(client.info())).is_err() {
+                return;
+            }
+            // 还没处理的请求（按发出顺序）
+            let mut queued = VecDeque::new();
+            loop {
+                // 控制器被丢弃时通道关闭，线程退出
+                let Some(call) = queued.pop_front().or_else(|| call_rx.recv().ok()) else {
+                    return;
+                };
+                match call {
+                    BotCall::Start(request) => {
+                        let _ = client.start(&request);
+                    }
+                    BotCall::Move(request) => {
+                        // 机器人慢的时候请求会越积越多；后面已经有更新的 /move 时这个早就作废了，不再发
+                        queued.extend(call_rx.try_iter());
+                        if queued.iter().any(|c| matches!(c, BotCall::Move(_))) {
+                            continue;
+                        }
+                        let sent = Instant::now();
+                        let result = client.request_move(&request);
+                        let reply = BotReply::Move { turn: request.turn, result, latency: sent.elapsed() };
+                        if reply_tx.send(reply).is_err() {
+                            return;
+                        }
+                    }
+                    BotCall::End(request) => {
+                        let _ = client.end(&request);
+                    }
+                }
+            }
+        });
+        Ok(BotController {
+            url,
+            timeout,
+            calls: call_tx,
+            replies: reply_rx,
+            info: None,
+            turn: 0,
+            pending: None,
+            arrived: None,
+            latency: None,
+            fallbacks: 0,
+            last_error: None,
+        })
+    }
+
+    /// 机器人地址
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// 每回合的回复时限
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 机器人的外观（`GET /` 还没回复或失败时为 None）
+    pub fn info(&mut self) -> Option<&BotInfo> {
+        self.drain();
+        self.info.as_ref()
+    }
+
+    /// 机器人自选的颜色
+    pub fn color(&mut self) -> Option<Color> {
+        self.info().and_then(|info| parse_hex_color(&info.color))
+    }
+
+    /// 最近一次回复的延迟
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// 本局回退的回合数
+    pub fn fallbacks(&self) -> u32 {
+        self.fallbacks
+    }
+
+    /// 最近一次失败的原因
+    pub fn last_error(&self) -> Option<&BotError> {
+        self.last_error.as_ref()
+    }
+
+    /// 一局开始：回合数和统计清零，发出 `/start`
+    pub fn start(&mut self, request: GameRequest) {
+        self.turn = 0;
+        self.pending = None;
+        self.arrived = None;
+        self.fallbacks = 0;
+        self.last_error = None;
+        let _ = self.calls.send(BotCall::Start(request));
+    }
+
+    /// 发出下一回合的 `/move`（请求里的回合数由控制器填写），之前没回复的请求作废
+    pub fn request_move(&mut self, mut request: GameRequest) {
+        request.turn = self.turn;
+        self.pending = Some((self.turn, Instant::now()));
+        self.arrived = None;
+        self.turn += 1;
+        let _ = self.calls.send(BotCall::Move(request));
+    }
+
+    /// 一局结束：发出 `/end`，不再等待没回复的请求
+    pub fn end(&mut self, mut request: GameRequest) {
+        request.turn = self.turn;
+        self.pending = None;
+        self.arrived = None;
+        let _ = self.calls.send(BotCall::End(request));
+    }
+
+    /// 轮到机器人走时调用：回复到了就用回复，过了时限就回退
+    pub fn decide(&mut self) -> BotDecision {
+        self.drain();
+        let Some((_, sent)) = self.pending else {
+            return BotDecision::Idle;
+        };
+        if let Some((result, latency)) = self.arrived.take() {
+            self.pending = None;
+            self.latency = Some(latency);
+            return match result {
+                Ok(response) => BotDecision::Move(response.direction),
+                Err(err) => {
+                    self.last_error = Some(err);
+                    self.fallbacks += 1;
+                    BotDecision::Fallback
+                }
+            };
+        }
+        if sent.elapsed() >= self.timeout {
+            self.pending = None;
+            self.fallbacks += 1;
+            self.last_error = Some(BotError::Network(format!("no reply within {} ms", self.timeout.as_millis())));
+            return BotDecision::Fallback;
+        }
+        BotDecision::Wait
+    }
+
+    /// 收下所有已到的回复：记下外观和正在等的那个回合的回复，迟到的回复丢弃
+    fn drain(&mut self) {
+        while let Ok(reply) = self.replies.try_recv() {
+            match reply {
+                BotReply::Info(result) => self.info = result.ok(),
+                BotReply::Move { turn, result, latency } => {
+                    if self.pending.is_some_and(|(waiting, _)| waiting == turn) {
+                        self.arrived = Some((result, latency));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::{BattleSnake, Board, Coord, GameInfo, ReferenceBot, REFERENCE_COLOR};
+    use crate::types::Topology;
+
+    /// 一条在棋盘中间往右走的蛇，食物在正上方
+    fn request() -> GameRequest {
+        let body = vec![Coord { x: 5, y: 5 }, Coord { x: 4, y: 5 }, Coord { x: 3, y: 5 }];
+        let me = BattleSnake { id: "P2".into(), name: "P2".into(), health: 100, head: body[0], length: 3, body, ..Default::default() };
+        let board = Board { width: 11, height: 11, food: vec![Coord { x: 5, y: 9 }], snakes: vec![me], ..Default::default() };
+        GameRequest::new(&GameInfo::new("test", "", Topology::Bounded, 500), 0, board, "P2")
+    }
+
+    /// 等到不再是 Wait
+    fn settle(bot: &mut BotController) -> BotDecision {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            match bot.decide() {
+                BotDecision::Wait if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(5)),
+                decision => return decision,
+            }
+        }
+    }
+
+    #[test]
+    fn test_bot_options_parsing() {
+        let mut args: Vec<String> = ["--bot", "http://a", "--udp", "--bot", "http://b", "--bot-timeout", "250"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let options = BotOptions::take(&mut args).unwrap().unwrap();
+        assert_eq!(options.urls, ["http://a", "http://b"]);
+        assert_eq!(options.timeout, Duration::from_millis(250));
+        assert_eq!(args, ["--udp"]);
+
+        assert_eq!(BotOptions::take(&mut vec![]).unwrap(), None);
+        let mut args: Vec<String> = ["--bot-timeout", "0", "--bot", "x"].iter().map(|s| s.to_string()).collect();
+        assert!(BotOptions::take(&mut args).is_err());
+        let mut args: Vec<String> = (0..=MAX_BOTS).flat_map(|_| ["--bot".to_string(), "x".to_string()]).collect();
+        assert!(BotOptions::take(&mut args).is_err());
+        assert!(BotOptions::take(&mut vec!["--bot".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_reference_bot_plays_over_loopback() {
+        let server = ReferenceBot::start("127.0.0.1:0").unwrap();
+        let mut bot = BotController::spawn(&server.url(), Duration::from_secs(2)).unwrap();
+        assert_eq!(bot.decide(), BotDecision::Idle);
+
+        bot.start(request());
+        bot.request_move(request());
+        assert_eq!(settle(&mut bot), BotDecision::Move(Direction::Up));
+        assert!(bot.latency().is_some());
+        assert_eq!(bot.decide(), BotDecision::Idle);
+        // 外观在第一个请求之前就取到了
+        assert_eq!(bot.info().map(|i| i.color.as_str()), Some(REFERENCE_COLOR));
+        assert!(bot.color().is_some());
+
+        bot.request_move(request());
+        assert_eq!(settle(&mut bot), BotDecision::Move(Direction::Up));
+        assert_eq!(bot.fallbacks(), 0);
+        bot.end(request());
+    }
+
+    #[test]
+    fn test_slow_bot_falls_back_and_late_reply_is_dropped() {
+        let server = ReferenceBot::start("127.0.0.1:0").unwrap().with_delay(Duration::from_millis(300));
+        let mut bot = BotController::spawn(&server.url(), Duration::from_millis(100)).unwrap();
+        bot.start(request());
+        bot.request_move(request());
+        assert_eq!(settle(&mut bot), BotDecision::Fallback);
+        assert_eq!(bot.fallbacks(), 1);
+        assert!(bot.last_error().is_some());
+
+        // 迟到的第 0 回合回复不会被当成第 1 回合的
+        bot.request_move(request());
+        std::thread::sleep(Duration::from_millis(250));
+        assert_eq!(bot.decide(), BotDecision::Fallback);
+        assert_eq!(bot.fallbacks(), 2);
+    }
+
+    #[test]
+    fn test_slow_bot_only_gets_the_latest_move() {
+        let server = ReferenceBot::start("127.0.0.1:0").unwrap().with_delay(Duration::from_millis(100));
+        let mut bot = BotController::spawn(&server.url(), Duration::from_secs(1)).unwrap();
+        bot.start(request());
+        // 游戏每 10 毫秒发一次 /move，机器人每个要 100 毫秒
+        for _ in 0..30 {
+            bot.request_move(request());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // 最新的一回合照样拿到回复
+        assert_eq!(settle(&mut bot), BotDecision::Move(Direction::Up));
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(server.moves() <= 6, "bot got {} stale /move requests", server.moves());
+    }
+
+    #[test]
+    fn test_unreachable_bot_falls_back() {
+        // 先占一个端口再关掉，保证连不上
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let mut bot = BotController::spawn(&format!("http://{}", addr), Duration::from_secs(2)).unwrap();
+        bot.request_move(request());
+        assert_eq!(settle(&mut bot), BotDecision::Fallback);
+        assert!(matches!(bot.last_error(), Some(BotError::Network(_))));
+        assert!(bot.info().is_none());
+    }
+}
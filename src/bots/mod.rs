@@ -0,0 +1,23 @@
+//! HTTP 机器人模块
+//!
+//! 兼容 Battlesnake API 的机器人玩家：游戏每回合把棋盘（含本游戏的果实、Buff 等扩展字段）
+//! POST 给机器人，机器人回复一个方向，超时或出错时按原方向继续走。机器人坐在本地多人的
+//! 最后几个玩家位置上（`--bot URL`），自带的参考机器人可以离线对战。
+//!
+//! ```text
+//! bots/
+//! ├── protocol.rs     # 请求/响应 JSON 结构、坐标换算、扩展字段
+//! ├── client.rs       # 阻塞 HTTP 客户端、错误类型
+//! ├── controller.rs   # 后台线程、超时回退、命令行参数
+//! └── reference.rs    # 本地参考机器人（测试和离线对战用）
+//! ```
+
+pub mod protocol;
+pub mod client;
+pub mod controller;
+pub mod reference;
+
+pub use protocol::*;
+pub use client::*;
+pub use controller::*;
+pub use reference::*;
@@ -0,0 +1,456 @@
+//! Battlesnake JSON 协议
+//!
+//! 和 Battlesnake API v1 相同的请求/响应结构：游戏对每个机器人 `POST /start`、每回合 `POST /move`、
+//! 结束时 `POST /end`，请求体都是 [`GameRequest`]；`GET /` 返回 [`BotInfo`]（颜色等外观）。
+//!
+//! 坐标按 Battlesnake 的约定：原点在左下角，`up` 是 y + 1（和棋盘的 y 轴相反，由 [`Coord`] 换算）。
+//! 本游戏特有的内容放在额外字段里，标准机器人会忽略它们：
+//!
+//! | 字段 | 内容 |
+//! |------|------|
+//! | `board.fruits` | 特殊果实：位置、类型 ID、类别（Normal / Trap / Power / Special） |
+//! | `board.topology` | 边界拓扑名称（环绕地图的规则名为 `wrapped`） |
+//! | `snake.score` | 分数（AI 蛇是经验值） |
+//! | `snake.buffs` | 生效中的 Buff 和剩余秒数 |
+//!
+//! 障碍物格子放在标准的 `board.hazards` 里（本游戏的障碍物是墙，撞上即死）。
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::broadcast::{BuffTag, WorldSnapshot};
+use crate::constants::{GRID_H, GRID_W, NO_FOOD};
+use crate::fruits::FruitRegistry;
+use crate::types::Topology;
+
+/// 协议版本
+pub const API_VERSION: &str = "1";
+/// 有墙地图的规则名
+pub const STANDARD_RULESET: &str = "standard";
+/// 环绕地图的规则名
+pub const WRAPPED_RULESET: &str = "wrapped";
+/// 机器人蛇的生命值（本游戏没有饥饿，固定为满）
+pub const FULL_HEALTH: u32 = 100;
+
+/// Battlesnake 坐标（原点在左下角）
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct Coord {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Coord {
+    /// 棋盘格子（原点在左上角）转成 Battlesnake 坐标
+    pub fn from_grid(pos: IVec2) -> Self {
+        Coord { x: pos.x, y: GRID_H - 1 - pos.y }
+    }
+
+    /// 转回棋盘格子
+    pub fn to_grid(self) -> IVec2 {
+        ivec2(self.x, GRID_H - 1 - self.y)
+    }
+
+    /// 朝 `dir` 走一格（不处理边界）
+    pub fn step(self, dir: Direction) -> Coord {
+        match dir {
+            Direction::Up => Coord { x: self.x, y: self.y + 1 },
+            Direction::Down => Coord { x: self.x, y: self.y - 1 },
+            Direction::Left => Coord { x: self.x - 1, y: self.y },
+            Direction::Right => Coord { x: self.x + 1, y: self.y },
+        }
+    }
+}
+
+/// 机器人回复的方向
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [Direction::Up, Direction::Down, Direction::Left, Direction::Right];
+
+    /// 棋盘上的方向（y 轴向下）
+    pub fn to_grid(self) -> IVec2 {
+        match self {
+            Direction::Up => ivec2(0, -1),
+            Direction::Down => ivec2(0, 1),
+            Direction::Left => ivec2(-1, 0),
+            Direction::Right => ivec2(1, 0),
+        }
+    }
+
+    /// 棋盘方向转回协议方向（斜向等没有对应时为 None）
+    pub fn from_grid(dir: IVec2) -> Option<Self> {
+        Direction::ALL.into_iter().find(|d| d.to_grid() == dir)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        }
+    }
+}
+
+/// 规则
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Ruleset {
+    pub name: String,
+    pub version: String,
+}
+
+/// 一局的信息（每次请求都一样）
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct GameInfo {
+    pub id: String,
+    pub ruleset: Ruleset,
+    #[serde(default)]
+    pub map: String,
+    /// 每回合的回复时限（毫秒）
+    pub timeout: u64,
+    #[serde(default)]
+    pub source: String,
+}
+
+impl GameInfo {
+    /// 一局的信息：环绕地图的规则名为 `wrapped`，其他为 `standard`
+    pub fn new(id: &str, map: &str, topology: Topology, timeout: u64) -> Self {
+        let name = if topology.wraps_any() { WRAPPED_RULESET } else { STANDARD_RULESET };
+        GameInfo {
+            id: id.to_string(),
+            ruleset: Ruleset { name: name.to_string(), version: env!("CARGO_PKG_VERSION").to_string() },
+            map: map.to_string(),
+            timeout,
+            source: "snake2d".to_string(),
+        }
+    }
+}
+
+/// 蛇的外观
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Customizations {
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub head: String,
+    #[serde(default)]
+    pub tail: String,
+}
+
+/// 棋盘上的一条蛇
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct BattleSnake {
+    pub id: String,
+    pub name: String,
+    pub health: u32,
+    /// 蛇身，第一个是蛇头
+    pub body: Vec<Coord>,
+    #[serde(default)]
+    pub latency: String,
+    pub head: Coord,
+    pub length: u32,
+    #[serde(default)]
+    pub shout: String,
+    #[serde(default)]
+    pub squad: String,
+    #[serde(default)]
+    pub customizations: Customizations,
+    /// 扩展：分数（AI 蛇是经验值）
+    #[serde(default)]
+    pub score: u32,
+    /// 扩展：生效中的 Buff
+    #[serde(default)]
+    pub buffs: Vec<BuffTag>,
+}
+
+/// 扩展：一个特殊果实
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FruitCoord {
+    pub x: i32,
+    pub y: i32,
+    /// 类型 ID（如 `shield`、`freeze`）
+    pub kind: String,
+    /// 类别：Normal / Trap / Power / Special
+    pub category: String,
+}
+
+impl FruitCoord {
+    pub fn coord(&self) -> Coord {
+        Coord { x: self.x, y: self.y }
+    }
+}
+
+/// 棋盘
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Board {
+    pub height: i32,
+    pub width: i32,
+    pub food: Vec<Coord>,
+    #[serde(default)]
+    pub hazards: Vec<Coord>,
+    pub snakes: Vec<BattleSnake>,
+    /// 扩展：特殊果实
+    #[serde(default)]
+    pub fruits: Vec<FruitCoord>,
+    /// 扩展：边界拓扑名称
+    #[serde(default)]
+    pub topology: String,
+}
+
+impl Board {
+    /// 按观战快照生成棋盘：食物含掉落食物（收起的食物不算），倒下的玩家不在棋盘上，果实类别从注册表查
+    pub fn from_snapshot(snapshot: &WorldSnapshot, registry: &FruitRegistry) -> Self {
+        let coord = |c: [i32; 2]| Coord::from_grid(IVec2::from_array(c));
+        let mut food: Vec<Coord> = Some(snapshot.food).filter(|&f| f != NO_FOOD.to_array()).map(coord).into_iter().collect();
+        food.extend(snapshot.dropped.iter().map(|d| coord(d.pos)));
+        let snakes = snapshot
+            .snakes
+            .iter()
+            .filter(|s| !s.downed && !s.body.is_empty())
+            .map(|s| {
+                let body: Vec<Coord> = s.body.iter().map(|&c| coord(c)).collect();
+                BattleSnake {
+                    id: s.name.clone(),
+                    name: s.name.clone(),
+                    health: FULL_HEALTH,
+                    head: body[0],
+                    length: body.len() as u32,
+                    body,
+                    squad: s.team.clone().unwrap_or_default(),
+                    customizations: Customizations { color: hex_color(s.color()), ..Default::default() },
+                    score: s.score,
+                    buffs: s.buffs.clone(),
+                    ..Default::default()
+                }
+            })
+            .collect();
+        let fruits = snapshot
+            .fruits
+            .iter()
+            .map(|f| {
+                let c = coord(f.pos);
+                let category = registry.get_config(&f.kind).map(|cfg| format!("{:?}", cfg.category)).unwrap_or_default();
+                FruitCoord { x: c.x, y: c.y, kind: f.kind.clone(), category }
+            })
+            .collect();
+        Board {
+            height: GRID_H,
+            width: GRID_W,
+            food,
+            hazards: snapshot.walls.iter().map(|&c| coord(c)).collect(),
+            snakes,
+            fruits,
+            topology: snapshot.topology.clone(),
+        }
+    }
+
+    /// 是否在棋盘内
+    pub fn contains(&self, c: Coord) -> bool {
+        (0..self.width).contains(&c.x) && (0..self.height).contains(&c.y)
+    }
+
+    /// 边界拓扑：按扩展字段的名称找，没有扩展字段的标准棋盘按 `wrapped` 规则决定
+    pub fn topology_kind(&self, wrapped: bool) -> Topology {
+        Topology::ALL
+            .into_iter()
+            .find(|t| t.name() == self.topology)
+            .unwrap_or(Topology::from_wrap(wrapped))
+    }
+
+    /// 从 `c` 朝 `dir` 走一格后的格子（越过环绕边界时换到对面，镜像环绕时翻转 y），撞墙时为 None
+    pub fn neighbor(&self, c: Coord, dir: Direction, topology: Topology) -> Option<Coord> {
+        let mut next = c.step(dir);
+        if !(0..self.width).contains(&next.x) {
+            if !topology.wraps_x() {
+                return None;
+            }
+            next.x = next.x.rem_euclid(self.width);
+            if topology.mirrors_x() {
+                next.y = self.height - 1 - next.y;
+            }
+        }
+        if !(0..self.height).contains(&next.y) {
+            if !topology.wraps_y() {
+                return None;
+            }
+            next.y = next.y.rem_euclid(self.height);
+        }
+        Some(next)
+    }
+}
+
+/// `/start`、`/move`、`/end` 的请求体
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct GameRequest {
+    pub game: GameInfo,
+    pub turn: u32,
+    pub board: Board,
+    pub you: BattleSnake,
+}
+
+impl GameRequest {
+    /// 给棋盘上名为 `you` 的蛇的请求（这条蛇不在棋盘上时 `you` 只有名字，用于倒下后的 `/end`）
+    pub fn new(game: &GameInfo, turn: u32, board: Board, you: &str) -> Self {
+        let you = board.snakes.iter().find(|s| s.id == you).cloned().unwrap_or_else(|| BattleSnake {
+            id: you.to_string(),
+            name: you.to_string(),
+            ..Default::default()
+        });
+        GameRequest { game: game.clone(), turn, board, you }
+    }
+
+    /// 边界拓扑
+    pub fn topology(&self) -> Topology {
+        self.board.topology_kind(self.game.ruleset.name == WRAPPED_RULESET)
+    }
+
+}
+
+/// `/move` 的回复
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MoveResponse {
+    #[serde(rename = "move")]
+    pub direction: Direction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shout: Option<String>,
+}
+
+/// `GET /` 的回复
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct BotInfo {
+    #[serde(default)]
+    pub apiversion: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub head: String,
+    #[serde(default)]
+    pub tail: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// 颜色转成 `#rrggbb`
+pub fn hex_color(color: Color) -> String {
+    let [r, g, b, _]: [u8; 4] = color.into();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// 解析 `#rrggbb`（机器人自选的颜色），格式不对时为 None
+pub fn parse_hex_color(hex: &str) -> Option<Color> {
+    let digits = hex.strip_prefix('#')?;
+    if digits.len() != 6 || !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    Some(Color::from_rgba(channel(0)?, channel(2)?, channel(4)?, 255))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broadcast::{DroppedSnapshot, FruitSnapshot, SnakeRole, SnakeSnapshot};
+    use crate::fruits::create_fruit_registry;
+
+    #[test]
+    fn test_coordinates_flip_y() {
+        let top_left = Coord::from_grid(ivec2(0, 0));
+        assert_eq!(top_left, Coord { x: 0, y: GRID_H - 1 });
+        assert_eq!(top_left.to_grid(), ivec2(0, 0));
+        // 协议的 up 是棋盘上往上走
+        let c = Coord::from_grid(ivec2(5, 5));
+        for dir in Direction::ALL {
+            assert_eq!(c.step(dir).to_grid(), ivec2(5, 5) + dir.to_grid());
+            assert_eq!(Direction::from_grid(dir.to_grid()), Some(dir));
+        }
+        assert_eq!(Direction::from_grid(ivec2(1, 1)), None);
+    }
+
+    #[test]
+    fn test_neighbor_follows_topology() {
+        let board = Board { width: 4, height: 3, ..Default::default() };
+        let corner = Coord { x: 3, y: 0 };
+        assert_eq!(board.neighbor(corner, Direction::Right, Topology::Bounded), None);
+        assert_eq!(board.neighbor(corner, Direction::Up, Topology::Bounded), Some(Coord { x: 3, y: 1 }));
+        assert_eq!(board.neighbor(corner, Direction::Right, Topology::Torus), Some(Coord { x: 0, y: 0 }));
+        assert_eq!(board.neighbor(corner, Direction::Down, Topology::Torus), Some(Coord { x: 3, y: 2 }));
+        // 莫比乌斯带左右镜像，上下是墙
+        assert_eq!(board.neighbor(corner, Direction::Right, Topology::Mobius), Some(Coord { x: 0, y: 2 }));
+        assert_eq!(board.neighbor(corner, Direction::Down, Topology::Mobius), None);
+    }
+
+    #[test]
+    fn test_json_matches_battlesnake_api() {
+        let response: MoveResponse = serde_json::from_str(r#"{"move":"left","shout":"hi"}"#).unwrap();
+        assert_eq!(response.direction, Direction::Left);
+        assert_eq!(serde_json::to_string(&MoveResponse { direction: Direction::Up, shout: None }).unwrap(), r#"{"move":"up"}"#);
+        assert!(serde_json::from_str::<MoveResponse>(r#"{"move":"north"}"#).is_err());
+
+        // 标准请求（没有扩展字段）也能解析
+        let request: GameRequest = serde_json::from_str(
+            r#"{"game":{"id":"g","ruleset":{"name":"standard","version":"v1"},"timeout":500},"turn":3,
+                "board":{"height":11,"width":11,"food":[{"x":1,"y":2}],"snakes":[]},
+                "you":{"id":"s","name":"s","health":90,"body":[{"x":0,"y":0}],"head":{"x":0,"y":0},"length":1}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.turn, 3);
+        assert_eq!(request.board.food, [Coord { x: 1, y: 2 }]);
+        assert_eq!(request.topology(), Topology::Bounded);
+        let info: BotInfo = serde_json::from_str(r##"{"apiversion":"1","color":"#ff8800"}"##).unwrap();
+        assert_eq!(parse_hex_color(&info.color), Some(Color::from_rgba(255, 136, 0, 255)));
+        assert_eq!(parse_hex_color("ff8800"), None);
+        assert_eq!(hex_color(Color::from_rgba(255, 136, 0, 255)), "#ff8800");
+    }
+
+    #[test]
+    fn test_board_from_snapshot_carries_extensions() {
+        let snake = |name: &str, body: Vec<[i32; 2]>, downed| SnakeSnapshot {
+            name: name.into(),
+            role: SnakeRole::Player,
+            body,
+            color: [0.0, 1.0, 0.0, 1.0],
+            score: 7,
+            level: 0,
+            team: None,
+            downed,
+            buffs: vec![BuffTag { name: "Ghost".into(), remaining: 1.0 }],
+        };
+        let snapshot = WorldSnapshot {
+            food: [1, 1],
+            dropped: vec![DroppedSnapshot { pos: [2, 2], spawn_time: 0.0, lifetime: 5.0 }],
+            fruits: vec![FruitSnapshot { pos: [3, 0], kind: "freeze".into(), spawn_time: 0.0, lifetime: 0.0 }],
+            walls: vec![[0, GRID_H - 1]],
+            topology: "Torus".into(),
+            snakes: vec![snake("P1", vec![[4, 4], [3, 4]], false), snake("P2", vec![[9, 9]], true)],
+            ..Default::default()
+        };
+        let board = Board::from_snapshot(&snapshot, &create_fruit_registry());
+        assert_eq!((board.width, board.height), (GRID_W, GRID_H));
+        assert_eq!(board.food, [Coord::from_grid(ivec2(1, 1)), Coord::from_grid(ivec2(2, 2))]);
+        assert_eq!(board.hazards, [Coord { x: 0, y: 0 }]);
+        assert_eq!(board.fruits[0].category, "Trap");
+        assert_eq!(board.topology, "Torus");
+        // 倒下的玩家不在棋盘上
+        assert_eq!(board.snakes.len(), 1);
+        let you = &board.snakes[0];
+        assert_eq!((you.head, you.length, you.score), (Coord::from_grid(ivec2(4, 4)), 2, 7));
+        assert_eq!(you.customizations.color, "#00ff00");
+
+        let game = GameInfo::new("g", "Open Field", Topology::Torus, 500);
+        assert_eq!(game.ruleset.name, WRAPPED_RULESET);
+        let request = GameRequest::new(&game, 5, board.clone(), "P1");
+        assert_eq!(request.you.buffs[0].name, "Ghost");
+        assert_eq!(request.topology(), Topology::Torus);
+        // 倒下的玩家只有名字
+        let downed = GameRequest::new(&game, 5, board, "P2");
+        assert_eq!((downed.you.name.as_str(), downed.you.length), ("P2", 0));
+    }
+}
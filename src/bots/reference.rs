@@ -0,0 +1,335 @@
+//! 本地参考机器人
+//!
+//! 只依赖标准库的极简 HTTP/1.1 服务器，实现 Battlesnake 的四个接口，
+//! 用于测试和 `cargo run --bin reference_bot` 离线对战，也可以当作写机器人的起点。
+//!
+//! 走法（[`choose_move`]）：不撞墙、障碍物和蛇身，不和一样长或更长的蛇抢同一格，
+//! 不钻进装不下自己的死路，在这些前提下走向最近的食物或好果实，绕开陷阱果实。
+
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use crate::types::Topology;
+use super::protocol::{BotInfo, Board, Coord, Direction, GameRequest, MoveResponse, API_VERSION};
+
+/// 参考机器人的颜色
+pub const REFERENCE_COLOR: &str = "#e0803a";
+/// 命令行模式的默认地址
+pub const DEFAULT_BOT_ADDR: &str = "127.0.0.1:8000";
+
+/// 运行在后台线程上的参考机器人
+#[derive(Debug)]
+pub struct ReferenceBot {
+    addr: SocketAddr,
+    /// 每次回复 `/move` 前等待的毫秒数（测试超时用）
+    delay_ms: Arc<AtomicU64>,
+    /// 收到的 `/move` 请求数
+    moves: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ReferenceBot {
+    /// 在 `addr` 上启动，端口为 0 时由系统分配
+    pub fn start(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let delay_ms = Arc::new(AtomicU64::new(0));
+        let moves = Arc::new(AtomicU64::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let delay_ms = Arc::clone(&delay_ms);
+            let moves = Arc::clone(&moves);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // 单个连接出错不影响服务器
+                        let delay = Duration::from_millis(delay_ms.load(Ordering::SeqCst));
+                        let _ = handle_connection(stream, delay, &moves);
+                    }
+                }
+            })
+        };
+
+        Ok(ReferenceBot { addr, delay_ms, moves, stop, handle: Some(handle) })
+    }
+
+    /// 每次回复 `/move` 前等待 `delay`（模拟慢机器人）
+    pub fn with_delay(self, delay: Duration) -> Self {
+        self.delay_ms.store(delay.as_millis() as u64, Ordering::SeqCst);
+        self
+    }
+
+    /// 到目前为止收到的 `/move` 请求数
+    pub fn moves(&self) -> u64 {
+        self.moves.load(Ordering::SeqCst)
+    }
+
+    /// 监听地址
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 游戏使用的机器人 URL
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 阻塞运行直到进程退出（命令行模式使用）
+    pub fn join(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for ReferenceBot {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // 连一次自己，唤醒阻塞在 accept 上的线程
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 处理一个连接上的一次请求
+fn handle_connection(stream: TcpStream, delay: Duration, moves: &AtomicU64) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let path = target.split_once('?').map_or(target, |(path, _)| path);
+    if path == "/move" {
+        moves.fetch_add(1, Ordering::SeqCst);
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+    }
+    let (status, json) = route(method, path, &body);
+    write_response(stream, status, &json)
+}
+
+/// 分发请求，返回状态码和 JSON 响应体
+fn route(method: &str, path: &str, body: &[u8]) -> (u16, String) {
+    match (method, path) {
+        ("GET", "/") => {
+            let info = BotInfo {
+                apiversion: API_VERSION.to_string(),
+                author: "snake2d".to_string(),
+                color: REFERENCE_COLOR.to_string(),
+                head: "default".to_string(),
+                tail: "default".to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            };
+            (200, serde_json::to_string(&info).unwrap_or_default())
+        }
+        ("POST", "/start") | ("POST", "/end") => (200, "{}".to_string()),
+        ("POST", "/move") => {
+            let Ok(request) = serde_json::from_slice::<GameRequest>(body) else {
+                return (400, r#"{"error":"bad request"}"#.to_string());
+            };
+            let response = MoveResponse { direction: choose_move(&request), shout: None };
+            (200, serde_json::to_string(&response).unwrap_or_default())
+        }
+        _ => (404, r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+/// 写出响应并关闭连接
+fn write_response(mut stream: TcpStream, status: u16, body: &str) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        _ => "Not Found",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// 参考走法：在安全的方向里选空间够大、离目标最近的
+pub fn choose_move(request: &GameRequest) -> Direction {
+    let board = &request.board;
+    let you = &request.you;
+    let topology = request.topology();
+
+    // 蛇身和障碍物都不能走；尾巴下一回合会挪开，除非长度为 1（尾巴就是头）
+    let mut blocked: HashSet<Coord> = board.hazards.iter().copied().collect();
+    for snake in &board.snakes {
+        let keep = snake.body.len().saturating_sub(1).max(1);
+        blocked.extend(snake.body.iter().take(keep).copied());
+    }
+
+    // 一样长或更长的对手蛇头旁边的格子可能被抢
+    let contested: HashSet<Coord> = board
+        .snakes
+        .iter()
+        .filter(|s| s.id != you.id && s.length >= you.length)
+        .flat_map(|s| Direction::ALL.into_iter().filter_map(move |d| board.neighbor(s.head, d, topology)))
+        .collect();
+
+    // 目标：普通食物和非陷阱果实；陷阱果实当作要绕开的格子
+    let traps: HashSet<Coord> = board.fruits.iter().filter(|f| f.category == "Trap").map(|f| f.coord()).collect();
+    let goals: Vec<Coord> = board
+        .food
+        .iter()
+        .copied()
+        .chain(board.fruits.iter().filter(|f| f.category != "Trap").map(|f| f.coord()))
+        .collect();
+
+    let need = you.length.max(1) as usize;
+    let current = you.body.get(1).and_then(|&neck| {
+        Direction::ALL.into_iter().find(|&d| board.neighbor(neck, d, topology) == Some(you.head))
+    });
+    Direction::ALL
+        .into_iter()
+        .filter_map(|dir| {
+            let next = board.neighbor(you.head, dir, topology)?;
+            if blocked.contains(&next) {
+                return None;
+            }
+            let space = flood_fill(board, next, &blocked, topology, need);
+            let distance = goals.iter().map(|&g| distance(board, next, g, topology)).min().unwrap_or(0);
+            // 越小越好：被抢、死路、陷阱、离目标的距离、不是原方向
+            let key = (contested.contains(&next), space < need, traps.contains(&next), distance, current != Some(dir));
+            Some((key, dir))
+        })
+        .min_by_key(|&(key, _)| key)
+        .map(|(_, dir)| dir)
+        .or(current)
+        .unwrap_or(Direction::Up)
+}
+
+/// 从 `start` 能走到的格子数（数到 `limit` 为止）
+fn flood_fill(board: &Board, start: Coord, blocked: &HashSet<Coord>, topology: Topology, limit: usize) -> usize {
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(c) = queue.pop_front() {
+        if seen.len() >= limit {
+            break;
+        }
+        for dir in Direction::ALL {
+            if let Some(next) = board.neighbor(c, dir, topology)
+                && !blocked.contains(&next)
+                && seen.insert(next)
+            {
+                queue.push_back(next);
+            }
+        }
+    }
+    seen.len()
+}
+
+/// 曼哈顿距离（环绕的方向取近的一边，镜像环绕按普通环绕估计）
+fn distance(board: &Board, a: Coord, b: Coord, topology: Topology) -> i32 {
+    let axis = |d: i32, size: i32, wraps: bool| if wraps { d.min(size - d) } else { d };
+    axis((a.x - b.x).abs(), board.width, topology.wraps_x()) + axis((a.y - b.y).abs(), board.height, topology.wraps_y())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bots::{BattleSnake, FruitCoord, GameInfo};
+
+    fn snake(id: &str, body: &[(i32, i32)]) -> BattleSnake {
+        let body: Vec<Coord> = body.iter().map(|&(x, y)| Coord { x, y }).collect();
+        BattleSnake {
+            id: id.into(),
+            name: id.into(),
+            health: 100,
+            head: body[0],
+            length: body.len() as u32,
+            body,
+            ..Default::default()
+        }
+    }
+
+    fn request(topology: Topology, food: &[(i32, i32)], snakes: Vec<BattleSnake>) -> GameRequest {
+        let board = Board {
+            width: 7,
+            height: 7,
+            food: food.iter().map(|&(x, y)| Coord { x, y }).collect(),
+            snakes,
+            topology: topology.name().to_string(),
+            ..Default::default()
+        };
+        GameRequest::new(&GameInfo::new("test", "", topology, 500), 0, board, "me")
+    }
+
+    #[test]
+    fn test_moves_toward_food_and_avoids_walls() {
+        let me = snake("me", &[(0, 3), (1, 3), (2, 3)]);
+        // 左边是墙，右边是自己，食物在上面
+        let req = request(Topology::Bounded, &[(0, 6)], vec![me.clone()]);
+        assert_eq!(choose_move(&req), Direction::Up);
+        let req = request(Topology::Bounded, &[(0, 0)], vec![me.clone()]);
+        assert_eq!(choose_move(&req), Direction::Down);
+        // 环绕地图上往左穿过边界离食物最近
+        let req = request(Topology::Torus, &[(6, 3)], vec![me]);
+        assert_eq!(choose_move(&req), Direction::Left);
+    }
+
+    #[test]
+    fn test_avoids_bodies_traps_and_bigger_heads() {
+        // 上面是另一条蛇的身子，只能往下
+        let me = snake("me", &[(3, 3), (2, 3)]);
+        let wall = snake("wall", &[(3, 4), (4, 4), (4, 3), (5, 3)]);
+        let req = request(Topology::Bounded, &[(3, 6)], vec![me.clone(), wall]);
+        assert_eq!(choose_move(&req), Direction::Down);
+
+        // 陷阱果实在路上时绕开
+        let mut req = request(Topology::Bounded, &[(5, 3)], vec![me.clone()]);
+        req.board.fruits.push(FruitCoord { x: 4, y: 3, kind: "bomb".into(), category: "Trap".into() });
+        assert_ne!(choose_move(&req), Direction::Right);
+
+        // 更长的蛇头旁边的格子不去抢
+        let big = snake("big", &[(5, 3), (6, 3), (6, 4)]);
+        let req = request(Topology::Bounded, &[(4, 3)], vec![me, big]);
+        assert_ne!(choose_move(&req), Direction::Right);
+    }
+
+    #[test]
+    fn test_avoids_dead_ends() {
+        // 左边的口袋只有 3 格，装不下 4 格长的自己，即使食物在里面也往右走
+        let me = snake("me", &[(1, 1), (1, 2), (1, 3), (1, 4)]);
+        let mut req = request(Topology::Bounded, &[(0, 0)], vec![me]);
+        req.board.hazards = vec![Coord { x: 0, y: 3 }, Coord { x: 1, y: 0 }];
+        assert_eq!(choose_move(&req), Direction::Right);
+    }
+}
@@ -0,0 +1,196 @@
+//! 观战镜头
+//!
+//! 三种模式：总览（整个棋盘居中）、自由（方向键平移、缩放）和跟随（镜头平滑地追一条蛇的蛇头）。
+//! 镜头只在像素坐标里工作，格子到像素的换算由渲染层完成。
+
+use macroquad::prelude::*;
+use crate::constants::{CELL, GRID_H, GRID_W};
+
+/// 最小缩放（整个棋盘缩成一小块）
+pub const MIN_ZOOM: f32 = 0.5;
+/// 最大缩放
+pub const MAX_ZOOM: f32 = 4.0;
+/// 自由镜头每秒平移的屏幕像素
+pub const PAN_SPEED: f32 = 600.0;
+/// 跟随时镜头追上目标的速度（越大越紧）
+pub const FOLLOW_RATE: f32 = 6.0;
+
+/// 镜头模式
+#[derive(Clone, PartialEq, Debug)]
+pub enum CameraMode {
+    /// 整个棋盘
+    Overview,
+    /// 手动平移和缩放
+    Free,
+    /// 跟随某条蛇（按快照里的名字）
+    Follow(String),
+}
+
+/// 观战镜头
+#[derive(Clone, Debug)]
+pub struct SpectatorCamera {
+    pub mode: CameraMode,
+    /// 镜头中心（棋盘像素坐标）
+    pub center: Vec2,
+    pub zoom: f32,
+}
+
+impl Default for SpectatorCamera {
+    fn default() -> Self {
+        SpectatorCamera { mode: CameraMode::Overview, center: board_center(), zoom: 1.0 }
+    }
+}
+
+/// 棋盘中心的像素坐标
+fn board_center() -> Vec2 {
+    vec2(GRID_W as f32 * CELL, GRID_H as f32 * CELL) * 0.5
+}
+
+impl SpectatorCamera {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回到总览
+    pub fn overview(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 按屏幕像素平移（切换为自由镜头）
+    pub fn pan(&mut self, screen_delta: Vec2) {
+        self.mode = CameraMode::Free;
+        self.center += screen_delta / self.zoom;
+    }
+
+    /// 乘以 `factor` 缩放（总览时切换为自由镜头，跟随时保持跟随）
+    pub fn zoom_by(&mut self, factor: f32) {
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        if self.mode == CameraMode::Overview {
+            self.mode = CameraMode::Free;
+        }
+    }
+
+    /// 跟随名为 `name` 的蛇；第一次跟随时拉近一些
+    pub fn follow(&mut self, name: &str) {
+        if !matches!(self.mode, CameraMode::Follow(_)) {
+            self.zoom = self.zoom.max(2.0);
+        }
+        self.mode = CameraMode::Follow(name.to_string());
+    }
+
+    /// 正在跟随的蛇
+    pub fn followed(&self) -> Option<&str> {
+        match &self.mode {
+            CameraMode::Follow(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// 在 `names` 里跟随当前目标的下一条（没在跟随时从第一条开始）
+    pub fn follow_next(&mut self, names: &[&str]) {
+        let next = match self.followed().and_then(|cur| names.iter().position(|n| *n == cur)) {
+            Some(i) => names[(i + 1) % names.len()],
+            None => match names.first() {
+                Some(first) => first,
+                None => return,
+            },
+        };
+        self.follow(next);
+    }
+
+    /// 每帧调用：`target` 是跟随的蛇头像素坐标（蛇已经不在时为 None，镜头停在原地改为自由）
+    pub fn update(&mut self, target: Option<Vec2>, dt: f32) {
+        match self.mode {
+            CameraMode::Overview => {
+                self.center = board_center();
+                self.zoom = 1.0;
+            }
+            CameraMode::Free => {}
+            CameraMode::Follow(_) => match target {
+                Some(target) => {
+                    let t = 1.0 - (-FOLLOW_RATE * dt).exp();
+                    self.center = self.center.lerp(target, t);
+                }
+                None => self.mode = CameraMode::Free,
+            },
+        }
+    }
+
+    /// 状态栏显示的镜头说明
+    pub fn label(&self) -> String {
+        match &self.mode {
+            CameraMode::Overview => "Overview".to_string(),
+            CameraMode::Free => format!("Free x{:.1}", self.zoom),
+            CameraMode::Follow(name) => format!("Following {}", name),
+        }
+    }
+
+    /// 对应尺寸为 `screen` 的窗口的 2D 相机
+    ///
+    /// 总览时缩放到整个棋盘正好放进窗口；y 轴向下，和默认屏幕坐标一致
+    pub fn camera2d(&self, screen: Vec2) -> Camera2D {
+        let board = vec2(GRID_W as f32 * CELL, GRID_H as f32 * CELL);
+        let fit = (screen.x / board.x).min(screen.y / board.y);
+        let scale = fit * self.zoom;
+        Camera2D {
+            target: self.center,
+            zoom: vec2(2.0 * scale / screen.x, 2.0 * scale / screen.y),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow_cycles_and_tracks_target() {
+        let mut camera = SpectatorCamera::new();
+        camera.follow_next(&[]);
+        assert_eq!(camera.mode, CameraMode::Overview);
+        camera.follow_next(&["P1", "P2", "Hunter #4"]);
+        assert_eq!(camera.followed(), Some("P1"));
+        assert_eq!(camera.zoom, 2.0);
+        camera.follow_next(&["P1", "P2", "Hunter #4"]);
+        camera.follow_next(&["P1", "P2", "Hunter #4"]);
+        assert_eq!(camera.label(), "Following Hunter #4");
+        camera.follow_next(&["P1", "P2", "Hunter #4"]);
+        assert_eq!(camera.followed(), Some("P1"));
+
+        // 镜头逐渐追上蛇头，不会越过去
+        let target = vec2(100.0, 40.0);
+        let start = camera.center.distance(target);
+        camera.update(Some(target), 0.1);
+        let mid = camera.center.distance(target);
+        assert!(mid < start && mid > 0.0);
+        for _ in 0..100 {
+            camera.update(Some(target), 0.1);
+        }
+        assert!(camera.center.distance(target) < 0.01);
+
+        // 跟随的蛇死了，镜头停在原地
+        camera.update(None, 0.1);
+        assert_eq!(camera.mode, CameraMode::Free);
+        assert!(camera.center.distance(target) < 0.01);
+    }
+
+    #[test]
+    fn test_free_camera_pans_and_zooms() {
+        let mut camera = SpectatorCamera::new();
+        camera.zoom_by(100.0);
+        assert_eq!(camera.zoom, MAX_ZOOM);
+        assert_eq!(camera.mode, CameraMode::Free);
+        let before = camera.center;
+        camera.pan(vec2(40.0, 0.0));
+        // 拉近时同样的屏幕位移对应更短的棋盘距离
+        assert_eq!(camera.center - before, vec2(10.0, 0.0));
+        camera.zoom_by(0.0);
+        assert_eq!(camera.zoom, MIN_ZOOM);
+
+        camera.overview();
+        camera.update(None, 0.1);
+        assert_eq!((camera.center, camera.zoom), (board_center(), 1.0));
+        assert_eq!(camera.label(), "Overview");
+    }
+}
@@ -0,0 +1,115 @@
+//! 观战端连接
+//!
+//! 连上游戏的广播服务，非阻塞地按行读取快照；每帧只需要最新的一帧，旧的直接丢掉。
+
+use std::io::{ErrorKind, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use super::snapshot::WorldSnapshot;
+
+/// 连接超时
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 到广播服务的只读连接
+pub struct SpectatorClient {
+    stream: TcpStream,
+    /// 还没凑成一整行的数据
+    buf: Vec<u8>,
+}
+
+impl SpectatorClient {
+    /// 连接 `addr` 上的广播服务
+    pub fn connect(addr: &str) -> std::io::Result<Self> {
+        let target = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, format!("cannot resolve {}", addr)))?;
+        let stream = TcpStream::connect_timeout(&target, CONNECT_TIMEOUT)?;
+        stream.set_nonblocking(true)?;
+        Ok(SpectatorClient { stream, buf: Vec::new() })
+    }
+
+    /// 取出最新收到的快照（没有新快照时为 None）；游戏关闭时返回错误
+    pub fn poll(&mut self) -> std::io::Result<Option<WorldSnapshot>> {
+        let mut chunk = [0u8; 16 * 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let Some(end) = self.buf.iter().rposition(|&b| b == b'\n') else {
+            return Ok(None);
+        };
+        let lines: Vec<u8> = self.buf.drain(..=end).collect();
+        // 只解码最后一行完整的快照
+        let last = lines[..end].rsplit(|&b| b == b'\n').next().unwrap_or_default();
+        WorldSnapshot::decode(last)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use crate::broadcast::BroadcastServer;
+
+    fn snapshot(time: f32) -> WorldSnapshot {
+        WorldSnapshot { time, state: "Playing".into(), ..Default::default() }
+    }
+
+    /// 广播服务跑 `frames` 帧，同时收集观战端收到的快照
+    fn pump(server: &mut BroadcastServer, client: &mut SpectatorClient, frames: u32, time: &mut f32) -> Vec<WorldSnapshot> {
+        let mut received = Vec::new();
+        for _ in 0..frames {
+            *time += 0.01;
+            let now = *time;
+            server.update(0.01, || snapshot(now));
+            std::thread::sleep(Duration::from_millis(10));
+            received.extend(client.poll().unwrap());
+        }
+        received
+    }
+
+    #[test]
+    fn test_spectator_receives_latest_snapshot() {
+        let mut server = BroadcastServer::bind("127.0.0.1:0").unwrap().with_rate(1000.0);
+        let addr = server.local_addr().unwrap().to_string();
+        let mut client = SpectatorClient::connect(&addr).unwrap();
+        let mut time = 0.0;
+        let received = pump(&mut server, &mut client, 30, &mut time);
+        assert_eq!(server.spectators(), 1);
+        assert!(!received.is_empty());
+        // 序号递增，每次只交出最新的一帧
+        assert!(received.windows(2).all(|w| w[0].frame < w[1].frame));
+        assert!(received.iter().all(|s| s.delay == 0.0));
+
+        drop(client);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while server.spectators() > 0 && Instant::now() < deadline {
+            server.update(0.01, || snapshot(0.0));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(server.spectators(), 0);
+    }
+
+    #[test]
+    fn test_delayed_broadcast_holds_snapshots_back() {
+        let mut server = BroadcastServer::bind("127.0.0.1:0").unwrap().with_rate(1000.0).with_delay(0.3);
+        let addr = server.local_addr().unwrap().to_string();
+        let mut client = SpectatorClient::connect(&addr).unwrap();
+        let mut time = 0.0;
+        // 前 0.15 秒什么都收不到
+        assert!(pump(&mut server, &mut client, 10, &mut time).is_empty());
+        let received = pump(&mut server, &mut client, 60, &mut time);
+        let latest = received.last().expect("delayed snapshots arrive");
+        assert_eq!(latest.delay, 0.3);
+        // 收到的画面落后于游戏
+        assert!(time - latest.time > 0.1, "game {} shown {}", time, latest.time);
+    }
+}
@@ -0,0 +1,23 @@
+//! 观战广播模块
+//!
+//! 游戏把世界拍成快照，通过本地套接字广播给只读的观战端（`spectator`），
+//! 观战端用自由镜头或跟随镜头绘制，并显示每条蛇的 Buff 和数据。适合比赛时投到大屏幕上，
+//! 可以延迟广播，避免选手看大屏幕。
+//!
+//! ```text
+//! broadcast/
+//! ├── snapshot.rs     # 世界快照（蛇、Buff、果实、障碍物），JSON 编码
+//! ├── server.rs       # 游戏端广播服务（非阻塞、延迟广播、命令行参数）
+//! ├── client.rs       # 观战端连接
+//! └── camera.rs       # 总览 / 自由 / 跟随镜头
+//! ```
+
+pub mod snapshot;
+pub mod server;
+pub mod client;
+pub mod camera;
+
+pub use snapshot::*;
+pub use server::*;
+pub use client::*;
+pub use camera::*;
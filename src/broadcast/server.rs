@@ -0,0 +1,243 @@
+//! 广播服务
+//!
+//! 游戏在本地套接字上监听观战端（[`BroadcastServer::bind`]），主循环每帧调用一次
+//! [`BroadcastServer::update`]：接受新连接，按广播频率拍快照，过了延迟时间的快照发给所有观战端。
+//! 全部非阻塞，观战端卡住时跳过快照（积压超过 [`MAX_BACKLOG`] 不再排队），不会拖慢游戏。
+
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+use super::snapshot::WorldSnapshot;
+
+/// 默认广播地址（只在本机可见；投屏机器在局域网时用 `0.0.0.0:7878`）
+pub const DEFAULT_BROADCAST_ADDR: &str = "127.0.0.1:7878";
+/// 默认每秒广播的快照数
+pub const DEFAULT_BROADCAST_RATE: f32 = 30.0;
+/// 每个观战端最多积压的字节数
+pub const MAX_BACKLOG: usize = 1 << 20;
+/// 最长的广播延迟（秒）
+pub const MAX_BROADCAST_DELAY: f32 = 600.0;
+
+/// 命令行指定的广播方式
+#[derive(Clone, PartialEq, Debug)]
+pub struct BroadcastOptions {
+    pub addr: String,
+    /// 延迟广播的秒数
+    pub delay: f32,
+}
+
+impl BroadcastOptions {
+    /// 从参数里取出 `--broadcast ADDR` 和 `--broadcast-delay SECS`，其余参数留给别的解析
+    ///
+    /// 延迟必须在 0 到 [`MAX_BROADCAST_DELAY`] 秒之间；只给了延迟时在默认地址广播；两个都没有时返回 `Ok(None)`
+    pub fn take(args: &mut Vec<String>) -> Result<Option<Self>, String> {
+        let addr = take_value(args, "--broadcast")?;
+        let delay = match take_value(args, "--broadcast-delay")? {
+            Some(secs) => match secs.parse::<f32>() {
+                Ok(secs) if secs.is_finite() && (0.0..=MAX_BROADCAST_DELAY).contains(&secs) => Some(secs),
+                _ => {
+                    return Err(format!("--broadcast-delay expects 0 to {} seconds, got {:?}", MAX_BROADCAST_DELAY, secs));
+                }
+            },
+            None => None,
+        };
+        if addr.is_none() && delay.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(BroadcastOptions {
+            addr: addr.unwrap_or_else(|| DEFAULT_BROADCAST_ADDR.to_string()),
+            delay: delay.unwrap_or(0.0),
+        }))
+    }
+}
+
+/// 取出 `flag VALUE` 两个参数
+pub(crate) fn take_value(args: &mut Vec<String>, flag: &str) -> Result<Option<String>, String> {
+    let Some(i) = args.iter().position(|a| a == flag) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(format!("{} needs a value", flag));
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+/// 一个观战端连接
+struct Spectator {
+    stream: TcpStream,
+    /// 还没写出去的数据
+    pending: Vec<u8>,
+}
+
+impl Spectator {
+    /// 尽量写出积压的数据，连接断开时返回 false
+    fn flush(&mut self) -> bool {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.pending.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+/// 把快照广播给所有观战端
+pub struct BroadcastServer {
+    listener: TcpListener,
+    spectators: Vec<Spectator>,
+    /// 到时间才发出的快照（发出时刻，编码后的数据）
+    queue: VecDeque<(Instant, Vec<u8>)>,
+    delay: Duration,
+    interval: f32,
+    since_last: f32,
+    frame: u64,
+}
+
+impl BroadcastServer {
+    /// 在 `addr` 上监听观战端（端口为 0 时由系统分配）
+    pub fn bind(addr: &str) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(BroadcastServer {
+            listener,
+            spectators: Vec::new(),
+            queue: VecDeque::new(),
+            delay: Duration::ZERO,
+            interval: 1.0 / DEFAULT_BROADCAST_RATE,
+            since_last: f32::INFINITY,
+            frame: 0,
+        })
+    }
+
+    /// 延迟 `secs` 秒广播（比赛投屏时防止选手看大屏幕），限制在 [`MAX_BROADCAST_DELAY`] 秒以内
+    pub fn with_delay(mut self, secs: f32) -> Self {
+        self.delay = Duration::try_from_secs_f32(secs.clamp(0.0, MAX_BROADCAST_DELAY)).unwrap_or(Duration::ZERO);
+        self
+    }
+
+    /// 每秒广播 `rate` 个快照
+    pub fn with_rate(mut self, rate: f32) -> Self {
+        self.interval = 1.0 / rate.max(1.0);
+        self
+    }
+
+    /// 实际监听的地址
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// 连接中的观战端数量
+    pub fn spectators(&self) -> usize {
+        self.spectators.len()
+    }
+
+    /// 每帧调用：接受新连接，到了广播间隔时用 `snapshot` 拍一帧，发出到时间的快照
+    pub fn update(&mut self, dt: f32, snapshot: impl FnOnce() -> WorldSnapshot) {
+        self.accept();
+        self.since_last += dt;
+        let now = Instant::now();
+        // 没人观战时也要排队，这样延迟广播中途加入的观战端看到的同样是延迟后的画面
+        if self.since_last >= self.interval {
+            self.since_last = 0.0;
+            let mut snap = snapshot();
+            snap.frame = self.frame;
+            snap.delay = self.delay.as_secs_f32();
+            self.frame += 1;
+            self.queue.push_back((now + self.delay, snap.encode()));
+        }
+        while let Some((due, _)) = self.queue.front()
+            && *due <= now
+        {
+            let (_, bytes) = self.queue.pop_front().expect("front checked above");
+            for spectator in &mut self.spectators {
+                if spectator.pending.len() + bytes.len() <= MAX_BACKLOG {
+                    spectator.pending.extend_from_slice(&bytes);
+                }
+            }
+        }
+        self.spectators.retain_mut(Spectator::flush);
+    }
+
+    fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_ok() {
+                        let _ = stream.set_nodelay(true);
+                        self.spectators.push(Spectator { stream, pending: Vec::new() });
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                // 个别连接出错不影响其他观战端
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_broadcast_options() {
+        let mut args: Vec<String> = ["--join", "a:1", "--broadcast", "0.0.0.0:9", "--broadcast-delay", "2.5"]
+            .map(String::from)
+            .to_vec();
+        let options = BroadcastOptions::take(&mut args).unwrap().unwrap();
+        assert_eq!(options, BroadcastOptions { addr: "0.0.0.0:9".into(), delay: 2.5 });
+        assert_eq!(args, ["--join", "a:1"]);
+
+        let mut args = vec!["--broadcast-delay".to_string(), "10".to_string()];
+        assert_eq!(BroadcastOptions::take(&mut args).unwrap().unwrap().addr, DEFAULT_BROADCAST_ADDR);
+        assert_eq!(BroadcastOptions::take(&mut vec![]), Ok(None));
+        assert!(BroadcastOptions::take(&mut vec!["--broadcast".to_string()]).is_err());
+        for bad in ["-1", "inf", "NaN", "1e30", "601"] {
+            assert!(BroadcastOptions::take(&mut vec!["--broadcast-delay".to_string(), bad.to_string()]).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_delay_does_not_panic() {
+        for (secs, expected) in [(f32::INFINITY, MAX_BROADCAST_DELAY), (1e30, MAX_BROADCAST_DELAY), (f32::NAN, 0.0), (-5.0, 0.0)] {
+            let server = BroadcastServer::bind("127.0.0.1:0").unwrap().with_delay(secs);
+            assert_eq!(server.delay.as_secs_f32(), expected, "{}", secs);
+        }
+    }
+
+    #[test]
+    fn test_stalled_spectator_skips_snapshots_past_backlog() {
+        let mut server = BroadcastServer::bind("127.0.0.1:0").unwrap().with_rate(1000.0);
+        // 连上但从不读，内核缓冲区塞满后数据都积压在服务端
+        let _stalled = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        // 每帧约 100 KB
+        let big = || WorldSnapshot { walls: vec![[10, 10]; 10_000], ..Default::default() };
+        let size = big().encode().len();
+        let mut frames = 0;
+        while frames < 500 && server.spectators.first().is_none_or(|s| s.pending.len() + size <= MAX_BACKLOG) {
+            server.update(1.0, big);
+            frames += 1;
+        }
+        assert_eq!(server.spectators(), 1);
+        let backlog = server.spectators[0].pending.len();
+        assert!(backlog + size > MAX_BACKLOG, "backlog {} never filled", backlog);
+
+        // 再广播的快照整帧跳过，积压不再增长，卡住的观战端也不会被断开
+        for _ in 0..5 {
+            server.update(1.0, big);
+        }
+        assert_eq!(server.spectators(), 1);
+        assert!(server.spectators[0].pending.len() <= backlog);
+        assert_eq!(server.spectators[0].pending.last(), Some(&b'\n'));
+    }
+}
@@ -0,0 +1,208 @@
+//! 世界快照
+//!
+//! 观战端只读：游戏每次广播时把渲染需要的状态拍成一个 [`WorldSnapshot`]，编码成一行 JSON。
+//! 快照不引用游戏里的任何结构体，观战端不需要模拟，只按快照绘制。
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::types::{BuffState, GridKind, Topology};
+
+/// 快照里的一条蛇属于谁
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum SnakeRole {
+    Player,
+    Ai,
+    Pet,
+    Boss,
+}
+
+/// 一个生效中的 Buff
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BuffTag {
+    pub name: String,
+    /// 剩余秒数（没有计时的为 0）
+    pub remaining: f32,
+}
+
+/// 一条蛇（玩家、AI、宠物或 Boss）
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SnakeSnapshot {
+    /// 显示名称（玩家是 P1-P4，AI 带编号，同一局内不重复）
+    pub name: String,
+    pub role: SnakeRole,
+    /// 蛇身，第一个是蛇头
+    pub body: Vec<[i32; 2]>,
+    pub color: [f32; 4],
+    /// 分数（AI 和宠物是经验值）
+    pub score: u32,
+    /// AI 等级（玩家为 0）
+    pub level: u32,
+    pub team: Option<String>,
+    /// 本地多人中倒下的玩家
+    pub downed: bool,
+    pub buffs: Vec<BuffTag>,
+}
+
+impl SnakeSnapshot {
+    /// 蛇头（空蛇身时为 None）
+    pub fn head(&self) -> Option<IVec2> {
+        self.body.first().map(|&c| IVec2::from_array(c))
+    }
+
+    pub fn color(&self) -> Color {
+        let [r, g, b, a] = self.color;
+        Color::new(r, g, b, a)
+    }
+}
+
+/// 一个果实（按类型 ID 在观战端的注册表里找渲染方式）
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FruitSnapshot {
+    pub pos: [i32; 2],
+    pub kind: String,
+    pub spawn_time: f32,
+    pub lifetime: f32,
+}
+
+/// 一颗掉落的食物
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct DroppedSnapshot {
+    pub pos: [i32; 2],
+    pub spawn_time: f32,
+    pub lifetime: f32,
+}
+
+/// 一对传送门
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PortalSnapshot {
+    pub a: [i32; 2],
+    pub b: [i32; 2],
+    pub color: [f32; 4],
+}
+
+/// 广播的一帧世界
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct WorldSnapshot {
+    /// 广播序号（由广播服务填写）
+    pub frame: u64,
+    /// 广播延迟秒数（由广播服务填写，0 为直播）
+    pub delay: f32,
+    /// 游戏时间
+    pub time: f32,
+    /// 游戏状态（Playing、GameOver 等）
+    pub state: String,
+    pub mode: String,
+    pub map: String,
+    pub grid: String,
+    pub topology: String,
+    pub food: [i32; 2],
+    pub fruits: Vec<FruitSnapshot>,
+    pub dropped: Vec<DroppedSnapshot>,
+    /// 当前实心的障碍物格子
+    pub walls: Vec<[i32; 2]>,
+    pub portals: Vec<PortalSnapshot>,
+    pub snakes: Vec<SnakeSnapshot>,
+}
+
+impl WorldSnapshot {
+    /// 棋盘几何（未知名称按方格）
+    pub fn grid_kind(&self) -> GridKind {
+        GridKind::ALL.into_iter().find(|g| g.name() == self.grid).unwrap_or_default()
+    }
+
+    /// 边界拓扑（未知名称按有墙）
+    pub fn topology_kind(&self) -> Topology {
+        Topology::ALL.into_iter().find(|t| t.name() == self.topology).unwrap_or(Topology::Bounded)
+    }
+
+    /// 按名字找蛇
+    pub fn snake(&self, name: &str) -> Option<&SnakeSnapshot> {
+        self.snakes.iter().find(|s| s.name == name)
+    }
+
+    /// 编码成一行 JSON（含换行）
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = serde_json::to_vec(self).expect("snapshots always serialize");
+        bytes.push(b'\n');
+        bytes
+    }
+
+    /// 解码一行 JSON
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        serde_json::from_slice(bytes.trim_ascii()).map_err(|e| e.to_string())
+    }
+}
+
+/// 颜色转成快照里的数组
+pub fn color_array(color: Color) -> [f32; 4] {
+    [color.r, color.g, color.b, color.a]
+}
+
+/// 生效中的 Buff 和剩余时间
+pub fn active_buffs(buffs: &BuffState) -> Vec<BuffTag> {
+    [
+        (buffs.shield_active, "Shield", buffs.shield_timer),
+        (buffs.speed_active, "Speed", buffs.speed_timer),
+        (buffs.ghost_active, "Ghost", buffs.ghost_timer),
+        (buffs.frozen, "Frozen", buffs.freeze_timer),
+        (buffs.slow_active, "Slow", buffs.slow_timer),
+        (buffs.dizzy_active, "Dizzy", buffs.dizzy_timer),
+        (buffs.slime_active, "Slime", buffs.slime_timer),
+        (buffs.sandworm_active, "Sandworm", 0.0),
+    ]
+    .into_iter()
+    .filter(|&(on, _, _)| on)
+    .map(|(_, name, remaining)| BuffTag { name: name.to_string(), remaining: remaining.max(0.0) })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = WorldSnapshot {
+            frame: 3,
+            grid: GridKind::Hex.name().to_string(),
+            topology: Topology::Torus.name().to_string(),
+            food: [4, 5],
+            snakes: vec![SnakeSnapshot {
+                name: "P1".into(),
+                role: SnakeRole::Player,
+                body: vec![[3, 3], [2, 3]],
+                color: color_array(GREEN),
+                score: 12,
+                level: 0,
+                team: None,
+                downed: false,
+                buffs: vec![BuffTag { name: "Shield".into(), remaining: 2.5 }],
+            }],
+            ..Default::default()
+        };
+        let bytes = snapshot.encode();
+        assert_eq!(bytes.last(), Some(&b'\n'));
+        let decoded = WorldSnapshot::decode(&bytes).unwrap();
+        assert_eq!(decoded, snapshot);
+        assert_eq!(decoded.grid_kind(), GridKind::Hex);
+        assert_eq!(decoded.topology_kind(), Topology::Torus);
+        assert_eq!(decoded.snake("P1").and_then(|s| s.head()), Some(ivec2(3, 3)));
+        assert!(decoded.snake("P2").is_none());
+        assert!(WorldSnapshot::decode(b"{").is_err());
+    }
+
+    #[test]
+    fn test_active_buffs_lists_running_effects() {
+        let mut buffs = BuffState::default();
+        assert!(active_buffs(&buffs).is_empty());
+        buffs.shield_active = true;
+        buffs.shield_timer = 3.0;
+        buffs.dizzy_active = true;
+        buffs.dizzy_timer = -0.1;
+        let tags = active_buffs(&buffs);
+        let names: Vec<&str> = tags.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["Shield", "Dizzy"]);
+        assert_eq!(tags[0].remaining, 3.0);
+        assert_eq!(tags[1].remaining, 0.0);
+    }
+}
@@ -15,6 +15,7 @@
 //! - **果实生命周期**: 各类果实的存在时长
 //! - **Buff持续时间**: 各种增益/减益效果的持续时间
 //! - **沙虫模式**: 沙虫变身动画的各阶段参数
+//! - **胜利**: 完美通关奖励
 
 use macroquad::prelude::*;
 
@@ -42,6 +43,11 @@ pub const GRID_W: i32 = 32;
 /// 垂直方向的网格数量。窗口高度 = GRID_H * CELL
 pub const GRID_H: i32 = 24;
 
+/// 收起的食物的位置（棋盘外）
+///
+/// 棋盘上没有空位放食物、玩家又没填满棋盘时（AI 蛇和果实占着其余的格子）食物先收起来，空出位置后再放回去。
+pub const NO_FOOD: IVec2 = IVec2::new(-1, -1);
+
 // ============================================================================
 // 果实生命周期常量
 // ============================================================================
@@ -154,3 +160,26 @@ pub const SANDWORM_TRANSFORM_INTERVAL: f32 = 0.08;
 ///
 /// 填满屏幕后的闪烁时间，为最终坍缩做准备。
 pub const SANDWORM_FILLED_FLASH_DURATION: f32 = 1.5;
+
+/// AI 沙虫横冲阶段持续时间（秒）
+///
+/// AI 蛇不会填满屏幕，变身后在棋盘上追吃玩家的尾巴，时间到后恢复原样。
+pub const AI_SANDWORM_DURATION: f32 = 8.0;
+
+/// AI 沙虫横冲阶段的移动间隔倍率
+pub const AI_SANDWORM_SPEED: f32 = 0.6;
+
+// ============================================================================
+// 胜利常量
+// ============================================================================
+// 蛇身占满棋盘上所有能走的格子（障碍物和围栏不算）即为完美通关。
+
+/// 完美通关奖励分数
+///
+/// 填满棋盘时一次性加到得分上。
+pub const VICTORY_BONUS: u32 = 500;
+
+/// 消灭整支敌对队伍的奖励分数
+///
+/// 队伍最后一名队员死亡时加到得分上（盟友队伍覆灭不扣分）。
+pub const TEAM_WIPE_BONUS: u32 = 25;
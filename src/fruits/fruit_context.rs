@@ -7,9 +7,10 @@
 //! FruitContext 应该足够丰富，让每种果实在自己的回调中直接完成所有操作，
 //! 而不是通过事件间接触发。这样新增果实时只需要修改该果实的源文件。
 
+use std::collections::VecDeque;
 use macroquad::prelude::*;
-use ::rand::rngs::ThreadRng;
-use crate::types::{BuffState, DamageState, Particle, ComboState, Fruit};
+use crate::game::GameRng;
+use crate::types::{BuffState, DamageState, Particle, ComboState, Fruit, GridKind};
 use crate::game::AIManager;
 
 /// 果实上下文
@@ -19,7 +20,7 @@ use crate::game::AIManager;
 /// ## 设计说明
 ///
 /// 包含足够的资源引用，让果实回调可以直接操作游戏状态：
-/// - 生成 AI 蛇: `ctx.ai_manager.spawn_snake()`
+/// - 生成 AI 蛇: `ctx.ai_manager.spawn_default_snake()`
 /// - 修改食物位置: `*ctx.food = new_pos`
 /// - 生成其他果实: `ctx.fruits.push(...)`
 pub struct FruitContext<'a> {
@@ -27,10 +28,12 @@ pub struct FruitContext<'a> {
     // 核心游戏状态
     // -------------------------------------------------------------------------
     
-    /// 蛇身位置列表
-    pub snake: &'a mut Vec<IVec2>,
+    /// 蛇身位置（环形缓冲，`snake[0]` 是蛇头）
+    pub snake: &'a mut VecDeque<IVec2>,
     /// 移动方向
     pub dir: &'a mut IVec2,
+    /// 棋盘几何（方向运算使用）
+    pub grid: GridKind,
     /// Buff状态
     pub buff_state: &'a mut BuffState,
     /// 粒子列表
@@ -42,7 +45,7 @@ pub struct FruitContext<'a> {
     /// Combo状态
     pub combo_state: &'a mut ComboState,
     /// 随机数生成器
-    pub rng: &'a mut ThreadRng,
+    pub rng: &'a mut GameRng,
     /// 当前游戏时间
     pub game_time: f32,
     /// 果实位置
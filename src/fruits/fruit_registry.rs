@@ -4,7 +4,7 @@
 
 use std::collections::HashMap;
 use ::rand::Rng;
-use ::rand::rngs::ThreadRng;
+use crate::game::GameRng;
 use macroquad::prelude::*;
 use super::{FruitBehavior, FruitCategory, FruitConfig};
 
@@ -71,7 +71,7 @@ impl FruitRegistry {
         &self,
         category: FruitCategory,
         snake_length: usize,
-        rng: &mut ThreadRng,
+        rng: &mut GameRng,
     ) -> Option<&'static str> {
         let ids = self.by_category.get(&category)?;
 
@@ -4,7 +4,8 @@
 mod proptests {
     use proptest::prelude::*;
     use crate::fruits::{create_fruit_registry, FruitCategory};
-    use ::rand::thread_rng;
+    use crate::game::GameRng;
+    use ::rand::SeedableRng;
 
     // **Feature: modular-migration, Property 6: Fruit Registry Unlock Condition**
     // *For any* snake length and fruit category, FruitRegistry::random_by_category
@@ -16,9 +17,10 @@ mod proptests {
         fn prop_fruit_unlock_condition(
             snake_length in 1usize..50,
             category_idx in 0usize..4,
+            seed in any::<u64>(),
         ) {
             let registry = create_fruit_registry();
-            let mut rng = thread_rng();
+            let mut rng = GameRng::seed_from_u64(seed);
 
             let category = match category_idx {
                 0 => FruitCategory::Normal,
@@ -210,7 +210,7 @@ pub trait FruitBehavior: Send + Sync {
     /// 消费结果，决定后续游戏行为（继续、结束、加分等）
     ///
     /// # 可用操作
-    /// - 修改蛇身: `ctx.snake.push()`, `ctx.snake.pop()`
+    /// - 修改蛇身: `ctx.snake.push_back()`, `ctx.snake.pop_back()`
     /// - 激活Buff: `ctx.buff_state.activate_shield()`
     /// - 生成粒子: `ctx.particles.push()`
     /// - 修改分数: `*ctx.score += 10`
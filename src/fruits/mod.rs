@@ -66,7 +66,7 @@ pub use fruit_context::*;
 use normal::NormalFruit;
 use trap::{TrapFruit, FreezeFruit, SlowFruit, DizzyFruit, SlimeFruit, BombFruit};
 use power::{ShieldFruit, SpeedFruit, GhostFruit, ReverseFruit, SandwormFruit, HealFruit};
-use special::{LuckyFruit, SnakeEggFruit};
+use special::{LuckyFruit, PetEggFruit, SnakeEggFruit};
 
 /// 创建并初始化果实注册表
 ///
@@ -96,6 +96,7 @@ pub fn create_fruit_registry() -> FruitRegistry {
     // 特殊果实
     registry.register(Box::new(LuckyFruit::new()));
     registry.register(Box::new(SnakeEggFruit::new()));
+    registry.register(Box::new(PetEggFruit::new()));
 
     registry
 }
@@ -2,6 +2,7 @@
 
 use macroquad::prelude::*;
 use crate::fruits::{FruitBehavior, FruitConfig, FruitCategory, FruitContext, ConsumeResult};
+use crate::types::Grid;
 
 /// 逆转果实
 ///
@@ -41,13 +42,13 @@ impl FruitBehavior for ReverseFruit {
 
     fn on_consume(&self, ctx: &mut FruitContext) -> ConsumeResult {
         // 逆转蛇身
-        ctx.snake.reverse();
+        ctx.snake.make_contiguous().reverse();
 
-        // 更新方向为新头部的方向
+        // 更新方向为新头部的方向（跨越环绕边界时无法推断，直接掉头）
         if ctx.snake.len() > 1 {
-            let new_head = ctx.snake[0];
-            let second = ctx.snake[1];
-            *ctx.dir = ivec2(new_head.x - second.x, new_head.y - second.y);
+            *ctx.dir = ctx.grid
+                .direction_between(ctx.snake[1], ctx.snake[0])
+                .unwrap_or(-*ctx.dir);
         }
 
         ConsumeResult::Continue
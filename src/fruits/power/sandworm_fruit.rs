@@ -47,7 +47,7 @@ impl FruitBehavior for SandwormFruit {
         ctx.buff_state.sandworm_phase = SandwormPhase::Flashing;
         ctx.buff_state.sandworm_phase_timer = 0.0;
         ctx.buff_state.sandworm_transform_index = 0;
-        ctx.buff_state.sandworm_original_snake = ctx.snake.clone();
+        ctx.buff_state.sandworm_original_snake = ctx.snake.iter().copied().collect();
         ctx.buff_state.sandworm_original_dir = *ctx.dir;
         ctx.buff_state.sandworm_path = vec![];
         ctx.buff_state.sandworm_index = 0;
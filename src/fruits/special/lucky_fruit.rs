@@ -103,8 +103,8 @@ impl FruitBehavior for LuckyFruit {
                 _ => {
                     // 蛇身增长3节
                     for _ in 0..3 {
-                        if let Some(tail) = ctx.snake.last().cloned() {
-                            ctx.snake.push(tail);
+                        if let Some(&tail) = ctx.snake.back() {
+                            ctx.snake.push_back(tail);
                         }
                     }
                 }
@@ -148,7 +148,7 @@ impl FruitBehavior for LuckyFruit {
                 }
                 _ => {
                     // 生成 AI 蛇 - 直接调用 ai_manager，不需要事件中转
-                    ctx.ai_manager.spawn_snake(ctx.snake, ctx.rng);
+                    ctx.ai_manager.spawn_default_snake(ctx.snake.make_contiguous(), ctx.rng);
                 }
             }
             ConsumeResult::ResetCombo
@@ -1,7 +1,9 @@
 //! 特殊果实模块
 
 mod lucky_fruit;
+mod pet_egg;
 mod snake_egg;
 
 pub use lucky_fruit::LuckyFruit;
+pub use pet_egg::PetEggFruit;
 pub use snake_egg::SnakeEggFruit;
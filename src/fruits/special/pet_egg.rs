@@ -0,0 +1,91 @@
+//! 宠物蛋果实
+//!
+//! 吃掉后蛋在玩家体内沿蛇身往后移动（像炸弹一样），到达蛇尾时孵化出一条宠物蛇。
+//! 宠物蛇吃到的东西给玩家加分，会挡住敌对 AI 蛇，玩家撞上它时它会死。
+
+use macroquad::prelude::*;
+use ::rand::Rng;
+use crate::constants::CELL;
+use crate::fruits::{FruitConfig, FruitCategory, FruitContext, FruitBehavior, ConsumeResult};
+
+/// 宠物蛋果实
+pub struct PetEggFruit {
+    config: FruitConfig,
+}
+
+impl PetEggFruit {
+    pub fn new() -> Self {
+        PetEggFruit {
+            config: FruitConfig {
+                id: "pet_egg",
+                name: "宠物蛋",
+                category: FruitCategory::Special,
+                color: Color::new(1.0, 0.85, 0.9, 1.0), // 粉色蛋壳
+                lifetime: 12.0,
+                spawn_weight: 30,
+                unlock_length: 8,
+                immune_to_buffs: false,
+                weight_growth: 0, // 不增长
+            },
+        }
+    }
+}
+
+impl Default for PetEggFruit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FruitBehavior for PetEggFruit {
+    fn config(&self) -> &FruitConfig {
+        &self.config
+    }
+
+    fn on_consume(&self, ctx: &mut FruitContext) -> ConsumeResult {
+        // 体内已经有蛋在孵化时只加分
+        if !ctx.buff_state.egg_state.active {
+            ctx.buff_state.egg_state.activate();
+        }
+
+        // 生成粉色粒子
+        let head = ctx.snake[0];
+        for _ in 0..10 {
+            let lifetime = 0.6;
+            ctx.particles.push(crate::types::Particle {
+                pos: vec2(
+                    head.x as f32 * CELL + CELL / 2.0,
+                    head.y as f32 * CELL + CELL / 2.0,
+                ),
+                vel: vec2(
+                    ctx.rng.gen_range(-40.0..40.0),
+                    ctx.rng.gen_range(-40.0..40.0),
+                ),
+                color: self.config.color,
+                lifetime,
+                max_lifetime: lifetime,
+                size: ctx.rng.gen_range(3.0..6.0),
+            });
+        }
+
+        ConsumeResult::AddScore(3)
+    }
+
+    fn render(&self, x: f32, y: f32, time: f32) {
+        // 蛋形外观，轻轻摇晃
+        let wobble = (time * 4.0).sin() * 1.5;
+        let cx = x + CELL / 2.0 + wobble;
+        let cy = y + CELL / 2.0;
+        draw_circle(cx, cy + 1.0, CELL / 2.0 - 3.0, self.config.color);
+        draw_circle(cx, cy - 3.0, CELL / 2.0 - 5.0, self.config.color);
+
+        // 蛋壳上的斑点
+        let spot = Color::new(0.95, 0.45, 0.65, 1.0);
+        draw_circle(cx - 3.0, cy, 2.0, spot);
+        draw_circle(cx + 3.0, cy + 3.0, 1.5, spot);
+        draw_circle(cx + 1.0, cy - 4.0, 1.5, spot);
+
+        // 高光
+        draw_rectangle(cx - 5.0, cy - 6.0, 2.0, 3.0, Color::new(1.0, 1.0, 1.0, 0.5));
+    }
+}
@@ -1,6 +1,6 @@
 //! 蛇蛋果实
 //!
-//! 触发后生成一条 AI 蛇，增加游戏挑战性。
+//! 触发后生成一条 AI 蛇，增加游戏挑战性。盟友编排下玩家吃掉蛇蛋会孵化一条盟友。
 
 use macroquad::prelude::*;
 use ::rand::Rng;
@@ -42,8 +42,9 @@ impl FruitBehavior for SnakeEggFruit {
     }
 
     fn on_consume(&self, ctx: &mut FruitContext) -> ConsumeResult {
-        // 被吃掉 = 阻止孵化，只加分
+        // 被吃掉 = 阻止孵化，只加分；盟友编排下孵化成玩家的盟友
         *ctx.score += 5;
+        ctx.ai_manager.spawn_ally(ctx.snake.make_contiguous(), ctx.rng);
         
         // 生成蛋壳碎片粒子效果
         let head = ctx.snake[0];
@@ -65,7 +66,7 @@ impl FruitBehavior for SnakeEggFruit {
             });
         }
         
-        // 被吃掉不生成 AI 蛇
+        // 被吃掉不生成敌对 AI 蛇
         ConsumeResult::AddScore(0) // 分数已经加过了
     }
 
@@ -75,7 +76,7 @@ impl FruitBehavior for SnakeEggFruit {
     /// 主循环不需要知道蛇蛋的存在。
     fn on_expire(&self, ctx: &mut FruitContext) {
         // 直接生成 AI 蛇 - 不需要事件中转
-        ctx.ai_manager.spawn_snake(ctx.snake, ctx.rng);
+        ctx.ai_manager.spawn_default_snake(ctx.snake.make_contiguous(), ctx.rng);
         
         // 生成孵化粒子效果
         let pos = ctx.fruit_pos;
@@ -87,7 +87,7 @@ impl FruitBehavior for FreezeFruit {
         ctx.buff_state.freeze_timer = FREEZE_DURATION;
 
         // 生成冰冻粒子
-        Self::spawn_freeze_particles(ctx.particles, ctx.snake, ctx.rng);
+        Self::spawn_freeze_particles(ctx.particles, ctx.snake.make_contiguous(), ctx.rng);
 
         ConsumeResult::ResetCombo
     }
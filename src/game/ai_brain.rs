@@ -0,0 +1,1227 @@
+//! AI 大脑
+//!
+//! AI 蛇的决策逻辑与蛇本身分离：[`AISnake`](super::AISnake) 持有一个 `Box<dyn AIBrain>`，
+//! 每次决策时把只读的 [`AIObservation`] 交给大脑，大脑返回下一步方向。
+//! 冰冻、眩晕等状态效果和"不能掉头"的规则由蛇自己处理，大脑只负责选路。
+//!
+//! ## 内置大脑
+//!
+//! | 大脑 | 行为 |
+//! |------|------|
+//! | [`GreedyBrain`] | 直线距离最近的目标，只看一步是否安全 |
+//! | [`PathfindingBrain`] | BFS 寻路 + 洪水填充生存检查 + 追尾巴 |
+//! | [`RandomBrain`] | 在安全方向中随机选择 |
+//! | [`HunterBrain`] | 抢到玩家蛇头前方拦截 |
+//! | [`CowardBrain`] | 远离玩家吃东西，玩家有护盾/幽灵时逃跑 |
+//! | [`ForagerBrain`] | 不吃陷阱果实，优先功能果实 |
+//! | [`TricksterBrain`] | 守在离玩家最近的陷阱果实旁，把玩家引过去 |
+//!
+//! 后四种是"性格"，各有固定颜色，生成时按难度加权随机（见 [`personality_weights`]）。
+//! 寻路类大脑共用 [`RoutePlan`]，只是给目标打分的方式不同；路线会穿过传送门。
+//!
+//! ## 目标估值
+//!
+//! 寻路类大脑的基础目标来自 [`AIObservation::scored_targets`]，按果实配置和双方 Buff 打分：
+//!
+//! - 陷阱果实不吃（自己有护盾时无害，才作为低优先目标）
+//! - 功能果实和特殊果实优先；对应 Buff 已经生效时当作普通果实
+//! - 按自己的移动间隔赶不上的果实（会先消失）不追
+//! - 玩家比自己先到的目标降低优先级；玩家被冰冻时不用让
+//! - 玩家变成沙虫时不靠近它的蛇头
+//! - 自己有护盾或玩家被冰冻时，把玩家蛇头前方的格子也当作目标（堵截）
+//! - 自己变成沙虫时追吃玩家的尾巴（玩家有护盾/幽灵/沙虫时不追），玩家和其他 AI 蛇都不再算阻挡
+//!
+//! 有队伍时（见 [`super::team`]）：
+//!
+//! - 队友已经在追的目标代价更高；关闭误伤时队友的身体不算阻挡
+//! - 敌对队伍两条以上时，每条蛇按队内序号去占玩家蛇头前方或两侧的一个格子（包围）
+//! - 玩家的盟友不堵截、不吞玩家，离玩家太远时去玩家尾巴旁边，并堵截敌对 AI 蛇的蛇头
+//!
+//! ## 编写自己的大脑
+//!
+//! ```rust,ignore
+//! struct Lefty;
+//!
+//! impl AIBrain for Lefty {
+//!     fn name(&self) -> &'static str { "Lefty" }
+//!     fn decide(&mut self, obs: &AIObservation, _rng: &mut dyn RngCore) -> Option<IVec2> {
+//!         obs.safe_dirs().into_iter().next()
+//!     }
+//! }
+//!
+//! ai_manager.spawn_snake(player_body, || Box::new(Lefty), &mut rng);
+//! ```
+
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+use ::rand::{Rng, RngCore};
+use crate::fruits::{FruitCategory, FruitRegistry};
+use crate::types::{BoardDims, BuffState, Difficulty, Fruit, Grid, GridKind, Portal, Topology};
+
+/// 功能果实相对普通目标的优先程度（步数）
+const POWER_BONUS: i32 = 6;
+/// 特殊果实相对普通目标的优先程度（步数）
+const SPECIAL_BONUS: i32 = 2;
+/// 免疫时吃陷阱果实的代价（没有坏处也没有好处）
+const IMMUNE_TRAP_COST: i32 = 4;
+/// 玩家会先到的目标的代价（步数）
+const CONTESTED_COST: i32 = 5;
+/// 堵截玩家蛇头相对普通目标的优先程度（步数）
+const INTERCEPT_BONUS: i32 = 10;
+/// 预测玩家前进的格子数
+const INTERCEPT_LOOKAHEAD: u32 = 5;
+/// 玩家是沙虫时不靠近其蛇头的距离
+const SANDWORM_RADIUS: u32 = 4;
+/// 队友已经在追的目标的代价（步数）
+const CLAIMED_COST: i32 = 8;
+/// 包围玩家相对普通目标的优先程度（步数）
+const SURROUND_BONUS: i32 = 6;
+/// 包围时占住的格子离玩家蛇头的步数
+const SURROUND_REACH: u32 = 2;
+/// 盟友离玩家蛇尾超过这个距离就跟上去
+const ESCORT_RANGE: i32 = 6;
+/// 盟友跟随玩家相对普通目标的优先程度（步数）
+const ESCORT_BONUS: i32 = 12;
+use super::{check_diagonal_squeeze_on, find_portal_exit, flood_fill, passable_step, OccupancyGrid, PathMap, TeamBoard};
+
+/// AI 蛇决策时看到的世界（只读）
+pub struct AIObservation<'a> {
+    /// 自己的 ID（在队伍快照里找到自己）
+    pub id: u32,
+    /// 自己的身体，`body[0]` 是蛇头
+    pub body: &'a VecDeque<IVec2>,
+    /// 自己身体的占用网格
+    pub occupancy: &'a OccupancyGrid,
+    /// 当前移动方向
+    pub dir: IVec2,
+    /// 自己的 Buff 状态
+    pub buff_state: &'a BuffState,
+    /// 自己当前的移动间隔（秒）
+    pub tick: f32,
+    /// 游戏时间（判断果实剩余寿命）
+    pub game_time: f32,
+    /// 棋盘几何
+    pub grid: GridKind,
+    /// 地图拓扑
+    pub topology: Topology,
+    /// 棋盘尺寸
+    pub board: BoardDims,
+    /// 普通食物位置
+    pub food: IVec2,
+    /// 场上的果实
+    pub fruits: &'a [Fruit],
+    /// 果实注册表（查询果实类别）
+    pub registry: &'a FruitRegistry,
+    /// 玩家蛇身占用网格
+    pub player: &'a OccupancyGrid,
+    /// 玩家蛇头（玩家蛇为空时为 None）
+    pub player_head: Option<IVec2>,
+    /// 玩家蛇尾（玩家蛇为空时为 None）
+    pub player_tail: Option<IVec2>,
+    /// 玩家移动方向
+    pub player_dir: IVec2,
+    /// 玩家的 Buff 状态
+    pub player_buffs: &'a BuffState,
+    /// 所有 AI 蛇（包括自己）的共享占用网格
+    pub ai_occupancy: &'a OccupancyGrid,
+    /// 障碍物在下一个 tick 时的预测位置
+    pub hazard_cells: &'a [IVec2],
+    /// 场上的传送门
+    pub portals: &'a [Portal],
+    /// 所有 AI 蛇的队伍快照
+    pub teams: &'a TeamBoard,
+}
+
+impl AIObservation<'_> {
+    /// 蛇头位置
+    pub fn head(&self) -> IVec2 {
+        self.body[0]
+    }
+
+    /// 蛇尾位置
+    pub fn tail(&self) -> IVec2 {
+        self.body[self.body.len() - 1]
+    }
+
+    /// 蛇长
+    pub fn len(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty()
+    }
+
+    /// 所有目标：普通食物和果实
+    pub fn targets(&self) -> impl Iterator<Item = IVec2> + '_ {
+        std::iter::once(self.food).chain(self.fruits.iter().map(|f| f.pos))
+    }
+
+    /// 果实的类别（未注册的果实返回 None）
+    pub fn category(&self, fruit: &Fruit) -> Option<FruitCategory> {
+        self.registry.get_config(fruit.type_id).map(|c| c.category)
+    }
+
+    /// 某类果实的位置
+    pub fn fruits_of(&self, category: FruitCategory) -> Vec<IVec2> {
+        self.fruits
+            .iter()
+            .filter(|f| self.category(f) == Some(category))
+            .map(|f| f.pos)
+            .collect()
+    }
+
+    /// 下一步可选的方向（不能掉头）
+    pub fn candidate_dirs(&self) -> Vec<IVec2> {
+        self.grid
+            .directions()
+            .iter()
+            .copied()
+            .filter(|&d| !self.grid.is_reverse(d, self.dir))
+            .collect()
+    }
+
+    /// 某格下一步是否可以进入（`pos` 已按拓扑映射回地图内）
+    ///
+    /// 自己的尾巴这一步会移走，所以不算阻挡；护盾/幽灵状态下障碍物不算阻挡；
+    /// 沙虫会吞掉撞上的玩家和 AI 蛇，它们也不算阻挡；关闭误伤时队友（盟友还包括玩家）不算阻挡
+    pub fn is_safe(&self, pos: IVec2) -> bool {
+        self.safety()(pos)
+    }
+
+    /// 同 [`is_safe`](Self::is_safe)，但和格子无关的判断（Buff、队伍）只做一次，寻路时逐格调用
+    pub fn safety(&self) -> impl Fn(IVec2) -> bool + '_ {
+        let tail = self.tail();
+        let passes_hazards = self.buff_state.can_pass_through();
+        let player_passable = self.devours_player() || self.passes_player();
+        let team = self.teams.passable_bodies(self.id);
+        let sandworm = self.buff_state.sandworm_active;
+        move |pos| {
+            let own = self.occupancy.count(pos);
+            let own_blocks = own > u16::from(pos == tail);
+            let hazard_blocks = !passes_hazards && self.hazard_cells.contains(&pos);
+            let player_blocks = !player_passable && self.player.is_occupied(pos);
+            let passable = team.map_or(own, |team| team.count(pos));
+            let ai_blocks = !sandworm && self.ai_occupancy.count(pos) > passable;
+            !own_blocks && !player_blocks && !ai_blocks && !hazard_blocks
+        }
+    }
+
+    /// 自己是沙虫且玩家没有护盾/幽灵/沙虫：撞上玩家会吞掉它的尾巴（盟友不吞）
+    pub fn devours_player(&self) -> bool {
+        self.buff_state.sandworm_active && !self.player_protected() && !self.is_ally()
+    }
+
+    /// 自己是不是玩家的盟友
+    pub fn is_ally(&self) -> bool {
+        self.teams.is_ally(self.id)
+    }
+
+    /// 关闭误伤时盟友可以穿过玩家
+    fn passes_player(&self) -> bool {
+        self.is_ally() && !self.teams.friendly_fire
+    }
+
+    /// 玩家是否处于护盾/幽灵/沙虫状态（撞上去不会死）
+    pub fn player_protected(&self) -> bool {
+        self.player_buffs.can_pass_through()
+    }
+
+    /// 沿 `dir` 走一步后的位置，撞墙、不安全或斜向挤过蛇身之间时返回 None
+    ///
+    /// 走进传送门入口时出口也必须安全
+    pub fn safe_step(&self, dir: IVec2) -> Option<IVec2> {
+        let (next, _) = self.grid.step_on(self.topology, self.board, self.head(), dir)?;
+        let squeezed = check_diagonal_squeeze_on(self.grid, self.topology, self.board, self.head(), dir, |p| {
+            self.player.is_occupied(p) || self.ai_occupancy.is_occupied(p)
+        });
+        let exit_safe = find_portal_exit(next, dir, self.portals).is_none_or(|exit| self.is_safe(exit.to));
+        (!squeezed && self.is_safe(next) && exit_safe).then_some(next)
+    }
+
+    /// 所有一步内安全的方向
+    pub fn safe_dirs(&self) -> Vec<IVec2> {
+        self.candidate_dirs()
+            .into_iter()
+            .filter(|&d| self.safe_step(d).is_some())
+            .collect()
+    }
+
+    /// 从玩家蛇头出发到各格的步数（按拓扑环绕，不考虑阻挡）
+    pub fn player_distances(&self) -> Option<PathMap> {
+        let head = self.player_head?;
+        Some(PathMap::search(self.grid, self.topology, self.board, head, self.grid.directions(), self.portals, |_| false))
+    }
+
+    /// 会伤到自己的陷阱果实位置（有护盾/沙虫免疫时为空），寻路时绕开
+    pub fn harmful_traps(&self) -> Vec<IVec2> {
+        if self.buff_state.has_immunity() {
+            return Vec::new();
+        }
+        self.fruits_of(FruitCategory::Trap)
+    }
+
+    /// 果实相对普通食物的偏置，不值得吃时返回 None
+    pub fn fruit_bias(&self, fruit: &Fruit) -> Option<i32> {
+        match self.category(fruit) {
+            Some(FruitCategory::Trap) => self.buff_state.has_immunity().then_some(IMMUNE_TRAP_COST),
+            Some(FruitCategory::Power) if self.buff_redundant(fruit.type_id) => Some(0),
+            Some(FruitCategory::Power) => Some(-POWER_BONUS),
+            Some(FruitCategory::Special) => Some(-SPECIAL_BONUS),
+            Some(FruitCategory::Normal) | None => Some(0),
+        }
+    }
+
+    /// 功能果实的效果是否已经在自己身上生效
+    fn buff_redundant(&self, type_id: &str) -> bool {
+        let buffs = self.buff_state;
+        match type_id {
+            "shield" => buffs.shield_active,
+            "speed" => buffs.speed_active,
+            "ghost" => buffs.ghost_active,
+            "heal" => !(buffs.slow_active || buffs.dizzy_active || buffs.slime_active),
+            _ => false,
+        }
+    }
+
+    /// 按自己的移动间隔走 `steps` 步之前果实是否已经消失
+    pub fn expires_before(&self, fruit: &Fruit, steps: u32) -> bool {
+        fruit.lifetime > 0.0 && fruit.spawn_time + fruit.lifetime - self.game_time < steps as f32 * self.tick
+    }
+
+    /// 玩家蛇头前方、能赶在玩家之前（或同时）到达的格子
+    ///
+    /// 玩家被冰冻时不会动，前方的格子都算来得及；玩家无敌时堵截没有意义，盟友不堵截玩家，都返回空
+    pub fn intercept_cells(&self, plan: &RoutePlan) -> Vec<IVec2> {
+        match self.player_head.filter(|_| !self.player_protected() && !self.is_ally()) {
+            Some(head) => self.cells_ahead(plan, head, self.player_dir, self.player_buffs.frozen),
+            None => Vec::new(),
+        }
+    }
+
+    /// 盟友堵截敌对 AI 蛇：每条敌对蛇蛇头前方能抢先到达的格子
+    pub fn enemy_intercept_cells(&self, plan: &RoutePlan) -> Vec<IVec2> {
+        if !self.is_ally() {
+            return Vec::new();
+        }
+        self.teams
+            .enemies_of(self.id)
+            .flat_map(|enemy| self.cells_ahead(plan, enemy.head, enemy.dir, enemy.frozen))
+            .collect()
+    }
+
+    /// 从 `head` 沿 `dir` 前进的格子中，自己能同时或更早到达的（`frozen` 时对方不动，都来得及）
+    fn cells_ahead(&self, plan: &RoutePlan, head: IVec2, dir: IVec2, frozen: bool) -> Vec<IVec2> {
+        let (mut pos, mut dir) = (head, dir);
+        let mut cells = Vec::new();
+        for k in 1..=INTERCEPT_LOOKAHEAD {
+            let Some((next, next_dir)) = self.grid.step_on(self.topology, self.board, pos, dir) else { break };
+            (pos, dir) = (next, next_dir);
+            if plan.distance(pos).is_some_and(|d| frozen || d <= k + 1) {
+                cells.push(pos);
+            }
+        }
+        cells
+    }
+
+    /// 包围玩家时分到的格子
+    ///
+    /// 敌对队伍至少两条蛇时，按队内序号轮流分配玩家蛇头前方和两侧 [`SURROUND_REACH`] 步处的格子；
+    /// 玩家无敌、自己是盟友或没有队友时为 None
+    pub fn surround_cell(&self) -> Option<IVec2> {
+        if self.is_ally() || self.player_protected() {
+            return None;
+        }
+        let (rank, _) = self.teams.rank(self.id).filter(|&(_, size)| size >= 2)?;
+        let head = self.player_head?;
+        let slots: Vec<IVec2> = self
+            .grid
+            .directions()
+            .iter()
+            .filter(|&&d| !self.grid.is_reverse(d, self.player_dir))
+            .filter_map(|&d| {
+                let (mut pos, mut dir) = (head, d);
+                for _ in 0..SURROUND_REACH {
+                    (pos, dir) = self.grid.step_on(self.topology, self.board, pos, dir)?;
+                }
+                Some(pos)
+            })
+            .collect();
+        (!slots.is_empty()).then(|| slots[rank % slots.len()])
+    }
+
+    /// 盟友离玩家蛇尾太远时，蛇尾旁边能进入的格子
+    pub fn escort_cells(&self) -> Vec<IVec2> {
+        let Some(tail) = self.player_tail.filter(|_| self.is_ally()) else {
+            return Vec::new();
+        };
+        if self.grid.distance(self.head(), tail) <= ESCORT_RANGE {
+            return Vec::new();
+        }
+        self.grid
+            .directions()
+            .iter()
+            .filter_map(|&d| self.grid.step_on(self.topology, self.board, tail, d).map(|(p, _)| p))
+            .filter(|&p| self.is_safe(p))
+            .collect()
+    }
+
+    /// 寻路类大脑的基础目标：(位置, 偏置)，偏置越小越想去，不值得去的目标不返回
+    ///
+    /// 规则见模块文档"目标估值"
+    pub fn scored_targets(&self, plan: &RoutePlan) -> Vec<(IVec2, i32)> {
+        let rival = self.player_distances();
+        let rival_distance = |pos: IVec2| rival.as_ref().and_then(|m| m.distance(pos));
+        let player_frozen = self.player_buffs.frozen;
+        let sandworm = self.player_buffs.sandworm_active;
+
+        let food = std::iter::once((self.food, Some(0)));
+        let fruits = self.fruits.iter().map(|f| {
+            let bias = self.fruit_bias(f).filter(|_| {
+                !plan.distance(f.pos).is_some_and(|steps| self.expires_before(f, steps))
+            });
+            (f.pos, bias)
+        });
+
+        let mut goals: Vec<(IVec2, i32)> = food
+            .chain(fruits)
+            .filter_map(|(pos, bias)| {
+                let mut bias = bias?;
+                let ours = plan.distance(pos)?;
+                match rival_distance(pos) {
+                    Some(theirs) if sandworm && theirs <= SANDWORM_RADIUS => return None,
+                    Some(theirs) if !player_frozen && theirs < ours => bias += CONTESTED_COST,
+                    _ => {}
+                }
+                if self.teams.claimed(self.id, pos) {
+                    bias += CLAIMED_COST;
+                }
+                Some((pos, bias))
+            })
+            .collect();
+
+        if self.buff_state.has_immunity() || player_frozen {
+            goals.extend(self.intercept_cells(plan).into_iter().map(|p| (p, -INTERCEPT_BONUS)));
+        }
+        if self.devours_player() {
+            goals.extend(self.player_tail.map(|p| (p, -INTERCEPT_BONUS)));
+        }
+        goals.extend(self.surround_cell().map(|p| (p, -SURROUND_BONUS)));
+        goals.extend(self.escort_cells().into_iter().map(|p| (p, -ESCORT_BONUS)));
+        goals.extend(self.enemy_intercept_cells(plan).into_iter().map(|p| (p, -INTERCEPT_BONUS)));
+        goals
+    }
+}
+
+/// 一次决策的寻路数据
+///
+/// 包含到所有格子的最短路径、每个方向走一步后的剩余空间和追尾巴的方向。
+/// 走进比身体小的区域会困死自己，除非这一步正是在追自己的尾巴。
+pub struct RoutePlan {
+    paths: PathMap,
+    /// (方向, 走一步后的位置, 剩余空间（数到蛇长为止）)
+    moves: Vec<(IVec2, IVec2, usize)>,
+    tail_dir: Option<IVec2>,
+    len: usize,
+}
+
+impl RoutePlan {
+    /// 规划路线，`avoid` 中的格子额外视为不可进入（例如陷阱果实）
+    pub fn new(obs: &AIObservation, avoid: impl Fn(IVec2) -> bool) -> Self {
+        let (grid, topology, board) = (obs.grid, obs.topology, obs.board);
+        let head = obs.head();
+        let len = obs.len();
+        let safe = obs.safety();
+        let blocked = |p: IVec2| !safe(p) || avoid(p);
+        let first_dirs = obs.candidate_dirs();
+
+        let moves = first_dirs
+            .iter()
+            .filter_map(|&dir| {
+                let (next, _) = passable_step(grid, topology, board, head, dir, &blocked)?;
+                Some((dir, next, flood_fill(grid, topology, board, next, len, obs.portals, blocked)))
+            })
+            .collect();
+
+        // 自己的尾巴这一步会移走，可以追
+        let paths = PathMap::search(grid, topology, board, head, &first_dirs, obs.portals, blocked);
+        let tail_dir = paths.first_step(obs.tail());
+        RoutePlan { paths, moves, tail_dir, len }
+    }
+
+    /// 到 `pos` 的最短步数
+    pub fn distance(&self, pos: IVec2) -> Option<u32> {
+        self.paths.distance(pos)
+    }
+
+    /// 这一步是否不会把自己困死
+    pub fn is_safe(&self, dir: IVec2) -> bool {
+        Some(dir) == self.tail_dir || self.moves.iter().any(|&(d, _, space)| d == dir && space >= self.len)
+    }
+
+    /// 所有不会把自己困死的 (方向, 走一步后的位置)
+    pub fn safe_moves(&self) -> impl Iterator<Item = (IVec2, IVec2)> + '_ {
+        self.moves.iter().filter(|&&(d, _, _)| self.is_safe(d)).map(|&(d, next, _)| (d, next))
+    }
+
+    /// 按"路径长度 + 偏置"给目标打分，返回得分最低且第一步安全的 (目标, 方向)
+    pub fn best(&self, goals: impl IntoIterator<Item = (IVec2, i32)>) -> Option<(IVec2, IVec2)> {
+        goals
+            .into_iter()
+            .filter_map(|(pos, bias)| {
+                let dir = self.paths.first_step(pos).filter(|&d| self.is_safe(d))?;
+                Some((self.distance(pos)? as i32 + bias, pos, dir))
+            })
+            .min_by_key(|&(score, _, _)| score)
+            .map(|(_, pos, dir)| (pos, dir))
+    }
+
+    /// 没有目标时的方向：追自己的尾巴，追不到就往空间最大的方向走
+    pub fn fallback(&self) -> Option<IVec2> {
+        self.tail_dir.or_else(|| self.moves.iter().max_by_key(|&&(_, _, space)| space).map(|&(d, _, _)| d))
+    }
+}
+
+/// AI 蛇的决策逻辑
+///
+/// `Send` 让持有大脑的 AI 蛇可以交给其他线程更新
+pub trait AIBrain: Send {
+    /// 显示名称
+    fn name(&self) -> &'static str;
+
+    /// 选择下一步方向，返回 None 保持当前方向
+    ///
+    /// 返回掉头方向会被忽略
+    fn decide(&mut self, obs: &AIObservation, rng: &mut dyn RngCore) -> Option<IVec2>;
+
+    /// 当前追逐的目标（调试和渲染用）
+    fn target(&self) -> Option<IVec2> {
+        None
+    }
+
+    /// 固定的蛇身颜色（None 时随机选择）
+    fn tint(&self) -> Option<Color> {
+        None
+    }
+}
+
+/// 按规划选目标，没有目标时走后备方向，同时记录目标
+fn follow(plan: &RoutePlan, goals: impl IntoIterator<Item = (IVec2, i32)>, target: &mut Option<IVec2>) -> Option<IVec2> {
+    let best = plan.best(goals);
+    *target = best.map(|(pos, _)| pos);
+    best.map(|(_, dir)| dir).or_else(|| plan.fallback())
+}
+
+/// 贪心大脑：直线距离最近的目标，只看一步
+#[derive(Clone, Debug, Default)]
+pub struct GreedyBrain {
+    target: Option<IVec2>,
+}
+
+impl AIBrain for GreedyBrain {
+    fn name(&self) -> &'static str {
+        "Greedy"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, rng: &mut dyn RngCore) -> Option<IVec2> {
+        let head = obs.head();
+        self.target = obs.targets().min_by_key(|&pos| obs.grid.distance(head, pos));
+
+        // 由棋盘几何决定朝目标前进的方向，不安全时按顺序换一个
+        let preferred = self.target.and_then(|t| obs.grid.toward(head, t)).unwrap_or(obs.dir);
+        let candidates = obs.candidate_dirs();
+        std::iter::once(preferred)
+            .chain(candidates.iter().copied())
+            .filter(|&d| !obs.grid.is_reverse(d, obs.dir))
+            .find(|&d| obs.safe_step(d).is_some())
+            // 没有安全方向，随机选一个（可能会死）
+            .or_else(|| (!candidates.is_empty()).then(|| candidates[rng.gen_range(0..candidates.len())]))
+    }
+
+    fn target(&self) -> Option<IVec2> {
+        self.target
+    }
+}
+
+/// 寻路大脑：按估值选路径最短的目标，带生存检查和追尾巴（所有搜索都按地图拓扑环绕）
+#[derive(Clone, Debug, Default)]
+pub struct PathfindingBrain {
+    target: Option<IVec2>,
+}
+
+impl AIBrain for PathfindingBrain {
+    fn name(&self) -> &'static str {
+        "Pathfinding"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, _rng: &mut dyn RngCore) -> Option<IVec2> {
+        let traps = obs.harmful_traps();
+        let plan = RoutePlan::new(obs, |p| traps.contains(&p));
+        follow(&plan, obs.scored_targets(&plan), &mut self.target)
+    }
+
+    fn target(&self) -> Option<IVec2> {
+        self.target
+    }
+}
+
+/// 随机大脑：在一步内安全的方向中随机选择
+#[derive(Clone, Debug, Default)]
+pub struct RandomBrain;
+
+impl AIBrain for RandomBrain {
+    fn name(&self) -> &'static str {
+        "Random"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, rng: &mut dyn RngCore) -> Option<IVec2> {
+        let safe = obs.safe_dirs();
+        let choices = if safe.is_empty() { obs.candidate_dirs() } else { safe };
+        (!choices.is_empty()).then(|| choices[rng.gen_range(0..choices.len())])
+    }
+}
+
+/// 猎手：抢到玩家蛇头前方拦截
+///
+/// 不管自己有没有护盾都会堵截（见 [`AIObservation::intercept_cells`]），
+/// 堵截点优先于食物；玩家无敌时拦截没有意义，退回普通寻路。
+#[derive(Clone, Debug, Default)]
+pub struct HunterBrain {
+    target: Option<IVec2>,
+}
+
+impl AIBrain for HunterBrain {
+    fn name(&self) -> &'static str {
+        "Hunter"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, _rng: &mut dyn RngCore) -> Option<IVec2> {
+        let traps = obs.harmful_traps();
+        let plan = RoutePlan::new(obs, |p| traps.contains(&p));
+        let mut goals = obs.scored_targets(&plan);
+        goals.extend(obs.intercept_cells(&plan).into_iter().map(|p| (p, -INTERCEPT_BONUS)));
+        follow(&plan, goals, &mut self.target)
+    }
+
+    fn target(&self) -> Option<IVec2> {
+        self.target
+    }
+
+    fn tint(&self) -> Option<Color> {
+        Some(Color::new(0.85, 0.12, 0.15, 1.0))
+    }
+}
+
+/// 胆小鬼：离玩家远的东西才吃，玩家无敌时直接逃跑
+#[derive(Clone, Debug, Default)]
+pub struct CowardBrain {
+    target: Option<IVec2>,
+}
+
+impl CowardBrain {
+    /// 保持的安全距离
+    const SAFE_DISTANCE: u32 = 6;
+    /// 目标每靠近玩家一格增加的代价（步数）
+    const FEAR: i32 = 4;
+}
+
+impl AIBrain for CowardBrain {
+    fn name(&self) -> &'static str {
+        "Coward"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, _rng: &mut dyn RngCore) -> Option<IVec2> {
+        let traps = obs.harmful_traps();
+        let plan = RoutePlan::new(obs, |p| traps.contains(&p));
+        let goals = obs.scored_targets(&plan);
+        let Some(danger) = obs.player_distances() else {
+            return follow(&plan, goals, &mut self.target);
+        };
+        let from_player = |p: IVec2| danger.distance(p).unwrap_or(u32::MAX);
+
+        // 逃跑：选离玩家最远的安全方向
+        if obs.player_protected() {
+            self.target = None;
+            return plan
+                .safe_moves()
+                .max_by_key(|&(_, next)| from_player(next))
+                .map(|(dir, _)| dir)
+                .or_else(|| plan.fallback());
+        }
+
+        let goals = goals.into_iter().map(|(p, bias)| {
+            let closeness = Self::SAFE_DISTANCE.saturating_sub(from_player(p)) as i32;
+            (p, bias + closeness * Self::FEAR)
+        });
+        follow(&plan, goals, &mut self.target)
+    }
+
+    fn target(&self) -> Option<IVec2> {
+        self.target
+    }
+
+    fn tint(&self) -> Option<Color> {
+        Some(Color::new(0.95, 0.92, 0.7, 1.0))
+    }
+}
+
+/// 采集者：有护盾也绕开陷阱果实，比其他大脑更看重功能果实
+#[derive(Clone, Debug, Default)]
+pub struct ForagerBrain {
+    target: Option<IVec2>,
+}
+
+impl ForagerBrain {
+    /// 功能果实在基础估值之外额外的优先程度（步数）
+    const POWER_BONUS: i32 = 8;
+}
+
+impl AIBrain for ForagerBrain {
+    fn name(&self) -> &'static str {
+        "Forager"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, _rng: &mut dyn RngCore) -> Option<IVec2> {
+        let traps = obs.fruits_of(FruitCategory::Trap);
+        let plan = RoutePlan::new(obs, |p| traps.contains(&p));
+        let powers = obs.fruits_of(FruitCategory::Power);
+        let goals = obs.scored_targets(&plan).into_iter().filter(|(p, _)| !traps.contains(p)).map(|(p, bias)| {
+            let bonus = if powers.contains(&p) { Self::POWER_BONUS } else { 0 };
+            (p, bias - bonus)
+        });
+        follow(&plan, goals, &mut self.target)
+    }
+
+    fn target(&self) -> Option<IVec2> {
+        self.target
+    }
+
+    fn tint(&self) -> Option<Color> {
+        Some(Color::new(0.15, 0.8, 0.7, 1.0))
+    }
+}
+
+/// 骗子：守在离玩家最近的陷阱果实旁边打转，把玩家引过去
+///
+/// 自己不吃陷阱；场上没有陷阱时像采集者一样吃东西。
+#[derive(Clone, Debug, Default)]
+pub struct TricksterBrain {
+    target: Option<IVec2>,
+}
+
+impl TricksterBrain {
+    /// 陷阱旁的格子相对食物的优先程度（步数）
+    const BAIT_BONUS: i32 = 6;
+}
+
+impl AIBrain for TricksterBrain {
+    fn name(&self) -> &'static str {
+        "Trickster"
+    }
+
+    fn decide(&mut self, obs: &AIObservation, _rng: &mut dyn RngCore) -> Option<IVec2> {
+        let traps = obs.fruits_of(FruitCategory::Trap);
+        let plan = RoutePlan::new(obs, |p| traps.contains(&p));
+        let mut goals: Vec<(IVec2, i32)> = obs
+            .scored_targets(&plan)
+            .into_iter()
+            .filter(|(p, _)| !traps.contains(p))
+            .map(|(p, bias)| (p, bias + Self::BAIT_BONUS))
+            .collect();
+
+        let bait = obs
+            .player_distances()
+            .and_then(|danger| traps.iter().copied().min_by_key(|&t| danger.distance(t).unwrap_or(u32::MAX)));
+        if let Some(trap) = bait {
+            let head = obs.head();
+            for &dir in obs.grid.directions() {
+                if let Some((beside, _)) = obs.grid.step_on(obs.topology, obs.board, trap, dir)
+                    && beside != head
+                {
+                    goals.push((beside, -Self::BAIT_BONUS));
+                }
+            }
+        }
+        follow(&plan, goals, &mut self.target)
+    }
+
+    fn target(&self) -> Option<IVec2> {
+        self.target
+    }
+
+    fn tint(&self) -> Option<Color> {
+        Some(Color::new(0.5, 0.35, 1.0, 1.0))
+    }
+}
+
+/// 内置大脑类型
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum BrainKind {
+    Greedy,
+    #[default]
+    Pathfinding,
+    Random,
+    Hunter,
+    Coward,
+    Forager,
+    Trickster,
+}
+
+impl BrainKind {
+    /// 所有内置大脑
+    pub const ALL: [BrainKind; 7] = [
+        BrainKind::Greedy,
+        BrainKind::Pathfinding,
+        BrainKind::Random,
+        BrainKind::Hunter,
+        BrainKind::Coward,
+        BrainKind::Forager,
+        BrainKind::Trickster,
+    ];
+
+    /// 有固定颜色和鲜明行为的性格
+    pub const PERSONALITIES: [BrainKind; 4] =
+        [BrainKind::Hunter, BrainKind::Coward, BrainKind::Forager, BrainKind::Trickster];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            BrainKind::Greedy => "Greedy",
+            BrainKind::Pathfinding => "Pathfinding",
+            BrainKind::Random => "Random",
+            BrainKind::Hunter => "Hunter",
+            BrainKind::Coward => "Coward",
+            BrainKind::Forager => "Forager",
+            BrainKind::Trickster => "Trickster",
+        }
+    }
+
+    /// 创建对应的大脑
+    pub fn create(&self) -> Box<dyn AIBrain> {
+        match self {
+            BrainKind::Greedy => Box::new(GreedyBrain::default()),
+            BrainKind::Pathfinding => Box::new(PathfindingBrain::default()),
+            BrainKind::Random => Box::new(RandomBrain),
+            BrainKind::Hunter => Box::new(HunterBrain::default()),
+            BrainKind::Coward => Box::new(CowardBrain::default()),
+            BrainKind::Forager => Box::new(ForagerBrain::default()),
+            BrainKind::Trickster => Box::new(TricksterBrain::default()),
+        }
+    }
+
+    /// 按权重随机选择（权重全为 0 时返回默认大脑）
+    pub fn pick<R: Rng>(weights: &[(BrainKind, u32)], rng: &mut R) -> BrainKind {
+        let total: u32 = weights.iter().map(|&(_, w)| w).sum();
+        if total == 0 {
+            return BrainKind::default();
+        }
+        let mut roll = rng.gen_range(0..total);
+        for &(kind, weight) in weights {
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        BrainKind::default()
+    }
+}
+
+/// 各难度下性格的生成权重：简单多胆小鬼和采集者，困难多猎手和骗子
+pub fn personality_weights(difficulty: Difficulty) -> [(BrainKind, u32); 4] {
+    let [hunter, coward, forager, trickster] = match difficulty {
+        Difficulty::Easy => [1, 4, 4, 1],
+        Difficulty::Normal => [1, 1, 1, 1],
+        Difficulty::Hard => [4, 1, 2, 3],
+    };
+    [
+        (BrainKind::Hunter, hunter),
+        (BrainKind::Coward, coward),
+        (BrainKind::Forager, forager),
+        (BrainKind::Trickster, trickster),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::SeedableRng;
+    use crate::constants::{GRID_H, GRID_W};
+    use crate::fruits::create_fruit_registry;
+    use crate::game::{AISnake, GameRng, Team};
+
+    /// 测试场景：空的有墙棋盘，玩家可选
+    struct Scene {
+        body: VecDeque<IVec2>,
+        dir: IVec2,
+        food: IVec2,
+        fruits: Vec<Fruit>,
+        hazards: Vec<IVec2>,
+        player: Vec<IVec2>,
+        player_dir: IVec2,
+        buffs: BuffState,
+        player_buffs: BuffState,
+        portals: Vec<Portal>,
+        teams: TeamBoard,
+        game_time: f32,
+    }
+
+    impl Scene {
+        fn new(body: &[IVec2], dir: IVec2, food: IVec2) -> Self {
+            Scene {
+                body: body.iter().copied().collect(),
+                dir,
+                food,
+                fruits: vec![],
+                hazards: vec![],
+                player: vec![],
+                player_dir: ivec2(1, 0),
+                buffs: BuffState::default(),
+                player_buffs: BuffState::default(),
+                portals: vec![],
+                teams: TeamBoard::default(),
+                game_time: 0.0,
+            }
+        }
+
+        fn decide(&self, brain: &mut dyn AIBrain) -> Option<IVec2> {
+            let occupancy = OccupancyGrid::from_cells(GRID_W, GRID_H, &self.body);
+            let player = OccupancyGrid::from_cells(GRID_W, GRID_H, &self.player);
+            let registry = create_fruit_registry();
+            let obs = AIObservation {
+                id: 1,
+                body: &self.body,
+                occupancy: &occupancy,
+                dir: self.dir,
+                buff_state: &self.buffs,
+                tick: 0.15,
+                game_time: self.game_time,
+                grid: GridKind::Square,
+                topology: Topology::Bounded,
+                board: BoardDims::DEFAULT,
+                food: self.food,
+                fruits: &self.fruits,
+                registry: &registry,
+                player: &player,
+                player_head: self.player.first().copied(),
+                player_tail: self.player.last().copied(),
+                player_dir: self.player_dir,
+                player_buffs: &self.player_buffs,
+                ai_occupancy: &occupancy,
+                hazard_cells: &self.hazards,
+                portals: &self.portals,
+                teams: &self.teams,
+            };
+            let mut rng = GameRng::seed_from_u64(3);
+            brain.decide(&obs, &mut rng)
+        }
+    }
+
+    /// 蛇头 (10,10) 向上，身体向下
+    fn upward_snake() -> Vec<IVec2> {
+        vec![ivec2(10, 10), ivec2(10, 11), ivec2(10, 12)]
+    }
+
+    /// 队伍快照中的一条蛇：(id, 队伍, 身体, 方向, 目标)，场景里的蛇 id 是 1
+    type Member<'a> = (u32, Team, &'a [IVec2], IVec2, Option<IVec2>);
+
+    fn team_board(members: &[Member]) -> TeamBoard {
+        let mut rng = GameRng::seed_from_u64(4);
+        let snakes: Vec<AISnake> = members
+            .iter()
+            .map(|&(id, team, body, dir, target)| {
+                let mut snake = AISnake::new(GridKind::Square, BoardDims::DEFAULT, id, BrainKind::Pathfinding.create(), |_| false, &mut rng).unwrap();
+                snake.body = body.iter().copied().collect();
+                snake.team = Some(team);
+                snake.dir = dir;
+                snake.target = target;
+                snake
+            })
+            .collect();
+        TeamBoard::new(&snakes, false)
+    }
+
+    #[test]
+    fn test_greedy_heads_straight_for_target() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(17, 10));
+        let mut brain = GreedyBrain::default();
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(1, 0)));
+        assert_eq!(brain.target(), Some(ivec2(17, 10)));
+
+        // 目标方向有障碍时换一个安全方向
+        scene.hazards = vec![ivec2(11, 10)];
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(0, -1)));
+    }
+
+    #[test]
+    fn test_random_brain_only_picks_safe_moves() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(0, 0));
+        scene.hazards = vec![ivec2(10, 9), ivec2(9, 10)];
+        assert_eq!(scene.decide(&mut RandomBrain), Some(ivec2(1, 0)));
+    }
+
+    #[test]
+    fn test_scoring_uses_fruit_config_and_own_buffs() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(25, 20));
+        let mut brain = PathfindingBrain::default();
+
+        // 6 步才能到，果实只剩 0.5 秒：放弃，去吃远处的食物
+        scene.game_time = 10.0;
+        scene.fruits = vec![Fruit::new(ivec2(10, 4), "normal", 0.0, 10.5)];
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(25, 20)));
+        scene.fruits[0].lifetime = 20.0;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 4)));
+
+        // 护盾果实比近处的普通果实优先，已有护盾时不再稀罕
+        scene.fruits = vec![Fruit::new(ivec2(14, 10), "normal", 0.0, 0.0), Fruit::new(ivec2(10, 2), "shield", 0.0, 0.0)];
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 2)));
+        scene.buffs.shield_active = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(14, 10)));
+
+        // 陷阱挡在路上时绕开；有护盾时直接穿过
+        scene.fruits = vec![Fruit::new(ivec2(10, 9), "trap", 0.0, 0.0)];
+        scene.food = ivec2(10, 5);
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(0, -1)));
+        scene.buffs.shield_active = false;
+        assert_ne!(scene.decide(&mut brain), Some(ivec2(0, -1)));
+    }
+
+    #[test]
+    fn test_shielded_ai_hunts_and_frozen_player_is_exploited() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(25, 20));
+        scene.player = vec![ivec2(7, 8), ivec2(6, 8), ivec2(5, 8)];
+        let mut brain = PathfindingBrain::default();
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(25, 20)));
+
+        scene.buffs.shield_active = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target().map(|t| t.y), Some(8));
+
+        scene.buffs.shield_active = false;
+        scene.player_buffs.frozen = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 8)));
+    }
+
+    #[test]
+    fn test_yields_contested_targets_unless_player_frozen() {
+        // 食物 6 步，但玩家 2 步就到；左边的果实 8 步
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(10, 4));
+        scene.player = vec![ivec2(12, 4), ivec2(13, 4), ivec2(14, 4)];
+        scene.player_dir = ivec2(-1, 0);
+        scene.fruits = vec![Fruit::new(ivec2(2, 10), "normal", 0.0, 0.0)];
+        let mut brain = PathfindingBrain::default();
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(2, 10)));
+
+        scene.player_buffs.frozen = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 4)));
+
+        // 沙虫附近的目标不去
+        scene.player_buffs.frozen = false;
+        scene.player_buffs.sandworm_active = true;
+        scene.fruits.clear();
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), None);
+    }
+
+    #[test]
+    fn test_paths_through_portals() {
+        // 食物在右下方：不走传送门时先向右绕
+        let food = ivec2(26, 20);
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), food);
+        let mut brain = PathfindingBrain::default();
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(1, 0)));
+
+        // 正上方的传送门直达食物旁边
+        scene.portals = vec![Portal::permanent(ivec2(10, 9), ivec2(25, 20), WHITE)];
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(0, -1)));
+        assert_eq!(brain.target(), Some(food));
+
+        // 出口有障碍时一步之内也不进传送门
+        scene.food = ivec2(10, 4);
+        scene.hazards = vec![ivec2(25, 20)];
+        assert_ne!(scene.decide(&mut GreedyBrain::default()), Some(ivec2(0, -1)));
+    }
+
+    #[test]
+    fn test_sandworm_hunts_player_tail() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(10, 2));
+        scene.player = (15..=18).map(|x| ivec2(x, 10)).collect();
+        scene.buffs.sandworm_active = true;
+        let mut brain = PathfindingBrain::default();
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(1, 0)));
+        assert_eq!(brain.target(), Some(ivec2(18, 10)));
+
+        // 玩家有护盾时吞不掉
+        scene.player_buffs.shield_active = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 2)));
+    }
+
+    #[test]
+    fn test_forager_skips_traps_and_prefers_power() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(10, 2));
+        scene.fruits = vec![Fruit::new(ivec2(10, 9), "trap", 0.0, 0.0), Fruit::new(ivec2(16, 10), "shield", 0.0, 0.0)];
+        let mut brain = ForagerBrain::default();
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(1, 0)));
+        assert_eq!(brain.target(), Some(ivec2(16, 10)));
+
+        // 有护盾时寻路大脑顺手吃掉眼前的陷阱，采集者照样绕开
+        scene.buffs.shield_active = true;
+        let mut plain = PathfindingBrain::default();
+        scene.decide(&mut plain);
+        assert_eq!(plain.target(), Some(ivec2(10, 9)));
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(1, 0)));
+    }
+
+    #[test]
+    fn test_hunter_cuts_off_player_head() {
+        // 玩家在左边向右走，会经过蛇头正上方的格子
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(25, 20));
+        scene.player = vec![ivec2(7, 8), ivec2(6, 8), ivec2(5, 8)];
+        let mut brain = HunterBrain::default();
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(0, -1)));
+        assert_eq!(brain.target().map(|t| t.y), Some(8));
+
+        // 玩家无敌时只管吃
+        scene.player_buffs.shield_active = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(25, 20)));
+    }
+
+    #[test]
+    fn test_coward_avoids_and_flees_player() {
+        // 食物就在玩家旁边：不去，改吃远处的果实
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(10, 6));
+        scene.player = vec![ivec2(10, 5), ivec2(11, 5), ivec2(12, 5)];
+        scene.player_dir = ivec2(-1, 0);
+        scene.fruits = vec![Fruit::new(ivec2(10, 20), "normal", 0.0, 0.0)];
+        let mut brain = CowardBrain::default();
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 20)));
+
+        // 玩家有护盾：背离玩家逃跑
+        scene.player_buffs.shield_active = true;
+        let dir = scene.decide(&mut brain).unwrap();
+        assert_ne!(dir, ivec2(0, -1));
+        assert_eq!(brain.target(), None);
+    }
+
+    #[test]
+    fn test_trickster_waits_beside_trap_near_player() {
+        let mut scene = Scene::new(&upward_snake(), ivec2(0, -1), ivec2(10, 2));
+        scene.player = vec![ivec2(20, 12), ivec2(21, 12), ivec2(22, 12)];
+        scene.fruits = vec![Fruit::new(ivec2(16, 12), "trap", 0.0, 0.0), Fruit::new(ivec2(2, 2), "slow", 0.0, 0.0)];
+        let mut brain = TricksterBrain::default();
+        scene.decide(&mut brain);
+        let target = brain.target().unwrap();
+        assert_eq!((target - ivec2(16, 12)).abs().element_sum(), 1);
+    }
+
+    #[test]
+    fn test_teammates_split_targets() {
+        // 食物 5 步，果实 7 步；队友已经在追食物时改去吃果实
+        let body = upward_snake();
+        let mut scene = Scene::new(&body, ivec2(0, -1), ivec2(10, 5));
+        scene.fruits = vec![Fruit::new(ivec2(17, 10), "normal", 0.0, 0.0)];
+        let mut brain = PathfindingBrain::default();
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 5)));
+
+        let mate = [ivec2(14, 3), ivec2(15, 3)];
+        scene.teams = team_board(&[(1, Team::Red, &body, ivec2(0, -1), None), (2, Team::Red, &mate, ivec2(-1, 0), Some(ivec2(10, 5)))]);
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(17, 10)));
+
+        // 敌队在追同一个目标不影响
+        scene.teams = team_board(&[(1, Team::Red, &body, ivec2(0, -1), None), (2, Team::Blue, &mate, ivec2(-1, 0), Some(ivec2(10, 5)))]);
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(10, 5)));
+    }
+
+    #[test]
+    fn test_squad_surrounds_player_from_different_sides() {
+        let body = upward_snake();
+        let mate = [ivec2(3, 20), ivec2(2, 20)];
+        let mut scene = Scene::new(&body, ivec2(0, -1), ivec2(2, 22));
+        scene.player = vec![ivec2(18, 10), ivec2(17, 10), ivec2(16, 10)];
+        let mut brain = PathfindingBrain::default();
+
+        // 没有队友时不包围
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(2, 22)));
+
+        let mut slots = vec![];
+        for order in [[1, 2], [2, 1]] {
+            let members: Vec<_> = order
+                .iter()
+                .map(|&id| if id == 1 { (1, Team::Red, &body[..], ivec2(0, -1), None) } else { (2, Team::Red, &mate[..], ivec2(1, 0), None) })
+                .collect();
+            scene.teams = team_board(&members);
+            scene.decide(&mut brain);
+            let slot = brain.target().unwrap();
+            assert_eq!(GridKind::Square.distance(slot, ivec2(18, 10)), 2);
+            slots.push(slot);
+        }
+        assert_ne!(slots[0], slots[1]);
+
+        // 玩家有护盾时散开
+        scene.player_buffs.shield_active = true;
+        scene.decide(&mut brain);
+        assert_eq!(brain.target(), Some(ivec2(2, 22)));
+    }
+
+    #[test]
+    fn test_ally_escorts_player_and_cuts_off_enemies() {
+        let body = upward_snake();
+        let mut scene = Scene::new(&body, ivec2(0, -1), ivec2(2, 22));
+        scene.player = vec![ivec2(25, 18), ivec2(26, 18), ivec2(27, 18)];
+        let enemy = [ivec2(3, 2), ivec2(2, 2)];
+        scene.teams = team_board(&[(1, Team::Player, &body, ivec2(0, -1), None), (2, Team::Red, &enemy, ivec2(1, 0), None)]);
+        let mut brain = HunterBrain::default();
+
+        // 离玩家太远：去玩家尾巴旁边，不堵截玩家
+        scene.decide(&mut brain);
+        assert_eq!(GridKind::Square.distance(brain.target().unwrap(), ivec2(27, 18)), 1);
+
+        // 敌人从左边横穿蛇头前方：抢先堵住
+        scene.player = vec![ivec2(13, 13), ivec2(12, 13), ivec2(11, 13)];
+        let enemy = [ivec2(6, 6), ivec2(5, 6)];
+        scene.teams = team_board(&[(1, Team::Player, &body, ivec2(0, -1), None), (2, Team::Red, &enemy, ivec2(1, 0), None)]);
+        assert_eq!(scene.decide(&mut brain), Some(ivec2(0, -1)));
+        assert_eq!(brain.target(), Some(ivec2(10, 6)));
+    }
+
+    #[test]
+    fn test_personality_weights_follow_difficulty() {
+        let share = |difficulty, kind| {
+            let weights = personality_weights(difficulty);
+            let total: u32 = weights.iter().map(|&(_, w)| w).sum();
+            weights.iter().find(|&&(k, _)| k == kind).unwrap().1 as f32 / total as f32
+        };
+        assert!(share(Difficulty::Hard, BrainKind::Hunter) > share(Difficulty::Easy, BrainKind::Hunter));
+        assert!(share(Difficulty::Easy, BrainKind::Coward) > share(Difficulty::Hard, BrainKind::Coward));
+
+        let mut rng = GameRng::seed_from_u64(9);
+        let picks: Vec<BrainKind> = (0..200).map(|_| BrainKind::pick(&personality_weights(Difficulty::Hard), &mut rng)).collect();
+        for kind in BrainKind::PERSONALITIES {
+            assert!(picks.contains(&kind));
+        }
+        assert_eq!(BrainKind::pick(&[], &mut rng), BrainKind::default());
+    }
+
+    #[test]
+    fn test_brain_kinds_create_named_brains() {
+        for kind in BrainKind::ALL {
+            let brain = kind.create();
+            assert_eq!(brain.name(), kind.name());
+            assert_eq!(brain.tint().is_some(), BrainKind::PERSONALITIES.contains(&kind));
+        }
+    }
+}
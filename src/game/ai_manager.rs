@@ -1,12 +1,61 @@
 //! AI 蛇管理器模块
 //!
 //! 管理所有 AI 蛇的生成、更新、碰撞检测和死亡处理。
+//!
+//! 所有 AI 蛇共享一张占用网格，随每条蛇的移动、增长、截断和死亡增量更新，
+//! 碰撞检测不再需要每帧复制所有蛇身。
+//!
+//! 决策用 rayon 并行：所有蛇读同一份只读快照，各用一个预先分好的种子，
+//! 然后顺序应用决策（见 [`AIManager::update_thinking`]），大量 AI 蛇时按核数扩展且结果可复现。
+//!
+//! 蛇与蛇相撞按 [`CombatRules`] 结算：经典规则下撞到别的蛇就死，
+//! 切割规则下撞到蛇身会把它切断（见 [`super::combat`]）。
+//!
+//! AI 蛇吃到沙虫果实后原地变身（见 [`update_ai_sandworm`]），横冲阶段撞上玩家会吞掉玩家的尾巴
+//! （玩家有护盾/幽灵/沙虫时除外，撞到蛇头玩家死亡），撞上其他 AI 蛇会把它整条吞掉。
+//!
+//! 按 [`TeamMode`] 编排时每条 AI 蛇属于一支队伍（见 [`super::team`]），关闭误伤时队友之间、
+//! 玩家和盟友之间互相穿过；盟友撞死的敌人算玩家的击杀。队员死亡、全队覆灭和包围玩家
+//! 都作为 [`TeamEvent`] 随 [`AIUpdateResult`] 报告。
+//!
+//! 宠物蛇（[`AISnake::pet`]）是玩家一方的 AI 蛇，从玩家体内孵化的宠物蛋里出生：
+//! 吃到的东西给玩家加分，像盟友一样挡住敌对 AI 蛇，玩家撞上它时它死亡（见 [`AIManager::crush_pet`]）。
+//!
+//! 本地多人时每名玩家都是目标：决策时盯住最近的玩家，所有玩家的身体都算阻挡；
+//! 移动时撞上哪名玩家就按那名玩家的 Buff 结算，结果按玩家分别报告（[`PlayerReport`]）。
+//!
+//! Boss（[`AISnake::boss`]）也是一条 AI 蛇，移动和决策照常，碰撞另算：穿过墙和其他蛇，碾过玩家，
+//! 只有蛇头是弱点（见 [`super::boss`]）。阶段动作在每帧移动前执行，事件作为 [`BossEvent`] 报告。
 
 use macroquad::prelude::*;
-use ::rand::Rng;
-use crate::types::{Fruit, Particle};
+use ::rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use crate::types::{BoardDims, CombatRules, Difficulty, Fruit, Grid, GridKind, Particle, Portal, TeamMode, Topology};
 use crate::fruits::{FruitRegistry, FruitCategory};
-use super::ai_snake::{AISnake, AIMoveResult};
+use super::ai_snake::{AIDecision, AISnake, AIMoveResult, AIWorld, PlayerView};
+use super::ai_brain::{personality_weights, AIBrain, BrainKind};
+use super::ai_progress::{XP_DROPPED, XP_FOOD, XP_FRUIT};
+use super::combat::{HeadOn, Strike, StrikeOutcome};
+use super::boss::{BOSS_BRAIN, BOSS_HEAD_DAMAGE, BOSS_HEALTH, BOSS_MIN_HEALTH, BOSS_MINIONS, BOSS_REWARD, BOSS_STUN};
+use super::{
+    BossEvent, BossPhase, BossState, FreeCellIndex, GameRng, HazardManager, OccupancyGrid, Snake, Team, TeamBoard,
+    TeamEvent, find_portal_exit, update_ai_sandworm,
+};
+
+/// 并行决策时每个任务至少处理的蛇数（蛇少时不值得分给多个线程）
+const THINK_CHUNK: usize = 4;
+
+/// 沙虫吞噬时的粒子颜色
+const SAND: Color = Color::new(0.8, 0.6, 0.2, 1.0);
+
+/// 玩家最多同时拥有的盟友数（不含宠物蛇）
+const MAX_ALLIES: usize = 2;
+
+/// 玩家最多同时拥有的宠物蛇数
+const MAX_PETS: usize = 2;
+
+/// 宠物蛇颜色
+const PET_COLOR: Color = Color::new(1.0, 0.7, 0.85, 1.0);
 
 /// 掉落的食物（AI蛇死亡后产生）
 #[derive(Clone)]
@@ -20,12 +69,32 @@ pub struct DroppedFood {
 pub struct AIManager {
     /// 所有 AI 蛇
     pub snakes: Vec<AISnake>,
-    /// 最大 AI 蛇数量
+    /// 最大 AI 蛇数量（不含玩家的盟友）
     pub max_snakes: usize,
     /// 下一个 AI 蛇 ID
     next_id: u32,
     /// 掉落的食物列表
     pub dropped_foods: Vec<DroppedFood>,
+    /// 新生成的 AI 蛇使用的棋盘几何
+    pub grid: GridKind,
+    /// 棋盘范围（见 [`set_board`](Self::set_board)）
+    board: BoardDims,
+    /// 所有 AI 蛇身体的共享占用网格，同时维护生成用的空闲格子索引（见 [`free_cells`](Self::free_cells)）
+    pub occupancy: OccupancyGrid,
+    /// 自动生成的 AI 蛇按权重随机选择大脑（见 [`personality_weights`]）
+    pub brain_weights: Vec<(BrainKind, u32)>,
+    /// 蛇与蛇相撞的规则
+    pub combat: CombatRules,
+    /// 新生成的 AI 蛇如何分队
+    pub teams: TeamMode,
+    /// 队友之间（以及玩家和盟友之间）相撞是否按敌人结算
+    pub friendly_fire: bool,
+    /// 还没报告的队伍事件（移除蛇的地方都会产生）
+    team_events: Vec<TeamEvent>,
+    /// 当前把玩家包围住的队伍（只在开始包围时报告一次）
+    surrounded_by: Option<Team>,
+    /// 还没报告的 Boss 事件
+    boss_events: Vec<BossEvent>,
 }
 
 impl Default for AIManager {
@@ -35,12 +104,28 @@ impl Default for AIManager {
 }
 
 impl AIManager {
+    /// 游戏棋盘（[`BoardDims::DEFAULT`]）上的管理器
     pub fn new() -> Self {
+        Self::with_board(BoardDims::DEFAULT)
+    }
+
+    /// 在 `board` 大小的棋盘上管理 AI 蛇（蜂群模式用更大的棋盘，见 [`super::swarm`]）
+    pub fn with_board(board: BoardDims) -> Self {
         AIManager {
             snakes: Vec::new(),
             max_snakes: 3,
             next_id: 1,
             dropped_foods: Vec::new(),
+            grid: GridKind::Square,
+            board,
+            occupancy: OccupancyGrid::with_free_cells(board.end().x, board.end().y),
+            brain_weights: personality_weights(Difficulty::Normal).to_vec(),
+            combat: CombatRules::default(),
+            teams: TeamMode::default(),
+            friendly_fire: false,
+            team_events: Vec::new(),
+            surrounded_by: None,
+            boss_events: Vec::new(),
         }
     }
     
@@ -49,25 +134,330 @@ impl AIManager {
         self.snakes.clear();
         self.dropped_foods.clear();
         self.next_id = 1;
+        self.occupancy.clear();
+        self.team_events.clear();
+        self.surrounded_by = None;
+        self.boss_events.clear();
+    }
+
+    /// 棋盘范围
+    pub fn board(&self) -> BoardDims {
+        self.board
+    }
+
+    /// 换到 `board` 上（缩小的棋盘）：之后生成的蛇都在这块区域内
+    ///
+    /// 共享占用网格按绝对坐标覆盖到棋盘右下角，尺寸变化时重建并清空固定阻挡；
+    /// 棋盘外的格子由调用方随后设为固定阻挡（见 [`set_blocked_cells`](Self::set_blocked_cells)）
+    pub fn set_board(&mut self, board: BoardDims) {
+        if board.end() != self.board.end() {
+            self.occupancy = OccupancyGrid::with_free_cells(board.end().x, board.end().y);
+            self.rebuild_occupancy();
+        }
+        self.board = board;
+        for snake in &mut self.snakes {
+            snake.board = board;
+        }
+    }
+
+    /// 设置生成蛇和食物时避开的固定格子（障碍物轨迹、缩小棋盘的围栏）
+    pub fn set_blocked_cells(&mut self, cells: &[IVec2]) {
+        self.occupancy.set_fixed(cells);
+    }
+
+    /// 格子是否固定阻挡（障碍物轨迹、缩小棋盘的围栏）
+    pub fn is_blocked_cell(&self, pos: IVec2) -> bool {
+        self.occupancy.is_fixed(pos)
+    }
+
+    /// 不是固定阻挡的格子数（玩家填满这些格子就通关）
+    pub fn open_cells(&self) -> usize {
+        self.occupancy.open_cells()
+    }
+
+    /// 空闲格子索引：没有 AI 蛇身、也不是固定阻挡的格子
+    ///
+    /// 随共享占用网格的蛇头/蛇尾变化同步更新，食物、果实和传送门都从这里采样
+    pub fn free_cells(&self) -> &FreeCellIndex {
+        self.occupancy.free_cells().expect("shared occupancy tracks free cells")
+    }
+
+    /// 按当前所有 AI 蛇身重建共享占用网格
+    pub fn rebuild_occupancy(&mut self) {
+        self.occupancy.rebuild(self.snakes.iter().flat_map(|s| s.body.iter()));
+    }
+
+    /// 移除一条 AI 蛇，同时从共享网格中减去它的身体，并记下队伍事件
+    fn remove_snake(&mut self, idx: usize) -> AISnake {
+        let snake = self.snakes.remove(idx);
+        for &pos in &snake.body {
+            self.occupancy.remove(pos);
+        }
+        if let Some(team) = snake.team {
+            self.team_events.push(TeamEvent::MemberLost { team, id: snake.id });
+            if self.members(team).next().is_none() {
+                self.team_events.push(TeamEvent::Eliminated(team));
+            }
+        }
+        snake
+    }
+
+    /// 某支队伍的所有队员
+    pub fn members(&self, team: Team) -> impl Iterator<Item = &AISnake> + '_ {
+        self.snakes.iter().filter(move |s| s.team == Some(team))
+    }
+
+    /// 某条蛇所属的队伍
+    pub fn team_of(&self, id: u32) -> Option<Team> {
+        self.snakes.iter().find(|s| s.id == id).and_then(|s| s.team)
+    }
+
+    /// 某格上有没有会撞死玩家的 AI 蛇（关闭误伤时盟友可以穿过，宠物蛇总是被撞死，Boss 的蛇头是弱点）
+    pub fn blocks_player(&self, pos: IVec2) -> bool {
+        self.occupancy.is_occupied(pos)
+            && self.snakes.iter().any(|s| {
+                harms_player(s, self.friendly_fire)
+                    && s.occupancy.is_occupied(pos)
+                    && !(s.boss.is_some() && s.head() == pos)
+            })
+    }
+
+    /// 当前的 Boss
+    pub fn boss(&self) -> Option<&AISnake> {
+        self.snakes.iter().find(|s| s.boss.is_some())
+    }
+
+    /// 新生成的敌对 AI 蛇加入的队伍：两队编排时补人少的一队
+    fn next_team(&self) -> Option<Team> {
+        match self.teams {
+            TeamMode::Off => None,
+            TeamMode::Squads if self.members(Team::Blue).count() < self.members(Team::Red).count() => Some(Team::Blue),
+            TeamMode::Squads | TeamMode::Allies => Some(Team::Red),
+        }
     }
     
-    /// 生成新的 AI 蛇
+    /// 按难度设置自动生成的性格权重
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.brain_weights = personality_weights(difficulty).to_vec();
+    }
+
+    /// 生成按权重随机选择大脑的 AI 蛇
+    pub fn spawn_default_snake<R: Rng>(&mut self, player_body: &[IVec2], rng: &mut R) -> bool {
+        let kind = BrainKind::pick(&self.brain_weights, rng);
+        self.spawn_snake(player_body, || kind.create(), rng)
+    }
+
+    /// 生成指定大脑类型的 AI 蛇
+    pub fn spawn_snake_of<R: Rng>(&mut self, kind: BrainKind, player_body: &[IVec2], rng: &mut R) -> bool {
+        self.spawn_snake(player_body, || kind.create(), rng)
+    }
+
+    /// 生成新的 AI 蛇，`make_brain` 只在确实生成时调用
+    ///
+    /// 有队伍编排时自动分队，蛇身使用队伍颜色
     pub fn spawn_snake<R: Rng>(
         &mut self,
         player_body: &[IVec2],
+        make_brain: impl FnOnce() -> Box<dyn AIBrain>,
         rng: &mut R,
     ) -> bool {
-        if self.snakes.len() >= self.max_snakes {
+        let hostile = self.snakes.iter().filter(|s| s.team != Some(Team::Player) && s.boss.is_none()).count();
+        if hostile >= self.max_snakes {
             return false;
         }
-        
-        // 收集所有已占用位置
-        let mut occupied: Vec<IVec2> = player_body.to_vec();
-        for snake in &self.snakes {
-            occupied.extend(&snake.body);
+        self.spawn_in(self.next_team(), player_body, make_brain, rng)
+    }
+
+    /// 孵化一条玩家的盟友（只在盟友编排下，最多 [`MAX_ALLIES`] 条，不占 AI 蛇名额）
+    pub fn spawn_ally<R: Rng>(&mut self, player_body: &[IVec2], rng: &mut R) -> bool {
+        if self.teams != TeamMode::Allies || self.members(Team::Player).filter(|s| !s.pet).count() >= MAX_ALLIES {
+            return false;
+        }
+        self.spawn_in(Some(Team::Player), player_body, || BrainKind::Pathfinding.create(), rng)
+    }
+
+    /// 在第 `owner` 名玩家的蛇尾旁孵化一条宠物蛇（最多 [`MAX_PETS`] 条，不占 AI 蛇名额）
+    ///
+    /// 宠物蛇从蛇尾的空邻格出生，优先朝远离蛇身的方向；蛇尾四周都被占住时孵化失败
+    pub fn spawn_pet(&mut self, owner: usize, player: &Snake, topology: Topology) -> bool {
+        if self.snakes.iter().filter(|s| s.pet).count() >= MAX_PETS || player.is_empty() {
+            return false;
+        }
+        let tail = player.tail();
+        let directions = self.grid.directions();
+        let away = player
+            .len()
+            .checked_sub(2)
+            .and_then(|i| directions.iter().copied().find(|&d| self.grid.neighbor(player.body[i], d) == tail));
+        let free = |p: IVec2| self.free_cells().is_free(p) && !player.occupies(p);
+        let hatched = away
+            .into_iter()
+            .chain(directions.iter().copied())
+            .filter_map(|dir| self.grid.step_on(topology, self.board(), tail, dir))
+            .find(|&(pos, _)| free(pos));
+        let Some((head, dir)) = hatched else { return false };
+
+        let brain = BrainKind::Pathfinding.create();
+        let mut snake = AISnake::hatch(self.grid, self.board(), self.next_id, brain, head, dir, PET_COLOR);
+        snake.team = Some(Team::Player);
+        snake.pet = true;
+        snake.owner = owner;
+        self.occupancy.add(head);
+        self.next_id += 1;
+        self.snakes.push(snake);
+        true
+    }
+
+    /// Boss 登场（同时只有一条，不占 AI 蛇名额）
+    ///
+    /// Boss 从地下钻出：先生成一小段，其余的节数随移动长出来
+    pub fn spawn_boss<R: Rng>(&mut self, player_body: &[IVec2], rng: &mut R) -> bool {
+        if self.boss().is_some() || !self.spawn_in(self.next_team(), player_body, || BOSS_BRAIN.create(), rng) {
+            return false;
         }
+        let boss = self.snakes.last_mut().expect("just spawned");
+        boss.grow_pending = BOSS_HEALTH.saturating_sub(boss.len()) as u32;
+        boss.color = SAND;
+        boss.boss = Some(BossState::new());
+        self.boss_events.push(BossEvent::Appeared);
+        true
+    }
+
+    /// 玩家蛇头撞上 Boss 的蛇头（弱点）：Boss 扣血并僵直，玩家不受影响
+    ///
+    /// 僵直期间再撞不扣血；Boss 钻在地下时撞不到
+    pub fn hit_boss_head<R: Rng>(&mut self, player_head: IVec2, particles: &mut Vec<Particle>, rng: &mut R) -> bool {
+        let Some(idx) = self.snakes.iter().position(|s| s.boss.is_some() && !s.burrowed() && s.head() == player_head)
+        else {
+            return false;
+        };
+        self.damage_boss(idx, BOSS_HEAD_DAMAGE, particles, rng);
+        true
+    }
+
+    /// Boss 弱点被撞中：先扣还没长出来的节数，再从蛇尾截掉
+    fn damage_boss<R: Rng>(&mut self, idx: usize, damage: usize, particles: &mut Vec<Particle>, rng: &mut R) {
+        let snake = &mut self.snakes[idx];
+        if snake.buff_state.frozen {
+            return;
+        }
+        snake.buff_state.frozen = true;
+        snake.buff_state.freeze_timer = BOSS_STUN;
+        let pending = (snake.grow_pending as usize).min(damage);
+        snake.grow_pending -= pending as u32;
+        let keep = snake.len().saturating_sub(damage - pending).max(1);
+        for pos in snake.truncate(keep) {
+            self.occupancy.remove(pos);
+            spawn_cut_particles(pos, SAND, particles, rng);
+        }
+        spawn_cut_particles(self.snakes[idx].head(), GOLD, particles, rng);
+        self.boss_events.push(BossEvent::Hit { health: self.snakes[idx].health() });
+    }
+
+    /// Boss 每帧的阶段动作（在移动前执行）
+    ///
+    /// 血量耗尽时被击败：整条身体变成掉落食物
+    #[allow(clippy::too_many_arguments)]
+    fn update_boss<R: Rng>(
+        &mut self,
+        players: &[PlayerView],
+        food: IVec2,
+        fruits: &mut Vec<Fruit>,
+        registry: &FruitRegistry,
+        particles: &mut Vec<Particle>,
+        game_time: f32,
+        dt: f32,
+        rng: &mut R,
+    ) {
+        let Some(idx) = self.snakes.iter().position(|s| s.boss.is_some()) else { return };
+        if self.snakes[idx].health() < BOSS_MIN_HEALTH {
+            let dead = self.remove_snake(idx);
+            for &pos in &dead.body {
+                spawn_cut_particles(pos, SAND, particles, rng);
+            }
+            self.drop_food(&dead.body.iter().copied().collect::<Vec<_>>(), game_time);
+            self.boss_events.push(BossEvent::Defeated { reward: BOSS_REWARD });
+            return;
+        }
+
+        let alive: Vec<u32> = self.snakes.iter().map(|s| s.id).collect();
+        let snake = &mut self.snakes[idx];
+        let tail = *snake.body.back().expect("boss has a body");
+        let team = snake.team;
+        // 刀枪不入：一直带着护盾（穿过墙、自己和其他蛇，免疫陷阱）
+        snake.buff_state.activate_shield();
+        let boss = snake.boss.as_mut().expect("found above");
+        let entered = boss.update(dt);
+        let lay_trap = boss.trap_due(dt);
+        boss.minions.retain(|id| alive.contains(id));
+        let missing = BOSS_MINIONS.saturating_sub(boss.minions.len());
+
+        match entered {
+            Some(BossPhase::Burrow) => {
+                let buff = &mut self.snakes[idx].buff_state;
+                buff.ghost_active = true;
+                buff.ghost_timer = BossPhase::Burrow.duration();
+            }
+            Some(BossPhase::Summon) => {
+                let player_body: Vec<IVec2> = players.iter().flat_map(|p| p.snake.body.iter().copied()).collect();
+                for _ in 0..missing {
+                    if self.spawn_in(team, &player_body, || BrainKind::Greedy.create(), rng) {
+                        let id = self.next_id - 1;
+                        if let Some(boss) = self.snakes[idx].boss.as_mut() {
+                            boss.minions.push(id);
+                        }
+                    }
+                }
+            }
+            Some(_) => self.snakes[idx].buff_state.ghost_active = false,
+            None => {}
+        }
+        if let Some(phase) = entered {
+            self.boss_events.push(BossEvent::Phase(phase));
+        }
+
+        // 布雷：蛇尾留下陷阱果实（格子上已经有食物或果实时跳过）
+        if lay_trap && tail != food && !fruits.iter().any(|f| f.pos == tail)
+            && let Some(config) = registry.get_config("trap")
+        {
+            fruits.push(Fruit::new(tail, config.id, game_time, config.lifetime));
+        }
+    }
+
+    /// 玩家蛇头撞上宠物蛇：宠物蛇死亡（不掉落食物），玩家不受影响
+    pub fn crush_pet<R: Rng>(&mut self, player_head: IVec2, particles: &mut Vec<Particle>, rng: &mut R) -> bool {
+        let Some(idx) = self.snakes.iter().position(|s| s.pet && s.occupancy.is_occupied(player_head)) else {
+            return false;
+        };
+        let pet = self.remove_snake(idx);
+        for &pos in &pet.body {
+            spawn_cut_particles(pos, pet.color, particles, rng);
+        }
+        true
+    }
+
+    /// 生成一条属于 `team` 的 AI 蛇（不检查数量上限）
+    fn spawn_in<R: Rng>(
+        &mut self,
+        team: Option<Team>,
+        player_body: &[IVec2],
+        make_brain: impl FnOnce() -> Box<dyn AIBrain>,
+        rng: &mut R,
+    ) -> bool {
+        // 避开玩家、所有 AI 蛇和固定阻挡格子
+        let free = self.free_cells();
+        let board = self.board();
+        let player = OccupancyGrid::from_cells(board.end().x, board.end().y, player_body);
+        let occupied = |p: IVec2| !free.is_free(p) || player.is_occupied(p);
         
-        if let Some(snake) = AISnake::new(self.next_id, &occupied, rng) {
+        if let Some(mut snake) = AISnake::new(self.grid, board, self.next_id, make_brain(), occupied, rng) {
+            snake.team = team;
+            if let Some(team) = team {
+                snake.color = team.color();
+            }
+            for &pos in &snake.body {
+                self.occupancy.add(pos);
+            }
             self.next_id += 1;
             self.snakes.push(snake);
             true
@@ -77,66 +467,140 @@ impl AIManager {
     }
     
     /// 更新所有 AI 蛇的决策
+    ///
+    /// 分三步：
+    ///
+    /// 1. 顺序推进决策计时器，挑出这一帧要决策的蛇，按蛇的顺序从 `rng` 各取一个种子
+    /// 2. 并行决策：每条蛇用自己的种子，只读同一份世界快照（[`AIWorld`]，含队伍快照 [`TeamBoard`]），
+    ///    按自己的移动间隔预测障碍物下一个 tick 的位置
+    /// 3. 顺序应用决策
+    ///
+    /// 随机数只在第 1 步顺序消耗，所以结果与线程数和调度无关，同一种子总是得到同样的决策
+    #[allow(clippy::too_many_arguments)]
     pub fn update_thinking<R: Rng>(
         &mut self,
         food: IVec2,
         fruits: &[Fruit],
-        player_body: &[IVec2],
-        wrap: bool,
+        registry: &FruitRegistry,
+        players: &[PlayerView],
+        hazards: &HazardManager,
+        portals: &[Portal],
+        topology: Topology,
+        game_time: f32,
         dt: f32,
         rng: &mut R,
     ) {
-        // 收集其他 AI 蛇的身体位置
-        let bodies: Vec<Vec<IVec2>> = self.snakes.iter()
-            .map(|s| s.body.clone())
+        // 每隔一段时间做一次决策（等级越高间隔越短）
+        let seeds: Vec<Option<u64>> = self
+            .snakes
+            .iter_mut()
+            .map(|snake| {
+                snake.think_timer += dt;
+                (snake.think_timer >= snake.progress.think_interval()).then(|| {
+                    snake.think_timer = 0.0;
+                    rng.next_u64()
+                })
+            })
             .collect();
-        
-        for (i, snake) in self.snakes.iter_mut().enumerate() {
-            snake.think_timer += dt;
-            
-            // 每 0.2 秒做一次决策
-            if snake.think_timer >= 0.2 {
-                snake.think_timer = 0.0;
-                
-                // 排除自己的身体
-                let other_bodies: Vec<Vec<IVec2>> = bodies.iter()
-                    .enumerate()
-                    .filter(|(j, _)| *j != i)
-                    .map(|(_, b)| b.clone())
-                    .collect();
-                
-                snake.think(food, fruits, player_body, &other_bodies, wrap, rng);
+
+        let teams = TeamBoard::new(&self.snakes, self.friendly_fire);
+        // 只有一名玩家时直接用它的占用网格
+        let combined;
+        let player_cells = match players {
+            [only] => &only.snake.occupancy,
+            _ => {
+                let cells = players.iter().flat_map(|p| p.snake.body.iter());
+                combined = OccupancyGrid::from_cells(self.occupancy.width(), self.occupancy.height(), cells);
+                &combined
             }
+        };
+        let world = AIWorld {
+            food,
+            fruits,
+            registry,
+            players,
+            player_cells,
+            ai_occupancy: &self.occupancy,
+            portals,
+            teams: &teams,
+            topology,
+            game_time,
+        };
+        let decisions: Vec<(usize, AIDecision)> = self
+            .snakes
+            .par_iter_mut()
+            .with_min_len(THINK_CHUNK)
+            .enumerate()
+            .filter_map(|(i, snake)| {
+                let mut rng = GameRng::seed_from_u64(seeds[i]?);
+                let hazard_cells = hazards.predict_cells(game_time, snake.get_tick());
+                snake.decide(&world, &hazard_cells, &mut rng).map(|d| (i, d))
+            })
+            .collect();
+
+        for (i, decision) in decisions {
+            self.snakes[i].apply(decision);
         }
     }
     
     /// 移动所有 AI 蛇并处理碰撞
+    ///
+    /// 玩家有护盾/幽灵/沙虫时撞不死；幽灵状态下 AI 蛇直接穿过玩家身体。
+    /// 结果里每名玩家一份 [`PlayerReport`]，顺序与 `players` 相同
+    #[allow(clippy::too_many_arguments)]
     pub fn update_movement<R: Rng>(
         &mut self,
         food: &mut IVec2,
         fruits: &mut Vec<Fruit>,
-        player_body: &[IVec2],
-        player_can_pass_through: bool,
-        player_is_ghost: bool,  // 幽灵状态：AI蛇可以穿过玩家身体
+        players: &[PlayerView],
+        hazards: &HazardManager,
+        portals: &[Portal],
         particles: &mut Vec<Particle>,
         registry: &FruitRegistry,
-        wrap: bool,
+        topology: Topology,
         game_time: f32,
         dt: f32,
         rng: &mut R,
     ) -> AIUpdateResult {
-        let mut result = AIUpdateResult::default();
+        let mut result = AIUpdateResult { players: vec![PlayerReport::default(); players.len()], ..Default::default() };
+        self.update_boss(players, *food, fruits, registry, particles, game_time, dt, rng);
         let mut dead_indices: Vec<usize> = Vec::new();
         let mut need_respawn_food = false;
+        // 切割规则下撞上其他 AI 蛇的 (撞击者下标, 撞击位置)，全部移动完再结算
+        let mut ai_strikes: Vec<(usize, IVec2)> = Vec::new();
+        // 沙虫撞上其他 AI 蛇的 (沙虫下标, 撞击位置)，被撞的蛇整条吞掉
+        let mut sandworm_strikes: Vec<(usize, IVec2)> = Vec::new();
+        // 蛇头撞上玩家蛇头的 Boss（弱点被撞中）
+        let mut boss_head_hits: Vec<usize> = Vec::new();
+        let cutting = self.combat == CombatRules::Cutting;
+        let friendly_fire = self.friendly_fire;
         
-        // 收集所有 AI 蛇的身体位置（用于互相碰撞检测）
-        let bodies: Vec<Vec<IVec2>> = self.snakes.iter()
-            .map(|s| s.body.clone())
-            .collect();
-        
-        for (i, snake) in self.snakes.iter_mut().enumerate() {
+        for i in 0..self.snakes.len() {
+            let (before, rest) = self.snakes.split_at_mut(i);
+            let Some((snake, after)) = rest.split_first_mut() else { break };
+            let team = snake.team;
+            // 某格上有没有会撞死自己的其他 AI 蛇（关闭误伤时队友可以穿过，钻在地下的 Boss 碰不到）
+            let hostile_at = |p: IVec2| {
+                before.iter().chain(after.iter()).any(|o| {
+                    o.occupancy.is_occupied(p) && !o.burrowed() && (friendly_fire || !Team::allied(o.team, team))
+                })
+            };
+            let spares_player = spares_player(team, friendly_fire);
+
             // 更新 Buff 状态
             snake.buff_state.update(dt);
+
+            // 存活时间也算经验
+            if snake.progress.age_by(dt) {
+                spawn_level_up_particles(snake, particles, rng);
+            }
+
+            // 沙虫变身，结束时化作一团沙尘
+            if snake.buff_state.sandworm_active && update_ai_sandworm(&mut snake.buff_state, snake.body.len(), dt) {
+                for &pos in &snake.body {
+                    spawn_cut_particles(pos, SAND, particles, rng);
+                }
+            }
             
             // 累积时间
             snake.move_accumulator += dt;
@@ -148,35 +612,140 @@ impl AIManager {
             }
             snake.move_accumulator -= tick;
             
-            // 移动
-            let move_result = snake.move_forward(wrap);
+            // 斜向挤过时两侧的蛇身：自己、玩家（幽灵状态和被放过的不算）或其他 AI 蛇（八方向模式）
+            let occupancy = &self.occupancy;
+            let others = |p: IVec2| {
+                players.iter().any(|h| h.snake.occupies(p) && !h.buffs.ghost_active && !spares_player)
+                    || (occupancy.is_occupied(p) && hostile_at(p))
+            };
+            
+            // 移动，并把蛇头/蛇尾的变化同步到共享网格
+            let len_before = snake.len();
+            let teleported = snake.pending_portal.is_some();
+            let passes = snake.buff_state.can_pass_through();
+            let move_result = snake.move_forward_among(topology, others);
+            if let AIMoveResult::Normal(new_head) = move_result {
+                self.occupancy.add(new_head);
+                if snake.len() == len_before {
+                    self.occupancy.remove(snake.prev_tail);
+                }
+            }
             
+            // 这一步撞上的玩家（玩家之间不会重叠，最多一名）
+            let struck = match move_result {
+                AIMoveResult::Normal(new_head) => players.iter().position(|h| h.snake.occupies(new_head)),
+                _ => None,
+            };
+
             match move_result {
+                // Boss 穿过一切，不吃东西；碾过玩家的身体（切割规则下切断），蛇头相撞时弱点被撞中
+                AIMoveResult::Normal(new_head) if snake.boss.is_some() => {
+                    let Some(j) = struck else { continue };
+                    let (player, report) = (players[j], &mut result.players[j]);
+                    if snake.burrowed() || player.buffs.ghost_active || player.buffs.can_pass_through() {
+                        continue;
+                    }
+                    match Strike::at(&player.snake.body, new_head) {
+                        Some(Strike::HeadOn) => boss_head_hits.push(i),
+                        Some(Strike::Sever(n)) if cutting => {
+                            report.cut = Some(report.cut.map_or(n, |m: usize| m.min(n)));
+                            result.cuts += 1;
+                            spawn_cut_particles(new_head, SAND, particles, rng);
+                        }
+                        Some(Strike::Sever(_)) => report.died = true,
+                        None => {}
+                    }
+                }
                 AIMoveResult::Normal(new_head) => {
                     // 检查是否撞到玩家身体
                     // 幽灵状态下，AI蛇可以穿过玩家身体（不会死亡）
-                    // 非幽灵状态下，AI蛇碰到玩家身体会死亡
-                    if player_body.contains(&new_head) && !player_is_ghost {
+                    // 非幽灵状态下，AI蛇碰到玩家身体会死亡，算玩家击杀；
+                    // 切割规则下切断玩家（玩家有护盾时仍然撞死），头对头按长度结算；
+                    // AI 沙虫吞掉玩家的尾巴，撞到蛇头玩家死亡；AI 有护盾/幽灵时穿过玩家；
+                    // 关闭误伤时盟友穿过玩家，开启时按敌人结算（但不算击杀）
+                    let ally = team == Some(Team::Player);
+                    let mut ai_dies = false;
+                    if let Some(j) = struck {
+                        let (player, report) = (players[j], &mut result.players[j]);
+                        let player_can_pass_through = player.buffs.can_pass_through();
+                        let hit_player = !player.buffs.ghost_active && !spares_player;
+                        let devour = snake.buff_state.sandworm_active && !player_can_pass_through && !ally;
+                        let strike = Strike::at(&player.snake.body, new_head)
+                            .filter(|_| hit_player && (devour || (cutting && !player_can_pass_through)));
+                        ai_dies = match strike {
+                            Some(Strike::Sever(n)) if devour => {
+                                report.devoured = Some(report.devoured.map_or(n, |m: usize| m.min(n)));
+                                spawn_cut_particles(new_head, SAND, particles, rng);
+                                false
+                            }
+                            Some(Strike::HeadOn) if devour => {
+                                report.died = true;
+                                false
+                            }
+                            Some(Strike::Sever(n)) => {
+                                report.cut = Some(report.cut.map_or(n, |m: usize| m.min(n)));
+                                result.cuts += 1;
+                                spawn_cut_particles(new_head, player.snake.color, particles, rng);
+                                false
+                            }
+                            Some(Strike::HeadOn) => {
+                                let outcome = HeadOn::resolve(snake.len(), player.snake.len());
+                                report.died |= !outcome.defender_survives();
+                                !outcome.attacker_survives()
+                            }
+                            None => hit_player && !passes,
+                        };
+                        // 撞死在玩家身上算这名玩家的击杀
+                        if ai_dies && !ally {
+                            report.kill_bonus += snake.progress.bounty();
+                        }
+                    }
+                    if ai_dies {
                         dead_indices.push(i);
                         continue;
                     }
                     
-                    // 检查是否撞到其他 AI 蛇
-                    for (j, other_body) in bodies.iter().enumerate() {
-                        if i != j && other_body.contains(&new_head) {
+                    // 撞到动态障碍物（与撞墙相同，幽灵/护盾可穿过）
+                    if hazards.is_solid(new_head, game_time) && !snake.buff_state.can_pass_through() {
+                        dead_indices.push(i);
+                        continue;
+                    }
+                    
+                    // 检查是否撞到其他 AI 蛇（共享网格减去自己的部分）
+                    // 沙虫吞掉对方，护盾/幽灵穿过对方；撞在盟友身上死亡的敌人算玩家击杀
+                    if self.occupancy.count(new_head) > snake.occupancy.count(new_head) && hostile_at(new_head) {
+                        if snake.buff_state.sandworm_active {
+                            sandworm_strikes.push((i, new_head));
+                        } else if cutting && !passes {
+                            ai_strikes.push((i, new_head));
+                        } else if !passes {
+                            let by_ally = before
+                                .iter()
+                                .chain(after.iter())
+                                .any(|o| o.team == Some(Team::Player) && o.occupancy.is_occupied(new_head));
+                            if by_ally && !ally {
+                                result.kill_bonus += snake.progress.bounty();
+                            }
                             dead_indices.push(i);
-                            break;
+                            continue;
                         }
                     }
                     
-                    if dead_indices.contains(&i) {
-                        continue;
+                    // 踏入传送门入口，下一步从出口出现（刚从出口出来的这一步不会再被传回去）
+                    if !teleported && let Some(exit) = find_portal_exit(new_head, snake.dir, portals) {
+                        snake.pending_portal = Some(exit);
                     }
                     
+                    // 这一步吃东西获得的经验和吃到的东西数（宠物蛇按个数给玩家加分）
+                    let mut xp = 0.0;
+                    let mut eaten = 0;
+
                     // 检查是否吃到普通食物
                     if new_head == *food {
                         snake.grow();
                         need_respawn_food = true;
+                        xp += XP_FOOD;
+                        eaten += 1;
                     }
                     
                     // 检查是否吃到掉落的食物
@@ -185,6 +754,8 @@ impl AIManager {
                         if new_head == dropped.pos {
                             snake.grow();
                             eaten_dropped.push(idx);
+                            xp += XP_DROPPED;
+                            eaten += 1;
                         }
                     }
                     for idx in eaten_dropped.into_iter().rev() {
@@ -194,6 +765,8 @@ impl AIManager {
                     // 检查是否吃到果实
                     if let Some(idx) = fruits.iter().position(|f| f.pos == new_head) {
                         let fruit = fruits.remove(idx);
+                        xp += XP_FRUIT;
+                        eaten += 1;
                         
                         // AI 蛇和玩家一样受到果实效果（除了沙虫）
                         if let Some(config) = registry.get_config(fruit.type_id) {
@@ -209,7 +782,9 @@ impl AIManager {
                                             "trap" => {
                                                 // 受伤：缩短蛇身
                                                 if snake.body.len() > 5 {
-                                                    snake.body.truncate(snake.body.len() - 2);
+                                                    for pos in snake.truncate(snake.body.len() - 2) {
+                                                        self.occupancy.remove(pos);
+                                                    }
                                                 }
                                             }
                                             "freeze" => {
@@ -225,17 +800,14 @@ impl AIManager {
                                     }
                                 }
                                 FruitCategory::Power => {
-                                    // 功能果实：应用正面效果（沙虫除外）
+                                    // 功能果实：应用正面效果
                                     match fruit.type_id {
                                         "shield" => snake.buff_state.activate_shield(),
                                         "speed" => snake.buff_state.activate_speed(),
                                         "ghost" => snake.buff_state.activate_ghost(),
-                                        "reverse" => snake.body.reverse(),
+                                        "reverse" => snake.reverse(),
                                         "heal" => snake.buff_state.clear_all_debuffs(),
-                                        "sandworm" => {
-                                            // AI 蛇不激活沙虫模式，只增长
-                                            snake.grow();
-                                        }
+                                        "sandworm" => snake.buff_state.activate_sandworm(),
                                         _ => snake.grow(),
                                     }
                                 }
@@ -246,6 +818,20 @@ impl AIManager {
                             }
                         }
                     }
+
+                    if xp > 0.0 && snake.progress.gain(xp) {
+                        spawn_level_up_particles(snake, particles, rng);
+                    }
+                    if snake.pet && eaten > 0 {
+                        result.pet_scores.push((snake.owner, eaten));
+                    }
+                }
+                // 斜向挤过蛇身之间撞死，和撞在玩家身上一样算击杀（盟友除外）
+                AIMoveResult::Squeezed => {
+                    if team != Some(Team::Player) {
+                        result.kill_bonus += snake.progress.bounty();
+                    }
+                    dead_indices.push(i);
                 }
                 AIMoveResult::WallCollision | AIMoveResult::SelfCollision => {
                     dead_indices.push(i);
@@ -256,35 +842,97 @@ impl AIManager {
             }
         }
         
+        // 切割规则：结算 AI 蛇之间的撞击
+        for (attacker, pos) in ai_strikes {
+            if dead_indices.contains(&attacker) {
+                continue;
+            }
+            let team = self.snakes[attacker].team;
+            let target = self.snakes.iter().enumerate().find_map(|(j, other)| {
+                let hostile = friendly_fire || !Team::allied(other.team, team);
+                (j != attacker && hostile).then(|| Strike::at(&other.body, pos).map(|s| (j, s))).flatten()
+            });
+            match target {
+                Some((victim, Strike::Sever(n))) => {
+                    let color = self.snakes[victim].color;
+                    self.sever(victim, n, game_time);
+                    result.cuts += 1;
+                    spawn_cut_particles(pos, color, particles, rng);
+                }
+                Some((victim, Strike::HeadOn)) => {
+                    let outcome = HeadOn::resolve(self.snakes[attacker].len(), self.snakes[victim].len());
+                    if !outcome.attacker_survives() {
+                        dead_indices.push(attacker);
+                    }
+                    if !outcome.defender_survives() {
+                        dead_indices.push(victim);
+                    }
+                }
+                None => {}
+            }
+        }
+
+        // Boss 的蛇头撞上玩家的蛇头
+        for idx in boss_head_hits {
+            self.damage_boss(idx, BOSS_HEAD_DAMAGE, particles, rng);
+        }
+
+        // 沙虫吞噬：被撞的 AI 蛇整条消失，不掉落食物（Boss 吞不掉）
+        let mut devoured: Vec<usize> = Vec::new();
+        for (attacker, pos) in sandworm_strikes {
+            if dead_indices.contains(&attacker) {
+                continue;
+            }
+            let team = self.snakes[attacker].team;
+            let victim = (0..self.snakes.len()).find(|&j| {
+                let other = &self.snakes[j];
+                j != attacker
+                    && other.boss.is_none()
+                    && (friendly_fire || !Team::allied(other.team, team))
+                    && other.occupancy.is_occupied(pos)
+            });
+            if let Some(victim) = victim {
+                for &p in &self.snakes[victim].body {
+                    spawn_cut_particles(p, SAND, particles, rng);
+                }
+                devoured.push(victim);
+            }
+        }
+
         // 如果有 AI 蛇吃到了食物，重新生成食物
         if need_respawn_food {
-            *food = Self::spawn_new_food(player_body, &self.snakes, rng);
+            // 没有空位时食物留在原处
+            if let Some(pos) = self.spawn_new_food(players, fruits, rng) {
+                *food = pos;
+            }
         }
         
-        // 检查玩家是否撞到 AI 蛇身体
-        if !player_body.is_empty() {
-            let player_head = player_body[0];
-            for snake in &self.snakes {
-                // 检查是否撞到 AI 蛇身体（不包括头，头对头另外处理）
-                if snake.body.iter().skip(1).any(|&p| p == player_head) {
-                    if !player_can_pass_through {
-                        result.player_died = true;
-                    }
-                }
-                // 头对头碰撞
-                if snake.head() == player_head {
-                    if !player_can_pass_through {
-                        result.player_died = true;
-                    }
+        // 检查玩家是否撞到 AI 蛇身体（切割规则下由 player_strike 结算）
+        for (player, report) in players.iter().zip(&mut result.players) {
+            if cutting || player.snake.is_empty() || player.buffs.can_pass_through() {
+                continue;
+            }
+            let player_head = player.snake.head();
+            for snake in self.snakes.iter().filter(|s| harms_player(s, friendly_fire)) {
+                // 撞到 AI 蛇身体（不包括头），或头对头（Boss 的蛇头是弱点，由 hit_boss_head 结算）
+                if snake.body.iter().skip(1).any(|&p| p == player_head)
+                    || (snake.head() == player_head && snake.boss.is_none())
+                {
+                    report.died = true;
                 }
             }
         }
         
-        // 处理死亡的 AI 蛇
+        // 处理死亡的 AI 蛇（被吞掉的蛇直接移除）
+        dead_indices.extend(&devoured);
         dead_indices.sort_unstable();
         dead_indices.dedup();
         for idx in dead_indices.into_iter().rev() {
-            let dead_snake = self.snakes.remove(idx);
+            let dead_snake = self.remove_snake(idx);
+            if devoured.contains(&idx) {
+                result.ai_deaths += 1;
+                continue;
+            }
             
             // 生成死亡粒子效果
             for &pos in &dead_snake.body {
@@ -302,6 +950,7 @@ impl AIManager {
             }
             
             // 掉落食物
+            let board = self.board();
             for &pos in &dead_snake.body {
                 // 随机偏移 -1 到 1
                 let offset = ivec2(
@@ -309,8 +958,8 @@ impl AIManager {
                     rng.gen_range(-1..=1),
                 );
                 let drop_pos = ivec2(
-                    (pos.x + offset.x).clamp(0, crate::constants::GRID_W as i32 - 1),
-                    (pos.y + offset.y).clamp(0, crate::constants::GRID_H as i32 - 1),
+                    (pos.x + offset.x).clamp(board.origin.x, board.end().x - 1),
+                    (pos.y + offset.y).clamp(board.origin.y, board.end().y - 1),
                 );
                 
                 self.dropped_foods.push(DroppedFood {
@@ -322,16 +971,115 @@ impl AIManager {
             
             result.ai_deaths += 1;
         }
+
+        // 包围玩家（只在开始包围时报告）
+        let surrounded = players.iter().find_map(|p| self.surrounding_team(p.snake, topology));
+        if surrounded != self.surrounded_by
+            && let Some(team) = surrounded
+        {
+            self.team_events.push(TeamEvent::PlayerSurrounded(team));
+        }
+        self.surrounded_by = surrounded;
+        result.team_events = std::mem::take(&mut self.team_events);
+        result.boss_events = std::mem::take(&mut self.boss_events);
         
         result
     }
+
+    /// 把玩家蛇头四周都堵死的敌对队伍
+    ///
+    /// 墙和玩家自己的身体也算堵住，其余每一格都必须是同一支队伍的蛇，且至少两格
+    fn surrounding_team(&self, player: &Snake, topology: Topology) -> Option<Team> {
+        let head = player.body.front().copied()?;
+        let mut team = None;
+        let mut cells = 0;
+        for &dir in player.grid.directions() {
+            let Some((pos, _)) = player.grid.step_on(topology, self.board(), head, dir) else { continue };
+            if player.occupies(pos) {
+                continue;
+            }
+            let owner = self.snakes.iter().find(|s| s.occupancy.is_occupied(pos))?.team?;
+            if owner == Team::Player || team.is_some_and(|t| t != owner) {
+                return None;
+            }
+            team = Some(owner);
+            cells += 1;
+        }
+        team.filter(|_| cells >= 2)
+    }
     
+    /// 把第 `idx` 条 AI 蛇从第 `keep` 节切断，切下的尾巴变成掉落食物
+    ///
+    /// 撞击点上是撞击者的蛇头，不放食物
+    fn sever(&mut self, idx: usize, keep: usize, game_time: f32) -> usize {
+        let removed = self.snakes[idx].truncate(keep);
+        for &pos in &removed {
+            self.occupancy.remove(pos);
+        }
+        self.drop_food(&removed[1..], game_time);
+        removed.len()
+    }
+
+    /// 在这些格子上放下掉落食物
+    pub fn drop_food(&mut self, cells: &[IVec2], game_time: f32) {
+        self.dropped_foods.extend(cells.iter().map(|&pos| DroppedFood { pos, spawn_time: game_time, lifetime: 10.0 }));
+    }
+
+    /// 玩家蛇头撞上 AI 蛇（切割规则）：切断 AI 蛇身，头对头按长度结算
+    ///
+    /// 调用前玩家已经移动到 `player_head`
+    pub fn player_strike<R: Rng>(
+        &mut self,
+        player_head: IVec2,
+        player_len: usize,
+        particles: &mut Vec<Particle>,
+        game_time: f32,
+        rng: &mut R,
+    ) -> StrikeOutcome {
+        let mut outcome = StrikeOutcome::default();
+        let hit = self
+            .snakes
+            .iter()
+            .enumerate()
+            .filter(|(_, snake)| harms_player(snake, self.friendly_fire))
+            .find_map(|(i, snake)| Strike::at(&snake.body, player_head).map(|s| (i, s)));
+        match hit {
+            // 沙虫切不断，撞上就死
+            Some((i, _)) if self.snakes[i].buff_state.sandworm_active => outcome.player_died = true,
+            Some((i, Strike::Sever(n))) => {
+                let color = self.snakes[i].color;
+                outcome.severed = self.sever(i, n, game_time);
+                spawn_cut_particles(player_head, color, particles, rng);
+            }
+            Some((i, Strike::HeadOn)) => {
+                let result = HeadOn::resolve(player_len, self.snakes[i].len());
+                outcome.player_died = !result.attacker_survives();
+                if !result.defender_survives() {
+                    outcome.kill_bonus = self.snakes[i].progress.bounty();
+                    let dead = self.remove_snake(i);
+                    self.drop_food(&dead.body.iter().copied().collect::<Vec<_>>(), game_time);
+                    spawn_cut_particles(player_head, dead.color, particles, rng);
+                }
+            }
+            None => {}
+        }
+        outcome
+    }
+
+    /// 把玩家蛇从第 `keep` 节切断，切下的尾巴变成掉落食物，返回切下的节数
+    pub fn sever_player(&mut self, player: &mut Snake, keep: usize, game_time: f32) -> usize {
+        let removed: Vec<IVec2> = player.body.iter().skip(keep).copied().collect();
+        player.truncate(keep);
+        self.drop_food(removed.get(1..).unwrap_or_default(), game_time);
+        removed.len()
+    }
+
     /// 更新掉落的食物（移除过期的）
     pub fn update_dropped_foods(&mut self, game_time: f32) {
         self.dropped_foods.retain(|f| game_time - f.spawn_time < f.lifetime);
     }
     
-    /// 沙虫模式吞噬 AI 蛇
+    /// 沙虫模式吞噬 AI 蛇（关闭误伤时不吞盟友，吞不掉 Boss）
     pub fn sandworm_devour<R: Rng>(
         &mut self,
         sandworm_positions: &[IVec2],
@@ -342,27 +1090,28 @@ impl AIManager {
         let mut devoured_indices: Vec<usize> = Vec::new();
         
         for (i, snake) in self.snakes.iter().enumerate() {
+            if spares_player(snake.team, self.friendly_fire) || snake.boss.is_some() {
+                continue;
+            }
             // 检查 AI 蛇是否被沙虫吞噬
-            for &pos in &snake.body {
-                if sandworm_positions.contains(&pos) {
-                    devoured_indices.push(i);
-                    score_bonus += snake.len() as u32 * 2; // 每节 2 分
-                    
-                    // 生成吞噬粒子效果
-                    for &body_pos in &snake.body {
-                        for _ in 0..2 {
-                            let lifetime = 0.3;
-                            particles.push(Particle {
-                                pos: vec2(body_pos.x as f32 * 20.0 + 10.0, body_pos.y as f32 * 20.0 + 10.0),
-                                vel: vec2(rng.gen_range(-30.0..30.0), rng.gen_range(-30.0..30.0)),
-                                color: Color::new(0.8, 0.6, 0.2, 1.0), // 沙色
-                                lifetime,
-                                max_lifetime: lifetime,
-                                size: rng.gen_range(3.0..6.0),
-                            });
-                        }
-                    }
-                    break;
+            if !sandworm_positions.iter().any(|&pos| snake.occupancy.is_occupied(pos)) {
+                continue;
+            }
+            devoured_indices.push(i);
+            score_bonus += snake.len() as u32 * 2 + snake.progress.bounty(); // 每节 2 分 + 击杀奖励
+
+            // 生成吞噬粒子效果
+            for &body_pos in &snake.body {
+                for _ in 0..2 {
+                    let lifetime = 0.3;
+                    particles.push(Particle {
+                        pos: vec2(body_pos.x as f32 * 20.0 + 10.0, body_pos.y as f32 * 20.0 + 10.0),
+                        vel: vec2(rng.gen_range(-30.0..30.0), rng.gen_range(-30.0..30.0)),
+                        color: SAND,
+                        lifetime,
+                        max_lifetime: lifetime,
+                        size: rng.gen_range(3.0..6.0),
+                    });
                 }
             }
         }
@@ -371,40 +1120,19 @@ impl AIManager {
         devoured_indices.sort_unstable();
         devoured_indices.dedup();
         for idx in devoured_indices.into_iter().rev() {
-            self.snakes.remove(idx);
+            self.remove_snake(idx);
         }
         
         score_bonus
     }
     
-    /// 生成新的食物位置（避开所有蛇）
-    fn spawn_new_food<R: Rng>(
-        player_body: &[IVec2],
-        ai_snakes: &[AISnake],
-        rng: &mut R,
-    ) -> IVec2 {
-        loop {
-            let pos = ivec2(
-                rng.gen_range(0..crate::constants::GRID_W as i32),
-                rng.gen_range(0..crate::constants::GRID_H as i32),
-            );
-            
-            if player_body.contains(&pos) {
-                continue;
-            }
-            
-            let mut occupied = false;
-            for snake in ai_snakes {
-                if snake.body.contains(&pos) {
-                    occupied = true;
-                    break;
-                }
-            }
-            
-            if !occupied {
-                return pos;
-            }
-        }
+    /// 生成新的食物位置（从空闲格子索引采样，避开玩家和果实）
+    ///
+    /// 棋盘已满时返回 None
+    fn spawn_new_food<R: Rng>(&self, players: &[PlayerView], fruits: &[Fruit], rng: &mut R) -> Option<IVec2> {
+        self.free_cells().sample_where(rng, |p| {
+            !players.iter().any(|h| h.snake.occupies(p)) && !fruits.iter().any(|f| f.pos == p)
+        })
     }
     
     /// 检查玩家是否吃到掉落的食物
@@ -422,11 +1150,624 @@ impl AIManager {
     }
 }
 
+/// AI 更新中一名玩家受到的影响
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct PlayerReport {
+    /// 玩家是否死亡
+    pub died: bool,
+    /// 切割规则下玩家被 AI 蛇撞断：保留前这么多节（用 [`AIManager::sever_player`] 执行）
+    pub cut: Option<usize>,
+    /// 玩家的尾巴被 AI 沙虫吞掉：保留前这么多节（被吞掉的部分不掉落食物）
+    pub devoured: Option<usize>,
+    /// AI 蛇撞死在这名玩家身上的奖励分数（按等级，精英额外加分）
+    pub kill_bonus: u32,
+}
+
 /// AI 更新结果
 #[derive(Default)]
 pub struct AIUpdateResult {
-    /// 玩家是否死亡
-    pub player_died: bool,
+    /// 每名玩家一份，顺序与传入的玩家相同
+    pub players: Vec<PlayerReport>,
     /// AI 蛇死亡数量
     pub ai_deaths: u32,
+    /// 不属于哪一名玩家的击杀奖励（盟友撞死敌人、敌人斜向挤过蛇身之间）
+    pub kill_bonus: u32,
+    /// 这一帧发生的切断次数（播放音效）
+    pub cuts: u32,
+    /// 上次更新以来的队伍事件（包括玩家切割、沙虫吞噬等其他途径移除的队员）
+    pub team_events: Vec<TeamEvent>,
+    /// 宠物蛇这一帧吃到的东西：(主人序号, 个数)，每个给主人加 1 分
+    pub pet_scores: Vec<(usize, u32)>,
+    /// 上次更新以来的 Boss 事件（包括玩家撞中弱点）
+    pub boss_events: Vec<BossEvent>,
+}
+
+/// 玩家和这支队伍的蛇是否互相穿过（关闭误伤时的盟友）
+fn spares_player(team: Option<Team>, friendly_fire: bool) -> bool {
+    team == Some(Team::Player) && !friendly_fire
+}
+
+/// 玩家撞上这条蛇时是否会死（穿过盟友和钻在地下的 Boss，撞死宠物蛇）
+fn harms_player(snake: &AISnake, friendly_fire: bool) -> bool {
+    !snake.pet && !snake.burrowed() && !spares_player(snake.team, friendly_fire)
+}
+
+/// 切断处飞溅的粒子
+fn spawn_cut_particles<R: Rng>(pos: IVec2, color: Color, particles: &mut Vec<Particle>, rng: &mut R) {
+    let center = vec2(pos.x as f32 * 20.0 + 10.0, pos.y as f32 * 20.0 + 10.0);
+    for _ in 0..10 {
+        let lifetime = 0.4;
+        particles.push(Particle {
+            pos: center,
+            vel: vec2(rng.gen_range(-80.0..80.0), rng.gen_range(-80.0..80.0)),
+            color,
+            lifetime,
+            max_lifetime: lifetime,
+            size: rng.gen_range(2.0..4.0),
+        });
+    }
+}
+
+/// AI 蛇升级时在蛇头周围放出一圈粒子
+fn spawn_level_up_particles<R: Rng>(snake: &AISnake, particles: &mut Vec<Particle>, rng: &mut R) {
+    let head = snake.head();
+    let center = vec2(head.x as f32 * 20.0 + 10.0, head.y as f32 * 20.0 + 10.0);
+    for k in 0..12 {
+        let angle = k as f32 / 12.0 * std::f32::consts::TAU;
+        let lifetime = 0.6;
+        particles.push(Particle {
+            pos: center,
+            vel: vec2(angle.cos(), angle.sin()) * rng.gen_range(40.0..70.0),
+            color: GOLD,
+            lifetime,
+            max_lifetime: lifetime,
+            size: rng.gen_range(2.0..4.0),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::thread_rng;
+    use crate::constants::{GRID_W, GRID_H};
+    use crate::fruits::create_fruit_registry;
+    use crate::game::ai_progress::BOUNTY_PER_LEVEL;
+    use crate::game::boss::BOSS_HEAD_DAMAGE;
+    use crate::game::GameRng;
+    use crate::types::{BuffState, SandwormPhase};
+
+    #[test]
+    fn test_shared_occupancy_tracks_all_bodies() {
+        for combat in CombatRules::ALL {
+            check_shared_occupancy(combat);
+        }
+    }
+
+    fn check_shared_occupancy(combat: CombatRules) {
+        let mut rng = thread_rng();
+        let mut manager = AIManager::new();
+        manager.combat = combat;
+        manager.max_snakes = 6;
+        let mut player = Snake::new();
+        let registry = create_fruit_registry();
+        let hazards = HazardManager::new();
+        let mut food = ivec2(1, 1);
+        let mut fruits = vec![];
+        let mut particles = vec![];
+        let wall = [ivec2(3, 3), ivec2(3, 4)];
+        manager.set_blocked_cells(&wall);
+
+        for _ in 0..6 {
+            manager.spawn_default_snake(player.body_slice(), &mut rng);
+        }
+        for frame in 0..200 {
+            manager.update_thinking(food, &fruits, &registry, &[PlayerView { snake: &player, buffs: &BuffState::default() }], &hazards, &[], Topology::Torus, frame as f32 * 0.05, 0.05, &mut rng);
+            manager.update_movement(
+                &mut food, &mut fruits, &[PlayerView { snake: &player, buffs: &BuffState::default() }], &hazards, &[],
+                &mut particles, &registry, Topology::Torus, frame as f32 * 0.05, 0.05, &mut rng,
+            );
+
+            let expected = OccupancyGrid::from_cells(
+                GRID_W,
+                GRID_H,
+                manager.snakes.iter().flat_map(|s| s.body.iter()),
+            );
+            for x in 0..GRID_W {
+                for y in 0..GRID_H {
+                    let pos = ivec2(x, y);
+                    assert_eq!(manager.occupancy.count(pos), expected.count(pos), "frame {frame} at {pos:?}");
+                    // 空闲格子索引随同样的蛇头/蛇尾变化更新
+                    let free = !expected.is_occupied(pos) && !wall.contains(&pos);
+                    assert_eq!(manager.free_cells().is_free(pos), free, "frame {frame} at {pos:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_food_avoids_players_fruits_and_snakes() {
+        let mut manager = AIManager::new();
+        // 只留下 (0,0)-(3,0) 四格，AI 蛇、玩家和果实各占一格
+        let wall: Vec<IVec2> = (0..GRID_W)
+            .flat_map(|x| (0..GRID_H).map(move |y| ivec2(x, y)))
+            .filter(|p| p.y > 0 || p.x > 3)
+            .collect();
+        place_snake(&mut manager, &[ivec2(0, 0)], ivec2(1, 0));
+        manager.set_blocked_cells(&wall);
+        let mut player = Snake::new();
+        player.set_body([ivec2(1, 0)]);
+        let fruits = [Fruit::new(ivec2(2, 0), "normal", 0.0, 0.0)];
+        let players = [PlayerView { snake: &player, buffs: &BuffState::default() }];
+        for _ in 0..20 {
+            assert_eq!(manager.spawn_new_food(&players, &fruits, &mut thread_rng()), Some(ivec2(3, 0)));
+        }
+        player.set_body([ivec2(3, 0), ivec2(2, 0), ivec2(1, 0)]);
+        let players = [PlayerView { snake: &player, buffs: &BuffState::default() }];
+        assert_eq!(manager.spawn_new_food(&players, &fruits, &mut thread_rng()), None);
+    }
+
+    /// 放一条指定身体的 AI 蛇，下一次 update_movement 就会移动
+    fn place_snake(manager: &mut AIManager, cells: &[IVec2], dir: IVec2) {
+        let mut rng = thread_rng();
+        manager.spawn_default_snake(&[], &mut rng);
+        let snake = manager.snakes.last_mut().unwrap();
+        snake.body = cells.iter().copied().collect();
+        snake.prev_tail = cells[cells.len() - 1];
+        snake.occupancy = OccupancyGrid::from_cells(GRID_W, GRID_H, cells);
+        snake.dir = dir;
+        snake.move_accumulator = 1.0;
+        manager.rebuild_occupancy();
+    }
+
+    fn step(manager: &mut AIManager, player: &Snake, portals: &[Portal]) -> AIUpdateResult {
+        let registry = create_fruit_registry();
+        let mut particles = vec![];
+        manager.update_movement(
+            &mut ivec2(1, 1), &mut vec![], &[PlayerView { snake: player, buffs: &BuffState::default() }], &HazardManager::new(), portals,
+            &mut particles, &registry, Topology::Bounded, 0.0, 0.01, &mut thread_rng(),
+        )
+    }
+
+    #[test]
+    fn test_cutting_severs_bodies_instead_of_killing() {
+        let mut manager = AIManager::new();
+        manager.combat = CombatRules::Cutting;
+        manager.max_snakes = 3;
+        let mut player = Snake::new();
+        player.set_body([ivec2(10, 8), ivec2(9, 8), ivec2(8, 8), ivec2(7, 8)]);
+
+        // A 向右撞进 B 的第 3 节；B 被冰冻不动
+        let victim: Vec<IVec2> = (3..=7).map(|y| ivec2(6, y)).collect();
+        place_snake(&mut manager, &[ivec2(5, 5), ivec2(4, 5), ivec2(3, 5)], ivec2(1, 0));
+        place_snake(&mut manager, &victim, ivec2(0, -1));
+        manager.snakes[1].buff_state.frozen = true;
+        manager.snakes[1].buff_state.freeze_timer = 10.0;
+        // C 向上撞进玩家第 3 节
+        place_snake(&mut manager, &[ivec2(8, 9), ivec2(8, 10), ivec2(8, 11)], ivec2(0, -1));
+
+        let result = step(&mut manager, &player, &[]);
+        assert_eq!(manager.snakes.len(), 3);
+        assert_eq!(manager.snakes[1].body.len(), 2);
+        let dropped: Vec<IVec2> = manager.dropped_foods.iter().map(|f| f.pos).collect();
+        assert_eq!(dropped, [ivec2(6, 6), ivec2(6, 7)]);
+        assert_eq!(result.players[0].cut, Some(2));
+        assert_eq!(result.cuts, 2);
+        assert!(!result.players[0].died);
+
+        assert_eq!(manager.sever_player(&mut player, 2, 0.0), 2);
+        assert_eq!(player.len(), 2);
+
+        // 玩家蛇头撞 AI 蛇头：玩家更长，AI 死亡
+        let head = manager.snakes[1].head();
+        let outcome = manager.player_strike(head, 10, &mut vec![], 0.0, &mut thread_rng());
+        assert!(!outcome.player_died);
+        assert!(outcome.kill_bonus > 0);
+        assert_eq!(manager.snakes.len(), 2);
+    }
+
+    #[test]
+    fn test_lethal_rules_kill_on_contact() {
+        let mut manager = AIManager::new();
+        let mut player = Snake::new();
+        player.set_body([ivec2(10, 8), ivec2(9, 8), ivec2(8, 8), ivec2(7, 8)]);
+        place_snake(&mut manager, &[ivec2(8, 9), ivec2(8, 10), ivec2(8, 11)], ivec2(0, -1));
+
+        let result = step(&mut manager, &player, &[]);
+        assert!(manager.snakes.is_empty());
+        assert_eq!(result.players[0].cut, None);
+        assert_eq!(result.players[0].kill_bonus, BOUNTY_PER_LEVEL);
+    }
+
+    #[test]
+    fn test_every_player_is_hit_separately() {
+        let mut manager = AIManager::new();
+        manager.combat = CombatRules::Cutting;
+        let mut far = Snake::new();
+        far.set_body([ivec2(25, 20), ivec2(26, 20), ivec2(27, 20)]);
+        let mut near = Snake::new();
+        near.set_body([ivec2(10, 8), ivec2(9, 8), ivec2(8, 8), ivec2(7, 8)]);
+        place_snake(&mut manager, &[ivec2(8, 9), ivec2(8, 10), ivec2(8, 11)], ivec2(0, -1));
+
+        // 撞上第二名玩家：只切断第二名玩家
+        let buffs = BuffState::default();
+        let players = [PlayerView { snake: &far, buffs: &buffs }, PlayerView { snake: &near, buffs: &buffs }];
+        let result = manager.update_movement(
+            &mut ivec2(1, 1), &mut vec![], &players, &HazardManager::new(), &[],
+            &mut vec![], &create_fruit_registry(), Topology::Bounded, 0.0, 0.01, &mut thread_rng(),
+        );
+        assert_eq!(result.players, [PlayerReport::default(), PlayerReport { cut: Some(2), ..Default::default() }]);
+
+        // 经典规则下撞死在第二名玩家身上，算第二名玩家的击杀
+        manager.reset();
+        manager.combat = CombatRules::Lethal;
+        place_snake(&mut manager, &[ivec2(8, 9), ivec2(8, 10), ivec2(8, 11)], ivec2(0, -1));
+        let result = manager.update_movement(
+            &mut ivec2(1, 1), &mut vec![], &players, &HazardManager::new(), &[],
+            &mut vec![], &create_fruit_registry(), Topology::Bounded, 0.0, 0.01, &mut thread_rng(),
+        );
+        assert!(manager.snakes.is_empty());
+        assert_eq!(result.players[0].kill_bonus, 0);
+        assert_eq!(result.players[1].kill_bonus, BOUNTY_PER_LEVEL);
+    }
+
+    #[test]
+    fn test_ai_sandworm_devours_player_tail_and_snakes() {
+        let mut manager = AIManager::new();
+        manager.max_snakes = 3;
+        let mut player = Snake::new();
+        player.set_body([ivec2(10, 8), ivec2(9, 8), ivec2(8, 8), ivec2(7, 8)]);
+
+        // 沙虫 A 向上撞进玩家第 3 节，沙虫 B 向右撞进冰冻的 C
+        place_snake(&mut manager, &[ivec2(8, 9), ivec2(8, 10), ivec2(8, 11)], ivec2(0, -1));
+        place_snake(&mut manager, &[ivec2(5, 15), ivec2(4, 15), ivec2(3, 15)], ivec2(1, 0));
+        place_snake(&mut manager, &[ivec2(6, 14), ivec2(6, 15), ivec2(6, 16)], ivec2(0, -1));
+        for snake in &mut manager.snakes[..2] {
+            snake.buff_state.activate_sandworm();
+            snake.buff_state.sandworm_phase = SandwormPhase::Rampaging;
+        }
+        manager.snakes[2].buff_state.frozen = true;
+        manager.snakes[2].buff_state.freeze_timer = 10.0;
+
+        let result = step(&mut manager, &player, &[]);
+        assert_eq!(result.players[0].devoured, Some(2));
+        assert!(!result.players[0].died);
+        assert_eq!(result.ai_deaths, 1);
+        assert_eq!(manager.snakes.len(), 2);
+        assert!(manager.dropped_foods.is_empty());
+
+        // 玩家有护盾时沙虫穿过去，谁也不受伤
+        place_snake(&mut manager, &[ivec2(9, 9), ivec2(9, 10), ivec2(9, 11)], ivec2(0, -1));
+        manager.snakes[2].buff_state.activate_sandworm();
+        manager.snakes[2].buff_state.sandworm_phase = SandwormPhase::Rampaging;
+        let registry = create_fruit_registry();
+        let mut shielded = BuffState::default();
+        shielded.activate_shield();
+        let result = manager.update_movement(
+            &mut ivec2(1, 1), &mut vec![], &[PlayerView { snake: &player, buffs: &shielded }], &HazardManager::new(), &[],
+            &mut vec![], &registry, Topology::Bounded, 0.0, 0.01, &mut thread_rng(),
+        );
+        assert_eq!(result.players[0].devoured, None);
+        assert_eq!(manager.snakes[2].head(), ivec2(9, 8));
+    }
+
+    #[test]
+    fn test_portal_exit_does_not_bounce_back() {
+        let mut manager = AIManager::new();
+        let player = Snake::new();
+        let portals = [Portal::permanent(ivec2(6, 3), ivec2(15, 3), WHITE)];
+        place_snake(&mut manager, &[ivec2(5, 3), ivec2(4, 3), ivec2(3, 3)], ivec2(1, 0));
+
+        let mut heads = vec![];
+        for _ in 0..3 {
+            manager.snakes[0].move_accumulator = 1.0;
+            step(&mut manager, &player, &portals);
+            heads.push(manager.snakes[0].head());
+        }
+        assert_eq!(heads, [ivec2(6, 3), ivec2(15, 3), ivec2(16, 3)]);
+    }
+
+    #[test]
+    fn test_octile_squeeze_between_mixed_bodies() {
+        // 自己的身体 (6,5) 和玩家的身体 (5,4) 之间斜向挤过：撞死，算击杀
+        let mut manager = AIManager::new();
+        let mut player = Snake::new();
+        player.set_body([ivec2(5, 4), ivec2(4, 4), ivec2(3, 4)]);
+        place_snake(&mut manager, &[ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)], ivec2(1, -1));
+        manager.snakes[0].grid = GridKind::Octile;
+        let result = step(&mut manager, &player, &[]);
+        assert!(manager.snakes.is_empty());
+        assert_eq!(result.kill_bonus, BOUNTY_PER_LEVEL);
+
+        // 玩家的身体 (16,10) 和另一条 AI 蛇 (15,9) 之间斜向挤过
+        manager.max_snakes = 2;
+        player.set_body([ivec2(16, 10), ivec2(17, 10), ivec2(18, 10)]);
+        place_snake(&mut manager, &[ivec2(15, 10), ivec2(15, 11), ivec2(15, 12)], ivec2(1, -1));
+        place_snake(&mut manager, &[ivec2(15, 9), ivec2(14, 9), ivec2(13, 9)], ivec2(0, -1));
+        manager.snakes[0].grid = GridKind::Octile;
+        freeze(&mut manager.snakes[1]);
+        step(&mut manager, &player, &[]);
+        assert_eq!(manager.snakes.len(), 1);
+        assert_eq!(manager.snakes[0].head(), ivec2(15, 9));
+
+        // 幽灵状态的玩家不算：只有一侧被占，可以通过
+        place_snake(&mut manager, &[ivec2(15, 10), ivec2(15, 11), ivec2(15, 12)], ivec2(1, -1));
+        manager.snakes[1].grid = GridKind::Octile;
+        let mut ghost = BuffState::default();
+        ghost.activate_ghost();
+        manager.update_movement(
+            &mut ivec2(1, 1), &mut vec![], &[PlayerView { snake: &player, buffs: &ghost }], &HazardManager::new(), &[],
+            &mut vec![], &create_fruit_registry(), Topology::Bounded, 0.0, 0.01, &mut thread_rng(),
+        );
+        assert_eq!(manager.snakes.len(), 2);
+        assert_eq!(manager.snakes[1].head(), ivec2(16, 9));
+    }
+
+    /// 冰冻一条 AI 蛇（测试期间不动）
+    fn freeze(snake: &mut AISnake) {
+        snake.buff_state.frozen = true;
+        snake.buff_state.freeze_timer = 10.0;
+    }
+
+    #[test]
+    fn test_teammates_pass_through_unless_friendly_fire() {
+        for friendly_fire in [false, true] {
+            let mut manager = AIManager::new();
+            manager.friendly_fire = friendly_fire;
+            let mut player = Snake::new();
+            player.set_body([ivec2(25, 20), ivec2(26, 20), ivec2(27, 20)]);
+            // A 向右撞进冰冻的队友 B
+            place_snake(&mut manager, &[ivec2(5, 5), ivec2(4, 5), ivec2(3, 5)], ivec2(1, 0));
+            place_snake(&mut manager, &(3..=7).map(|y| ivec2(6, y)).collect::<Vec<_>>(), ivec2(0, -1));
+            freeze(&mut manager.snakes[1]);
+            for snake in &mut manager.snakes {
+                snake.team = Some(Team::Red);
+            }
+            let id = manager.snakes[0].id;
+
+            let result = step(&mut manager, &player, &[]);
+            if friendly_fire {
+                assert_eq!(manager.snakes.len(), 1);
+                assert_eq!(result.team_events, [TeamEvent::MemberLost { team: Team::Red, id }]);
+            } else {
+                assert_eq!(manager.snakes[0].head(), ivec2(6, 5));
+                assert!(result.team_events.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_allies_spare_player_and_score_kills() {
+        let mut manager = AIManager::new();
+        manager.teams = TeamMode::Allies;
+        let mut player = Snake::new();
+        player.set_body([ivec2(10, 8), ivec2(9, 8), ivec2(8, 8), ivec2(7, 8)]);
+
+        // 盟友向上穿过玩家；红队的蛇撞上盟友的身体
+        place_snake(&mut manager, &[ivec2(8, 9), ivec2(8, 10), ivec2(8, 11)], ivec2(0, -1));
+        place_snake(&mut manager, &[ivec2(7, 10), ivec2(6, 10), ivec2(5, 10)], ivec2(1, 0));
+        manager.snakes[0].team = Some(Team::Player);
+        let enemy = manager.snakes[1].id;
+        assert_eq!(manager.team_of(enemy), Some(Team::Red));
+
+        let result = step(&mut manager, &player, &[]);
+        assert_eq!(manager.snakes.len(), 1);
+        assert_eq!(manager.snakes[0].head(), ivec2(8, 8));
+        assert!(!result.players[0].died);
+        assert_eq!(result.kill_bonus, BOUNTY_PER_LEVEL);
+        assert_eq!(
+            result.team_events,
+            [TeamEvent::MemberLost { team: Team::Red, id: enemy }, TeamEvent::Eliminated(Team::Red)]
+        );
+
+        // 玩家也能穿过盟友，开启误伤后不行
+        assert!(!manager.blocks_player(ivec2(8, 9)));
+        manager.friendly_fire = true;
+        assert!(manager.blocks_player(ivec2(8, 9)));
+
+        // 盟友数量有上限，也不占敌对 AI 蛇的名额
+        let mut rng = thread_rng();
+        assert!(manager.spawn_ally(&[], &mut rng));
+        assert!(!manager.spawn_ally(&[], &mut rng));
+        manager.max_snakes = 1;
+        assert!(manager.spawn_default_snake(&[], &mut rng));
+        assert_eq!(manager.snakes.last().unwrap().team, Some(Team::Red));
+        manager.teams = TeamMode::Squads;
+        assert!(!manager.spawn_ally(&[], &mut rng));
+    }
+
+    #[test]
+    fn test_squads_balance_and_report_surrounding() {
+        let mut manager = AIManager::new();
+        manager.teams = TeamMode::Squads;
+        manager.max_snakes = 4;
+        let mut rng = thread_rng();
+        while manager.spawn_default_snake(&[], &mut rng) {}
+        assert_eq!(manager.members(Team::Red).count(), 2);
+        assert_eq!(manager.members(Team::Blue).count(), 2);
+        assert!(manager.snakes.iter().all(|s| s.color == s.team.unwrap().color()));
+
+        // 玩家向右，上、前、下三格都被红队堵住
+        manager.reset();
+        let mut player = Snake::new();
+        player.set_body([ivec2(10, 10), ivec2(9, 10), ivec2(8, 10)]);
+        place_snake(&mut manager, &[ivec2(10, 9), ivec2(11, 9), ivec2(12, 9)], ivec2(-1, 0));
+        place_snake(&mut manager, &[ivec2(11, 10), ivec2(11, 11), ivec2(10, 11)], ivec2(0, -1));
+        for snake in &mut manager.snakes {
+            freeze(snake);
+            snake.team = Some(Team::Red);
+        }
+        let result = step(&mut manager, &player, &[]);
+        assert_eq!(result.team_events, [TeamEvent::PlayerSurrounded(Team::Red)]);
+        assert!(step(&mut manager, &player, &[]).team_events.is_empty());
+
+        // 两队各堵一部分不算
+        manager.snakes[1].team = Some(Team::Blue);
+        manager.surrounded_by = None;
+        assert!(step(&mut manager, &player, &[]).team_events.is_empty());
+    }
+
+    #[test]
+    fn test_pet_blocks_enemies_feeds_player_and_dies_to_player() {
+        let mut manager = AIManager::new();
+        let mut player = Snake::new();
+        player.set_body([ivec2(10, 8), ivec2(9, 8), ivec2(8, 8)]);
+
+        // 宠物蛇从蛇尾后面出生，朝远离蛇身的方向
+        assert!(manager.spawn_pet(0, &player, Topology::Bounded));
+        let pet = &manager.snakes[0];
+        assert!(pet.pet);
+        assert_eq!(pet.team, Some(Team::Player));
+        assert_eq!((pet.head(), pet.dir), (ivec2(7, 8), ivec2(-1, 0)));
+
+        // 敌对 AI 蛇撞上宠物蛇死亡，算玩家击杀
+        freeze(&mut manager.snakes[0]);
+        place_snake(&mut manager, &[ivec2(7, 9), ivec2(7, 10), ivec2(7, 11)], ivec2(0, -1));
+        let result = step(&mut manager, &player, &[]);
+        assert_eq!(manager.snakes.len(), 1);
+        assert_eq!(result.kill_bonus, BOUNTY_PER_LEVEL);
+
+        // 宠物蛇吃到的东西给玩家加分（清掉敌人随机掉落的食物）
+        manager.dropped_foods.clear();
+        let pet = &mut manager.snakes[0];
+        pet.buff_state.frozen = false;
+        pet.move_accumulator = 1.0;
+        let mut food = ivec2(6, 8);
+        let result = manager.update_movement(
+            &mut food, &mut vec![], &[PlayerView { snake: &player, buffs: &BuffState::default() }], &HazardManager::new(), &[],
+            &mut vec![], &create_fruit_registry(), Topology::Bounded, 0.0, 0.01, &mut thread_rng(),
+        );
+        assert_eq!(manager.snakes[0].head(), ivec2(6, 8));
+        assert_eq!(result.pet_scores, [(0, 1)]);
+
+        // 玩家撞上宠物蛇不会死，宠物蛇死亡（开启误伤也一样）
+        manager.friendly_fire = true;
+        assert!(!manager.blocks_player(ivec2(6, 8)));
+        assert!(!manager.crush_pet(ivec2(5, 8), &mut vec![], &mut thread_rng()));
+        assert!(manager.crush_pet(ivec2(6, 8), &mut vec![], &mut thread_rng()));
+        assert!(manager.snakes.is_empty());
+
+        // 宠物蛇数量有上限
+        for _ in 0..MAX_PETS {
+            assert!(manager.spawn_pet(0, &player, Topology::Bounded));
+        }
+        assert!(!manager.spawn_pet(0, &player, Topology::Bounded));
+    }
+
+    /// 生成一条指定身体的 Boss（已经完全钻出地面）
+    fn place_boss(manager: &mut AIManager, cells: &[IVec2], dir: IVec2) {
+        assert!(manager.spawn_boss(&[], &mut thread_rng()));
+        let idx = manager.snakes.len() - 1;
+        let boss = &mut manager.snakes[idx];
+        boss.body = cells.iter().copied().collect();
+        boss.prev_tail = cells[cells.len() - 1];
+        boss.occupancy = OccupancyGrid::from_cells(GRID_W, GRID_H, cells);
+        boss.grow_pending = 0;
+        boss.dir = dir;
+        boss.move_accumulator = 1.0;
+        manager.rebuild_occupancy();
+    }
+
+    #[test]
+    fn test_boss_weak_point_and_defeat() {
+        let mut manager = AIManager::new();
+        let player = Snake::new();
+        place_boss(&mut manager, &(3..9).map(|x| ivec2(x, 12)).rev().collect::<Vec<_>>(), ivec2(1, 0));
+        assert!(!manager.spawn_boss(&[], &mut thread_rng()));
+
+        // 只有蛇头是弱点；钻地时碰不到
+        assert!(!manager.blocks_player(ivec2(8, 12)));
+        assert!(manager.blocks_player(ivec2(5, 12)));
+        manager.snakes[0].boss.as_mut().unwrap().phase = BossPhase::Burrow;
+        assert!(!manager.blocks_player(ivec2(5, 12)));
+        assert!(!manager.hit_boss_head(ivec2(8, 12), &mut vec![], &mut thread_rng()));
+        manager.snakes[0].boss.as_mut().unwrap().phase = BossPhase::Charge;
+
+        // 撞中弱点扣血并僵直，僵直期间不再扣血
+        assert!(manager.hit_boss_head(ivec2(8, 12), &mut vec![], &mut thread_rng()));
+        assert!(manager.hit_boss_head(ivec2(8, 12), &mut vec![], &mut thread_rng()));
+        assert_eq!(manager.snakes[0].health(), 6 - BOSS_HEAD_DAMAGE);
+        assert!(manager.snakes[0].buff_state.frozen);
+
+        // 血量不足时被击败，身体变成掉落食物
+        let result = step(&mut manager, &player, &[]);
+        assert!(manager.snakes.is_empty());
+        assert_eq!(manager.dropped_foods.len(), 6 - BOSS_HEAD_DAMAGE);
+        assert_eq!(
+            result.boss_events,
+            [
+                BossEvent::Appeared,
+                BossEvent::Hit { health: 6 - BOSS_HEAD_DAMAGE },
+                BossEvent::Defeated { reward: BOSS_REWARD },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_boss_crushes_player_and_passes_through_snakes() {
+        for combat in CombatRules::ALL {
+            let mut manager = AIManager::new();
+            manager.combat = combat;
+            let mut player = Snake::new();
+            player.set_body([ivec2(6, 3), ivec2(6, 4), ivec2(6, 5), ivec2(6, 6)]);
+            // Boss 向右碾过玩家的身体
+            place_boss(&mut manager, &[ivec2(5, 5), ivec2(4, 5), ivec2(3, 5), ivec2(2, 5)], ivec2(1, 0));
+
+            let result = step(&mut manager, &player, &[]);
+            assert_eq!(manager.snakes[0].head(), ivec2(6, 5));
+            match combat {
+                CombatRules::Lethal => assert!(result.players[0].died),
+                CombatRules::Cutting => assert_eq!(result.players[0].cut, Some(2)),
+            }
+        }
+
+        // 撞上其他 AI 蛇时穿过去，两条蛇都活着
+        let mut manager = AIManager::new();
+        let mut player = Snake::new();
+        player.set_body([ivec2(25, 20), ivec2(26, 20), ivec2(27, 20)]);
+        place_boss(&mut manager, &[ivec2(5, 10), ivec2(4, 10), ivec2(3, 10), ivec2(2, 10)], ivec2(1, 0));
+        place_snake(&mut manager, &(9..=11).map(|y| ivec2(6, y)).collect::<Vec<_>>(), ivec2(0, -1));
+        freeze(&mut manager.snakes[1]);
+        step(&mut manager, &player, &[]);
+        assert_eq!(manager.snakes.len(), 2);
+        assert_eq!(manager.snakes[0].head(), ivec2(6, 10));
+    }
+
+    #[test]
+    fn test_parallel_thinking_is_deterministic() {
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            let mut rng = GameRng::seed_from_u64(7);
+            let mut manager = AIManager::new();
+            manager.max_snakes = 24;
+            while manager.spawn_default_snake(&[], &mut rng) {}
+            // 眩晕的蛇决策时也会用到随机数
+            for snake in manager.snakes.iter_mut().step_by(3) {
+                snake.buff_state.activate_dizzy();
+            }
+            let registry = create_fruit_registry();
+            let player = Snake::new();
+            let mut fruits = vec![Fruit::new(ivec2(20, 4), "shield", 0.0, 0.0)];
+            let mut food = ivec2(3, 3);
+            let mut history = vec![];
+            for frame in 0..20 {
+                let game_time = frame as f32 * 0.1;
+                pool.install(|| {
+                    manager.update_thinking(
+                        food, &fruits, &registry, &[PlayerView { snake: &player, buffs: &BuffState::default() }], &HazardManager::new(), &[],
+                        Topology::Bounded, game_time, 0.1, &mut rng,
+                    )
+                });
+                manager.update_movement(
+                    &mut food, &mut fruits, &[PlayerView { snake: &player, buffs: &BuffState::default() }], &HazardManager::new(), &[], &mut vec![],
+                    &registry, Topology::Bounded, game_time, 0.1, &mut rng,
+                );
+                history.extend(manager.snakes.iter().map(|s| (s.id, s.head(), s.dir, s.target)));
+            }
+            history
+        };
+        let serial = run(1);
+        assert!(serial.len() > 100);
+        assert_eq!(serial, run(4));
+    }
 }
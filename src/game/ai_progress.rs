@@ -0,0 +1,163 @@
+//! AI 蛇成长
+//!
+//! AI 蛇吃东西和存活都会积累经验，升级后移动更快、反应更快（决策间隔更短），
+//! 身上出现与等级对应的条纹（颜色取自 [`AI_COLORS`]）。
+//! 存活足够久的 AI 蛇成为精英：再快一档，头上有金色标记，被玩家击杀时奖励额外分数。
+//!
+//! | 等级 | 所需经验 | 移动间隔 | 决策间隔 |
+//! |------|----------|----------|----------|
+//! | 1 | 0 | ×1.00 | 0.200s |
+//! | 2 | 40 | ×0.95 | 0.175s |
+//! | 3 | 100 | ×0.90 | 0.150s |
+//! | 4 | 200 | ×0.85 | 0.125s |
+//! | 5 | 350 | ×0.80 | 0.100s |
+
+use macroquad::prelude::*;
+use super::ai_snake::AI_COLORS;
+
+/// 各等级所需的累计经验（下标 0 为 1 级）
+pub const LEVEL_XP: [f32; 5] = [0.0, 40.0, 100.0, 200.0, 350.0];
+/// 最高等级
+pub const MAX_LEVEL: u32 = LEVEL_XP.len() as u32;
+/// 每存活一秒获得的经验
+pub const XP_PER_SECOND: f32 = 1.0;
+/// 吃到普通食物的经验
+pub const XP_FOOD: f32 = 10.0;
+/// 吃到果实的经验
+pub const XP_FRUIT: f32 = 15.0;
+/// 吃到掉落食物的经验
+pub const XP_DROPPED: f32 = 5.0;
+/// 成为精英所需的存活时间（秒）
+pub const ELITE_AGE: f32 = 90.0;
+/// 精英额外的移动间隔倍率
+pub const ELITE_SPEED: f32 = 0.9;
+/// 每级的击杀奖励
+pub const BOUNTY_PER_LEVEL: u32 = 5;
+/// 精英的额外击杀奖励
+pub const ELITE_BOUNTY: u32 = 50;
+
+/// 一条 AI 蛇的成长状态
+#[derive(Clone, Debug, PartialEq)]
+pub struct AIProgress {
+    /// 累计经验
+    pub xp: f32,
+    /// 当前等级（从 1 开始）
+    pub level: u32,
+    /// 存活时间（秒）
+    pub age: f32,
+}
+
+impl Default for AIProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AIProgress {
+    pub fn new() -> Self {
+        AIProgress { xp: 0.0, level: 1, age: 0.0 }
+    }
+
+    /// 获得经验，返回是否升级
+    pub fn gain(&mut self, xp: f32) -> bool {
+        self.xp += xp;
+        let level = LEVEL_XP.iter().filter(|&&need| self.xp >= need).count() as u32;
+        let leveled = level > self.level;
+        self.level = level.max(self.level);
+        leveled
+    }
+
+    /// 存活 `dt` 秒，返回是否升级
+    pub fn age_by(&mut self, dt: f32) -> bool {
+        self.age += dt;
+        self.gain(dt * XP_PER_SECOND)
+    }
+
+    /// 是否是精英
+    pub fn is_elite(&self) -> bool {
+        self.age >= ELITE_AGE
+    }
+
+    /// 移动间隔倍率（乘在 [`AISnake::get_tick`](super::AISnake::get_tick) 上）
+    pub fn tick_multiplier(&self) -> f32 {
+        let level = 1.0 - 0.05 * (self.level - 1) as f32;
+        if self.is_elite() { level * ELITE_SPEED } else { level }
+    }
+
+    /// 两次决策之间的间隔（秒）
+    pub fn think_interval(&self) -> f32 {
+        0.2 - 0.025 * (self.level - 1) as f32
+    }
+
+    /// 被玩家击杀时的奖励分数
+    pub fn bounty(&self) -> u32 {
+        let elite = if self.is_elite() { ELITE_BOUNTY } else { 0 };
+        self.level * BOUNTY_PER_LEVEL + elite
+    }
+
+    /// 到下一级的进度（0..=1，满级为 1）
+    pub fn level_progress(&self) -> f32 {
+        let i = self.level as usize;
+        match LEVEL_XP.get(i) {
+            Some(&next) => {
+                let prev = LEVEL_XP[i - 1];
+                ((self.xp - prev) / (next - prev)).clamp(0.0, 1.0)
+            }
+            None => 1.0,
+        }
+    }
+
+    /// 等级条纹颜色（1 级没有条纹），避开蛇身自己的颜色
+    pub fn marking_color(&self, body: Color) -> Option<Color> {
+        if self.level < 2 {
+            return None;
+        }
+        let start = (self.level - 2) as usize;
+        (0..AI_COLORS.len())
+            .map(|k| AI_COLORS[(start + k) % AI_COLORS.len()])
+            .find(|&c| c != body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_up_from_eating_and_surviving() {
+        let mut progress = AIProgress::new();
+        assert!(!progress.gain(XP_FOOD * 3.0));
+        assert!(progress.gain(XP_FOOD));
+        assert_eq!(progress.level, 2);
+
+        // 一次获得大量经验可以连升几级
+        assert!(progress.gain(250.0));
+        assert_eq!(progress.level, 4);
+
+        for _ in 0..100 {
+            progress.age_by(1.0);
+        }
+        assert_eq!(progress.level, MAX_LEVEL);
+        assert!(progress.is_elite());
+        assert_eq!(progress.level_progress(), 1.0);
+    }
+
+    #[test]
+    fn test_higher_tiers_are_faster_and_worth_more() {
+        let rookie = AIProgress::new();
+        let mut veteran = AIProgress::new();
+        veteran.gain(LEVEL_XP[2]);
+        assert!(veteran.tick_multiplier() < rookie.tick_multiplier());
+        assert!(veteran.think_interval() < rookie.think_interval());
+        assert!(veteran.bounty() > rookie.bounty());
+        assert_eq!(rookie.marking_color(ORANGE), None);
+
+        let marking = veteran.marking_color(AI_COLORS[1]).unwrap();
+        assert_ne!(marking, AI_COLORS[1]);
+
+        let mut elite = veteran.clone();
+        elite.age = ELITE_AGE;
+        assert!(elite.tick_multiplier() < veteran.tick_multiplier());
+        assert_eq!(elite.bounty(), veteran.bounty() + ELITE_BOUNTY);
+    }
+}
@@ -1,11 +1,17 @@
 //! AI 蛇模块
 //!
 //! 管理 AI 控制的蛇，包括移动、决策、Buff 状态等。
+//!
+//! 功能果实对 AI 蛇的效果与玩家相同：护盾/幽灵/沙虫状态下可以穿过自己并在墙边界环绕，
+//! 沙虫变身见 [`update_ai_sandworm`](super::update_ai_sandworm)。
 
+use std::collections::VecDeque;
 use macroquad::prelude::*;
 use ::rand::Rng;
-use crate::constants::{GRID_W, GRID_H};
-use crate::types::{Fruit, BuffState};
+use crate::constants::AI_SANDWORM_SPEED;
+use crate::fruits::FruitRegistry;
+use crate::types::{BoardDims, Fruit, BuffState, Grid, GridKind, Portal, PortalExit, SandwormPhase, Topology};
+use super::{check_diagonal_squeeze_on, AIBrain, AIObservation, AIProgress, BossState, OccupancyGrid, Snake, Team, TeamBoard};
 
 /// AI 蛇颜色池
 pub const AI_COLORS: [Color; 6] = [
@@ -18,19 +24,24 @@ pub const AI_COLORS: [Color; 6] = [
 ];
 
 /// AI 蛇结构体
-#[derive(Clone)]
 pub struct AISnake {
-    /// 蛇身位置列表，body[0] 是蛇头
-    pub body: Vec<IVec2>,
-    /// 上一帧位置（用于插值渲染）
-    pub prev_body: Vec<IVec2>,
+    /// 蛇身位置（环形缓冲），body[0] 是蛇头
+    pub body: VecDeque<IVec2>,
+    /// 上一步移走的尾巴（用于插值渲染）
+    pub prev_tail: IVec2,
+    /// 自己身体的占用网格
+    pub occupancy: OccupancyGrid,
     /// 当前移动方向
     pub dir: IVec2,
     /// 蛇的颜色
     pub color: Color,
     /// Buff 状态（独立于玩家）
     pub buff_state: BuffState,
-    /// 当前目标位置（食物/果实）
+    /// 决策逻辑
+    pub brain: Box<dyn AIBrain>,
+    /// 经验、等级和存活时间
+    pub progress: AIProgress,
+    /// 当前目标位置（由大脑报告，调试和渲染用）
     pub target: Option<IVec2>,
     /// 决策计时器
     pub think_timer: f32,
@@ -38,15 +49,40 @@ pub struct AISnake {
     pub move_accumulator: f32,
     /// 唯一标识符
     pub id: u32,
+    /// 所属队伍（None 表示各自为战）
+    pub team: Option<Team>,
+    /// 玩家的宠物蛇：吃到的东西给玩家加分，玩家撞上它时它死亡
+    pub pet: bool,
+    /// 宠物蛇的主人（玩家序号，本地多人时宠物蛇吃到的东西算主人的分）
+    pub owner: usize,
+    /// Boss 状态（只有 Boss 有，见 [`super::boss`]）
+    pub boss: Option<BossState>,
     /// 待增长节数
     pub grow_pending: u32,
+    /// 待完成的传送（下一步从出口出现）
+    pub pending_portal: Option<PortalExit>,
+    /// 棋盘几何
+    pub grid: GridKind,
+    /// 所在棋盘的范围
+    pub board: BoardDims,
 }
 
 impl AISnake {
     /// 创建新的 AI 蛇
-    pub fn new<R: Rng>(id: u32, occupied: &[IVec2], rng: &mut R) -> Option<Self> {
-        // 随机选择颜色
-        let color = AI_COLORS[rng.gen_range(0..AI_COLORS.len())];
+    ///
+    /// 蛇头出生在 `board` 内离边界至少 4 格的地方，最长的初始身体也不会伸出棋盘。
+    /// `occupied` 判断某格是否已被其他蛇占用，`brain` 负责这条蛇的决策；
+    /// 大脑有固定颜色（性格）时使用它，否则随机选色
+    pub fn new<R: Rng>(
+        grid: GridKind,
+        board: BoardDims,
+        id: u32,
+        brain: Box<dyn AIBrain>,
+        occupied: impl Fn(IVec2) -> bool,
+        rng: &mut R,
+    ) -> Option<Self> {
+        // 性格颜色或随机颜色
+        let color = brain.tint().unwrap_or_else(|| AI_COLORS[rng.gen_range(0..AI_COLORS.len())]);
         
         // 随机选择初始位置（避开已占用位置）
         let mut attempts = 0;
@@ -54,48 +90,96 @@ impl AISnake {
             if attempts > 100 {
                 return None; // 找不到合适位置
             }
-            let pos = ivec2(
-                rng.gen_range(3..GRID_W as i32 - 3),
-                rng.gen_range(3..GRID_H as i32 - 3),
+            let pos = board.origin + ivec2(
+                rng.gen_range(4..board.width - 4),
+                rng.gen_range(4..board.height - 4),
             );
-            if !occupied.contains(&pos) {
+            if !occupied(pos) {
                 break pos;
             }
             attempts += 1;
         };
         
         // 随机选择初始方向
-        let directions = [ivec2(1, 0), ivec2(-1, 0), ivec2(0, 1), ivec2(0, -1)];
-        let dir = directions[rng.gen_range(0..4)];
+        let directions = grid.directions();
+        let dir = directions[rng.gen_range(0..directions.len())];
         
-        // 初始长度 3-5 节
+        // 初始长度 3-5 节（身体沿反方向排开）
         let initial_len = rng.gen_range(3..=5);
-        let mut body = vec![head];
-        for i in 1..initial_len {
-            body.push(head - dir * i as i32);
+        let mut body = VecDeque::from([head]);
+        for _ in 1..initial_len {
+            let last = body[body.len() - 1];
+            body.push_back(grid.neighbor(last, -dir));
         }
         
-        Some(AISnake {
-            prev_body: body.clone(),
+        Some(Self::with_body(grid, board, id, brain, body, dir, color))
+    }
+
+    /// 在指定格子上孵化一条只有蛇头的 AI 蛇（宠物蛋孵化）
+    pub fn hatch(
+        grid: GridKind,
+        board: BoardDims,
+        id: u32,
+        brain: Box<dyn AIBrain>,
+        head: IVec2,
+        dir: IVec2,
+        color: Color,
+    ) -> Self {
+        Self::with_body(grid, board, id, brain, VecDeque::from([head]), dir, color)
+    }
+
+    fn with_body(
+        grid: GridKind,
+        board: BoardDims,
+        id: u32,
+        brain: Box<dyn AIBrain>,
+        body: VecDeque<IVec2>,
+        dir: IVec2,
+        color: Color,
+    ) -> Self {
+        AISnake {
+            prev_tail: body[body.len() - 1],
+            occupancy: OccupancyGrid::from_cells(board.end().x, board.end().y, &body),
             body,
             dir,
             color,
             buff_state: BuffState::default(),
+            brain,
+            progress: AIProgress::new(),
             target: None,
             think_timer: 0.0,
             move_accumulator: 0.0,
             id,
+            team: None,
+            pet: false,
+            owner: 0,
+            boss: None,
             grow_pending: 0,
-        })
+            pending_portal: None,
+            grid,
+            board,
+        }
     }
     
-    /// 计算移动间隔（基于蛇长度）
+    /// 计算移动间隔（基于蛇长度和等级，Boss 按阶段）
     pub fn get_tick(&self) -> f32 {
         let len = self.body.len() as u32;
         // 基础 tick: 150ms，比玩家稍慢
         let base_tick = ((150u32.saturating_sub((len / 3) * 5)).max(60)) as f32 / 1000.0;
-        // 应用 Buff 倍率
-        base_tick * self.buff_state.tick_multiplier()
+        // 应用等级和 Buff 倍率，沙虫横冲时更快
+        let sandworm = if self.buff_state.sandworm_phase == SandwormPhase::Rampaging { AI_SANDWORM_SPEED } else { 1.0 };
+        let boss = self.boss.as_ref().map_or(1.0, |b| b.phase.tick_multiplier());
+        base_tick * self.progress.tick_multiplier() * self.buff_state.tick_multiplier() * sandworm * boss
+    }
+
+    /// 是否是钻在地下的 Boss（碰不到也伤不到）
+    pub fn burrowed(&self) -> bool {
+        self.boss.as_ref().is_some_and(|b| b.burrowed())
+    }
+
+    /// 血量：蛇身节数加上还没长出来的节数（Boss 血条使用）
+    pub fn health(&self) -> usize {
+        self.body.len() + self.grow_pending as usize
     }
     
     /// 获取蛇头位置
@@ -112,208 +196,217 @@ impl AISnake {
     pub fn grow(&mut self) {
         self.grow_pending += 1;
     }
+
+    /// 第 `i` 节上一步所在的位置（用于插值渲染）
+    pub fn prev_segment(&self, i: usize) -> IVec2 {
+        self.body.get(i + 1).copied().unwrap_or(self.prev_tail)
+    }
+
+    /// 截断到指定长度，返回被移除的格子
+    pub fn truncate(&mut self, len: usize) -> Vec<IVec2> {
+        let removed: Vec<IVec2> = self.body.drain(len.min(self.body.len())..).collect();
+        for &pos in &removed {
+            self.occupancy.remove(pos);
+        }
+        removed
+    }
+
+    /// 头尾反转，方向改为新蛇头的朝向
+    pub fn reverse(&mut self) {
+        self.body.make_contiguous().reverse();
+        self.prev_tail = self.body[self.body.len() - 1];
+        self.pending_portal = None;
+        // 跨越环绕边界时无法推断，直接掉头
+        if self.body.len() > 1 {
+            self.dir = self.grid.direction_between(self.body[1], self.body[0]).unwrap_or(-self.dir);
+        }
+    }
     
     /// AI 决策：选择下一个方向
-    pub fn think<R: Rng>(
-        &mut self,
-        food: IVec2,
-        fruits: &[Fruit],
-        player_body: &[IVec2],
-        other_ai_bodies: &[Vec<IVec2>],
-        wrap: bool,
-        rng: &mut R,
-    ) {
-        // 冰冻状态不做决策
-        if self.buff_state.frozen {
-            return;
+    ///
+    /// 等价于 [`decide`](Self::decide) 之后立即 [`apply`](Self::apply)
+    pub fn think<R: Rng>(&mut self, world: &AIWorld, hazard_cells: &[IVec2], rng: &mut R) {
+        if let Some(decision) = self.decide(world, hazard_cells, rng) {
+            self.apply(decision);
         }
-        
+    }
+
+    /// 决策阶段：只读取世界快照，只改动自己的大脑状态，可以和其他蛇并行执行
+    ///
+    /// 冰冻或正在变身沙虫时不决策（返回 None），眩晕时有概率随机转向；其余交给大脑。
+    /// `hazard_cells` 是障碍物在本蛇下一个 tick 时的预测位置
+    pub fn decide<R: Rng>(&mut self, world: &AIWorld, hazard_cells: &[IVec2], rng: &mut R) -> Option<AIDecision> {
+        if self.buff_state.frozen || self.buff_state.sandworm_transforming() {
+            return None;
+        }
+
         // 眩晕状态随机方向
         if self.buff_state.dizzy_active && rng.gen_bool(0.4) {
-            let directions = [ivec2(0, -1), ivec2(0, 1), ivec2(-1, 0), ivec2(1, 0)];
-            let valid: Vec<_> = directions.iter()
-                .filter(|&&d| d != -self.dir)
+            let valid: Vec<_> = self.grid.directions().iter()
+                .filter(|&&d| !self.grid.is_reverse(d, self.dir))
                 .collect();
-            if !valid.is_empty() {
-                self.dir = *valid[rng.gen_range(0..valid.len())];
-            }
-            return;
+            let dir = (!valid.is_empty()).then(|| *valid[rng.gen_range(0..valid.len())]);
+            return Some(AIDecision { dir, target: self.target });
         }
-        
-        // 寻找最近的目标
+
+        // 盯住最近的一名玩家（本地多人时每名玩家都是目标），所有玩家的身体都算阻挡
         let head = self.head();
-        let mut best_target: Option<IVec2> = None;
-        let mut best_dist = i32::MAX;
-        
-        // 检查普通食物
-        let dist = (food.x - head.x).abs() + (food.y - head.y).abs();
-        if dist < best_dist {
-            best_dist = dist;
-            best_target = Some(food);
-        }
-        
-        // 检查果实（只考虑非陷阱果实，但 AI 不够聪明会吃到陷阱）
-        for fruit in fruits {
-            let dist = (fruit.pos.x - head.x).abs() + (fruit.pos.y - head.y).abs();
-            if dist < best_dist {
-                best_dist = dist;
-                best_target = Some(fruit.pos);
-            }
-        }
-        
-        self.target = best_target;
-        
-        // 根据目标选择方向
-        let desired_dir = if let Some(target) = self.target {
-            let dx = target.x - head.x;
-            let dy = target.y - head.y;
-            
-            // 优先选择距离更远的轴向
-            if dx.abs() > dy.abs() {
-                if dx > 0 { ivec2(1, 0) } else { ivec2(-1, 0) }
-            } else if dy != 0 {
-                if dy > 0 { ivec2(0, 1) } else { ivec2(0, -1) }
-            } else {
-                self.dir
-            }
-        } else {
-            // 没有目标，随机转向
-            if rng.gen_bool(0.1) {
-                let directions = [ivec2(0, -1), ivec2(0, 1), ivec2(-1, 0), ivec2(1, 0)];
-                directions[rng.gen_range(0..4)]
-            } else {
-                self.dir
-            }
+        let focus = world
+            .players
+            .iter()
+            .filter_map(|p| Some((self.grid.distance(head, *p.snake.body.front()?), p)))
+            .min_by_key(|&(d, _)| d)
+            .map(|(_, p)| p);
+        let idle = BuffState::default();
+        let obs = AIObservation {
+            id: self.id,
+            body: &self.body,
+            occupancy: &self.occupancy,
+            dir: self.dir,
+            buff_state: &self.buff_state,
+            tick: self.get_tick(),
+            game_time: world.game_time,
+            grid: self.grid,
+            topology: world.topology,
+            board: self.board,
+            food: world.food,
+            fruits: world.fruits,
+            registry: world.registry,
+            player: world.player_cells,
+            player_head: focus.and_then(|p| p.snake.body.front().copied()),
+            player_tail: focus.and_then(|p| p.snake.body.back().copied()),
+            player_dir: focus.map_or(ivec2(1, 0), |p| p.snake.dir),
+            player_buffs: focus.map_or(&idle, |p| p.buffs),
+            ai_occupancy: world.ai_occupancy,
+            hazard_cells,
+            portals: world.portals,
+            teams: world.teams,
         };
-        
-        // 检查期望方向是否安全
-        let new_dir = self.find_safe_direction(
-            desired_dir,
-            player_body,
-            other_ai_bodies,
-            wrap,
-            rng,
-        );
-        
-        // 不能反向
-        if new_dir != -self.dir {
-            self.dir = new_dir;
-        }
+        let dir = self.brain.decide(&obs, rng);
+        Some(AIDecision { dir, target: self.brain.target() })
     }
-    
-    /// 寻找安全的移动方向
-    fn find_safe_direction<R: Rng>(
-        &self,
-        preferred: IVec2,
-        player_body: &[IVec2],
-        other_ai_bodies: &[Vec<IVec2>],
-        wrap: bool,
-        rng: &mut R,
-    ) -> IVec2 {
-        let directions = [preferred, ivec2(0, -1), ivec2(0, 1), ivec2(-1, 0), ivec2(1, 0)];
-        
-        for &dir in &directions {
-            if dir == -self.dir {
-                continue;
-            }
-            
-            let next_pos = self.head() + dir;
-            if self.is_position_safe(next_pos, player_body, other_ai_bodies, wrap) {
-                return dir;
-            }
-        }
-        
-        // 没有安全方向，随机选一个（可能会死）
-        let valid: Vec<_> = directions.iter()
-            .filter(|&&d| d != -self.dir)
-            .collect();
-        if !valid.is_empty() {
-            *valid[rng.gen_range(0..valid.len())]
-        } else {
-            self.dir
+
+    /// 应用阶段：转向并记录目标（不能反向；没有决定时保持方向）
+    pub fn apply(&mut self, decision: AIDecision) {
+        self.target = decision.target;
+        if let Some(dir) = decision.dir
+            && !self.grid.is_reverse(dir, self.dir)
+        {
+            self.dir = dir;
         }
     }
-    
-    /// 检查位置是否安全
-    fn is_position_safe(
-        &self,
-        pos: IVec2,
-        player_body: &[IVec2],
-        other_ai_bodies: &[Vec<IVec2>],
-        wrap: bool,
-    ) -> bool {
-        let check_pos = if wrap {
-            ivec2(
-                pos.x.rem_euclid(GRID_W as i32),
-                pos.y.rem_euclid(GRID_H as i32),
-            )
-        } else {
-            if pos.x < 0 || pos.x >= GRID_W as i32 || pos.y < 0 || pos.y >= GRID_H as i32 {
-                return false;
-            }
-            pos
-        };
-        
-        // 检查自己身体（除了尾巴）
-        for segment in self.body.iter().take(self.body.len().saturating_sub(1)) {
-            if *segment == check_pos {
-                return false;
-            }
-        }
-        
-        // 检查玩家身体
-        if player_body.contains(&check_pos) {
-            return false;
-        }
-        
-        // 检查其他 AI 蛇身体
-        for other_body in other_ai_bodies {
-            if other_body.contains(&check_pos) {
-                return false;
-            }
-        }
-        
-        true
+
+    /// 某格是否被自己除尾巴以外的身体占用（尾巴这一步会移走）
+    fn body_blocks(&self, pos: IVec2) -> bool {
+        let tail = self.body[self.body.len() - 1];
+        self.occupancy.count(pos) > u16::from(pos == tail)
     }
-    
+
     /// 移动 AI 蛇
-    pub fn move_forward(&mut self, wrap: bool) -> AIMoveResult {
-        // 冰冻状态不移动
-        if self.buff_state.frozen {
+    ///
+    /// 自己的占用网格同步更新；没有增长时移走的尾巴记录在 `prev_tail`。
+    /// 护盾/幽灵/沙虫状态下可以穿过自己，墙边界按圆环面环绕
+    pub fn move_forward(&mut self, topology: Topology) -> AIMoveResult {
+        self.move_forward_among(topology, |_| false)
+    }
+
+    /// 在其他蛇之间移动 AI 蛇，`others` 判断某格是否有会撞死自己的别的蛇身
+    ///
+    /// 斜向挤过的两侧格子只要都有蛇身（自己的或别的蛇）就返回 [`AIMoveResult::Squeezed`]
+    pub fn move_forward_among(&mut self, topology: Topology, others: impl Fn(IVec2) -> bool) -> AIMoveResult {
+        // 冰冻或正在变身沙虫时不移动
+        if self.buff_state.frozen || self.buff_state.sandworm_transforming() {
             return AIMoveResult::Frozen;
         }
+        let can_pass_self = self.buff_state.can_pass_through();
+        let board = self.board;
         
-        // 保存上一帧位置
-        self.prev_body = self.body.clone();
-        
-        let mut new_head = self.head() + self.dir;
+        // 斜向从两节蛇身之间挤过（八方向模式）
+        let squeezed = self.pending_portal.is_none()
+            && check_diagonal_squeeze_on(self.grid, topology, board, self.head(), self.dir, |p| {
+                self.occupancy.is_occupied(p) || others(p)
+            });
+
+        // 上一步踏入了传送门入口：这一步从出口出现
+        let head = self.head();
+        let stepped = match self.pending_portal.take() {
+            Some(exit) => Some((exit.to, exit.dir)),
+            None => self.grid
+                .step_on(topology, board, head, self.dir)
+                .or_else(|| can_pass_self.then(|| self.grid.step_on(Topology::Torus, board, head, self.dir)).flatten()),
+        };
         
-        // 处理边界
-        if wrap {
-            new_head.x = new_head.x.rem_euclid(GRID_W as i32);
-            new_head.y = new_head.y.rem_euclid(GRID_H as i32);
-        } else if new_head.x < 0 || new_head.x >= GRID_W as i32 
-               || new_head.y < 0 || new_head.y >= GRID_H as i32 {
+        // 处理边界（镜像环绕会翻转方向）
+        let Some((new_head, new_dir)) = stepped else {
             return AIMoveResult::WallCollision;
-        }
+        };
+        self.dir = new_dir;
         
         // 检查自身碰撞
-        if self.body.iter().take(self.body.len().saturating_sub(1)).any(|&p| p == new_head) {
+        if !can_pass_self && squeezed {
+            return AIMoveResult::Squeezed;
+        }
+        if !can_pass_self && self.body_blocks(new_head) {
             return AIMoveResult::SelfCollision;
         }
         
         // 移动
-        self.body.insert(0, new_head);
+        self.body.push_front(new_head);
+        self.occupancy.add(new_head);
         if self.grow_pending > 0 {
             self.grow_pending -= 1;
-        } else {
-            self.body.pop();
+        } else if let Some(tail) = self.body.pop_back() {
+            self.occupancy.remove(tail);
+            self.prev_tail = tail;
         }
         
         AIMoveResult::Normal(new_head)
     }
 }
 
+/// 一轮决策中所有 AI 蛇共享的只读世界快照
+#[derive(Clone, Copy)]
+pub struct AIWorld<'a> {
+    /// 普通食物位置
+    pub food: IVec2,
+    /// 场上的果实
+    pub fruits: &'a [Fruit],
+    /// 果实注册表
+    pub registry: &'a FruitRegistry,
+    /// 场上的玩家（本地多人时不止一名，倒下的玩家不在其中）
+    pub players: &'a [PlayerView<'a>],
+    /// 所有玩家蛇身的占用网格
+    pub player_cells: &'a OccupancyGrid,
+    /// 所有 AI 蛇（包括自己）的共享占用网格
+    pub ai_occupancy: &'a OccupancyGrid,
+    /// 场上的传送门（寻路会穿过）
+    pub portals: &'a [Portal],
+    /// 所有 AI 蛇的队伍快照
+    pub teams: &'a TeamBoard,
+    /// 地图拓扑
+    pub topology: Topology,
+    /// 游戏时间
+    pub game_time: f32,
+}
+
+/// AI 看到的一名玩家
+#[derive(Clone, Copy)]
+pub struct PlayerView<'a> {
+    /// 玩家蛇
+    pub snake: &'a Snake,
+    /// 玩家的 Buff 状态
+    pub buffs: &'a BuffState,
+}
+
+/// 一条 AI 蛇的决策结果
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct AIDecision {
+    /// 下一步方向（None 保持当前方向）
+    pub dir: Option<IVec2>,
+    /// 当前追逐的目标
+    pub target: Option<IVec2>,
+}
+
 /// AI 蛇移动结果
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AIMoveResult {
@@ -323,6 +416,128 @@ pub enum AIMoveResult {
     WallCollision,
     /// 撞自己
     SelfCollision,
+    /// 斜向从两节蛇身之间挤过（八方向模式，不论属于哪条蛇）
+    Squeezed,
     /// 冰冻状态
     Frozen,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{GRID_W, GRID_H};
+    use ::rand::SeedableRng;
+    use crate::fruits::create_fruit_registry;
+    use crate::game::{BrainKind, GameRng};
+
+    /// 按给定身体创建 AI 蛇
+    fn snake_with_body(cells: &[IVec2], dir: IVec2) -> AISnake {
+        let mut rng = GameRng::seed_from_u64(1);
+        let brain = BrainKind::Pathfinding.create();
+        let mut snake = AISnake::new(GridKind::Square, BoardDims::DEFAULT, 1, brain, |_| false, &mut rng).unwrap();
+        snake.body = cells.iter().copied().collect();
+        snake.prev_tail = cells[cells.len() - 1];
+        snake.occupancy = OccupancyGrid::from_cells(GRID_W, GRID_H, cells);
+        snake.dir = dir;
+        snake
+    }
+
+    fn think(snake: &mut AISnake, food: IVec2, hazard_cells: &[IVec2], topology: Topology) {
+        // 玩家停在远处的角落
+        let mut player = Snake::new();
+        player.set_body([ivec2(25, 20), ivec2(26, 20), ivec2(27, 20)]);
+        let shared = snake.occupancy.clone();
+        let registry = create_fruit_registry();
+        let world = AIWorld {
+            food,
+            fruits: &[],
+            registry: &registry,
+            players: &[PlayerView { snake: &player, buffs: &BuffState::default() }],
+            player_cells: &player.occupancy,
+            ai_occupancy: &shared,
+            portals: &[],
+            teams: &TeamBoard::default(),
+            topology,
+            game_time: 0.0,
+        };
+        let mut rng = GameRng::seed_from_u64(2);
+        snake.think(&world, hazard_cells, &mut rng);
+    }
+
+    #[test]
+    fn test_refuses_pocket_smaller_than_body_and_chases_tail() {
+        // 蛇头 (4,1) 向上，身体竖直向下共 8 节；食物在上方 5 格的死胡同里
+        let body: Vec<IVec2> = (1..=8).map(|y| ivec2(4, y)).collect();
+        let mut snake = snake_with_body(&body, ivec2(0, -1));
+        let walls = [ivec2(0, 1), ivec2(1, 1), ivec2(2, 1), ivec2(3, 1), ivec2(5, 0)];
+
+        think(&mut snake, ivec2(0, 0), &walls, Topology::Bounded);
+        assert_eq!(snake.target, None);
+        assert_eq!(snake.dir, ivec2(1, 0));
+
+        // 蛇短到能装进死胡同时照常去吃
+        let mut short = snake_with_body(&body[..3], ivec2(0, -1));
+        think(&mut short, ivec2(0, 0), &walls, Topology::Bounded);
+        assert_eq!(short.target, Some(ivec2(0, 0)));
+        assert_eq!(short.dir, ivec2(0, -1));
+    }
+
+    #[test]
+    fn test_paths_through_wrapping_edge() {
+        let body = [ivec2(1, 5), ivec2(2, 5), ivec2(3, 5)];
+        let food = ivec2(GRID_W - 2, 5);
+
+        let mut snake = snake_with_body(&body, ivec2(-1, 0));
+        think(&mut snake, food, &[], Topology::Torus);
+        assert_eq!(snake.target, Some(food));
+        assert_eq!(snake.dir, ivec2(-1, 0));
+
+        // 有墙时绕开自己的身体从上方或下方走
+        let mut snake = snake_with_body(&body, ivec2(-1, 0));
+        think(&mut snake, food, &[], Topology::Bounded);
+        assert_eq!(snake.dir.x, 0);
+    }
+
+    #[test]
+    fn test_power_effects_match_player() {
+        let body = [ivec2(0, 5), ivec2(1, 5), ivec2(2, 5)];
+        let mut snake = snake_with_body(&body, ivec2(-1, 0));
+        assert_eq!(snake.move_forward(Topology::Bounded), AIMoveResult::WallCollision);
+
+        // 护盾：穿墙环绕
+        let mut snake = snake_with_body(&body, ivec2(-1, 0));
+        snake.buff_state.activate_shield();
+        assert_eq!(snake.move_forward(Topology::Bounded), AIMoveResult::Normal(ivec2(GRID_W - 1, 5)));
+
+        // 反转后朝新蛇头方向前进
+        let mut snake = snake_with_body(&body, ivec2(-1, 0));
+        snake.reverse();
+        assert_eq!(snake.dir, ivec2(1, 0));
+        assert_eq!(snake.move_forward(Topology::Bounded), AIMoveResult::Normal(ivec2(3, 5)));
+
+        // 沙虫变身期间原地不动
+        snake.buff_state.activate_sandworm();
+        assert_eq!(snake.move_forward(Topology::Bounded), AIMoveResult::Frozen);
+    }
+
+    #[test]
+    fn test_octile_squeeze_between_own_and_other_snake() {
+        // 右侧 (6,5) 是自己的身体，上方 (5,4) 是别的蛇
+        let body = [ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)];
+        let other = |p: IVec2| p == ivec2(5, 4);
+        let mut snake = snake_with_body(&body, ivec2(1, -1));
+        snake.grid = GridKind::Octile;
+        assert_eq!(snake.move_forward_among(Topology::Bounded, other), AIMoveResult::Squeezed);
+
+        // 只看自己的身体时只有一侧被占
+        let mut snake = snake_with_body(&body, ivec2(1, -1));
+        snake.grid = GridKind::Octile;
+        assert_eq!(snake.move_forward(Topology::Bounded), AIMoveResult::Normal(ivec2(6, 4)));
+
+        // 幽灵状态挤过去
+        let mut snake = snake_with_body(&body, ivec2(1, -1));
+        snake.grid = GridKind::Octile;
+        snake.buff_state.activate_ghost();
+        assert_eq!(snake.move_forward_among(Topology::Bounded, other), AIMoveResult::Normal(ivec2(6, 4)));
+    }
+}
@@ -0,0 +1,220 @@
+//! Boss 战
+//!
+//! Boss 是一条巨大的 AI 蛇（复用 [`AISnake`](super::AISnake) 的移动），由 [`AIManager`](super::AIManager) 驱动：
+//!
+//! - 血量就是蛇身节数（含还没钻出来的部分），少于 [`BOSS_MIN_HEALTH`] 节时被击败
+//! - 蛇头是弱点：玩家蛇头撞上 Boss 蛇头扣 [`BOSS_HEAD_DAMAGE`] 节并让它僵直一会儿；
+//!   切割规则下还能切断它的身体
+//! - 刀枪不入：穿过墙和其他蛇，碾过玩家的身体（经典规则下玩家死亡，切割规则下切断玩家）
+//! - 按固定剧本循环四个阶段，见 [`BossPhase`]
+//!
+//! Boss 在分数达到阈值时登场（[`BossSchedule`]），同时只有一条；击败后奖励 [`BOSS_REWARD`] 分，
+//! 整条身体变成掉落食物。
+
+use super::ai_brain::BrainKind;
+
+/// Boss 的初始血量（蛇身节数）
+pub const BOSS_HEALTH: usize = 24;
+/// 血量低于这个值时 Boss 被击败
+pub const BOSS_MIN_HEALTH: usize = 4;
+/// 撞中弱点（蛇头）造成的伤害（节数）
+pub const BOSS_HEAD_DAMAGE: usize = 5;
+/// 被撞中弱点后的僵直时间（秒），期间不动也不再受伤
+pub const BOSS_STUN: f32 = 1.5;
+/// 击败 Boss 的奖励分数
+pub const BOSS_REWARD: u32 = 100;
+/// 召唤阶段最多同时存在的小兵数
+pub const BOSS_MINIONS: usize = 2;
+/// 布雷阶段每隔多久在蛇尾留下一个陷阱果实（秒）
+pub const BOSS_TRAP_INTERVAL: f32 = 0.8;
+/// Boss 的大脑（追猎玩家）
+pub const BOSS_BRAIN: BrainKind = BrainKind::Hunter;
+/// 竞技场模式下 Boss 登场的分数阈值（地图自己的阈值之外）
+pub const ARENA_BOSS_SCORES: [u32; 3] = [80, 200, 400];
+
+/// Boss 阶段（按 [`BossPhase::SCRIPT`] 的顺序循环）
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BossPhase {
+    /// 冲锋：移动速度大幅提高，直扑玩家
+    Charge,
+    /// 召唤：生成小兵 AI 蛇
+    Summon,
+    /// 布雷：沿途在蛇尾留下陷阱果实
+    Traps,
+    /// 钻地：像沙虫一样潜入地下，碰不到也伤不到，到玩家附近再钻出来
+    Burrow,
+}
+
+impl BossPhase {
+    /// 阶段剧本：(阶段, 持续时间)
+    pub const SCRIPT: [(BossPhase, f32); 4] = [
+        (BossPhase::Charge, 6.0),
+        (BossPhase::Summon, 1.5),
+        (BossPhase::Traps, 5.0),
+        (BossPhase::Burrow, 4.0),
+    ];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            BossPhase::Charge => "Charge",
+            BossPhase::Summon => "Summon",
+            BossPhase::Traps => "Traps",
+            BossPhase::Burrow => "Burrow",
+        }
+    }
+
+    /// 阶段持续时间（秒）
+    pub fn duration(&self) -> f32 {
+        Self::SCRIPT.iter().find(|(p, _)| p == self).map_or(0.0, |&(_, d)| d)
+    }
+
+    /// 剧本中的下一个阶段
+    pub fn next(&self) -> BossPhase {
+        let i = Self::SCRIPT.iter().position(|(p, _)| p == self).unwrap_or(0);
+        Self::SCRIPT[(i + 1) % Self::SCRIPT.len()].0
+    }
+
+    /// 移动间隔倍率（Boss 体型大，平时比普通 AI 蛇慢）
+    pub fn tick_multiplier(&self) -> f32 {
+        match self {
+            BossPhase::Charge => 0.55,
+            BossPhase::Burrow => 0.8,
+            BossPhase::Summon | BossPhase::Traps => 1.3,
+        }
+    }
+}
+
+/// Boss 事件（随 [`AIUpdateResult`](super::AIUpdateResult) 报告）
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BossEvent {
+    /// Boss 登场
+    Appeared,
+    /// 进入新阶段
+    Phase(BossPhase),
+    /// 弱点被撞中，剩余血量
+    Hit { health: usize },
+    /// Boss 被击败
+    Defeated { reward: u32 },
+}
+
+/// 一条 AI 蛇的 Boss 状态（[`AISnake::boss`](super::AISnake::boss)）
+#[derive(Clone, Debug, PartialEq)]
+pub struct BossState {
+    /// 当前阶段
+    pub phase: BossPhase,
+    /// 当前阶段已经持续的时间
+    pub phase_timer: f32,
+    /// 布雷计时器
+    pub trap_timer: f32,
+    /// 最大血量（血条满格）
+    pub max_health: usize,
+    /// 召唤出来的小兵 ID
+    pub minions: Vec<u32>,
+}
+
+impl Default for BossState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BossState {
+    /// 从冲锋阶段开始
+    pub fn new() -> Self {
+        BossState {
+            phase: BossPhase::Charge,
+            phase_timer: 0.0,
+            trap_timer: 0.0,
+            max_health: BOSS_HEALTH,
+            minions: Vec::new(),
+        }
+    }
+
+    /// 推进阶段计时，返回这一帧进入的新阶段
+    pub fn update(&mut self, dt: f32) -> Option<BossPhase> {
+        self.phase_timer += dt;
+        if self.phase_timer < self.phase.duration() {
+            return None;
+        }
+        self.phase_timer = 0.0;
+        self.trap_timer = 0.0;
+        self.phase = self.phase.next();
+        Some(self.phase)
+    }
+
+    /// 布雷阶段推进布雷计时，返回这一帧是否该留下陷阱
+    pub fn trap_due(&mut self, dt: f32) -> bool {
+        if self.phase != BossPhase::Traps {
+            return false;
+        }
+        self.trap_timer += dt;
+        if self.trap_timer < BOSS_TRAP_INTERVAL {
+            return false;
+        }
+        self.trap_timer -= BOSS_TRAP_INTERVAL;
+        true
+    }
+
+    /// 是否钻在地下
+    pub fn burrowed(&self) -> bool {
+        self.phase == BossPhase::Burrow
+    }
+}
+
+/// Boss 登场时间表：分数第一次达到每个阈值时登场一次
+#[derive(Clone, Debug, Default)]
+pub struct BossSchedule {
+    /// 从小到大排好的分数阈值
+    thresholds: Vec<u32>,
+    /// 下一个还没触发的阈值
+    next: usize,
+}
+
+impl BossSchedule {
+    pub fn new(mut thresholds: Vec<u32>) -> Self {
+        thresholds.sort_unstable();
+        BossSchedule { thresholds, next: 0 }
+    }
+
+    /// 分数是否刚越过下一个阈值（每个阈值只触发一次，一帧越过多个也只触发一次）
+    pub fn reached(&mut self, score: u32) -> bool {
+        let passed = self.thresholds[self.next..].iter().take_while(|&&t| score >= t).count();
+        self.next += passed;
+        passed > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_follow_script() {
+        let mut boss = BossState::new();
+        let mut seen = vec![boss.phase];
+        for _ in 0..4 {
+            let entered = boss.update(boss.phase.duration());
+            seen.push(entered.unwrap());
+        }
+        assert_eq!(seen, [BossPhase::Charge, BossPhase::Summon, BossPhase::Traps, BossPhase::Burrow, BossPhase::Charge]);
+        assert_eq!(boss.update(0.1), None);
+
+        // 只在布雷阶段留陷阱
+        assert!(!boss.trap_due(BOSS_TRAP_INTERVAL));
+        boss.phase = BossPhase::Traps;
+        assert!(!boss.trap_due(BOSS_TRAP_INTERVAL / 2.0));
+        assert!(boss.trap_due(BOSS_TRAP_INTERVAL / 2.0));
+    }
+
+    #[test]
+    fn test_schedule_triggers_each_threshold_once() {
+        let mut schedule = BossSchedule::new(vec![200, 50]);
+        assert!(!schedule.reached(49));
+        assert!(schedule.reached(50));
+        assert!(!schedule.reached(120));
+        assert!(schedule.reached(500));
+        assert!(!schedule.reached(900));
+        assert!(BossSchedule::new(vec![0]).reached(0));
+    }
+}
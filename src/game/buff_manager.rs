@@ -2,7 +2,7 @@
 //!
 //! 提供 BuffState 的更新和激活方法
 
-use crate::types::BuffState;
+use crate::types::{BuffState, SandwormPhase};
 use crate::constants::*;
 
 /// BuffState 的扩展方法
@@ -104,6 +104,28 @@ impl BuffState {
         None
     }
 
+    /// 更新宠物蛋在体内的移动
+    /// 返回 true 如果蛋到达蛇尾，应该孵化
+    pub fn update_egg(&mut self, dt: f32, snake_len: usize) -> bool {
+        if !self.egg_state.active {
+            return false;
+        }
+
+        self.egg_state.move_timer += dt;
+        if self.egg_state.move_timer >= self.egg_state.move_interval {
+            self.egg_state.move_timer -= self.egg_state.move_interval;
+            self.egg_state.position += 1;
+        }
+
+        // 蛇身可能在孵化途中变短，蛋直接到了蛇尾
+        if self.egg_state.should_hatch(snake_len) {
+            self.egg_state.clear();
+            return true;
+        }
+
+        false
+    }
+
     /// 激活护盾
     pub fn activate_shield(&mut self) {
         self.shield_active = true;
@@ -122,6 +144,20 @@ impl BuffState {
         self.ghost_timer = GHOST_DURATION;
     }
 
+    /// 激活沙虫变身（从闪烁阶段开始）
+    pub fn activate_sandworm(&mut self) {
+        self.sandworm_active = true;
+        self.sandworm_phase = SandwormPhase::Flashing;
+        self.sandworm_phase_timer = 0.0;
+        self.sandworm_transform_index = 0;
+        self.sandworm_tick = 0.0;
+    }
+
+    /// 是否正在原地变身（闪烁或变色阶段，不能移动）
+    pub fn sandworm_transforming(&self) -> bool {
+        matches!(self.sandworm_phase, SandwormPhase::Flashing | SandwormPhase::Transforming)
+    }
+
     /// 激活冰冻
     pub fn activate_freeze(&mut self) {
         if !self.has_immunity() {
@@ -222,6 +258,25 @@ mod tests {
         buff.speed_active = false;
         assert_eq!(buff.tick_multiplier(), 2.0);
     }
+
+    #[test]
+    fn test_egg_hatches_at_tail() {
+        let mut buff = BuffState::default();
+        buff.egg_state.activate();
+        let interval = buff.egg_state.move_interval;
+        for _ in 0..3 {
+            assert!(!buff.update_egg(interval, 5));
+        }
+        assert_eq!(buff.egg_state.position, 3);
+        assert!(buff.update_egg(interval, 5));
+        assert!(!buff.egg_state.active);
+
+        // 蛇身变短时提前孵化
+        buff.egg_state.activate();
+        buff.update_egg(interval, 8);
+        buff.update_egg(interval, 8);
+        assert!(buff.update_egg(0.0, 3));
+    }
 }
 
 
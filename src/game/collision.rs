@@ -4,7 +4,8 @@
 
 use macroquad::prelude::*;
 use crate::constants::{GRID_W, GRID_H};
-use crate::types::{Fruit, Portal};
+use crate::types::{BoardDims, Fruit, Grid, GridKind, Portal, PortalExit, Topology};
+use super::HazardManager;
 
 /// 检查是否撞墙
 ///
@@ -22,6 +23,46 @@ pub fn check_wall_collision(pos: IVec2, wrap: bool) -> bool {
     }
 }
 
+/// 按地图拓扑检查是否撞墙
+///
+/// `pos` 为未经环绕处理的原始位置
+pub fn check_topology_collision(pos: IVec2, topology: Topology) -> bool {
+    topology.is_wall(pos)
+}
+
+/// 检查斜向移动是否从两节蛇身之间挤过
+///
+/// 从 `from` 沿 `dir` 斜向移动时，两侧的两个正交格都被占据则视为碰撞。
+/// 墙外的格子不算占据；非斜向几何永远返回 false。
+///
+/// # 参数
+/// - `occupied`: 判断某格（已映射回地图内）是否有蛇身
+pub fn check_diagonal_squeeze(
+    grid: GridKind,
+    topology: Topology,
+    from: IVec2,
+    dir: IVec2,
+    occupied: impl Fn(IVec2) -> bool,
+) -> bool {
+    check_diagonal_squeeze_on(grid, topology, BoardDims::DEFAULT, from, dir, occupied)
+}
+
+/// 在 `board` 上检查斜向移动是否从两节蛇身之间挤过（见 [`check_diagonal_squeeze`]）
+pub fn check_diagonal_squeeze_on(
+    grid: GridKind,
+    topology: Topology,
+    board: BoardDims,
+    from: IVec2,
+    dir: IVec2,
+    occupied: impl Fn(IVec2) -> bool,
+) -> bool {
+    grid.corner_cells(from, dir).is_some_and(|cells| {
+        cells
+            .iter()
+            .all(|&c| topology.wrap_pos_on(board, c).is_some_and(|(p, _)| occupied(p)))
+    })
+}
+
 /// 检查是否撞到自己
 ///
 /// # 参数
@@ -54,16 +95,37 @@ pub fn check_fruit_collision(pos: IVec2, fruits: &[Fruit]) -> Option<usize> {
 /// - `portals`: 传送门列表
 ///
 /// # 返回
-/// 如果碰到传送门，返回 (进入位置, 出口位置)
+/// 如果碰到传送门，返回 (进入位置, 出口位置)。单向传送门只能从 A 端进入。
 pub fn check_portal_collision(pos: IVec2, portals: &[Portal]) -> Option<(IVec2, IVec2)> {
-    for portal in portals {
-        if pos == portal.pos_a {
-            return Some((portal.pos_a, portal.pos_b));
-        } else if pos == portal.pos_b {
-            return Some((portal.pos_b, portal.pos_a));
-        }
-    }
-    None
+    portals
+        .iter()
+        .find_map(|p| p.exit_for(pos, IVec2::ZERO))
+        .map(|exit| (exit.from, exit.to))
+}
+
+/// 查找传送出口（含出门方向）
+///
+/// # 参数
+/// - `pos`: 蛇头位置
+/// - `dir`: 当前移动方向（定向传送门会覆盖它）
+/// - `portals`: 传送门列表
+pub fn find_portal_exit(pos: IVec2, dir: IVec2, portals: &[Portal]) -> Option<PortalExit> {
+    portals.iter().find_map(|p| p.exit_for(pos, dir))
+}
+
+/// 检查是否撞到动态障碍物
+///
+/// 规则与撞墙相同：调用方根据 `can_pass_through` 决定是否致命
+///
+/// # 参数
+/// - `pos`: 要检查的位置
+/// - `hazards`: 障碍物管理器
+/// - `game_time`: 当前游戏时间
+///
+/// # 返回
+/// 如果该格子当前被障碍物占据返回 true
+pub fn check_hazard_collision(pos: IVec2, hazards: &HazardManager, game_time: f32) -> bool {
+    hazards.is_solid(pos, game_time)
 }
 
 /// 检查位置是否与蛇身重叠
@@ -93,6 +155,26 @@ mod tests {
         assert!(!check_wall_collision(ivec2(0, 0), false));
     }
 
+    #[test]
+    fn test_topology_collision_per_edge() {
+        assert!(!check_topology_collision(ivec2(-1, 3), Topology::CylinderX));
+        assert!(check_topology_collision(ivec2(3, -1), Topology::CylinderX));
+        assert!(!check_topology_collision(ivec2(3, GRID_H), Topology::Klein));
+        assert!(check_topology_collision(ivec2(3, GRID_H), Topology::Mobius));
+    }
+
+    #[test]
+    fn test_diagonal_squeeze() {
+        let body = [ivec2(4, 3), ivec2(3, 2)];
+        let occupied = |p: IVec2| body.contains(&p);
+        let (from, dir) = (ivec2(3, 3), ivec2(1, -1));
+        assert!(check_diagonal_squeeze(GridKind::Octile, Topology::Torus, from, dir, occupied));
+        // 只有一侧被占据可以通过
+        assert!(!check_diagonal_squeeze(GridKind::Octile, Topology::Torus, from, dir, |p| p == body[0]));
+        // 四方向几何没有斜向移动
+        assert!(!check_diagonal_squeeze(GridKind::Square, Topology::Torus, from, dir, occupied));
+    }
+
     #[test]
     fn test_self_collision() {
         let body = vec![ivec2(5, 5), ivec2(4, 5), ivec2(3, 5), ivec2(2, 5)];
@@ -104,6 +186,24 @@ mod tests {
         assert!(!check_self_collision(ivec2(2, 5), &body));
     }
 
+    #[test]
+    fn test_portal_one_way_and_exit_dir() {
+        let portals = vec![
+            Portal::permanent(ivec2(1, 1), ivec2(8, 8), WHITE),
+            Portal::permanent(ivec2(3, 3), ivec2(6, 6), WHITE)
+                .one_way()
+                .with_exit_dir(ivec2(0, -1)),
+        ];
+        // 双向传送门两端都可进入，保持方向
+        let exit = find_portal_exit(ivec2(8, 8), ivec2(1, 0), &portals).unwrap();
+        assert_eq!((exit.to, exit.dir), (ivec2(1, 1), ivec2(1, 0)));
+        // 单向传送门只能从 A 进入，出门方向被改变
+        let exit = find_portal_exit(ivec2(3, 3), ivec2(1, 0), &portals).unwrap();
+        assert_eq!((exit.to, exit.dir), (ivec2(6, 6), ivec2(0, -1)));
+        assert_eq!(find_portal_exit(ivec2(6, 6), ivec2(1, 0), &portals), None);
+        assert_eq!(check_portal_collision(ivec2(6, 6), &portals), None);
+    }
+
     #[test]
     fn test_fruit_collision() {
         let fruits = vec![
@@ -0,0 +1,94 @@
+//! 切割战斗规则
+//!
+//! [`CombatRules::Cutting`](crate::types::CombatRules) 下蛇头撞上别的蛇身不再直接死亡：
+//!
+//! - 撞到蛇身：从撞击点切断，被切下的尾巴变成掉落食物，撞击者继续前进
+//! - 头对头：按长度结算（Battlesnake 规则），长的一方存活，一样长同归于尽
+//!
+//! 玩家与 AI、AI 与 AI 之间使用同一套规则；撞墙、撞自己和斜向挤过蛇身之间仍然致命。
+
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+
+/// 蛇头撞上另一条蛇的结果
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Strike {
+    /// 撞到对方蛇头
+    HeadOn,
+    /// 撞到对方第 `n` 节（n ≥ 1），对方保留前 `n` 节
+    Sever(usize),
+}
+
+impl Strike {
+    /// 蛇头撞上 `body` 的 `pos` 格，没撞到时返回 None
+    pub fn at(body: &VecDeque<IVec2>, pos: IVec2) -> Option<Strike> {
+        match body.iter().position(|&p| p == pos)? {
+            0 => Some(Strike::HeadOn),
+            n => Some(Strike::Sever(n)),
+        }
+    }
+}
+
+/// 头对头结算
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HeadOn {
+    /// 撞击者更长，对方死亡
+    AttackerWins,
+    /// 对方更长，撞击者死亡
+    DefenderWins,
+    /// 一样长，同归于尽
+    BothDie,
+}
+
+impl HeadOn {
+    /// 按长度结算
+    pub fn resolve(attacker_len: usize, defender_len: usize) -> HeadOn {
+        match attacker_len.cmp(&defender_len) {
+            std::cmp::Ordering::Greater => HeadOn::AttackerWins,
+            std::cmp::Ordering::Less => HeadOn::DefenderWins,
+            std::cmp::Ordering::Equal => HeadOn::BothDie,
+        }
+    }
+
+    /// 撞击者是否存活
+    pub fn attacker_survives(&self) -> bool {
+        *self == HeadOn::AttackerWins
+    }
+
+    /// 对方是否存活
+    pub fn defender_survives(&self) -> bool {
+        *self == HeadOn::DefenderWins
+    }
+}
+
+/// 玩家蛇头撞上 AI 蛇的结算结果
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct StrikeOutcome {
+    /// 玩家是否死亡
+    pub player_died: bool,
+    /// 被切下的节数
+    pub severed: usize,
+    /// 击杀 AI 蛇的奖励分数
+    pub kill_bonus: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strike_position() {
+        let body: VecDeque<IVec2> = (0..4).map(|x| ivec2(x, 0)).collect();
+        assert_eq!(Strike::at(&body, ivec2(0, 0)), Some(Strike::HeadOn));
+        assert_eq!(Strike::at(&body, ivec2(2, 0)), Some(Strike::Sever(2)));
+        assert_eq!(Strike::at(&body, ivec2(2, 1)), None);
+    }
+
+    #[test]
+    fn test_head_on_resolved_by_length() {
+        assert_eq!(HeadOn::resolve(5, 3), HeadOn::AttackerWins);
+        assert_eq!(HeadOn::resolve(3, 5), HeadOn::DefenderWins);
+        let tie = HeadOn::resolve(4, 4);
+        assert!(!tie.attacker_survives() && !tie.defender_survives());
+    }
+}
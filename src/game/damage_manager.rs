@@ -4,10 +4,11 @@
 
 use macroquad::prelude::*;
 use ::rand::Rng;
-use ::rand::rngs::ThreadRng;
+use crate::game::GameRng;
 use crate::types::{DamageState, DamagePhase, Particle, BloodStain};
 use crate::types::{DAMAGE_FLASH_DURATION, DAMAGE_FLASH_COUNT, DAMAGE_CRUMBLE_INTERVAL, BLOOD_STAIN_LIFETIME};
 use crate::constants::CELL;
+use super::Snake;
 
 /// 开始受伤动画
 pub fn start_damage_animation(damage: &mut DamageState, snake: &[IVec2]) {
@@ -32,12 +33,12 @@ pub fn start_damage_animation(damage: &mut DamageState, snake: &[IVec2]) {
 /// 更新受伤动画
 pub fn update_damage_animation(
     damage: &mut DamageState,
-    snake: &mut Vec<IVec2>,
+    snake: &mut Snake,
     particles: &mut Vec<Particle>,
     blood_stains: &mut Vec<BloodStain>,
     game_time: f32,
     dt: f32,
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) {
     match damage.phase {
         DamagePhase::Flashing => {
@@ -61,7 +62,7 @@ pub fn update_damage_animation(
                 damage.crumble_timer = 0.0;
 
                 if damage.crumble_index < damage.tail_to_remove.len() && snake.len() > 3 {
-                    let tail_pos = snake.pop().unwrap();
+                    let tail_pos = snake.pop_tail().unwrap();
 
                     // 生成血液粒子
                     let center = vec2(
@@ -96,7 +97,7 @@ pub fn update_damage_animation(
 }
 
 /// 生成血液粒子
-pub fn spawn_blood_particles(particles: &mut Vec<Particle>, pos: Vec2, rng: &mut ThreadRng) {
+pub fn spawn_blood_particles(particles: &mut Vec<Particle>, pos: Vec2, rng: &mut GameRng) {
     let blood_colors = [
         Color { r: 0.8, g: 0.1, b: 0.1, a: 1.0 },
         Color { r: 1.0, g: 0.2, b: 0.2, a: 1.0 },
@@ -0,0 +1,190 @@
+//! 空闲格子索引
+//!
+//! 维护棋盘上所有未被占用的格子，支持 O(1) 的占用、释放和随机采样。
+//! 生成食物、果实和传送门时直接从中采样，不再随机试探；
+//! 索引为空就说明棋盘已经被填满。
+
+use macroquad::prelude::*;
+use ::rand::Rng;
+
+/// [`FreeCellIndex::sample_where`] 遍历之前随机试探的次数
+const SAMPLE_ATTEMPTS: usize = 16;
+
+/// 空闲格子索引
+///
+/// `free` 保存所有空闲格子（无序），`slots` 记录每个格子在 `free` 中的下标，
+/// 占用时与末尾元素交换后弹出，因此增删都是 O(1)。
+#[derive(Clone, Debug)]
+pub struct FreeCellIndex {
+    width: i32,
+    height: i32,
+    free: Vec<IVec2>,
+    slots: Vec<Option<usize>>,
+}
+
+impl FreeCellIndex {
+    /// 创建 `width × height` 的索引，所有格子都空闲
+    pub fn new(width: i32, height: i32) -> Self {
+        let width = width.max(0);
+        let height = height.max(0);
+        let mut free = Vec::with_capacity((width * height) as usize);
+        let mut slots = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                slots.push(Some(free.len()));
+                free.push(ivec2(x, y));
+            }
+        }
+        FreeCellIndex { width, height, free, slots }
+    }
+
+    /// 创建索引并占用给定的格子（重复或越界的格子会被忽略）
+    pub fn with_blocked(width: i32, height: i32, blocked: impl IntoIterator<Item = IVec2>) -> Self {
+        let mut index = Self::new(width, height);
+        for pos in blocked {
+            index.occupy(pos);
+        }
+        index
+    }
+
+    fn slot_of(&self, pos: IVec2) -> Option<usize> {
+        if pos.x < 0 || pos.y < 0 || pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        Some((pos.y * self.width + pos.x) as usize)
+    }
+
+    /// 格子是否空闲（越界视为不空闲）
+    pub fn is_free(&self, pos: IVec2) -> bool {
+        self.slot_of(pos).is_some_and(|s| self.slots[s].is_some())
+    }
+
+    /// 占用格子，返回格子之前是否空闲
+    pub fn occupy(&mut self, pos: IVec2) -> bool {
+        let Some(slot) = self.slot_of(pos) else { return false };
+        let Some(i) = self.slots[slot].take() else { return false };
+
+        self.free.swap_remove(i);
+        // 原来的末尾元素被换到了 i
+        if let Some(&moved) = self.free.get(i) {
+            let moved_slot = (moved.y * self.width + moved.x) as usize;
+            self.slots[moved_slot] = Some(i);
+        }
+        true
+    }
+
+    /// 释放格子，返回格子之前是否被占用
+    pub fn release(&mut self, pos: IVec2) -> bool {
+        let Some(slot) = self.slot_of(pos) else { return false };
+        if self.slots[slot].is_some() {
+            return false;
+        }
+        self.slots[slot] = Some(self.free.len());
+        self.free.push(pos);
+        true
+    }
+
+    /// 空闲格子数量
+    pub fn len(&self) -> usize {
+        self.free.len()
+    }
+
+    /// 棋盘是否已被填满
+    pub fn is_empty(&self) -> bool {
+        self.free.is_empty()
+    }
+
+    /// 所有空闲格子（顺序不固定）
+    pub fn cells(&self) -> &[IVec2] {
+        &self.free
+    }
+
+    /// 均匀随机取一个空闲格子，棋盘已满时返回 None
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<IVec2> {
+        if self.free.is_empty() {
+            return None;
+        }
+        Some(self.free[rng.gen_range(0..self.free.len())])
+    }
+
+    /// 均匀随机取一个 `accept` 接受的空闲格子，没有这样的格子时返回 None
+    ///
+    /// 先随机试探几次；接受的格子很少、试探都落空时才遍历所有空闲格子
+    pub fn sample_where<R: Rng + ?Sized>(&self, rng: &mut R, accept: impl Fn(IVec2) -> bool) -> Option<IVec2> {
+        for _ in 0..SAMPLE_ATTEMPTS {
+            let pos = self.sample(rng)?;
+            if accept(pos) {
+                return Some(pos);
+            }
+        }
+        let accepted: Vec<IVec2> = self.free.iter().copied().filter(|&p| accept(p)).collect();
+        if accepted.is_empty() {
+            return None;
+        }
+        Some(accepted[rng.gen_range(0..accepted.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::thread_rng;
+
+    #[test]
+    fn test_occupy_and_release() {
+        let mut index = FreeCellIndex::new(3, 2);
+        assert_eq!(index.len(), 6);
+
+        assert!(index.occupy(ivec2(0, 0)));
+        assert!(!index.occupy(ivec2(0, 0)));
+        assert!(!index.occupy(ivec2(5, 5)));
+        assert!(!index.is_free(ivec2(0, 0)));
+        assert_eq!(index.len(), 5);
+
+        assert!(index.release(ivec2(0, 0)));
+        assert!(!index.release(ivec2(0, 0)));
+        assert!(index.is_free(ivec2(0, 0)));
+        assert_eq!(index.len(), 6);
+    }
+
+    #[test]
+    fn test_sample_only_returns_free_cells() {
+        let mut rng = thread_rng();
+        let index = FreeCellIndex::with_blocked(2, 2, [ivec2(0, 0), ivec2(1, 0), ivec2(0, 1)]);
+        for _ in 0..20 {
+            assert_eq!(index.sample(&mut rng), Some(ivec2(1, 1)));
+        }
+    }
+
+    #[test]
+    fn test_sample_where_skips_rejected_cells() {
+        let mut rng = thread_rng();
+        let index = FreeCellIndex::new(8, 8);
+        for _ in 0..20 {
+            assert_eq!(index.sample_where(&mut rng, |p| p == ivec2(5, 6)), Some(ivec2(5, 6)));
+        }
+        assert_eq!(index.sample_where(&mut rng, |_| false), None);
+    }
+
+    #[test]
+    fn test_full_board_has_no_sample() {
+        let mut rng = thread_rng();
+        let cells: Vec<IVec2> = (0..3).flat_map(|x| (0..3).map(move |y| ivec2(x, y))).collect();
+        let index = FreeCellIndex::with_blocked(3, 3, cells);
+        assert!(index.is_empty());
+        assert_eq!(index.sample(&mut rng), None);
+    }
+
+    #[test]
+    fn test_slots_stay_consistent_after_swaps() {
+        let mut index = FreeCellIndex::new(4, 4);
+        for pos in [ivec2(0, 0), ivec2(3, 3), ivec2(1, 2), ivec2(2, 1)] {
+            index.occupy(pos);
+        }
+        index.release(ivec2(3, 3));
+        for &pos in index.cells() {
+            assert!(index.is_free(pos));
+        }
+        assert_eq!(index.cells().len(), 13);
+    }
+}
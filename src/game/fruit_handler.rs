@@ -3,7 +3,7 @@
 //! 处理 FruitBehavior::on_consume 的结果
 
 use macroquad::prelude::*;
-use ::rand::rngs::ThreadRng;
+use crate::game::GameRng;
 use crate::types::{Fruit, BuffState, DamageState, Particle, ComboState, GameState};
 use crate::fruits::{FruitRegistry, FruitContext, ConsumeResult};
 use crate::game::Snake;
@@ -60,7 +60,7 @@ pub fn consume_fruit(
     score: &mut u32,
     combo_state: &mut ComboState,
     game_state: &mut GameState,
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
     game_time: f32,
     ai_manager: &mut crate::game::AIManager,
     food: &mut IVec2,
@@ -80,6 +80,7 @@ pub fn consume_fruit(
 
     // 创建上下文 - 包含所有游戏资源的引用
     let mut ctx = FruitContext {
+        grid: snake.grid,
         snake: &mut snake.body,
         dir: &mut snake.dir,
         buff_state,
@@ -95,8 +96,9 @@ pub fn consume_fruit(
         fruits,
     };
 
-    // 调用 on_consume
+    // 调用 on_consume（回调可能直接修改蛇身，之后重建占用网格）
     let result = behavior.on_consume(&mut ctx);
+    snake.sync_occupancy();
 
     // 处理结果
     let game_over = handle_consume_result(result, score, combo_state, game_state);
@@ -123,10 +125,9 @@ pub fn update_combo(combo: &mut ComboState, game_time: f32) {
 use ::rand::Rng;
 use crate::constants::CELL;
 use crate::fruits::FruitCategory;
-use crate::types::SandwormPhase;
 use crate::game::start_damage_animation;
 use crate::render::spawn_lucky_particles;
-use crate::audio::SoundManager;
+use crate::audio::SoundEffects;
 
 /// 处理果实效果（完整版）
 ///
@@ -134,7 +135,7 @@ use crate::audio::SoundManager;
 /// - Normal: 加分、增长
 /// - Trap: 负面效果（可被护盾免疫）
 /// - Power: 激活增益Buff
-/// - Special: 随机效果
+/// - Special: 随机效果（宠物蛋除外：开始在体内孵化）
 ///
 /// # 参数
 /// - `fruit`: 被吃掉的果实
@@ -147,7 +148,7 @@ use crate::audio::SoundManager;
 /// - `combo_state`: Combo状态
 /// - `game_state`: 游戏状态
 /// - `rng`: 随机数生成器
-/// - `sounds`: 音效管理器
+/// - `sounds`: 音效（无界面模拟时为 [`Silence`](crate::audio::Silence)）
 pub fn handle_fruit_effect(
     fruit: &Fruit,
     registry: &FruitRegistry,
@@ -160,7 +161,7 @@ pub fn handle_fruit_effect(
     game_state: &mut GameState,
     rng: &mut impl Rng,
     _game_time: f32,
-    sounds: &SoundManager,
+    sounds: &dyn SoundEffects,
 ) {
     let config = match registry.get_config(fruit.type_id) {
         Some(c) => c,
@@ -185,7 +186,7 @@ pub fn handle_fruit_effect(
                         if snake.len() <= 3 {
                             *game_state = GameState::GameOver;
                         } else {
-                            start_damage_animation(damage_state, &snake.body);
+                            start_damage_animation(damage_state, snake.body_slice());
                         }
                     }
                     "freeze" => buff_state.activate_freeze(),
@@ -211,18 +212,22 @@ pub fn handle_fruit_effect(
                 "reverse" => snake.reverse(),
                 "sandworm" => {
                     // 激活沙虫模式
-                    buff_state.sandworm_active = true;
-                    buff_state.sandworm_phase = SandwormPhase::Flashing;
-                    buff_state.sandworm_phase_timer = 0.0;
-                    buff_state.sandworm_transform_index = 0;
-                    buff_state.sandworm_tick = 0.0;
+                    buff_state.activate_sandworm();
                     // 保存原始状态，坍缩后恢复
-                    buff_state.sandworm_original_snake = snake.body.clone();
+                    buff_state.sandworm_original_snake = snake.body.iter().copied().collect();
                     buff_state.sandworm_original_dir = snake.dir;
                 }
                 _ => {}
             }
         }
+        FruitCategory::Special if fruit.type_id == "pet_egg" => {
+            // 宠物蛋：在体内孵化，到达蛇尾时孵化出宠物蛇（体内已有蛋时只加分）
+            sounds.play_power();
+            *score += 3;
+            if !buff_state.egg_state.active {
+                buff_state.egg_state.activate();
+            }
+        }
         FruitCategory::Special => {
             // 特殊果实：随机效果
             sounds.play_power();
@@ -256,7 +261,7 @@ pub fn handle_fruit_effect(
                     3 => buff_state.activate_slime(),
                     _ => {
                         if snake.len() > 3 {
-                            start_damage_animation(damage_state, &snake.body);
+                            start_damage_animation(damage_state, snake.body_slice());
                         }
                     }
                 }
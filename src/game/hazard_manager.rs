@@ -0,0 +1,154 @@
+//! 动态障碍物管理器
+//!
+//! 管理旋转横杆、轨道滑块和定时墙，提供碰撞查询和位置预测。
+//!
+//! ## 碰撞规则
+//!
+//! 障碍物的实心格子与静态墙规则一致：
+//! - 普通状态下蛇头进入实心格子 = 撞墙
+//! - 护盾、幽灵、沙虫可以穿过（与穿墙规则相同）
+//! - 定时墙处于 Open / Warning 阶段时可以通行
+
+use macroquad::prelude::*;
+use crate::types::Hazard;
+
+/// 动态障碍物管理器
+#[derive(Clone, Default)]
+pub struct HazardManager {
+    /// 所有障碍物
+    pub hazards: Vec<Hazard>,
+}
+
+impl HazardManager {
+    /// 创建空的障碍物管理器
+    pub fn new() -> Self {
+        HazardManager { hazards: Vec::new() }
+    }
+
+    /// 添加障碍物（链式调用）
+    pub fn with_hazard(mut self, hazard: Hazard) -> Self {
+        self.hazards.push(hazard);
+        self
+    }
+
+    /// 是否没有任何障碍物
+    pub fn is_empty(&self) -> bool {
+        self.hazards.is_empty()
+    }
+
+    /// 指定时间所有实心格子
+    pub fn solid_cells(&self, time: f32) -> Vec<IVec2> {
+        self.hazards.iter().flat_map(|h| h.cells_at(time)).collect()
+    }
+
+    /// 检查指定时间某格子是否被障碍物占据
+    pub fn is_solid(&self, pos: IVec2, time: f32) -> bool {
+        self.hazards.iter().any(|h| h.cells_at(time).contains(&pos))
+    }
+
+    /// 预测 `lookahead` 秒后的实心格子
+    ///
+    /// AI 在决策时用自己的移动间隔作为 lookahead，
+    /// 这样判断的是"我走到那一格时"障碍物的位置。
+    pub fn predict_cells(&self, time: f32, lookahead: f32) -> Vec<IVec2> {
+        let mut cells = self.solid_cells(time);
+        cells.extend(self.solid_cells(time + lookahead));
+        cells
+    }
+
+    /// 所有障碍物可能占据的格子（生成食物和果实时避开）
+    pub fn footprint(&self) -> Vec<IVec2> {
+        self.hazards.iter().flat_map(|h| h.footprint()).collect()
+    }
+}
+
+/// 创建默认的障碍物布局
+///
+/// 布局避开蛇的初始行，给玩家开局留出空间
+pub fn create_default_hazard_manager() -> HazardManager {
+    HazardManager::new()
+        // 左上角旋转横杆：臂长2，每0.6秒转45°
+        .with_hazard(Hazard::rotating_bar(ivec2(7, 6), 2, 0.6, true))
+        // 底部轨道滑块：2格长，每0.25秒移动一格
+        .with_hazard(Hazard::sliding_block(
+            (10..=22).map(|x| ivec2(x, 19)).collect(),
+            2,
+            0.25,
+        ))
+        // 右上角定时墙：打开4秒，预警1.5秒，关闭3秒
+        .with_hazard(Hazard::timed_wall(
+            (2..=6).map(|y| ivec2(24, y)).collect(),
+            4.0,
+            1.5,
+            3.0,
+        ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::WallPhase;
+
+    #[test]
+    fn test_rotating_bar_rotates() {
+        let bar = Hazard::rotating_bar(ivec2(10, 10), 1, 1.0, true);
+        let t0 = bar.cells_at(0.0);
+        assert_eq!(t0, vec![ivec2(9, 10), ivec2(10, 10), ivec2(11, 10)]);
+        let t1 = bar.cells_at(1.0);
+        assert_eq!(t1, vec![ivec2(9, 9), ivec2(10, 10), ivec2(11, 11)]);
+        // 转 180° 后与初始位置重合
+        let mut t4 = bar.cells_at(4.0);
+        t4.sort_by_key(|p| (p.x, p.y));
+        assert_eq!(t4, t0);
+    }
+
+    #[test]
+    fn test_sliding_block_ping_pong() {
+        let rail: Vec<IVec2> = (0..4).map(|x| ivec2(x, 0)).collect();
+        let block = Hazard::sliding_block(rail, 2, 1.0);
+        assert_eq!(block.cells_at(0.0), vec![ivec2(0, 0), ivec2(1, 0)]);
+        assert_eq!(block.cells_at(2.0), vec![ivec2(2, 0), ivec2(3, 0)]);
+        assert_eq!(block.cells_at(3.0), vec![ivec2(1, 0), ivec2(2, 0)]);
+        assert_eq!(block.cells_at(4.0), vec![ivec2(0, 0), ivec2(1, 0)]);
+    }
+
+    #[test]
+    fn test_timed_wall_cycle() {
+        let wall = Hazard::timed_wall(vec![ivec2(5, 5)], 2.0, 1.0, 1.0);
+        assert_eq!(wall.wall_phase(0.5), WallPhase::Open);
+        assert!(matches!(wall.wall_phase(2.5), WallPhase::Warning(p) if (p - 0.5).abs() < 1e-4));
+        assert_eq!(wall.wall_phase(3.5), WallPhase::Closed);
+        assert!(wall.cells_at(2.5).is_empty());
+        assert_eq!(wall.cells_at(3.5), vec![ivec2(5, 5)]);
+        // 下一个周期重新打开
+        assert_eq!(wall.wall_phase(4.5), WallPhase::Open);
+    }
+
+    #[test]
+    fn test_static_wall_never_opens() {
+        let wall = Hazard::static_wall(vec![ivec2(0, 0)]);
+        for step in 0..20 {
+            assert_eq!(wall.wall_phase(step as f32 * 0.37), WallPhase::Closed);
+        }
+    }
+
+    #[test]
+    fn test_predict_includes_next_tick() {
+        let manager = HazardManager::new()
+            .with_hazard(Hazard::timed_wall(vec![ivec2(3, 3)], 1.0, 0.0, 1.0));
+        assert!(!manager.is_solid(ivec2(3, 3), 0.9));
+        assert!(manager.predict_cells(0.9, 0.2).contains(&ivec2(3, 3)));
+    }
+
+    #[test]
+    fn test_footprint_covers_all_positions() {
+        let manager = create_default_hazard_manager();
+        let footprint = manager.footprint();
+        for step in 0..40 {
+            let t = step as f32 * 0.25;
+            for cell in manager.solid_cells(t) {
+                assert!(footprint.contains(&cell));
+            }
+        }
+    }
+}
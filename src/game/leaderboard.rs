@@ -0,0 +1,352 @@
+//! 本地排行榜
+//!
+//! 按 模式 × 地图 × 难度 分别保存前 N 名，写入一个纯文本文件。
+//!
+//! ## 文件格式
+//!
+//! 第一行是版本头，之后每行一条记录，字段以制表符分隔：
+//!
+//! ```text
+//! # snake2d leaderboard v1
+//! mode  map  difficulty  name  score  length  duration  date  seed  replay
+//! ```
+//!
+//! `replay` 没有回放时写 `-`。无法解析的行会被跳过，不会导致整个文件失效。
+//!
+//! ## 原子写入
+//!
+//! 先写入同目录下的临时文件并 `sync_all`，再 `rename` 覆盖正式文件；
+//! 写到一半崩溃只会留下临时文件，正式文件始终是完整的旧版本或新版本。
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::types::{Difficulty, GameMode};
+
+/// 文件版本头
+const HEADER: &str = "# snake2d leaderboard v1";
+
+/// 每个榜单默认保留的名次
+pub const DEFAULT_LEADERBOARD_SIZE: usize = 10;
+
+/// 玩家名字最大长度（字符）
+pub const MAX_NAME_LEN: usize = 12;
+
+/// 榜单标识：模式 + 地图 + 难度
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct LeaderboardKey {
+    pub mode: GameMode,
+    pub map: String,
+    pub difficulty: Difficulty,
+}
+
+impl LeaderboardKey {
+    pub fn new(mode: GameMode, map: impl Into<String>, difficulty: Difficulty) -> Self {
+        LeaderboardKey { mode, map: map.into(), difficulty }
+    }
+}
+
+/// 排行榜记录
+#[derive(Clone, PartialEq, Debug)]
+pub struct ScoreEntry {
+    /// 玩家名字
+    pub name: String,
+    /// 最终得分
+    pub score: u32,
+    /// 最终蛇长
+    pub length: usize,
+    /// 游戏时长（秒，不含暂停）
+    pub duration: f32,
+    /// 记录时间（Unix 秒）
+    pub date: u64,
+    /// 本局随机种子
+    pub seed: u64,
+    /// 回放文件引用（没有回放时为 None）
+    pub replay: Option<String>,
+}
+
+impl ScoreEntry {
+    /// 创建记录，时间取当前时间，名字会被清理
+    pub fn new(name: &str, score: u32, length: usize, duration: f32, seed: u64) -> Self {
+        ScoreEntry {
+            name: sanitize_name(name),
+            score,
+            length,
+            duration,
+            date: unix_now(),
+            seed,
+            replay: None,
+        }
+    }
+}
+
+/// 本地排行榜
+#[derive(Clone, Debug)]
+pub struct Leaderboard {
+    /// 每个榜单保留的名次
+    capacity: usize,
+    /// 所有榜单（按首次出现顺序），每个榜单按分数降序
+    tables: Vec<(LeaderboardKey, Vec<ScoreEntry>)>,
+}
+
+impl Default for Leaderboard {
+    fn default() -> Self {
+        Self::new(DEFAULT_LEADERBOARD_SIZE)
+    }
+}
+
+impl Leaderboard {
+    /// 创建空排行榜，每个榜单保留 `capacity` 名
+    pub fn new(capacity: usize) -> Self {
+        Leaderboard { capacity: capacity.max(1), tables: Vec::new() }
+    }
+
+    /// 指定榜单的所有记录（分数降序）
+    pub fn entries(&self, key: &LeaderboardKey) -> &[ScoreEntry] {
+        self.tables
+            .iter()
+            .find(|(k, _)| k == key)
+            .map_or(&[], |(_, entries)| entries.as_slice())
+    }
+
+    /// 指定榜单的最高分（空榜单为 0）
+    pub fn best_score(&self, key: &LeaderboardKey) -> u32 {
+        self.entries(key).first().map_or(0, |e| e.score)
+    }
+
+    /// 分数能否进入指定榜单（0 分不上榜）
+    pub fn qualifies(&self, key: &LeaderboardKey, score: u32) -> bool {
+        if score == 0 {
+            return false;
+        }
+        let entries = self.entries(key);
+        entries.len() < self.capacity || entries.last().is_some_and(|e| score > e.score)
+    }
+
+    /// 插入记录，返回名次（从 0 开始）；没有进入前 N 名时返回 None
+    ///
+    /// 同分时先上榜的排在前面
+    pub fn insert(&mut self, key: LeaderboardKey, entry: ScoreEntry) -> Option<usize> {
+        let capacity = self.capacity;
+        let entries = match self.tables.iter().position(|(k, _)| *k == key) {
+            Some(i) => &mut self.tables[i].1,
+            None => {
+                self.tables.push((key, Vec::new()));
+                &mut self.tables.last_mut().expect("just pushed").1
+            }
+        };
+        let rank = entries.iter().position(|e| entry.score > e.score).unwrap_or(entries.len());
+        if rank >= capacity {
+            return None;
+        }
+        entries.insert(rank, entry);
+        entries.truncate(capacity);
+        Some(rank)
+    }
+
+    /// 从文本解析（跳过无法解析的行，每个榜单截断到容量）
+    pub fn parse(text: &str, capacity: usize) -> Self {
+        let mut board = Self::new(capacity);
+        for line in text.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            if let Some((key, entry)) = parse_line(line) {
+                board.insert(key, entry);
+            }
+        }
+        board
+    }
+
+    /// 序列化为文本
+    pub fn to_text(&self) -> String {
+        let mut out = String::from(HEADER);
+        out.push('\n');
+        for (key, entries) in &self.tables {
+            for e in entries {
+                out.push_str(&format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{}\t{}\n",
+                    key.mode.name(),
+                    key.map,
+                    key.difficulty.name(),
+                    e.name,
+                    e.score,
+                    e.length,
+                    e.duration,
+                    e.date,
+                    e.seed,
+                    e.replay.as_deref().unwrap_or("-"),
+                ));
+            }
+        }
+        out
+    }
+
+    /// 从文件加载，文件不存在时返回空排行榜
+    pub fn load(path: &Path, capacity: usize) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text, capacity)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new(capacity)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 原子写入文件：先写临时文件并刷盘，再重命名覆盖
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = temp_path(path);
+        {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(self.to_text().as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)
+    }
+}
+
+/// 默认排行榜文件路径（可用环境变量 `SNAKE2D_LEADERBOARD` 覆盖）
+pub fn default_leaderboard_path() -> PathBuf {
+    std::env::var_os("SNAKE2D_LEADERBOARD")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("snake2d_leaderboard.txt"))
+}
+
+/// 清理玩家名字：去掉控制字符（含制表符），截断长度，空名字用 "Player"
+pub fn sanitize_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LEN)
+        .collect();
+    let name = name.trim();
+    if name.is_empty() { "Player".to_string() } else { name.to_string() }
+}
+
+/// 把 Unix 秒格式化为 UTC 日期 `YYYY-MM-DD`
+pub fn format_date(unix_secs: u64) -> String {
+    // 按公历从 0000-03-01 起算的天数换算年月日
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 当前 Unix 时间（秒）
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// 同目录下的临时文件路径
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(|n| n.to_os_string()).unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// 解析一行记录
+fn parse_line(line: &str) -> Option<(LeaderboardKey, ScoreEntry)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [mode, map, difficulty, name, score, length, duration, date, seed, replay] = fields[..] else {
+        return None;
+    };
+    let key = LeaderboardKey {
+        mode: GameMode::ALL.into_iter().find(|m| m.name() == mode)?,
+        map: map.to_string(),
+        difficulty: Difficulty::ALL.into_iter().find(|d| d.name() == difficulty)?,
+    };
+    let entry = ScoreEntry {
+        name: sanitize_name(name),
+        score: score.parse().ok()?,
+        length: length.parse().ok()?,
+        duration: duration.parse().ok()?,
+        date: date.parse().ok()?,
+        seed: seed.parse().ok()?,
+        replay: (replay != "-").then(|| replay.to_string()),
+    };
+    Some((key, entry))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> ScoreEntry {
+        ScoreEntry { date: 1_700_000_000, ..ScoreEntry::new(name, score, 10, 42.5, 99) }
+    }
+
+    #[test]
+    fn test_insert_keeps_top_n_per_key() {
+        let mut board = Leaderboard::new(3);
+        let key = LeaderboardKey::new(GameMode::Arena, "Classic", Difficulty::Normal);
+        let other = LeaderboardKey::new(GameMode::Classic, "Classic", Difficulty::Normal);
+
+        assert_eq!(board.insert(key.clone(), entry("a", 10)), Some(0));
+        assert_eq!(board.insert(key.clone(), entry("b", 30)), Some(0));
+        assert_eq!(board.insert(key.clone(), entry("c", 10)), Some(2));
+        // 同分排在先上榜的后面，满榜后低分进不去
+        assert!(!board.qualifies(&key, 10));
+        assert_eq!(board.insert(key.clone(), entry("d", 5)), None);
+        assert_eq!(board.insert(key.clone(), entry("e", 20)), Some(1));
+
+        let names: Vec<&str> = board.entries(&key).iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["b", "e", "a"]);
+        assert_eq!(board.best_score(&key), 30);
+        assert!(board.entries(&other).is_empty());
+        assert!(!board.qualifies(&other, 0));
+    }
+
+    #[test]
+    fn test_text_round_trip_skips_bad_lines() {
+        let mut board = Leaderboard::default();
+        let key = LeaderboardKey::new(GameMode::Classic, "Portal Hub", Difficulty::Hard);
+        let mut with_replay = entry("Ann", 120);
+        with_replay.replay = Some("replays/1.rpl".to_string());
+        board.insert(key.clone(), with_replay);
+        board.insert(key.clone(), entry("Bob", 80));
+
+        let mut text = board.to_text();
+        text.push_str("garbage line\n");
+        text.push_str("Arena\tClassic\tNormal\tEve\tnot-a-number\t1\t1.0\t0\t0\t-\n");
+
+        let parsed = Leaderboard::parse(&text, DEFAULT_LEADERBOARD_SIZE);
+        assert_eq!(parsed.entries(&key), board.entries(&key));
+        assert_eq!(parsed.tables.len(), 1);
+    }
+
+    #[test]
+    fn test_save_is_atomic_and_loadable() {
+        let dir = std::env::temp_dir().join(format!("snake2d_lb_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("scores.txt");
+
+        let mut board = Leaderboard::default();
+        let key = LeaderboardKey::new(GameMode::Arena, "Mobius", Difficulty::Easy);
+        board.insert(key.clone(), entry("Zed", 7));
+        board.save(&path).unwrap();
+
+        assert!(!temp_path(&path).exists());
+        let loaded = Leaderboard::load(&path, DEFAULT_LEADERBOARD_SIZE).unwrap();
+        assert_eq!(loaded.entries(&key), board.entries(&key));
+
+        // 文件不存在时得到空排行榜
+        let missing = Leaderboard::load(&dir.join("missing.txt"), 5).unwrap();
+        assert!(missing.entries(&key).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_name_and_date_formatting() {
+        assert_eq!(sanitize_name("  \tAl\nice  "), "Alice");
+        assert_eq!(sanitize_name(""), "Player");
+        assert_eq!(sanitize_name("abcdefghijklmnopq").chars().count(), MAX_NAME_LEN);
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1_700_000_000), "2023-11-14");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+    }
+}
@@ -0,0 +1,113 @@
+//! 地图定义模块
+//!
+//! 地图描述关卡的静态布局：边界拓扑、动态障碍物、永久传送门和 Boss 登场的分数。
+//! 新地图只需在 `builtin_maps()` 中添加一个构造函数。
+
+use macroquad::prelude::*;
+use crate::constants::GRID_W;
+use crate::types::{Hazard, Portal, Topology, PORTAL_COLORS};
+use super::{HazardManager, create_default_hazard_manager};
+
+/// 地图定义
+#[derive(Clone)]
+pub struct GameMap {
+    /// 地图名称（UI 显示）
+    pub name: &'static str,
+    /// 边界拓扑
+    pub topology: Topology,
+    /// 动态障碍物布局
+    pub hazards: HazardManager,
+    /// 永久传送门
+    pub portals: Vec<Portal>,
+    /// 分数达到这些值时 Boss 登场（见 [`BossSchedule`](super::BossSchedule)）
+    pub boss_scores: Vec<u32>,
+}
+
+impl GameMap {
+    /// 创建空地图
+    pub fn new(name: &'static str) -> Self {
+        GameMap {
+            name,
+            topology: Topology::default(),
+            hazards: HazardManager::new(),
+            portals: Vec::new(),
+            boss_scores: Vec::new(),
+        }
+    }
+
+    /// 设置边界拓扑（链式调用）
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// 设置障碍物布局（链式调用）
+    pub fn with_hazards(mut self, hazards: HazardManager) -> Self {
+        self.hazards = hazards;
+        self
+    }
+
+    /// 添加永久传送门（链式调用）
+    pub fn with_portal(mut self, portal: Portal) -> Self {
+        self.portals.push(portal);
+        self
+    }
+
+    /// 分数达到 `score` 时 Boss 登场（链式调用，0 表示开局就登场）
+    pub fn with_boss_at(mut self, score: u32) -> Self {
+        self.boss_scores.push(score);
+        self
+    }
+}
+
+/// 经典地图：默认障碍物，没有永久传送门
+pub fn classic_map() -> GameMap {
+    GameMap::new("Classic").with_hazards(create_default_hazard_manager())
+}
+
+/// 传送枢纽地图：四角双向传送门 + 一对单向定向传送门
+pub fn portal_hub_map() -> GameMap {
+    GameMap::new("Portal Hub")
+        .with_portal(Portal::permanent(ivec2(3, 3), ivec2(28, 20), PORTAL_COLORS[0]))
+        .with_portal(Portal::permanent(ivec2(28, 3), ivec2(3, 20), PORTAL_COLORS[1]))
+        // 从左侧中部进入，从顶部中央出来并向下移动
+        .with_portal(
+            Portal::permanent(ivec2(5, 12), ivec2(16, 1), PORTAL_COLORS[2])
+                .one_way()
+                .with_exit_dir(ivec2(0, 1)),
+        )
+}
+
+/// 莫比乌斯带地图：左右镜像环绕，上下是墙
+///
+/// 中央的定时墙关闭时把地图分成上下两半，只能经过镜像边界换到另一半
+pub fn mobius_map() -> GameMap {
+    let wall: Vec<IVec2> = (0..GRID_W).map(|x| ivec2(x, 11)).collect();
+    GameMap::new("Mobius Strip")
+        .with_topology(Topology::Mobius)
+        .with_hazards(HazardManager::new().with_hazard(
+            Hazard::timed_wall(wall, 6.0, 1.5, 6.0),
+        ))
+}
+
+/// 克莱因瓶地图：左右镜像环绕 + 上下普通环绕
+pub fn klein_map() -> GameMap {
+    GameMap::new("Klein Bottle")
+        .with_topology(Topology::Klein)
+        .with_hazards(HazardManager::new().with_hazard(
+            Hazard::rotating_bar(ivec2(16, 5), 3, 0.5, true),
+        ))
+}
+
+/// 巨蛇巢穴地图：没有障碍物，开局就有 Boss，之后每过一段分数再来一条
+pub fn serpent_lair_map() -> GameMap {
+    GameMap::new("Serpent Lair")
+        .with_boss_at(0)
+        .with_boss_at(60)
+        .with_boss_at(150)
+}
+
+/// 所有内置地图
+pub fn builtin_maps() -> Vec<GameMap> {
+    vec![classic_map(), portal_hub_map(), mobius_map(), klein_map(), serpent_lair_map()]
+}
@@ -7,11 +7,25 @@
 //! ```text
 //! game/
 //! ├── snake.rs          # 蛇状态和移动逻辑
+//! ├── occupancy.rs      # 占用网格（O(1) 碰撞检测）
 //! ├── collision.rs      # 碰撞检测函数
 //! ├── spawn.rs          # 生成逻辑（食物、果实、传送门）
+//! ├── free_cells.rs     # 空闲格子索引（生成采样、满盘检测）
 //! ├── buff_manager.rs   # Buff 计时器管理
 //! ├── damage_manager.rs # 受伤动画状态机
-//! └── fruit_handler.rs  # 果实消费处理
+//! ├── fruit_handler.rs  # 果实消费处理
+//! ├── pathfinding.rs    # BFS 寻路和洪水填充（AI 决策，支持环绕）
+//! ├── ai_brain.rs       # 可替换的 AI 决策逻辑（贪心、寻路、随机、四种性格）
+//! ├── ai_progress.rs    # AI 蛇经验、等级和精英
+//! ├── combat.rs         # 切割战斗规则（切断蛇身、头对头按长度结算）
+//! ├── team.rs           # AI 蛇队伍（误伤、共享目标、包围、玩家盟友）
+//! ├── boss.rs           # Boss 战（血量、阶段剧本、登场时间表）
+//! ├── party.rs          # 本地多人（玩家、出生位置、对战/合作规则）
+//! ├── swarm.rs          # 蜂群模式（大棋盘上几百条 AI 蛇的无界面模拟）
+//! ├── hazard_manager.rs # 动态障碍物（旋转横杆、滑块、定时墙）
+//! ├── portal_manager.rs # 传送门（永久 + 临时，多对）
+//! ├── map.rs            # 地图定义（障碍物、永久传送门）
+//! └── leaderboard.rs    # 本地排行榜（按模式/地图/难度，原子写入）
 //! ```
 //!
 //! ## 设计原则
@@ -46,26 +60,68 @@
 //! ```
 
 pub mod snake;
+pub mod occupancy;
 pub mod collision;
 pub mod spawn;
+pub mod free_cells;
 pub mod buff_manager;
 pub mod damage_manager;
 pub mod fruit_handler;
 pub mod sandworm_manager;
+pub mod pathfinding;
+pub mod ai_brain;
+pub mod ai_progress;
+pub mod combat;
+pub mod team;
+pub mod boss;
+pub mod party;
 pub mod ai_snake;
 pub mod ai_manager;
+pub mod swarm;
 pub mod spawn_manager;
 pub mod bomb_manager;
 pub mod game_events;
+pub mod hazard_manager;
+pub mod portal_manager;
+pub mod map;
+pub mod leaderboard;
 
 pub use snake::*;
+pub use occupancy::OccupancyGrid;
 pub use collision::*;
 pub use spawn::*;
+pub use free_cells::FreeCellIndex;
 pub use damage_manager::*;
 pub use fruit_handler::*;
 pub use sandworm_manager::*;
-pub use ai_snake::{AISnake, AIMoveResult, AI_COLORS};
-pub use ai_manager::{AIManager, AIUpdateResult, DroppedFood};
+pub use pathfinding::{PathMap, flood_fill, passable_step};
+pub use ai_brain::{
+    personality_weights, AIBrain, AIObservation, BrainKind, CowardBrain, ForagerBrain, GreedyBrain, HunterBrain,
+    PathfindingBrain, RandomBrain, RoutePlan, TricksterBrain,
+};
+pub use ai_progress::AIProgress;
+pub use combat::{HeadOn, Strike, StrikeOutcome};
+pub use team::{Team, TeamBoard, TeamEvent, TeamMember};
+pub use boss::{BossEvent, BossPhase, BossSchedule, BossState, ARENA_BOSS_SCORES};
+pub use party::{
+    free_spot, start_position, Party, PartyOutcome, Player, PlayerClash, MAX_PLAYERS, PLAYER_COLORS, RESPAWN_DELAY,
+    VERSUS_TIME_LIMIT,
+};
+pub use ai_snake::{AIDecision, AISnake, AIMoveResult, AIWorld, PlayerView, AI_COLORS};
+pub use ai_manager::{AIManager, AIUpdateResult, DroppedFood, PlayerReport};
+pub use swarm::{Swarm, SwarmConfig, SWARM_BOARD, SWARM_MAX_SIDE, SWARM_MAX_SNAKES, SWARM_MIN_SIDE};
 pub use spawn_manager::{FruitSpawnManager, SpawnRule, IndependentSpawnRule, create_default_spawn_manager};
 pub use bomb_manager::{BombManager, BombUpdateResult};
 pub use game_events::{GameEvent, SoundType, EventQueue, ExpireResult, handle_expired_fruits};
+pub use hazard_manager::{HazardManager, create_default_hazard_manager};
+pub use portal_manager::{PortalManager, create_default_portal_manager};
+pub use map::{GameMap, builtin_maps};
+pub use leaderboard::{
+    Leaderboard, LeaderboardKey, ScoreEntry, DEFAULT_LEADERBOARD_SIZE, MAX_NAME_LEN,
+    default_leaderboard_path, format_date, sanitize_name,
+};
+
+/// 游戏随机数生成器
+///
+/// 每局用一个种子初始化（排行榜会记录这个种子），同一种子产生相同的随机序列
+pub type GameRng = ::rand::rngs::StdRng;
@@ -0,0 +1,241 @@
+//! 占用网格
+//!
+//! 按格子记录有多少节蛇身压在上面，代替对蛇身列表的 `contains` 线性扫描。
+//! 蛇移动时只需增加新蛇头、减少旧蛇尾，碰撞检测是一次数组访问。
+//!
+//! 使用计数而不是布尔值：幽灵/护盾穿过自己、刚吃完食物尾巴重叠时，
+//! 同一格可能被压两次，减掉一次后仍然是占用状态。
+//!
+//! 用 [`OccupancyGrid::with_free_cells`] 创建的网格同时维护一份 [`FreeCellIndex`]：
+//! 格子计数在 0 和非 0 之间变化时同步占用/释放，生成东西时直接从中采样。
+
+use macroquad::prelude::*;
+use crate::constants::{GRID_W, GRID_H};
+use super::FreeCellIndex;
+
+/// 占用网格
+#[derive(Clone, Debug)]
+pub struct OccupancyGrid {
+    width: i32,
+    height: i32,
+    counts: Vec<u16>,
+    free: Option<FreeCells>,
+}
+
+/// 随占用网格同步的空闲格子
+#[derive(Clone, Debug)]
+struct FreeCells {
+    /// 没有蛇身、也不是固定阻挡的格子
+    index: FreeCellIndex,
+    /// 固定阻挡的格子（障碍物经过的格子等，不算占用，但永远不是空闲格子）
+    fixed: Vec<bool>,
+}
+
+impl Default for OccupancyGrid {
+    fn default() -> Self {
+        Self::new(GRID_W, GRID_H)
+    }
+}
+
+impl OccupancyGrid {
+    /// 创建 `width × height` 的空网格
+    pub fn new(width: i32, height: i32) -> Self {
+        let width = width.max(0);
+        let height = height.max(0);
+        OccupancyGrid {
+            width,
+            height,
+            counts: vec![0; (width * height) as usize],
+            free: None,
+        }
+    }
+
+    /// 创建 `width × height` 的空网格，同时维护空闲格子索引
+    pub fn with_free_cells(width: i32, height: i32) -> Self {
+        let mut grid = Self::new(width, height);
+        grid.free = Some(FreeCells {
+            index: FreeCellIndex::new(grid.width, grid.height),
+            fixed: vec![false; grid.counts.len()],
+        });
+        grid
+    }
+
+    /// 创建网格并加入给定的格子
+    pub fn from_cells<'a>(width: i32, height: i32, cells: impl IntoIterator<Item = &'a IVec2>) -> Self {
+        let mut grid = Self::new(width, height);
+        for &pos in cells {
+            grid.add(pos);
+        }
+        grid
+    }
+
+    fn index(&self, pos: IVec2) -> Option<usize> {
+        if pos.x < 0 || pos.y < 0 || pos.x >= self.width || pos.y >= self.height {
+            return None;
+        }
+        Some((pos.y * self.width + pos.x) as usize)
+    }
+
+    /// 网格宽度
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    /// 网格高度
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    /// 格子上压了一节蛇身（越界忽略）
+    pub fn add(&mut self, pos: IVec2) {
+        if let Some(i) = self.index(pos) {
+            if self.counts[i] == 0
+                && let Some(free) = &mut self.free
+            {
+                free.index.occupy(pos);
+            }
+            self.counts[i] = self.counts[i].saturating_add(1);
+        }
+    }
+
+    /// 格子上少了一节蛇身（越界忽略）
+    pub fn remove(&mut self, pos: IVec2) {
+        if let Some(i) = self.index(pos) {
+            self.counts[i] = self.counts[i].saturating_sub(1);
+            if self.counts[i] == 0
+                && let Some(free) = &mut self.free
+                && !free.fixed[i]
+            {
+                free.index.release(pos);
+            }
+        }
+    }
+
+    /// 格子上的蛇身节数
+    pub fn count(&self, pos: IVec2) -> u16 {
+        self.index(pos).map_or(0, |i| self.counts[i])
+    }
+
+    /// 格子是否被占用
+    pub fn is_occupied(&self, pos: IVec2) -> bool {
+        self.count(pos) > 0
+    }
+
+    /// 清空所有格子（固定阻挡的格子保留）
+    pub fn clear(&mut self) {
+        self.counts.fill(0);
+        self.sync_free_cells();
+    }
+
+    /// 设置固定阻挡的格子（只对维护空闲格子索引的网格有效）
+    pub fn set_fixed(&mut self, cells: &[IVec2]) {
+        let Some(free) = &mut self.free else { return };
+        free.fixed.fill(false);
+        for &pos in cells {
+            if pos.x >= 0 && pos.y >= 0 && pos.x < self.width && pos.y < self.height {
+                free.fixed[(pos.y * self.width + pos.x) as usize] = true;
+            }
+        }
+        self.sync_free_cells();
+    }
+
+    /// 按计数和固定阻挡重建空闲格子索引
+    fn sync_free_cells(&mut self) {
+        let Some(free) = &mut self.free else { return };
+        let width = self.width;
+        let counts = &self.counts;
+        let blocked = (0..counts.len())
+            .filter(|&i| counts[i] > 0 || free.fixed[i])
+            .map(|i| ivec2(i as i32 % width, i as i32 / width));
+        free.index = FreeCellIndex::with_blocked(width, self.height, blocked);
+    }
+
+    /// 格子是否固定阻挡
+    pub fn is_fixed(&self, pos: IVec2) -> bool {
+        match (&self.free, self.index(pos)) {
+            (Some(free), Some(i)) => free.fixed[i],
+            _ => false,
+        }
+    }
+
+    /// 不是固定阻挡的格子数（没有维护空闲格子索引时是全部格子）
+    pub fn open_cells(&self) -> usize {
+        let cells = self.counts.len();
+        self.free.as_ref().map_or(cells, |free| cells - free.fixed.iter().filter(|&&fixed| fixed).count())
+    }
+
+    /// 空闲格子索引：没有蛇身、也不是固定阻挡的格子（只有 [`with_free_cells`](Self::with_free_cells) 创建的网格有）
+    pub fn free_cells(&self) -> Option<&FreeCellIndex> {
+        self.free.as_ref().map(|f| &f.index)
+    }
+
+    /// 按给定的格子重新计数
+    pub fn rebuild<'a>(&mut self, cells: impl IntoIterator<Item = &'a IVec2>) {
+        self.clear();
+        for &pos in cells {
+            self.add(pos);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_overlapping_segments() {
+        let mut grid = OccupancyGrid::new(4, 4);
+        grid.add(ivec2(1, 1));
+        grid.add(ivec2(1, 1));
+        assert_eq!(grid.count(ivec2(1, 1)), 2);
+
+        grid.remove(ivec2(1, 1));
+        assert!(grid.is_occupied(ivec2(1, 1)));
+        grid.remove(ivec2(1, 1));
+        assert!(!grid.is_occupied(ivec2(1, 1)));
+
+        // 多余的减少和越界访问都被忽略
+        grid.remove(ivec2(1, 1));
+        grid.add(ivec2(-1, 9));
+        assert_eq!(grid.count(ivec2(1, 1)), 0);
+        assert_eq!(grid.count(ivec2(-1, 9)), 0);
+    }
+
+    #[test]
+    fn test_rebuild_matches_cells() {
+        let cells = [ivec2(0, 0), ivec2(3, 2), ivec2(3, 2)];
+        let mut grid = OccupancyGrid::from_cells(4, 4, &cells);
+        assert_eq!(grid.count(ivec2(3, 2)), 2);
+
+        grid.rebuild(&[ivec2(2, 2)]);
+        assert!(!grid.is_occupied(ivec2(0, 0)));
+        assert!(grid.is_occupied(ivec2(2, 2)));
+    }
+
+    #[test]
+    fn test_free_cells_follow_counts_and_fixed_cells() {
+        let mut grid = OccupancyGrid::with_free_cells(3, 2);
+        grid.set_fixed(&[ivec2(2, 1)]);
+        assert_eq!(grid.free_cells().unwrap().len(), 5);
+        assert_eq!(grid.open_cells(), 5);
+
+        // 同一格压两次，减掉一次后仍然不空闲
+        grid.add(ivec2(0, 0));
+        grid.add(ivec2(0, 0));
+        grid.remove(ivec2(0, 0));
+        assert!(!grid.free_cells().unwrap().is_free(ivec2(0, 0)));
+        grid.remove(ivec2(0, 0));
+        assert!(grid.free_cells().unwrap().is_free(ivec2(0, 0)));
+
+        // 蛇身离开固定阻挡的格子后它仍然不空闲
+        grid.add(ivec2(2, 1));
+        grid.remove(ivec2(2, 1));
+        assert!(grid.is_fixed(ivec2(2, 1)));
+        assert!(!grid.free_cells().unwrap().is_free(ivec2(2, 1)));
+
+        grid.rebuild(&[ivec2(1, 0)]);
+        assert_eq!(grid.free_cells().unwrap().len(), 4);
+        assert_eq!(grid.open_cells(), 5);
+        assert!(OccupancyGrid::new(3, 2).free_cells().is_none());
+    }
+}
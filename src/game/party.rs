@@ -0,0 +1,411 @@
+//! 本地多人（同一个键盘 2-4 名玩家）
+//!
+//! 每名玩家（[`Player`]）有自己的蛇、按键、颜色、Buff、受伤动画、Combo 和分数，
+//! 按自己的速度独立移动。输赢由 [`PartyMode`] 决定（见 [`Party::update`]）：
+//!
+//! - 对战：最后存活的蛇获胜
+//! - 限时对战：倒下的玩家 [`RESPAWN_DELAY`] 秒后重生，[`VERSUS_TIME_LIMIT`] 秒后分数最高的获胜
+//! - 合作：分数共享，活着的玩家吃到食物时救起一名倒下的队友，全员倒下时游戏结束
+//!
+//! 玩家之间相撞按 [`CombatRules`](crate::types::CombatRules) 结算，合作时关闭误伤则互相穿过；
+//! AI 蛇把每名玩家都当作目标（见 [`AIManager`](super::AIManager)）。
+
+use macroquad::prelude::*;
+use crate::types::{BoardDims, BuffState, CombatRules, ComboState, DamageState, Difficulty, GridKind, KeyBindings, PartyMode};
+use super::{HeadOn, PlayerView, Snake, Strike};
+
+/// 最多同时游玩的本地玩家数
+pub const MAX_PLAYERS: usize = 4;
+/// 限时对战的时长（秒）
+pub const VERSUS_TIME_LIMIT: f32 = 120.0;
+/// 限时对战中倒下的玩家重生前等待的时间（秒）
+pub const RESPAWN_DELAY: f32 = 3.0;
+
+/// 每名玩家的 (蛇头颜色, 蛇身颜色)，避开 AI 蛇和队伍的颜色
+pub const PLAYER_COLORS: [(Color, Color); MAX_PLAYERS] = [
+    (GREEN, LIME),
+    (Color::new(0.4, 1.0, 1.0, 1.0), Color::new(0.1, 0.65, 0.7, 1.0)),
+    (Color::new(1.0, 1.0, 0.5, 1.0), Color::new(0.75, 0.75, 0.15, 1.0)),
+    (Color::new(1.0, 1.0, 1.0, 1.0), Color::new(0.65, 0.65, 0.72, 1.0)),
+];
+
+/// 一名本地玩家
+#[derive(Clone)]
+pub struct Player {
+    /// 玩家序号（0 是第 1 名玩家）
+    pub index: usize,
+    /// 玩家的蛇（颜色也在蛇上）
+    pub snake: Snake,
+    /// 方向键
+    pub keys: KeyBindings,
+    /// Buff 状态
+    pub buff_state: BuffState,
+    /// 受伤动画状态
+    pub damage_state: DamageState,
+    /// Combo 状态
+    pub combo_state: ComboState,
+    /// 个人分数（合作时全队分数是所有人的总和）
+    pub score: u32,
+    /// 已经倒下（蛇不在棋盘上）
+    pub downed: bool,
+    /// 倒下以来的时间（限时对战重生用）
+    pub down_timer: f32,
+    /// 固定时间步长累加器（每名玩家按自己的速度移动）
+    pub accumulator: f32,
+}
+
+impl Player {
+    /// 第 `index` 名玩家（共 `count` 名），蛇放在 `board` 上的出生位置
+    pub fn new(index: usize, count: usize, grid: GridKind, board: BoardDims, keys: KeyBindings) -> Self {
+        let (head_color, color) = PLAYER_COLORS[index % MAX_PLAYERS];
+        let mut snake = Snake::on_grid(grid);
+        snake.head_color = head_color;
+        snake.color = color;
+        snake.board = board;
+        let (head, step) = start_position(board, index, count);
+        snake.reset_at(head, step);
+        Player {
+            index,
+            snake,
+            keys,
+            buff_state: BuffState::default(),
+            damage_state: DamageState::default(),
+            combo_state: ComboState::default(),
+            score: 0,
+            downed: false,
+            down_timer: 0.0,
+            accumulator: 0.0,
+        }
+    }
+
+    /// 显示名称（P1-P4）
+    pub fn name(&self) -> String {
+        format!("P{}", self.index + 1)
+    }
+
+    /// 移动间隔（秒）
+    ///
+    /// 基础 120ms，每增长 3 节减少 5ms，最小 40ms；再乘 Buff 倍率（速度模式 0.5x，减速 2x，粘液 1.5x）
+    /// 和难度倍率（简单 1.25x，困难 0.8x）
+    pub fn tick(&self, difficulty: Difficulty) -> f32 {
+        let len = self.snake.len() as u32;
+        let base = (120u32.saturating_sub((len / 3) * 5)).max(40) as f32 / 1000.0;
+        base * self.buff_state.tick_multiplier() * difficulty.tick_multiplier()
+    }
+
+    /// AI 看到的这名玩家
+    pub fn view(&self) -> PlayerView<'_> {
+        PlayerView { snake: &self.snake, buffs: &self.buff_state }
+    }
+
+    /// 其他玩家撞到某格时会不会撞上这名玩家（倒下或幽灵状态时穿过）
+    pub fn solid_at(&self, pos: IVec2) -> bool {
+        !self.downed && !self.buff_state.ghost_active && self.snake.occupies(pos)
+    }
+
+    /// 倒下：清掉所有状态，返回留在棋盘上的蛇身（调用方把它变成掉落食物）
+    pub fn knock_out(&mut self) -> Vec<IVec2> {
+        self.downed = true;
+        self.down_timer = 0.0;
+        self.buff_state = BuffState::default();
+        self.damage_state = DamageState::default();
+        self.snake.body.iter().copied().collect()
+    }
+
+    /// 重新回到棋盘：蛇头在 `head`、沿 `step` 排开 3 节，带一段护盾防止刚出现就撞死
+    pub fn revive(&mut self, head: IVec2, step: IVec2) {
+        self.snake.reset_at(head, step);
+        self.downed = false;
+        self.accumulator = 0.0;
+        self.buff_state.activate_shield();
+    }
+}
+
+/// `board` 上第 `index` 名玩家（共 `count` 名）的出生位置：(蛇头, 排开方向)
+///
+/// 各占一行，相邻两名玩家朝相反方向出发；只有一名玩家时就是 [`Snake::reset`] 的位置
+pub fn start_position(board: BoardDims, index: usize, count: usize) -> (IVec2, IVec2) {
+    let y = board.origin.y + board.height * (index as i32 + 1) / (count as i32 + 1);
+    let x = board.center().x;
+    if index.is_multiple_of(2) {
+        (ivec2(x, y), ivec2(1, 0))
+    } else {
+        (ivec2(x - 1, y), ivec2(-1, 0))
+    }
+}
+
+/// 给倒下的玩家找一个能放下 3 节蛇身的空位：先试出生位置，再从上到下扫描整个 `board`
+pub fn free_spot(board: BoardDims, preferred: (IVec2, IVec2), blocked: impl Fn(IVec2) -> bool) -> Option<(IVec2, IVec2)> {
+    let fits = |(head, step): (IVec2, IVec2)| {
+        (0..3).map(|i| head - step * i).all(|p| board.contains(p) && !blocked(p))
+    };
+    let (origin, end) = (board.origin, board.end());
+    let scan = (origin.y..end.y)
+        .flat_map(|y| (origin.x..end.x).map(move |x| ivec2(x, y)))
+        .flat_map(|head| [(head, ivec2(1, 0)), (head, ivec2(-1, 0))]);
+    std::iter::once(preferred).chain(scan).find(|&spot| fits(spot))
+}
+
+/// 玩家蛇头撞上另一名玩家的结算（见 [`Party::clash`]）
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PlayerClash {
+    /// 经典规则：撞击者死亡
+    Lethal,
+    /// 切割规则：`victim` 保留前 `keep` 节
+    Sever { victim: usize, keep: usize },
+    /// 切割规则：头对头按长度结算
+    HeadOn { victim: usize, result: HeadOn },
+}
+
+/// 一局本地多人的结果
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PartyOutcome {
+    /// 这名玩家获胜
+    Winner(usize),
+    /// 平局（同归于尽或分数相同）
+    Draw,
+    /// 单人或合作时全员倒下
+    Wiped,
+}
+
+/// 本地多人规则状态
+#[derive(Clone, Debug)]
+pub struct Party {
+    /// 规则
+    pub mode: PartyMode,
+    /// 限时对战的剩余时间（秒）
+    pub time_left: f32,
+}
+
+impl Party {
+    /// `players` 名玩家按 `mode` 开局（只有一名玩家时总是单人）
+    pub fn new(mode: PartyMode, players: usize) -> Self {
+        let mode = if players > 1 { mode } else { PartyMode::Solo };
+        Party { mode, time_left: VERSUS_TIME_LIMIT }
+    }
+
+    /// 全队分数：单人和合作时是所有人的总和，对战时是最高分
+    pub fn score(&self, players: &[Player]) -> u32 {
+        match self.mode {
+            PartyMode::Solo | PartyMode::Coop => players.iter().map(|p| p.score).sum(),
+            PartyMode::Versus | PartyMode::Timed => players.iter().map(|p| p.score).max().unwrap_or(0),
+        }
+    }
+
+    /// 推进计时，分出结果时返回
+    pub fn update(&mut self, players: &mut [Player], dt: f32) -> Option<PartyOutcome> {
+        for player in players.iter_mut().filter(|p| p.downed) {
+            player.down_timer += dt;
+        }
+        let mut alive = players.iter().filter(|p| !p.downed);
+        match self.mode {
+            PartyMode::Solo | PartyMode::Coop => alive.next().is_none().then_some(PartyOutcome::Wiped),
+            PartyMode::Versus => match (alive.next(), alive.next()) {
+                (None, _) => Some(PartyOutcome::Draw),
+                (Some(last), None) => Some(PartyOutcome::Winner(last.index)),
+                _ => None,
+            },
+            PartyMode::Timed => {
+                self.time_left = (self.time_left - dt).max(0.0);
+                (self.time_left <= 0.0).then(|| Self::best_score(players))
+            }
+        }
+    }
+
+    /// 分数最高的玩家，并列时平局
+    fn best_score(players: &[Player]) -> PartyOutcome {
+        let best = players.iter().map(|p| p.score).max().unwrap_or(0);
+        let mut leaders = players.iter().filter(|p| p.score == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(leader), None) => PartyOutcome::Winner(leader.index),
+            _ => PartyOutcome::Draw,
+        }
+    }
+
+    /// 合作时活着的玩家吃到食物能救起的队友（倒下最久的那个）
+    pub fn rescue_target(&self, players: &[Player]) -> Option<usize> {
+        if self.mode != PartyMode::Coop {
+            return None;
+        }
+        players
+            .iter()
+            .filter(|p| p.downed)
+            .max_by(|a, b| a.down_timer.total_cmp(&b.down_timer))
+            .map(|p| p.index)
+    }
+
+    /// 玩家之间会不会相撞（合作时关闭误伤则互相穿过）
+    pub fn players_collide(&self, friendly_fire: bool) -> bool {
+        self.mode != PartyMode::Coop || friendly_fire
+    }
+
+    /// 第 `attacker` 名玩家的蛇头移动到 `head` 后撞上的其他玩家
+    ///
+    /// 撞击者能穿过身体（护盾、幽灵、沙虫）或对方处于幽灵状态时互相穿过；
+    /// 合作时关闭误伤也互相穿过
+    pub fn clash(
+        &self,
+        players: &[Player],
+        attacker: usize,
+        head: IVec2,
+        combat: CombatRules,
+        friendly_fire: bool,
+    ) -> Option<PlayerClash> {
+        if players[attacker].buff_state.can_pass_through() || !self.players_collide(friendly_fire) {
+            return None;
+        }
+        let (victim, strike) = players
+            .iter()
+            .filter(|p| p.index != attacker && p.solid_at(head))
+            .find_map(|p| Strike::at(&p.snake.body, head).map(|s| (p.index, s)))?;
+        Some(match (combat, strike) {
+            (CombatRules::Lethal, _) => PlayerClash::Lethal,
+            (CombatRules::Cutting, Strike::Sever(keep)) => PlayerClash::Sever { victim, keep },
+            (CombatRules::Cutting, Strike::HeadOn) => PlayerClash::HeadOn {
+                victim,
+                result: HeadOn::resolve(players[attacker].snake.len(), players[victim].snake.len()),
+            },
+        })
+    }
+
+    /// 限时对战中等够了时间、该重生的玩家
+    pub fn respawns_due(&self, players: &[Player]) -> Vec<usize> {
+        if self.mode != PartyMode::Timed {
+            return Vec::new();
+        }
+        players.iter().filter(|p| p.downed && p.down_timer >= RESPAWN_DELAY).map(|p| p.index).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BoardSize;
+
+    fn party(mode: PartyMode, count: usize) -> (Party, Vec<Player>) {
+        let players = (0..count)
+            .map(|i| Player::new(i, count, GridKind::Square, BoardDims::DEFAULT, KeyBindings::player(i)))
+            .collect();
+        (Party::new(mode, count), players)
+    }
+
+    #[test]
+    fn test_start_positions_are_separate_and_playable() {
+        for size in BoardSize::ALL {
+            for count in 1..=MAX_PLAYERS {
+                let players: Vec<Player> = (0..count)
+                    .map(|i| Player::new(i, count, GridKind::Square, size.dims(), KeyBindings::player(i)))
+                    .collect();
+                let cells: Vec<IVec2> = players.iter().flat_map(|p| p.snake.body.iter().copied()).collect();
+                for (i, cell) in cells.iter().enumerate() {
+                    assert!(!cells[i + 1..].contains(cell));
+                    assert!(size.contains(*cell), "{cell:?} outside the {} board", size.name());
+                }
+            }
+        }
+        // 单人时和原来的初始位置一样
+        let (_, solo) = party(PartyMode::Solo, 1);
+        assert_eq!(solo[0].snake.body, Snake::new().body);
+        assert_eq!(solo[0].snake.head_color, GREEN);
+    }
+
+    #[test]
+    fn test_versus_last_alive_wins() {
+        let (mut rules, mut players) = party(PartyMode::Versus, 3);
+        assert_eq!(rules.update(&mut players, 0.1), None);
+        players[0].knock_out();
+        assert_eq!(rules.update(&mut players, 0.1), None);
+        players[2].knock_out();
+        assert_eq!(rules.update(&mut players, 0.1), Some(PartyOutcome::Winner(1)));
+        players[1].knock_out();
+        assert_eq!(rules.update(&mut players, 0.1), Some(PartyOutcome::Draw));
+        // 对战不救人也不重生
+        assert_eq!(rules.rescue_target(&players), None);
+        assert!(rules.respawns_due(&players).is_empty());
+    }
+
+    #[test]
+    fn test_timed_versus_respawns_and_ranks_by_score() {
+        let (mut rules, mut players) = party(PartyMode::Timed, 2);
+        players[0].score = 7;
+        players[1].score = 9;
+        players[1].knock_out();
+        assert_eq!(rules.update(&mut players, RESPAWN_DELAY / 2.0), None);
+        assert!(rules.respawns_due(&players).is_empty());
+        assert_eq!(rules.update(&mut players, RESPAWN_DELAY / 2.0), None);
+        assert_eq!(rules.respawns_due(&players), [1]);
+        assert_eq!(rules.score(&players), 9);
+
+        assert_eq!(rules.update(&mut players, VERSUS_TIME_LIMIT), Some(PartyOutcome::Winner(1)));
+        players[0].score = 9;
+        assert_eq!(rules.update(&mut players, 0.1), Some(PartyOutcome::Draw));
+    }
+
+    #[test]
+    fn test_coop_shares_score_and_revives_partner() {
+        let (mut rules, mut players) = party(PartyMode::Coop, 2);
+        players[0].score = 4;
+        players[1].score = 6;
+        assert_eq!(rules.score(&players), 10);
+
+        let body = players[1].knock_out();
+        assert_eq!(body.len(), 3);
+        assert_eq!(rules.update(&mut players, 0.1), None);
+        assert_eq!(rules.rescue_target(&players), Some(1));
+
+        // 出生位置被占住时换一个空位，救起来带护盾
+        let start = start_position(BoardDims::DEFAULT, 1, 2);
+        let spot = free_spot(BoardDims::DEFAULT, start, |p| p == start.0).unwrap();
+        assert_ne!(spot.0, start.0);
+        players[1].revive(spot.0, spot.1);
+        assert!(!players[1].downed && players[1].buff_state.shield_active);
+        assert_eq!(players[1].snake.head(), spot.0);
+        assert_eq!(rules.rescue_target(&players), None);
+
+        players[0].knock_out();
+        players[1].knock_out();
+        assert_eq!(rules.update(&mut players, 0.1), Some(PartyOutcome::Wiped));
+        assert!(free_spot(BoardDims::DEFAULT, start, |_| true).is_none());
+    }
+
+    #[test]
+    fn test_clash_follows_combat_rules() {
+        let (rules, mut players) = party(PartyMode::Versus, 2);
+        let body: Vec<IVec2> = players[1].snake.body.iter().copied().collect();
+        assert_eq!(rules.clash(&players, 0, body[1], CombatRules::Lethal, false), Some(PlayerClash::Lethal));
+        assert_eq!(
+            rules.clash(&players, 0, body[1], CombatRules::Cutting, false),
+            Some(PlayerClash::Sever { victim: 1, keep: 1 })
+        );
+        assert_eq!(
+            rules.clash(&players, 0, body[0], CombatRules::Cutting, false),
+            Some(PlayerClash::HeadOn { victim: 1, result: HeadOn::BothDie })
+        );
+        assert_eq!(rules.clash(&players, 0, ivec2(0, 0), CombatRules::Lethal, false), None);
+
+        // 幽灵状态的玩家被穿过，护盾状态的玩家穿过别人
+        players[1].buff_state.activate_ghost();
+        assert_eq!(rules.clash(&players, 0, body[1], CombatRules::Lethal, false), None);
+        players[1].buff_state = BuffState::default();
+        players[0].buff_state.activate_shield();
+        assert_eq!(rules.clash(&players, 0, body[1], CombatRules::Lethal, false), None);
+
+        // 合作时关闭误伤互相穿过
+        let (coop, players) = party(PartyMode::Coop, 2);
+        let hit = players[1].snake.body[1];
+        assert_eq!(coop.clash(&players, 0, hit, CombatRules::Lethal, false), None);
+        assert_eq!(coop.clash(&players, 0, hit, CombatRules::Lethal, true), Some(PlayerClash::Lethal));
+    }
+
+    #[test]
+    fn test_tick_speeds_up_with_length_and_buffs() {
+        let (_, mut players) = party(PartyMode::Solo, 1);
+        let player = &mut players[0];
+        assert_eq!(player.tick(Difficulty::Normal), 0.115);
+        player.snake.set_body((0..30).map(|x| ivec2(x, 0)));
+        assert_eq!(player.tick(Difficulty::Normal), 0.07);
+        player.buff_state.activate_speed();
+        assert_eq!(player.tick(Difficulty::Normal), 0.035);
+        assert_eq!(Party::new(PartyMode::Coop, 1).mode, PartyMode::Solo);
+    }
+}
@@ -0,0 +1,284 @@
+//! 寻路与空间分析
+//!
+//! AI 蛇决策用到的棋盘搜索，全部按 [`Grid`] 几何和地图 [`Topology`] 走步，
+//! 所以穿墙环绕、镜像环绕和六边形棋盘都自动支持：
+//!
+//! - [`PathMap`]：从起点出发的 BFS 距离图，记录到每个格子的最短步数和第一步方向。
+//!   棋盘每步代价相同，BFS 即最优；环绕拓扑下曼哈顿距离不是可采纳的启发值，
+//!   所以不用 A*，一次 BFS 同时得到到所有目标的距离。
+//! - [`flood_fill`]：统计从某格出发可到达的空间大小，用来判断一步棋会不会把自己困死。
+//!
+//! 棋盘尺寸按 [`BoardDims`] 传入，蜂群模式的大棋盘和游戏棋盘走同一套搜索。
+//!
+//! 八方向棋盘上不能从两个被占格子之间斜向挤过（见 [`check_diagonal_squeeze`]），
+//! 搜索同样遵守这一规则。
+//!
+//! 传送门入口只有一条出边：踏入入口的下一步一定出现在出口。
+//! 从出口出来的那一步不会再次触发传送，所以出口格子按"是否刚传送过来"区分两种状态；
+//! 距离图每格只记一个距离，先到达的状态生效。
+
+use std::collections::VecDeque;
+use macroquad::prelude::*;
+use crate::types::{BoardDims, Grid, GridKind, Portal, Topology};
+use super::{check_diagonal_squeeze_on, find_portal_exit};
+
+/// 从 `pos` 沿 `dir` 走一步能到达的格子（撞墙、被占或斜向挤过时返回 None）
+///
+/// 返回新位置和（镜像环绕后可能翻转的）新方向
+pub fn passable_step(
+    grid: GridKind,
+    topology: Topology,
+    board: BoardDims,
+    pos: IVec2,
+    dir: IVec2,
+    blocked: &impl Fn(IVec2) -> bool,
+) -> Option<(IVec2, IVec2)> {
+    let (next, next_dir) = grid.step_on(topology, board, pos, dir)?;
+    if blocked(next) || check_diagonal_squeeze_on(grid, topology, board, pos, dir, blocked) {
+        return None;
+    }
+    Some((next, next_dir))
+}
+
+/// 从 `pos` 出发的下一步：入口只能走到出口，其他格子（包括刚传送出来的出口）按方向走一步
+///
+/// 对每个后继调用 `visit(新位置, 是否经过传送)`（搜索的内层循环，不分配内存）
+#[allow(clippy::too_many_arguments)]
+fn for_each_successor(
+    grid: GridKind,
+    topology: Topology,
+    board: BoardDims,
+    pos: IVec2,
+    warped: bool,
+    portals: &[Portal],
+    blocked: &impl Fn(IVec2) -> bool,
+    mut visit: impl FnMut(IVec2, bool),
+) {
+    if !warped && let Some(exit) = find_portal_exit(pos, IVec2::ZERO, portals) {
+        if !blocked(exit.to) {
+            visit(exit.to, true);
+        }
+        return;
+    }
+    for &dir in grid.directions() {
+        if let Some((next, _)) = passable_step(grid, topology, board, pos, dir, blocked) {
+            visit(next, false);
+        }
+    }
+}
+
+/// BFS 距离图
+///
+/// 起点本身不检查是否被占（通常是蛇头）
+#[derive(Clone, Debug)]
+pub struct PathMap {
+    board: BoardDims,
+    start: IVec2,
+    dist: Vec<u32>,
+    first_dir: Vec<Option<IVec2>>,
+}
+
+impl PathMap {
+    /// 从 `start` 出发搜索，`blocked` 判断格子是否不可进入
+    ///
+    /// `first_dirs` 限定第一步可选的方向（例如不能掉头）；
+    /// 经过 `portals` 的路径第一步方向记为走向入口的方向
+    pub fn search(
+        grid: GridKind,
+        topology: Topology,
+        board: BoardDims,
+        start: IVec2,
+        first_dirs: &[IVec2],
+        portals: &[Portal],
+        blocked: impl Fn(IVec2) -> bool,
+    ) -> Self {
+        let cells = board.cells();
+        let mut map = PathMap {
+            board,
+            start,
+            dist: vec![u32::MAX; cells],
+            first_dir: vec![None; cells],
+        };
+        let Some(start_idx) = board.index(start) else {
+            return map;
+        };
+        map.dist[start_idx] = 0;
+
+        let mut queue = VecDeque::new();
+        for &dir in first_dirs {
+            let Some((next, _)) = passable_step(grid, topology, board, start, dir, &blocked) else {
+                continue;
+            };
+            let Some(i) = board.index(next) else { continue };
+            if map.dist[i] == u32::MAX {
+                map.dist[i] = 1;
+                map.first_dir[i] = Some(dir);
+                queue.push_back((next, false));
+            }
+        }
+
+        while let Some((pos, warped)) = queue.pop_front() {
+            let Some(i) = board.index(pos) else { continue };
+            let (d, first) = (map.dist[i], map.first_dir[i]);
+            for_each_successor(grid, topology, board, pos, warped, portals, &blocked, |next, warp| {
+                let Some(j) = board.index(next) else { return };
+                if map.dist[j] == u32::MAX {
+                    map.dist[j] = d + 1;
+                    map.first_dir[j] = first;
+                    queue.push_back((next, warp));
+                }
+            });
+        }
+        map
+    }
+
+    /// 起点
+    pub fn start(&self) -> IVec2 {
+        self.start
+    }
+
+    /// 到 `pos` 的最短步数，不可达返回 None
+    pub fn distance(&self, pos: IVec2) -> Option<u32> {
+        self.board.index(pos).map(|i| self.dist[i]).filter(|&d| d != u32::MAX)
+    }
+
+    /// 沿最短路径前往 `pos` 的第一步方向，不可达或就是起点时返回 None
+    pub fn first_step(&self, pos: IVec2) -> Option<IVec2> {
+        self.board.index(pos).and_then(|i| self.first_dir[i])
+    }
+
+    /// 是否可以到达 `pos`
+    pub fn reachable(&self, pos: IVec2) -> bool {
+        self.distance(pos).is_some()
+    }
+}
+
+/// 从 `start` 出发可到达的格子数（含起点），数到 `limit` 即停止
+///
+/// 起点被占时返回 0；起点是传送门入口时从出口继续数
+pub fn flood_fill(
+    grid: GridKind,
+    topology: Topology,
+    board: BoardDims,
+    start: IVec2,
+    limit: usize,
+    portals: &[Portal],
+    blocked: impl Fn(IVec2) -> bool,
+) -> usize {
+    let Some(start_idx) = board.index(start) else {
+        return 0;
+    };
+    if blocked(start) {
+        return 0;
+    }
+    let mut seen = vec![false; board.cells()];
+    seen[start_idx] = true;
+    let mut queue = VecDeque::from([(start, false)]);
+    let mut count = 1;
+
+    while let Some((pos, warped)) = queue.pop_front() {
+        for_each_successor(grid, topology, board, pos, warped, portals, &blocked, |next, warp| {
+            let Some(j) = board.index(next).filter(|_| count < limit) else { return };
+            if !seen[j] {
+                seen[j] = true;
+                count += 1;
+                queue.push_back((next, warp));
+            }
+        });
+        if count >= limit {
+            return count;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::GRID_W;
+
+    /// 竖墙 x = 5（留出 y = 0 一个缺口）
+    fn wall_with_gap(pos: IVec2) -> bool {
+        pos.x == 5 && pos.y != 0
+    }
+
+    #[test]
+    fn test_path_goes_around_wall() {
+        let dirs = GridKind::Square.directions();
+        let map = PathMap::search(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(4, 10), dirs, &[], wall_with_gap);
+        // 绕过缺口：向上 10，向右 3，向下 10
+        assert_eq!(map.distance(ivec2(7, 10)), Some(23));
+        assert_eq!(map.first_step(ivec2(7, 10)), Some(ivec2(0, -1)));
+        assert!(!map.reachable(ivec2(5, 3)));
+    }
+
+    #[test]
+    fn test_path_wraps_around_torus() {
+        let dirs = GridKind::Square.directions();
+        let map = PathMap::search(GridKind::Square, Topology::Torus, BoardDims::DEFAULT, ivec2(1, 5), dirs, &[], |_| false);
+        assert_eq!(map.distance(ivec2(GRID_W - 1, 5)), Some(2));
+        assert_eq!(map.first_step(ivec2(GRID_W - 1, 5)), Some(ivec2(-1, 0)));
+
+        let bounded = PathMap::search(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(1, 5), dirs, &[], |_| false);
+        assert_eq!(bounded.distance(ivec2(GRID_W - 1, 5)), Some(GRID_W as u32 - 2));
+    }
+
+    #[test]
+    fn test_search_covers_larger_board() {
+        let board = BoardDims::new(120, 90);
+        let dirs = GridKind::Square.directions();
+        let map = PathMap::search(GridKind::Square, Topology::Bounded, board, ivec2(1, 5), dirs, &[], |_| false);
+        assert_eq!(map.distance(ivec2(119, 89)), Some(118 + 84));
+        assert!(!map.reachable(ivec2(120, 5)));
+
+        let torus = PathMap::search(GridKind::Square, Topology::Torus, board, ivec2(1, 5), dirs, &[], |_| false);
+        assert_eq!(torus.distance(ivec2(119, 5)), Some(2));
+        assert_eq!(flood_fill(GridKind::Square, Topology::Torus, board, ivec2(0, 0), usize::MAX, &[], |_| false), board.cells());
+    }
+
+    #[test]
+    fn test_first_dirs_restrict_initial_move() {
+        let map = PathMap::search(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(10, 10), &[ivec2(1, 0)], &[], |_| false);
+        // 不能直接向左，只能先向右再绕回来（起点是蛇头，不能穿过）
+        assert_eq!(map.distance(ivec2(9, 10)), Some(5));
+        assert_eq!(map.first_step(ivec2(9, 10)), Some(ivec2(1, 0)));
+    }
+
+    #[test]
+    fn test_flood_fill_measures_pocket() {
+        // 左上角 3×2 的口袋
+        let pocket = |p: IVec2| (p.x == 3 && p.y <= 2) || (p.y == 2 && p.x <= 3);
+        assert_eq!(flood_fill(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(0, 0), usize::MAX, &[], pocket), 6);
+        assert_eq!(flood_fill(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(0, 0), 4, &[], pocket), 4);
+        assert_eq!(flood_fill(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(3, 0), usize::MAX, &[], pocket), 0);
+
+        // 环绕后口袋和棋盘另一侧连通
+        let total = flood_fill(GridKind::Square, Topology::Torus, BoardDims::DEFAULT, ivec2(0, 0), usize::MAX, &[], pocket);
+        assert!(total > 6);
+    }
+
+    #[test]
+    fn test_path_goes_through_portal() {
+        let dirs = GridKind::Square.directions();
+        let portals = [Portal::permanent(ivec2(3, 10), ivec2(20, 10), WHITE)];
+        let map = PathMap::search(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(1, 10), dirs, &portals, wall_with_gap);
+        // 入口 2 步，出口 1 步，再走 2 步
+        assert_eq!(map.distance(ivec2(22, 10)), Some(5));
+        assert_eq!(map.first_step(ivec2(22, 10)), Some(ivec2(1, 0)));
+        // 刚传送出来不会再被传回去：出口格子的邻居照常可达
+        assert_eq!(map.distance(ivec2(20, 11)), Some(4));
+
+        // 出口被占时入口是死路
+        let sealed = |p: IVec2| p == ivec2(20, 10) || wall_with_gap(p);
+        let filled = flood_fill(GridKind::Square, Topology::Bounded, BoardDims::DEFAULT, ivec2(3, 10), usize::MAX, &portals, sealed);
+        assert_eq!(filled, 1);
+    }
+
+    #[test]
+    fn test_octile_search_respects_squeeze_rule() {
+        // (1,0) 和 (0,1) 被占，不能从 (0,0) 斜向挤到 (1,1)
+        let blocked = |p: IVec2| p == ivec2(1, 0) || p == ivec2(0, 1);
+        let filled = flood_fill(GridKind::Octile, Topology::Bounded, BoardDims::DEFAULT, ivec2(0, 0), usize::MAX, &[], blocked);
+        assert_eq!(filled, 1);
+    }
+}
@@ -0,0 +1,158 @@
+//! 传送门管理器
+//!
+//! 统一管理地图定义的永久传送门和随机生成的临时传送门。
+//! 生成间隔、概率和同时存在的最大对数都可以声明式配置。
+
+use macroquad::prelude::*;
+use ::rand::Rng;
+use crate::game::GameRng;
+use crate::types::{Fruit, Portal, PortalExit};
+use super::{spawn_portal, update_portals, find_portal_exit, FreeCellIndex};
+
+/// 传送门管理器
+pub struct PortalManager {
+    /// 当前所有传送门（永久 + 临时）
+    pub portals: Vec<Portal>,
+    /// 地图定义的永久传送门（重置时恢复）
+    map_portals: Vec<Portal>,
+    /// 临时传送门生成间隔（秒）
+    pub interval: f32,
+    /// 每次到达间隔时的生成概率
+    pub probability: f32,
+    /// 临时传送门最大同时存在对数
+    pub max_pairs: usize,
+    /// 生成计时器
+    spawn_timer: f32,
+}
+
+impl Default for PortalManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortalManager {
+    /// 创建空的传送门管理器（不生成临时传送门）
+    pub fn new() -> Self {
+        PortalManager {
+            portals: Vec::new(),
+            map_portals: Vec::new(),
+            interval: 0.0,
+            probability: 0.0,
+            max_pairs: 0,
+            spawn_timer: 0.0,
+        }
+    }
+
+    /// 设置生成间隔
+    pub fn interval(mut self, seconds: f32) -> Self {
+        self.interval = seconds;
+        self
+    }
+
+    /// 设置生成概率
+    pub fn probability(mut self, prob: f32) -> Self {
+        self.probability = prob.clamp(0.0, 1.0);
+        self
+    }
+
+    /// 设置临时传送门最大对数
+    pub fn max_pairs(mut self, count: usize) -> Self {
+        self.max_pairs = count;
+        self
+    }
+
+    /// 设置地图永久传送门（替换之前的地图传送门）
+    pub fn set_map_portals(&mut self, portals: Vec<Portal>) {
+        self.map_portals = portals;
+        self.reset();
+    }
+
+    /// 重置：清除临时传送门，恢复地图传送门
+    pub fn reset(&mut self) {
+        self.portals = self.map_portals.clone();
+        self.spawn_timer = 0.0;
+    }
+
+    /// 当前临时传送门数量
+    pub fn temporary_count(&self) -> usize {
+        self.portals.iter().filter(|p| !p.is_permanent()).count()
+    }
+
+    /// 查找传送出口
+    pub fn find_exit(&self, pos: IVec2, dir: IVec2) -> Option<PortalExit> {
+        find_portal_exit(pos, dir, &self.portals)
+    }
+
+    /// 更新：移除过期传送门并按规则生成新的临时传送门
+    ///
+    /// 位置从空闲格子索引 `free` 采样，`blocked` 为索引之外不能放置传送门的格子（玩家蛇身等）
+    pub fn update(
+        &mut self,
+        free: &FreeCellIndex,
+        blocked: impl Fn(IVec2) -> bool,
+        fruits: &[Fruit],
+        game_time: f32,
+        dt: f32,
+        rng: &mut GameRng,
+    ) {
+        update_portals(&mut self.portals, game_time);
+
+        if self.max_pairs == 0 {
+            return;
+        }
+
+        self.spawn_timer += dt;
+        if self.spawn_timer < self.interval {
+            return;
+        }
+        self.spawn_timer = 0.0;
+
+        if self.temporary_count() >= self.max_pairs || !rng.gen_bool(self.probability as f64) {
+            return;
+        }
+
+        if let Some(portal) = spawn_portal(free, blocked, fruits, &self.portals, game_time, rng) {
+            self.portals.push(portal);
+        }
+    }
+}
+
+/// 创建默认的传送门管理器
+///
+/// 每15秒30%概率生成一对临时传送门，最多同时3对
+pub fn create_default_portal_manager() -> PortalManager {
+    PortalManager::new()
+        .interval(15.0)
+        .probability(0.3)
+        .max_pairs(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::SeedableRng;
+    use crate::constants::{GRID_W, GRID_H};
+
+    #[test]
+    fn test_reset_restores_map_portals() {
+        let mut manager = create_default_portal_manager();
+        manager.set_map_portals(vec![Portal::permanent(ivec2(1, 1), ivec2(9, 9), WHITE)]);
+        manager.portals.push(Portal::new(ivec2(2, 2), ivec2(3, 3), WHITE, 0.0, 5.0));
+        assert_eq!(manager.temporary_count(), 1);
+        manager.reset();
+        assert_eq!(manager.portals.len(), 1);
+        assert!(manager.portals[0].is_permanent());
+    }
+
+    #[test]
+    fn test_spawns_up_to_max_pairs() {
+        let mut manager = PortalManager::new().interval(1.0).probability(1.0).max_pairs(2);
+        let mut rng = GameRng::seed_from_u64(7);
+        let free = FreeCellIndex::new(GRID_W, GRID_H);
+        for _ in 0..5 {
+            manager.update(&free, |_| false, &[], 0.0, 1.0, &mut rng);
+        }
+        assert_eq!(manager.temporary_count(), 2);
+    }
+}
@@ -1,21 +1,24 @@
 //! 沙虫模式管理模块
 //!
 //! 处理沙虫变身的完整状态机逻辑
+//!
+//! 玩家沙虫离开棋盘、填满整个屏幕再坍缩（[`update_sandworm_mode`]）；
+//! AI 沙虫变色后留在棋盘上横冲直撞一段时间（[`update_ai_sandworm`]）。
 
 use macroquad::prelude::*;
 use ::rand::Rng;
-use ::rand::rngs::ThreadRng;
+use crate::game::GameRng;
 use crate::constants::{
     CELL, GRID_W, GRID_H,
     SANDWORM_TICK_INTERVAL, SANDWORM_FLASH_DURATION,
-    SANDWORM_TRANSFORM_INTERVAL, SANDWORM_FILLED_FLASH_DURATION,
+    SANDWORM_TRANSFORM_INTERVAL, SANDWORM_FILLED_FLASH_DURATION, AI_SANDWORM_DURATION,
 };
 use crate::types::{BuffState, SandwormPhase, Fruit, Particle};
-use crate::game::{Snake, spawn_food};
+use crate::game::Snake;
 
 /// 沙虫模式更新结果
 pub struct SandwormUpdateResult {
-    /// 是否需要重新生成食物
+    /// 是否需要重新生成食物（沙虫吞掉的食物在棋盘外）
     pub need_respawn_food: bool,
     /// 奖励分数
     pub bonus_score: u32,
@@ -38,7 +41,7 @@ pub fn update_sandworm_mode(
     food: &mut IVec2,
     particles: &mut Vec<Particle>,
     dt: f32,
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) -> SandwormUpdateResult {
     let mut result = SandwormUpdateResult {
         need_respawn_food: false,
@@ -103,8 +106,8 @@ pub fn update_sandworm_mode(
                     head.x + buff.sandworm_exit_dir.x,
                     head.y + buff.sandworm_exit_dir.y,
                 );
-                snake.body.insert(0, new_head);
-                snake.body.pop();
+                snake.push_head(new_head);
+                snake.pop_tail();
 
                 // 检查是否完全离开屏幕
                 let all_outside = snake.body.iter().all(|seg| {
@@ -133,7 +136,7 @@ pub fn update_sandworm_mode(
                     buff.sandworm_index = 0;
 
                     // 重置蛇身为起点
-                    snake.body = vec![entry_pos];
+                    snake.set_body([entry_pos]);
                     snake.dir = entry_dir;
                 }
             }
@@ -146,7 +149,7 @@ pub fn update_sandworm_mode(
 
                 if buff.sandworm_index < buff.sandworm_path.len() {
                     let next_pos = buff.sandworm_path[buff.sandworm_index];
-                    snake.body.insert(0, next_pos);
+                    snake.push_head(next_pos);
                     buff.sandworm_index += 1;
 
                     // 沙虫期间吞噬所有果实
@@ -202,9 +205,8 @@ pub fn update_sandworm_mode(
 
             if buff.sandworm_collapse_progress >= 1.0 {
                 // 坍缩完成，恢复原状态继续游戏
-                snake.body = buff.sandworm_original_snake.clone();
+                snake.set_body(buff.sandworm_original_snake.iter().copied());
                 snake.dir = buff.sandworm_original_dir;
-                snake.prev_body = snake.body.clone();
 
                 // 生成爆炸粒子
                 let center = buff.sandworm_collapse_center;
@@ -233,25 +235,58 @@ pub fn update_sandworm_mode(
                 buff.sandworm_original_snake.clear();
                 buff.sandworm_collapse_positions.clear();
 
-                // 重新生成食物
-                *food = spawn_food(&snake.body, rng);
-
-                // 奖励分数
+                // 奖励分数；食物由调用方从空闲格子里重新生成
                 result.bonus_score += 100;
                 result.need_respawn_food = true;
             }
         }
-        SandwormPhase::None => {}
+        SandwormPhase::Rampaging | SandwormPhase::None => {}
     }
 
     result
 }
 
+/// 更新 AI 蛇的沙虫变身，返回变身是否刚刚结束
+///
+/// AI 沙虫不离开棋盘：原地闪烁、逐节变色之后进入横冲阶段，
+/// 持续 [`AI_SANDWORM_DURATION`] 秒后恢复原样（身体长度保持不变）
+pub fn update_ai_sandworm(buff: &mut BuffState, len: usize, dt: f32) -> bool {
+    buff.sandworm_phase_timer += dt;
+    match buff.sandworm_phase {
+        SandwormPhase::Flashing if buff.sandworm_phase_timer >= SANDWORM_FLASH_DURATION => {
+            buff.sandworm_phase = SandwormPhase::Transforming;
+            buff.sandworm_phase_timer = 0.0;
+            buff.sandworm_transform_index = 0;
+        }
+        SandwormPhase::Transforming if buff.sandworm_phase_timer >= SANDWORM_TRANSFORM_INTERVAL => {
+            buff.sandworm_phase_timer = 0.0;
+            buff.sandworm_transform_index += 1;
+            if buff.sandworm_transform_index >= len {
+                buff.sandworm_phase = SandwormPhase::Rampaging;
+            }
+        }
+        SandwormPhase::Rampaging if buff.sandworm_phase_timer >= AI_SANDWORM_DURATION => {
+            buff.sandworm_active = false;
+            buff.sandworm_phase = SandwormPhase::None;
+            buff.sandworm_phase_timer = 0.0;
+            return true;
+        }
+        _ => {}
+    }
+    false
+}
+
 /// 生成蛇形扫描填充路径
+///
+/// 路径每一步都是四方向相邻的，八方向模式下斜向的初始方向只保留水平分量。
 pub fn generate_fill_path(start: IVec2, initial_dir: IVec2) -> Vec<IVec2> {
     let mut path = Vec::new();
     let mut pos = start;
-    let mut dir = initial_dir;
+    let mut dir = if initial_dir.x != 0 && initial_dir.y != 0 {
+        ivec2(initial_dir.x, 0)
+    } else {
+        initial_dir
+    };
     let mut visited = vec![vec![false; GRID_H as usize]; GRID_W as usize];
 
     // 蛇形扫描整个屏幕
@@ -344,3 +379,40 @@ pub fn generate_fill_path(start: IVec2, initial_dir: IVec2) -> Vec<IVec2> {
 
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ai_sandworm_phases() {
+        let mut buff = BuffState::default();
+        buff.activate_sandworm();
+        assert!(buff.has_immunity());
+        assert!(!update_ai_sandworm(&mut buff, 3, SANDWORM_FLASH_DURATION));
+        assert_eq!(buff.sandworm_phase, SandwormPhase::Transforming);
+
+        // 每节一个变色间隔
+        for _ in 0..3 {
+            assert!(!update_ai_sandworm(&mut buff, 3, SANDWORM_TRANSFORM_INTERVAL));
+        }
+        assert_eq!(buff.sandworm_phase, SandwormPhase::Rampaging);
+
+        assert!(update_ai_sandworm(&mut buff, 3, AI_SANDWORM_DURATION));
+        assert_eq!(buff.sandworm_phase, SandwormPhase::None);
+        assert!(!buff.sandworm_active);
+    }
+
+    #[test]
+    fn test_fill_path_from_diagonal_dir_stays_orthogonal() {
+        for dir in [ivec2(1, 0), ivec2(0, -1), ivec2(1, 1), ivec2(-1, -1)] {
+            let path = generate_fill_path(ivec2(0, GRID_H / 2), dir);
+            assert!(path.len() > 1);
+            // 相邻两步始终四方向相邻
+            for pair in path.windows(2) {
+                let d = pair[1] - pair[0];
+                assert_eq!(d.x.abs() + d.y.abs(), 1, "{:?} -> {:?}", pair[0], pair[1]);
+            }
+        }
+    }
+}
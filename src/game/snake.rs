@@ -4,8 +4,9 @@
 //!
 //! ## 设计说明
 //!
-//! 蛇使用 `Vec<IVec2>` 存储身体位置，索引 0 为头部。
-//! 移动时采用"插入头部、移除尾部"的方式，保持长度不变。
+//! 蛇使用 `VecDeque<IVec2>` 环形缓冲存储身体位置，索引 0 为头部。
+//! 移动时采用"插入头部、移除尾部"的方式，保持长度不变，两端操作都是 O(1)。
+//! `occupancy` 随身体同步增减，自撞检测不再扫描整条蛇。
 //!
 //! ## 移动算法
 //!
@@ -21,11 +22,19 @@
 //!
 //! ## 插值渲染
 //!
-//! `prev_body` 存储上一帧的位置，用于在帧间插值实现平滑移动。
-//! 渲染时根据累积时间计算插值比例。
+//! 每一节上一步的位置就是它后面一节现在的位置，尾巴则记录在 `prev_tail`，
+//! 所以不需要每步复制整条蛇身。渲染时通过 `prev_segment` 取得并插值。
+//!
+//! ## 传送门
+//!
+//! 蛇头踏入传送门入口后记录 `pending_portal`，下一步直接出现在出口。
+//! 这样每一节身体都会依次经过 入口 → 出口，而不是只有蛇头瞬移。
 
+use std::collections::VecDeque;
 use macroquad::prelude::*;
 use crate::constants::{GRID_W, GRID_H};
+use crate::types::{BoardDims, Grid, GridKind, PortalExit, Topology};
+use super::{check_diagonal_squeeze_on, OccupancyGrid};
 
 /// 移动结果枚举
 ///
@@ -50,7 +59,7 @@ pub enum MoveResult {
 
     /// 撞到自己
     ///
-    /// 新头部位置与身体某节重叠
+    /// 新头部位置与身体某节重叠，或斜向从两节蛇身之间挤过
     SelfCollision,
 }
 
@@ -62,48 +71,83 @@ pub enum MoveResult {
 ///
 /// - `body`: 蛇身位置列表，`body[0]` 是头部
 /// - `dir`: 当前移动方向，每帧移动一格
-/// - `prev_body`: 上一帧位置，用于插值渲染
+/// - `prev_tail`: 上一步移走的尾巴，用于插值渲染
+/// - `occupancy`: 蛇身占用网格
 ///
 /// ## 不变量
 ///
 /// - `body.len() >= 1`（至少有头部）
-/// - `dir` 是四方向之一：(1,0), (-1,0), (0,1), (0,-1)
+/// - `dir` 是 `grid.directions()` 之一
+/// - `occupancy` 与 `body` 一致：直接修改 `body` 后需要调用 `sync_occupancy`
 #[derive(Clone)]
 pub struct Snake {
     /// 蛇身位置列表
     ///
     /// 索引 0 为头部，最后一个元素为尾部。
     /// 相邻元素在网格上相邻（曼哈顿距离为 1）。
-    pub body: Vec<IVec2>,
+    pub body: VecDeque<IVec2>,
 
     /// 当前移动方向
     ///
-    /// 每次移动时，头部位置 += dir。
-    /// 只能是四方向之一，不能是 (0,0) 或对角线。
+    /// 每次移动时，头部移动到 `grid.neighbor(head, dir)`。
+    /// 只能是棋盘几何的方向之一，不能是 (0,0)。
     pub dir: IVec2,
 
-    /// 上一帧的蛇身位置
+    /// 棋盘几何
+    pub grid: GridKind,
+
+    /// 棋盘范围（缩小的棋盘在这块区域的边上撞墙或环绕）
+    pub board: BoardDims,
+
+    /// 上一步移动时离开的尾巴位置
+    ///
+    /// 用于插值渲染，见 `prev_segment`。
+    pub prev_tail: IVec2,
+
+    /// 蛇身占用网格
+    pub occupancy: OccupancyGrid,
+
+    /// 待完成的传送
     ///
-    /// 用于插值渲染，实现平滑移动效果。
-    /// 在 `move_forward` 开始时更新。
-    pub prev_body: Vec<IVec2>,
+    /// 蛇头位于传送门入口时设置，下一次移动时蛇头出现在出口。
+    pub pending_portal: Option<PortalExit>,
+
+    /// 蛇头颜色（本地多人时每名玩家不同）
+    pub head_color: Color,
+
+    /// 蛇身颜色
+    pub color: Color,
 }
 
 impl Snake {
     /// 创建新的蛇，初始位置在屏幕中央
     pub fn new() -> Self {
-        let body = vec![
+        let body = VecDeque::from([
             ivec2(GRID_W / 2, GRID_H / 2),     // 头部
             ivec2(GRID_W / 2 - 1, GRID_H / 2), // 身体
             ivec2(GRID_W / 2 - 2, GRID_H / 2), // 尾部
-        ];
+        ]);
         Snake {
-            prev_body: body.clone(),
+            prev_tail: body[body.len() - 1],
+            occupancy: OccupancyGrid::from_cells(GRID_W, GRID_H, &body),
             body,
             dir: ivec2(1, 0), // 初始向右
+            grid: GridKind::Square,
+            board: BoardDims::DEFAULT,
+            pending_portal: None,
+            head_color: GREEN,
+            color: LIME,
         }
     }
 
+    /// 在指定棋盘几何上创建蛇
+    pub fn on_grid(grid: GridKind) -> Self {
+        let mut snake = Self::new();
+        snake.grid = grid;
+        snake.reset();
+        snake
+    }
+
     /// 获取蛇头位置
     pub fn head(&self) -> IVec2 {
         self.body[0]
@@ -119,10 +163,62 @@ impl Snake {
         self.body.is_empty()
     }
 
+    /// 获取尾巴位置
+    pub fn tail(&self) -> IVec2 {
+        self.body[self.body.len() - 1]
+    }
+
+    /// 蛇身是否经过某格（O(1)）
+    pub fn occupies(&self, pos: IVec2) -> bool {
+        self.occupancy.is_occupied(pos)
+    }
+
+    /// 第 `i` 节上一步所在的位置（用于插值渲染）
+    pub fn prev_segment(&self, i: usize) -> IVec2 {
+        self.body.get(i + 1).copied().unwrap_or(self.prev_tail)
+    }
+
+    /// 以连续切片的形式访问蛇身
+    pub fn body_slice(&mut self) -> &[IVec2] {
+        self.body.make_contiguous()
+    }
+
+    /// 替换整条蛇身
+    pub fn set_body(&mut self, body: impl IntoIterator<Item = IVec2>) {
+        self.body = body.into_iter().collect();
+        self.prev_tail = self.tail();
+        self.sync_occupancy();
+    }
+
+    /// 按 `body` 重建占用网格（直接修改 `body` 后调用）
+    pub fn sync_occupancy(&mut self) {
+        self.occupancy.rebuild(&self.body);
+    }
+
+    /// 在头部加入一节
+    pub fn push_head(&mut self, pos: IVec2) {
+        self.body.push_front(pos);
+        self.occupancy.add(pos);
+    }
+
+    /// 移除尾部一节
+    pub fn pop_tail(&mut self) -> Option<IVec2> {
+        let tail = self.body.pop_back()?;
+        self.occupancy.remove(tail);
+        Some(tail)
+    }
+
+    /// 截断到指定长度
+    pub fn truncate(&mut self, len: usize) {
+        while self.body.len() > len {
+            self.pop_tail();
+        }
+    }
+
     /// 设置移动方向（防止反向）
     pub fn set_direction(&mut self, new_dir: IVec2) {
         // 防止反向掉头
-        if new_dir.x != -self.dir.x || new_dir.y != -self.dir.y {
+        if !self.grid.is_reverse(new_dir, self.dir) {
             self.dir = new_dir;
         }
     }
@@ -136,44 +232,71 @@ impl Snake {
     /// # 返回
     /// 移动结果，包含新头部位置或碰撞类型
     pub fn move_forward(&mut self, wrap: bool, can_pass_self: bool) -> MoveResult {
-        // 保存上一帧位置
-        self.prev_body = self.body.clone();
+        self.move_forward_on(Topology::from_wrap(wrap), can_pass_self)
+    }
 
-        let head = self.head();
-        let mut nx = head.x + self.dir.x;
-        let mut ny = head.y + self.dir.y;
-
-        // 处理边界（可穿过自己的模式通常也可以穿墙）
-        if can_pass_self || wrap {
-            if nx < 0 { nx = GRID_W - 1; }
-            if nx >= GRID_W { nx = 0; }
-            if ny < 0 { ny = GRID_H - 1; }
-            if ny >= GRID_H { ny = 0; }
-        } else {
-            // 非包围模式，检查撞墙
-            if nx < 0 || nx >= GRID_W || ny < 0 || ny >= GRID_H {
-                return MoveResult::WallCollision;
-            }
-        }
+    /// 按指定地图拓扑向前移动一步
+    ///
+    /// 镜像环绕的边界会同时翻转蛇头方向；
+    /// 可穿过自己的模式在墙边界上按圆环面环绕
+    pub fn move_forward_on(&mut self, topology: Topology, can_pass_self: bool) -> MoveResult {
+        self.move_forward_among(topology, can_pass_self, |_| false)
+    }
 
-        let new_head = ivec2(nx, ny);
+    /// 在其他蛇之间向前移动一步，`others` 判断某格是否有别的蛇身
+    ///
+    /// 八方向模式下斜向从两节蛇身之间挤过（不论属于哪条蛇）与撞到自己相同
+    pub fn move_forward_among(
+        &mut self,
+        topology: Topology,
+        can_pass_self: bool,
+        others: impl Fn(IVec2) -> bool,
+    ) -> MoveResult {
+        let head = self.head();
+        // 斜向从两节蛇身之间挤过（八方向模式）
+        let squeezed = self.pending_portal.is_none()
+            && check_diagonal_squeeze_on(self.grid, topology, self.board, head, self.dir, |p| self.occupies(p) || others(p));
+
+        // 上一步踏入了传送门入口：这一步从出口出现
+        let stepped = match self.pending_portal.take() {
+            Some(exit) => Some((exit.to, exit.dir)),
+            None => self.grid
+                .step_on(topology, self.board, head, self.dir)
+                // 处理边界（可穿过自己的模式通常也可以穿墙）
+                .or_else(|| can_pass_self.then(|| self.grid.step_on(Topology::Torus, self.board, head, self.dir)).flatten()),
+        };
+
+        let Some((new_head, new_dir)) = stepped else {
+            return MoveResult::WallCollision;
+        };
+        self.dir = new_dir;
 
         // 检查自撞（护盾/幽灵/沙虫模式下可穿过自己）
-        if !can_pass_self && self.body.iter().skip(1).any(|&p| p == new_head) {
+        // 蛇头所在格不算，除非身体也压在上面
+        let hits_body = self.occupancy.count(new_head) > u16::from(new_head == head);
+        if !can_pass_self && (squeezed || hits_body) {
             return MoveResult::SelfCollision;
         }
 
         // 移动：插入新头部，移除尾部
-        self.body.insert(0, new_head);
-        self.body.pop();
+        self.push_head(new_head);
+        if let Some(tail) = self.pop_tail() {
+            self.prev_tail = tail;
+        }
 
         MoveResult::Normal(new_head)
     }
 
+    /// 踏入传送门入口，下一步从出口出现
+    pub fn enter_portal(&mut self, exit: PortalExit) {
+        self.pending_portal = Some(exit);
+    }
+
     /// 增长一节（吃到食物时调用）
     pub fn grow(&mut self) {
-        if let Some(tail) = self.body.last().cloned() {
-            self.body.push(tail);
+        if let Some(&tail) = self.body.back() {
+            self.body.push_back(tail);
+            self.occupancy.add(tail);
         }
     }
 
@@ -181,44 +304,46 @@ impl Snake {
     pub fn shrink(&mut self, count: usize) {
         for _ in 0..count {
             if self.body.len() > 1 {
-                self.body.pop();
+                self.pop_tail();
             }
         }
     }
 
     /// 头尾反转
     pub fn reverse(&mut self) {
-        self.body.reverse();
-        // 更新方向为新头部的方向
+        self.body.make_contiguous().reverse();
+        self.prev_tail = self.tail();
+        self.pending_portal = None;
+        // 更新方向为新头部的方向（跨越环绕边界时无法推断，直接掉头）
         if self.body.len() > 1 {
-            let new_head = self.body[0];
-            let second = self.body[1];
-            self.dir = ivec2(new_head.x - second.x, new_head.y - second.y);
+            self.dir = self.grid
+                .direction_between(self.body[1], self.body[0])
+                .unwrap_or(-self.dir);
         }
     }
 
-    /// 重置到初始状态
+    /// 重置到初始状态（棋盘中央）
     pub fn reset(&mut self) {
-        self.body = vec![
-            ivec2(GRID_W / 2, GRID_H / 2),
-            ivec2(GRID_W / 2 - 1, GRID_H / 2),
-            ivec2(GRID_W / 2 - 2, GRID_H / 2),
-        ];
-        self.prev_body = self.body.clone();
-        self.dir = ivec2(1, 0);
+        self.reset_at(self.board.center(), ivec2(1, 0));
+    }
+
+    /// 重置为蛇头在 `head`、沿 `step` 方向排开的 3 节（蛇尾在 `head - 2 * step`）
+    pub fn reset_at(&mut self, head: IVec2, step: IVec2) {
+        self.set_body([head, head - step, head - step * 2]);
+        self.dir = self.grid.direction_between(self.body[1], self.body[0]).unwrap_or(step);
+        self.pending_portal = None;
     }
 
     /// 传送蛇（整条蛇加上偏移量）
-    pub fn teleport(&mut self, offset: IVec2) {
+    ///
+    /// 越界的身体节按地图拓扑映射回地图内
+    pub fn teleport(&mut self, offset: IVec2, topology: Topology) {
+        self.pending_portal = None;
         for seg in self.body.iter_mut() {
-            seg.x += offset.x;
-            seg.y += offset.y;
-            // 处理边界包围
-            if seg.x < 0 { seg.x += GRID_W; }
-            if seg.x >= GRID_W { seg.x -= GRID_W; }
-            if seg.y < 0 { seg.y += GRID_H; }
-            if seg.y >= GRID_H { seg.y -= GRID_H; }
+            *seg = topology.normalize_on(self.board, *seg + offset);
         }
+        self.prev_tail = self.tail();
+        self.sync_occupancy();
     }
 }
 
@@ -269,10 +394,148 @@ mod tests {
     fn test_snake_reverse() {
         let mut snake = Snake::new();
         let original_head = snake.head();
-        let original_tail = *snake.body.last().unwrap();
+        let original_tail = snake.tail();
         snake.reverse();
         assert_eq!(snake.head(), original_tail);
-        assert_eq!(*snake.body.last().unwrap(), original_head);
+        assert_eq!(snake.tail(), original_head);
+    }
+
+    #[test]
+    fn test_snake_trails_through_portal() {
+        let mut snake = Snake::new();
+        let entry = snake.head() + snake.dir;
+        let exit = PortalExit { from: entry, to: ivec2(2, 2), dir: ivec2(0, 1) };
+
+        assert_eq!(snake.move_forward(true, false), MoveResult::Normal(entry));
+        snake.enter_portal(exit);
+        assert_eq!(snake.move_forward(true, false), MoveResult::Normal(ivec2(2, 2)));
+        assert_eq!(snake.dir, ivec2(0, 1));
+        // 身体仍然停留在入口一侧，随后依次经过入口和出口
+        assert_eq!(snake.body[1], entry);
+        assert_eq!(snake.move_forward(true, false), MoveResult::Normal(ivec2(2, 3)));
+        assert_eq!(snake.body[1], ivec2(2, 2));
+        assert_eq!(snake.body[2], entry);
+    }
+
+    #[test]
+    fn test_snake_passes_through_two_way_portal() {
+        use crate::game::find_portal_exit;
+        use crate::types::Portal;
+
+        // 双向传送门的出口也是入口：刚从出口出来的这一步不会再被传回去，之后继续前进
+        let portals = vec![Portal::permanent(ivec2(18, 12), ivec2(5, 5), WHITE)];
+        let mut snake = Snake::new();
+        let mut heads = Vec::new();
+        for _ in 0..5 {
+            let teleported = snake.pending_portal.is_some();
+            let MoveResult::Normal(head) = snake.move_forward(true, false) else {
+                panic!("snake should keep moving");
+            };
+            if !teleported && let Some(exit) = find_portal_exit(head, snake.dir, &portals) {
+                snake.enter_portal(exit);
+            }
+            heads.push(head);
+        }
+        assert_eq!(heads, [ivec2(17, 12), ivec2(18, 12), ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)]);
+        assert_eq!(snake.pending_portal, None);
+    }
+
+    #[test]
+    fn test_hex_snake_stays_connected() {
+        let mut snake = Snake::on_grid(GridKind::Hex);
+        for turn in [0, 1, 0, -1, -1, 0, 0] {
+            snake.set_direction(snake.grid.turn(snake.dir, turn));
+            assert!(matches!(snake.move_forward_on(Topology::Torus, false), MoveResult::Normal(_)));
+            let grid = snake.grid;
+            for pair in snake.body_slice().windows(2) {
+                assert!(grid.direction_between(pair[1], pair[0]).is_some());
+            }
+        }
+        // 六边形棋盘上的反方向同样被拒绝
+        let dir = snake.dir;
+        snake.set_direction(-dir);
+        assert_eq!(snake.dir, dir);
+    }
+
+    #[test]
+    fn test_octile_squeeze_between_own_segments() {
+        let mut snake = Snake::on_grid(GridKind::Octile);
+        // 头在 (5,5)，身体绕一圈：右侧 (6,5) 与上方 (5,4) 都是身体，目标 (6,4) 是空的
+        snake.set_body([
+            ivec2(5, 5), ivec2(6, 5), ivec2(6, 6), ivec2(5, 6),
+            ivec2(4, 6), ivec2(4, 5), ivec2(4, 4), ivec2(5, 4),
+        ]);
+        snake.dir = ivec2(1, -1);
+        assert_eq!(snake.move_forward_on(Topology::Bounded, false), MoveResult::SelfCollision);
+
+        // 只有一侧是身体时可以斜向通过
+        snake.set_body([ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)]);
+        snake.dir = ivec2(-1, -1);
+        assert_eq!(snake.move_forward_on(Topology::Bounded, false), MoveResult::Normal(ivec2(4, 4)));
+    }
+
+    #[test]
+    fn test_octile_squeeze_between_own_and_other_snake() {
+        let mut snake = Snake::on_grid(GridKind::Octile);
+        // 上方 (5,4) 是别的蛇：向左上只有一侧被占，可以通过
+        snake.set_body([ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)]);
+        snake.dir = ivec2(-1, -1);
+        let other = |p: IVec2| p == ivec2(5, 4);
+        assert_eq!(snake.move_forward_among(Topology::Bounded, false, other), MoveResult::Normal(ivec2(4, 4)));
+
+        // 右侧 (6,5) 是自己的身体：向右上从自己和别的蛇之间挤过去同样撞死
+        snake.set_body([ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)]);
+        snake.dir = ivec2(1, -1);
+        assert_eq!(snake.move_forward_among(Topology::Bounded, false, other), MoveResult::SelfCollision);
+
+        // 两侧都是别的蛇
+        snake.set_body([ivec2(5, 5), ivec2(5, 6), ivec2(5, 7)]);
+        snake.dir = ivec2(1, -1);
+        let others = |p: IVec2| p == ivec2(5, 4) || p == ivec2(6, 5);
+        assert_eq!(snake.move_forward_among(Topology::Bounded, false, others), MoveResult::SelfCollision);
+
+        // 护盾/幽灵可以挤过去
+        snake.set_body([ivec2(5, 5), ivec2(6, 5), ivec2(7, 5)]);
+        snake.dir = ivec2(1, -1);
+        assert_eq!(snake.move_forward_among(Topology::Bounded, true, other), MoveResult::Normal(ivec2(6, 4)));
+    }
+
+    #[test]
+    fn test_occupancy_follows_body() {
+        let mut snake = Snake::new();
+        snake.grow();
+        snake.grow();
+        for dir in [ivec2(0, 1), ivec2(-1, 0)] {
+            snake.set_direction(dir);
+            snake.move_forward(true, false);
+            for &seg in &snake.body {
+                assert!(snake.occupies(seg));
+            }
+            let expected = OccupancyGrid::from_cells(GRID_W, GRID_H, &snake.body);
+            for x in 0..GRID_W {
+                for y in 0..GRID_H {
+                    assert_eq!(snake.occupancy.count(ivec2(x, y)), expected.count(ivec2(x, y)));
+                }
+            }
+        }
+        // 掉头向上正好撞回自己的身体
+        snake.set_direction(ivec2(0, -1));
+        assert_eq!(snake.move_forward(true, false), MoveResult::SelfCollision);
+    }
+
+    #[test]
+    fn test_snake_mobius_flips_row() {
+        let mut snake = Snake::new();
+        snake.set_body([ivec2(GRID_W - 1, 3), ivec2(GRID_W - 2, 3)]);
+        snake.dir = ivec2(1, 0);
+        assert_eq!(
+            snake.move_forward_on(Topology::Mobius, false),
+            MoveResult::Normal(ivec2(0, GRID_H - 4))
+        );
+        // 上下是墙
+        snake.set_body([ivec2(5, 0), ivec2(5, 1)]);
+        snake.dir = ivec2(0, -1);
+        assert_eq!(snake.move_forward_on(Topology::Mobius, false), MoveResult::WallCollision);
     }
 }
 
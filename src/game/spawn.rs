@@ -2,45 +2,39 @@
 //!
 //! 提供食物、果实、传送门的生成函数
 
+use std::collections::VecDeque;
 use macroquad::prelude::*;
 use ::rand::Rng;
-use ::rand::rngs::ThreadRng;
-use crate::constants::{GRID_W, GRID_H};
+use crate::game::GameRng;
 use crate::types::{Fruit, Portal};
 use crate::fruits::{FruitRegistry, FruitCategory};
+use super::FreeCellIndex;
 
 /// 生成不与蛇身和现有果实重叠的随机位置
 ///
 /// # 参数
-/// - `snake`: 蛇身位置列表
+/// - `free`: 空闲格子索引（已经避开 AI 蛇身和固定阻挡，见 [`AIManager::free_cells`]）
+/// - `blocked`: 索引之外不能放东西的格子（玩家蛇身等）
 /// - `fruits`: 现有果实列表
 /// - `rng`: 随机数生成器
 ///
 /// # 返回
-/// - `Some(pos)`: 找到的空闲位置
-/// - `None`: 无法找到空闲位置（尝试100次后）
+/// - `Some(pos)`: 随机的空闲位置
+/// - `None`: 棋盘上已没有空闲位置
 pub fn spawn_position(
-    snake: &[IVec2],
+    free: &FreeCellIndex,
+    blocked: impl Fn(IVec2) -> bool,
     fruits: &[Fruit],
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) -> Option<IVec2> {
-    for _ in 0..100 {
-        let p = ivec2(rng.gen_range(0..GRID_W), rng.gen_range(0..GRID_H));
-        if !snake.iter().any(|&s| s == p) && !fruits.iter().any(|f| f.pos == p) {
-            return Some(p);
-        }
-    }
-    None
+    free.sample_where(rng, |p| !blocked(p) && !fruits.iter().any(|f| f.pos == p))
 }
 
-/// 生成食物位置（只检查蛇身）
-pub fn spawn_food(snake: &[IVec2], rng: &mut ThreadRng) -> IVec2 {
-    loop {
-        let p = ivec2(rng.gen_range(0..GRID_W), rng.gen_range(0..GRID_H));
-        if !snake.iter().any(|&s| s == p) {
-            return p;
-        }
-    }
+/// 生成食物位置（避开空闲格子索引之外的 `blocked` 格子）
+///
+/// 棋盘被填满时返回 None，由调用方判定为胜利
+pub fn spawn_food(free: &FreeCellIndex, blocked: impl Fn(IVec2) -> bool, rng: &mut GameRng) -> Option<IVec2> {
+    free.sample_where(rng, |p| !blocked(p))
 }
 
 /// 使用 FruitRegistry 生成果实
@@ -48,61 +42,69 @@ pub fn spawn_food(snake: &[IVec2], rng: &mut ThreadRng) -> IVec2 {
 /// # 参数
 /// - `registry`: 果实注册表
 /// - `category`: 果实类别
-/// - `snake`: 蛇身位置列表
+/// - `free`: 空闲格子索引
+/// - `blocked`: 索引之外不能放东西的格子（玩家蛇身等）
+/// - `snake_len`: 玩家蛇长度（决定可以生成哪些果实）
 /// - `fruits`: 现有果实列表
 /// - `game_time`: 当前游戏时间
 /// - `rng`: 随机数生成器
 ///
 /// # 返回
 /// 生成的果实实例，如果无法生成则返回 None
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_fruit(
     registry: &FruitRegistry,
     category: FruitCategory,
-    snake: &[IVec2],
+    free: &FreeCellIndex,
+    blocked: impl Fn(IVec2) -> bool,
+    snake_len: usize,
     fruits: &[Fruit],
     game_time: f32,
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) -> Option<Fruit> {
-    let pos = spawn_position(snake, fruits, rng)?;
-    let type_id = registry.random_by_category(category, snake.len(), rng)?;
+    let pos = spawn_position(free, blocked, fruits, rng)?;
+    let type_id = registry.random_by_category(category, snake_len, rng)?;
     let config = registry.get_config(type_id)?;
 
     Some(Fruit::new(pos, type_id, game_time, config.lifetime))
 }
 
 /// 生成传送门（成对出现）
+///
+/// 新传送门避开已有传送门的位置，并优先使用未被占用的颜色，
+/// 以便同时存在的多对传送门可以区分。
 pub fn spawn_portal(
-    snake: &[IVec2],
+    free: &FreeCellIndex,
+    blocked: impl Fn(IVec2) -> bool,
     fruits: &[Fruit],
+    portals: &[Portal],
     game_time: f32,
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) -> Option<Portal> {
-    use crate::types::PORTAL_LIFETIME;
+    use crate::types::{PORTAL_LIFETIME, PORTAL_COLORS};
 
-    // 生成第一个位置
-    let pos_a = spawn_position(snake, fruits, rng)?;
+    // 避开已有传送门
+    let on_portal = |p: IVec2| portals.iter().any(|portal| portal.pos_a == p || portal.pos_b == p);
 
-    // 临时创建一个假果实来避免第二个位置与第一个重叠
-    let mut temp_fruits: Vec<Fruit> = fruits.to_vec();
-    temp_fruits.push(Fruit::new(pos_a, "temp", 0.0, 0.0));
+    // 生成第一个位置
+    let pos_a = spawn_position(free, |p| blocked(p) || on_portal(p), fruits, rng)?;
 
-    let pos_b = spawn_position(snake, &temp_fruits, rng)?;
+    // 避免第二个位置与第一个重叠
+    let pos_b = spawn_position(free, |p| blocked(p) || on_portal(p) || p == pos_a, fruits, rng)?;
 
-    // 随机颜色
-    let colors = [
-        Color { r: 0.5, g: 0.0, b: 1.0, a: 1.0 }, // 紫色
-        Color { r: 0.0, g: 1.0, b: 0.5, a: 1.0 }, // 青绿
-        Color { r: 1.0, g: 0.5, b: 0.0, a: 1.0 }, // 橙色
-    ];
-    let color = colors[rng.gen_range(0..colors.len())];
+    // 随机选择一个未被使用的颜色（全部用完则随机）
+    let unused: Vec<Color> = PORTAL_COLORS
+        .iter()
+        .copied()
+        .filter(|c| !portals.iter().any(|p| p.color == *c))
+        .collect();
+    let color = if unused.is_empty() {
+        PORTAL_COLORS[rng.gen_range(0..PORTAL_COLORS.len())]
+    } else {
+        unused[rng.gen_range(0..unused.len())]
+    };
 
-    Some(Portal {
-        pos_a,
-        pos_b,
-        color,
-        spawn_time: game_time,
-        lifetime: PORTAL_LIFETIME,
-    })
+    Some(Portal::new(pos_a, pos_b, color, game_time, PORTAL_LIFETIME))
 }
 
 /// 过期果实信息
@@ -131,13 +133,13 @@ pub fn update_fruits(fruits: &mut Vec<Fruit>, game_time: f32) -> Vec<ExpiredFrui
     expired
 }
 
-/// 更新传送门列表，移除过期传送门
+/// 更新传送门列表，移除过期传送门（永久传送门保留）
 pub fn update_portals(portals: &mut Vec<Portal>, game_time: f32) {
-    portals.retain(|p| game_time - p.spawn_time < p.lifetime);
+    portals.retain(|p| !p.is_expired(game_time));
 }
 
 use crate::fruits::FruitContext;
-use crate::types::{BuffState, DamageState, Particle, ComboState};
+use crate::types::{BuffState, DamageState, Particle, ComboState, GridKind};
 use crate::game::AIManager;
 
 /// 更新果实列表，处理过期果实并调用 on_expire 回调
@@ -148,8 +150,9 @@ use crate::game::AIManager;
 /// # 参数
 /// - `fruits`: 果实列表
 /// - `registry`: 果实注册表
-/// - `snake`: 蛇身位置列表
+/// - `snake`: 蛇身位置（回调修改后调用方需要同步占用网格）
 /// - `dir`: 蛇移动方向
+/// - `grid`: 棋盘几何
 /// - `buff_state`: Buff状态
 /// - `damage_state`: 受伤状态
 /// - `particles`: 粒子列表
@@ -166,8 +169,9 @@ use crate::game::AIManager;
 pub fn update_fruits_with_callbacks(
     fruits: &mut Vec<Fruit>,
     registry: &FruitRegistry,
-    snake: &mut Vec<IVec2>,
+    snake: &mut VecDeque<IVec2>,
     dir: &mut IVec2,
+    grid: GridKind,
     buff_state: &mut BuffState,
     damage_state: &mut DamageState,
     particles: &mut Vec<Particle>,
@@ -176,7 +180,7 @@ pub fn update_fruits_with_callbacks(
     ai_manager: &mut AIManager,
     food: &mut IVec2,
     game_time: f32,
-    rng: &mut ThreadRng,
+    rng: &mut GameRng,
 ) -> Vec<ExpiredFruit> {
     let mut expired = vec![];
     
@@ -196,6 +200,7 @@ pub fn update_fruits_with_callbacks(
             let mut ctx = FruitContext {
                 snake,
                 dir,
+                grid,
                 buff_state,
                 particles,
                 damage_state,
@@ -227,26 +232,72 @@ pub fn update_fruits_with_callbacks(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ::rand::thread_rng;
+    use ::rand::SeedableRng;
+    use crate::constants::{GRID_W, GRID_H};
+    use crate::game::OccupancyGrid;
 
     #[test]
     fn test_spawn_food_not_on_snake() {
-        let snake = vec![ivec2(5, 5), ivec2(4, 5), ivec2(3, 5)];
-        let mut rng = thread_rng();
+        let snake = [ivec2(5, 5), ivec2(4, 5), ivec2(3, 5)];
+        let free = FreeCellIndex::new(GRID_W, GRID_H);
+        let mut rng = GameRng::seed_from_u64(7);
         for _ in 0..100 {
-            let food = spawn_food(&snake, &mut rng);
+            let food = spawn_food(&free, |p| snake.contains(&p), &mut rng).unwrap();
             assert!(!snake.contains(&food));
         }
     }
 
+    #[test]
+    fn test_spawn_food_on_full_small_board() {
+        let mut rng = GameRng::seed_from_u64(7);
+        let mut grid = OccupancyGrid::with_free_cells(2, 2);
+        for pos in [ivec2(0, 0), ivec2(1, 0), ivec2(1, 1)] {
+            grid.add(pos);
+        }
+        assert_eq!(spawn_food(grid.free_cells().unwrap(), |_| false, &mut rng), Some(ivec2(0, 1)));
+
+        // 最后一格被别的东西挡住也算满
+        assert_eq!(spawn_food(grid.free_cells().unwrap(), |p| p == ivec2(0, 1), &mut rng), None);
+        grid.add(ivec2(0, 1));
+        assert_eq!(spawn_food(grid.free_cells().unwrap(), |_| false, &mut rng), None);
+    }
+
+    #[test]
+    fn test_spawn_portal_distinct_colors() {
+        let snake = [ivec2(5, 5), ivec2(4, 5)];
+        let free = FreeCellIndex::with_blocked(GRID_W, GRID_H, snake.iter().copied());
+        let mut rng = GameRng::seed_from_u64(7);
+        let mut portals: Vec<Portal> = vec![];
+        for _ in 0..3 {
+            let portal = spawn_portal(&free, |_| false, &[], &portals, 0.0, &mut rng).unwrap();
+            assert!(!portals.iter().any(|p| p.color == portal.color));
+            assert!(!portals.iter().any(|p| [p.pos_a, p.pos_b].contains(&portal.pos_a)));
+            assert!(!snake.contains(&portal.pos_a) && !snake.contains(&portal.pos_b));
+            assert_ne!(portal.pos_a, portal.pos_b);
+            portals.push(portal);
+        }
+    }
+
+    #[test]
+    fn test_update_portals_keeps_permanent() {
+        let mut portals = vec![
+            Portal::permanent(ivec2(1, 1), ivec2(2, 2), WHITE),
+            Portal::new(ivec2(3, 3), ivec2(4, 4), WHITE, 0.0, 5.0),
+        ];
+        update_portals(&mut portals, 100.0);
+        assert_eq!(portals.len(), 1);
+        assert!(portals[0].is_permanent());
+    }
+
     #[test]
     fn test_spawn_position_not_on_snake_or_fruits() {
-        let snake = vec![ivec2(5, 5), ivec2(4, 5)];
+        let snake = [ivec2(5, 5), ivec2(4, 5)];
         let fruits = vec![Fruit::new(ivec2(10, 10), "normal", 0.0, 0.0)];
-        let mut rng = thread_rng();
+        let free = FreeCellIndex::new(GRID_W, GRID_H);
+        let mut rng = GameRng::seed_from_u64(7);
 
         for _ in 0..100 {
-            if let Some(pos) = spawn_position(&snake, &fruits, &mut rng) {
+            if let Some(pos) = spawn_position(&free, |p| snake.contains(&p), &fruits, &mut rng) {
                 assert!(!snake.contains(&pos));
                 assert!(!fruits.iter().any(|f| f.pos == pos));
             }
@@ -259,7 +310,8 @@ mod tests {
 mod proptests {
     use super::*;
     use proptest::prelude::*;
-    use ::rand::thread_rng;
+    use crate::constants::{GRID_W, GRID_H};
+    use ::rand::SeedableRng;
 
     // **Feature: modular-migration, Property 3: Spawn Position Validity**
     // *For any* snake body and existing fruits, a spawned position should never
@@ -271,8 +323,9 @@ mod proptests {
         fn prop_spawn_position_no_overlap(
             snake_len in 3usize..20,
             fruit_count in 0usize..10,
+            seed in any::<u64>(),
         ) {
-            let mut rng = thread_rng();
+            let mut rng = GameRng::seed_from_u64(seed);
 
             // 生成蛇身（直线）
             let snake: Vec<IVec2> = (0..snake_len as i32)
@@ -288,8 +341,9 @@ mod proptests {
                 })
                 .collect();
 
-            // 尝试生成位置
-            if let Some(pos) = spawn_position(&snake, &fruits, &mut rng) {
+            // 尝试生成位置（蛇身在空闲格子索引里）
+            let free = FreeCellIndex::with_blocked(GRID_W, GRID_H, snake.iter().copied());
+            if let Some(pos) = spawn_position(&free, |_| false, &fruits, &mut rng) {
                 // 验证不与蛇身重叠
                 prop_assert!(!snake.contains(&pos), "Position overlaps with snake");
                 // 验证不与果实重叠
@@ -4,11 +4,11 @@
 
 use macroquad::prelude::*;
 use ::rand::Rng;
-use ::rand::rngs::ThreadRng;
+use crate::game::GameRng;
 use std::collections::HashMap;
 use crate::types::Fruit;
 use crate::fruits::{FruitRegistry, FruitCategory};
-use super::spawn_position;
+use super::{spawn_position, FreeCellIndex};
 
 /// 生成规则配置
 #[derive(Clone)]
@@ -152,17 +152,22 @@ impl FruitSpawnManager {
     }
 
     /// 更新并生成果实
+    ///
+    /// 果实位置从空闲格子索引 `free` 采样，并避开 `blocked`（玩家蛇身等）和现有果实；
+    /// `snake_len` 决定解锁了哪些规则
+    #[allow(clippy::too_many_arguments)]
     pub fn update(
         &mut self,
         registry: &FruitRegistry,
-        snake_body: &[IVec2],
+        free: &FreeCellIndex,
+        blocked: impl Fn(IVec2) -> bool,
+        snake_len: usize,
         fruits: &mut Vec<Fruit>,
         game_time: f32,
         sandworm_active: bool,
         dt: f32,
-        rng: &mut ThreadRng,
+        rng: &mut GameRng,
     ) {
-        let snake_len = snake_body.len();
 
         // 处理类别规则
         for rule in &self.category_rules {
@@ -208,7 +213,7 @@ impl FruitSpawnManager {
             }
 
             // 生成果实
-            if let Some(pos) = spawn_position(snake_body, fruits, rng) {
+            if let Some(pos) = spawn_position(free, &blocked, fruits, rng) {
                 if let Some(type_id) = registry.random_by_category(rule.category, snake_len, rng) {
                     if let Some(config) = registry.get_config(type_id) {
                         fruits.push(Fruit::new(pos, type_id, game_time, config.lifetime));
@@ -242,7 +247,7 @@ impl FruitSpawnManager {
 
             // 生成果实
             if let Some(config) = registry.get_config(rule.fruit_id) {
-                if let Some(pos) = spawn_position(snake_body, fruits, rng) {
+                if let Some(pos) = spawn_position(free, &blocked, fruits, rng) {
                     fruits.push(Fruit::new(pos, rule.fruit_id, game_time, config.lifetime));
                 }
             }
@@ -294,4 +299,11 @@ pub fn create_default_spawn_manager() -> FruitSpawnManager {
                 .max_count(1)
                 .unlock_length(5)
         )
+        // 宠物蛋：每帧0.4%概率，最多1个，蛇长>=8解锁
+        .with_independent_rule(
+            IndependentSpawnRule::new("pet_egg")
+                .probability(0.004)
+                .max_count(1)
+                .unlock_length(8)
+        )
 }
@@ -0,0 +1,213 @@
+//! 蜂群模式
+//!
+//! 没有玩家，几百条 AI 蛇在比游戏棋盘大得多的棋盘上抢食物和果实（无界面模拟，见 `swarm` 程序）。
+//! 棋盘尺寸、蛇数和拓扑由 [`SwarmConfig`] 配置，蛇数最多 [`SWARM_MAX_SNAKES`] 条。
+//!
+//! 决策和移动直接复用 [`AIManager`]：决策在 rayon 线程池里并行、顺序应用，
+//! 所以同一种子的模拟与线程数无关。死掉的蛇马上在空地上补回来，吃掉的果实也马上补上。
+
+use macroquad::prelude::*;
+use ::rand::{Rng, SeedableRng};
+use crate::fruits::{create_fruit_registry, FruitRegistry};
+use crate::types::{BoardDims, Fruit, Particle, Topology};
+use super::{AIManager, AIUpdateResult, GameRng, HazardManager};
+
+/// 蜂群里最多的 AI 蛇数
+pub const SWARM_MAX_SNAKES: usize = 256;
+
+/// 蜂群棋盘边长的下限（格）
+pub const SWARM_MIN_SIDE: i32 = 16;
+
+/// 蜂群棋盘边长的上限（格）
+pub const SWARM_MAX_SIDE: i32 = 512;
+
+/// 默认的蜂群棋盘（游戏棋盘的 5×5 倍）
+pub const SWARM_BOARD: BoardDims = BoardDims::new(160, 120);
+
+/// 蜂群模式设置
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SwarmConfig {
+    /// 棋盘尺寸
+    pub board: BoardDims,
+    /// AI 蛇数
+    pub snakes: usize,
+    /// 场上同时存在的果实数
+    pub fruits: usize,
+    /// 地图拓扑
+    pub topology: Topology,
+    /// 随机种子
+    pub seed: u64,
+}
+
+impl Default for SwarmConfig {
+    fn default() -> Self {
+        SwarmConfig { board: SWARM_BOARD, snakes: 200, fruits: 64, topology: Topology::Torus, seed: 0 }
+    }
+}
+
+impl SwarmConfig {
+    /// 棋盘尺寸（每边限制在 [`SWARM_MIN_SIDE`]..=[`SWARM_MAX_SIDE`]）
+    pub fn with_board(mut self, width: i32, height: i32) -> Self {
+        let side = |n: i32| n.clamp(SWARM_MIN_SIDE, SWARM_MAX_SIDE);
+        self.board = BoardDims::new(side(width), side(height));
+        self
+    }
+
+    /// AI 蛇数（最多 [`SWARM_MAX_SNAKES`] 条）
+    pub fn with_snakes(mut self, snakes: usize) -> Self {
+        self.snakes = snakes.min(SWARM_MAX_SNAKES);
+        self
+    }
+
+    /// 场上的果实数（最多占棋盘的十分之一）
+    pub fn with_fruits(mut self, fruits: usize) -> Self {
+        self.fruits = fruits.min(self.board.cells() / 10);
+        self
+    }
+
+    /// 地图拓扑
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// 随机种子
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+}
+
+/// 一场蜂群模拟
+pub struct Swarm {
+    pub config: SwarmConfig,
+    /// 所有 AI 蛇
+    pub ai: AIManager,
+    /// 普通食物位置
+    pub food: IVec2,
+    /// 场上的果实
+    pub fruits: Vec<Fruit>,
+    /// 模拟时间（秒）
+    pub time: f32,
+    /// 到目前为止死掉的蛇数
+    pub deaths: u32,
+    registry: FruitRegistry,
+    hazards: HazardManager,
+    /// 移动时产生的粒子（无界面，每帧丢弃）
+    particles: Vec<Particle>,
+    rng: GameRng,
+}
+
+impl Swarm {
+    /// 按设置生成棋盘、所有 AI 蛇、食物和果实
+    pub fn new(config: SwarmConfig) -> Self {
+        let mut ai = AIManager::with_board(config.board);
+        ai.max_snakes = config.snakes;
+        let mut swarm = Swarm {
+            config,
+            ai,
+            food: IVec2::ZERO,
+            fruits: Vec::with_capacity(config.fruits),
+            time: 0.0,
+            deaths: 0,
+            registry: create_fruit_registry(),
+            hazards: HazardManager::new(),
+            particles: Vec::new(),
+            rng: GameRng::seed_from_u64(config.seed),
+        };
+        swarm.respawn_snakes();
+        swarm.food = swarm.ai.free_cells().sample(&mut swarm.rng).unwrap_or_default();
+        swarm.refill_fruits();
+        swarm
+    }
+
+    /// 推进 `dt` 秒：所有蛇决策、移动，然后补回死掉的蛇和吃掉的果实
+    pub fn step(&mut self, dt: f32) -> AIUpdateResult {
+        let topology = self.config.topology;
+        self.time += dt;
+        self.ai.update_thinking(
+            self.food, &self.fruits, &self.registry, &[], &self.hazards, &[],
+            topology, self.time, dt, &mut self.rng,
+        );
+        let result = self.ai.update_movement(
+            &mut self.food, &mut self.fruits, &[], &self.hazards, &[], &mut self.particles,
+            &self.registry, topology, self.time, dt, &mut self.rng,
+        );
+        self.particles.clear();
+        self.ai.update_dropped_foods(self.time);
+        self.deaths += result.ai_deaths;
+        self.respawn_snakes();
+        self.refill_fruits();
+        result
+    }
+
+    /// 补足 AI 蛇（棋盘太挤、找不到空地时少几条）
+    ///
+    /// 新蛇的决策计时器随机错开，几百条蛇不会挤在同一帧决策
+    fn respawn_snakes(&mut self) {
+        while self.ai.snakes.len() < self.config.snakes && self.ai.spawn_default_snake(&[], &mut self.rng) {
+            let snake = self.ai.snakes.last_mut().expect("just spawned");
+            snake.think_timer = self.rng.gen_range(0.0..snake.progress.think_interval());
+        }
+    }
+
+    /// 补足普通果实
+    fn refill_fruits(&mut self) {
+        while self.fruits.len() < self.config.fruits {
+            let (food, fruits) = (self.food, &self.fruits);
+            let free = self.ai.free_cells().sample_where(&mut self.rng, |p| p != food && fruits.iter().all(|f| f.pos != p));
+            let Some(pos) = free else { break };
+            self.fruits.push(Fruit::new(pos, "normal", self.time, 0.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{GRID_H, GRID_W};
+
+    fn bodies(swarm: &Swarm) -> Vec<Vec<IVec2>> {
+        swarm.ai.snakes.iter().map(|s| s.body.iter().copied().collect()).collect()
+    }
+
+    #[test]
+    fn test_config_caps_snakes_and_board() {
+        let config = SwarmConfig::default().with_board(10_000, 4).with_snakes(10_000).with_fruits(usize::MAX);
+        assert_eq!(config.board, BoardDims::new(SWARM_MAX_SIDE, SWARM_MIN_SIDE));
+        assert_eq!(config.snakes, SWARM_MAX_SNAKES);
+        assert_eq!(config.fruits, config.board.cells() / 10);
+    }
+
+    #[test]
+    fn test_fills_a_board_larger_than_the_game() {
+        let config = SwarmConfig::default().with_board(GRID_W * 3, GRID_H * 3).with_snakes(SWARM_MAX_SNAKES).with_seed(5);
+        let mut swarm = Swarm::new(config);
+        assert_eq!(swarm.ai.snakes.len(), SWARM_MAX_SNAKES);
+        assert!(swarm.ai.snakes.iter().any(|s| s.head().x >= GRID_W || s.head().y >= GRID_H));
+
+        for _ in 0..5 {
+            swarm.step(0.05);
+        }
+        let board = swarm.config.board;
+        assert!(swarm.ai.snakes.iter().all(|s| s.body.iter().all(|&p| board.contains(p))));
+        assert_eq!(swarm.ai.snakes.len(), SWARM_MAX_SNAKES);
+        assert_eq!(swarm.fruits.len(), swarm.config.fruits);
+    }
+
+    #[test]
+    fn test_same_seed_same_swarm_on_any_thread_count() {
+        let config = SwarmConfig::default().with_board(64, 48).with_snakes(200).with_seed(11);
+        let run = |threads: usize| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| {
+                let mut swarm = Swarm::new(config);
+                for _ in 0..6 {
+                    swarm.step(0.1);
+                }
+                (bodies(&swarm), swarm.food, swarm.deaths)
+            })
+        };
+        assert_eq!(run(1), run(4));
+    }
+}
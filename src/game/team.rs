@@ -0,0 +1,203 @@
+//! AI 蛇队伍
+//!
+//! [`TeamMode`](crate::types::TeamMode) 不是 Off 时 AI 蛇分属不同队伍：
+//!
+//! - 队友之间不互相阻挡，撞上去直接穿过（开启误伤时和敌人一样结算）
+//! - 队友共享目标：别的队友已经在追的目标代价更高，队伍自然分头吃东西
+//! - 敌对队伍有两条以上的蛇时，每条蛇按队内序号占住玩家蛇头前方或两侧的一个格子，合力包围
+//! - 玩家的盟友（[`Team::Player`]）不伤害玩家，离远了会跟上来，并拦截敌对 AI 蛇的蛇头
+//!
+//! 决策时所有蛇读同一份只读的 [`TeamBoard`]。
+
+use macroquad::prelude::*;
+use crate::types::BoardDims;
+use super::{AISnake, OccupancyGrid};
+
+/// 队伍
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Team {
+    /// 玩家的盟友
+    Player,
+    Red,
+    Blue,
+}
+
+impl Team {
+    /// 所有队伍
+    pub const ALL: [Team; 3] = [Team::Player, Team::Red, Team::Blue];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Team::Player => "Allies",
+            Team::Red => "Red",
+            Team::Blue => "Blue",
+        }
+    }
+
+    /// 队伍颜色（队员的蛇身颜色）
+    pub fn color(&self) -> Color {
+        match self {
+            Team::Player => Color::new(0.55, 0.95, 0.45, 1.0),
+            Team::Red => Color::new(0.95, 0.35, 0.3, 1.0),
+            Team::Blue => Color::new(0.3, 0.55, 1.0, 1.0),
+        }
+    }
+
+    /// 两条蛇是否是队友（没有队伍的蛇没有队友）
+    pub fn allied(a: Option<Team>, b: Option<Team>) -> bool {
+        a.is_some() && a == b
+    }
+}
+
+/// 队伍事件（随 [`AIUpdateResult`](super::AIUpdateResult) 报告）
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TeamEvent {
+    /// 队员死亡
+    MemberLost { team: Team, id: u32 },
+    /// 队伍最后一名队员死亡
+    Eliminated(Team),
+    /// 玩家蛇头四周都被这支队伍（加上墙和玩家自己）堵死
+    PlayerSurrounded(Team),
+}
+
+/// 决策时看到的一条 AI 蛇
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TeamMember {
+    pub id: u32,
+    pub team: Option<Team>,
+    pub head: IVec2,
+    pub dir: IVec2,
+    /// 正在追的目标
+    pub target: Option<IVec2>,
+    /// 被冰冻（不会动）
+    pub frozen: bool,
+}
+
+/// 一轮决策中所有 AI 蛇的队伍快照
+#[derive(Default)]
+pub struct TeamBoard {
+    members: Vec<TeamMember>,
+    /// 按 ID 排序的各蛇队伍（寻路时每一格都要查，用二分查找）
+    teams: Vec<(u32, Option<Team>)>,
+    /// 各队所有队员身体的占用网格；开启误伤时为空（队友照样阻挡）
+    bodies: Vec<(Team, OccupancyGrid)>,
+    /// 队友之间相撞是否按敌人结算
+    pub friendly_fire: bool,
+}
+
+impl TeamBoard {
+    /// 按当前所有 AI 蛇生成快照
+    pub fn new(snakes: &[AISnake], friendly_fire: bool) -> Self {
+        let members: Vec<_> = snakes
+            .iter()
+            .map(|s| TeamMember {
+                id: s.id,
+                team: s.team,
+                head: s.head(),
+                dir: s.dir,
+                target: s.target,
+                frozen: s.buff_state.frozen,
+            })
+            .collect();
+        let mut teams: Vec<_> = members.iter().map(|m| (m.id, m.team)).collect();
+        teams.sort_unstable_by_key(|&(id, _)| id);
+        let board = snakes.first().map_or(BoardDims::DEFAULT, |s| s.board).end();
+        let bodies = if friendly_fire {
+            Vec::new()
+        } else {
+            Team::ALL
+                .into_iter()
+                .filter(|&team| snakes.iter().any(|s| s.team == Some(team)))
+                .map(|team| {
+                    let cells = snakes.iter().filter(|s| s.team == Some(team)).flat_map(|s| s.body.iter());
+                    (team, OccupancyGrid::from_cells(board.x, board.y, cells))
+                })
+                .collect()
+        };
+        TeamBoard { members, teams, bodies, friendly_fire }
+    }
+
+    /// 某条蛇的队伍
+    pub fn team_of(&self, id: u32) -> Option<Team> {
+        let i = self.teams.binary_search_by_key(&id, |&(id, _)| id).ok()?;
+        self.teams[i].1
+    }
+
+    /// 是否是玩家的盟友
+    pub fn is_ally(&self, id: u32) -> bool {
+        self.team_of(id) == Some(Team::Player)
+    }
+
+    /// 同队的其他蛇
+    pub fn teammates(&self, id: u32) -> impl Iterator<Item = &TeamMember> + '_ {
+        let team = self.team_of(id);
+        self.members.iter().filter(move |m| m.id != id && Team::allied(m.team, team))
+    }
+
+    /// 与这条蛇敌对的 AI 蛇（不同队或没有队伍）
+    pub fn enemies_of(&self, id: u32) -> impl Iterator<Item = &TeamMember> + '_ {
+        let team = self.team_of(id);
+        self.members.iter().filter(move |m| m.id != id && !Team::allied(m.team, team))
+    }
+
+    /// 在队内的序号和队伍人数（没有队伍时为 None）
+    pub fn rank(&self, id: u32) -> Option<(usize, usize)> {
+        let team = self.team_of(id)?;
+        let ids: Vec<u32> = self.members.iter().filter(|m| m.team == Some(team)).map(|m| m.id).collect();
+        Some((ids.iter().position(|&m| m == id)?, ids.len()))
+    }
+
+    /// 是否有队友正在追这个目标
+    pub fn claimed(&self, id: u32, pos: IVec2) -> bool {
+        self.teammates(id).any(|m| m.target == Some(pos))
+    }
+
+    /// 可以穿过的身体（自己和所有队友，包括自己）；开启误伤或没有队伍时为 None
+    pub fn passable_bodies(&self, id: u32) -> Option<&OccupancyGrid> {
+        let team = self.team_of(id)?;
+        self.bodies.iter().find(|(t, _)| *t == team).map(|(_, grid)| grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::SeedableRng;
+    use crate::game::{BrainKind, GameRng};
+    use crate::types::{BoardDims, GridKind};
+
+    fn member(id: u32, team: Option<Team>, head: IVec2) -> AISnake {
+        let mut rng = GameRng::seed_from_u64(id as u64);
+        let mut snake = AISnake::new(GridKind::Square, BoardDims::DEFAULT, id, BrainKind::Pathfinding.create(), |_| false, &mut rng).unwrap();
+        snake.body = [head, head + ivec2(0, 1)].into();
+        snake.team = team;
+        snake.target = Some(head + ivec2(5, 0));
+        snake
+    }
+
+    #[test]
+    fn test_board_groups_teammates() {
+        let snakes = [
+            member(1, Some(Team::Red), ivec2(2, 2)),
+            member(2, Some(Team::Red), ivec2(6, 2)),
+            member(3, Some(Team::Blue), ivec2(10, 2)),
+            member(4, None, ivec2(14, 2)),
+        ];
+        let board = TeamBoard::new(&snakes, false);
+        assert_eq!(board.teammates(1).map(|m| m.id).collect::<Vec<_>>(), [2]);
+        assert_eq!(board.enemies_of(1).map(|m| m.id).collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(board.teammates(4).count(), 0);
+        assert_eq!(board.rank(2), Some((1, 2)));
+        assert_eq!(board.rank(4), None);
+        assert!(board.claimed(1, ivec2(11, 2)));
+        assert!(!board.claimed(1, ivec2(7, 2)));
+
+        // 队友的身体可以穿过，敌人的不行
+        let red = board.passable_bodies(1).unwrap();
+        assert!(red.is_occupied(ivec2(6, 3)));
+        assert!(!red.is_occupied(ivec2(10, 2)));
+        assert!(board.passable_bodies(4).is_none());
+        assert!(TeamBoard::new(&snakes, true).passable_bodies(1).is_none());
+    }
+}
@@ -12,6 +12,12 @@
 //! - **render**: 渲染系统（蛇、果实、特效、HUD）
 //! - **audio**: 音效系统（程序化WAV生成）
 //! - **fruits**: 可扩展果实系统（Trait驱动设计）
+//! - **ui**: 键盘菜单控件和菜单界面流程
+//! - **online**: 在线排行榜客户端（离线队列、模拟服务器）
+//! - **net**: 联机对战（lockstep 输入同步、TCP/UDP、大厅）
+//! - **broadcast**: 观战广播（世界快照、延迟广播、观战镜头）
+//! - **bots**: HTTP 机器人玩家（Battlesnake 协议、超时回退、参考机器人）
+//! - **world**: 游戏世界（一局的全部状态和逐帧推进、单人对局回放）
 //!
 //! ## 快速开始
 //!
@@ -94,3 +100,39 @@ pub mod render;
 ///
 /// 程序化生成WAV音效，无需外部音频文件
 pub mod audio;
+
+/// 界面系统
+///
+/// 键盘可操作的菜单控件（按钮、开关、滑块、选项、按键绑定），
+/// 以及标题、模式选择、地图选择、设置、制作人员和暂停菜单
+pub mod ui;
+
+/// 在线排行榜
+///
+/// HTTP/JSON 成绩提交和查询、离线重试队列、后台线程服务，
+/// 以及用于测试的本地模拟服务器
+pub mod online;
+
+/// 联机对战
+///
+/// 确定性 lockstep 输入同步（输入延迟、状态哈希比对），TCP/UDP 传输，
+/// 以及按地址开房间 / 加入的大厅
+pub mod net;
+
+/// 观战广播
+///
+/// 把世界快照通过本地套接字广播给只读的观战端，支持延迟广播；
+/// 观战端的总览、自由和跟随镜头
+pub mod broadcast;
+
+/// HTTP 机器人
+///
+/// 兼容 Battlesnake API 的机器人玩家（带果实、Buff 扩展字段，超时回退），
+/// 以及离线对战用的本地参考机器人
+pub mod bots;
+
+/// 游戏世界
+///
+/// 一局游戏的全部状态和逐帧推进逻辑（不依赖窗口和键盘），
+/// 以及单人对局的回放录制和无界面重放
+pub mod world;
@@ -0,0 +1,122 @@
+//! 无界面的 lockstep 联调端
+//!
+//! 不开窗口，用随机输入驱动一局真正的游戏（无界面的 [`GameWorld`]），按游戏相同的协议同步并比对状态哈希。
+//! 两个进程（或一个进程加一个游戏窗口）通过回环地址即可测试联机：
+//!
+//! ```text
+//! cargo run --bin lockstep_peer -- --host 127.0.0.1:7777 [--udp] [--ticks 600]
+//! cargo run --bin lockstep_peer -- --join 127.0.0.1:7777 [--udp] [--ticks 600]
+//! ```
+//!
+//! 跑完后打印最终哈希，双方应当相同；断线或不同步时以非零状态退出。
+
+use std::time::{Duration, Instant};
+use ::rand::{Rng, SeedableRng};
+use rtest::audio::Silence;
+use rtest::game::GameRng;
+use rtest::net::{Lobby, MatchConfig, NetError, NetInput, NetLaunch, NetRole, NetSession};
+use rtest::types::{GameMode, Grid, GridKind, Settings};
+use rtest::world::GameWorld;
+
+/// 默认跑的 tick 数（10 秒）
+const DEFAULT_TICKS: u64 = 600;
+
+fn main() {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let ticks = match take_ticks(&mut args) {
+        Ok(ticks) => ticks,
+        Err(err) => exit_with(&err),
+    };
+    let launch = match NetLaunch::parse(args) {
+        Ok(Some(launch)) => launch,
+        Ok(None) => exit_with("usage: lockstep_peer (--host ADDR | --join ADDR) [--udp] [--delay N] [--hash-every N] [--ticks N]"),
+        Err(err) => exit_with(&err),
+    };
+    match run(&launch, ticks) {
+        Ok(hash) => println!("{:016x}", hash),
+        Err(err) => exit_with(&err.to_string()),
+    }
+}
+
+fn exit_with(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+/// 取出 `--ticks N`（其余参数交给 [`NetLaunch::parse`]）
+fn take_ticks(args: &mut Vec<String>) -> Result<u64, String> {
+    let Some(i) = args.iter().position(|a| a == "--ticks") else {
+        return Ok(DEFAULT_TICKS);
+    };
+    let value = args.get(i + 1).ok_or("--ticks needs a value")?;
+    let ticks = value.parse().map_err(|_| format!("--ticks expects a number, got {:?}", value))?;
+    args.drain(i..=i + 1);
+    Ok(ticks)
+}
+
+fn connect(launch: &NetLaunch) -> Result<NetSession, NetError> {
+    let mut lobby = match launch.role {
+        NetRole::Host => {
+            let config = MatchConfig::new(::rand::random(), GameMode::Classic, 0, GridKind::Square, &Settings::default())
+                .with_input_delay(launch.input_delay)
+                .with_hash_interval(launch.hash_interval);
+            Lobby::host(&launch.addr, launch.transport, config)?
+        }
+        NetRole::Join => Lobby::join(&launch.addr, launch.transport, &launch.name)?,
+    };
+    eprintln!("{}", lobby.status());
+    loop {
+        if let Some(session) = lobby.poll()? {
+            return Ok(session);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// 跑 `ticks` 个 tick，返回最终状态哈希
+fn run(launch: &NetLaunch, ticks: u64) -> Result<u64, NetError> {
+    let mut session = connect(launch)?;
+    let config = session.config().clone();
+    eprintln!(
+        "playing seat {} over {}, seed {:016x}, delay {}",
+        session.local() + 1,
+        session.transport_kind().name(),
+        config.seed,
+        config.input_delay
+    );
+
+    let mut world = GameWorld::headless();
+    world.start_online(&config)?;
+    // 本地输入用自己的随机数，模拟的结果只取决于交换后的输入
+    let mut input_rng = GameRng::seed_from_u64(config.seed ^ session.local() as u64);
+    let step = Duration::from_secs(1) / rtest::net::TICK_RATE;
+    let mut next_push = Instant::now();
+    let mut hash = world.state_hash();
+
+    while session.lockstep().tick() < ticks {
+        session.poll()?;
+        if Instant::now() >= next_push {
+            let dirs = world.grid.directions();
+            let turn = input_rng.gen_bool(0.1).then(|| dirs[input_rng.gen_range(0..dirs.len())]);
+            if session.push_input(NetInput::turn(turn))? {
+                next_push += step;
+            }
+        }
+        while let Some((tick, inputs)) = session.next_tick() {
+            hash = world.advance_tick(&inputs, &Silence);
+            session.record_hash(tick, hash)?;
+            if tick + 1 == ticks {
+                break;
+            }
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    // 把对方还没确认的输入送到再离开
+    let linger = Instant::now() + Duration::from_secs(2);
+    while session.lockstep().has_unacked() && Instant::now() < linger {
+        let _ = session.poll();
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    Ok(hash)
+}
@@ -0,0 +1,22 @@
+//! 本地模拟排行榜服务器
+//!
+//! 用法：`cargo run --bin mock_leaderboard [地址]`，默认监听 `127.0.0.1:8787`，
+//! 然后用 `SNAKE2D_ONLINE_URL=http://127.0.0.1:8787 cargo run` 启动游戏。
+
+use rtest::online::MockServer;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:8787";
+
+fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    match MockServer::start(addr.as_str()) {
+        Ok(server) => {
+            println!("Mock leaderboard listening on {}", server.url());
+            server.join();
+        }
+        Err(e) => {
+            eprintln!("Failed to bind {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    }
+}
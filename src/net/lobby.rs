@@ -0,0 +1,410 @@
+//! 联机大厅
+//!
+//! 主机在一个地址上等待加入（[`Lobby::host`]），加入方按地址连接（[`Lobby::join`]）。
+//! 握手：加入方发 Hello，主机检查协议版本后回 Welcome（座位 + 对局设置），双方各自得到一个
+//! [`NetSession`]。大厅同样是非阻塞的，主循环每帧调用 [`Lobby::poll`] 直到返回会话。
+//!
+//! 游戏从命令行进入大厅（见 [`NetLaunch::parse`]）：
+//!
+//! ```text
+//! snake2d --host 0.0.0.0:7777 [--udp] [--delay 3] [--hash-every 60]
+//! snake2d --join 127.0.0.1:7777 [--udp] [--name Ann]
+//! ```
+
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+use super::protocol::{MatchConfig, NetError, NetMessage, TransportKind, DEFAULT_HASH_INTERVAL, DEFAULT_INPUT_DELAY, PROTOCOL_VERSION};
+use super::session::NetSession;
+use super::transport::{TcpTransport, Transport, UdpTransport};
+
+/// 联机对局的玩家数（主机 + 一名加入方）
+pub const NET_PLAYERS: usize = 2;
+/// 加入方重发 Hello 的间隔（UDP）
+pub const JOIN_RETRY: Duration = Duration::from_millis(200);
+/// TCP 连接超时
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 大厅中的角色
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NetRole {
+    Host,
+    Join,
+}
+
+/// 命令行指定的联机方式
+#[derive(Clone, PartialEq, Debug)]
+pub struct NetLaunch {
+    pub role: NetRole,
+    /// 主机监听 / 加入方连接的地址
+    pub addr: String,
+    pub transport: TransportKind,
+    /// 输入延迟（tick，只有主机的生效）
+    pub input_delay: u32,
+    /// 哈希比对间隔（tick，只有主机的生效）
+    pub hash_interval: u32,
+    /// 玩家名字（握手时发给主机）
+    pub name: String,
+}
+
+impl NetLaunch {
+    /// 解析命令行参数（不含程序名）；没有 `--host` / `--join` 时返回 `Ok(None)`
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let mut role = None;
+        let mut launch = NetLaunch {
+            role: NetRole::Host,
+            addr: String::new(),
+            transport: TransportKind::Tcp,
+            input_delay: DEFAULT_INPUT_DELAY,
+            hash_interval: DEFAULT_HASH_INTERVAL,
+            name: "Player".to_string(),
+        };
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--host" | "--join" => {
+                    if role.is_some() {
+                        return Err("use only one of --host and --join".to_string());
+                    }
+                    role = Some(if arg == "--host" { NetRole::Host } else { NetRole::Join });
+                    launch.addr = value()?;
+                }
+                "--udp" => launch.transport = TransportKind::Udp,
+                "--tcp" => launch.transport = TransportKind::Tcp,
+                "--delay" => launch.input_delay = parse_number(&arg, &value()?)?,
+                "--hash-every" => launch.hash_interval = parse_number(&arg, &value()?)?.max(1),
+                "--name" => launch.name = value()?,
+                _ => return Err(format!("unknown argument {}", arg)),
+            }
+        }
+        Ok(role.map(|role| NetLaunch { role, ..launch }))
+    }
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| format!("{} expects a number, got {:?}", flag, value))
+}
+
+/// 大厅状态
+enum LobbyState {
+    /// 主机：等待 TCP 连接，已连上的等它发 Hello
+    TcpHost { listener: TcpListener, pending: Vec<TcpTransport> },
+    /// 主机：等待第一个 UDP Hello
+    UdpHost { socket: Option<UdpSocket> },
+    /// 加入方：等待 Welcome
+    Joining { transport: Box<dyn Transport>, last_hello: Option<Instant> },
+}
+
+/// 主机等待加入或加入方等待握手完成
+pub struct Lobby {
+    state: LobbyState,
+    /// 主机的对局设置
+    config: Option<MatchConfig>,
+    name: String,
+    local_addr: Option<SocketAddr>,
+    remote: String,
+}
+
+impl Lobby {
+    /// 在 `addr` 上开房间（端口为 0 时由系统分配），有人加入时按 `config` 开局
+    pub fn host(addr: &str, kind: TransportKind, config: MatchConfig) -> Result<Self, NetError> {
+        let (state, local_addr) = match kind {
+            TransportKind::Tcp => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                let local = listener.local_addr()?;
+                (LobbyState::TcpHost { listener, pending: Vec::new() }, local)
+            }
+            TransportKind::Udp => {
+                let socket = UdpSocket::bind(addr)?;
+                socket.set_nonblocking(true)?;
+                let local = socket.local_addr()?;
+                (LobbyState::UdpHost { socket: Some(socket) }, local)
+            }
+        };
+        Ok(Lobby {
+            state,
+            config: Some(config),
+            name: String::new(),
+            local_addr: Some(local_addr),
+            remote: String::new(),
+        })
+    }
+
+    /// 加入 `addr` 上的房间
+    pub fn join(addr: &str, kind: TransportKind, name: &str) -> Result<Self, NetError> {
+        let target = addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| NetError::Io(format!("cannot resolve {}", addr)))?;
+        let transport: Box<dyn Transport> = match kind {
+            TransportKind::Tcp => Box::new(TcpTransport::new(TcpStream::connect_timeout(&target, CONNECT_TIMEOUT)?)?),
+            TransportKind::Udp => {
+                let any: SocketAddr = if target.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+                let socket = UdpSocket::bind(any)?;
+                socket.connect(target)?;
+                Box::new(UdpTransport::new(socket)?)
+            }
+        };
+        Ok(Lobby {
+            state: LobbyState::Joining { transport, last_hello: None },
+            config: None,
+            name: name.to_string(),
+            local_addr: None,
+            remote: target.to_string(),
+        })
+    }
+
+    /// 主机实际监听的地址
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+
+    /// 大厅界面显示的状态
+    pub fn status(&self) -> String {
+        match &self.state {
+            LobbyState::TcpHost { .. } | LobbyState::UdpHost { .. } => match self.local_addr {
+                Some(addr) => format!("Waiting for a player on {}", addr),
+                None => "Waiting for a player".to_string(),
+            },
+            LobbyState::Joining { .. } => format!("Joining {}", self.remote),
+        }
+    }
+
+    /// 推进握手，完成时返回会话
+    pub fn poll(&mut self) -> Result<Option<NetSession>, NetError> {
+        let name = self.name.clone();
+        match &mut self.state {
+            LobbyState::TcpHost { listener, pending } => {
+                loop {
+                    match listener.accept() {
+                        Ok((stream, _)) => pending.push(TcpTransport::new(stream)?),
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                let config = self.config.as_ref().expect("host lobby has a config");
+                for i in 0..pending.len() {
+                    // 握手前断开或发来乱码的连接直接丢掉
+                    let Ok(messages) = pending[i].poll() else {
+                        pending.remove(i);
+                        return Ok(None);
+                    };
+                    let Some(NetMessage::Hello { version, .. }) = messages.into_iter().next() else {
+                        continue;
+                    };
+                    let mut transport = pending.remove(i);
+                    if version != PROTOCOL_VERSION {
+                        let _ = transport.send(&reject_version());
+                        return Ok(None);
+                    }
+                    return welcome(Box::new(transport), config).map(Some);
+                }
+                Ok(None)
+            }
+            LobbyState::UdpHost { socket } => {
+                let mut datagram = vec![0u8; 65_507];
+                let Some(sock) = socket.as_ref() else {
+                    return Ok(None);
+                };
+                loop {
+                    let (n, peer) = match sock.recv_from(&mut datagram) {
+                        Ok(received) => received,
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return Ok(None),
+                        // 上一个对方已经关掉的端口回来的 ICMP，忽略
+                        Err(e) if e.kind() == std::io::ErrorKind::ConnectionReset => continue,
+                        Err(e) => return Err(e.into()),
+                    };
+                    let Ok(NetMessage::Hello { version, .. }) = NetMessage::decode(&datagram[..n]) else {
+                        continue;
+                    };
+                    if version != PROTOCOL_VERSION {
+                        let _ = sock.send_to(&reject_version().encode(), peer);
+                        continue;
+                    }
+                    let sock = socket.take().expect("checked above");
+                    sock.connect(peer)?;
+                    let config = self.config.as_ref().expect("host lobby has a config");
+                    return welcome(Box::new(UdpTransport::new(sock)?), config).map(Some);
+                }
+            }
+            LobbyState::Joining { transport, last_hello } => {
+                let due = last_hello.is_none_or(|t| t.elapsed() >= JOIN_RETRY);
+                if due && (last_hello.is_none() || transport.kind() == TransportKind::Udp) {
+                    transport.send(&NetMessage::Hello { version: PROTOCOL_VERSION, name })?;
+                    *last_hello = Some(Instant::now());
+                }
+                let messages = match transport.poll() {
+                    // UDP 下主机还没开房间时会收到拒绝连接，继续重发 Hello
+                    Err(NetError::Disconnected) if transport.kind() == TransportKind::Udp => Vec::new(),
+                    result => result?,
+                };
+                for msg in messages {
+                    match msg {
+                        NetMessage::Welcome { player, players, config } => {
+                            let LobbyState::Joining { transport, .. } = std::mem::replace(
+                                &mut self.state,
+                                LobbyState::UdpHost { socket: None },
+                            ) else {
+                                unreachable!("matched Joining above");
+                            };
+                            return Ok(Some(NetSession::new(transport, config, players, player)));
+                        }
+                        NetMessage::Reject { reason } => return Err(NetError::Rejected(reason)),
+                        _ => {}
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// 协议版本不同的拒绝消息
+fn reject_version() -> NetMessage {
+    NetMessage::Reject { reason: format!("protocol version {} required", PROTOCOL_VERSION) }
+}
+
+/// 主机接受加入：发 Welcome，本地坐第 0 个座位
+fn welcome(mut transport: Box<dyn Transport>, config: &MatchConfig) -> Result<NetSession, NetError> {
+    let msg = NetMessage::Welcome { player: 1, players: NET_PLAYERS, config: config.clone() };
+    transport.send(&msg)?;
+    Ok(NetSession::new(transport, config.clone(), NET_PLAYERS, 0).with_welcome(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::{Rng, SeedableRng};
+    use macroquad::prelude::ivec2;
+    use crate::game::GameRng;
+    use crate::net::{NetInput, StateHasher};
+    use crate::types::{GameMode, GridKind, Settings};
+
+    /// 轮询大厅直到握手完成
+    fn connect(lobby: &mut Lobby) -> NetSession {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(session) = lobby.poll().unwrap() {
+                return session;
+            }
+            assert!(Instant::now() < deadline, "handshake timed out");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// 用随机输入跑 `ticks` 个 tick，状态是所有输入的哈希；`skew` 从这个 tick 起故意算错
+    fn play(mut session: NetSession, ticks: u64, seed: u64, skew: Option<u64>) -> Result<u64, NetError> {
+        let mut rng = GameRng::seed_from_u64(seed);
+        let mut state = StateHasher::new();
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while session.lockstep().tick() < ticks {
+            assert!(Instant::now() < deadline, "lockstep stalled at {}", session.lockstep().tick());
+            session.poll()?;
+            let input = NetInput::turn(rng.gen_bool(0.2).then(|| ivec2(rng.gen_range(-1..=1), 1)));
+            session.push_input(input)?;
+            while let Some((tick, inputs)) = session.next_tick() {
+                for input in inputs {
+                    let dir = input.dir().unwrap_or_default();
+                    state.write_ivec2(dir);
+                }
+                if skew.is_some_and(|t| tick >= t) {
+                    state.write_u64(1);
+                }
+                session.record_hash(tick, state.finish())?;
+                if tick + 1 == ticks {
+                    break;
+                }
+            }
+            std::thread::sleep(Duration::from_micros(200));
+        }
+        // 把对方还没确认的输入送到再离开
+        let linger = Instant::now() + Duration::from_secs(2);
+        while session.lockstep().has_unacked() && Instant::now() < linger {
+            let _ = session.poll();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Ok(state.finish())
+    }
+
+    fn run_match(kind: TransportKind, skew: Option<u64>) -> (Result<u64, NetError>, Result<u64, NetError>) {
+        let config = MatchConfig::new(99, GameMode::Arena, 0, GridKind::Square, &Settings::default())
+            .with_input_delay(2)
+            .with_hash_interval(20);
+        let mut host = Lobby::host("127.0.0.1:0", kind, config.clone()).unwrap();
+        let addr = host.local_addr().unwrap().to_string();
+        assert!(host.status().contains(&addr));
+
+        let guest = std::thread::spawn(move || {
+            let mut lobby = Lobby::join(&addr, kind, "Guest").unwrap();
+            let session = connect(&mut lobby);
+            assert_eq!(session.local(), 1);
+            assert_eq!(session.config().seed, 99);
+            play(session, 300, 2, skew)
+        });
+        let session = connect(&mut host);
+        assert_eq!(session.local(), 0);
+        assert_eq!(session.transport_kind(), kind);
+        let host_result = play(session, 300, 1, None);
+        (host_result, guest.join().unwrap())
+    }
+
+    #[test]
+    fn test_tcp_match_over_loopback() {
+        let (host, guest) = run_match(TransportKind::Tcp, None);
+        assert_eq!(host.unwrap(), guest.unwrap());
+    }
+
+    #[test]
+    fn test_udp_match_over_loopback() {
+        let (host, guest) = run_match(TransportKind::Udp, None);
+        assert_eq!(host.unwrap(), guest.unwrap());
+    }
+
+    #[test]
+    fn test_desync_is_detected() {
+        let (host, guest) = run_match(TransportKind::Tcp, Some(50));
+        // 第一次比对到不同哈希的是 tick 60
+        let desync = |r: Result<u64, NetError>| matches!(r, Err(NetError::Desync { tick: 60, .. }));
+        assert!(desync(host.clone()) || desync(guest.clone()), "{:?} {:?}", host, guest);
+    }
+
+    #[test]
+    fn test_version_mismatch_is_rejected() {
+        let config = MatchConfig::new(1, GameMode::Classic, 0, GridKind::Square, &Settings::default());
+        let mut host = Lobby::host("127.0.0.1:0", TransportKind::Udp, config).unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.connect(host.local_addr().unwrap()).unwrap();
+        let mut transport = UdpTransport::new(socket).unwrap();
+        transport.send(&NetMessage::Hello { version: PROTOCOL_VERSION + 1, name: "Old".into() }).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let reply = loop {
+            assert!(host.poll().unwrap().is_none());
+            if let Some(msg) = transport.poll().unwrap().pop() {
+                break msg;
+            }
+            assert!(Instant::now() < deadline);
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert!(matches!(reply, NetMessage::Reject { .. }));
+    }
+
+    #[test]
+    fn test_parse_launch_arguments() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+        assert_eq!(NetLaunch::parse(args("")), Ok(None));
+        let host = NetLaunch::parse(args("--host 0.0.0.0:7777 --udp --delay 5 --hash-every 0")).unwrap().unwrap();
+        assert_eq!(host.role, NetRole::Host);
+        assert_eq!(host.addr, "0.0.0.0:7777");
+        assert_eq!(host.transport, TransportKind::Udp);
+        assert_eq!((host.input_delay, host.hash_interval), (5, 1));
+        let join = NetLaunch::parse(args("--join 127.0.0.1:7777 --name Ann")).unwrap().unwrap();
+        assert_eq!((join.role, join.name.as_str(), join.transport), (NetRole::Join, "Ann", TransportKind::Tcp));
+
+        assert!(NetLaunch::parse(args("--host a --join b")).is_err());
+        assert!(NetLaunch::parse(args("--delay x --host a")).is_err());
+        assert!(NetLaunch::parse(args("--join")).is_err());
+        assert!(NetLaunch::parse(args("--fast")).is_err());
+    }
+}
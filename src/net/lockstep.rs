@@ -0,0 +1,329 @@
+//! Lockstep 输入同步
+//!
+//! [`Lockstep`] 只管理数据，不碰套接字：
+//!
+//! - 本地输入排在 `当前 tick + input_delay` 上（开局的前 `input_delay` 个 tick 所有人都是空输入），
+//!   本地最多领先当前 tick `input_delay` 个 tick，再多就等对方
+//! - 所有玩家某个 tick 的输入都到齐后 [`Lockstep::advance`] 才交出这个 tick
+//! - 发给对方的 [`NetMessage::Inputs`] 总是包含对方还没确认的全部本地输入，丢包后下一条消息补上
+//! - 每隔 `hash_interval` 个 tick 记录一次状态哈希，和对方同一 tick 的哈希不同就是不同步；
+//!   对方确认（[`NetMessage::Inputs`] 的 `hash_ack`）之前哈希随输入消息一起重发，丢包也不会漏掉比对
+
+use std::collections::{BTreeMap, BTreeSet};
+use super::protocol::{NetError, NetInput, NetMessage};
+
+/// 一局 lockstep 对局的输入和哈希
+#[derive(Clone, Debug)]
+pub struct Lockstep {
+    players: usize,
+    local: usize,
+    input_delay: u64,
+    hash_interval: u64,
+    /// 下一个要执行的 tick
+    tick: u64,
+    /// 每名玩家已经连续收到（本地玩家是已经排好）的输入 tick 数
+    received: Vec<u64>,
+    /// 对方已经确认收到的本地输入 tick 数
+    acked: u64,
+    /// 每名玩家按 tick 的输入（执行过且对方已确认的会被清掉）
+    inputs: Vec<BTreeMap<u64, NetInput>>,
+    /// 还没和对方比对的本地哈希 / 对方哈希
+    local_hashes: BTreeMap<u64, u64>,
+    remote_hashes: BTreeMap<u64, u64>,
+    /// 对方还没确认收到的本地哈希
+    sent_hashes: BTreeMap<u64, u64>,
+    /// 还没收到的第一个对方哈希的 tick
+    hash_ack: u64,
+    /// 已经收到、但前面还有没收到的对方哈希的 tick
+    hashes_ahead: BTreeSet<u64>,
+    /// 第一次发现的不同步
+    desync: Option<NetError>,
+}
+
+impl Lockstep {
+    /// `players` 名玩家中本地是第 `local` 名
+    pub fn new(players: usize, local: usize, input_delay: u32, hash_interval: u32) -> Self {
+        let input_delay = u64::from(input_delay);
+        let prefill: BTreeMap<u64, NetInput> = (0..input_delay).map(|t| (t, NetInput::default())).collect();
+        Lockstep {
+            players,
+            local,
+            input_delay,
+            hash_interval: u64::from(hash_interval.max(1)),
+            tick: 0,
+            received: vec![input_delay; players],
+            acked: input_delay,
+            inputs: vec![prefill; players],
+            local_hashes: BTreeMap::new(),
+            remote_hashes: BTreeMap::new(),
+            sent_hashes: BTreeMap::new(),
+            hash_ack: 0,
+            hashes_ahead: BTreeSet::new(),
+            desync: None,
+        }
+    }
+
+    /// 本地玩家的座位
+    pub fn local(&self) -> usize {
+        self.local
+    }
+
+    /// 下一个要执行的 tick
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// 排入本地玩家的下一个输入；已经领先 `input_delay` 个 tick 时返回 false（等对方）
+    pub fn push_local(&mut self, input: NetInput) -> bool {
+        let next = self.received[self.local];
+        if next > self.tick + self.input_delay {
+            return false;
+        }
+        self.inputs[self.local].insert(next, input);
+        self.received[self.local] = next + 1;
+        true
+    }
+
+    /// 还有对方没确认的本地输入或哈希
+    pub fn has_unacked(&self) -> bool {
+        self.acked < self.received[self.local] || !self.sent_hashes.is_empty()
+    }
+
+    /// 发给对方的输入消息：所有没确认的本地输入，以及本地已经收到对方多少 tick 的输入和哈希
+    pub fn outgoing(&self) -> NetMessage {
+        let first_tick = self.acked;
+        let inputs = self.inputs[self.local].range(first_tick..).map(|(_, &input)| input).collect();
+        NetMessage::Inputs { player: self.local, first_tick, inputs, ack: self.remote_received(), hash_ack: self.hash_ack }
+    }
+
+    /// 对方还没确认的哈希，和输入消息一起重发
+    pub fn outgoing_hashes(&self) -> impl Iterator<Item = NetMessage> + '_ {
+        self.sent_hashes.iter().map(|(&tick, &hash)| NetMessage::Hash { tick, hash })
+    }
+
+    /// 已经从所有对方连续收到的 tick 数
+    fn remote_received(&self) -> u64 {
+        (0..self.players).filter(|&p| p != self.local).map(|p| self.received[p]).min().unwrap_or(u64::MAX)
+    }
+
+    /// 处理对方发来的输入和哈希，其他消息忽略
+    pub fn receive(&mut self, msg: &NetMessage) -> Result<(), NetError> {
+        match *msg {
+            NetMessage::Inputs { player, first_tick, ref inputs, ack, hash_ack } => {
+                if player >= self.players || player == self.local {
+                    return Err(NetError::Protocol(format!("inputs for seat {}", player)));
+                }
+                for (tick, &input) in (first_tick..).zip(inputs) {
+                    if tick >= self.received[player] {
+                        self.inputs[player].insert(tick, input);
+                    }
+                }
+                while self.inputs[player].contains_key(&self.received[player]) {
+                    self.received[player] += 1;
+                }
+                self.acked = self.acked.max(ack.min(self.received[self.local]));
+                self.sent_hashes = self.sent_hashes.split_off(&hash_ack);
+                self.prune();
+            }
+            NetMessage::Hash { tick, hash } => {
+                if !self.hash_due(tick) {
+                    return Err(NetError::Protocol(format!("hash for tick {} off the interval", tick)));
+                }
+                // 重发的哈希已经收到过
+                if tick < self.hash_ack || !self.hashes_ahead.insert(tick) {
+                    return Ok(());
+                }
+                while self.hashes_ahead.remove(&self.hash_ack) {
+                    self.hash_ack += self.hash_interval;
+                }
+                self.remote_hashes.insert(tick, hash);
+                self.compare_hashes();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 当前 tick 所有玩家的输入都到齐了
+    pub fn ready(&self) -> bool {
+        self.received.iter().all(|&r| r > self.tick)
+    }
+
+    /// 输入到齐时交出当前 tick 和所有玩家的输入（按座位排列），并前进一个 tick
+    pub fn advance(&mut self) -> Option<(u64, Vec<NetInput>)> {
+        if !self.ready() {
+            return None;
+        }
+        let tick = self.tick;
+        let inputs = self.inputs.iter().map(|by_tick| by_tick[&tick]).collect();
+        self.tick += 1;
+        self.prune();
+        Some((tick, inputs))
+    }
+
+    /// 这个 tick 结束时是否该比对状态哈希
+    pub fn hash_due(&self, tick: u64) -> bool {
+        tick.is_multiple_of(self.hash_interval)
+    }
+
+    /// 记录 `tick` 结束时的状态哈希，到了比对的 tick 时返回要发给对方的消息（对方确认前还会随输入重发）
+    pub fn record_hash(&mut self, tick: u64, hash: u64) -> Option<NetMessage> {
+        if !self.hash_due(tick) {
+            return None;
+        }
+        self.local_hashes.insert(tick, hash);
+        self.sent_hashes.insert(tick, hash);
+        self.compare_hashes();
+        Some(NetMessage::Hash { tick, hash })
+    }
+
+    /// 发现的第一次不同步
+    pub fn desync(&self) -> Option<&NetError> {
+        self.desync.as_ref()
+    }
+
+    /// 比对双方都有的哈希，比对过的丢掉
+    fn compare_hashes(&mut self) {
+        let common: Vec<u64> = self.local_hashes.keys().filter(|t| self.remote_hashes.contains_key(t)).copied().collect();
+        for tick in common {
+            let local = self.local_hashes.remove(&tick).unwrap_or_default();
+            let remote = self.remote_hashes.remove(&tick).unwrap_or_default();
+            if local != remote && self.desync.is_none() {
+                self.desync = Some(NetError::Desync { tick, local, remote });
+            }
+        }
+    }
+
+    /// 丢掉已经执行过的对方输入，以及已经执行过且对方已确认的本地输入
+    fn prune(&mut self) {
+        for (player, by_tick) in self.inputs.iter_mut().enumerate() {
+            let keep_from = if player == self.local { self.tick.min(self.acked) } else { self.tick };
+            *by_tick = by_tick.split_off(&keep_from);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::rand::{Rng, SeedableRng};
+    use macroquad::prelude::ivec2;
+    use crate::game::GameRng;
+
+    fn turn(x: i32) -> NetInput {
+        NetInput::turn(Some(ivec2(x, 0)))
+    }
+
+    #[test]
+    fn test_input_delay_and_stall() {
+        let mut host = Lockstep::new(2, 0, 2, 60);
+        // 开局的延迟 tick 不需要任何输入
+        assert_eq!(host.advance(), Some((0, vec![NetInput::default(); 2])));
+        assert!(host.push_local(turn(1)));
+        assert!(host.push_local(turn(1)));
+        // 最多领先 input_delay 个 tick
+        assert!(!host.push_local(turn(1)));
+        assert_eq!(host.advance(), Some((1, vec![NetInput::default(); 2])));
+        // 对方的输入还没到，停在 tick 2
+        assert_eq!(host.advance(), None);
+
+        let guest = NetMessage::Inputs { player: 1, first_tick: 2, inputs: vec![turn(-1)], ack: 3, hash_ack: 0 };
+        host.receive(&guest).unwrap();
+        assert_eq!(host.advance(), Some((2, vec![turn(1), turn(-1)])));
+        assert_eq!(host.advance(), None);
+        // 对方确认收到了 tick 2 之前的输入，只需重发 tick 3
+        assert_eq!(
+            host.outgoing(),
+            NetMessage::Inputs { player: 0, first_tick: 3, inputs: vec![turn(1)], ack: 3, hash_ack: 0 }
+        );
+        assert!(host.receive(&NetMessage::Inputs { player: 0, first_tick: 0, inputs: vec![], ack: 0, hash_ack: 0 }).is_err());
+    }
+
+    #[test]
+    fn test_lossy_exchange_stays_in_step() {
+        let mut rng = GameRng::seed_from_u64(7);
+        let mut peers = [Lockstep::new(2, 0, 3, 10), Lockstep::new(2, 1, 3, 10)];
+        let mut executed: [Vec<Vec<NetInput>>; 2] = [Vec::new(), Vec::new()];
+        let mut sent: [Vec<NetInput>; 2] = [Vec::new(), Vec::new()];
+
+        for _ in 0..2000 {
+            for seat in 0..2 {
+                let input = NetInput::turn(rng.gen_bool(0.3).then(|| ivec2(rng.gen_range(-1..=1), 1)));
+                if peers[seat].push_local(input) {
+                    sent[seat].push(input);
+                }
+                // 一半的消息丢掉
+                if rng.gen_bool(0.5) {
+                    let msg = peers[seat].outgoing();
+                    peers[1 - seat].receive(&msg).unwrap();
+                }
+                while let Some((tick, inputs)) = peers[seat].advance() {
+                    assert_eq!(tick as usize, executed[seat].len());
+                    executed[seat].push(inputs);
+                    if let Some(msg) = peers[seat].record_hash(tick, tick * 31) {
+                        peers[1 - seat].receive(&msg).unwrap();
+                    }
+                }
+            }
+        }
+
+        let common = executed[0].len().min(executed[1].len());
+        assert!(common > 1000, "only {} ticks executed", common);
+        assert_eq!(executed[0][..common], executed[1][..common]);
+        // 每名玩家的输入延迟 3 个 tick 生效
+        for seat in 0..2 {
+            let applied: Vec<NetInput> = executed[seat].iter().skip(3).map(|inputs| inputs[seat]).collect();
+            assert_eq!(applied[..], sent[seat][..applied.len()]);
+        }
+        assert!(peers.iter().all(|p| p.desync().is_none()));
+    }
+
+    #[test]
+    fn test_hash_mismatch_is_desync() {
+        let mut a = Lockstep::new(2, 0, 0, 30);
+        assert!(a.record_hash(1, 5).is_none());
+        let msg = a.record_hash(30, 5).unwrap();
+        assert_eq!(msg, NetMessage::Hash { tick: 30, hash: 5 });
+        a.receive(&NetMessage::Hash { tick: 30, hash: 5 }).unwrap();
+        assert!(a.desync().is_none());
+
+        // 对方的哈希先到也能比对
+        a.receive(&NetMessage::Hash { tick: 60, hash: 9 }).unwrap();
+        a.record_hash(60, 8);
+        assert_eq!(a.desync(), Some(&NetError::Desync { tick: 60, local: 8, remote: 9 }));
+    }
+
+    #[test]
+    fn test_dropped_hash_is_resent_until_acked() {
+        let mut a = Lockstep::new(2, 0, 0, 30);
+        let mut b = Lockstep::new(2, 1, 0, 30);
+        for (tick, hash) in [(0, 1), (30, 2)] {
+            b.receive(&a.record_hash(tick, hash).unwrap()).unwrap();
+            a.receive(&b.record_hash(tick, hash).unwrap()).unwrap();
+        }
+        a.receive(&b.outgoing()).unwrap();
+        assert_eq!(a.outgoing_hashes().count(), 0);
+        assert!(!a.has_unacked());
+
+        // a 在 tick 60 的哈希丢了，b 的到了：a 比对过了，b 还没有
+        let _lost = a.record_hash(60, 3);
+        a.receive(&b.record_hash(60, 4).unwrap()).unwrap();
+        assert_eq!(a.desync(), Some(&NetError::Desync { tick: 60, local: 3, remote: 4 }));
+        assert!(b.desync().is_none());
+        assert!(a.has_unacked());
+
+        // 重发的哈希让 b 也发现不同步；重复收到不会重复比对
+        for _ in 0..2 {
+            for msg in a.outgoing_hashes().collect::<Vec<_>>() {
+                b.receive(&msg).unwrap();
+            }
+        }
+        assert_eq!(b.desync(), Some(&NetError::Desync { tick: 60, local: 4, remote: 3 }));
+        assert!(b.remote_hashes.is_empty());
+
+        // b 确认之后不再重发
+        a.receive(&b.outgoing()).unwrap();
+        assert_eq!(a.outgoing_hashes().count(), 0);
+        assert!(b.receive(&NetMessage::Hash { tick: 61, hash: 0 }).is_err());
+    }
+}
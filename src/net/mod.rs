@@ -0,0 +1,30 @@
+//! 联机对战模块
+//!
+//! 两台机器各跑一份完全相同的确定性模拟，只交换每个 tick 的玩家输入（lockstep）：
+//! 本地输入延迟 `input_delay` 个 tick 生效，双方都拿到某个 tick 的全部输入后才推进这个 tick。
+//! 每隔 `hash_interval` 个 tick 双方交换一次世界状态哈希，不一致时报告不同步。
+//!
+//! 主机决定对局设置和随机种子，加入方握手时收到（[`MatchConfig`]）。
+//! 传输层可以是 TCP 或 UDP；输入消息总是重发对方还没确认的所有 tick，UDP 丢包也能补上。
+//! 只依赖标准库套接字，两个本地进程通过回环地址即可联调（见 `lockstep_peer`）。
+//!
+//! ```text
+//! net/
+//! ├── protocol.rs     # 消息、对局设置、编解码、错误类型
+//! ├── transport.rs    # TCP / UDP 传输（非阻塞）
+//! ├── lockstep.rs     # 按 tick 收集输入、输入延迟、状态哈希比对
+//! ├── session.rs      # 一条连接上的 lockstep 会话（重发、超时）
+//! └── lobby.rs        # 主机等待加入 / 按地址加入、命令行参数
+//! ```
+
+pub mod protocol;
+pub mod transport;
+pub mod lockstep;
+pub mod session;
+pub mod lobby;
+
+pub use protocol::*;
+pub use transport::*;
+pub use lockstep::*;
+pub use session::*;
+pub use lobby::*;
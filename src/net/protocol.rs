@@ -0,0 +1,305 @@
+//! 联机协议
+//!
+//! 每条消息是一行 JSON（TCP 按换行分帧，UDP 一个数据报一条）：
+//!
+//! | 消息 | 方向 | 用途 |
+//! |------|------|------|
+//! | [`NetMessage::Hello`] | 加入方 → 主机 | 请求加入（UDP 下重发直到收到回复） |
+//! | [`NetMessage::Welcome`] | 主机 → 加入方 | 分配座位，下发 [`MatchConfig`] |
+//! | [`NetMessage::Reject`] | 主机 → 加入方 | 拒绝加入（版本不同、房间已满） |
+//! | [`NetMessage::Inputs`] | 双向 | 从 `first_tick` 开始的连续输入，附带输入和哈希的确认 |
+//! | [`NetMessage::Hash`] | 双向 | 某个 tick 结束时的世界状态哈希（对方确认前一直重发） |
+//! | [`NetMessage::Bye`] | 双向 | 离开对局 |
+
+use std::fmt;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::types::{BoardSize, CombatRules, Difficulty, GameMode, GridKind, Settings, TeamMode};
+
+/// 协议版本，双方不同时拒绝加入
+pub const PROTOCOL_VERSION: u32 = 2;
+/// 每秒模拟的 tick 数（联机时世界按固定步长推进）
+pub const TICK_RATE: u32 = 60;
+/// 默认输入延迟（tick）
+pub const DEFAULT_INPUT_DELAY: u32 = 3;
+/// 默认每隔多少 tick 比对一次状态哈希
+pub const DEFAULT_HASH_INTERVAL: u32 = 60;
+
+/// 联机错误
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum NetError {
+    /// 套接字错误
+    Io(String),
+    /// 收到无法解析或不该出现的消息
+    Protocol(String),
+    /// 主机拒绝加入
+    Rejected(String),
+    /// 对方离开、断开连接或长时间没有消息
+    Disconnected,
+    /// 双方在同一个 tick 的状态哈希不同
+    Desync { tick: u64, local: u64, remote: u64 },
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(msg) => write!(f, "network error: {}", msg),
+            NetError::Protocol(msg) => write!(f, "protocol error: {}", msg),
+            NetError::Rejected(reason) => write!(f, "rejected by host: {}", reason),
+            NetError::Disconnected => write!(f, "opponent disconnected"),
+            NetError::Desync { tick, local, remote } => {
+                write!(f, "desync at tick {}: {:016x} vs {:016x}", tick, local, remote)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetError {}
+
+impl From<std::io::Error> for NetError {
+    fn from(e: std::io::Error) -> Self {
+        NetError::Io(e.to_string())
+    }
+}
+
+/// 传输层
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TransportKind {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl TransportKind {
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransportKind::Tcp => "TCP",
+            TransportKind::Udp => "UDP",
+        }
+    }
+}
+
+/// 一名玩家在一个 tick 的输入
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub struct NetInput {
+    /// 这个 tick 按下的方向，没按时为 None
+    pub dir: Option<[i32; 2]>,
+}
+
+impl NetInput {
+    /// 转向 `dir`（None 表示不转向）
+    pub fn turn(dir: Option<IVec2>) -> Self {
+        NetInput { dir: dir.map(|d| d.to_array()) }
+    }
+
+    /// 按下的方向
+    pub fn dir(&self) -> Option<IVec2> {
+        self.dir.map(IVec2::from_array)
+    }
+}
+
+/// 对局设置：主机决定，握手时下发，双方用它开始同一局
+///
+/// 枚举按显示名称传输（和排行榜文件一样），地图按索引
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchConfig {
+    pub seed: u64,
+    pub mode: String,
+    pub map: usize,
+    pub grid: String,
+    pub difficulty: String,
+    pub board_size: String,
+    pub combat: String,
+    pub teams: String,
+    pub friendly_fire: bool,
+    /// 输入延迟（tick）
+    pub input_delay: u32,
+    /// 每隔多少 tick 比对一次状态哈希
+    pub hash_interval: u32,
+}
+
+impl MatchConfig {
+    /// 按主机当前的模式、地图、棋盘和设置创建，使用默认的输入延迟和哈希间隔
+    pub fn new(seed: u64, mode: GameMode, map: usize, grid: GridKind, settings: &Settings) -> Self {
+        MatchConfig {
+            seed,
+            mode: mode.name().to_string(),
+            map,
+            grid: grid.name().to_string(),
+            difficulty: settings.difficulty.name().to_string(),
+            board_size: settings.board_size.name().to_string(),
+            combat: settings.combat.name().to_string(),
+            teams: settings.teams.name().to_string(),
+            friendly_fire: settings.friendly_fire,
+            input_delay: DEFAULT_INPUT_DELAY,
+            hash_interval: DEFAULT_HASH_INTERVAL,
+        }
+    }
+
+    pub fn with_input_delay(mut self, ticks: u32) -> Self {
+        self.input_delay = ticks;
+        self
+    }
+
+    pub fn with_hash_interval(mut self, ticks: u32) -> Self {
+        self.hash_interval = ticks.max(1);
+        self
+    }
+
+    /// 把对局设置写进本地设置，返回模式和棋盘几何
+    pub fn apply(&self, settings: &mut Settings) -> Result<(GameMode, GridKind), NetError> {
+        settings.difficulty = lookup(&Difficulty::ALL, &self.difficulty, Difficulty::name)?;
+        settings.board_size = lookup(&BoardSize::ALL, &self.board_size, BoardSize::name)?;
+        settings.combat = lookup(&CombatRules::ALL, &self.combat, CombatRules::name)?;
+        settings.teams = lookup(&TeamMode::ALL, &self.teams, TeamMode::name)?;
+        settings.friendly_fire = self.friendly_fire;
+        let mode = lookup(&GameMode::ALL, &self.mode, GameMode::name)?;
+        let grid = lookup(&GridKind::ALL, &self.grid, GridKind::name)?;
+        Ok((mode, grid))
+    }
+}
+
+/// 按显示名称找枚举值
+fn lookup<T: Copy>(all: &[T], name: &str, name_of: fn(&T) -> &'static str) -> Result<T, NetError> {
+    all.iter()
+        .copied()
+        .find(|v| name_of(v) == name)
+        .ok_or_else(|| NetError::Protocol(format!("unknown setting {:?}", name)))
+}
+
+/// 联机消息
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum NetMessage {
+    Hello { version: u32, name: String },
+    Welcome { player: usize, players: usize, config: MatchConfig },
+    Reject { reason: String },
+    /// `player` 从 `first_tick` 开始的连续输入；`ack` 是发送方已经连续收到的对方输入的 tick 数，
+    /// `hash_ack` 是发送方还没收到的第一个对方哈希的 tick（之前的都收到了）
+    Inputs { player: usize, first_tick: u64, inputs: Vec<NetInput>, ack: u64, hash_ack: u64 },
+    Hash { tick: u64, hash: u64 },
+    Bye,
+}
+
+impl NetMessage {
+    /// 编码成一行 JSON（含换行）
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = serde_json::to_vec(self).expect("net messages always serialize");
+        bytes.push(b'\n');
+        bytes
+    }
+
+    /// 解码一行 JSON（忽略首尾空白）
+    pub fn decode(bytes: &[u8]) -> Result<Self, NetError> {
+        serde_json::from_slice(bytes.trim_ascii()).map_err(|e| NetError::Protocol(e.to_string()))
+    }
+}
+
+/// 世界状态哈希（FNV-1a 64 位，和回放哈希同一算法）
+///
+/// 只对会影响后续模拟的状态求哈希；浮点数按位写入，双方必须逐位相同
+#[derive(Clone, Copy, Debug)]
+pub struct StateHasher(u64);
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        StateHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl StateHasher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= u64::from(b);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write(&value.to_bits().to_le_bytes());
+    }
+
+    pub fn write_ivec2(&mut self, value: IVec2) {
+        self.write(&value.x.to_le_bytes());
+        self.write(&value.y.to_le_bytes());
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::online::replay_hash;
+
+    #[test]
+    fn test_messages_round_trip() {
+        let config = MatchConfig::new(42, GameMode::Arena, 2, GridKind::Hex, &Settings::default())
+            .with_input_delay(5)
+            .with_hash_interval(0);
+        assert_eq!(config.hash_interval, 1);
+        let messages = [
+            NetMessage::Hello { version: PROTOCOL_VERSION, name: "Ann".into() },
+            NetMessage::Welcome { player: 1, players: 2, config },
+            NetMessage::Inputs {
+                player: 0,
+                first_tick: 7,
+                inputs: vec![NetInput::turn(Some(ivec2(-1, 0))), NetInput::default()],
+                ack: 6,
+                hash_ack: 60,
+            },
+            NetMessage::Hash { tick: 60, hash: u64::MAX },
+            NetMessage::Bye,
+        ];
+        for msg in messages {
+            let bytes = msg.encode();
+            assert_eq!(bytes.last(), Some(&b'\n'));
+            assert_eq!(NetMessage::decode(&bytes).unwrap(), msg);
+        }
+        assert!(matches!(NetMessage::decode(b"{oops"), Err(NetError::Protocol(_))));
+    }
+
+    #[test]
+    fn test_config_applies_host_settings() {
+        let host = Settings {
+            difficulty: Difficulty::Hard,
+            combat: CombatRules::Cutting,
+            teams: TeamMode::Squads,
+            board_size: BoardSize::Small,
+            ..Default::default()
+        };
+        let config = MatchConfig::new(7, GameMode::Classic, 1, GridKind::Octile, &host);
+
+        let mut guest = Settings::default();
+        assert_eq!(config.apply(&mut guest), Ok((GameMode::Classic, GridKind::Octile)));
+        assert_eq!(guest.difficulty, Difficulty::Hard);
+        assert_eq!(guest.combat, CombatRules::Cutting);
+        assert_eq!(guest.teams, TeamMode::Squads);
+        assert_eq!(guest.board_size, BoardSize::Small);
+
+        let bad = MatchConfig { mode: "Zen".into(), ..config };
+        assert!(bad.apply(&mut guest).is_err());
+    }
+
+    #[test]
+    fn test_state_hasher_matches_replay_hash() {
+        let mut hasher = StateHasher::new();
+        hasher.write(b"ab");
+        assert_eq!(format!("{:016x}", hasher.finish()), replay_hash(b"ab"));
+        let mut a = StateHasher::new();
+        a.write_ivec2(ivec2(1, 2));
+        let mut b = StateHasher::new();
+        b.write_ivec2(ivec2(2, 1));
+        assert_ne!(a.finish(), b.finish());
+    }
+}
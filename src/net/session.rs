@@ -0,0 +1,148 @@
+//! 联机会话
+//!
+//! [`NetSession`] 把 [`Lockstep`] 接到一条 [`Transport`] 上：主循环每帧调用 [`NetSession::poll`]
+//! 收消息，每个 tick 用 [`NetSession::push_input`] 发出本地输入，再用 [`NetSession::next_tick`]
+//! 取出输入到齐的 tick 推进模拟。没确认的输入和状态哈希按 [`RESEND_INTERVAL`] 重发，
+//! 没有新输入时也按 [`KEEPALIVE_INTERVAL`] 发一条确认，[`DISCONNECT_TIMEOUT`] 内没收到任何消息就算断线。
+
+use std::time::{Duration, Instant};
+use super::lockstep::Lockstep;
+use super::protocol::{MatchConfig, NetError, NetInput, NetMessage, TransportKind};
+use super::transport::Transport;
+
+/// 没确认的输入的重发间隔
+pub const RESEND_INTERVAL: Duration = Duration::from_millis(30);
+/// 没有新输入时发送确认的间隔
+pub const KEEPALIVE_INTERVAL: Duration = Duration::from_millis(100);
+/// 多久没收到消息算断线
+pub const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 一局联机对局
+pub struct NetSession {
+    transport: Box<dyn Transport>,
+    lockstep: Lockstep,
+    config: MatchConfig,
+    /// 主机发过的 Welcome（UDP 下对方没收到会重发 Hello，再回一次）
+    welcome: Option<NetMessage>,
+    last_recv: Instant,
+    last_send: Instant,
+    /// 已经收到或发出 Bye
+    closed: bool,
+}
+
+impl NetSession {
+    /// `players` 名玩家中本地是第 `local` 名，按 `config` 的输入延迟和哈希间隔同步
+    pub fn new(transport: Box<dyn Transport>, config: MatchConfig, players: usize, local: usize) -> Self {
+        let now = Instant::now();
+        NetSession {
+            transport,
+            lockstep: Lockstep::new(players, local, config.input_delay, config.hash_interval),
+            config,
+            welcome: None,
+            last_recv: now,
+            last_send: now,
+            closed: false,
+        }
+    }
+
+    pub fn with_welcome(mut self, welcome: NetMessage) -> Self {
+        self.welcome = Some(welcome);
+        self
+    }
+
+    /// 对局设置
+    pub fn config(&self) -> &MatchConfig {
+        &self.config
+    }
+
+    /// 本地玩家的座位
+    pub fn local(&self) -> usize {
+        self.lockstep.local()
+    }
+
+    /// 传输类型
+    pub fn transport_kind(&self) -> TransportKind {
+        self.transport.kind()
+    }
+
+    /// 输入同步状态
+    pub fn lockstep(&self) -> &Lockstep {
+        &self.lockstep
+    }
+
+    /// 收取消息并按需重发；对方离开、超时或发现不同步时返回错误
+    pub fn poll(&mut self) -> Result<(), NetError> {
+        let now = Instant::now();
+        let messages = self.transport.poll()?;
+        if !messages.is_empty() {
+            self.last_recv = now;
+        }
+        for msg in messages {
+            match msg {
+                NetMessage::Bye => {
+                    self.closed = true;
+                    return Err(NetError::Disconnected);
+                }
+                NetMessage::Hello { .. } => {
+                    if let Some(welcome) = &self.welcome {
+                        self.transport.send(welcome)?;
+                    }
+                }
+                other => self.lockstep.receive(&other)?,
+            }
+        }
+        if now.duration_since(self.last_recv) > DISCONNECT_TIMEOUT {
+            return Err(NetError::Disconnected);
+        }
+        if let Some(desync) = self.lockstep.desync() {
+            return Err(desync.clone());
+        }
+        let interval = if self.lockstep.has_unacked() { RESEND_INTERVAL } else { KEEPALIVE_INTERVAL };
+        if now.duration_since(self.last_send) >= interval {
+            self.send_inputs()?;
+        }
+        Ok(())
+    }
+
+    /// 排入本地玩家的下一个输入并立刻发出；已经领先对方太多时返回 false
+    pub fn push_input(&mut self, input: NetInput) -> Result<bool, NetError> {
+        if !self.lockstep.push_local(input) {
+            return Ok(false);
+        }
+        self.send_inputs()?;
+        Ok(true)
+    }
+
+    /// 输入到齐时取出下一个 tick 和所有玩家的输入
+    pub fn next_tick(&mut self) -> Option<(u64, Vec<NetInput>)> {
+        self.lockstep.advance()
+    }
+
+    /// 记录 `tick` 结束时的状态哈希，到了比对的 tick 时发给对方
+    pub fn record_hash(&mut self, tick: u64, hash: u64) -> Result<(), NetError> {
+        if let Some(msg) = self.lockstep.record_hash(tick, hash) {
+            self.transport.send(&msg)?;
+        }
+        match self.lockstep.desync() {
+            Some(desync) => Err(desync.clone()),
+            None => Ok(()),
+        }
+    }
+
+    fn send_inputs(&mut self) -> Result<(), NetError> {
+        self.transport.send(&self.lockstep.outgoing())?;
+        for msg in self.lockstep.outgoing_hashes() {
+            self.transport.send(&msg)?;
+        }
+        self.last_send = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for NetSession {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self.transport.send(&NetMessage::Bye);
+        }
+    }
+}
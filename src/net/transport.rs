@@ -0,0 +1,202 @@
+//! 传输层
+//!
+//! 两种传输都是非阻塞的：[`Transport::poll`] 只取出已经到达的消息，游戏主循环每帧调用。
+//! TCP 按换行分帧；UDP 使用已连接的套接字，一个数据报一条消息，丢包由 lockstep 的重发补上。
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, UdpSocket};
+use super::protocol::{NetError, NetMessage, TransportKind};
+
+/// UDP 数据报上限
+const MAX_DATAGRAM: usize = 65_507;
+
+/// 一条到对方的连接
+pub trait Transport: Send {
+    /// 发送一条消息
+    fn send(&mut self, msg: &NetMessage) -> Result<(), NetError>;
+
+    /// 取出已经到达的消息（不阻塞）；对方关闭连接时返回 [`NetError::Disconnected`]
+    fn poll(&mut self) -> Result<Vec<NetMessage>, NetError>;
+
+    /// 传输类型
+    fn kind(&self) -> TransportKind;
+}
+
+/// TCP 传输
+pub struct TcpTransport {
+    stream: TcpStream,
+    /// 还没凑成一整行的数据
+    buf: Vec<u8>,
+}
+
+impl TcpTransport {
+    /// 包装已连接的流（切换为非阻塞，关闭 Nagle 以降低输入延迟）
+    pub fn new(stream: TcpStream) -> Result<Self, NetError> {
+        stream.set_nodelay(true)?;
+        stream.set_nonblocking(true)?;
+        Ok(TcpTransport { stream, buf: Vec::new() })
+    }
+}
+
+impl Transport for TcpTransport {
+    fn send(&mut self, msg: &NetMessage) -> Result<(), NetError> {
+        let bytes = msg.encode();
+        let mut sent = 0;
+        while sent < bytes.len() {
+            match self.stream.write(&bytes[sent..]) {
+                Ok(0) => return Err(NetError::Disconnected),
+                Ok(n) => sent += n,
+                // 发送缓冲区满：消息很小，等一下就能写完
+                Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::yield_now(),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) if is_disconnect(e.kind()) => return Err(NetError::Disconnected),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    fn poll(&mut self) -> Result<Vec<NetMessage>, NetError> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(NetError::Disconnected),
+                Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) if is_disconnect(e.kind()) => return Err(NetError::Disconnected),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let mut messages = Vec::new();
+        while let Some(end) = self.buf.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buf.drain(..=end).collect();
+            messages.push(NetMessage::decode(&line)?);
+        }
+        Ok(messages)
+    }
+
+    fn kind(&self) -> TransportKind {
+        TransportKind::Tcp
+    }
+}
+
+/// UDP 传输（套接字已经 `connect` 到对方）
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    /// 包装已连接的套接字（切换为非阻塞）
+    pub fn new(socket: UdpSocket) -> Result<Self, NetError> {
+        socket.set_nonblocking(true)?;
+        Ok(UdpTransport { socket })
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, msg: &NetMessage) -> Result<(), NetError> {
+        match self.socket.send(&msg.encode()) {
+            Ok(_) => Ok(()),
+            // 没有连接可言，发送缓冲区满时就当丢包
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) if is_disconnect(e.kind()) => Err(NetError::Disconnected),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn poll(&mut self) -> Result<Vec<NetMessage>, NetError> {
+        let mut datagram = vec![0u8; MAX_DATAGRAM];
+        let mut messages = Vec::new();
+        loop {
+            match self.socket.recv(&mut datagram) {
+                // 损坏的数据报直接丢掉，和丢包一样由重发补上
+                Ok(n) => messages.extend(NetMessage::decode(&datagram[..n]).ok()),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) if is_disconnect(e.kind()) => return Err(NetError::Disconnected),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(messages)
+    }
+
+    fn kind(&self) -> TransportKind {
+        TransportKind::Udp
+    }
+}
+
+/// 表示对方已经不在的套接字错误
+fn is_disconnect(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::ConnectionReset | ErrorKind::ConnectionAborted | ErrorKind::ConnectionRefused | ErrorKind::BrokenPipe
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::time::{Duration, Instant};
+
+    /// 轮询直到收到 `count` 条消息
+    fn receive(transport: &mut dyn Transport, count: usize) -> Vec<NetMessage> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut messages = Vec::new();
+        while messages.len() < count && Instant::now() < deadline {
+            messages.extend(transport.poll().unwrap());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        messages
+    }
+
+    fn sample() -> Vec<NetMessage> {
+        vec![
+            NetMessage::Hash { tick: 1, hash: 2 },
+            NetMessage::Hello { version: 1, name: "x".repeat(5000) },
+            NetMessage::Bye,
+        ]
+    }
+
+    #[test]
+    fn test_tcp_round_trip_and_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let mut a = TcpTransport::new(client).unwrap();
+        let mut b = TcpTransport::new(server).unwrap();
+
+        for msg in sample() {
+            a.send(&msg).unwrap();
+        }
+        assert_eq!(receive(&mut b, 3), sample());
+        assert!(b.poll().unwrap().is_empty());
+
+        drop(a);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let result = loop {
+            match b.poll() {
+                Ok(_) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(1)),
+                other => break other,
+            }
+        };
+        assert_eq!(result, Err(NetError::Disconnected));
+    }
+
+    #[test]
+    fn test_udp_round_trip() {
+        let sa = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let sb = UdpSocket::bind("127.0.0.1:0").unwrap();
+        sa.connect(sb.local_addr().unwrap()).unwrap();
+        sb.connect(sa.local_addr().unwrap()).unwrap();
+        let mut a = UdpTransport::new(sa).unwrap();
+        let mut b = UdpTransport::new(sb).unwrap();
+
+        for msg in sample() {
+            a.send(&msg).unwrap();
+        }
+        assert_eq!(receive(&mut b, 3), sample());
+        assert_eq!(b.kind(), TransportKind::Udp);
+    }
+}
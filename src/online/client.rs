@@ -0,0 +1,171 @@
+//! 在线排行榜 HTTP 客户端
+//!
+//! 基于 reqwest 阻塞客户端，只在后台线程（见 [`super::OnlineService`]）中调用，
+//! 不会卡住游戏主循环。
+
+use std::fmt;
+use std::time::Duration;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
+use crate::game::LeaderboardKey;
+use super::protocol::{RemoteEntry, ScoreSubmission, SubmitResponse};
+
+/// 单次请求超时
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 在线请求错误
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum OnlineError {
+    /// 连接失败、超时等网络错误
+    Network(String),
+    /// 服务器返回非 2xx 状态码
+    Http(u16),
+    /// 响应内容无法解析
+    Parse(String),
+}
+
+impl OnlineError {
+    /// 是否值得稍后重试（网络错误和 5xx），4xx 和解析错误重试也不会成功
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            OnlineError::Network(_) => true,
+            OnlineError::Http(status) => *status >= 500,
+            OnlineError::Parse(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for OnlineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnlineError::Network(msg) => write!(f, "network error: {}", msg),
+            OnlineError::Http(status) => write!(f, "server returned HTTP {}", status),
+            OnlineError::Parse(msg) => write!(f, "invalid response: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for OnlineError {}
+
+/// 失败重试的指数退避策略
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RetryPolicy {
+    /// 第一次失败后的等待时间
+    pub base_delay: Duration,
+    /// 等待时间上限
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            base_delay: Duration::from_secs(2),
+            max_delay: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 连续失败 `failures` 次后下一次重试前的等待时间（每次翻倍，不超过上限）
+    pub fn delay(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::ZERO;
+        }
+        let factor = 1u32 << (failures - 1).min(16);
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+/// 在线排行榜客户端
+#[derive(Clone, Debug)]
+pub struct LeaderboardClient {
+    base_url: String,
+    http: Client,
+}
+
+impl LeaderboardClient {
+    /// 创建客户端，`base_url` 形如 `http://127.0.0.1:8787`
+    pub fn new(base_url: &str) -> Result<Self, OnlineError> {
+        let http = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| OnlineError::Network(e.to_string()))?;
+        Ok(LeaderboardClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http,
+        })
+    }
+
+    /// 服务器地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 提交一条成绩
+    pub fn submit(&self, submission: &ScoreSubmission) -> Result<SubmitResponse, OnlineError> {
+        let body = serde_json::to_string(submission).map_err(|e| OnlineError::Parse(e.to_string()))?;
+        let response = self
+            .http
+            .post(format!("{}/scores", self.base_url))
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .map_err(|e| OnlineError::Network(e.to_string()))?;
+        Self::read_json(response)
+    }
+
+    /// 查询某个模式/地图/难度的前 `limit` 名
+    pub fn fetch(&self, key: &LeaderboardKey, limit: usize) -> Result<Vec<RemoteEntry>, OnlineError> {
+        let limit = limit.to_string();
+        let response = self
+            .http
+            .get(format!("{}/scores", self.base_url))
+            .query(&[
+                ("mode", key.mode.name()),
+                ("map", key.map.as_str()),
+                ("difficulty", key.difficulty.name()),
+                ("limit", limit.as_str()),
+            ])
+            .send()
+            .map_err(|e| OnlineError::Network(e.to_string()))?;
+        Self::read_json(response)
+    }
+
+    /// 检查状态码并解析 JSON 响应体
+    fn read_json<T: serde::de::DeserializeOwned>(
+        response: reqwest::blocking::Response,
+    ) -> Result<T, OnlineError> {
+        let status = response.status();
+        if !status.is_success() {
+            return Err(OnlineError::Http(status.as_u16()));
+        }
+        let text = response.text().map_err(|e| OnlineError::Network(e.to_string()))?;
+        serde_json::from_str(&text).map_err(|e| OnlineError::Parse(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_policy_backs_off_and_caps() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+        };
+        assert_eq!(policy.delay(0), Duration::ZERO);
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(10), Duration::from_secs(10));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_only_transient_errors_retry() {
+        assert!(OnlineError::Network("refused".into()).is_retryable());
+        assert!(OnlineError::Http(503).is_retryable());
+        assert!(!OnlineError::Http(400).is_retryable());
+        assert!(!OnlineError::Parse("eof".into()).is_retryable());
+    }
+}
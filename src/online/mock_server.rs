@@ -0,0 +1,391 @@
+//! 本地模拟排行榜服务器
+//!
+//! 只依赖标准库的极简 HTTP/1.1 服务器，实现和真实服务器相同的两个接口，
+//! 用于集成测试和 `cargo run --bin mock_leaderboard` 本地联调。
+//! 提交附带回放时，服务器用和游戏相同的确定性模拟（[`Replay::play`]）无界面重放这一局，
+//! 回放哈希、种子、榜单和最终分数都对得上才返回 `verified: true`。
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use crate::types::GameState;
+use crate::world::Replay;
+use super::protocol::{replay_hash, RemoteEntry, ScoreSubmission, SubmitResponse};
+
+/// 每个榜单保留的最大条数
+pub const MOCK_TABLE_SIZE: usize = 100;
+/// 请求体的最大字节数（足够放下很长一局的回放），超过时返回 413
+pub const MOCK_MAX_BODY: usize = 8 * 1024 * 1024;
+
+/// 服务器共享状态
+#[derive(Debug, Default)]
+struct MockState {
+    /// (mode, map, difficulty) -> 按分数降序的记录
+    tables: HashMap<(String, String, String), Vec<RemoteEntry>>,
+    /// 接下来这么多次提交直接返回 503，用于测试重试
+    failures: u32,
+    /// 收到的提交总数（含失败）
+    submissions: usize,
+}
+
+/// 运行在后台线程上的模拟服务器
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// 在 `addr` 上启动，端口为 0 时由系统分配
+    pub fn start(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = Arc::clone(&state);
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        // 每个连接一个线程，验证回放时其他请求照常处理；单个连接出错不影响服务器
+                        let state = Arc::clone(&state);
+                        std::thread::spawn(move || {
+                            let _ = handle_connection(stream, &state);
+                        });
+                    }
+                }
+            })
+        };
+
+        Ok(MockServer { addr, state, stop, handle: Some(handle) })
+    }
+
+    /// 让接下来 `count` 次提交返回 503
+    pub fn with_failures(self, count: u32) -> Self {
+        self.state.lock().unwrap().failures = count;
+        self
+    }
+
+    /// 监听地址
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 客户端使用的基础 URL
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// 收到的提交总数（含返回 503 的）
+    pub fn submissions(&self) -> usize {
+        self.state.lock().unwrap().submissions
+    }
+
+    /// 阻塞运行直到进程退出（命令行模式使用）
+    pub fn join(mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        // 连一次自己，唤醒阻塞在 accept 上的线程
+        let _ = TcpStream::connect(self.addr);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 处理一个连接上的一次请求
+fn handle_connection(stream: TcpStream, state: &Mutex<MockState>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(());
+    };
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+    // 长度来自客户端，先检查上限再分配
+    if content_length > MOCK_MAX_BODY {
+        return write_response(stream, 413, r#"{"error":"body too large"}"#);
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let (status, json) = route(method, path, query, &body, state);
+    write_response(stream, status, &json)
+}
+
+/// 分发请求，返回状态码和 JSON 响应体
+///
+/// 重放回放时不持有共享状态的锁，只在计数和写入榜单时加锁
+fn route(method: &str, path: &str, query: &str, body: &[u8], state: &Mutex<MockState>) -> (u16, String) {
+    match (method, path) {
+        ("POST", "/scores") => {
+            {
+                let mut state = state.lock().unwrap();
+                state.submissions += 1;
+                if state.failures > 0 {
+                    state.failures -= 1;
+                    return (503, r#"{"error":"unavailable"}"#.to_string());
+                }
+            }
+            let Ok(sub) = serde_json::from_slice::<ScoreSubmission>(body) else {
+                return (400, r#"{"error":"bad submission"}"#.to_string());
+            };
+            let verified = verify(&sub);
+            let mut state = state.lock().unwrap();
+            let table = state
+                .tables
+                .entry((sub.mode.clone(), sub.map.clone(), sub.difficulty.clone()))
+                .or_default();
+            let pos = table.iter().position(|e| sub.score > e.score).unwrap_or(table.len());
+            let rank = if pos < MOCK_TABLE_SIZE {
+                table.insert(pos, RemoteEntry { verified, ..RemoteEntry::from(&sub) });
+                table.truncate(MOCK_TABLE_SIZE);
+                Some(pos)
+            } else {
+                None
+            };
+            let response = SubmitResponse { rank, verified };
+            (200, serde_json::to_string(&response).unwrap_or_default())
+        }
+        ("GET", "/scores") => {
+            let params: HashMap<String, String> = query
+                .split('&')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(k, v)| (percent_decode(k), percent_decode(v)))
+                .collect();
+            let param = |name: &str| params.get(name).cloned().unwrap_or_default();
+            let limit = params.get("limit").and_then(|l| l.parse().ok()).unwrap_or(10);
+            let entries: Vec<RemoteEntry> = state
+                .lock()
+                .unwrap()
+                .tables
+                .get(&(param("mode"), param("map"), param("difficulty")))
+                .map(|table| table.iter().take(limit).cloned().collect())
+                .unwrap_or_default();
+            (200, serde_json::to_string(&entries).unwrap_or_default())
+        }
+        _ => (404, r#"{"error":"not found"}"#.to_string()),
+    }
+}
+
+/// 重放提交附带的回放，复核回放哈希、种子、榜单和分数（回放必须打到这一局结束）
+fn verify(sub: &ScoreSubmission) -> bool {
+    let (Some(hash), Some(data)) = (&sub.replay_hash, &sub.replay) else {
+        return false;
+    };
+    if *hash != replay_hash(data.as_bytes()) {
+        return false;
+    }
+    let Ok(replay) = Replay::from_json(data) else {
+        return false;
+    };
+    let config = &replay.config;
+    if config.seed != sub.seed || config.mode != sub.mode || config.difficulty != sub.difficulty {
+        return false;
+    }
+    let Ok(world) = replay.play() else {
+        return false;
+    };
+    world.maps[world.map_index].name == sub.map
+        && matches!(world.state, GameState::GameOver | GameState::Victory)
+        && world.score() == sub.score
+}
+
+/// 写出响应并关闭连接
+fn write_response(mut stream: TcpStream, status: u16, body: &str) -> io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        413 => "Payload Too Large",
+        _ => "Service Unavailable",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// 解码查询参数中的 `%XX` 和 `+`
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|h| std::str::from_utf8(h).ok())
+                    .and_then(|h| u8::from_str_radix(h, 16).ok());
+                match hex {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use crate::game::{LeaderboardKey, ScoreEntry};
+    use crate::online::{LeaderboardClient, OnlineError, RetryPolicy, SubmissionQueue};
+    use crate::types::{Difficulty, GameMode};
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("Portal+Hub"), "Portal Hub");
+        assert_eq!(percent_decode("a%20b%2Fc"), "a b/c");
+        assert_eq!(percent_decode("%E8%9B%87"), "蛇");
+        assert_eq!(percent_decode("100%"), "100%");
+    }
+
+    #[test]
+    fn test_submit_and_fetch_over_loopback() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let client = LeaderboardClient::new(&server.url()).unwrap();
+        let key = LeaderboardKey::new(GameMode::Arena, "Portal Hub", Difficulty::Normal);
+
+        for (name, score) in [("Ann", 50), ("Bob", 80), ("Cy", 60)] {
+            let entry = ScoreEntry::new(name, score, 5, 10.0, 1);
+            client.submit(&ScoreSubmission::from_entry(&key, &entry)).unwrap();
+        }
+        let entry = ScoreEntry::new("Dee", 70, 5, 10.0, 1);
+        let response = client.submit(&ScoreSubmission::from_entry(&key, &entry)).unwrap();
+        assert_eq!(response, SubmitResponse { rank: Some(1), verified: false });
+
+        let top = client.fetch(&key, 3).unwrap();
+        let names: Vec<&str> = top.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["Bob", "Dee", "Cy"]);
+
+        let other = LeaderboardKey::new(GameMode::Classic, "Portal Hub", Difficulty::Normal);
+        assert!(client.fetch(&other, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_oversized_body_is_rejected() {
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        write!(stream, "POST /scores HTTP/1.1\r\nContent-Length: {}\r\n\r\n", u64::MAX).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 413 "), "{response}");
+        assert_eq!(server.submissions(), 0);
+    }
+
+    #[test]
+    fn test_replays_verify_the_score() {
+        // 一直往右走，撞墙结束
+        let mut world = crate::world::GameWorld::headless();
+        world.mode = GameMode::Classic;
+        world.reset();
+        while world.state == GameState::Playing {
+            world.advance(1.0 / 60.0, &crate::audio::Silence);
+        }
+        let replay = world.finished_replay.take().expect("finished solo game");
+        let dir = std::env::temp_dir().join(format!("snake2d_mock_replays_{}", std::process::id()));
+        let path = replay.save(&dir).unwrap();
+
+        let server = MockServer::start("127.0.0.1:0").unwrap();
+        let client = LeaderboardClient::new(&server.url()).unwrap();
+        let key = world.leaderboard_key();
+        let mut entry = ScoreEntry::new("Ann", world.score(), world.players[0].snake.len(), world.game_time, world.seed);
+        entry.replay = Some(path.display().to_string());
+        let sub = ScoreSubmission::from_entry(&key, &entry);
+        assert!(client.submit(&sub).unwrap().verified);
+
+        // 改过分数、回放被篡改或没有回放都不通过
+        let inflated = ScoreSubmission { score: sub.score + 10, ..sub.clone() };
+        assert!(!client.submit(&inflated).unwrap().verified);
+        let tampered = ScoreSubmission { replay: sub.replay.as_ref().map(|r| r.replacen("\"dt\"", "\"dt\" ", 1)), ..sub.clone() };
+        assert!(!client.submit(&tampered).unwrap().verified);
+        let bare = ScoreSubmission { replay: None, replay_hash: None, ..sub.clone() };
+        assert!(!client.submit(&bare).unwrap().verified);
+
+        let top = client.fetch(&key, 10).unwrap();
+        assert_eq!(top.iter().filter(|e| e.verified).count(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_queue_retries_through_server_failures() {
+        let server = MockServer::start("127.0.0.1:0").unwrap().with_failures(2);
+        let client = LeaderboardClient::new(&server.url()).unwrap();
+        let key = LeaderboardKey::new(GameMode::Classic, "Classic", Difficulty::Easy);
+        let mut queue = SubmissionQueue::new(RetryPolicy {
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+        });
+        queue.push(ScoreSubmission::from_entry(&key, &ScoreEntry::new("Ann", 40, 5, 10.0, 3)));
+
+        let mut now = Instant::now();
+        let report = queue.flush(now, &client);
+        assert_eq!(report.deferred, Some(OnlineError::Http(503)));
+        now += Duration::from_millis(1);
+        assert!(queue.flush(now, &client).deferred.is_some());
+        now += Duration::from_millis(2);
+        let report = queue.flush(now, &client);
+        assert_eq!(report.sent.len(), 1);
+        assert!(queue.is_empty());
+        assert_eq!(server.submissions(), 3);
+        assert_eq!(client.fetch(&key, 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_unreachable_server_is_retryable() {
+        let addr = {
+            let server = MockServer::start("127.0.0.1:0").unwrap();
+            server.addr()
+        };
+        let client = LeaderboardClient::new(&format!("http://{}", addr)).unwrap();
+        let key = LeaderboardKey::new(GameMode::Arena, "Classic", Difficulty::Normal);
+        let err = client.fetch(&key, 10).unwrap_err();
+        assert!(err.is_retryable(), "{:?}", err);
+    }
+}
@@ -0,0 +1,25 @@
+//! 在线排行榜模块
+//!
+//! 通过 HTTP/JSON 提交和查询在线排行榜。成绩附带种子和回放哈希，服务器可以用
+//! 确定性模拟复核；网络不可用时成绩进入离线队列，按退避策略自动重试。
+//!
+//! ```text
+//! online/
+//! ├── protocol.rs     # 请求/响应 JSON 结构、回放哈希
+//! ├── client.rs       # 阻塞 HTTP 客户端、错误类型、重试策略
+//! ├── queue.rs        # 离线提交队列（持久化到文件）
+//! ├── service.rs      # 后台线程服务，主循环每帧轮询结果
+//! └── mock_server.rs  # 本地模拟服务器（测试和联调用）
+//! ```
+
+pub mod protocol;
+pub mod client;
+pub mod queue;
+pub mod service;
+pub mod mock_server;
+
+pub use protocol::*;
+pub use client::*;
+pub use queue::*;
+pub use service::*;
+pub use mock_server::*;
@@ -0,0 +1,138 @@
+//! 在线排行榜协议
+//!
+//! 客户端和服务器之间交换的 JSON 数据结构：
+//!
+//! | 请求 | 路径 | 请求体 | 响应体 |
+//! |------|------|--------|--------|
+//! | 提交成绩 | `POST /scores` | [`ScoreSubmission`] | [`SubmitResponse`] |
+//! | 查询榜单 | `GET /scores?mode=&map=&difficulty=&limit=` | - | `[RemoteEntry]` |
+
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::game::{LeaderboardKey, ScoreEntry};
+
+/// 提交到服务器的一条成绩
+///
+/// `seed`、`replay_hash` 和 `replay` 让服务器可以用确定性模拟重放这一局来复核分数；
+/// 没有回放文件时后两者为 None，服务器只能按未验证成绩处理。
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ScoreSubmission {
+    pub mode: String,
+    pub map: String,
+    pub difficulty: String,
+    pub name: String,
+    pub score: u32,
+    pub length: usize,
+    pub duration: f32,
+    pub date: u64,
+    pub seed: u64,
+    pub replay_hash: Option<String>,
+    /// 回放文件的内容（[`Replay`](crate::world::Replay) 的 JSON），`replay_hash` 按这些字节计算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<String>,
+}
+
+impl ScoreSubmission {
+    /// 由本地排行榜记录创建，有回放文件时附上回放和回放哈希
+    pub fn from_entry(key: &LeaderboardKey, entry: &ScoreEntry) -> Self {
+        let replay = entry.replay.as_deref().and_then(|path| std::fs::read_to_string(Path::new(path)).ok());
+        ScoreSubmission {
+            mode: key.mode.name().to_string(),
+            map: key.map.clone(),
+            difficulty: key.difficulty.name().to_string(),
+            name: entry.name.clone(),
+            score: entry.score,
+            length: entry.length,
+            duration: entry.duration,
+            date: entry.date,
+            seed: entry.seed,
+            replay_hash: replay.as_deref().map(|data| replay_hash(data.as_bytes())),
+            replay,
+        }
+    }
+}
+
+/// 提交结果
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SubmitResponse {
+    /// 在服务器榜单中的名次（从 0 开始），未进入榜单时为 None
+    pub rank: Option<usize>,
+    /// 服务器是否已通过重放复核该成绩
+    pub verified: bool,
+}
+
+/// 服务器榜单中的一条记录
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub score: u32,
+    pub length: usize,
+    pub duration: f32,
+    pub date: u64,
+    pub seed: u64,
+    pub verified: bool,
+}
+
+impl From<&ScoreSubmission> for RemoteEntry {
+    fn from(sub: &ScoreSubmission) -> Self {
+        RemoteEntry {
+            name: sub.name.clone(),
+            score: sub.score,
+            length: sub.length,
+            duration: sub.duration,
+            date: sub.date,
+            seed: sub.seed,
+            verified: false,
+        }
+    }
+}
+
+impl From<&RemoteEntry> for ScoreEntry {
+    fn from(remote: &RemoteEntry) -> Self {
+        ScoreEntry {
+            name: remote.name.clone(),
+            score: remote.score,
+            length: remote.length,
+            duration: remote.duration,
+            date: remote.date,
+            seed: remote.seed,
+            replay: None,
+        }
+    }
+}
+
+/// 回放数据的哈希（FNV-1a 64 位，16 位十六进制）
+pub fn replay_hash(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Difficulty, GameMode};
+
+    #[test]
+    fn test_submission_json_round_trip() {
+        let key = LeaderboardKey::new(GameMode::Arena, "Portal Hub", Difficulty::Hard);
+        let entry = ScoreEntry::new("Ann", 120, 30, 95.5, 42);
+        let sub = ScoreSubmission::from_entry(&key, &entry);
+        assert_eq!(sub.mode, "Arena");
+        assert_eq!(sub.replay_hash, None);
+
+        let json = serde_json::to_string(&sub).unwrap();
+        let back: ScoreSubmission = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, sub);
+    }
+
+    #[test]
+    fn test_replay_hash_is_stable() {
+        assert_eq!(replay_hash(b""), "cbf29ce484222325");
+        assert_eq!(replay_hash(b"a"), "af63dc4c8601ec8c");
+        assert_ne!(replay_hash(b"ab"), replay_hash(b"ba"));
+    }
+}
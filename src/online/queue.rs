@@ -0,0 +1,203 @@
+//! 离线提交队列
+//!
+//! 提交失败（断网、服务器 5xx）的成绩留在队列里，按 [`RetryPolicy`] 退避后重发；
+//! 队列以每行一条 JSON 的格式持久化，下次启动游戏继续发送。
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::game::leaderboard::temp_path;
+use super::client::{LeaderboardClient, OnlineError, RetryPolicy};
+use super::protocol::{ScoreSubmission, SubmitResponse};
+
+/// 一次发送队列的结果
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct FlushReport {
+    /// 发送成功的提交及服务器响应
+    pub sent: Vec<(ScoreSubmission, SubmitResponse)>,
+    /// 被服务器拒绝（不可重试）而丢弃的提交
+    pub dropped: Vec<(ScoreSubmission, OnlineError)>,
+    /// 本次遇到的可重试错误，队列保留剩余提交
+    pub deferred: Option<OnlineError>,
+}
+
+/// 待发送成绩队列
+#[derive(Clone, Debug, Default)]
+pub struct SubmissionQueue {
+    pending: Vec<ScoreSubmission>,
+    policy: RetryPolicy,
+    failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl SubmissionQueue {
+    pub fn new(policy: RetryPolicy) -> Self {
+        SubmissionQueue { policy, ..Default::default() }
+    }
+
+    /// 待发送的提交
+    pub fn pending(&self) -> &[ScoreSubmission] {
+        &self.pending
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// 连续失败次数
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// 加入队尾，并取消退避等待让它尽快发送
+    pub fn push(&mut self, submission: ScoreSubmission) {
+        self.pending.push(submission);
+        self.next_attempt = None;
+    }
+
+    /// 退避时间是否已过
+    pub fn ready(&self, now: Instant) -> bool {
+        !self.pending.is_empty() && self.next_attempt.is_none_or(|t| now >= t)
+    }
+
+    /// 按顺序发送，遇到可重试错误即停止并进入退避
+    pub fn flush_with<F>(&mut self, now: Instant, mut send: F) -> FlushReport
+    where
+        F: FnMut(&ScoreSubmission) -> Result<SubmitResponse, OnlineError>,
+    {
+        let mut report = FlushReport::default();
+        while let Some(submission) = self.pending.first() {
+            match send(submission) {
+                Ok(response) => {
+                    report.sent.push((self.pending.remove(0), response));
+                }
+                Err(e) if e.is_retryable() => {
+                    self.failures = self.failures.saturating_add(1);
+                    self.next_attempt = Some(now + self.policy.delay(self.failures));
+                    report.deferred = Some(e);
+                    return report;
+                }
+                Err(e) => {
+                    report.dropped.push((self.pending.remove(0), e));
+                }
+            }
+        }
+        self.failures = 0;
+        self.next_attempt = None;
+        report
+    }
+
+    /// 用客户端发送队列
+    pub fn flush(&mut self, now: Instant, client: &LeaderboardClient) -> FlushReport {
+        self.flush_with(now, |submission| client.submit(submission))
+    }
+
+    /// 序列化为每行一条 JSON
+    pub fn to_text(&self) -> String {
+        self.pending
+            .iter()
+            .filter_map(|s| serde_json::to_string(s).ok())
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// 从文本恢复，无法解析的行被跳过
+    pub fn parse(text: &str, policy: RetryPolicy) -> Self {
+        let mut queue = Self::new(policy);
+        queue.pending = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        queue
+    }
+
+    /// 从文件加载，文件不存在时返回空队列
+    pub fn load(path: &Path, policy: RetryPolicy) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(Self::parse(&text, policy)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new(policy)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 原子写入文件
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let tmp = temp_path(path);
+        {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(self.to_text().as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, path)
+    }
+}
+
+/// 默认离线队列文件路径（可用环境变量 `SNAKE2D_ONLINE_QUEUE` 覆盖）
+pub fn default_queue_path() -> PathBuf {
+    std::env::var_os("SNAKE2D_ONLINE_QUEUE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("snake2d_online_queue.txt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn submission(name: &str, score: u32) -> ScoreSubmission {
+        ScoreSubmission {
+            mode: "Arena".into(),
+            map: "Classic".into(),
+            difficulty: "Normal".into(),
+            name: name.into(),
+            score,
+            length: 10,
+            duration: 30.0,
+            date: 0,
+            seed: 7,
+            replay_hash: None,
+            replay: None,
+        }
+    }
+
+    #[test]
+    fn test_flush_backs_off_then_drains() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        };
+        let mut queue = SubmissionQueue::new(policy);
+        queue.push(submission("A", 10));
+        queue.push(submission("B", 20));
+        queue.push(submission("C", 30));
+        let t0 = Instant::now();
+
+        let report = queue.flush_with(t0, |_| Err(OnlineError::Network("offline".into())));
+        assert_eq!(report.deferred, Some(OnlineError::Network("offline".into())));
+        assert_eq!(queue.pending().len(), 3);
+        assert!(!queue.ready(t0));
+        assert!(queue.ready(t0 + Duration::from_secs(1)));
+
+        // 一条被拒绝、其余成功
+        let ok = SubmitResponse { rank: Some(0), verified: false };
+        let report = queue.flush_with(t0 + Duration::from_secs(1), |s| {
+            if s.name == "B" { Err(OnlineError::Http(400)) } else { Ok(ok.clone()) }
+        });
+        assert_eq!(report.sent.len(), 2);
+        assert_eq!(report.dropped.len(), 1);
+        assert!(queue.is_empty());
+        assert_eq!(queue.failures(), 0);
+    }
+
+    #[test]
+    fn test_queue_text_round_trip() {
+        let mut queue = SubmissionQueue::default();
+        queue.push(submission("A", 10));
+        queue.push(submission("B\tC", 20));
+        let text = queue.to_text() + "not json\n";
+        let restored = SubmissionQueue::parse(&text, RetryPolicy::default());
+        assert_eq!(restored.pending(), queue.pending());
+    }
+}
@@ -0,0 +1,205 @@
+//! 在线服务后台线程
+//!
+//! 游戏主循环通过 [`OnlineService`] 投递提交和查询任务，网络请求在后台线程里
+//! 阻塞执行，结果以 [`OnlineEvent`] 返回，主循环每帧用 [`OnlineService::poll`] 取出。
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use crate::game::LeaderboardKey;
+use super::client::{LeaderboardClient, OnlineError, RetryPolicy};
+use super::protocol::{RemoteEntry, ScoreSubmission, SubmitResponse};
+use super::queue::{default_queue_path, SubmissionQueue};
+
+/// 后台线程检查离线队列的间隔
+const QUEUE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 后台线程的任务
+enum Job {
+    Submit(ScoreSubmission),
+    Fetch(LeaderboardKey, usize),
+    Shutdown,
+}
+
+/// 后台线程返回的结果
+#[derive(Clone, PartialEq, Debug)]
+pub enum OnlineEvent {
+    /// 成绩已被服务器接收
+    Submitted(ScoreSubmission, SubmitResponse),
+    /// 暂时发不出去，`pending` 条成绩留在离线队列中等待重试
+    Queued { pending: usize, error: OnlineError },
+    /// 服务器拒绝了这条成绩，已从队列丢弃
+    Rejected(ScoreSubmission, OnlineError),
+    /// 查询完成
+    Fetched(LeaderboardKey, Vec<RemoteEntry>),
+    /// 查询失败
+    FetchFailed(LeaderboardKey, OnlineError),
+}
+
+/// 在线排行榜服务
+#[derive(Debug)]
+pub struct OnlineService {
+    base_url: String,
+    jobs: Sender<Job>,
+    events: Receiver<OnlineEvent>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl OnlineService {
+    /// 启动后台线程，离线队列保存在 `queue_path`
+    pub fn start(base_url: &str, queue_path: PathBuf, policy: RetryPolicy) -> Result<Self, OnlineError> {
+        let client = LeaderboardClient::new(base_url)?;
+        let queue = match SubmissionQueue::load(&queue_path, policy) {
+            Ok(queue) => queue,
+            Err(e) => {
+                eprintln!("Failed to load online queue {}: {}", queue_path.display(), e);
+                SubmissionQueue::new(policy)
+            }
+        };
+        let (job_tx, job_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let handle = std::thread::spawn(move || worker(client, queue, queue_path, job_rx, event_tx));
+        Ok(OnlineService {
+            base_url: base_url.to_string(),
+            jobs: job_tx,
+            events: event_rx,
+            handle: Some(handle),
+        })
+    }
+
+    /// 设置了环境变量 `SNAKE2D_ONLINE_URL` 时启动，否则不启用在线功能
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("SNAKE2D_ONLINE_URL").ok().filter(|u| !u.trim().is_empty())?;
+        match Self::start(url.trim(), default_queue_path(), RetryPolicy::default()) {
+            Ok(service) => Some(service),
+            Err(e) => {
+                eprintln!("Online leaderboard disabled: {}", e);
+                None
+            }
+        }
+    }
+
+    /// 服务器地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 提交成绩（先进入离线队列，再由后台线程发送）
+    pub fn submit(&self, submission: ScoreSubmission) {
+        let _ = self.jobs.send(Job::Submit(submission));
+    }
+
+    /// 查询榜单前 `limit` 名
+    pub fn fetch(&self, key: LeaderboardKey, limit: usize) {
+        let _ = self.jobs.send(Job::Fetch(key, limit));
+    }
+
+    /// 取出已完成的结果（不阻塞）
+    pub fn poll(&self) -> Vec<OnlineEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// 阻塞等待下一个结果，超时返回 None
+    pub fn wait(&self, timeout: Duration) -> Option<OnlineEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+}
+
+impl Drop for OnlineService {
+    fn drop(&mut self) {
+        let _ = self.jobs.send(Job::Shutdown);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// 后台线程主循环
+fn worker(
+    client: LeaderboardClient,
+    mut queue: SubmissionQueue,
+    queue_path: PathBuf,
+    jobs: Receiver<Job>,
+    events: Sender<OnlineEvent>,
+) {
+    loop {
+        match jobs.recv_timeout(QUEUE_POLL_INTERVAL) {
+            Ok(Job::Submit(submission)) => {
+                queue.push(submission);
+                save_queue(&queue, &queue_path);
+            }
+            Ok(Job::Fetch(key, limit)) => {
+                let event = match client.fetch(&key, limit) {
+                    Ok(entries) => OnlineEvent::Fetched(key, entries),
+                    Err(e) => OnlineEvent::FetchFailed(key, e),
+                };
+                let _ = events.send(event);
+            }
+            Ok(Job::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+
+        let now = Instant::now();
+        if !queue.ready(now) {
+            continue;
+        }
+        let report = queue.flush(now, &client);
+        save_queue(&queue, &queue_path);
+        for (submission, response) in report.sent {
+            let _ = events.send(OnlineEvent::Submitted(submission, response));
+        }
+        for (submission, error) in report.dropped {
+            let _ = events.send(OnlineEvent::Rejected(submission, error));
+        }
+        if let Some(error) = report.deferred {
+            let _ = events.send(OnlineEvent::Queued { pending: queue.pending().len(), error });
+        }
+    }
+}
+
+/// 保存离线队列，失败只打印日志
+fn save_queue(queue: &SubmissionQueue, path: &Path) {
+    if let Err(e) = queue.save(path) {
+        eprintln!("Failed to save online queue {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::ScoreEntry;
+    use crate::online::MockServer;
+    use crate::types::{Difficulty, GameMode};
+
+    #[test]
+    fn test_service_submits_in_background_and_persists_queue() {
+        let server = MockServer::start("127.0.0.1:0").unwrap().with_failures(1);
+        let dir = std::env::temp_dir().join(format!("snake2d_online_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let queue_path = dir.join("queue.txt");
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+        };
+        let service = OnlineService::start(&server.url(), queue_path.clone(), policy).unwrap();
+        let key = LeaderboardKey::new(GameMode::Arena, "Classic", Difficulty::Hard);
+        service.submit(ScoreSubmission::from_entry(&key, &ScoreEntry::new("Ann", 90, 9, 20.0, 5)));
+
+        let timeout = Duration::from_secs(5);
+        assert!(matches!(service.wait(timeout), Some(OnlineEvent::Queued { pending: 1, .. })));
+        assert!(matches!(service.wait(timeout), Some(OnlineEvent::Submitted(_, _))));
+        assert_eq!(std::fs::read_to_string(&queue_path).unwrap(), "");
+
+        service.fetch(key.clone(), 5);
+        match service.wait(timeout) {
+            Some(OnlineEvent::Fetched(k, entries)) => {
+                assert_eq!(k, key);
+                assert_eq!(entries[0].name, "Ann");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        drop(service);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,20 @@
+//! 本地参考机器人
+//!
+//! 用法：`cargo run --bin reference_bot [地址]`，默认监听 `127.0.0.1:8000`，
+//! 然后用 `cargo run --bin snake2d -- --bot http://127.0.0.1:8000` 启动游戏（`--bot` 可以给多次）。
+
+use rtest::bots::{ReferenceBot, DEFAULT_BOT_ADDR};
+
+fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_BOT_ADDR.to_string());
+    match ReferenceBot::start(addr.as_str()) {
+        Ok(bot) => {
+            println!("Reference bot listening on {}", bot.url());
+            bot.join();
+        }
+        Err(e) => {
+            eprintln!("Failed to bind {}: {}", addr, e);
+            std::process::exit(1);
+        }
+    }
+}
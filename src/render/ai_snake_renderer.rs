@@ -1,9 +1,11 @@
 //! AI 蛇渲染模块
 
 use macroquad::prelude::*;
-use crate::constants::CELL;
+use crate::constants::{CELL, SANDWORM_TRANSFORM_INTERVAL};
 use crate::game::{AISnake, DroppedFood};
 use crate::types::SandwormPhase;
+use super::fruit_renderer::{sandworm_body_color, sandworm_head_color};
+use super::sandworm_renderer::{draw_sandworm_head, draw_sandworm_mode, draw_sandworm_segment};
 
 /// 绘制所有 AI 蛇
 pub fn draw_ai_snakes(snakes: &[AISnake], game_time: f32, _blend: f32) {
@@ -14,8 +16,16 @@ pub fn draw_ai_snakes(snakes: &[AISnake], game_time: f32, _blend: f32) {
 
 /// 绘制单条 AI 蛇
 fn draw_ai_snake(snake: &AISnake, game_time: f32) {
+    if snake.boss.is_some() {
+        draw_boss(snake, game_time);
+        return;
+    }
+    if snake.buff_state.sandworm_phase != SandwormPhase::None {
+        draw_ai_snake_sandworm_effect(snake, game_time);
+        return;
+    }
+
     let body = &snake.body;
-    let prev_body = &snake.prev_body;
     let color = snake.color;
     
     // 计算 AI 蛇自己的插值比例
@@ -36,17 +46,22 @@ fn draw_ai_snake(snake: &AISnake, game_time: f32) {
     
     // 减速效果（紫色光晕）
     let is_slowed = snake.buff_state.slow_active;
+
+    // 等级条纹：等级越高条纹越密
+    let marking = snake.progress.marking_color(color);
+    let stripe_every = (7 - snake.progress.level.min(5)) as usize;
+    let is_elite = snake.progress.is_elite();
     
     for (i, &pos) in body.iter().enumerate() {
         // 插值位置 - 使用 AI 蛇自己的 blend
-        let prev_pos = prev_body.get(i).copied().unwrap_or(pos);
+        let prev_pos = snake.prev_segment(i);
         
-        // 检测是否发生穿墙（位置差距超过2格）
+        // 检测是否发生穿墙或传送（位置不相邻）
         let dx = (pos.x - prev_pos.x).abs();
         let dy = (pos.y - prev_pos.y).abs();
-        let is_wrap = dx > 2 || dy > 2;
+        let is_wrap = dx.max(dy) > 1;
         
-        // 穿墙时不插值，直接使用当前位置
+        // 穿墙/传送时不插值，直接使用当前位置
         let (lerp_x, lerp_y) = if is_wrap {
             (pos.x as f32, pos.y as f32)
         } else {
@@ -83,6 +98,12 @@ fn draw_ai_snake(snake: &AISnake, game_time: f32) {
         if i == 0 {
             // 蛇头 - 稍大一点
             draw_rectangle(x - 1.0, y - 1.0, CELL + 2.0, CELL + 2.0, segment_color);
+
+            // 性格蛇的蛇头外框（颜色与性格一致）
+            if let Some(tint) = snake.brain.tint() {
+                let pulse = 0.6 + (game_time * 4.0).sin() * 0.2;
+                draw_rectangle_lines(x - 3.0, y - 3.0, CELL + 6.0, CELL + 6.0, 2.0, Color { a: pulse, ..tint });
+            }
             
             // 眼睛
             let eye_color = if is_frozen { SKYBLUE } else { WHITE };
@@ -118,6 +139,33 @@ fn draw_ai_snake(snake: &AISnake, game_time: f32) {
         } else {
             // 身体段
             draw_rectangle(x + 1.0, y + 1.0, CELL - 2.0, CELL - 2.0, segment_color);
+
+            if let Some(mark) = marking
+                && i % stripe_every == 0
+            {
+                draw_rectangle(x + 1.0, y + CELL / 2.0 - 2.0, CELL - 2.0, 4.0, mark);
+            }
+        }
+
+        // 精英：蛇头上方的金色王冠
+        if i == 0 && is_elite {
+            let glow = Color { a: 0.7 + (game_time * 6.0).sin() * 0.3, ..GOLD };
+            let base = y - 4.0;
+            for k in 0..3 {
+                let cx = x + 4.0 + k as f32 * (CELL - 8.0) / 2.0;
+                draw_triangle(vec2(cx - 3.0, base), vec2(cx + 3.0, base), vec2(cx, base - 6.0), glow);
+            }
+            draw_rectangle(x + 1.0, base, CELL - 2.0, 2.0, glow);
+        }
+        
+        // 宠物蛇：蛇头上方的小爱心
+        if i == 0 && snake.pet {
+            let bob = (game_time * 4.0).sin() * 1.5;
+            let heart = Color::new(0.95, 0.45, 0.65, 1.0);
+            let (cx, cy) = (x + CELL / 2.0, y - 5.0 + bob);
+            draw_circle(cx - 2.0, cy, 2.5, heart);
+            draw_circle(cx + 2.0, cy, 2.5, heart);
+            draw_triangle(vec2(cx - 4.5, cy + 0.5), vec2(cx + 4.5, cy + 0.5), vec2(cx, cy + 5.0), heart);
         }
         
         // 眩晕效果（头顶星星）
@@ -129,6 +177,40 @@ fn draw_ai_snake(snake: &AISnake, game_time: f32) {
     }
 }
 
+/// 绘制 Boss（沙虫外观）
+///
+/// 钻地时只露出翻动的沙堆；蛇头（弱点）外围有闪烁的红框，僵直时蛇头发白
+fn draw_boss(snake: &AISnake, game_time: f32) {
+    if snake.burrowed() {
+        for (i, &pos) in snake.body.iter().enumerate().step_by(2) {
+            let (x, y) = (pos.x as f32 * CELL, pos.y as f32 * CELL);
+            let rise = ((game_time * 6.0 + i as f32).sin() * 0.5 + 0.5) * 3.0;
+            let mound = Color { a: 0.45, ..sandworm_body_color() };
+            draw_sandworm_segment(x + 4.0, y + 4.0 - rise, CELL - 8.0, mound, pos.x, pos.y, game_time);
+        }
+        return;
+    }
+
+    for (i, &pos) in snake.body.iter().enumerate().rev() {
+        let (x, y) = (pos.x as f32 * CELL, pos.y as f32 * CELL);
+        if i == 0 {
+            draw_sandworm_head(x, y, snake.dir);
+        } else {
+            draw_sandworm_segment(x, y, CELL, sandworm_body_color(), pos.x, pos.y, game_time);
+        }
+    }
+
+    let head = snake.head();
+    let (x, y) = (head.x as f32 * CELL, head.y as f32 * CELL);
+    if snake.buff_state.frozen {
+        let flash = (game_time * 10.0).sin() * 0.3 + 0.4;
+        draw_rectangle(x - 2.0, y - 2.0, CELL + 4.0, CELL + 4.0, Color::new(1.0, 1.0, 1.0, flash));
+    } else {
+        let pulse = (game_time * 5.0).sin() * 0.3 + 0.6;
+        draw_rectangle_lines(x - 4.0, y - 4.0, CELL + 8.0, CELL + 8.0, 2.0, Color::new(1.0, 0.2, 0.1, pulse));
+    }
+}
+
 /// 绘制掉落的食物
 pub fn draw_dropped_foods(foods: &[DroppedFood], game_time: f32) {
     for food in foods {
@@ -152,21 +234,53 @@ pub fn draw_dropped_foods(foods: &[DroppedFood], game_time: f32) {
     }
 }
 
-/// 绘制 AI 蛇被沙虫吞噬的效果
-pub fn draw_ai_snake_sandworm_effect(snake: &AISnake, phase: SandwormPhase, game_time: f32) {
-    if phase == SandwormPhase::None {
-        return;
-    }
-    
-    // 沙虫模式下 AI 蛇变暗并抖动
-    for (i, &pos) in snake.body.iter().enumerate() {
-        let shake = (game_time * 20.0 + i as f32).sin() * 2.0;
-        let x = pos.x as f32 * CELL + shake;
-        let y = pos.y as f32 * CELL;
-        
-        let alpha = 0.3 + (game_time * 5.0).sin() * 0.1;
-        let color = Color::new(snake.color.r * 0.5, snake.color.g * 0.5, snake.color.b * 0.5, alpha);
-        
-        draw_rectangle(x + 2.0, y + 2.0, CELL - 4.0, CELL - 4.0, color);
+/// 绘制 AI 蛇的沙虫变身
+///
+/// 闪烁和变色阶段从蛇自己的颜色开始过渡，横冲阶段与玩家沙虫外观相同
+pub fn draw_ai_snake_sandworm_effect(snake: &AISnake, game_time: f32) {
+    let buff = &snake.buff_state;
+    let base = snake.color;
+    match buff.sandworm_phase {
+        SandwormPhase::Flashing => {
+            // 原地闪烁并抖动
+            let flash = ((game_time * 12.0).sin() * 0.5 + 0.5).powi(2);
+            for (i, &pos) in snake.body.iter().enumerate() {
+                let shake = (game_time * 20.0 + i as f32).sin() * 2.0 * flash;
+                let color = Color::new(
+                    base.r + (1.0 - base.r) * flash,
+                    base.g + (1.0 - base.g) * flash,
+                    base.b + (1.0 - base.b) * flash,
+                    1.0,
+                );
+                draw_rectangle(pos.x as f32 * CELL + shake, pos.y as f32 * CELL, CELL, CELL, color);
+            }
+        }
+        SandwormPhase::Transforming => {
+            // 从头到尾逐节变成沙色
+            for (i, &pos) in snake.body.iter().enumerate() {
+                let (x, y) = (pos.x as f32 * CELL, pos.y as f32 * CELL);
+                if i < buff.sandworm_transform_index {
+                    if i == 0 {
+                        draw_sandworm_head(x, y, snake.dir);
+                    } else {
+                        draw_sandworm_segment(x, y, CELL, sandworm_body_color(), pos.x, pos.y, game_time);
+                    }
+                } else {
+                    let target = if i == 0 { sandworm_head_color() } else { sandworm_body_color() };
+                    let t = if i == buff.sandworm_transform_index { buff.sandworm_phase_timer / SANDWORM_TRANSFORM_INTERVAL } else { 0.0 };
+                    let color = Color::new(
+                        base.r + (target.r - base.r) * t,
+                        base.g + (target.g - base.g) * t,
+                        base.b + (target.b - base.b) * t,
+                        1.0,
+                    );
+                    draw_rectangle(x, y, CELL, CELL, color);
+                }
+            }
+        }
+        _ => {
+            let body: Vec<IVec2> = snake.body.iter().copied().collect();
+            draw_sandworm_mode(&body, buff, game_time);
+        }
     }
 }
@@ -57,19 +57,24 @@ pub fn draw_blood_stains(blood_stains: &[BloodStain], game_time: f32) {
 }
 
 /// 绘制传送门
+///
+/// - 永久传送门不闪烁
+/// - 单向传送门的出口端画成空心框
+/// - 定向传送门在出口端画出方向箭头
 pub fn draw_portals(portals: &[Portal], game_time: f32) {
     for portal in portals {
         let elapsed = game_time - portal.spawn_time;
-        let fade = if elapsed > portal.lifetime - 3.0 {
+        let fade = if !portal.is_permanent() && elapsed > portal.lifetime - 3.0 {
             let blink = (game_time * 8.0).sin() * 0.5 + 0.5;
             blink * (portal.lifetime - elapsed) / 3.0
         } else {
             1.0
         };
 
-        for pos in [portal.pos_a, portal.pos_b] {
+        for (end, pos) in [portal.pos_a, portal.pos_b].into_iter().enumerate() {
             let x = pos.x as f32 * CELL;
             let y = pos.y as f32 * CELL;
+            let exit_only = portal.one_way && end == 1;
 
             let pulse = (game_time * 4.0).sin() * 0.15 + 0.85;
 
@@ -80,7 +85,11 @@ pub fn draw_portals(portals: &[Portal], game_time: f32) {
                 b: portal.color.b,
                 a: 0.6 * fade,
             };
-            draw_rectangle(x - 2.0, y - 2.0, CELL + 4.0, CELL + 4.0, outer_color);
+            if exit_only {
+                draw_rectangle_lines(x - 2.0, y - 2.0, CELL + 4.0, CELL + 4.0, 3.0, outer_color);
+            } else {
+                draw_rectangle(x - 2.0, y - 2.0, CELL + 4.0, CELL + 4.0, outer_color);
+            }
 
             // 内圈（脉动）
             let inner_size = CELL * pulse;
@@ -89,7 +98,7 @@ pub fn draw_portals(portals: &[Portal], game_time: f32) {
                 r: portal.color.r * 0.5 + 0.5,
                 g: portal.color.g * 0.5 + 0.5,
                 b: portal.color.b * 0.5 + 0.5,
-                a: 0.8 * fade,
+                a: if exit_only { 0.3 } else { 0.8 } * fade,
             };
             draw_rectangle(x + inner_offset, y + inner_offset, inner_size, inner_size, inner_color);
 
@@ -98,6 +107,12 @@ pub fn draw_portals(portals: &[Portal], game_time: f32) {
             let cx = x + CELL / 2.0 - center_size / 2.0;
             let cy = y + CELL / 2.0 - center_size / 2.0;
             draw_rectangle(cx, cy, center_size, center_size, Color { a: fade, ..WHITE });
+
+            // 出门方向箭头（单向传送门只画在出口端）
+            let shows_exit = end == 1 || !portal.one_way;
+            if let Some(dir) = portal.exit_dir.filter(|_| shows_exit) {
+                draw_portal_arrow(x + CELL / 2.0, y + CELL / 2.0, dir, Color { a: fade, ..WHITE });
+            }
         }
 
         // 连接线
@@ -110,6 +125,20 @@ pub fn draw_portals(portals: &[Portal], game_time: f32) {
     }
 }
 
+/// 绘制传送门出口方向箭头
+fn draw_portal_arrow(cx: f32, cy: f32, dir: IVec2, color: Color) {
+    let d = vec2(dir.x as f32, dir.y as f32).normalize_or_zero();
+    let side = vec2(-d.y, d.x);
+    let tip = vec2(cx, cy) + d * (CELL * 0.6);
+    let base = vec2(cx, cy) + d * (CELL * 0.2);
+    draw_triangle(
+        tip,
+        base + side * (CELL * 0.25),
+        base - side * (CELL * 0.25),
+        color,
+    );
+}
+
 /// 绘制残影
 pub fn draw_afterimages(afterimages: &[Afterimage], game_time: f32) {
     const AFTERIMAGE_LIFETIME: f32 = 0.3;
@@ -160,6 +189,13 @@ pub fn draw_ghost_effect(snake_body: &[IVec2], time: f32) {
     }
 }
 
+/// 绘制高对比度描边（辅助功能，方格模式）
+pub fn draw_contrast_outlines(cells: &[IVec2], color: Color) {
+    for pos in cells {
+        draw_rectangle_lines(pos.x as f32 * CELL, pos.y as f32 * CELL, CELL, CELL, 3.0, color);
+    }
+}
+
 /// 生成冰冻粒子
 pub fn spawn_freeze_particles(particles: &mut Vec<Particle>, snake_body: &[IVec2], rng: &mut impl ::rand::Rng) {
     let ice_colors = [
@@ -194,6 +230,29 @@ pub fn spawn_freeze_particles(particles: &mut Vec<Particle>, snake_body: &[IVec2
     }
 }
 
+/// 生成宠物蛋孵化的蛋壳粒子
+pub fn spawn_egg_hatch_particles(particles: &mut Vec<Particle>, cell: IVec2, rng: &mut impl ::rand::Rng) {
+    let center = vec2(cell.x as f32 * CELL + CELL / 2.0, cell.y as f32 * CELL + CELL / 2.0);
+    for _ in 0..15 {
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(30.0..90.0);
+        let lifetime = rng.gen_range(0.5..0.9);
+        let color = if rng.gen_bool(0.5) {
+            Color { r: 1.0, g: 0.85, b: 0.9, a: 1.0 } // 蛋壳
+        } else {
+            Color { r: 0.95, g: 0.45, b: 0.65, a: 1.0 } // 斑点
+        };
+        particles.push(Particle {
+            pos: center,
+            vel: vec2(angle.cos() * speed, angle.sin() * speed),
+            color,
+            lifetime,
+            max_lifetime: lifetime,
+            size: rng.gen_range(3.0..6.0),
+        });
+    }
+}
+
 /// 生成幸运方块粒子
 pub fn spawn_lucky_particles(particles: &mut Vec<Particle>, pos: Vec2, rng: &mut impl ::rand::Rng) {
     let colors = [
@@ -3,7 +3,7 @@
 //! 提供果实的绘制功能
 
 use macroquad::prelude::*;
-use crate::constants::CELL;
+use crate::constants::{CELL, NO_FOOD};
 use crate::types::Fruit;
 use crate::fruits::FruitRegistry;
 
@@ -29,10 +29,16 @@ pub fn draw_fruits(fruits: &[Fruit], registry: &FruitRegistry, game_time: f32) {
     }
 }
 
-/// 绘制普通食物（苹果样式）
+/// 绘制普通食物（苹果样式，收起的食物不画）
 pub fn draw_food(pos: IVec2) {
-    let x = pos.x as f32 * CELL;
-    let y = pos.y as f32 * CELL;
+    if pos == NO_FOOD {
+        return;
+    }
+    draw_food_at(pos.x as f32 * CELL, pos.y as f32 * CELL);
+}
+
+/// 在像素位置绘制普通食物（`x`, `y` 为 CELL×CELL 区域左上角）
+pub fn draw_food_at(x: f32, y: f32) {
     let cx = x + CELL / 2.0;
     let _cy = y + CELL / 2.0 + 1.0; // 稍微下移，给茎留空间
     
@@ -0,0 +1,92 @@
+//! 动态障碍物渲染模块
+//!
+//! 绘制旋转横杆、轨道滑块和定时墙（含关闭前的预警动画）
+
+use macroquad::prelude::*;
+use crate::constants::CELL;
+use crate::game::HazardManager;
+use crate::types::{Hazard, HazardKind, WallPhase};
+
+/// 绘制所有动态障碍物
+pub fn draw_hazards(hazards: &HazardManager, game_time: f32) {
+    for hazard in &hazards.hazards {
+        match &hazard.kind {
+            HazardKind::RotatingBar { pivot, .. } => draw_rotating_bar(hazard, *pivot, game_time),
+            HazardKind::SlidingBlock { rail, .. } => draw_sliding_block(hazard, rail, game_time),
+            HazardKind::TimedWall { cells, .. } => draw_timed_wall(hazard, cells, game_time),
+        }
+    }
+}
+
+/// 绘制旋转横杆
+fn draw_rotating_bar(hazard: &Hazard, pivot: IVec2, game_time: f32) {
+    for cell in hazard.cells_at(game_time) {
+        let x = cell.x as f32 * CELL;
+        let y = cell.y as f32 * CELL;
+        draw_rectangle(x + 1.0, y + 1.0, CELL - 2.0, CELL - 2.0, hazard.color);
+        // 金属条纹
+        let stripe = Color::new(0.2, 0.1, 0.05, 0.6);
+        draw_line(x + 3.0, y + CELL - 3.0, x + CELL - 3.0, y + 3.0, 2.0, stripe);
+    }
+
+    // 支点铆钉
+    let cx = pivot.x as f32 * CELL + CELL / 2.0;
+    let cy = pivot.y as f32 * CELL + CELL / 2.0;
+    draw_circle(cx, cy, CELL * 0.25, DARKGRAY);
+    draw_circle(cx, cy, CELL * 0.12, LIGHTGRAY);
+}
+
+/// 绘制轨道滑块
+fn draw_sliding_block(hazard: &Hazard, rail: &[IVec2], game_time: f32) {
+    // 轨道
+    let rail_color = Color::new(0.4, 0.4, 0.45, 0.5);
+    for cell in rail {
+        let x = cell.x as f32 * CELL;
+        let y = cell.y as f32 * CELL;
+        draw_rectangle(x, y + CELL / 2.0 - 1.5, CELL, 3.0, rail_color);
+    }
+
+    // 滑块
+    for cell in hazard.cells_at(game_time) {
+        let x = cell.x as f32 * CELL;
+        let y = cell.y as f32 * CELL;
+        draw_rectangle(x, y, CELL, CELL, hazard.color);
+        draw_rectangle_lines(x + 2.0, y + 2.0, CELL - 4.0, CELL - 4.0, 2.0, DARKGRAY);
+    }
+}
+
+/// 绘制定时墙
+///
+/// - Open: 地面上淡淡的虚线轮廓
+/// - Warning: 红色闪烁边框，频率和亮度随进度增加
+/// - Closed: 实心砖墙
+fn draw_timed_wall(hazard: &Hazard, cells: &[IVec2], game_time: f32) {
+    let phase = hazard.wall_phase(game_time);
+
+    for cell in cells {
+        let x = cell.x as f32 * CELL;
+        let y = cell.y as f32 * CELL;
+
+        match phase {
+            WallPhase::Open => {
+                let outline = Color { a: 0.25, ..hazard.color };
+                draw_rectangle_lines(x + 3.0, y + 3.0, CELL - 6.0, CELL - 6.0, 1.0, outline);
+            }
+            WallPhase::Warning(progress) => {
+                let freq = 4.0 + progress * 12.0;
+                let flash = (game_time * freq).sin() * 0.5 + 0.5;
+                let warn = Color::new(1.0, 0.15, 0.1, (0.3 + progress * 0.5) * flash);
+                draw_rectangle(x, y, CELL, CELL, Color { a: progress * 0.4, ..hazard.color });
+                draw_rectangle_lines(x, y, CELL, CELL, 2.0, warn);
+            }
+            WallPhase::Closed => {
+                draw_rectangle(x, y, CELL, CELL, hazard.color);
+                // 砖缝
+                let mortar = Color::new(0.35, 0.35, 0.35, 1.0);
+                draw_line(x, y + CELL / 2.0, x + CELL, y + CELL / 2.0, 1.0, mortar);
+                let offset = if cell.y % 2 == 0 { CELL / 2.0 } else { CELL / 4.0 };
+                draw_line(x + offset, y, x + offset, y + CELL / 2.0, 1.0, mortar);
+            }
+        }
+    }
+}
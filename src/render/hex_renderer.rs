@@ -0,0 +1,253 @@
+//! 六边形棋盘渲染模块
+//!
+//! 六边形模式下替代方格渲染：棋盘、蛇、AI 蛇、食物、果实、传送门和障碍物。
+//! 平顶六边形，奇数列下沉半格，整个棋盘缩放到与方格模式相同的窗口内。
+
+use macroquad::prelude::*;
+use crate::constants::{GRID_W, GRID_H, CELL, NO_FOOD};
+use crate::fruits::FruitRegistry;
+use crate::game::{AISnake, DroppedFood, HazardManager, Snake};
+use crate::types::{
+    BoardDims, BuffState, DamagePhase, Fruit, Grid, GridKind, Portal, SandwormPhase, Topology,
+};
+use super::{FENCE_COLOR, draw_food_at, snake_segment_color, sandworm_body_color, sandworm_head_color};
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// 六边形外接圆半径（像素）
+///
+/// 按窗口高度计算：GRID_H 行加上奇数列下沉的半行
+pub fn hex_radius() -> f32 {
+    GRID_H as f32 * CELL / ((GRID_H as f32 + 0.5) * SQRT_3)
+}
+
+/// 六边形格子中心的像素坐标
+pub fn hex_center(pos: IVec2) -> Vec2 {
+    let r = hex_radius();
+    let h = SQRT_3 * r;
+    // 水平居中
+    let board_w = 1.5 * r * (GRID_W - 1) as f32 + 2.0 * r;
+    let offset_x = (GRID_W as f32 * CELL - board_w) / 2.0;
+
+    let x = offset_x + r + 1.5 * r * pos.x as f32;
+    let y = h / 2.0 + h * (pos.y as f32 + 0.5 * (pos.x & 1) as f32);
+    vec2(x, y)
+}
+
+/// 绘制实心六边形格子
+pub fn draw_hex_cell(pos: IVec2, scale: f32, color: Color) {
+    let c = hex_center(pos);
+    draw_poly(c.x, c.y, 6, hex_radius() * scale, 0.0, color);
+}
+
+/// 绘制六边形棋盘（`board` 内的背景网格 + 墙边界高亮）
+pub fn draw_hex_board(topology: Topology, board: BoardDims) {
+    let grid_color = Color { r: 0.6, g: 0.6, b: 0.6, a: 0.2 };
+    let wall_color = Color::new(0.9, 0.9, 0.9, 0.8);
+    let r = hex_radius();
+    let (first, last) = (board.origin, board.end() - IVec2::ONE);
+
+    for x in first.x..=last.x {
+        for y in first.y..=last.y {
+            let c = hex_center(ivec2(x, y));
+            let on_wall = (!topology.wraps_x() && (x == first.x || x == last.x))
+                || (!topology.wraps_y() && (y == first.y || y == last.y));
+            let (color, thickness) = if on_wall { (wall_color, 1.5) } else { (grid_color, 1.0) };
+            draw_poly_lines(c.x, c.y, 6, r, 0.0, thickness, color);
+        }
+    }
+}
+
+/// 用围栏盖住 `board` 之外的六边形格子（完整棋盘时不绘制）
+pub fn draw_hex_fence(board: BoardDims) {
+    for x in 0..GRID_W {
+        for y in 0..GRID_H {
+            if !board.contains(ivec2(x, y)) {
+                draw_hex_cell(ivec2(x, y), 1.0, FENCE_COLOR);
+            }
+        }
+    }
+}
+
+/// 两帧之间的插值中心
+///
+/// 相邻格平滑移动；穿越边界或传送门时直接出现在新位置
+fn lerp_center(grid: GridKind, from: IVec2, to: IVec2, blend: f32) -> Vec2 {
+    if from == to || grid.direction_between(from, to).is_none() {
+        return hex_center(to);
+    }
+    hex_center(from).lerp(hex_center(to), blend)
+}
+
+/// 绘制玩家蛇
+pub fn draw_hex_snake(
+    snake: &Snake,
+    buff: &BuffState,
+    damage_phase: DamagePhase,
+    game_time: f32,
+    blend: f32,
+) {
+    let r = hex_radius();
+    let blend = if buff.frozen { 1.0 } else { blend };
+
+    // 从尾到头绘制，蛇头在最上层
+    for (i, &seg) in snake.body.iter().enumerate().rev() {
+        let c = lerp_center(snake.grid, snake.prev_segment(i), seg, blend);
+        let color = snake_segment_color(snake, i, buff, damage_phase, game_time);
+        let scale = if i == 0 { 0.95 } else { 0.85 };
+        draw_poly(c.x, c.y, 6, r * scale, 0.0, color);
+    }
+
+    // 蛇头眼睛：沿移动方向
+    if let Some(&head) = snake.body.front() {
+        let c = lerp_center(snake.grid, snake.prev_segment(0), head, blend);
+        draw_hex_eyes(c, hex_center(snake.grid.neighbor(head, snake.dir)) - hex_center(head));
+    }
+}
+
+/// 绘制所有 AI 蛇
+pub fn draw_hex_ai_snakes(snakes: &[AISnake]) {
+    let r = hex_radius();
+    for snake in snakes {
+        let tick = snake.get_tick();
+        let blend = if tick > 0.0 && !snake.buff_state.frozen {
+            (snake.move_accumulator / tick).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        // 沙虫变身：已经变色的节数
+        let sand = match snake.buff_state.sandworm_phase {
+            SandwormPhase::Transforming => snake.buff_state.sandworm_transform_index,
+            SandwormPhase::Rampaging => snake.body.len(),
+            _ => 0,
+        };
+
+        for (i, &seg) in snake.body.iter().enumerate().rev() {
+            let c = lerp_center(snake.grid, snake.prev_segment(i), seg, blend);
+            let color = match (i < sand, i == 0) {
+                (true, true) => sandworm_head_color(),
+                (true, false) => sandworm_body_color(),
+                (false, true) => snake.color,
+                (false, false) => Color { a: 0.85, ..snake.color },
+            };
+            draw_poly(c.x, c.y, 6, r * if i == 0 { 0.95 } else { 0.8 }, 0.0, color);
+
+            // 等级条纹（每隔几节一个小圆点）
+            let stripe_every = (7 - snake.progress.level.min(5)) as usize;
+            if let Some(mark) = snake.progress.marking_color(snake.color)
+                && i > 0
+                && i % stripe_every == 0
+            {
+                draw_circle(c.x, c.y, r * 0.35, mark);
+            }
+        }
+
+        if let Some(&head) = snake.body.front() {
+            let c = lerp_center(snake.grid, snake.prev_segment(0), head, blend);
+            // 精英：金色外圈
+            if snake.progress.is_elite() {
+                draw_poly_lines(c.x, c.y, 6, r * 1.15, 0.0, 2.0, GOLD);
+            }
+            draw_hex_eyes(c, hex_center(snake.grid.neighbor(head, snake.dir)) - hex_center(head));
+        }
+    }
+}
+
+/// 绘制眼睛（`facing` 为朝向的像素向量）
+fn draw_hex_eyes(center: Vec2, facing: Vec2) {
+    let forward = facing.normalize_or_zero();
+    let side = vec2(-forward.y, forward.x);
+    let r = hex_radius();
+    for s in [-1.0, 1.0] {
+        let eye = center + forward * r * 0.35 + side * r * 0.35 * s;
+        draw_circle(eye.x, eye.y, r * 0.18, WHITE);
+        draw_circle(eye.x + forward.x, eye.y + forward.y, r * 0.09, BLACK);
+    }
+}
+
+/// 绘制普通食物（收起的食物不画）
+pub fn draw_hex_food(pos: IVec2) {
+    if pos == NO_FOOD {
+        return;
+    }
+    let c = hex_center(pos);
+    draw_food_at(c.x - CELL / 2.0, c.y - CELL / 2.0);
+}
+
+/// 绘制 AI 蛇死亡掉落的食物
+pub fn draw_hex_dropped_foods(foods: &[DroppedFood], game_time: f32) {
+    for food in foods {
+        let remaining = food.lifetime - (game_time - food.spawn_time);
+        let alpha = if remaining < 3.0 {
+            0.5 + (game_time * 8.0).sin() * 0.3
+        } else {
+            1.0
+        };
+        draw_hex_cell(food.pos, 0.5, Color::new(1.0, 0.85, 0.0, alpha));
+    }
+}
+
+/// 绘制所有果实（复用各果实自己的渲染，居中到六边形格子）
+pub fn draw_hex_fruits(fruits: &[Fruit], registry: &FruitRegistry, game_time: f32) {
+    for fruit in fruits {
+        let c = hex_center(fruit.pos);
+        let (x, y) = (c.x - CELL / 2.0, c.y - CELL / 2.0);
+
+        if let Some(behavior) = registry.get(fruit.type_id) {
+            behavior.render(x, y, game_time);
+        } else {
+            draw_hex_cell(fruit.pos, 0.8, RED);
+        }
+
+        // 有时限的果实显示剩余时间弧线
+        if fruit.lifetime > 0.0 {
+            let progress = fruit.remaining_ratio(game_time);
+            let color = if progress > 0.3 { YELLOW } else { RED };
+            draw_arc(c.x, c.y, 24, hex_radius() * 1.05, -90.0, 2.0, 360.0 * progress, color);
+        }
+    }
+}
+
+/// 绘制传送门
+pub fn draw_hex_portals(portals: &[Portal], game_time: f32) {
+    let pulse = (game_time * 4.0).sin() * 0.15 + 0.85;
+    for portal in portals {
+        for (end, pos) in [portal.pos_a, portal.pos_b].into_iter().enumerate() {
+            let exit_only = portal.one_way && end == 1;
+            let c = hex_center(pos);
+            let outer = Color { a: 0.6, ..portal.color };
+            if exit_only {
+                draw_poly_lines(c.x, c.y, 6, hex_radius(), 0.0, 2.5, outer);
+            } else {
+                draw_poly(c.x, c.y, 6, hex_radius(), 0.0, outer);
+            }
+            let inner = Color::new(
+                portal.color.r * 0.5 + 0.5,
+                portal.color.g * 0.5 + 0.5,
+                portal.color.b * 0.5 + 0.5,
+                if exit_only { 0.3 } else { 0.8 },
+            );
+            draw_poly(c.x, c.y, 6, hex_radius() * 0.6 * pulse, 0.0, inner);
+        }
+        let (a, b) = (hex_center(portal.pos_a), hex_center(portal.pos_b));
+        draw_line(a.x, a.y, b.x, b.y, 1.0, Color { a: 0.3, ..portal.color });
+    }
+}
+
+/// 绘制动态障碍物（当前为实体的格子）
+pub fn draw_hex_hazards(hazards: &HazardManager, game_time: f32) {
+    for hazard in &hazards.hazards {
+        for cell in hazard.cells_at(game_time) {
+            draw_hex_cell(cell, 0.95, hazard.color);
+        }
+    }
+}
+
+/// 绘制沙虫模式（六边形简化版：沙色格子 + 深色头部）
+pub fn draw_hex_sandworm(snake: &Snake) {
+    for (i, &seg) in snake.body.iter().enumerate().rev() {
+        let color = if i == 0 { sandworm_head_color() } else { sandworm_body_color() };
+        draw_hex_cell(seg, 0.9, color);
+    }
+}
@@ -4,7 +4,8 @@
 
 use macroquad::prelude::*;
 use crate::constants::{GRID_W, GRID_H, CELL};
-use crate::types::{ComboState, BuffState};
+use crate::game::{BossPhase, Party, Player, Team};
+use crate::types::{BoardDims, ComboState, BuffState, GridKind, PartyMode, Topology};
 
 /// 绘制 HUD（分数、最高分、Combo、Buff状态）
 pub fn draw_hud(score: u32, high_score: u32, combo: &ComboState, buff: &BuffState) {
@@ -44,7 +45,114 @@ pub fn draw_hud(score: u32, high_score: u32, combo: &ComboState, buff: &BuffStat
     }
 
     // 操作提示
-    draw_text("[Arrows] Move  [Space] Pause  [Enter/R] Restart  [W] Wrap", 8.0, 40.0, 20.0, LIGHTGRAY);
+    draw_text("[Arrows] Move  [Space] Pause  [R] Restart  [T] Topology  [G] Grid  [M] Map", 8.0, 40.0, 18.0, LIGHTGRAY);
+}
+
+/// 绘制本地多人 HUD：顶部每名玩家一栏（名字、分数、长度、Buff、倒下状态），下面一行是规则和计时
+pub fn draw_party_hud(players: &[Player], party: &Party) {
+    let w = GRID_W as f32 * CELL / players.len() as f32;
+    for (i, player) in players.iter().enumerate() {
+        let x = 8.0 + i as f32 * w;
+        let color = if player.downed { GRAY } else { player.snake.head_color };
+        draw_text(&format!("{} {}", player.name(), player.score), x, 18.0, 24.0, color);
+        let status = if player.downed {
+            "DOWN".to_string()
+        } else {
+            let buffs = [
+                (player.buff_state.shield_active, "S"),
+                (player.buff_state.speed_active, ">"),
+                (player.buff_state.ghost_active, "G"),
+                (player.buff_state.frozen, "*"),
+            ];
+            let tags: String = buffs.iter().filter(|(on, _)| *on).map(|(_, tag)| *tag).collect();
+            format!("len {} {}", player.snake.len(), tags)
+        };
+        draw_text(&status, x, 36.0, 18.0, color);
+    }
+
+    let line = match party.mode {
+        PartyMode::Timed => format!("Timed  {:.0}s", party.time_left.ceil()),
+        PartyMode::Coop => format!("Co-op  Team {}", party.score(players)),
+        mode => mode.name().to_string(),
+    };
+    draw_text(&line, 8.0, 54.0, 18.0, LIGHTGRAY);
+}
+
+/// 绘制联机状态（右下角：传输、本地座位、tick，或机器人的延迟；等待对方输入或机器人超时过时变黄）
+pub fn draw_net_status(line: &str, waiting: bool) {
+    let w = GRID_W as f32 * CELL;
+    let h = GRID_H as f32 * CELL;
+    let color = if waiting { YELLOW } else { Color::new(0.8, 0.8, 0.8, 0.7) };
+    let size = measure_text(line, None, 18, 1.0);
+    draw_text(line, w - size.width - 8.0, h - 8.0, 18.0, color);
+}
+
+/// 绘制各队存活人数（右上角，用队伍颜色；本地多人时下移到玩家栏下面）
+pub fn draw_team_roster(roster: &[(Team, usize)], y: f32) {
+    let mut x = GRID_W as f32 * CELL - 8.0;
+    for &(team, count) in roster.iter().rev() {
+        let text = format!("{} {}", team.name(), count);
+        x -= measure_text(&text, None, 18, 1.0).width;
+        draw_text(&text, x, y, 18.0, team.color());
+        x -= 16.0;
+    }
+}
+
+/// 绘制 Boss 血条（底部居中，标出当前阶段；僵直时闪烁）
+pub fn draw_boss_health_bar(phase: BossPhase, health: usize, max_health: usize, stunned: bool, time: f32) {
+    let w = 240.0;
+    let h = 10.0;
+    let x = (GRID_W as f32 * CELL - w) / 2.0;
+    let y = GRID_H as f32 * CELL - 40.0;
+    let ratio = (health as f32 / max_health.max(1) as f32).clamp(0.0, 1.0);
+    let fill = if stunned && (time * 10.0).sin() > 0.0 {
+        WHITE
+    } else {
+        Color::new(0.85, 0.55, 0.15, 1.0) // 沙色
+    };
+
+    draw_text(&format!("BOSS  -  {}", phase.name()), x, y - 4.0, 18.0, Color::new(1.0, 0.8, 0.4, 1.0));
+    draw_rectangle(x, y, w, h, Color::new(0.2, 0.1, 0.05, 0.8));
+    draw_rectangle(x, y, w * ratio, h, fill);
+    draw_rectangle_lines(x, y, w, h, 1.0, Color::new(1.0, 0.8, 0.4, 0.8));
+}
+
+/// 绘制地图信息（左下角地图名 + 拓扑 + 几何）和 `board` 的边界样式
+///
+/// - 墙边界：实线
+/// - 普通环绕：不绘制
+/// - 镜像环绕：紫色虚线
+///
+/// 六边形棋盘自己绘制墙边界，这里只显示文字
+pub fn draw_map_info(map_name: &str, topology: Topology, grid: GridKind, board: BoardDims) {
+    let h = GRID_H as f32 * CELL;
+    let (x0, y0) = (board.origin.x as f32 * CELL, board.origin.y as f32 * CELL);
+    let (x1, y1) = (board.end().x as f32 * CELL, board.end().y as f32 * CELL);
+    let wall = Color::new(0.8, 0.8, 0.8, 0.6);
+    let mirror = Color::new(0.7, 0.3, 1.0, 0.7);
+
+    let info = format!("{}  |  {}  |  {}", map_name, topology.name(), grid.name());
+    draw_text(&info, 8.0, h - 8.0, 18.0, Color::new(0.8, 0.8, 0.8, 0.7));
+
+    if grid == GridKind::Hex {
+        return;
+    }
+
+    if !topology.wraps_x() {
+        draw_line(x0 + 1.0, y0, x0 + 1.0, y1, 2.0, wall);
+        draw_line(x1 - 1.0, y0, x1 - 1.0, y1, 2.0, wall);
+    } else if topology.mirrors_x() {
+        let mut y = y0;
+        while y < y1 {
+            draw_line(x0 + 1.0, y, x0 + 1.0, y + CELL * 0.5, 2.0, mirror);
+            draw_line(x1 - 1.0, y, x1 - 1.0, y + CELL * 0.5, 2.0, mirror);
+            y += CELL;
+        }
+    }
+    if !topology.wraps_y() {
+        draw_line(x0, y0 + 1.0, x1, y0 + 1.0, 2.0, wall);
+        draw_line(x0, y1 - 1.0, x1, y1 - 1.0, 2.0, wall);
+    }
 }
 
 /// 绘制覆盖层（暂停、游戏结束）
@@ -63,3 +171,24 @@ pub fn draw_overlay(title: &str, subtitle: &str) {
     let s = measure_text(subtitle, None, 24, 1.0);
     draw_text(subtitle, (w - s.width) * 0.5, h * 0.5 + 30.0, 24.0, LIGHTGRAY);
 }
+
+/// 绘制完美通关覆盖层（金色标题 + 最终得分）
+pub fn draw_victory_overlay(score: u32, bonus: u32) {
+    let w = GRID_W as f32 * CELL;
+    let h = GRID_H as f32 * CELL;
+    let gold = Color::new(1.0, 0.84, 0.0, 1.0);
+
+    draw_rectangle(0.0, 0.0, w, h, Color { r: 0.1, g: 0.08, b: 0.0, a: 0.6 });
+
+    let title = "Perfect Game!";
+    let t = measure_text(title, None, 48, 1.0);
+    draw_text(title, (w - t.width) * 0.5, h * 0.5 - 30.0, 48.0, gold);
+
+    let info = format!("Board filled  +{}  Score: {}", bonus, score);
+    let i = measure_text(&info, None, 24, 1.0);
+    draw_text(&info, (w - i.width) * 0.5, h * 0.5 + 10.0, 24.0, WHITE);
+
+    let hint = "Enter/R to restart, Esc for menu";
+    let s = measure_text(hint, None, 24, 1.0);
+    draw_text(hint, (w - s.width) * 0.5, h * 0.5 + 40.0, 24.0, LIGHTGRAY);
+}
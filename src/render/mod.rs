@@ -9,8 +9,11 @@
 //! ├── snake_renderer.rs     # 蛇渲染（含各种Buff视觉效果）
 //! ├── fruit_renderer.rs     # 果实渲染
 //! ├── effect_renderer.rs    # 粒子、血迹、传送门、残影
-//! ├── hud_renderer.rs       # 分数、Combo、Buff状态
-//! └── sandworm_renderer.rs  # 沙虫变身动画
+//! ├── hud_renderer.rs       # 分数、Combo、Buff状态、本地多人玩家栏、联机状态
+//! ├── sandworm_renderer.rs  # 沙虫变身动画
+//! ├── hazard_renderer.rs    # 动态障碍物
+//! ├── hex_renderer.rs       # 六边形棋盘模式
+//! └── spectator_renderer.rs # 观战端（按快照绘制、名字标签、数据面板）
 //! ```
 //!
 //! ## 设计原则
@@ -28,21 +31,24 @@
 //! 2. draw_border_and_grid() # 边框和网格线
 //! 3. draw_blood_stains()    # 血迹（最底层）
 //! 4. draw_portals()         # 传送门
+//!    draw_hazards()         # 动态障碍物
+//!    draw_fence()           # 缩小棋盘外的围栏
 //! 5. draw_food()            # 食物
 //! 6. draw_fruits()          # 果实
 //! 7. draw_afterimages()     # 残影
 //! 8. draw_shield_effect()   # 护盾光环
 //! 9. draw_snake()           # 蛇（主体）
 //! 10. draw_particles()      # 粒子（最顶层）
-//! 11. draw_overlay()        # 覆盖层（暂停/结束）
+//! 11. draw_overlay()        # 覆盖层（暂停/结束/胜利）
 //! 12. draw_hud()            # HUD（始终最顶）
 //! ```
 //!
 //! ## 坐标系统
 //!
-//! - 网格坐标: `IVec2`，范围 (0,0) 到 (GRID_W-1, GRID_H-1)
+//! - 网格坐标: `IVec2`，范围 (0,0) 到 (GRID_W-1, GRID_H-1)；缩小的棋盘是其中的 `BoardDims` 区域
 //! - 像素坐标: `f32`，网格坐标 * CELL
 //! - 原点在左上角，Y轴向下
+//! - 六边形模式: 像素坐标由 `hex_center()` 计算
 
 pub mod snake_renderer;
 pub mod fruit_renderer;
@@ -50,6 +56,9 @@ pub mod effect_renderer;
 pub mod hud_renderer;
 pub mod sandworm_renderer;
 pub mod ai_snake_renderer;
+pub mod hazard_renderer;
+pub mod hex_renderer;
+pub mod spectator_renderer;
 
 pub use snake_renderer::*;
 pub use fruit_renderer::*;
@@ -57,28 +66,52 @@ pub use effect_renderer::*;
 pub use hud_renderer::*;
 pub use sandworm_renderer::*;
 pub use ai_snake_renderer::*;
+pub use hazard_renderer::*;
+pub use hex_renderer::*;
+pub use spectator_renderer::*;
 
 use macroquad::prelude::*;
 use crate::constants::{GRID_W, GRID_H, CELL};
+use crate::types::BoardDims;
+
+/// 围栏（缩小的棋盘之外）的颜色
+pub const FENCE_COLOR: Color = Color { r: 0.12, g: 0.12, b: 0.14, a: 1.0 };
 
 /// 绘制背景
 pub fn draw_background() {
     clear_background(DARKGRAY);
 }
 
-/// 绘制边框和网格
-pub fn draw_border_and_grid() {
-    let w = GRID_W as f32 * CELL;
-    let h = GRID_H as f32 * CELL;
-    draw_rectangle_lines(0.0, 0.0, w, h, 2.0, WHITE);
+/// 绘制棋盘边框和网格（只画 `board` 范围内）
+pub fn draw_border_and_grid(board: BoardDims) {
+    let x0 = board.origin.x as f32 * CELL;
+    let y0 = board.origin.y as f32 * CELL;
+    let w = board.width as f32 * CELL;
+    let h = board.height as f32 * CELL;
+    draw_rectangle_lines(x0, y0, w, h, 2.0, WHITE);
 
     let gc = Color { r: 0.6, g: 0.6, b: 0.6, a: 0.2 };
-    for x in 1..GRID_W {
-        let xf = x as f32 * CELL;
-        draw_line(xf, 0.0, xf, h, 1.0, gc);
+    for x in 1..board.width {
+        let xf = x0 + x as f32 * CELL;
+        draw_line(xf, y0, xf, y0 + h, 1.0, gc);
     }
-    for y in 1..GRID_H {
-        let yf = y as f32 * CELL;
-        draw_line(0.0, yf, w, yf, 1.0, gc);
+    for y in 1..board.height {
+        let yf = y0 + y as f32 * CELL;
+        draw_line(x0, yf, x0 + w, yf, 1.0, gc);
     }
 }
+
+/// 用围栏盖住 `board` 之外的区域（完整棋盘时不绘制）
+///
+/// 在地面层之后绘制，伸到棋盘外的地图障碍物也被盖住
+pub fn draw_fence(board: BoardDims) {
+    let (w, h) = (GRID_W as f32 * CELL, GRID_H as f32 * CELL);
+    let x0 = board.origin.x as f32 * CELL;
+    let y0 = board.origin.y as f32 * CELL;
+    let x1 = board.end().x as f32 * CELL;
+    let y1 = board.end().y as f32 * CELL;
+    draw_rectangle(0.0, 0.0, w, y0, FENCE_COLOR);
+    draw_rectangle(0.0, y1, w, h - y1, FENCE_COLOR);
+    draw_rectangle(0.0, y0, x0, y1 - y0, FENCE_COLOR);
+    draw_rectangle(x1, y0, w - x1, y1 - y0, FENCE_COLOR);
+}
@@ -332,6 +332,14 @@ pub fn draw_sandworm_mode(snake: &[IVec2], buff: &BuffState, time: f32) {
             // 坍缩动画：使用专用渲染
             draw_sandworm_consuming(buff);
         }
+        SandwormPhase::Rampaging => {
+            // 横冲阶段（AI 沙虫）：完整的沙虫留在棋盘上
+            let dir = match snake {
+                [head, next, ..] => *head - *next,
+                _ => buff.sandworm_original_dir,
+            };
+            draw_sandworm_exiting(snake, dir, time);
+        }
         SandwormPhase::None => {}
     }
 }
@@ -3,62 +3,69 @@
 //! 提供蛇的各种视觉效果渲染
 
 use macroquad::prelude::*;
-use crate::constants::{CELL, GRID_W, GRID_H};
-use crate::types::{BuffState, DamagePhase};
+use crate::constants::CELL;
+use crate::types::{BuffState, DamagePhase, Topology};
 use crate::game::Snake;
 
+/// 计算蛇身某一节的颜色（蛇自己的颜色，受伤闪烁和各种 Buff 着色）
+pub fn snake_segment_color(
+    snake: &Snake,
+    i: usize,
+    buff: &BuffState,
+    damage_phase: DamagePhase,
+    game_time: f32,
+) -> Color {
+    // 受伤闪烁效果
+    let damage_flash = damage_phase == DamagePhase::Flashing && (game_time * 20.0).sin() > 0.0;
+    let base_color = if i == 0 { snake.head_color } else { snake.color };
+
+    // 根据状态选择颜色
+    if damage_flash {
+        Color { r: 1.0, g: 0.2, b: 0.2, a: 1.0 } // 红色（受伤）
+    } else if buff.frozen {
+        Color { r: 0.6, g: 0.9, b: 1.0, a: 1.0 } // 浅蓝色（冰冻）
+    } else if buff.ghost_active {
+        Color { r: base_color.r, g: base_color.g, b: base_color.b, a: 0.5 } // 半透明（幽灵）
+    } else if buff.speed_active {
+        Color { r: 0.3, g: 0.7, b: 1.0, a: 1.0 } // 蓝色（速度）
+    } else if buff.slow_active {
+        Color { r: 1.0, g: 0.6, b: 0.2, a: 1.0 } // 橙色（减速）
+    } else if buff.dizzy_active {
+        let dizzy_flash = (game_time * 15.0).sin() * 0.3 + 0.7;
+        Color { r: 0.8 * dizzy_flash, g: 1.0 * dizzy_flash, b: 0.3, a: 1.0 }
+    } else if buff.slime_active {
+        Color { r: 0.3, g: 0.8, b: 0.3, a: 1.0 } // 绿色（粘液）
+    } else {
+        base_color
+    }
+}
+
 /// 绘制蛇（带所有视觉效果）
 pub fn draw_snake(
     snake: &Snake,
     buff: &BuffState,
     damage_phase: DamagePhase,
     game_time: f32,
-    wrap: bool,
+    topology: Topology,
     blend: f32,
 ) {
-    // 受伤闪烁效果
-    let damage_flash = damage_phase == DamagePhase::Flashing && (game_time * 20.0).sin() > 0.0;
-
     for (i, seg) in snake.body.iter().enumerate() {
-        let base_color = if i == 0 { GREEN } else { LIME };
-
-        // 根据状态选择颜色
-        let color = if damage_flash {
-            Color { r: 1.0, g: 0.2, b: 0.2, a: 1.0 } // 红色（受伤）
-        } else if buff.frozen {
-            Color { r: 0.6, g: 0.9, b: 1.0, a: 1.0 } // 浅蓝色（冰冻）
-        } else if buff.ghost_active {
-            Color { r: base_color.r, g: base_color.g, b: base_color.b, a: 0.5 } // 半透明（幽灵）
-        } else if buff.speed_active {
-            Color { r: 0.3, g: 0.7, b: 1.0, a: 1.0 } // 蓝色（速度）
-        } else if buff.slow_active {
-            Color { r: 1.0, g: 0.6, b: 0.2, a: 1.0 } // 橙色（减速）
-        } else if buff.dizzy_active {
-            let dizzy_flash = (game_time * 15.0).sin() * 0.3 + 0.7;
-            Color { r: 0.8 * dizzy_flash, g: 1.0 * dizzy_flash, b: 0.3, a: 1.0 }
-        } else if buff.slime_active {
-            Color { r: 0.3, g: 0.8, b: 0.3, a: 1.0 } // 绿色（粘液）
-        } else {
-            base_color
-        };
+        let color = snake_segment_color(snake, i, buff, damage_phase, game_time);
 
         // 计算插值位置
-        let from = snake.prev_body.get(i).unwrap_or(seg);
+        let from = &snake.prev_segment(i);
         let gx = from.x as f32;
         let gy = from.y as f32;
-        let mut dx = seg.x as f32 - gx;
-        let mut dy = seg.y as f32 - gy;
-
-        if wrap && !buff.sandworm_active {
-            let gw = GRID_W as f32;
-            let gh = GRID_H as f32;
-            if dx > gw * 0.5 { dx -= gw; }
-            if dx < -gw * 0.5 { dx += gw; }
-            if dy > gh * 0.5 { dy -= gh; }
-            if dy < -gh * 0.5 { dy += gh; }
-        }
+        // 跨越普通环绕边界时按相邻格插值
+        let delta = if buff.sandworm_active { *seg - *from } else { topology.display_delta_on(snake.board, *from, *seg) };
+        let dx = delta.x as f32;
+        let dy = delta.y as f32;
+
+        // 经过传送门或镜像边界的一节：不插值，直接出现在出口
+        // （斜向移动的一步两个分量都是 1，不算跳跃）
+        let portal_jump = dx.abs().max(dy.abs()) > 1.0;
 
-        let (mut x, mut y) = if buff.frozen {
+        let (mut x, mut y) = if buff.frozen || portal_jump {
             (seg.x as f32 * CELL, seg.y as f32 * CELL)
         } else {
             ((gx + dx * blend) * CELL, (gy + dy * blend) * CELL)
@@ -116,6 +123,15 @@ pub fn draw_snake(
             draw_rectangle(x + CELL / 2.0 - 2.0, y - 2.0, 4.0, 4.0, spark_color);
         }
         
+        // 宠物蛋在体内孵化（越接近蛇尾跳动越快）
+        if buff.egg_state.active && i == buff.egg_state.position {
+            let beat = 2.0 + buff.egg_state.progress(snake.body.len()) * 6.0;
+            let pulse = (game_time * beat * std::f32::consts::TAU).sin() * 0.2 + 0.8;
+            let shell = Color::new(1.0, 0.85, 0.9, pulse);
+            draw_circle(x + CELL / 2.0, y + CELL / 2.0, CELL / 2.0 - 3.0, shell);
+            draw_circle(x + CELL / 2.0 - 2.0, y + CELL / 2.0, 1.5, Color::new(0.95, 0.45, 0.65, 1.0));
+        }
+        
         // 炸弹后遗症撕裂效果
         if buff.bomb_after_effect.active && i == snake.body.len().saturating_sub(1) {
             // 尾部撕裂纹理
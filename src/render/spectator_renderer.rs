@@ -0,0 +1,208 @@
+//! 观战渲染模块
+//!
+//! 按 [`WorldSnapshot`] 绘制：棋盘和蛇在镜头（`Camera2D`）里用棋盘像素坐标绘制，
+//! 名字标签、状态栏和每条蛇的 Buff / 数据面板在屏幕坐标里绘制，字不会随缩放变糊。
+
+use macroquad::prelude::*;
+use crate::broadcast::{SnakeRole, SnakeSnapshot, WorldSnapshot};
+use crate::constants::{CELL, GRID_H, GRID_W};
+use crate::fruits::FruitRegistry;
+use crate::game::DroppedFood;
+use crate::types::{BoardDims, Fruit, GridKind};
+use super::{
+    draw_border_and_grid, draw_dropped_foods, draw_food, draw_fruits, draw_hex_board, draw_hex_cell,
+    draw_hex_dropped_foods, draw_hex_food, draw_hex_fruits, hex_center,
+};
+
+/// 数据面板宽度（像素）
+const PANEL_W: f32 = 280.0;
+/// 面板每条蛇占的高度
+const ROW_H: f32 = 38.0;
+
+/// 格子中心的棋盘像素坐标
+pub fn cell_center(pos: IVec2, grid: GridKind) -> Vec2 {
+    match grid {
+        GridKind::Hex => hex_center(pos),
+        _ => (pos.as_vec2() + 0.5) * CELL,
+    }
+}
+
+/// 画一个格子（方格是方块，六边形是六边形）
+fn draw_cell(pos: IVec2, grid: GridKind, scale: f32, color: Color) {
+    if grid == GridKind::Hex {
+        draw_hex_cell(pos, scale, color);
+    } else {
+        let size = CELL * scale;
+        let c = cell_center(pos, grid);
+        draw_rectangle(c.x - size / 2.0, c.y - size / 2.0, size, size, color);
+    }
+}
+
+/// 绘制快照中的世界（在镜头里调用）；`followed` 的蛇加一圈高亮
+pub fn draw_spectator_world(snapshot: &WorldSnapshot, registry: &FruitRegistry, followed: Option<&str>) {
+    let grid = snapshot.grid_kind();
+    let time = snapshot.time;
+
+    // --- 棋盘 ---
+    draw_rectangle(0.0, 0.0, GRID_W as f32 * CELL, GRID_H as f32 * CELL, Color::new(0.2, 0.2, 0.22, 1.0));
+    if grid == GridKind::Hex {
+        draw_hex_board(snapshot.topology_kind(), BoardDims::DEFAULT);
+    } else {
+        draw_border_and_grid(BoardDims::DEFAULT);
+    }
+
+    // --- 障碍物和传送门 ---
+    for &cell in &snapshot.walls {
+        draw_cell(IVec2::from_array(cell), grid, 1.0, Color::new(0.55, 0.55, 0.6, 1.0));
+    }
+    for portal in &snapshot.portals {
+        let [r, g, b, a] = portal.color;
+        for cell in [portal.a, portal.b] {
+            let c = cell_center(IVec2::from_array(cell), grid);
+            draw_circle_lines(c.x, c.y, CELL * 0.45, 3.0, Color::new(r, g, b, a));
+        }
+    }
+
+    // --- 食物和果实（复用游戏的渲染，类型按注册表找回） ---
+    let fruits: Vec<Fruit> = snapshot
+        .fruits
+        .iter()
+        .filter_map(|f| {
+            let config = registry.get_config(&f.kind)?;
+            Some(Fruit { pos: IVec2::from_array(f.pos), type_id: config.id, spawn_time: f.spawn_time, lifetime: f.lifetime })
+        })
+        .collect();
+    let dropped: Vec<DroppedFood> = snapshot
+        .dropped
+        .iter()
+        .map(|d| DroppedFood { pos: IVec2::from_array(d.pos), spawn_time: d.spawn_time, lifetime: d.lifetime })
+        .collect();
+    let food = IVec2::from_array(snapshot.food);
+    if grid == GridKind::Hex {
+        draw_hex_food(food);
+        draw_hex_fruits(&fruits, registry, time);
+        draw_hex_dropped_foods(&dropped, time);
+    } else {
+        draw_food(food);
+        draw_fruits(&fruits, registry, time);
+        draw_dropped_foods(&dropped, time);
+    }
+
+    // --- 蛇 ---
+    for snake in snapshot.snakes.iter().filter(|s| !s.downed) {
+        let color = snake.color();
+        let highlight = followed == Some(snake.name.as_str());
+        for (i, &cell) in snake.body.iter().enumerate().rev() {
+            let pos = IVec2::from_array(cell);
+            if highlight {
+                draw_cell(pos, grid, 1.15, Color::new(1.0, 1.0, 1.0, 0.35));
+            }
+            // 蛇头原色，身体越往后越暗
+            let fade = 1.0 - (i as f32 / snake.body.len().max(1) as f32) * 0.5;
+            let segment = Color::new(color.r * fade, color.g * fade, color.b * fade, color.a);
+            draw_cell(pos, grid, if i == 0 { 0.95 } else { 0.8 }, segment);
+        }
+        if snake.buffs.iter().any(|b| b.name == "Shield")
+            && let Some(head) = snake.head()
+        {
+            let c = cell_center(head, grid);
+            draw_circle_lines(c.x, c.y, CELL * 0.8, 2.0, Color::new(0.3, 0.7, 1.0, 0.8));
+        }
+    }
+}
+
+/// 绘制蛇头上方的名字标签（在屏幕坐标里调用，`camera` 是绘制世界用的镜头）
+pub fn draw_name_tags(snapshot: &WorldSnapshot, camera: &Camera2D) {
+    let grid = snapshot.grid_kind();
+    for snake in snapshot.snakes.iter().filter(|s| !s.downed && s.role != SnakeRole::Pet) {
+        let Some(head) = snake.head() else {
+            continue;
+        };
+        let p = camera.world_to_screen(cell_center(head, grid));
+        let label = match &snake.team {
+            Some(team) => format!("{} [{}]", snake.name, team),
+            None => snake.name.clone(),
+        };
+        let size = measure_text(&label, None, 18, 1.0);
+        let (x, y) = (p.x - size.width / 2.0, p.y - 18.0);
+        draw_rectangle(x - 3.0, y - 14.0, size.width + 6.0, 18.0, Color::new(0.0, 0.0, 0.0, 0.5));
+        draw_text(&label, x, y, 18.0, snake.color());
+    }
+}
+
+/// 面板里的排序：玩家在前，然后 Boss，AI 和宠物按分数、长度从高到低
+fn panel_order(snakes: &[SnakeSnapshot]) -> Vec<&SnakeSnapshot> {
+    let rank = |s: &SnakeSnapshot| match s.role {
+        SnakeRole::Player => 0,
+        SnakeRole::Boss => 1,
+        SnakeRole::Ai | SnakeRole::Pet => 2,
+    };
+    let mut order: Vec<&SnakeSnapshot> = snakes.iter().collect();
+    order.sort_by(|a, b| {
+        rank(a)
+            .cmp(&rank(b))
+            .then(b.score.cmp(&a.score))
+            .then(b.body.len().cmp(&a.body.len()))
+    });
+    order
+}
+
+/// 绘制状态栏、每条蛇的 Buff 和数据面板以及操作提示（在屏幕坐标里调用）
+pub fn draw_spectator_overlay(snapshot: &WorldSnapshot, camera_label: &str, followed: Option<&str>, show_panel: bool) {
+    let (w, h) = (screen_width(), screen_height());
+
+    // --- 状态栏：直播 / 延迟、模式、地图、镜头 ---
+    draw_rectangle(0.0, 0.0, w, 28.0, Color::new(0.0, 0.0, 0.0, 0.6));
+    let (badge, badge_color) = if snapshot.delay > 0.0 {
+        (format!("DELAY {:.0}s", snapshot.delay), ORANGE)
+    } else {
+        ("LIVE".to_string(), RED)
+    };
+    draw_text(&badge, 10.0, 20.0, 22.0, badge_color);
+    let info = format!(
+        "{}  |  {}  |  {}  |  {}  |  {:.0}s  |  {}",
+        snapshot.state, snapshot.mode, snapshot.map, snapshot.grid, snapshot.time, camera_label
+    );
+    draw_text(&info, 120.0, 20.0, 20.0, LIGHTGRAY);
+
+    // --- 数据面板 ---
+    if show_panel {
+        let x = w - PANEL_W;
+        draw_rectangle(x, 28.0, PANEL_W, h - 56.0, Color::new(0.0, 0.0, 0.0, 0.55));
+        let rows = ((h - 64.0) / ROW_H).max(0.0) as usize;
+        for (i, snake) in panel_order(&snapshot.snakes).into_iter().take(rows).enumerate() {
+            let y = 36.0 + i as f32 * ROW_H;
+            if followed == Some(snake.name.as_str()) {
+                draw_rectangle(x, y - 2.0, PANEL_W, ROW_H, Color::new(1.0, 1.0, 1.0, 0.12));
+            }
+            let color = if snake.downed { GRAY } else { snake.color() };
+            draw_rectangle(x + 8.0, y + 3.0, 10.0, 10.0, color);
+            let level = if snake.level > 0 { format!("  Lv{}", snake.level) } else { String::new() };
+            let status = if snake.downed { "  DOWN" } else { "" };
+            let line = format!("{}  {}  len {}{}{}", snake.name, snake.score, snake.body.len(), level, status);
+            draw_text(&line, x + 24.0, y + 14.0, 18.0, color);
+            let buffs: Vec<String> = snake
+                .buffs
+                .iter()
+                .map(|b| if b.remaining > 0.0 { format!("{} {:.1}s", b.name, b.remaining) } else { b.name.clone() })
+                .collect();
+            let buffs = if buffs.is_empty() { "-".to_string() } else { buffs.join("  ") };
+            draw_text(&buffs, x + 24.0, y + 30.0, 16.0, Color::new(0.7, 0.85, 1.0, 0.9));
+        }
+    }
+
+    // --- 操作提示 ---
+    let help = "Tab follow  1-4 players  WASD/arrows pan  +/- or wheel zoom  Space overview  H panel  Esc quit";
+    draw_rectangle(0.0, h - 26.0, w, 26.0, Color::new(0.0, 0.0, 0.0, 0.6));
+    draw_text(help, 10.0, h - 8.0, 18.0, Color::new(0.8, 0.8, 0.8, 0.8));
+}
+
+/// 没连上游戏时的等待画面
+pub fn draw_spectator_waiting(status: &str) {
+    let (w, h) = (screen_width(), screen_height());
+    let title = "Spectator";
+    let t = measure_text(title, None, 48, 1.0);
+    draw_text(title, (w - t.width) * 0.5, h * 0.5 - 10.0, 48.0, WHITE);
+    let s = measure_text(status, None, 24, 1.0);
+    draw_text(status, (w - s.width) * 0.5, h * 0.5 + 30.0, 24.0, LIGHTGRAY);
+}
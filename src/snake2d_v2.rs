@@ -6,11 +6,11 @@
 //! ## 架构说明
 //!
 //! 本文件只包含：
-//! - 游戏世界状态结构体 (`GameWorld`)
+//! - 键盘输入读取 (`read_direction_input`)
 //! - 窗口配置 (`window_conf`)
 //! - 游戏主循环 (`main`)
 //!
-//! 所有游戏逻辑、渲染、音效都封装在独立模块中。
+//! 游戏世界状态和逐帧逻辑在 `rtest::world::GameWorld` 中，渲染、音效都封装在独立模块中。
 
 // =============================================================================
 // 外部依赖
@@ -26,9 +26,8 @@ use macroquad::prelude::*;
 // - get_frame_time, next_frame: 帧控制
 // - Conf: 窗口配置
 
-use ::rand::{thread_rng, Rng};
-// thread_rng: 获取线程本地随机数生成器
-// Rng: 随机数生成器 trait，提供 gen_bool, gen_range 等方法
+use ::rand::{thread_rng, RngCore};
+// thread_rng: 获取线程本地随机数生成器（只用于生成每局的种子）
 
 // =============================================================================
 // 游戏库模块导入
@@ -39,67 +38,37 @@ use rtest::constants::{
     CELL,    // 单元格像素尺寸 (20.0)
     GRID_W,  // 游戏区域宽度格子数 (32)
     GRID_H,  // 游戏区域高度格子数 (24)
+    VICTORY_BONUS, // 完美通关奖励分数
+    NO_FOOD,       // 收起的食物（棋盘外）
 };
 
 // --- 类型模块 ---
 use rtest::types::{
     // 游戏状态
-    GameState,      // 游戏状态枚举: Playing, Paused, GameOver
-    BuffState,      // Buff状态管理: 护盾、速度、幽灵、冰冻等
-    DamageState,    // 受伤动画状态
-    ComboState,     // Combo连击状态
+    GameState,      // 游戏状态枚举: 菜单界面, Playing, Paused, GameOver, Victory
     SandwormPhase,  // 沙虫变身阶段枚举
 
-    // 游戏对象
-    Fruit,          // 果实实例 (位置、类型、生命周期)
-    Portal,         // 传送门 (两个位置、颜色、生命周期)
-    Particle,       // 粒子 (位置、速度、颜色、生命周期)
-    BloodStain,     // 血迹 (位置、透明度、生命周期)
-    Afterimage,     // 残影 (蛇身位置快照)
-};
+    // 地图
+    Grid,           // 棋盘几何抽象: 方向、相邻格、距离
+    GridKind,       // 棋盘几何选择: 方格、六边形
+    hex_dir,        // 六边形方向常量
 
-// --- 果实系统模块 ---
-use rtest::fruits::{
-    create_fruit_registry,  // 创建并初始化果实注册表
-    FruitRegistry,          // 果实注册表: 管理所有果实类型
+    // 设置
+    KeyBindings,    // 按键绑定
+    TeamMode,       // AI 蛇队伍编排: 无、红蓝两队、盟友
+    PartyMode,      // 本地多人规则: 单人、对战、限时对战、合作
 };
 
 // --- 游戏逻辑模块 ---
 use rtest::game::{
-    // 蛇
-    Snake,              // 蛇结构体: 身体位置、方向、移动
-    MoveResult,         // 移动结果: Normal, WallCollision, SelfCollision
-
-    // 碰撞检测
-    check_fruit_collision,   // 检查蛇头是否碰到果实
-    check_portal_collision,  // 检查蛇头是否碰到传送门
-
-    // 生成逻辑
-    spawn_food,         // 生成食物位置
-    spawn_portal,       // 生成传送门
-    update_fruits_with_callbacks, // 更新果实列表并调用过期回调
-    update_portals,     // 更新传送门列表 (移除过期)
-
-    // 状态更新
-    update_combo,               // 更新Combo状态
-    update_particles,           // 更新粒子 (移动、衰减)
-    update_damage_animation,    // 更新受伤动画状态机
-    update_blood_stains,        // 更新血迹 (移除过期)
-    spawn_blood_particles,      // 生成血液粒子
-    update_sandworm_mode,       // 更新沙虫模式状态机
-
-    // 果实效果
-    handle_fruit_effect,        // 处理果实消费效果
+    // 本地多人
+    PartyOutcome,       // 本地多人结果: 获胜、平局、全员倒下
 
     // AI 蛇系统
-    AIManager,          // AI蛇管理器: 生成、更新、碰撞检测
-    
-    // 果实生成管理器
-    FruitSpawnManager,          // 果实生成管理器
-    create_default_spawn_manager, // 创建默认生成管理器
-    
-    // 炸弹管理器
-    BombManager,        // 炸弹管理器
+    Team,               // AI 蛇队伍: 盟友、红队、蓝队
+
+    // 排行榜
+    DEFAULT_LEADERBOARD_SIZE,       // 每个榜单保留的名次
 };
 
 // --- 渲染模块 ---
@@ -107,6 +76,7 @@ use rtest::render::{
     // 基础渲染
     draw_background,        // 绘制背景 (清屏)
     draw_border_and_grid,   // 绘制边框和网格线
+    draw_fence,             // 绘制缩小棋盘外的围栏
 
     // 游戏对象渲染
     draw_food,              // 绘制食物
@@ -118,10 +88,11 @@ use rtest::render::{
     draw_particles,         // 绘制粒子
     draw_blood_stains,      // 绘制血迹
     draw_portals,           // 绘制传送门
+    draw_hazards,           // 绘制动态障碍物
     draw_afterimages,       // 绘制残影
     draw_shield_effect,     // 绘制护盾光环
     draw_ghost_effect,      // 绘制幽灵效果
-    spawn_freeze_particles, // 生成冰冻粒子
+    draw_contrast_outlines, // 绘制高对比度描边（辅助功能）
 
     // AI蛇渲染
     draw_ai_snakes,         // 绘制所有AI蛇
@@ -129,7 +100,66 @@ use rtest::render::{
 
     // UI渲染
     draw_hud,               // 绘制HUD (分数、Combo、Buff状态)
+    draw_party_hud,         // 绘制本地多人玩家栏
+    draw_team_roster,       // 绘制各队存活人数
+    draw_boss_health_bar,   // 绘制 Boss 血条
+
+    // 六边形模式渲染
+    draw_hex_board,         // 绘制六边形棋盘
+    draw_hex_fence,         // 绘制六边形棋盘外的围栏
+    draw_hex_snake,         // 绘制六边形蛇
+    draw_hex_ai_snakes,     // 绘制六边形 AI 蛇
+    draw_hex_food,          // 绘制六边形食物
+    draw_hex_fruits,        // 绘制六边形果实
+    draw_hex_dropped_foods, // 绘制六边形掉落食物
+    draw_hex_portals,       // 绘制六边形传送门
+    draw_hex_hazards,       // 绘制六边形障碍物
+    draw_hex_sandworm,      // 绘制六边形沙虫
+    draw_map_info,          // 绘制地图名、拓扑和边界样式
     draw_overlay,           // 绘制覆盖层 (暂停、游戏结束)
+    draw_net_status,        // 绘制联机状态（也用于机器人状态）
+    draw_victory_overlay,   // 绘制完美通关覆盖层
+};
+
+// --- 界面模块 ---
+use rtest::ui::{
+    MenuSystem,     // 标题、模式、地图、设置、制作人员、暂停菜单
+    UiAction,       // 菜单结果: 开始、继续、重新开始、退出等
+};
+
+// --- 在线排行榜 ---
+use rtest::online::{
+    OnlineEvent,        // 后台线程返回的结果
+};
+
+// --- 联机对战 ---
+use rtest::net::{
+    Lobby,          // 主机等待加入 / 按地址加入
+    NetLaunch,      // 命令行参数: --host / --join / --udp / --delay
+    NetRole,        // 大厅角色: 主机、加入方
+    NetSession,     // 一局联机对局: lockstep 输入同步、重发、断线检测
+    NetInput,       // 一名玩家一个 tick 的输入
+    NetError,       // 联机错误: 断线、拒绝、不同步
+    MatchConfig,    // 主机下发的对局设置和种子
+    TICK_RATE,      // 联机时每秒模拟的 tick 数
+};
+
+// --- 观战广播 ---
+use rtest::broadcast::{
+    BroadcastOptions,   // 命令行参数: --broadcast / --broadcast-delay
+    BroadcastServer,    // 把世界快照广播给观战端
+};
+
+// --- HTTP 机器人 ---
+use rtest::bots::{
+    BotOptions,     // 命令行参数: --bot / --bot-timeout
+    BotController,  // 一个机器人: 后台线程、超时回退
+};
+
+// --- 游戏世界 ---
+use rtest::world::{
+    GameWorld,      // 一局游戏的全部状态和逐帧推进（单人对局同时录制回放）
+    human_cells,    // 场上所有玩家的蛇身
 };
 
 // --- 音效模块 ---
@@ -140,195 +170,138 @@ use rtest::audio::SoundManager;
 // - play_trap(): 播放陷阱音效
 // - play_power(): 播放功能果实音效
 // - play_game_over(): 播放游戏结束音效
+// - play_victory(): 播放完美通关音效
 
-// =============================================================================
-// 游戏世界状态
-// =============================================================================
-
-/// 游戏世界状态
-///
-/// 集中管理所有游戏状态，作为游戏主循环的核心数据结构。
-///
-/// ## 设计说明
+/// 读取本帧新按下的方向键（方向键可在设置中重新绑定，每名玩家一套）
 ///
-/// 将所有状态集中在一个结构体中的好处：
-/// - 便于序列化/反序列化（存档功能）
-/// - 便于重置游戏状态
-/// - 清晰的所有权边界
-struct GameWorld {
-    // -------------------------------------------------------------------------
-    // 核心游戏对象
-    // -------------------------------------------------------------------------
-
-    /// 蛇实例
-    ///
-    /// 包含蛇身位置列表、移动方向、上一帧位置（用于插值）
-    snake: Snake,
-
-    /// 食物位置
-    ///
-    /// 普通食物，吃到后+1分并增长1节
-    food: IVec2,
-
-    /// 果实列表
-    ///
-    /// 特殊果实（陷阱、功能、幸运方块等），有生命周期
-    fruits: Vec<Fruit>,
-
-    /// 传送门列表
-    ///
-    /// 成对出现，碰到一个会传送到另一个
-    portals: Vec<Portal>,
-
-    // -------------------------------------------------------------------------
-    // 效果状态
-    // -------------------------------------------------------------------------
-
-    /// Buff状态
-    ///
-    /// 管理所有增益/减益效果：护盾、速度、幽灵、冰冻、减速、眩晕、粘液、沙虫
-    buff_state: BuffState,
-
-    /// 受伤状态
-    ///
-    /// 管理受伤动画：闪烁 → 逐节掉落
-    damage_state: DamageState,
-
-    /// Combo状态
-    ///
-    /// 连续吃果实的计数和显示计时器
-    combo_state: ComboState,
-
-    // -------------------------------------------------------------------------
-    // 视觉效果
-    // -------------------------------------------------------------------------
-
-    /// 粒子列表
-    ///
-    /// 用于爆炸、血液飞溅等特效
-    particles: Vec<Particle>,
-
-    /// 血迹列表
-    ///
-    /// 受伤时留下的血迹，会逐渐消失
-    blood_stains: Vec<BloodStain>,
-
-    /// 残影列表
-    ///
-    /// 速度模式下的蛇身残影
-    afterimages: Vec<Afterimage>,
-
-    // -------------------------------------------------------------------------
-    // 游戏控制
-    // -------------------------------------------------------------------------
-
-    /// 游戏状态
-    ///
-    /// Playing: 游戏进行中
-    /// Paused: 暂停
-    /// GameOver: 游戏结束
-    state: GameState,
-
-    /// 当前分数
-    score: u32,
-
-    /// 最高分
-    high_score: u32,
-
-    /// 游戏时间（秒）
-    ///
-    /// 用于动画和果实生命周期计算
-    game_time: f32,
-
-    /// 是否启用穿墙模式
-    ///
-    /// true: 蛇可以从一边穿到另一边
-    /// false: 撞墙游戏结束
-    wrap: bool,
-
-    // -------------------------------------------------------------------------
-    // 生成计时器
-    // -------------------------------------------------------------------------
-
-    /// 传送门生成计时器
-    portal_spawn_timer: f32,
-
-    // -------------------------------------------------------------------------
-    // 系统
-    // -------------------------------------------------------------------------
-
-    /// 果实注册表
-    ///
-    /// 管理所有已注册的果实类型
-    registry: FruitRegistry,
-    
-    /// 果实生成管理器
-    ///
-    /// 统一管理所有果实类别的生成逻辑，支持声明式配置
-    spawn_manager: FruitSpawnManager,
-
-    // -------------------------------------------------------------------------
-    // AI 蛇系统
-    // -------------------------------------------------------------------------
+/// - 方格：方向键
+/// - 八方向：方向键，同时按住两个相邻方向键为斜向（组合键）
+/// - 六边形：QWE/ASD 对应 NW/N/NE/SW/S/SE（只在 `letters` 为真，即单人时可用，多人时和其他玩家的键冲突）；
+///   上下方向键为 N/S，左右方向键为相对当前方向逆时针/顺时针转向
+fn read_direction_input(grid: GridKind, current: IVec2, keys: &KeyBindings, letters: bool) -> Option<IVec2> {
+    match grid {
+        GridKind::Square => {
+            if is_key_pressed(keys.up) {
+                Some(ivec2(0, -1))
+            } else if is_key_pressed(keys.down) {
+                Some(ivec2(0, 1))
+            } else if is_key_pressed(keys.left) {
+                Some(ivec2(-1, 0))
+            } else if is_key_pressed(keys.right) {
+                Some(ivec2(1, 0))
+            } else {
+                None
+            }
+        }
+        GridKind::Octile => {
+            if !keys.arrows().iter().any(|&key| is_key_pressed(key)) {
+                return None;
+            }
+            // 新按下一个方向键时，合并所有仍按住的方向键
+            let held = |key| is_key_down(key) as i32;
+            let dir = ivec2(
+                held(keys.right) - held(keys.left),
+                held(keys.down) - held(keys.up),
+            );
+            (dir != IVec2::ZERO).then_some(dir)
+        }
+        GridKind::Hex => {
+            let letter_keys = [
+                (KeyCode::Q, hex_dir::NW),
+                (KeyCode::W, hex_dir::N),
+                (KeyCode::E, hex_dir::NE),
+                (KeyCode::A, hex_dir::SW),
+                (KeyCode::S, hex_dir::S),
+                (KeyCode::D, hex_dir::SE),
+            ];
+            let arrow_keys = [(keys.up, hex_dir::N), (keys.down, hex_dir::S)];
+            let hex_keys = if letters { &letter_keys[..] } else { &[] };
+            if let Some(&(_, dir)) = hex_keys.iter().chain(&arrow_keys).find(|(key, _)| is_key_pressed(*key)) {
+                Some(dir)
+            } else if is_key_pressed(keys.left) {
+                Some(grid.turn(current, -1))
+            } else if is_key_pressed(keys.right) {
+                Some(grid.turn(current, 1))
+            } else {
+                None
+            }
+        }
+    }
+}
 
-    /// AI 蛇管理器
-    ///
-    /// 管理所有 AI 蛇的生成、移动、碰撞和死亡
-    ai_manager: AIManager,
+/// 按命令行参数开房间或加入（主机用当前模式、地图和设置，种子随机）
+fn open_lobby(launch: &NetLaunch, world: &GameWorld) -> Result<Lobby, NetError> {
+    match launch.role {
+        NetRole::Host => {
+            let config = MatchConfig::new(thread_rng().next_u64(), world.mode, world.map_index, world.grid, &world.settings)
+                .with_input_delay(launch.input_delay)
+                .with_hash_interval(launch.hash_interval);
+            Lobby::host(&launch.addr, launch.transport, config)
+        }
+        NetRole::Join => Lobby::join(&launch.addr, launch.transport, &launch.name),
+    }
 }
 
-impl GameWorld {
-    /// 创建新的游戏世界
-    fn new() -> Self {
-        let mut rng = thread_rng();
-        let snake = Snake::new();
-        let food = spawn_food(&snake.body, &mut rng);
-
-        GameWorld {
-            snake,
-            food,
-            fruits: vec![],
-            portals: vec![],
-            buff_state: BuffState::default(),
-            damage_state: DamageState::default(),
-            combo_state: ComboState::default(),
-            particles: vec![],
-            blood_stains: vec![],
-            afterimages: vec![],
-            state: GameState::Playing,
-            score: 0,
-            high_score: 0,
-            game_time: 0.0,
-            wrap: true,
-            portal_spawn_timer: 0.0,
-            registry: create_fruit_registry(),
-            spawn_manager: create_default_spawn_manager(),
-            ai_manager: AIManager::new(),
+/// 联机对局的一帧：收发输入，本地输入按固定步长排进 tick，输入到齐的 tick 推进世界并记录哈希
+fn run_online_frame(
+    world: &mut GameWorld,
+    session: &mut NetSession,
+    pending: &mut Option<IVec2>,
+    accumulator: &mut f32,
+    sounds: &SoundManager,
+) -> Result<(), NetError> {
+    let step = 1.0 / TICK_RATE as f32;
+    session.poll()?;
+    while *accumulator >= step {
+        // 领先对方太多时等一等，攒下的时间留到下一帧
+        if !session.push_input(NetInput::turn(*pending))? {
+            break;
         }
+        *pending = None;
+        *accumulator -= step;
     }
+    while let Some((tick, inputs)) = session.next_tick() {
+        let hash = world.advance_tick(&inputs, sounds);
+        session.record_hash(tick, hash)?;
+    }
+    Ok(())
+}
 
-    /// 重置游戏状态
-    ///
-    /// 保留最高分，重置其他所有状态
-    fn reset(&mut self) {
-        let mut rng = thread_rng();
-        self.snake.reset();
-        self.food = spawn_food(&self.snake.body, &mut rng);
-        self.fruits.clear();
-        self.portals.clear();
-        self.buff_state = BuffState::default();
-        self.damage_state = DamageState::default();
-        self.combo_state = ComboState::default();
-        self.particles.clear();
-        self.blood_stains.clear();
-        self.afterimages.clear();
-        self.state = GameState::Playing;
-        self.score = 0;
-        self.game_time = 0.0;
-        self.portal_spawn_timer = 0.0;
-        self.spawn_manager.reset();
-        self.ai_manager.reset();
+/// 六边形模式的世界渲染（背景、地面、对象、蛇）
+///
+/// 拖影、护盾、幽灵和血迹等方格特效在六边形模式下不绘制
+fn draw_hex_world(world: &GameWorld) {
+    // --- 背景层 ---
+    draw_background();
+    draw_hex_board(world.topology, world.board());
+
+    // --- 地面层 ---
+    draw_hex_portals(&world.portal_manager.portals, world.game_time);
+    draw_hex_hazards(&world.hazards, world.game_time);
+    draw_hex_fence(world.board());
+
+    // --- 对象层 ---
+    draw_hex_food(world.food);
+    draw_hex_fruits(&world.fruits, &world.registry, world.game_time);
+    draw_hex_dropped_foods(&world.ai_manager.dropped_foods, world.game_time);
+
+    // --- 玩家的蛇 ---
+    for (i, player) in world.players.iter().enumerate().filter(|(_, p)| !p.downed) {
+        if player.buff_state.sandworm_phase != SandwormPhase::None {
+            draw_hex_sandworm(&player.snake);
+        } else {
+            draw_hex_snake(
+                &player.snake,
+                &player.buff_state,
+                player.damage_state.phase,
+                world.game_time,
+                world.blend(i),
+            );
+        }
     }
+
+    // --- AI 蛇 ---
+    draw_hex_ai_snakes(&world.ai_manager.snakes);
 }
 
 // =============================================================================
@@ -361,10 +334,65 @@ async fn main() {
     // -------------------------------------------------------------------------
 
     let mut world = GameWorld::new();
-    let mut rng = thread_rng();
-    let sounds = SoundManager::new().await;
-    let mut over_once = false;      // 防止重复播放游戏结束音效
-    let mut accumulator = 0.0f32;   // 固定时间步长累加器
+    let mut sounds = SoundManager::new().await;
+    sounds.set_volume(world.settings.volume);
+    let map_names: Vec<&'static str> = world.maps.iter().map(|m| m.name).collect();
+    let mut menus = MenuSystem::new(&map_names, &world.settings);
+    if world.online.is_some() {
+        menus.enable_online();
+    }
+    let mut over_once = false;      // 防止重复播放游戏结束音效（和重复触发名字输入）
+    let mut finished_state = GameState::GameOver; // 跳过名字输入后回到的结束画面
+
+    // --- 命令行：观战广播和联机（--host / --join 时先进大厅） ---
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let options = BotOptions::take(&mut args).and_then(|bots| {
+        let broadcast = BroadcastOptions::take(&mut args)?;
+        let launch = NetLaunch::parse(args)?;
+        if bots.is_some() && launch.is_some() {
+            return Err("--bot cannot be combined with --host or --join".to_string());
+        }
+        Ok((bots, broadcast, launch))
+    });
+    let (bots, broadcast, launch) = match options {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            eprintln!(
+                "usage: snake2d [--host ADDR | --join ADDR] [--udp] [--delay TICKS] [--hash-every TICKS] [--name NAME] \
+                 [--broadcast ADDR] [--broadcast-delay SECS] [--bot URL]... [--bot-timeout MS]"
+            );
+            return;
+        }
+    };
+    if let Some(bots) = bots {
+        match bots.urls.iter().map(|url| BotController::spawn(url, bots.timeout)).collect() {
+            Ok(controllers) => world.attach_bots(controllers),
+            Err(err) => {
+                eprintln!("cannot connect bots: {}", err);
+                return;
+            }
+        }
+    }
+    let mut broadcast = match broadcast.map(|b| BroadcastServer::bind(&b.addr).map(|server| server.with_delay(b.delay))) {
+        Some(Ok(server)) => Some(server),
+        Some(Err(err)) => {
+            eprintln!("cannot start spectator broadcast: {}", err);
+            return;
+        }
+        None => None,
+    };
+    let mut lobby = match launch.as_ref().map(|launch| open_lobby(launch, &world)).transpose() {
+        Ok(lobby) => lobby,
+        Err(err) => {
+            eprintln!("cannot start online match: {}", err);
+            return;
+        }
+    };
+    let mut net: Option<NetSession> = None;
+    let mut net_error: Option<NetError> = None;
+    let mut net_pending: Option<IVec2> = None; // 本地玩家最近按下、还没排进 tick 的方向
+    let mut net_accumulator = 0.0;
 
     // -------------------------------------------------------------------------
     // 主循环
@@ -372,484 +400,384 @@ async fn main() {
 
     loop {
         // =====================================================================
-        // 计算 Tick 间隔
+        // 在线排行榜结果
         // =====================================================================
-        // 蛇越长移动越快，Buff 会影响速度
-
-        let len = world.snake.len() as u32;
-        // 基础 tick: 120ms，每增长3节减少5ms，最小40ms
-        let mut tick = ((120u32.saturating_sub((len / 3) * 5)).max(40)) as f32 / 1000.0;
-        // 应用 Buff 倍率（速度模式 0.5x，减速 2x，粘液 1.5x）
-        tick *= world.buff_state.tick_multiplier();
-        accumulator += get_frame_time();
-
-        // =====================================================================
-        // 输入处理
-        // =====================================================================
-
-        // --- 退出游戏 ---
-        if is_key_pressed(KeyCode::Escape) || is_key_pressed(KeyCode::Q) {
-            break;
-        }
-
-        // --- 暂停/继续 ---
-        if is_key_pressed(KeyCode::Space) {
-            world.state = match world.state {
-                GameState::Playing => GameState::Paused,
-                GameState::Paused => GameState::Playing,
-                s => s,
-            };
-        }
 
-        // --- 重新开始 ---
-        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::R) {
-            if world.state != GameState::Playing {
-                world.reset();
-                over_once = false;
+        if let Some(online) = &world.online {
+            for event in online.poll() {
+                match event {
+                    OnlineEvent::Fetched(key, entries) => menus.show_online_leaderboard(&key, Ok(&entries)),
+                    OnlineEvent::FetchFailed(key, err) => menus.show_online_leaderboard(&key, Err(&err)),
+                    OnlineEvent::Queued { pending, error } => {
+                        eprintln!("online leaderboard offline ({}), {} score(s) queued", error, pending);
+                    }
+                    OnlineEvent::Rejected(sub, error) => {
+                        eprintln!("online leaderboard rejected score {} by {}: {}", sub.score, sub.name, error);
+                    }
+                    OnlineEvent::Submitted(..) => {}
+                }
             }
         }
 
-        // --- 切换穿墙模式 ---
-        if is_key_pressed(KeyCode::W) {
-            world.wrap = !world.wrap;
-        }
+        // =====================================================================
+        // 联机大厅
+        // =====================================================================
 
-        // --- 方向输入 ---
-        // 使用 is_key_pressed 只检测按键首次按下，忽略按住时的重复事件
-        // 这样可以避免按住方向键时的卡顿问题
-        if world.state == GameState::Playing && !world.buff_state.frozen {
-            // 获取本帧新按下的方向（只响应首次按下，不响应按住）
-            let pressed_dir = if is_key_pressed(KeyCode::Up) {
-                Some(ivec2(0, -1))
-            } else if is_key_pressed(KeyCode::Down) {
-                Some(ivec2(0, 1))
-            } else if is_key_pressed(KeyCode::Left) {
-                Some(ivec2(-1, 0))
-            } else if is_key_pressed(KeyCode::Right) {
-                Some(ivec2(1, 0))
+        if let Some(waiting) = &mut lobby {
+            if is_key_pressed(KeyCode::Escape) {
+                lobby = None;
             } else {
-                None
-            };
-
-            // 只有当有新按键且不是反向时才更新方向
-            if let Some(new_dir) = pressed_dir {
-                // 防止反向移动（不能直接掉头）
-                let is_opposite = new_dir.x == -world.snake.dir.x && new_dir.y == -world.snake.dir.y
-                    && (world.snake.dir.x != 0 || world.snake.dir.y != 0);
-                
-                if !is_opposite {
-                    let mut final_dir = new_dir;
-                    
-                    // 眩晕效果：40% 概率方向偏移
-                    if world.buff_state.dizzy_active {
-                        if rng.gen_bool(0.4) {
-                            let directions = [ivec2(0, -1), ivec2(0, 1), ivec2(-1, 0), ivec2(1, 0)];
-                            let valid_dirs: Vec<_> = directions
-                                .iter()
-                                .filter(|&&d| d != ivec2(-world.snake.dir.x, -world.snake.dir.y))
-                                .collect();
-                            if !valid_dirs.is_empty() {
-                                final_dir = *valid_dirs[rng.gen_range(0..valid_dirs.len())];
-                            }
-                        }
+                let started = waiting.poll().and_then(|session| match session {
+                    Some(session) => world.start_online(session.config()).map(|_| Some(session)),
+                    None => Ok(None),
+                });
+                match started {
+                    Ok(Some(session)) => {
+                        net = Some(session);
+                        net_error = None;
+                        net_pending = None;
+                        net_accumulator = 0.0;
+                        over_once = false;
+                        lobby = None;
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        eprintln!("online match failed: {}", err);
+                        lobby = None;
                     }
-                    world.snake.dir = final_dir;
                 }
             }
         }
 
         // =====================================================================
-        // 固定时间步长游戏逻辑
+        // 输入处理
         // =====================================================================
-        // 每个 tick 蛇移动一格，最多处理5步防止卡顿时跳帧过多
-
-        let mut steps = 0;
-        while accumulator >= tick && steps < 5 {
-            accumulator -= tick;
 
-            // 非游戏中状态跳过
-            if world.state != GameState::Playing {
-                break;
+        if lobby.is_some() {
+            // 大厅中只响应 Esc（上面已处理）
+        } else if world.state.is_menu() {
+            // --- 菜单界面（标题、模式、地图、设置、制作人员、暂停） ---
+            match menus.update(&mut world.state, &mut world.settings) {
+                Some(UiAction::StartGame { mode, map, grid, party, players }) => {
+                    world.mode = mode;
+                    world.grid = grid;
+                    world.seat(party, players);
+                    world.load_map(map);
+                    over_once = false;
+                }
+                Some(UiAction::Resume) => world.state = GameState::Playing,
+                Some(UiAction::Restart) => {
+                    world.reset();
+                    over_once = false;
+                }
+                Some(UiAction::QuitToTitle) => {
+                    world.reset();
+                    world.state = GameState::Title;
+                    over_once = false;
+                }
+                Some(UiAction::Exit) => break,
+                Some(UiAction::SettingsChanged) => sounds.set_volume(world.settings.volume),
+                Some(UiAction::SubmitName) => {
+                    let name = menus.name_input.value().to_string();
+                    let rank = world.record_score(&name);
+                    let key = world.leaderboard_key();
+                    menus.open_leaderboard(&mut world.state, &key, &world.leaderboard, rank);
+                }
+                Some(UiAction::SkipName) => world.state = finished_state,
+                Some(UiAction::RefreshLeaderboard) => {
+                    menus.refresh_leaderboard(&world.leaderboard, None);
+                    if menus.leaderboard_online()
+                        && let Some(online) = &world.online
+                    {
+                        online.fetch(menus.leaderboard_key(), DEFAULT_LEADERBOARD_SIZE);
+                    }
+                }
+                None => {}
+            }
+        } else if net.is_some() {
+            // --- 联机：不能暂停、重开或切换地图，Esc 离开对局 ---
+            if is_key_pressed(KeyCode::Escape) {
+                net = None;
+                net_error = None;
+                world.leave_online();
+                over_once = false;
+            }
+        } else {
+            // --- 暂停（Esc 或暂停键打开暂停菜单） ---
+            if world.state == GameState::Playing
+                && (is_key_pressed(KeyCode::Escape) || is_key_pressed(world.settings.keys.pause))
+            {
+                world.state = GameState::Paused;
             }
 
-            // 冰冻状态跳过移动
-            if world.buff_state.frozen {
-                continue;
+            // --- 游戏结束 / 通关：重新开始或回到标题 ---
+            if matches!(world.state, GameState::GameOver | GameState::Victory) {
+                if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::R) {
+                    world.reset();
+                    over_once = false;
+                } else if is_key_pressed(KeyCode::Escape) {
+                    world.reset();
+                    world.state = GameState::Title;
+                    over_once = false;
+                }
             }
 
-            steps += 1;
+            // --- 切换边界拓扑 ---
+            if is_key_pressed(KeyCode::T) {
+                world.topology = world.topology.next();
+            }
 
-            // --- 速度模式残影 ---
-            if world.buff_state.speed_active {
-                world.afterimages.push(Afterimage {
-                    positions: world.snake.body.clone(),
-                    alpha: 0.4,
-                    spawn_time: world.game_time,
-                });
+            // --- 切换棋盘几何（重新开始） ---
+            if is_key_pressed(KeyCode::G) {
+                world.grid = world.grid.next();
+                world.reset();
+                over_once = false;
             }
 
-            // --- 沙虫模式跳过正常移动 ---
-            if world.buff_state.sandworm_phase != SandwormPhase::None {
-                continue;
+            // --- 切换地图（重新开始） ---
+            if is_key_pressed(KeyCode::M) {
+                world.load_map(world.map_index + 1);
+                over_once = false;
             }
+        }
 
-            // --- 移动蛇 ---
-            // 护盾/幽灵/沙虫模式可穿过自己
-            let can_pass_self = world.buff_state.can_pass_through();
-            let move_result = world.snake.move_forward(world.wrap, can_pass_self);
-
-            match move_result {
-                // 碰撞处理
-                MoveResult::WallCollision | MoveResult::SelfCollision => {
-                    if !world.buff_state.can_pass_through() {
-                        world.state = GameState::GameOver;
-                        break;
-                    }
+        // --- 方向输入 ---
+        // 使用 is_key_pressed 只检测按键首次按下，忽略按住时的重复事件
+        // 这样可以避免按住方向键时的卡顿问题；每名玩家读自己的按键
+        if world.state == GameState::Playing && net.is_none() {
+            let letters = world.players.len() == 1;
+            for i in 0..world.players.len() {
+                // 机器人的方向由 HTTP 回复决定
+                if world.bot_of(i).is_some() {
+                    continue;
                 }
-                // 正常移动
-                MoveResult::Normal(mut new_head) => {
-                    // 检查传送门 - 只传送蛇头，身体会自然跟随穿过
-                    if let Some((_from, to)) = check_portal_collision(new_head, &world.portals) {
-                        // 将蛇头移动到传送门出口
-                        world.snake.body[0] = to;
-                        new_head = to;
-                    }
-
-                    // 检查食物
-                    if new_head == world.food {
-                        world.score += 1;
-                        world.snake.grow();
-                        world.food = spawn_food(&world.snake.body, &mut rng);
-                        sounds.play_eat();
-                    }
+                let player = &world.players[i];
+                // 获取本帧新按下的方向（只响应首次按下，不响应按住）
+                if let Some(new_dir) = read_direction_input(world.grid, player.snake.dir, &player.keys, letters) {
+                    world.turn(i, new_dir);
+                }
+            }
+        }
 
-                    // 检查果实
-                    if let Some(idx) = check_fruit_collision(new_head, &world.fruits) {
-                        let fruit = world.fruits.remove(idx);
-                        
-                        // 蛇蛋被吃掉 = 阻止孵化，不生成 AI 蛇
-                        // AI 蛇只在蛇蛋过期时自动生成
-                        
-                        update_combo(&mut world.combo_state, world.game_time);
-
-                        handle_fruit_effect(
-                            &fruit,
-                            &world.registry,
-                            &mut world.snake,
-                            &mut world.buff_state,
-                            &mut world.damage_state,
-                            &mut world.particles,
-                            &mut world.score,
-                            &mut world.combo_state,
-                            &mut world.state,
-                            &mut rng,
-                            world.game_time,
-                            &sounds,
-                        );
-                    }
-                    
-                    // 检查是否吃到 AI 蛇掉落的食物
-                    let eaten = world.ai_manager.check_player_eat_dropped(new_head);
-                    if eaten > 0 {
-                        world.score += eaten;
-                        world.snake.grow();
-                        sounds.play_eat();
-                    }
-                    
-                    // 检查是否撞到 AI 蛇身体
-                    if !world.buff_state.can_pass_through() {
-                        for ai_snake in &world.ai_manager.snakes {
-                            // 撞到 AI 蛇身体（不包括头）
-                            if ai_snake.body.iter().skip(1).any(|&p| p == new_head) {
-                                world.state = GameState::GameOver;
-                                break;
-                            }
-                            // 头对头碰撞
-                            if ai_snake.head() == new_head {
-                                world.state = GameState::GameOver;
-                                break;
-                            }
-                        }
-                    }
+        // --- 联机：本地玩家用主按键，方向攒到下一个 tick 发出 ---
+        if let Some(session) = &mut net
+            && net_error.is_none()
+        {
+            if world.state == GameState::Playing {
+                let player = &world.players[session.local()];
+                if let Some(dir) = read_direction_input(world.grid, player.snake.dir, &world.settings.keys, true) {
+                    net_pending = Some(dir);
                 }
             }
+            // 卡顿或等待对方时最多补 0.25 秒
+            net_accumulator = (net_accumulator + get_frame_time()).min(0.25);
+            if let Err(err) = run_online_frame(&mut world, session, &mut net_pending, &mut net_accumulator, &sounds) {
+                eprintln!("online match ended: {}", err);
+                net_error = Some(err);
+            }
         }
 
         // =====================================================================
-        // 更新游戏系统
+        // 固定时间步长游戏逻辑
         // =====================================================================
+        // 每名玩家按自己的 tick 移动（蛇越长越快，Buff 和难度影响速度），
+        // 最多处理5步防止卡顿时跳帧过多；联机时由上面按固定步长推进
 
-        let dt = get_frame_time();
+        if net.is_none() {
+            world.advance(get_frame_time(), &sounds);
+        }
 
-        if world.state == GameState::Playing {
-            world.game_time += dt;
+        // =====================================================================
+        // 渲染
+        // =====================================================================
+        // 渲染顺序很重要，后绘制的会覆盖先绘制的
 
-            // --- 更新沙虫模式 ---
-            let sandworm_result = update_sandworm_mode(
-                &mut world.snake,
-                &mut world.buff_state,
-                &mut world.fruits,
-                &mut world.food,
-                &mut world.particles,
-                dt,
-                &mut rng,
-            );
-            world.score += sandworm_result.bonus_score;
-            
-            // 沙虫模式吞噬 AI 蛇
-            if world.buff_state.sandworm_phase != SandwormPhase::None {
-                let devour_score = world.ai_manager.sandworm_devour(
-                    &world.snake.body,
-                    &mut world.particles,
-                    &mut rng,
-                );
-                world.score += devour_score;
+        if world.grid == GridKind::Hex {
+            draw_hex_world(&world);
+        } else {
+            // --- 背景层 ---
+            draw_background();
+            draw_border_and_grid(world.board());
+
+            // --- 地面层 ---
+            draw_blood_stains(&world.blood_stains, world.game_time);
+            draw_portals(&world.portal_manager.portals, world.game_time);
+            draw_hazards(&world.hazards, world.game_time);
+            draw_fence(world.board());
+
+            // --- 对象层 ---
+            draw_food(world.food);
+            draw_fruits(&world.fruits, &world.registry, world.game_time);
+            draw_dropped_foods(&world.ai_manager.dropped_foods, world.game_time);
+
+            // --- 特效层 ---
+            if !world.settings.reduced_motion {
+                draw_afterimages(&world.afterimages, world.game_time);
             }
 
-            // --- 更新 Buff 计时器 ---
-            world.buff_state.update(dt);
-            
-            // --- 更新炸弹状态（使用 BombManager） ---
-            let bomb_result = BombManager::update(
-                &mut world.buff_state,
-                &world.snake.body,
-                dt,
-                &mut rng,
-            );
-            
-            // 处理炸弹爆炸结果
-            if let Some(truncate_pos) = bomb_result.truncate_to {
-                // 添加爆炸粒子
-                world.particles.extend(bomb_result.particles);
-                // 截断蛇身
-                world.snake.body.truncate(truncate_pos);
-                // 检查游戏结束
-                if bomb_result.game_over || world.snake.body.len() < 3 {
-                    world.state = GameState::GameOver;
+            // --- 玩家的蛇（含护盾、幽灵光环） ---
+            for i in 0..world.players.len() {
+                if world.players[i].downed {
+                    continue;
                 }
-            }
-            
-            // --- 炸弹后遗症掉血（使用 BombManager） ---
-            let (need_bleed, bleed_game_over) = BombManager::update_after_effect(
-                &mut world.buff_state,
-                world.snake.body.len(),
-                dt,
-            );
-            if need_bleed {
-                // 获取尾部位置用于生成血迹
-                if let Some(&tail_pos) = world.snake.body.last() {
-                    let center = vec2(
-                        tail_pos.x as f32 * CELL + CELL / 2.0,
-                        tail_pos.y as f32 * CELL + CELL / 2.0,
+                let blend = world.blend(i);
+                let player = &mut world.players[i];
+                if player.buff_state.shield_active {
+                    draw_shield_effect(player.snake.body_slice(), world.game_time);
+                }
+                if player.buff_state.ghost_active {
+                    draw_ghost_effect(player.snake.body_slice(), world.game_time);
+                }
+                if player.buff_state.sandworm_phase != SandwormPhase::None {
+                    draw_sandworm_mode(player.snake.body_slice(), &player.buff_state, world.game_time);
+                } else {
+                    draw_snake(
+                        &player.snake,
+                        &player.buff_state,
+                        player.damage_state.phase,
+                        world.game_time,
+                        world.topology,
+                        blend,
                     );
-                    // 生成血液粒子
-                    spawn_blood_particles(&mut world.particles, center, &mut rng);
-                    // 留下血迹
-                    world.blood_stains.push(BloodStain {
-                        pos: tail_pos,
-                        spawn_time: world.game_time,
-                        lifetime: 5.0,
-                        size: rng.gen_range(0.6..1.0),
-                        alpha: rng.gen_range(0.5..0.8),
-                    });
                 }
-                world.snake.body.pop();
-            }
-            if bleed_game_over {
-                world.state = GameState::GameOver;
             }
 
-            // --- 冰冻粒子效果 ---
-            if world.buff_state.frozen && rng.gen_bool(0.3) {
-                spawn_freeze_particles(&mut world.particles, &world.snake.body, &mut rng);
-            }
-            
-            // --- 更新 AI 蛇决策 ---
-            world.ai_manager.update_thinking(
-                world.food,
-                &world.fruits,
-                &world.snake.body,
-                world.wrap,
-                dt,
-                &mut rng,
-            );
-            
-            // --- 更新 AI 蛇移动 ---
-            let ai_result = world.ai_manager.update_movement(
-                &mut world.food,
-                &mut world.fruits,
-                &world.snake.body,
-                world.buff_state.can_pass_through(),
-                world.buff_state.ghost_active,  // 幽灵状态：AI蛇可以穿过玩家
-                &mut world.particles,
-                &world.registry,
-                world.wrap,
-                world.game_time,
-                dt,
-                &mut rng,
-            );
-            
-            // AI 蛇撞到玩家导致玩家死亡
-            if ai_result.player_died {
-                world.state = GameState::GameOver;
+            // --- AI 蛇 ---
+            draw_ai_snakes(&world.ai_manager.snakes, world.game_time, world.blend(0));
+
+            // --- 高对比度描边 ---
+            if world.settings.high_contrast {
+                let ai_cells: Vec<IVec2> = world
+                    .ai_manager
+                    .snakes
+                    .iter()
+                    .flat_map(|s| s.body.iter().copied())
+                    .collect();
+                draw_contrast_outlines(&ai_cells, MAGENTA);
+                draw_contrast_outlines(&human_cells(&world.players), WHITE);
+                if world.food != NO_FOOD {
+                    draw_contrast_outlines(&[world.food], YELLOW);
+                }
             }
-            
-            // --- 更新掉落的食物 ---
-            world.ai_manager.update_dropped_foods(world.game_time);
-
-            // --- 更新果实（移除过期并调用 on_expire 回调） ---
-            // 所有过期逻辑都在各果实的 on_expire 回调中处理
-            // 例如：蛇蛋过期时会在其 on_expire 中直接调用 ai_manager.spawn_snake()
-            let _expired_fruits = update_fruits_with_callbacks(
-                &mut world.fruits,
-                &world.registry,
-                &mut world.snake.body,
-                &mut world.snake.dir,
-                &mut world.buff_state,
-                &mut world.damage_state,
-                &mut world.particles,
-                &mut world.score,
-                &mut world.combo_state,
-                &mut world.ai_manager,
-                &mut world.food,
-                world.game_time,
-                &mut rng,
-            );
+        }
 
-            // --- 果实生成（使用 FruitSpawnManager 统一管理） ---
-            // 所有生成规则都在 create_default_spawn_manager() 中声明式配置
-            world.spawn_manager.update(
-                &world.registry,
-                &world.snake.body,
-                &mut world.fruits,
-                world.game_time,
-                world.buff_state.sandworm_active,
-                dt,
-                &mut rng,
-            );
+        // --- 粒子层（减少动态效果时不绘制） ---
+        if !world.settings.reduced_motion {
+            draw_particles(&world.particles);
+        }
 
-            // --- 传送门生成 ---
-            world.portal_spawn_timer += dt;
-            if world.portal_spawn_timer >= 15.0 && world.portals.is_empty() {
-                world.portal_spawn_timer = 0.0;
-                if rng.gen_bool(0.3) {
-                    if let Some(portal) = spawn_portal(
-                        &world.snake.body,
-                        &world.fruits,
-                        world.game_time,
-                        &mut rng,
-                    ) {
-                        world.portals.push(portal);
-                    }
+        // --- 覆盖层 ---
+        // 排行榜和最高分只记录单人成绩
+        let solo = world.party.mode == PartyMode::Solo;
+        let score = world.score();
+        if world.state == GameState::GameOver {
+            if solo && score > world.high_score {
+                world.high_score = score;
+            }
+            if !over_once {
+                match world.outcome {
+                    Some(PartyOutcome::Winner(_)) => sounds.play_victory(),
+                    _ => sounds.play_game_over(),
+                }
+                over_once = true;
+                // 分数能进排行榜就先输入名字
+                if solo && world.leaderboard.qualifies(&world.leaderboard_key(), score) {
+                    finished_state = world.state;
+                    menus.open_name_entry(&mut world.state, score, &world.player_name);
                 }
             }
-
-            // --- 更新传送门和残影 ---
-            update_portals(&mut world.portals, world.game_time);
-            world
-                .afterimages
-                .retain(|a| world.game_time - a.spawn_time < 0.3);
-
-            // --- 更新 Combo 显示 ---
-            world.combo_state.display_timer -= dt;
+            let title = match world.outcome {
+                Some(PartyOutcome::Winner(i)) => format!("{} Wins!", world.players[i].name()),
+                Some(PartyOutcome::Draw) => "Draw".to_string(),
+                Some(PartyOutcome::Wiped) | None => "Game Over".to_string(),
+            };
+            let hint = if net.is_some() { "Esc to leave" } else { "Enter/R to restart, Esc for menu" };
+            draw_overlay(&title, hint);
         }
-
-        // --- 更新粒子 ---
-        update_particles(&mut world.particles, dt);
-
-        // --- 更新受伤动画 ---
-        if world.damage_state.active {
-            update_damage_animation(
-                &mut world.damage_state,
-                &mut world.snake.body,
-                &mut world.particles,
-                &mut world.blood_stains,
-                world.game_time,
-                dt,
-                &mut rng,
-            );
+        if world.state == GameState::Victory {
+            if solo && score > world.high_score {
+                world.high_score = score;
+            }
+            if !over_once {
+                sounds.play_victory();
+                over_once = true;
+                if solo && world.leaderboard.qualifies(&world.leaderboard_key(), score) {
+                    finished_state = world.state;
+                    menus.open_name_entry(&mut world.state, score, &world.player_name);
+                }
+            }
+            draw_victory_overlay(score, VICTORY_BONUS);
         }
 
-        // --- 更新血迹 ---
-        update_blood_stains(&mut world.blood_stains, world.game_time);
-
-        // =====================================================================
-        // 渲染
-        // =====================================================================
-        // 渲染顺序很重要，后绘制的会覆盖先绘制的
-
-        // --- 背景层 ---
-        draw_background();
-        draw_border_and_grid();
-
-        // --- 地面层 ---
-        draw_blood_stains(&world.blood_stains, world.game_time);
-        draw_portals(&world.portals, world.game_time);
-
-        // --- 对象层 ---
-        draw_food(world.food);
-        draw_fruits(&world.fruits, &world.registry, world.game_time);
-        draw_dropped_foods(&world.ai_manager.dropped_foods, world.game_time);
-
-        // --- 计算插值 ---
-        let blend = if world.state == GameState::Playing && !world.buff_state.frozen {
-            (accumulator / tick).clamp(0.0, 1.0)
+        // --- HUD层 ---
+        draw_map_info(world.maps[world.map_index].name, world.topology, world.grid, world.board());
+        if solo {
+            let player = &world.players[0];
+            draw_hud(score, world.high_score, &player.combo_state, &player.buff_state);
         } else {
-            0.0
-        };
-
-        // --- 特效层 ---
-        draw_afterimages(&world.afterimages, world.game_time);
-
-        if world.buff_state.shield_active {
-            draw_shield_effect(&world.snake.body, world.game_time);
+            draw_party_hud(&world.players, &world.party);
+        }
+        if world.settings.teams != TeamMode::Off {
+            let roster: Vec<(Team, usize)> = Team::ALL
+                .into_iter()
+                .map(|team| (team, world.ai_manager.members(team).count()))
+                .filter(|&(_, count)| count > 0)
+                .collect();
+            draw_team_roster(&roster, if solo { 18.0 } else { 72.0 });
         }
-        if world.buff_state.ghost_active {
-            draw_ghost_effect(&world.snake.body, world.game_time);
+        if let Some(boss) = world.ai_manager.boss()
+            && let Some(state) = &boss.boss
+        {
+            draw_boss_health_bar(state.phase, boss.health(), state.max_health, boss.buff_state.frozen, world.game_time);
         }
 
-        // --- 蛇 ---
-        if world.buff_state.sandworm_phase != SandwormPhase::None {
-            draw_sandworm_mode(&world.snake.body, &world.buff_state, world.game_time);
-        } else {
-            draw_snake(
-                &world.snake,
-                &world.buff_state,
-                world.damage_state.phase,
-                world.game_time,
-                world.wrap,
-                blend,
+        // --- 联机状态 ---
+        if let Some(session) = &net {
+            let waiting = !session.lockstep().ready();
+            let line = format!(
+                "{} P{}  tick {}",
+                session.transport_kind().name(),
+                session.local() + 1,
+                session.lockstep().tick()
             );
+            draw_net_status(&line, waiting);
+        }
+        // --- 机器人状态（回复延迟和超时回退次数，回退过时变黄） ---
+        if !world.bots.is_empty() {
+            let first = world.players.len() - world.bots.len();
+            let line = world
+                .bots
+                .iter()
+                .enumerate()
+                .map(|(k, bot)| {
+                    let latency = bot.latency().map_or("-".to_string(), |l| format!("{}ms", l.as_millis()));
+                    format!("P{} bot {}  {} fallback", first + k + 1, latency, bot.fallbacks())
+                })
+                .collect::<Vec<_>>()
+                .join("   ");
+            draw_net_status(&line, world.bots.iter().any(|bot| bot.fallbacks() > 0));
+        }
+        if let Some(err) = &net_error {
+            let title = match err {
+                NetError::Disconnected => "Disconnected",
+                NetError::Desync { .. } => "Desync",
+                _ => "Network Error",
+            };
+            draw_overlay(title, "Esc to leave");
         }
-        
-        // --- AI 蛇 ---
-        draw_ai_snakes(&world.ai_manager.snakes, world.game_time, blend);
 
-        // --- 粒子层 ---
-        draw_particles(&world.particles);
+        // --- 菜单层（标题、设置、暂停等） ---
+        menus.draw(world.state);
 
-        // --- 覆盖层 ---
-        if world.state == GameState::GameOver {
-            if world.score > world.high_score {
-                world.high_score = world.score;
-            }
-            if !over_once {
-                sounds.play_game_over();
-                over_once = true;
-            }
-            draw_overlay("Game Over", "Enter/R to restart, Esc to quit");
-        }
-        if world.state == GameState::Paused {
-            draw_overlay("Paused", "Space to resume, Enter/R to restart");
+        // --- 观战广播 ---
+        if let Some(server) = &mut broadcast {
+            server.update(get_frame_time(), || world.snapshot());
         }
 
-        // --- HUD层 ---
-        draw_hud(
-            world.score,
-            world.high_score,
-            &world.combo_state,
-            &world.buff_state,
-        );
+        // --- 联机大厅 ---
+        if let Some(waiting) = &lobby {
+            draw_overlay("Online Lobby", &waiting.status());
+        }
 
         // --- 等待下一帧 ---
         next_frame().await;
@@ -0,0 +1,165 @@
+//! 观战端
+//!
+//! 只读地连上正在运行的游戏（游戏用 `--broadcast` 启动），绘制实时画面：
+//!
+//! ```text
+//! cargo run --bin snake2d -- --broadcast 127.0.0.1:7878 [--broadcast-delay 10]
+//! cargo run --bin spectator -- [127.0.0.1:7878]
+//! ```
+//!
+//! | 按键 | 功能 |
+//! |------|------|
+//! | Tab | 依次跟随每条蛇 |
+//! | 1-4 | 跟随玩家 P1-P4 |
+//! | WASD / 方向键 | 自由平移 |
+//! | + / - / 滚轮 | 缩放 |
+//! | Space | 回到总览 |
+//! | H | 显示 / 隐藏数据面板 |
+//! | F11 | 全屏 |
+//! | Esc | 退出 |
+//!
+//! 游戏关闭或还没启动时每秒重连一次。
+
+use std::time::{Duration, Instant};
+use macroquad::prelude::*;
+use rtest::broadcast::{SpectatorCamera, SpectatorClient, WorldSnapshot, DEFAULT_BROADCAST_ADDR, PAN_SPEED};
+use rtest::fruits::create_fruit_registry;
+use rtest::render::{cell_center, draw_name_tags, draw_spectator_overlay, draw_spectator_waiting, draw_spectator_world};
+
+/// 断线后的重连间隔
+const RETRY_INTERVAL: Duration = Duration::from_secs(1);
+/// 缩放键每秒的缩放倍数
+const ZOOM_SPEED: f32 = 2.0;
+
+fn window_conf() -> Conf {
+    Conf {
+        window_title: String::from("Snake 2D Spectator"),
+        window_width: 1280,
+        window_height: 720,
+        window_resizable: true,
+        high_dpi: true,
+        ..Default::default()
+    }
+}
+
+/// 本帧的平移方向（WASD / 方向键）
+fn pan_input() -> Vec2 {
+    let mut dir = Vec2::ZERO;
+    if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
+        dir.x -= 1.0;
+    }
+    if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
+        dir.x += 1.0;
+    }
+    if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
+        dir.y -= 1.0;
+    }
+    if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
+        dir.y += 1.0;
+    }
+    dir.normalize_or_zero()
+}
+
+#[macroquad::main(window_conf)]
+async fn main() {
+    let addr = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_BROADCAST_ADDR.to_string());
+    let registry = create_fruit_registry();
+    let mut camera = SpectatorCamera::new();
+    let mut client: Option<SpectatorClient> = None;
+    let mut last_attempt: Option<Instant> = None;
+    let mut snapshot: Option<WorldSnapshot> = None;
+    let mut show_panel = true;
+    let mut fullscreen = false;
+
+    loop {
+        let dt = get_frame_time();
+
+        // --- 连接和接收 ---
+        if client.is_none() && last_attempt.is_none_or(|t| t.elapsed() >= RETRY_INTERVAL) {
+            last_attempt = Some(Instant::now());
+            client = SpectatorClient::connect(&addr).ok();
+        }
+        if let Some(conn) = &mut client {
+            match conn.poll() {
+                Ok(Some(latest)) => snapshot = Some(latest),
+                Ok(None) => {}
+                Err(_) => {
+                    client = None;
+                    snapshot = None;
+                }
+            }
+        }
+
+        // --- 输入 ---
+        if is_key_pressed(KeyCode::Escape) {
+            break;
+        }
+        if is_key_pressed(KeyCode::F11) {
+            fullscreen = !fullscreen;
+            set_fullscreen(fullscreen);
+        }
+        if is_key_pressed(KeyCode::H) {
+            show_panel = !show_panel;
+        }
+        if is_key_pressed(KeyCode::Space) {
+            camera.overview();
+        }
+        let pan = pan_input();
+        if pan != Vec2::ZERO {
+            camera.pan(pan * PAN_SPEED * dt);
+        }
+        if is_key_down(KeyCode::Equal) || is_key_down(KeyCode::KpAdd) {
+            camera.zoom_by(ZOOM_SPEED.powf(dt));
+        }
+        if is_key_down(KeyCode::Minus) || is_key_down(KeyCode::KpSubtract) {
+            camera.zoom_by(ZOOM_SPEED.powf(-dt));
+        }
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            camera.zoom_by(if wheel > 0.0 { 1.1 } else { 1.0 / 1.1 });
+        }
+        if let Some(snap) = &snapshot {
+            if is_key_pressed(KeyCode::Tab) {
+                let names: Vec<&str> = snap.snakes.iter().filter(|s| !s.downed).map(|s| s.name.as_str()).collect();
+                camera.follow_next(&names);
+            }
+            for (i, key) in [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4].into_iter().enumerate() {
+                let name = format!("P{}", i + 1);
+                if is_key_pressed(key) && snap.snake(&name).is_some() {
+                    camera.follow(&name);
+                }
+            }
+        }
+
+        // --- 镜头 ---
+        let target = snapshot.as_ref().and_then(|snap| {
+            let name = camera.followed()?;
+            let snake = snap.snake(name).filter(|s| !s.downed)?;
+            Some(cell_center(snake.head()?, snap.grid_kind()))
+        });
+        camera.update(target, dt);
+
+        // --- 渲染 ---
+        clear_background(Color::new(0.08, 0.08, 0.1, 1.0));
+        match &snapshot {
+            Some(snap) => {
+                let camera2d = camera.camera2d(vec2(screen_width(), screen_height()));
+                set_camera(&camera2d);
+                draw_spectator_world(snap, &registry, camera.followed());
+                set_default_camera();
+                draw_name_tags(snap, &camera2d);
+                draw_spectator_overlay(snap, &camera.label(), camera.followed(), show_panel);
+            }
+            None => {
+                let status = if client.is_some() {
+                    format!("Connected to {}, waiting for the first frame", addr)
+                } else {
+                    format!("Waiting for a game broadcasting on {}", addr)
+                };
+                draw_spectator_waiting(&status);
+            }
+        }
+
+        next_frame().await;
+    }
+}
@@ -0,0 +1,108 @@
+//! 无界面的蜂群模拟
+//!
+//! 几百条 AI 蛇在大棋盘上抢食物（见 [`rtest::game::swarm`]），每秒打印一行存活、死亡和每帧耗时：
+//!
+//! ```text
+//! cargo run --release --bin swarm -- [--width 160] [--height 120] [--snakes 200] [--fruits 64]
+//!                                    [--seconds 30] [--threads N] [--seed N]
+//! ```
+//!
+//! `--threads` 限定决策用的 rayon 线程数（默认按核数），同一种子的结果与线程数无关。
+
+use std::time::{Duration, Instant};
+use rtest::game::{Swarm, SwarmConfig, SWARM_MAX_SNAKES};
+
+/// 固定帧时间（秒）
+const FRAME: f32 = 1.0 / 60.0;
+/// 默认模拟的秒数
+const DEFAULT_SECONDS: u32 = 30;
+
+const USAGE: &str = "usage: swarm [--width N] [--height N] [--snakes N] [--fruits N] [--seconds N] [--threads N] [--seed N]";
+
+/// 命令行参数
+struct Options {
+    config: SwarmConfig,
+    seconds: u32,
+    threads: Option<usize>,
+}
+
+fn main() {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => exit_with(&err),
+    };
+    if let Some(threads) = options.threads
+        && let Err(err) = rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()
+    {
+        exit_with(&err.to_string());
+    }
+    run(&options);
+}
+
+fn exit_with(msg: &str) -> ! {
+    eprintln!("{}", msg);
+    std::process::exit(1);
+}
+
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let defaults = SwarmConfig::default();
+    let (mut width, mut height) = (defaults.board.width, defaults.board.height);
+    let (mut snakes, mut fruits, mut seed) = (defaults.snakes, defaults.fruits, defaults.seed);
+    let mut options = Options { config: defaults, seconds: DEFAULT_SECONDS, threads: None };
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or_else(|| format!("{} needs a value\n{}", flag, USAGE))?;
+        let bad = || format!("{} expects a number, got {:?}", flag, value);
+        match flag.as_str() {
+            "--width" => width = value.parse().map_err(|_| bad())?,
+            "--height" => height = value.parse().map_err(|_| bad())?,
+            "--snakes" => snakes = value.parse().map_err(|_| bad())?,
+            "--fruits" => fruits = value.parse().map_err(|_| bad())?,
+            "--seconds" => options.seconds = value.parse().map_err(|_| bad())?,
+            "--threads" => options.threads = Some(value.parse().ok().filter(|&n| n > 0).ok_or_else(bad)?),
+            "--seed" => seed = value.parse().map_err(|_| bad())?,
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    if snakes > SWARM_MAX_SNAKES {
+        eprintln!("at most {} snakes, using {}", SWARM_MAX_SNAKES, SWARM_MAX_SNAKES);
+    }
+    options.config = defaults.with_board(width, height).with_snakes(snakes).with_fruits(fruits).with_seed(seed);
+    Ok(options)
+}
+
+fn run(options: &Options) {
+    let config = options.config;
+    println!(
+        "{}x{} board, {} snakes, {} fruits, {} threads, seed {}",
+        config.board.width,
+        config.board.height,
+        config.snakes,
+        config.fruits,
+        rayon::current_num_threads(),
+        config.seed,
+    );
+    let mut swarm = Swarm::new(config);
+    let frames_per_second = (1.0 / FRAME).round() as u32;
+    for second in 1..=options.seconds {
+        let mut busy = Duration::ZERO;
+        let mut slowest = Duration::ZERO;
+        for _ in 0..frames_per_second {
+            let start = Instant::now();
+            swarm.step(FRAME);
+            let took = start.elapsed();
+            busy += took;
+            slowest = slowest.max(took);
+        }
+        let longest = swarm.ai.snakes.iter().map(|s| s.len()).max().unwrap_or(0);
+        println!(
+            "{:>4}s  alive {:>3}  deaths {:>5}  longest {:>3}  frame avg {:>6.2} ms  max {:>6.2} ms",
+            second,
+            swarm.ai.snakes.len(),
+            swarm.deaths,
+            longest,
+            busy.as_secs_f64() * 1000.0 / f64::from(frames_per_second),
+            slowest.as_secs_f64() * 1000.0,
+        );
+    }
+}
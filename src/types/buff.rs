@@ -21,6 +21,8 @@ pub enum SandwormPhase {
     FilledFlashing,
     /// 吞噬阶段（坍缩动画）
     Consuming,
+    /// 横冲阶段（只有 AI 沙虫使用：变色完成后留在棋盘上追吃玩家的尾巴）
+    Rampaging,
 }
 
 /// Buff状态管理结构体
@@ -76,6 +78,10 @@ pub struct BuffState {
     pub bomb_state: super::bomb::BombState,
     /// 炸弹爆炸后遗症
     pub bomb_after_effect: super::bomb::BombAfterEffect,
+
+    // === 宠物蛋孵化 ===
+    /// 宠物蛋在体内的孵化状态
+    pub egg_state: super::egg::EggState,
 }
 
 impl Default for BuffState {
@@ -110,6 +116,7 @@ impl Default for BuffState {
             slime_timer: 0.0,
             bomb_state: super::bomb::BombState::default(),
             bomb_after_effect: super::bomb::BombAfterEffect::default(),
+            egg_state: super::egg::EggState::default(),
         }
     }
 }
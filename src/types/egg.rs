@@ -0,0 +1,46 @@
+//! 宠物蛋孵化状态相关类型定义
+
+/// 宠物蛋在蛇体内的孵化状态
+///
+/// 和 [`BombState`](super::BombState) 一样从蛇头沿蛇身往后移动，到达蛇尾时孵化出宠物蛇
+#[derive(Clone, Default)]
+pub struct EggState {
+    /// 是否有蛋在体内
+    pub active: bool,
+    /// 蛋当前在蛇身的位置索引（0=头部）
+    pub position: usize,
+    /// 蛋移动计时器
+    pub move_timer: f32,
+    /// 每格移动间隔（秒）
+    pub move_interval: f32,
+}
+
+impl EggState {
+    /// 开始孵化（刚吞食时）
+    pub fn activate(&mut self) {
+        self.active = true;
+        self.position = 0;
+        self.move_timer = 0.0;
+        self.move_interval = 0.25; // 每0.25秒移动一格
+    }
+
+    /// 清除孵化状态
+    pub fn clear(&mut self) {
+        self.active = false;
+        self.position = 0;
+        self.move_timer = 0.0;
+    }
+
+    /// 孵化进度（0.0 ~ 1.0，到达蛇尾为 1.0）
+    pub fn progress(&self, snake_len: usize) -> f32 {
+        if snake_len <= 1 {
+            return 1.0;
+        }
+        (self.position as f32 / (snake_len - 1) as f32).min(1.0)
+    }
+
+    /// 检查是否应该孵化（到达蛇尾）
+    pub fn should_hatch(&self, snake_len: usize) -> bool {
+        self.active && self.position + 1 >= snake_len
+    }
+}
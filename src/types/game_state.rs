@@ -3,12 +3,41 @@
 /// 游戏状态枚举
 ///
 /// 控制游戏的主循环行为：
+/// - Title / ModeSelect / MapSelect / Settings / Credits: 菜单界面，游戏逻辑不更新
 /// - Playing: 正常游戏进行中，响应输入和更新逻辑
-/// - Paused: 暂停状态，显示暂停覆盖层
+/// - Paused: 暂停状态，显示暂停菜单
 /// - GameOver: 游戏结束，显示结束画面
+/// - Victory: 蛇填满了整个棋盘（完美通关），显示胜利画面
+/// - NameEntry: 分数进入排行榜，输入名字
+/// - Leaderboard: 排行榜界面
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum GameState {
+    Title,
+    ModeSelect,
+    MapSelect,
+    Settings,
+    Credits,
     Playing,
     Paused,
     GameOver,
+    Victory,
+    NameEntry,
+    Leaderboard,
+}
+
+impl GameState {
+    /// 是否是菜单界面（含暂停菜单）
+    pub fn is_menu(&self) -> bool {
+        matches!(
+            self,
+            GameState::Title
+                | GameState::ModeSelect
+                | GameState::MapSelect
+                | GameState::Settings
+                | GameState::Credits
+                | GameState::Paused
+                | GameState::NameEntry
+                | GameState::Leaderboard
+        )
+    }
 }
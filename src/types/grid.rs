@@ -0,0 +1,396 @@
+//! 棋盘几何定义
+//!
+//! 所有"位置 + 方向"的运算都通过 [`Grid`] 完成，
+//! 这样同一套移动、碰撞、生成和 AI 逻辑可以运行在不同几何的棋盘上。
+//!
+//! ## 坐标约定
+//!
+//! 位置始终是 `0..GRID_W × 0..GRID_H` 的存储坐标（列, 行），
+//! 所以碰撞、生成、障碍物等只比较位置的逻辑与几何无关。
+//! 方向是几何相关的向量，但总满足"反方向 = 取负"。
+//!
+//! ## 六边形棋盘
+//!
+//! 平顶六边形，奇数列下沉半格（odd-q 偏移坐标）。
+//! 方向使用轴向坐标 (q, r)：
+//!
+//! ```text
+//!        N (0,-1)
+//! NW (-1,0)   NE (1,-1)
+//! SW (-1,1)   SE (1,0)
+//!        S (0,1)
+//! ```
+
+use macroquad::prelude::*;
+use super::{BoardDims, Topology};
+
+/// 四方向（上下左右）
+pub const SQUARE_DIRECTIONS: [IVec2; 4] = [
+    IVec2::new(0, -1),
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 0),
+];
+
+/// 八方向（顺时针：N, NE, E, SE, S, SW, W, NW）
+pub const OCTILE_DIRECTIONS: [IVec2; 8] = [
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+    IVec2::new(1, 0),
+    IVec2::new(1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, -1),
+];
+
+/// 六边形六方向（顺时针：N, NE, SE, S, SW, NW）
+pub const HEX_DIRECTIONS: [IVec2; 6] = [
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, 0),
+];
+
+/// 六边形方向常量（轴向坐标）
+pub mod hex_dir {
+    use macroquad::prelude::IVec2;
+
+    pub const N: IVec2 = IVec2::new(0, -1);
+    pub const NE: IVec2 = IVec2::new(1, -1);
+    pub const SE: IVec2 = IVec2::new(1, 0);
+    pub const S: IVec2 = IVec2::new(0, 1);
+    pub const SW: IVec2 = IVec2::new(-1, 1);
+    pub const NW: IVec2 = IVec2::new(-1, 0);
+}
+
+/// 棋盘几何抽象
+///
+/// 实现者只需提供方向列表、相邻格、距离和镜像，
+/// 其余方法都有基于这些的默认实现。
+pub trait Grid {
+    /// 所有可移动方向（顺时针顺序，相对转向依赖该顺序）
+    fn directions(&self) -> &'static [IVec2];
+
+    /// 相邻格（不做边界处理）
+    fn neighbor(&self, pos: IVec2, dir: IVec2) -> IVec2;
+
+    /// 两格之间的最少步数（不考虑环绕）
+    fn distance(&self, a: IVec2, b: IVec2) -> i32;
+
+    /// 上下翻转后的方向（镜像环绕边界使用）
+    fn mirror_y(&self, dir: IVec2) -> IVec2;
+
+    /// 斜向移动时夹在两侧的两个正交格（未做边界处理）
+    ///
+    /// 只有允许斜向移动的几何才返回 Some，用于判定"挤过两节身体之间"
+    fn corner_cells(&self, _pos: IVec2, _dir: IVec2) -> Option<[IVec2; 2]> {
+        None
+    }
+
+    /// 是否为反方向（不能直接掉头）
+    fn is_reverse(&self, dir: IVec2, other: IVec2) -> bool {
+        dir != IVec2::ZERO && dir == -other
+    }
+
+    /// 相邻两格之间的方向；不相邻返回 None
+    fn direction_between(&self, from: IVec2, to: IVec2) -> Option<IVec2> {
+        self.directions().iter().copied().find(|&d| self.neighbor(from, d) == to)
+    }
+
+    /// 朝目标前进的首选方向（已在目标上返回 None）
+    fn toward(&self, from: IVec2, to: IVec2) -> Option<IVec2> {
+        if from == to {
+            return None;
+        }
+        self.directions()
+            .iter()
+            .copied()
+            .min_by_key(|&d| self.distance(self.neighbor(from, d), to))
+    }
+
+    /// 相对转向：`turns` 为正顺时针，为负逆时针
+    fn turn(&self, dir: IVec2, turns: i32) -> IVec2 {
+        let dirs = self.directions();
+        match dirs.iter().position(|&d| d == dir) {
+            Some(i) => dirs[(i as i32 + turns).rem_euclid(dirs.len() as i32) as usize],
+            None => dir,
+        }
+    }
+
+    /// 按地图拓扑走一步
+    ///
+    /// # 返回
+    /// 新位置和（镜像环绕后可能翻转的）新方向；撞墙返回 None
+    fn step(&self, topology: Topology, pos: IVec2, dir: IVec2) -> Option<(IVec2, IVec2)> {
+        self.step_on(topology, BoardDims::DEFAULT, pos, dir)
+    }
+
+    /// 在 `board` 上按地图拓扑走一步
+    fn step_on(&self, topology: Topology, board: BoardDims, pos: IVec2, dir: IVec2) -> Option<(IVec2, IVec2)> {
+        let (next, mirrored) = topology.wrap_pos_on(board, self.neighbor(pos, dir))?;
+        Some((next, if mirrored { self.mirror_y(dir) } else { dir }))
+    }
+}
+
+/// 方格棋盘（四方向）
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SquareGrid;
+
+impl Grid for SquareGrid {
+    fn directions(&self) -> &'static [IVec2] {
+        &SQUARE_DIRECTIONS
+    }
+
+    fn neighbor(&self, pos: IVec2, dir: IVec2) -> IVec2 {
+        pos + dir
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        (a.x - b.x).abs() + (a.y - b.y).abs()
+    }
+
+    fn mirror_y(&self, dir: IVec2) -> IVec2 {
+        ivec2(dir.x, -dir.y)
+    }
+
+    /// 优先沿距离更远的轴前进
+    fn toward(&self, from: IVec2, to: IVec2) -> Option<IVec2> {
+        let dx = to.x - from.x;
+        let dy = to.y - from.y;
+        if dx.abs() > dy.abs() {
+            Some(ivec2(dx.signum(), 0))
+        } else if dy != 0 {
+            Some(ivec2(0, dy.signum()))
+        } else {
+            None
+        }
+    }
+}
+
+/// 八方向方格棋盘（允许斜向移动）
+///
+/// ## 斜向挤过规则
+///
+/// 斜向移动会从两个正交相邻格之间穿过（见 [`Grid::corner_cells`]）。
+/// 如果这两个格子都被蛇身占据（不论属于哪条蛇），视为撞到蛇身：
+///
+/// ```text
+/// . B        . B
+/// A .   →    A H     H 从左下斜向右上：A、B 都是蛇身 → 碰撞
+/// ```
+///
+/// 只有一侧被占据时可以正常斜向通过。
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OctileGrid;
+
+impl Grid for OctileGrid {
+    fn directions(&self) -> &'static [IVec2] {
+        &OCTILE_DIRECTIONS
+    }
+
+    fn neighbor(&self, pos: IVec2, dir: IVec2) -> IVec2 {
+        pos + dir
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        (a.x - b.x).abs().max((a.y - b.y).abs())
+    }
+
+    fn mirror_y(&self, dir: IVec2) -> IVec2 {
+        ivec2(dir.x, -dir.y)
+    }
+
+    fn corner_cells(&self, pos: IVec2, dir: IVec2) -> Option<[IVec2; 2]> {
+        (dir.x != 0 && dir.y != 0).then(|| [pos + ivec2(dir.x, 0), pos + ivec2(0, dir.y)])
+    }
+
+    /// 直接朝目标斜向前进
+    fn toward(&self, from: IVec2, to: IVec2) -> Option<IVec2> {
+        let d = to - from;
+        (d != IVec2::ZERO).then(|| ivec2(d.x.signum(), d.y.signum()))
+    }
+}
+
+/// 六边形棋盘（平顶，odd-q 偏移存储）
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HexGrid;
+
+impl HexGrid {
+    /// 偏移坐标 → 轴向坐标
+    pub fn to_axial(pos: IVec2) -> IVec2 {
+        ivec2(pos.x, pos.y - (pos.x - (pos.x & 1)) / 2)
+    }
+
+    /// 轴向坐标 → 偏移坐标
+    pub fn from_axial(axial: IVec2) -> IVec2 {
+        ivec2(axial.x, axial.y + (axial.x - (axial.x & 1)) / 2)
+    }
+}
+
+impl Grid for HexGrid {
+    fn directions(&self) -> &'static [IVec2] {
+        &HEX_DIRECTIONS
+    }
+
+    fn neighbor(&self, pos: IVec2, dir: IVec2) -> IVec2 {
+        Self::from_axial(Self::to_axial(pos) + dir)
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        let d = Self::to_axial(a) - Self::to_axial(b);
+        (d.x.abs() + d.y.abs() + (d.x + d.y).abs()) / 2
+    }
+
+    fn mirror_y(&self, dir: IVec2) -> IVec2 {
+        ivec2(dir.x, -dir.x - dir.y)
+    }
+}
+
+/// 棋盘几何选择
+///
+/// 可复制的枚举，方便存放在蛇、管理器和世界状态中
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GridKind {
+    /// 方格四方向
+    #[default]
+    Square,
+    /// 方格八方向（可斜向移动）
+    Octile,
+    /// 六边形六方向
+    Hex,
+}
+
+impl GridKind {
+    /// 所有几何（G 键循环顺序）
+    pub const ALL: [GridKind; 3] = [GridKind::Square, GridKind::Octile, GridKind::Hex];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            GridKind::Square => "Square",
+            GridKind::Octile => "8-Way",
+            GridKind::Hex => "Hex",
+        }
+    }
+
+    /// 下一个几何（循环）
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&g| g == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 具体的几何实现
+    fn inner(&self) -> &'static dyn Grid {
+        match self {
+            GridKind::Square => &SquareGrid,
+            GridKind::Octile => &OctileGrid,
+            GridKind::Hex => &HexGrid,
+        }
+    }
+}
+
+impl Grid for GridKind {
+    fn directions(&self) -> &'static [IVec2] {
+        self.inner().directions()
+    }
+
+    fn neighbor(&self, pos: IVec2, dir: IVec2) -> IVec2 {
+        self.inner().neighbor(pos, dir)
+    }
+
+    fn distance(&self, a: IVec2, b: IVec2) -> i32 {
+        self.inner().distance(a, b)
+    }
+
+    fn mirror_y(&self, dir: IVec2) -> IVec2 {
+        self.inner().mirror_y(dir)
+    }
+
+    fn toward(&self, from: IVec2, to: IVec2) -> Option<IVec2> {
+        self.inner().toward(from, to)
+    }
+
+    fn corner_cells(&self, pos: IVec2, dir: IVec2) -> Option<[IVec2; 2]> {
+        self.inner().corner_cells(pos, dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_axial_roundtrip() {
+        for x in -3..6 {
+            for y in -3..6 {
+                let pos = ivec2(x, y);
+                assert_eq!(HexGrid::from_axial(HexGrid::to_axial(pos)), pos);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_neighbors_are_distance_one() {
+        let grid = HexGrid;
+        for pos in [ivec2(4, 4), ivec2(5, 4)] {
+            for &dir in grid.directions() {
+                let n = grid.neighbor(pos, dir);
+                assert_eq!(grid.distance(pos, n), 1);
+                // 反方向回到原位
+                assert_eq!(grid.neighbor(n, -dir), pos);
+                assert_eq!(grid.direction_between(pos, n), Some(dir));
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_mirror_swaps_north_south() {
+        let grid = HexGrid;
+        assert_eq!(grid.mirror_y(hex_dir::N), hex_dir::S);
+        assert_eq!(grid.mirror_y(hex_dir::NE), hex_dir::SE);
+        assert_eq!(grid.mirror_y(hex_dir::SW), hex_dir::NW);
+    }
+
+    #[test]
+    fn test_turn_cycles_directions() {
+        let grid = GridKind::Hex;
+        assert_eq!(grid.turn(hex_dir::N, 1), hex_dir::NE);
+        assert_eq!(grid.turn(hex_dir::N, -1), hex_dir::NW);
+        assert_eq!(GridKind::Square.turn(ivec2(0, -1), 1), ivec2(1, 0));
+    }
+
+    #[test]
+    fn test_square_step_matches_topology() {
+        let grid = GridKind::Square;
+        for topology in Topology::ALL {
+            for pos in [ivec2(0, 0), ivec2(31, 23), ivec2(5, 7)] {
+                for &dir in grid.directions() {
+                    assert_eq!(grid.step(topology, pos, dir), topology.step(pos, dir));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_octile_corner_cells_only_for_diagonals() {
+        let grid = GridKind::Octile;
+        assert_eq!(grid.corner_cells(ivec2(3, 3), ivec2(1, 0)), None);
+        assert_eq!(
+            grid.corner_cells(ivec2(3, 3), ivec2(1, -1)),
+            Some([ivec2(4, 3), ivec2(3, 2)])
+        );
+        assert!(grid.is_reverse(ivec2(1, -1), ivec2(-1, 1)));
+        assert_eq!(grid.toward(ivec2(0, 0), ivec2(5, 2)), Some(ivec2(1, 1)));
+    }
+
+    #[test]
+    fn test_hex_toward_reduces_distance() {
+        let grid = GridKind::Hex;
+        let (from, to) = (ivec2(3, 3), ivec2(10, 1));
+        let dir = grid.toward(from, to).unwrap();
+        assert!(grid.distance(grid.neighbor(from, dir), to) < grid.distance(from, to));
+    }
+}
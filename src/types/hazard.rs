@@ -0,0 +1,214 @@
+//! 动态障碍物相关类型定义
+//!
+//! 旋转横杆、轨道滑块和定时墙。所有障碍物的位置都是游戏时间的纯函数，
+//! 因此可以直接查询"下一个 tick"的位置，供 AI 预判使用。
+
+use macroquad::prelude::*;
+
+/// 旋转横杆的 8 个离散朝向（顺时针，每步 45°）
+pub const BAR_DIRECTIONS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(1, 1),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, -1),
+    IVec2::new(0, -1),
+    IVec2::new(1, -1),
+];
+
+/// 定时墙阶段
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WallPhase {
+    /// 打开（可通行）
+    Open,
+    /// 即将关闭的预警阶段，携带进度 0.0-1.0
+    Warning(f32),
+    /// 关闭（与静态墙相同）
+    Closed,
+}
+
+/// 动态障碍物类型
+#[derive(Clone, Debug)]
+pub enum HazardKind {
+    /// 旋转横杆
+    ///
+    /// 以 `pivot` 为中心向两侧各延伸 `arm` 格，每 `step_interval` 秒转动 45°
+    RotatingBar {
+        pivot: IVec2,
+        arm: i32,
+        step_interval: f32,
+        clockwise: bool,
+    },
+    /// 轨道滑块
+    ///
+    /// 沿 `rail` 往返移动，每 `step_interval` 秒前进一格，占据 `size` 格
+    SlidingBlock {
+        rail: Vec<IVec2>,
+        size: usize,
+        step_interval: f32,
+    },
+    /// 定时墙
+    ///
+    /// 周期：打开 `open_duration` → 预警 `warn_duration` → 关闭 `closed_duration`
+    TimedWall {
+        cells: Vec<IVec2>,
+        open_duration: f32,
+        warn_duration: f32,
+        closed_duration: f32,
+        /// 周期相位偏移（秒），用于错开多面墙
+        phase_offset: f32,
+    },
+}
+
+/// 动态障碍物
+#[derive(Clone, Debug)]
+pub struct Hazard {
+    /// 障碍物类型和参数
+    pub kind: HazardKind,
+    /// 障碍物颜色
+    pub color: Color,
+}
+
+impl Hazard {
+    /// 创建旋转横杆
+    pub fn rotating_bar(pivot: IVec2, arm: i32, step_interval: f32, clockwise: bool) -> Self {
+        Hazard {
+            kind: HazardKind::RotatingBar { pivot, arm, step_interval, clockwise },
+            color: Color::new(0.85, 0.35, 0.1, 1.0),
+        }
+    }
+
+    /// 创建轨道滑块
+    pub fn sliding_block(rail: Vec<IVec2>, size: usize, step_interval: f32) -> Self {
+        Hazard {
+            kind: HazardKind::SlidingBlock { rail, size: size.max(1), step_interval },
+            color: Color::new(0.55, 0.55, 0.65, 1.0),
+        }
+    }
+
+    /// 创建定时墙
+    pub fn timed_wall(
+        cells: Vec<IVec2>,
+        open_duration: f32,
+        warn_duration: f32,
+        closed_duration: f32,
+    ) -> Self {
+        Hazard {
+            kind: HazardKind::TimedWall {
+                cells,
+                open_duration,
+                warn_duration,
+                closed_duration,
+                phase_offset: 0.0,
+            },
+            color: Color::new(0.6, 0.6, 0.6, 1.0),
+        }
+    }
+
+    /// 创建永久墙（始终关闭的定时墙，用于缩小棋盘的围栏）
+    pub fn static_wall(cells: Vec<IVec2>) -> Self {
+        Hazard::timed_wall(cells, 0.0, 0.0, 1.0).with_color(Color::new(0.25, 0.25, 0.3, 1.0))
+    }
+
+    /// 设置定时墙相位偏移（对其他类型无效）
+    pub fn with_phase_offset(mut self, offset: f32) -> Self {
+        if let HazardKind::TimedWall { phase_offset, .. } = &mut self.kind {
+            *phase_offset = offset;
+        }
+        self
+    }
+
+    /// 设置颜色
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// 计算指定时间障碍物占据的格子（实心部分）
+    pub fn cells_at(&self, time: f32) -> Vec<IVec2> {
+        match &self.kind {
+            HazardKind::RotatingBar { pivot, arm, step_interval, clockwise } => {
+                let dir = bar_direction(time, *step_interval, *clockwise);
+                (-arm..=*arm).map(|k| *pivot + dir * k).collect()
+            }
+            HazardKind::SlidingBlock { rail, size, step_interval } => {
+                if rail.is_empty() {
+                    return vec![];
+                }
+                let start = rail_index(time, *step_interval, rail.len(), *size);
+                rail.iter().skip(start).take(*size).copied().collect()
+            }
+            HazardKind::TimedWall { cells, .. } => {
+                if self.wall_phase(time) == WallPhase::Closed {
+                    cells.clone()
+                } else {
+                    vec![]
+                }
+            }
+        }
+    }
+
+    /// 计算定时墙阶段（非定时墙始终视为关闭）
+    pub fn wall_phase(&self, time: f32) -> WallPhase {
+        match &self.kind {
+            HazardKind::TimedWall { open_duration, warn_duration, closed_duration, phase_offset, .. } => {
+                let period = open_duration + warn_duration + closed_duration;
+                if period <= 0.0 {
+                    return WallPhase::Closed;
+                }
+                let t = (time + phase_offset).rem_euclid(period);
+                if t < *open_duration {
+                    WallPhase::Open
+                } else if t < open_duration + warn_duration {
+                    WallPhase::Warning((t - open_duration) / warn_duration.max(f32::EPSILON))
+                } else {
+                    WallPhase::Closed
+                }
+            }
+            _ => WallPhase::Closed,
+        }
+    }
+
+    /// 障碍物可能占据的所有格子（用于生成食物时避开）
+    pub fn footprint(&self) -> Vec<IVec2> {
+        match &self.kind {
+            HazardKind::RotatingBar { pivot, arm, .. } => {
+                let mut cells = vec![*pivot];
+                for dir in BAR_DIRECTIONS {
+                    for k in 1..=*arm {
+                        cells.push(*pivot + dir * k);
+                    }
+                }
+                cells
+            }
+            HazardKind::SlidingBlock { rail, .. } => rail.clone(),
+            HazardKind::TimedWall { cells, .. } => cells.clone(),
+        }
+    }
+}
+
+/// 计算旋转横杆在指定时间的朝向
+fn bar_direction(time: f32, step_interval: f32, clockwise: bool) -> IVec2 {
+    let steps = if step_interval > 0.0 {
+        (time / step_interval).floor() as i64
+    } else {
+        0
+    };
+    // 横杆关于支点对称，转 180° 与原位重合，因此只需 4 个不同朝向
+    let idx = steps.rem_euclid(4) as usize;
+    let idx = if clockwise { idx } else { (8 - idx) % 8 };
+    BAR_DIRECTIONS[idx]
+}
+
+/// 计算滑块在轨道上的起始索引（往返运动）
+fn rail_index(time: f32, step_interval: f32, rail_len: usize, size: usize) -> usize {
+    let span = rail_len.saturating_sub(size);
+    if span == 0 || step_interval <= 0.0 {
+        return 0;
+    }
+    let steps = (time / step_interval).floor() as i64;
+    let cycle = (span * 2) as i64;
+    let t = steps.rem_euclid(cycle) as usize;
+    if t <= span { t } else { cycle as usize - t }
+}
@@ -10,6 +10,11 @@ pub mod portal;
 pub mod combo;
 pub mod fruit;
 pub mod bomb;
+pub mod egg;
+pub mod hazard;
+pub mod topology;
+pub mod grid;
+pub mod settings;
 
 pub use game_state::*;
 pub use particle::*;
@@ -19,3 +24,8 @@ pub use portal::*;
 pub use combo::*;
 pub use fruit::*;
 pub use bomb::*;
+pub use egg::*;
+pub use hazard::*;
+pub use topology::*;
+pub use grid::*;
+pub use settings::*;
@@ -5,9 +5,28 @@ use macroquad::prelude::*;
 /// 传送门存在时间（秒）
 pub const PORTAL_LIFETIME: f32 = 20.0;
 
+/// 传送门颜色池
+///
+/// 同时存在的多对传送门使用不同颜色区分
+pub const PORTAL_COLORS: [Color; 6] = [
+    Color { r: 0.5, g: 0.0, b: 1.0, a: 1.0 }, // 紫色
+    Color { r: 0.0, g: 1.0, b: 0.5, a: 1.0 }, // 青绿
+    Color { r: 1.0, g: 0.5, b: 0.0, a: 1.0 }, // 橙色
+    Color { r: 0.0, g: 0.6, b: 1.0, a: 1.0 }, // 天蓝
+    Color { r: 1.0, g: 0.2, b: 0.6, a: 1.0 }, // 玫红
+    Color { r: 0.9, g: 0.9, b: 0.2, a: 1.0 }, // 柠檬黄
+];
+
 /// 传送门结构体
 ///
 /// 成对出现的传送门，蛇进入一个会从另一个出来
+///
+/// ## 变体
+///
+/// - 临时传送门：`lifetime > 0`，到时消失
+/// - 永久传送门：`lifetime == 0`，由地图定义
+/// - 单向传送门：`one_way == true`，只能从 A 进、从 B 出
+/// - 定向传送门：`exit_dir` 为 `Some`，出门后强制改变移动方向
 #[derive(Clone)]
 pub struct Portal {
     /// 传送门A位置
@@ -18,6 +37,83 @@ pub struct Portal {
     pub color: Color,
     /// 生成时间
     pub spawn_time: f32,
-    /// 存在时长
+    /// 存在时长（0=永久）
     pub lifetime: f32,
+    /// 是否单向（只能从 A 进入）
+    pub one_way: bool,
+    /// 出门方向（None=保持原方向）
+    pub exit_dir: Option<IVec2>,
+}
+
+impl Portal {
+    /// 创建临时传送门
+    pub fn new(pos_a: IVec2, pos_b: IVec2, color: Color, spawn_time: f32, lifetime: f32) -> Self {
+        Portal {
+            pos_a,
+            pos_b,
+            color,
+            spawn_time,
+            lifetime,
+            one_way: false,
+            exit_dir: None,
+        }
+    }
+
+    /// 创建永久传送门（地图传送门）
+    pub fn permanent(pos_a: IVec2, pos_b: IVec2, color: Color) -> Self {
+        Self::new(pos_a, pos_b, color, 0.0, 0.0)
+    }
+
+    /// 设为单向传送门
+    pub fn one_way(mut self) -> Self {
+        self.one_way = true;
+        self
+    }
+
+    /// 设置出门方向
+    pub fn with_exit_dir(mut self, dir: IVec2) -> Self {
+        self.exit_dir = Some(dir);
+        self
+    }
+
+    /// 是否为永久传送门
+    pub fn is_permanent(&self) -> bool {
+        self.lifetime <= 0.0
+    }
+
+    /// 检查传送门是否已过期
+    pub fn is_expired(&self, current_time: f32) -> bool {
+        !self.is_permanent() && current_time - self.spawn_time >= self.lifetime
+    }
+
+    /// 计算从 `pos` 以方向 `dir` 进入时的出口
+    ///
+    /// 单向传送门的 B 端只是出口，进入 B 不会触发传送
+    pub fn exit_for(&self, pos: IVec2, dir: IVec2) -> Option<PortalExit> {
+        let to = if pos == self.pos_a {
+            self.pos_b
+        } else if pos == self.pos_b && !self.one_way {
+            self.pos_a
+        } else {
+            return None;
+        };
+        Some(PortalExit {
+            from: pos,
+            to,
+            dir: self.exit_dir.unwrap_or(dir),
+        })
+    }
+}
+
+/// 传送结果
+///
+/// 蛇头踏入入口后，下一步会出现在 `to` 并以 `dir` 继续移动
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PortalExit {
+    /// 入口位置
+    pub from: IVec2,
+    /// 出口位置
+    pub to: IVec2,
+    /// 出门后的移动方向
+    pub dir: IVec2,
 }
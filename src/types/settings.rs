@@ -0,0 +1,368 @@
+//! 游戏设置定义
+//!
+//! 模式、本地多人规则、难度、棋盘大小、战斗规则、按键绑定和辅助功能选项。
+//! 设置界面直接修改 [`Settings`]，难度、棋盘大小和战斗规则在下一局开始时生效。
+
+use macroquad::prelude::*;
+use crate::constants::{GRID_W, GRID_H};
+use super::BoardDims;
+
+/// 游戏模式
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum GameMode {
+    /// 经典模式：只有玩家一条蛇，蛇蛋不会孵化
+    Classic,
+    /// 竞技场：AI 蛇参与争夺食物
+    #[default]
+    Arena,
+}
+
+impl GameMode {
+    /// 所有模式（菜单顺序）
+    pub const ALL: [GameMode; 2] = [GameMode::Classic, GameMode::Arena];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Arena => "Arena",
+        }
+    }
+
+    /// 模式说明（模式选择界面显示）
+    pub fn description(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Just you, the food and the fruits",
+            GameMode::Arena => "AI snakes hatch from eggs and compete",
+        }
+    }
+}
+
+/// 本地多人规则（同一个键盘 2-4 名玩家，见 [`crate::game::party`]）
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PartyMode {
+    /// 单人
+    #[default]
+    Solo,
+    /// 对战：最后存活的蛇获胜
+    Versus,
+    /// 限时对战：倒下的玩家稍后重生，时间到时分数最高的获胜
+    Timed,
+    /// 合作：共享分数，吃到食物时救起倒下的队友
+    Coop,
+}
+
+impl PartyMode {
+    /// 多人规则（模式选择界面的顺序，不含单人）
+    pub const MULTIPLAYER: [PartyMode; 3] = [PartyMode::Versus, PartyMode::Timed, PartyMode::Coop];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            PartyMode::Solo => "Solo",
+            PartyMode::Versus => "Versus",
+            PartyMode::Timed => "Timed",
+            PartyMode::Coop => "Co-op",
+        }
+    }
+
+    /// 规则说明（模式选择界面显示）
+    pub fn description(&self) -> &'static str {
+        match self {
+            PartyMode::Solo => "One player",
+            PartyMode::Versus => "Last snake alive wins",
+            PartyMode::Timed => "Highest score when the timer runs out wins",
+            PartyMode::Coop => "Shared score, eat food to revive a downed partner",
+        }
+    }
+
+    /// 是否是玩家之间的对战
+    pub fn is_versus(&self) -> bool {
+        matches!(self, PartyMode::Versus | PartyMode::Timed)
+    }
+}
+
+/// 难度
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// 所有难度（菜单顺序）
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// 玩家移动间隔倍率（越小越快）
+    pub fn tick_multiplier(&self) -> f32 {
+        match self {
+            Difficulty::Easy => 1.25,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 0.8,
+        }
+    }
+
+    /// 竞技场模式下同时存在的 AI 蛇上限
+    pub fn max_ai_snakes(&self) -> usize {
+        match self {
+            Difficulty::Easy => 2,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 5,
+        }
+    }
+}
+
+/// 蛇与蛇相撞的规则
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum CombatRules {
+    /// 撞到别的蛇就死（经典规则）
+    #[default]
+    Lethal,
+    /// 撞到蛇身把它切断，头对头按长度结算（见 [`crate::game::combat`]）
+    Cutting,
+}
+
+impl CombatRules {
+    /// 所有规则（菜单顺序）
+    pub const ALL: [CombatRules; 2] = [CombatRules::Lethal, CombatRules::Cutting];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            CombatRules::Lethal => "Lethal",
+            CombatRules::Cutting => "Cutting",
+        }
+    }
+}
+
+/// AI 蛇的队伍编排
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TeamMode {
+    /// 没有队伍，各自为战
+    #[default]
+    Off,
+    /// AI 蛇分成红蓝两队
+    Squads,
+    /// AI 蛇都是红队；玩家吃掉蛇蛋会孵化一条盟友
+    Allies,
+}
+
+impl TeamMode {
+    /// 所有编排（菜单顺序）
+    pub const ALL: [TeamMode; 3] = [TeamMode::Off, TeamMode::Squads, TeamMode::Allies];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            TeamMode::Off => "Off",
+            TeamMode::Squads => "Squads",
+            TeamMode::Allies => "Allies",
+        }
+    }
+}
+
+/// 棋盘大小
+///
+/// 窗口尺寸固定，较小的棋盘是窗口中央的一块区域（[`BoardSize::dims`]），
+/// 移动和环绕都在这块区域的边上发生，四周的围栏不可进入
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BoardSize {
+    Small,
+    Medium,
+    #[default]
+    Full,
+}
+
+impl BoardSize {
+    /// 所有大小（菜单顺序）
+    pub const ALL: [BoardSize; 3] = [BoardSize::Small, BoardSize::Medium, BoardSize::Full];
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoardSize::Small => "Small",
+            BoardSize::Medium => "Medium",
+            BoardSize::Full => "Full",
+        }
+    }
+
+    /// 每条边向内收缩的格数
+    pub fn inset(&self) -> i32 {
+        match self {
+            BoardSize::Small => 4,
+            BoardSize::Medium => 2,
+            BoardSize::Full => 0,
+        }
+    }
+
+    /// 可游玩区域的格子数（宽, 高）
+    pub fn play_area(&self) -> (i32, i32) {
+        (GRID_W - 2 * self.inset(), GRID_H - 2 * self.inset())
+    }
+
+    /// 可游玩区域在窗口棋盘中的范围
+    pub fn dims(&self) -> BoardDims {
+        let (w, h) = self.play_area();
+        BoardDims::at(IVec2::splat(self.inset()), w, h)
+    }
+
+    /// 格子是否在可游玩区域内
+    pub fn contains(&self, pos: IVec2) -> bool {
+        self.dims().contains(pos)
+    }
+
+    /// 可游玩区域之外的所有格子（完整棋盘时为空）
+    pub fn fence_cells(&self) -> Vec<IVec2> {
+        (0..GRID_H)
+            .flat_map(|y| (0..GRID_W).map(move |x| ivec2(x, y)))
+            .filter(|&pos| !self.contains(pos))
+            .collect()
+    }
+}
+
+/// 按键绑定
+///
+/// 方格和八方向模式直接使用；六边形模式中上下键为 N/S，左右键为相对转向
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyBindings {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub pause: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            up: KeyCode::Up,
+            down: KeyCode::Down,
+            left: KeyCode::Left,
+            right: KeyCode::Right,
+            pause: KeyCode::Space,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// 本地多人时第 `index` 名玩家的默认按键：方向键、WASD、IJKL、小键盘 8456（暂停键共用）
+    pub fn player(index: usize) -> Self {
+        let [up, down, left, right] = match index {
+            1 => [KeyCode::W, KeyCode::S, KeyCode::A, KeyCode::D],
+            2 => [KeyCode::I, KeyCode::K, KeyCode::J, KeyCode::L],
+            3 => [KeyCode::Kp8, KeyCode::Kp5, KeyCode::Kp4, KeyCode::Kp6],
+            _ => return KeyBindings::default(),
+        };
+        KeyBindings { up, down, left, right, ..KeyBindings::default() }
+    }
+
+    /// 四个方向键（上、下、左、右）
+    pub fn arrows(&self) -> [KeyCode; 4] {
+        [self.up, self.down, self.left, self.right]
+    }
+}
+
+/// 玩家设置
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Settings {
+    /// 主音量 0.0-1.0
+    pub volume: f32,
+    /// 按键绑定
+    pub keys: KeyBindings,
+    /// 本地多人时第 2-4 名玩家的按键
+    pub party_keys: [KeyBindings; 3],
+    /// 棋盘大小
+    pub board_size: BoardSize,
+    /// 难度
+    pub difficulty: Difficulty,
+    /// 高对比度：给蛇、食物描边
+    pub high_contrast: bool,
+    /// 减少动态效果：不绘制粒子和残影
+    pub reduced_motion: bool,
+    /// 蛇与蛇相撞的规则
+    pub combat: CombatRules,
+    /// AI 蛇的队伍编排
+    pub teams: TeamMode,
+    /// 队友之间相撞是否按敌人结算
+    pub friendly_fire: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            volume: 1.0,
+            keys: KeyBindings::default(),
+            party_keys: [KeyBindings::player(1), KeyBindings::player(2), KeyBindings::player(3)],
+            board_size: BoardSize::default(),
+            difficulty: Difficulty::default(),
+            high_contrast: false,
+            reduced_motion: false,
+            combat: CombatRules::default(),
+            teams: TeamMode::default(),
+            friendly_fire: false,
+        }
+    }
+}
+
+impl Settings {
+    /// 第 `index` 名玩家的按键（第 1 名玩家用可以在设置界面修改的 `keys`）
+    pub fn player_keys(&self, index: usize) -> KeyBindings {
+        match index {
+            0 => self.keys,
+            _ => self.party_keys.get(index - 1).copied().unwrap_or_default(),
+        }
+    }
+
+    /// 当前模式和难度下的 AI 蛇上限
+    pub fn max_ai_snakes(&self, mode: GameMode) -> usize {
+        match mode {
+            GameMode::Classic => 0,
+            GameMode::Arena => self.difficulty.max_ai_snakes(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fence_surrounds_play_area() {
+        for size in BoardSize::ALL {
+            let (w, h) = size.play_area();
+            let fence = size.fence_cells();
+            assert_eq!(fence.len() as i32, GRID_W * GRID_H - w * h);
+            assert!(fence.iter().all(|&p| !size.contains(p)));
+        }
+        assert!(BoardSize::Full.fence_cells().is_empty());
+        // 蛇的初始位置在所有棋盘大小下都可游玩
+        assert!(BoardSize::Small.contains(ivec2(GRID_W / 2 - 2, GRID_H / 2)));
+    }
+
+    #[test]
+    fn test_party_keys_do_not_overlap() {
+        let settings = Settings::default();
+        let keys: Vec<KeyCode> = (0..4).flat_map(|i| settings.player_keys(i).arrows()).collect();
+        for (i, key) in keys.iter().enumerate() {
+            assert!(!keys[i + 1..].contains(key), "{key:?} bound twice");
+        }
+    }
+
+    #[test]
+    fn test_classic_mode_has_no_ai() {
+        let settings = Settings { difficulty: Difficulty::Hard, ..Default::default() };
+        assert_eq!(settings.max_ai_snakes(GameMode::Classic), 0);
+        assert_eq!(settings.max_ai_snakes(GameMode::Arena), 5);
+    }
+}
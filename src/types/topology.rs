@@ -0,0 +1,313 @@
+//! 地图拓扑定义
+//!
+//! 拓扑决定蛇越过边界时的行为：撞墙、普通环绕或镜像环绕。
+//! 每张地图可以选择不同拓扑，同一套规则适用于玩家、AI、碰撞和渲染。
+//!
+//! 游戏棋盘是 [`GRID_W`]×[`GRID_H`]（[`BoardDims::DEFAULT`]）；移动、决策和渲染按 [`BoardDims`] 传入棋盘范围
+//! （`*_on` 系列方法）。缩小的棋盘是窗口内偏移的一块区域，蜂群模式在更大的棋盘上复用同一套规则。
+
+use macroquad::prelude::*;
+use crate::constants::{GRID_W, GRID_H};
+
+/// 棋盘范围（格）：左上角 `origin` 起 `width`×`height` 个格子
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BoardDims {
+    pub origin: IVec2,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl BoardDims {
+    /// 游戏棋盘
+    pub const DEFAULT: BoardDims = BoardDims::new(GRID_W, GRID_H);
+
+    pub const fn new(width: i32, height: i32) -> Self {
+        BoardDims { origin: IVec2::ZERO, width, height }
+    }
+
+    /// 左上角在 `origin` 的棋盘（缩小的棋盘）
+    pub const fn at(origin: IVec2, width: i32, height: i32) -> Self {
+        BoardDims { origin, width, height }
+    }
+
+    /// 右下角之外的第一个格子（按绝对坐标建立占用网格时的尺寸）
+    pub fn end(&self) -> IVec2 {
+        self.origin + ivec2(self.width, self.height)
+    }
+
+    /// 棋盘中心格
+    pub fn center(&self) -> IVec2 {
+        self.origin + ivec2(self.width / 2, self.height / 2)
+    }
+
+    /// 格子总数
+    pub fn cells(&self) -> usize {
+        (self.width.max(0) * self.height.max(0)) as usize
+    }
+
+    /// 格子是否在棋盘内
+    pub fn contains(&self, pos: IVec2) -> bool {
+        let p = pos - self.origin;
+        p.x >= 0 && p.y >= 0 && p.x < self.width && p.y < self.height
+    }
+
+    /// 按行排列的格子下标（越界返回 None）
+    pub fn index(&self, pos: IVec2) -> Option<usize> {
+        let p = pos - self.origin;
+        self.contains(pos).then(|| (p.y * self.width + p.x) as usize)
+    }
+}
+
+impl Default for BoardDims {
+    fn default() -> Self {
+        BoardDims::DEFAULT
+    }
+}
+
+/// 地图拓扑
+///
+/// 镜像环绕：从右边出去会从左边进来，且 y 坐标上下翻转
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Topology {
+    /// 四面都是墙
+    Bounded,
+    /// 上下左右都环绕（圆环面）
+    #[default]
+    Torus,
+    /// 仅左右环绕（水平圆柱面）
+    CylinderX,
+    /// 仅上下环绕（垂直圆柱面）
+    CylinderY,
+    /// 左右镜像环绕，上下是墙（莫比乌斯带）
+    Mobius,
+    /// 左右镜像环绕 + 上下普通环绕（克莱因瓶）
+    Klein,
+}
+
+impl Topology {
+    /// 所有拓扑（T 键循环顺序）
+    pub const ALL: [Topology; 6] = [
+        Topology::Bounded,
+        Topology::Torus,
+        Topology::CylinderX,
+        Topology::CylinderY,
+        Topology::Mobius,
+        Topology::Klein,
+    ];
+
+    /// 从旧的包围开关转换
+    pub fn from_wrap(wrap: bool) -> Self {
+        if wrap { Topology::Torus } else { Topology::Bounded }
+    }
+
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Bounded => "Walls",
+            Topology::Torus => "Torus",
+            Topology::CylinderX => "Cylinder (H)",
+            Topology::CylinderY => "Cylinder (V)",
+            Topology::Mobius => "Mobius",
+            Topology::Klein => "Klein",
+        }
+    }
+
+    /// 下一个拓扑（循环）
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&t| t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// 左右边界是否环绕
+    pub fn wraps_x(&self) -> bool {
+        !matches!(self, Topology::Bounded | Topology::CylinderY)
+    }
+
+    /// 上下边界是否环绕
+    pub fn wraps_y(&self) -> bool {
+        matches!(self, Topology::Torus | Topology::CylinderY | Topology::Klein)
+    }
+
+    /// 左右环绕时是否翻转 y
+    pub fn mirrors_x(&self) -> bool {
+        matches!(self, Topology::Mobius | Topology::Klein)
+    }
+
+    /// 是否有任意方向环绕
+    pub fn wraps_any(&self) -> bool {
+        self.wraps_x() || self.wraps_y()
+    }
+
+    /// 越界位置是否算撞墙（未经过环绕处理的原始位置）
+    pub fn is_wall(&self, pos: IVec2) -> bool {
+        self.is_wall_on(BoardDims::DEFAULT, pos)
+    }
+
+    /// 在 `board` 上越界位置是否算撞墙
+    pub fn is_wall_on(&self, board: BoardDims, pos: IVec2) -> bool {
+        let p = pos - board.origin;
+        ((p.x < 0 || p.x >= board.width) && !self.wraps_x())
+            || ((p.y < 0 || p.y >= board.height) && !self.wraps_y())
+    }
+
+    /// 从 `pos` 沿 `dir` 走一步
+    ///
+    /// # 返回
+    /// 新位置和（可能被镜像翻转的）新方向；撞墙返回 None
+    pub fn step(&self, pos: IVec2, dir: IVec2) -> Option<(IVec2, IVec2)> {
+        self.wrap(pos + dir, dir)
+    }
+
+    /// 把越界位置映射回地图内
+    ///
+    /// 镜像环绕会翻转 y 坐标和方向的 y 分量；撞墙返回 None
+    pub fn wrap(&self, pos: IVec2, dir: IVec2) -> Option<(IVec2, IVec2)> {
+        let (pos, mirrored) = self.wrap_pos(pos)?;
+        Some((pos, if mirrored { ivec2(dir.x, -dir.y) } else { dir }))
+    }
+
+    /// 把越界位置映射回地图内，同时返回是否经过了镜像边界
+    ///
+    /// 方向的翻转与棋盘几何有关，由调用方处理；撞墙返回 None
+    pub fn wrap_pos(&self, pos: IVec2) -> Option<(IVec2, bool)> {
+        self.wrap_pos_on(BoardDims::DEFAULT, pos)
+    }
+
+    /// 在 `board` 上把越界位置映射回棋盘内，同时返回是否经过了镜像边界
+    pub fn wrap_pos_on(&self, board: BoardDims, pos: IVec2) -> Option<(IVec2, bool)> {
+        if board.contains(pos) {
+            return Some((pos, false));
+        }
+        if self.is_wall_on(board, pos) {
+            return None;
+        }
+
+        // 先处理上下，再处理左右（镜像翻转基于已在范围内的 y）
+        let p = pos - board.origin;
+        let mut y = p.y.rem_euclid(board.height);
+        let laps = p.x.div_euclid(board.width);
+        let x = p.x.rem_euclid(board.width);
+        // 每穿过一次左右边界翻转一次
+        let mirrored = self.mirrors_x() && laps % 2 != 0;
+        if mirrored {
+            y = board.height - 1 - y;
+        }
+
+        Some((board.origin + ivec2(x, y), mirrored))
+    }
+
+    /// 把任意位置放回地图内（环绕边界按拓扑映射，墙边界截断）
+    ///
+    /// 用于整体平移等不能失败的操作
+    pub fn normalize(&self, pos: IVec2) -> IVec2 {
+        self.normalize_on(BoardDims::DEFAULT, pos)
+    }
+
+    /// 把任意位置放回 `board` 内（见 [`Topology::normalize`]）
+    pub fn normalize_on(&self, board: BoardDims, pos: IVec2) -> IVec2 {
+        let last = board.end() - IVec2::ONE;
+        let clamped = ivec2(
+            if self.wraps_x() { pos.x } else { pos.x.clamp(board.origin.x, last.x) },
+            if self.wraps_y() { pos.y } else { pos.y.clamp(board.origin.y, last.y) },
+        );
+        self.wrap_pos_on(board, clamped).map(|(p, _)| p).unwrap_or(clamped)
+    }
+
+    /// 两个相邻格之间的显示位移（用于渲染插值）
+    ///
+    /// 跨越普通环绕边界时返回 ±1；镜像环绕或跳跃时返回原始差值
+    pub fn display_delta(&self, from: IVec2, to: IVec2) -> IVec2 {
+        self.display_delta_on(BoardDims::DEFAULT, from, to)
+    }
+
+    /// 在 `board` 上两个相邻格之间的显示位移（见 [`Topology::display_delta`]）
+    pub fn display_delta_on(&self, board: BoardDims, from: IVec2, to: IVec2) -> IVec2 {
+        let mut d = to - from;
+        if self.wraps_x() && !self.mirrors_x() {
+            if d.x > 1 { d.x -= board.width; }
+            if d.x < -1 { d.x += board.width; }
+        }
+        if self.wraps_y() {
+            if d.y > 1 { d.y -= board.height; }
+            if d.y < -1 { d.y += board.height; }
+        }
+        d
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_hits_wall() {
+        assert_eq!(Topology::Bounded.step(ivec2(GRID_W - 1, 5), ivec2(1, 0)), None);
+        assert!(Topology::Bounded.step(ivec2(3, 5), ivec2(1, 0)).is_some());
+    }
+
+    #[test]
+    fn test_cylinder_wraps_one_axis() {
+        let t = Topology::CylinderX;
+        assert_eq!(t.step(ivec2(GRID_W - 1, 5), ivec2(1, 0)), Some((ivec2(0, 5), ivec2(1, 0))));
+        assert_eq!(t.step(ivec2(4, 0), ivec2(0, -1)), None);
+
+        let t = Topology::CylinderY;
+        assert_eq!(t.step(ivec2(4, 0), ivec2(0, -1)), Some((ivec2(4, GRID_H - 1), ivec2(0, -1))));
+        assert_eq!(t.step(ivec2(0, 4), ivec2(-1, 0)), None);
+    }
+
+    #[test]
+    fn test_mobius_flips_y() {
+        let t = Topology::Mobius;
+        assert_eq!(t.step(ivec2(GRID_W - 1, 2), ivec2(1, 0)), Some((ivec2(0, GRID_H - 3), ivec2(1, 0))));
+        assert_eq!(t.step(ivec2(0, 2), ivec2(-1, 0)), Some((ivec2(GRID_W - 1, GRID_H - 3), ivec2(-1, 0))));
+        // 上下是墙
+        assert_eq!(t.step(ivec2(3, 0), ivec2(0, -1)), None);
+    }
+
+    #[test]
+    fn test_klein_wraps_vertically_without_flip() {
+        let t = Topology::Klein;
+        assert_eq!(t.step(ivec2(3, GRID_H - 1), ivec2(0, 1)), Some((ivec2(3, 0), ivec2(0, 1))));
+    }
+
+    #[test]
+    fn test_normalize_clamps_walls() {
+        assert_eq!(Topology::Bounded.normalize(ivec2(-3, GRID_H + 2)), ivec2(0, GRID_H - 1));
+        assert_eq!(Topology::Torus.normalize(ivec2(-1, GRID_H)), ivec2(GRID_W - 1, 0));
+    }
+
+    #[test]
+    fn test_larger_board_wraps_at_its_own_edges() {
+        let board = BoardDims::new(GRID_W * 4, GRID_H * 4);
+        assert_eq!(Topology::Bounded.wrap_pos_on(board, ivec2(GRID_W, 5)), Some((ivec2(GRID_W, 5), false)));
+        assert_eq!(Topology::Bounded.wrap_pos_on(board, ivec2(board.width, 5)), None);
+        assert_eq!(Topology::Torus.wrap_pos_on(board, ivec2(-1, board.height)), Some((ivec2(board.width - 1, 0), false)));
+        assert_eq!(Topology::Mobius.wrap_pos_on(board, ivec2(board.width, 2)), Some((ivec2(0, board.height - 3), true)));
+        assert_eq!(board.index(ivec2(1, 1)), Some(board.width as usize + 1));
+        assert_eq!(board.cells(), (board.width * board.height) as usize);
+    }
+
+    #[test]
+    fn test_inset_board_wraps_at_its_own_edges() {
+        let board = BoardDims::at(ivec2(4, 4), GRID_W - 8, GRID_H - 8);
+        let last = board.end() - IVec2::ONE;
+        assert_eq!(Topology::Torus.wrap_pos_on(board, ivec2(last.x + 1, 6)), Some((ivec2(4, 6), false)));
+        assert_eq!(Topology::Torus.wrap_pos_on(board, ivec2(6, 3)), Some((ivec2(6, last.y), false)));
+        assert_eq!(Topology::Mobius.wrap_pos_on(board, ivec2(3, 4)), Some((ivec2(last.x, last.y), true)));
+        assert_eq!(Topology::Bounded.wrap_pos_on(board, ivec2(3, 6)), None);
+        assert_eq!(Topology::Bounded.normalize_on(board, ivec2(0, 100)), ivec2(4, last.y));
+        assert_eq!(Topology::Torus.display_delta_on(board, ivec2(last.x, 6), ivec2(4, 6)), ivec2(1, 0));
+        assert_eq!(board.index(ivec2(4, 4)), Some(0));
+        assert_eq!(board.index(ivec2(3, 4)), None);
+    }
+
+    #[test]
+    fn test_next_cycles_all() {
+        let mut t = Topology::Bounded;
+        for _ in 0..Topology::ALL.len() {
+            t = t.next();
+        }
+        assert_eq!(t, Topology::Bounded);
+    }
+}
@@ -0,0 +1,392 @@
+//! 菜单控件
+//!
+//! 一个菜单是一列可选择的条目，支持按钮、开关、滑块、选项和按键绑定。
+//! 输入先转换成 [`MenuInput`]，再交给 [`Menu::handle_input`] 处理；
+//! 处理逻辑不依赖 macroquad 的输入状态，可以直接测试。
+
+use macroquad::prelude::*;
+use crate::constants::{GRID_W, GRID_H, CELL};
+
+/// 菜单输入（与具体按键无关）
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+}
+
+impl MenuInput {
+    /// 读取本帧新按下的菜单按键
+    ///
+    /// 方向键移动/调整，Enter/Space 确认，Esc/Backspace 返回
+    pub fn read() -> Option<Self> {
+        let keys = [
+            (KeyCode::Up, MenuInput::Up),
+            (KeyCode::Down, MenuInput::Down),
+            (KeyCode::Left, MenuInput::Left),
+            (KeyCode::Right, MenuInput::Right),
+            (KeyCode::Enter, MenuInput::Confirm),
+            (KeyCode::KpEnter, MenuInput::Confirm),
+            (KeyCode::Space, MenuInput::Confirm),
+            (KeyCode::Escape, MenuInput::Back),
+            (KeyCode::Backspace, MenuInput::Back),
+        ];
+        keys.iter().find(|(key, _)| is_key_pressed(*key)).map(|&(_, input)| input)
+    }
+}
+
+/// 菜单条目类型
+#[derive(Clone, PartialEq, Debug)]
+pub enum ItemKind {
+    /// 按钮：确认时触发
+    Button,
+    /// 开关：确认或左右切换
+    Toggle(bool),
+    /// 滑块：左右按步长调整
+    Slider { value: f32, min: f32, max: f32, step: f32 },
+    /// 选项：左右循环切换，确认切到下一项
+    Choice { options: Vec<&'static str>, index: usize },
+    /// 按键绑定：确认后由调用方捕获下一个按键
+    Key(KeyCode),
+}
+
+/// 菜单条目
+#[derive(Clone, PartialEq, Debug)]
+pub struct MenuItem {
+    /// 条目标识，事件中返回
+    pub id: &'static str,
+    /// 显示文字
+    pub label: String,
+    /// 条目类型和当前值
+    pub kind: ItemKind,
+}
+
+impl MenuItem {
+    /// 创建按钮
+    pub fn button(id: &'static str, label: impl Into<String>) -> Self {
+        MenuItem { id, label: label.into(), kind: ItemKind::Button }
+    }
+
+    /// 创建开关
+    pub fn toggle(id: &'static str, label: impl Into<String>, value: bool) -> Self {
+        MenuItem { id, label: label.into(), kind: ItemKind::Toggle(value) }
+    }
+
+    /// 创建滑块（值会被限制在范围内）
+    pub fn slider(id: &'static str, label: impl Into<String>, value: f32, min: f32, max: f32, step: f32) -> Self {
+        MenuItem {
+            id,
+            label: label.into(),
+            kind: ItemKind::Slider { value: value.clamp(min, max), min, max, step },
+        }
+    }
+
+    /// 创建选项
+    pub fn choice(id: &'static str, label: impl Into<String>, options: Vec<&'static str>, index: usize) -> Self {
+        let index = index.min(options.len().saturating_sub(1));
+        MenuItem { id, label: label.into(), kind: ItemKind::Choice { options, index } }
+    }
+
+    /// 创建按键绑定
+    pub fn key(id: &'static str, label: impl Into<String>, key: KeyCode) -> Self {
+        MenuItem { id, label: label.into(), kind: ItemKind::Key(key) }
+    }
+
+    /// 当前值的显示文字（按钮为空）
+    pub fn value_text(&self) -> String {
+        match &self.kind {
+            ItemKind::Button => String::new(),
+            ItemKind::Toggle(on) => if *on { "On".to_string() } else { "Off".to_string() },
+            ItemKind::Slider { value, min, max, .. } => {
+                let ratio = if max > min { (value - min) / (max - min) } else { 0.0 };
+                format!("{:>3}%", (ratio * 100.0).round() as i32)
+            }
+            ItemKind::Choice { options, index } => {
+                format!("< {} >", options.get(*index).copied().unwrap_or(""))
+            }
+            ItemKind::Key(key) => format!("[{:?}]", key),
+        }
+    }
+
+    /// 左右调整，返回值是否改变
+    fn adjust(&mut self, delta: i32) -> bool {
+        match &mut self.kind {
+            ItemKind::Toggle(on) => {
+                *on = !*on;
+                true
+            }
+            ItemKind::Slider { value, min, max, step } => {
+                let old = *value;
+                *value = (*value + *step * delta as f32).clamp(*min, *max);
+                *value != old
+            }
+            ItemKind::Choice { options, index } => {
+                if options.len() < 2 {
+                    return false;
+                }
+                let len = options.len() as i32;
+                *index = (*index as i32 + delta).rem_euclid(len) as usize;
+                true
+            }
+            ItemKind::Button | ItemKind::Key(_) => false,
+        }
+    }
+}
+
+/// 菜单事件
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuEvent {
+    /// 按钮或按键绑定条目被确认
+    Activated(&'static str),
+    /// 开关、滑块或选项的值改变
+    Changed(&'static str),
+    /// 返回上一级
+    Back,
+}
+
+/// 菜单
+#[derive(Clone, Debug, Default)]
+pub struct Menu {
+    /// 标题
+    pub title: String,
+    /// 标题下方的说明文字（每个元素一行）
+    pub lines: Vec<String>,
+    /// 所有条目
+    pub items: Vec<MenuItem>,
+    /// 当前选中的条目索引
+    pub selected: usize,
+    /// 底部提示
+    pub footer: String,
+}
+
+impl Menu {
+    /// 创建空菜单
+    pub fn new(title: impl Into<String>) -> Self {
+        Menu {
+            title: title.into(),
+            footer: "Up/Down select   Left/Right change   Enter confirm   Esc back".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 添加条目（链式调用）
+    pub fn with_item(mut self, item: MenuItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    /// 添加说明文字（链式调用）
+    pub fn with_line(mut self, line: impl Into<String>) -> Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    /// 设置底部提示（链式调用）
+    pub fn with_footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = footer.into();
+        self
+    }
+
+    /// 当前选中的条目
+    pub fn selected_item(&self) -> Option<&MenuItem> {
+        self.items.get(self.selected)
+    }
+
+    /// 按标识查找条目
+    pub fn item(&self, id: &str) -> Option<&MenuItem> {
+        self.items.iter().find(|item| item.id == id)
+    }
+
+    /// 按标识查找条目（可变）
+    pub fn item_mut(&mut self, id: &str) -> Option<&mut MenuItem> {
+        self.items.iter_mut().find(|item| item.id == id)
+    }
+
+    /// 开关的值（条目不存在或类型不符时为 false）
+    pub fn toggle_value(&self, id: &str) -> bool {
+        matches!(self.item(id).map(|item| &item.kind), Some(ItemKind::Toggle(true)))
+    }
+
+    /// 滑块的值
+    pub fn slider_value(&self, id: &str) -> Option<f32> {
+        match self.item(id).map(|item| &item.kind) {
+            Some(ItemKind::Slider { value, .. }) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// 选项的当前索引
+    pub fn choice_index(&self, id: &str) -> Option<usize> {
+        match self.item(id).map(|item| &item.kind) {
+            Some(ItemKind::Choice { index, .. }) => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// 按键绑定的当前按键
+    pub fn key_value(&self, id: &str) -> Option<KeyCode> {
+        match self.item(id).map(|item| &item.kind) {
+            Some(ItemKind::Key(key)) => Some(*key),
+            _ => None,
+        }
+    }
+
+    /// 设置按键绑定条目的按键
+    pub fn set_key(&mut self, id: &str, key: KeyCode) {
+        if let Some(MenuItem { kind: ItemKind::Key(k), .. }) = self.item_mut(id) {
+            *k = key;
+        }
+    }
+
+    /// 设置选项条目的当前索引（越界时忽略）
+    pub fn set_choice(&mut self, id: &str, index: usize) {
+        if let Some(MenuItem { kind: ItemKind::Choice { options, index: i }, .. }) = self.item_mut(id)
+            && index < options.len()
+        {
+            *i = index;
+        }
+    }
+
+    /// 处理一次输入
+    ///
+    /// 上下移动选中项（首尾循环），左右调整值，确认触发按钮或切换值
+    pub fn handle_input(&mut self, input: MenuInput) -> Option<MenuEvent> {
+        if input == MenuInput::Back {
+            return Some(MenuEvent::Back);
+        }
+        if self.items.is_empty() {
+            return None;
+        }
+        let len = self.items.len();
+        self.selected = self.selected.min(len - 1);
+
+        match input {
+            MenuInput::Up => {
+                self.selected = (self.selected + len - 1) % len;
+                None
+            }
+            MenuInput::Down => {
+                self.selected = (self.selected + 1) % len;
+                None
+            }
+            MenuInput::Left | MenuInput::Right => {
+                let delta = if input == MenuInput::Left { -1 } else { 1 };
+                let item = &mut self.items[self.selected];
+                item.adjust(delta).then_some(MenuEvent::Changed(item.id))
+            }
+            MenuInput::Confirm => {
+                let item = &mut self.items[self.selected];
+                match item.kind {
+                    ItemKind::Button | ItemKind::Key(_) => Some(MenuEvent::Activated(item.id)),
+                    _ => item.adjust(1).then_some(MenuEvent::Changed(item.id)),
+                }
+            }
+            MenuInput::Back => unreachable!(),
+        }
+    }
+
+    /// 绘制菜单（半透明背景覆盖整个棋盘）
+    pub fn draw(&self) {
+        let w = GRID_W as f32 * CELL;
+        let h = GRID_H as f32 * CELL;
+        draw_rectangle(0.0, 0.0, w, h, Color::new(0.0, 0.0, 0.0, 0.7));
+
+        let title = measure_text(&self.title, None, 44, 1.0);
+        draw_text(&self.title, (w - title.width) * 0.5, 70.0, 44.0, WHITE);
+
+        let mut y = 100.0;
+        for line in &self.lines {
+            let m = measure_text(line, None, 20, 1.0);
+            draw_text(line, (w - m.width) * 0.5, y, 20.0, LIGHTGRAY);
+            y += 22.0;
+        }
+
+        // 条目较多时压缩行高，保证不与底部提示重叠
+        let top_min = y + 20.0;
+        let available = h - 40.0 - top_min;
+        let row_h = (available / self.items.len().max(1) as f32).min(30.0);
+        let top = top_min.max(h * 0.5 - self.items.len() as f32 * row_h * 0.5);
+        for (i, item) in self.items.iter().enumerate() {
+            let row_y = top + i as f32 * row_h;
+            let selected = i == self.selected;
+            if selected {
+                draw_rectangle(w * 0.2, row_y - 20.0, w * 0.6, row_h - 4.0, Color::new(1.0, 1.0, 1.0, 0.12));
+            }
+            let color = if selected { YELLOW } else { WHITE };
+            let value = item.value_text();
+            if value.is_empty() {
+                let m = measure_text(&item.label, None, 26, 1.0);
+                draw_text(&item.label, (w - m.width) * 0.5, row_y, 26.0, color);
+            } else {
+                draw_text(&item.label, w * 0.22, row_y, 26.0, color);
+                let m = measure_text(&value, None, 26, 1.0);
+                draw_text(&value, w * 0.78 - m.width, row_y, 26.0, color);
+            }
+        }
+
+        let footer = measure_text(&self.footer, None, 18, 1.0);
+        draw_text(&self.footer, (w - footer.width) * 0.5, h - 16.0, 18.0, GRAY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_menu() -> Menu {
+        Menu::new("Test")
+            .with_item(MenuItem::button("play", "Play"))
+            .with_item(MenuItem::toggle("flag", "Flag", false))
+            .with_item(MenuItem::slider("vol", "Volume", 0.95, 0.0, 1.0, 0.1))
+            .with_item(MenuItem::choice("size", "Size", vec!["S", "M", "L"], 0))
+            .with_item(MenuItem::key("up", "Up", KeyCode::Up))
+    }
+
+    #[test]
+    fn test_navigation_wraps() {
+        let mut menu = sample_menu();
+        assert_eq!(menu.handle_input(MenuInput::Up), None);
+        assert_eq!(menu.selected, 4);
+        menu.handle_input(MenuInput::Down);
+        assert_eq!(menu.selected, 0);
+        assert_eq!(menu.handle_input(MenuInput::Confirm), Some(MenuEvent::Activated("play")));
+        assert_eq!(menu.handle_input(MenuInput::Back), Some(MenuEvent::Back));
+    }
+
+    #[test]
+    fn test_value_items_adjust() {
+        let mut menu = sample_menu();
+        menu.selected = 1;
+        assert_eq!(menu.handle_input(MenuInput::Confirm), Some(MenuEvent::Changed("flag")));
+        assert!(menu.toggle_value("flag"));
+
+        // 滑块被限制在范围内，到顶后不再产生事件
+        menu.selected = 2;
+        assert_eq!(menu.handle_input(MenuInput::Right), Some(MenuEvent::Changed("vol")));
+        assert_eq!(menu.slider_value("vol"), Some(1.0));
+        assert_eq!(menu.handle_input(MenuInput::Right), None);
+
+        menu.selected = 3;
+        menu.handle_input(MenuInput::Left);
+        assert_eq!(menu.choice_index("size"), Some(2));
+        menu.handle_input(MenuInput::Confirm);
+        assert_eq!(menu.choice_index("size"), Some(0));
+    }
+
+    #[test]
+    fn test_key_item_is_activated_not_adjusted() {
+        let mut menu = sample_menu();
+        menu.selected = 4;
+        assert_eq!(menu.handle_input(MenuInput::Left), None);
+        assert_eq!(menu.handle_input(MenuInput::Confirm), Some(MenuEvent::Activated("up")));
+        menu.set_key("up", KeyCode::W);
+        assert_eq!(menu.key_value("up"), Some(KeyCode::W));
+
+        menu.set_choice("size", 1);
+        menu.set_choice("size", 9);
+        assert_eq!(menu.choice_index("size"), Some(1));
+    }
+}
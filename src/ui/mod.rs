@@ -0,0 +1,12 @@
+//! 界面模块
+//!
+//! 基于 macroquad 的简单键盘菜单控件和文本输入控件，以及标题、模式、地图、
+//! 设置、排行榜、制作人员和暂停等菜单界面的流程。
+
+pub mod menu;
+pub mod text_input;
+pub mod screens;
+
+pub use menu::*;
+pub use text_input::*;
+pub use screens::*;
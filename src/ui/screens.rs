@@ -0,0 +1,665 @@
+//! 菜单界面流程
+//!
+//! 标题 → 模式选择 → 地图选择 → 开始游戏；标题和暂停菜单都可以进入设置，
+//! 设置界面返回进入它的界面。当前界面由 [`GameState`] 决定，
+//! 需要主循环处理的结果（开始、继续、重新开始、退出）以 [`UiAction`] 返回。
+//!
+//! 游戏结束时分数能进排行榜就进入名字输入界面，保存后显示对应的排行榜。
+
+use macroquad::prelude::*;
+use crate::game::{format_date, Leaderboard, LeaderboardKey, ScoreEntry, MAX_NAME_LEN};
+use crate::online::{OnlineError, RemoteEntry};
+use crate::types::{BoardSize, CombatRules, Difficulty, GameMode, GameState, GridKind, PartyMode, Settings, TeamMode};
+use super::{Menu, MenuEvent, MenuInput, MenuItem, TextEvent, TextInput};
+
+/// 需要主循环处理的菜单结果
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UiAction {
+    /// 按选择的模式、地图、棋盘几何和本地多人设置开始新游戏
+    StartGame { mode: GameMode, map: usize, grid: GridKind, party: PartyMode, players: usize },
+    /// 从暂停菜单继续
+    Resume,
+    /// 从暂停菜单重新开始
+    Restart,
+    /// 放弃当前游戏回到标题
+    QuitToTitle,
+    /// 退出程序
+    Exit,
+    /// 设置被修改
+    SettingsChanged,
+    /// 确认名字，把本局成绩写入排行榜
+    SubmitName,
+    /// 跳过名字输入，不记录本局成绩
+    SkipName,
+    /// 排行榜界面切换了榜单，需要重新填充
+    RefreshLeaderboard,
+}
+
+/// 按键绑定条目标识
+const KEY_ITEMS: [&str; 5] = ["key_up", "key_down", "key_left", "key_right", "key_pause"];
+
+/// 所有菜单界面
+pub struct MenuSystem {
+    pub title: Menu,
+    pub mode_select: Menu,
+    pub map_select: Menu,
+    pub settings: Menu,
+    pub pause: Menu,
+    pub credits: Menu,
+    pub leaderboard: Menu,
+    pub name_entry: Menu,
+    /// 名字输入框
+    pub name_input: TextInput,
+    /// 所有地图名（排行榜按地图筛选）
+    map_names: Vec<&'static str>,
+    /// 模式选择界面选中的模式
+    mode: GameMode,
+    /// 设置界面返回的界面（标题或暂停）
+    settings_return: GameState,
+    /// 正在等待新按键的绑定条目
+    capturing: Option<&'static str>,
+}
+
+impl MenuSystem {
+    /// 创建所有菜单，设置界面按当前设置初始化
+    pub fn new(map_names: &[&'static str], settings: &Settings) -> Self {
+        let title = Menu::new("Snake 2D")
+            .with_line("Eat, grow, and outlast the arena")
+            .with_item(MenuItem::button("play", "Play"))
+            .with_item(MenuItem::button("leaderboard", "Leaderboard"))
+            .with_item(MenuItem::button("settings", "Settings"))
+            .with_item(MenuItem::button("credits", "Credits"))
+            .with_item(MenuItem::button("exit", "Quit"));
+
+        let mut mode_select = Menu::new("Select Mode");
+        for mode in GameMode::ALL {
+            mode_select = mode_select
+                .with_line(format!("{}: {}", mode.name(), mode.description()))
+                .with_item(MenuItem::button(mode.name(), mode.name()));
+        }
+        let grid_names = GridKind::ALL.iter().map(|g| g.name()).collect();
+        let party_names = PartyMode::MULTIPLAYER.iter().map(|p| p.name()).collect();
+        for party in PartyMode::MULTIPLAYER {
+            mode_select = mode_select.with_line(format!("{}: {}", party.name(), party.description()));
+        }
+        mode_select = mode_select
+            .with_item(MenuItem::choice("grid", "Board", grid_names, 0))
+            .with_item(MenuItem::choice("players", "Players", vec!["1", "2", "3", "4"], 0))
+            .with_item(MenuItem::choice("party", "Rules", party_names, 0))
+            .with_item(MenuItem::button("back", "Back"));
+
+        let mut map_select = Menu::new("Select Map");
+        for &name in map_names {
+            map_select = map_select.with_item(MenuItem::button(name, name));
+        }
+        map_select = map_select.with_item(MenuItem::button("back", "Back"));
+
+        let difficulty = Difficulty::ALL.iter().position(|&d| d == settings.difficulty).unwrap_or(0);
+        let board_size = BoardSize::ALL.iter().position(|&b| b == settings.board_size).unwrap_or(0);
+        let combat = CombatRules::ALL.iter().position(|&c| c == settings.combat).unwrap_or(0);
+        let teams = TeamMode::ALL.iter().position(|&t| t == settings.teams).unwrap_or(0);
+        let keys = settings.keys;
+        let settings_menu = Menu::new("Settings")
+            .with_line("Difficulty, board size, combat and teams apply to the next game")
+            .with_item(MenuItem::slider("volume", "Volume", settings.volume, 0.0, 1.0, 0.1))
+            .with_item(MenuItem::choice(
+                "difficulty",
+                "Difficulty",
+                Difficulty::ALL.iter().map(|d| d.name()).collect(),
+                difficulty,
+            ))
+            .with_item(MenuItem::choice(
+                "board_size",
+                "Board Size",
+                BoardSize::ALL.iter().map(|b| b.name()).collect(),
+                board_size,
+            ))
+            .with_item(MenuItem::toggle("high_contrast", "High Contrast", settings.high_contrast))
+            .with_item(MenuItem::toggle("reduced_motion", "Reduced Motion", settings.reduced_motion))
+            .with_item(MenuItem::key("key_up", "Move Up", keys.up))
+            .with_item(MenuItem::key("key_down", "Move Down", keys.down))
+            .with_item(MenuItem::key("key_left", "Move Left", keys.left))
+            .with_item(MenuItem::key("key_right", "Move Right", keys.right))
+            .with_item(MenuItem::key("key_pause", "Pause", keys.pause))
+            .with_item(MenuItem::choice(
+                "combat",
+                "Combat",
+                CombatRules::ALL.iter().map(|c| c.name()).collect(),
+                combat,
+            ))
+            .with_item(MenuItem::choice("teams", "Teams", TeamMode::ALL.iter().map(|t| t.name()).collect(), teams))
+            .with_item(MenuItem::toggle("friendly_fire", "Friendly Fire", settings.friendly_fire))
+            .with_item(MenuItem::button("back", "Back"));
+
+        let pause = Menu::new("Paused")
+            .with_item(MenuItem::button("resume", "Resume"))
+            .with_item(MenuItem::button("restart", "Restart"))
+            .with_item(MenuItem::button("settings", "Settings"))
+            .with_item(MenuItem::button("quit", "Quit to Title"));
+
+        let credits = Menu::new("Credits")
+            .with_line("Snake 2D V2")
+            .with_line("Built with Rust and macroquad")
+            .with_line("All sounds are synthesized at runtime")
+            .with_line("Thanks for playing!")
+            .with_item(MenuItem::button("back", "Back"));
+
+        // 条目少于窗口能放下的行数，返回只用 Esc
+        let leaderboard = Menu::new("Leaderboard")
+            .with_item(MenuItem::choice("lb_mode", "Mode", GameMode::ALL.iter().map(|m| m.name()).collect(), 0))
+            .with_item(MenuItem::choice("lb_map", "Map", map_names.to_vec(), 0))
+            .with_item(MenuItem::choice(
+                "lb_difficulty",
+                "Difficulty",
+                Difficulty::ALL.iter().map(|d| d.name()).collect(),
+                0,
+            ))
+            .with_footer("Up/Down select   Left/Right change board   Esc back");
+
+        let name_entry = Menu::new("New High Score!")
+            .with_footer("Type your name   Enter save   Esc skip");
+
+        MenuSystem {
+            title,
+            mode_select,
+            map_select,
+            settings: settings_menu,
+            pause,
+            credits,
+            leaderboard,
+            name_entry,
+            name_input: TextInput::new(MAX_NAME_LEN),
+            map_names: map_names.to_vec(),
+            mode: GameMode::default(),
+            settings_return: GameState::Title,
+            capturing: None,
+        }
+    }
+
+    /// 当前界面对应的菜单（非菜单状态返回 None）
+    pub fn menu(&self, state: GameState) -> Option<&Menu> {
+        match state {
+            GameState::Title => Some(&self.title),
+            GameState::ModeSelect => Some(&self.mode_select),
+            GameState::MapSelect => Some(&self.map_select),
+            GameState::Settings => Some(&self.settings),
+            GameState::Credits => Some(&self.credits),
+            GameState::Paused => Some(&self.pause),
+            GameState::Leaderboard => Some(&self.leaderboard),
+            GameState::NameEntry => Some(&self.name_entry),
+            _ => None,
+        }
+    }
+
+    fn menu_mut(&mut self, state: GameState) -> Option<&mut Menu> {
+        match state {
+            GameState::Title => Some(&mut self.title),
+            GameState::ModeSelect => Some(&mut self.mode_select),
+            GameState::MapSelect => Some(&mut self.map_select),
+            GameState::Settings => Some(&mut self.settings),
+            GameState::Credits => Some(&mut self.credits),
+            GameState::Paused => Some(&mut self.pause),
+            GameState::Leaderboard => Some(&mut self.leaderboard),
+            GameState::NameEntry => Some(&mut self.name_entry),
+            _ => None,
+        }
+    }
+
+    /// 是否正在等待新的绑定按键
+    pub fn is_capturing(&self) -> bool {
+        self.capturing.is_some()
+    }
+
+    /// 读取本帧输入并处理
+    pub fn update(&mut self, state: &mut GameState, settings: &mut Settings) -> Option<UiAction> {
+        if self.capturing.is_some() {
+            return get_last_key_pressed().and_then(|key| self.capture_key(key, settings));
+        }
+        if *state == GameState::NameEntry {
+            return self.name_input.read().map(|event| match event {
+                TextEvent::Submit => UiAction::SubmitName,
+                TextEvent::Cancel => UiAction::SkipName,
+            });
+        }
+        MenuInput::read().and_then(|input| self.handle_input(input, state, settings))
+    }
+
+    /// 把按键绑定到正在捕获的条目（Esc 取消）
+    pub fn capture_key(&mut self, key: KeyCode, settings: &mut Settings) -> Option<UiAction> {
+        let id = self.capturing.take()?;
+        if key == KeyCode::Escape {
+            return None;
+        }
+        self.settings.set_key(id, key);
+        self.sync_settings(settings);
+        Some(UiAction::SettingsChanged)
+    }
+
+    /// 处理一次菜单输入，必要时切换界面
+    pub fn handle_input(
+        &mut self,
+        input: MenuInput,
+        state: &mut GameState,
+        settings: &mut Settings,
+    ) -> Option<UiAction> {
+        let event = self.menu_mut(*state)?.handle_input(input)?;
+
+        match (*state, event) {
+            // --- 标题 ---
+            (GameState::Title, MenuEvent::Back) => Some(UiAction::Exit),
+            (GameState::Title, MenuEvent::Activated(id)) => match id {
+                "play" => self.goto(state, GameState::ModeSelect),
+                "leaderboard" => {
+                    self.goto(state, GameState::Leaderboard);
+                    Some(UiAction::RefreshLeaderboard)
+                }
+                "settings" => self.open_settings(state),
+                "credits" => self.goto(state, GameState::Credits),
+                "exit" => Some(UiAction::Exit),
+                _ => None,
+            },
+
+            // --- 模式选择 ---
+            (GameState::ModeSelect, MenuEvent::Activated("back") | MenuEvent::Back) => {
+                self.goto(state, GameState::Title)
+            }
+            (GameState::ModeSelect, MenuEvent::Activated(id)) => {
+                self.mode = GameMode::ALL.iter().copied().find(|m| m.name() == id)?;
+                self.goto(state, GameState::MapSelect)
+            }
+
+            // --- 地图选择 ---
+            (GameState::MapSelect, MenuEvent::Activated("back") | MenuEvent::Back) => {
+                self.goto(state, GameState::ModeSelect)
+            }
+            (GameState::MapSelect, MenuEvent::Activated(_)) => {
+                let map = self.map_select.selected;
+                let grid_index = self.mode_select.choice_index("grid").unwrap_or(0);
+                let players = self.mode_select.choice_index("players").unwrap_or(0) + 1;
+                let party = match players {
+                    1 => PartyMode::Solo,
+                    _ => PartyMode::MULTIPLAYER[self.mode_select.choice_index("party").unwrap_or(0)],
+                };
+                Some(UiAction::StartGame { mode: self.mode, map, grid: GridKind::ALL[grid_index], party, players })
+            }
+
+            // --- 设置 ---
+            (GameState::Settings, MenuEvent::Activated("back") | MenuEvent::Back) => {
+                let target = self.settings_return;
+                self.goto(state, target)
+            }
+            (GameState::Settings, MenuEvent::Activated(id)) if KEY_ITEMS.contains(&id) => {
+                self.capturing = Some(id);
+                None
+            }
+            (GameState::Settings, MenuEvent::Changed(_)) => {
+                self.sync_settings(settings);
+                Some(UiAction::SettingsChanged)
+            }
+
+            // --- 制作人员 ---
+            (GameState::Credits, MenuEvent::Activated(_) | MenuEvent::Back) => {
+                self.goto(state, GameState::Title)
+            }
+
+            // --- 排行榜 ---
+            (GameState::Leaderboard, MenuEvent::Back) => {
+                self.goto(state, GameState::Title);
+                Some(UiAction::QuitToTitle)
+            }
+            (GameState::Leaderboard, MenuEvent::Changed(_)) => Some(UiAction::RefreshLeaderboard),
+
+            // --- 暂停 ---
+            (GameState::Paused, MenuEvent::Back) => Some(UiAction::Resume),
+            (GameState::Paused, MenuEvent::Activated(id)) => match id {
+                "resume" => Some(UiAction::Resume),
+                "restart" => Some(UiAction::Restart),
+                "settings" => self.open_settings(state),
+                "quit" => Some(UiAction::QuitToTitle),
+                _ => None,
+            },
+
+            _ => None,
+        }
+    }
+
+    /// 切换到另一个菜单界面，新界面从第一项开始
+    fn goto(&mut self, state: &mut GameState, target: GameState) -> Option<UiAction> {
+        *state = target;
+        if let Some(menu) = self.menu_mut(target) {
+            menu.selected = 0;
+        }
+        None
+    }
+
+    /// 打开设置界面，记住返回位置
+    fn open_settings(&mut self, state: &mut GameState) -> Option<UiAction> {
+        self.settings_return = *state;
+        self.goto(state, GameState::Settings)
+    }
+
+    /// 打开名字输入界面，输入框预填上次使用的名字
+    pub fn open_name_entry(&mut self, state: &mut GameState, score: u32, last_name: &str) {
+        self.name_entry.lines = vec![format!("Score: {}", score), String::new()];
+        self.name_input.set_value(last_name);
+        *state = GameState::NameEntry;
+    }
+
+    /// 打开指定榜单，`highlight` 是要标记的名次
+    pub fn open_leaderboard(
+        &mut self,
+        state: &mut GameState,
+        key: &LeaderboardKey,
+        board: &Leaderboard,
+        highlight: Option<usize>,
+    ) {
+        let mode = GameMode::ALL.iter().position(|&m| m == key.mode);
+        let map = self.map_names.iter().position(|&m| m == key.map);
+        let difficulty = Difficulty::ALL.iter().position(|&d| d == key.difficulty);
+        for (id, index) in [("lb_mode", mode), ("lb_map", map), ("lb_difficulty", difficulty)] {
+            if let Some(index) = index {
+                self.leaderboard.set_choice(id, index);
+            }
+        }
+        self.goto(state, GameState::Leaderboard);
+        self.refresh_leaderboard(board, highlight);
+    }
+
+    /// 排行榜界面当前选择的榜单
+    pub fn leaderboard_key(&self) -> LeaderboardKey {
+        let menu = &self.leaderboard;
+        LeaderboardKey {
+            mode: GameMode::ALL[menu.choice_index("lb_mode").unwrap_or(0)],
+            map: self.map_names.get(menu.choice_index("lb_map").unwrap_or(0)).copied().unwrap_or("").to_string(),
+            difficulty: Difficulty::ALL[menu.choice_index("lb_difficulty").unwrap_or(0)],
+        }
+    }
+
+    /// 在排行榜界面加入 本地/在线 榜单来源选项（启用在线服务时调用）
+    pub fn enable_online(&mut self) {
+        if self.leaderboard.item("lb_source").is_none() {
+            self.leaderboard.items.push(MenuItem::choice("lb_source", "Source", vec!["Local", "Online"], 0));
+        }
+    }
+
+    /// 排行榜界面是否选择了在线榜单
+    pub fn leaderboard_online(&self) -> bool {
+        self.leaderboard.choice_index("lb_source") == Some(1)
+    }
+
+    /// 按当前选择的榜单重新填充排行榜表格，在线榜单先显示加载提示
+    pub fn refresh_leaderboard(&mut self, board: &Leaderboard, highlight: Option<usize>) {
+        self.leaderboard.lines = if self.leaderboard_online() {
+            vec![TABLE_HEADER.to_string(), "Loading online scores...".to_string()]
+        } else {
+            table_lines(board.entries(&self.leaderboard_key()), highlight)
+        };
+    }
+
+    /// 显示在线查询结果，榜单选择已经变化或切回本地时忽略
+    pub fn show_online_leaderboard(&mut self, key: &LeaderboardKey, result: Result<&[RemoteEntry], &OnlineError>) {
+        if !self.leaderboard_online() || *key != self.leaderboard_key() {
+            return;
+        }
+        self.leaderboard.lines = match result {
+            Ok(remote) => {
+                let entries: Vec<ScoreEntry> = remote.iter().map(ScoreEntry::from).collect();
+                table_lines(&entries, None)
+            }
+            Err(err) => vec![TABLE_HEADER.to_string(), format!("Online scores unavailable ({})", err)],
+        };
+    }
+
+    /// 把设置界面的值写回设置
+    fn sync_settings(&self, settings: &mut Settings) {
+        let menu = &self.settings;
+        if let Some(volume) = menu.slider_value("volume") {
+            settings.volume = volume;
+        }
+        if let Some(i) = menu.choice_index("difficulty") {
+            settings.difficulty = Difficulty::ALL[i];
+        }
+        if let Some(i) = menu.choice_index("board_size") {
+            settings.board_size = BoardSize::ALL[i];
+        }
+        settings.high_contrast = menu.toggle_value("high_contrast");
+        settings.reduced_motion = menu.toggle_value("reduced_motion");
+        if let Some(i) = menu.choice_index("combat") {
+            settings.combat = CombatRules::ALL[i];
+        }
+        if let Some(i) = menu.choice_index("teams") {
+            settings.teams = TeamMode::ALL[i];
+        }
+        settings.friendly_fire = menu.toggle_value("friendly_fire");
+
+        let keys = &mut settings.keys;
+        for (id, slot) in KEY_ITEMS.iter().zip([
+            &mut keys.up,
+            &mut keys.down,
+            &mut keys.left,
+            &mut keys.right,
+            &mut keys.pause,
+        ]) {
+            if let Some(key) = menu.key_value(id) {
+                *slot = key;
+            }
+        }
+    }
+
+    /// 绘制当前界面的菜单
+    pub fn draw(&self, state: GameState) {
+        let Some(menu) = self.menu(state) else { return };
+        if state == GameState::NameEntry {
+            let mut menu = menu.clone();
+            menu.lines.push(format!("Name: {}_", self.name_input.value()));
+            menu.draw();
+        } else if state == GameState::Settings && self.capturing.is_some() {
+            let mut menu = menu.clone();
+            menu.footer = "Press a key to bind it (Esc cancels)".to_string();
+            menu.draw();
+        } else {
+            menu.draw();
+        }
+    }
+}
+
+/// 排行榜表头
+const TABLE_HEADER: &str = "#   Name            Score   Length   Time    Date";
+
+/// 排行榜表格行，`highlight` 名次前加 "> "
+fn table_lines(entries: &[ScoreEntry], highlight: Option<usize>) -> Vec<String> {
+    let mut lines = vec![TABLE_HEADER.to_string()];
+    if entries.is_empty() {
+        lines.push("No scores yet".to_string());
+    }
+    for (rank, e) in entries.iter().enumerate() {
+        let secs = e.duration.max(0.0) as u32;
+        lines.push(format!(
+            "{}{:>2}  {:<14}  {:>6}   {:>5}   {:>2}:{:02}   {}",
+            if highlight == Some(rank) { "> " } else { "" },
+            rank + 1,
+            e.name,
+            e.score,
+            e.length,
+            secs / 60,
+            secs % 60,
+            format_date(e.date),
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(
+        menus: &mut MenuSystem,
+        state: &mut GameState,
+        settings: &mut Settings,
+        inputs: &[MenuInput],
+    ) -> Option<UiAction> {
+        let mut last = None;
+        for &input in inputs {
+            last = menus.handle_input(input, state, settings);
+        }
+        last
+    }
+
+    #[test]
+    fn test_title_to_game_flow() {
+        let mut settings = Settings::default();
+        let mut menus = MenuSystem::new(&["Classic", "Portal Hub"], &settings);
+        let mut state = GameState::Title;
+
+        use MenuInput::*;
+        // Play → Classic → 切到 Hex → 选第二张地图
+        press(&mut menus, &mut state, &mut settings, &[Confirm]);
+        assert_eq!(state, GameState::ModeSelect);
+        press(&mut menus, &mut state, &mut settings, &[Down, Down, Right, Right, Up, Up, Confirm]);
+        assert_eq!(state, GameState::MapSelect);
+        let action = press(&mut menus, &mut state, &mut settings, &[Down, Confirm]);
+        assert_eq!(
+            action,
+            Some(UiAction::StartGame {
+                mode: GameMode::Classic,
+                map: 1,
+                grid: GridKind::Hex,
+                party: PartyMode::Solo,
+                players: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_party_choices_reach_start_game() {
+        let mut settings = Settings::default();
+        let mut menus = MenuSystem::new(&["Classic"], &settings);
+        let mut state = GameState::ModeSelect;
+
+        use MenuInput::*;
+        // 两名玩家、限时对战、竞技场
+        press(&mut menus, &mut state, &mut settings, &[Down, Down, Down, Right, Down, Right, Up, Up, Up, Confirm]);
+        let action = press(&mut menus, &mut state, &mut settings, &[Confirm]);
+        assert_eq!(
+            action,
+            Some(UiAction::StartGame {
+                mode: GameMode::Arena,
+                map: 0,
+                grid: GridKind::Square,
+                party: PartyMode::Timed,
+                players: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_settings_return_to_caller() {
+        let mut settings = Settings::default();
+        let mut menus = MenuSystem::new(&["Classic"], &settings);
+        let mut state = GameState::Paused;
+
+        use MenuInput::*;
+        press(&mut menus, &mut state, &mut settings, &[Down, Down, Confirm]);
+        assert_eq!(state, GameState::Settings);
+
+        // 音量减一档，高对比度打开
+        let action = press(&mut menus, &mut state, &mut settings, &[Left]);
+        assert_eq!(action, Some(UiAction::SettingsChanged));
+        assert!((settings.volume - 0.9).abs() < 1e-4);
+        press(&mut menus, &mut state, &mut settings, &[Down, Down, Down, Confirm]);
+        assert!(settings.high_contrast);
+
+        press(&mut menus, &mut state, &mut settings, &[Back]);
+        assert_eq!(state, GameState::Paused);
+        assert_eq!(press(&mut menus, &mut state, &mut settings, &[Back]), Some(UiAction::Resume));
+    }
+
+    #[test]
+    fn test_leaderboard_shows_selected_board() {
+        let mut settings = Settings::default();
+        let mut menus = MenuSystem::new(&["Classic", "Portal Hub"], &settings);
+        let mut board = Leaderboard::default();
+        let key = LeaderboardKey::new(GameMode::Arena, "Portal Hub", Difficulty::Hard);
+        let rank = board.insert(key.clone(), ScoreEntry::new("Ann", 42, 12, 75.0, 1));
+
+        let mut state = GameState::GameOver;
+        menus.open_leaderboard(&mut state, &key, &board, rank);
+        assert_eq!(state, GameState::Leaderboard);
+        assert_eq!(menus.leaderboard_key(), key);
+        assert!(menus.leaderboard.lines[1].starts_with("> ") && menus.leaderboard.lines[1].contains("Ann"));
+
+        // 切换难度后是另一个（空）榜单
+        let action = menus.handle_input(MenuInput::Up, &mut state, &mut settings);
+        assert_eq!(action, None);
+        let action = menus.handle_input(MenuInput::Right, &mut state, &mut settings);
+        assert_eq!(action, Some(UiAction::RefreshLeaderboard));
+        menus.refresh_leaderboard(&board, None);
+        assert_eq!(menus.leaderboard.lines[1], "No scores yet");
+
+        assert_eq!(
+            menus.handle_input(MenuInput::Back, &mut state, &mut settings),
+            Some(UiAction::QuitToTitle)
+        );
+        assert_eq!(state, GameState::Title);
+    }
+
+    #[test]
+    fn test_online_leaderboard_source() {
+        let mut settings = Settings::default();
+        let mut menus = MenuSystem::new(&["Classic"], &settings);
+        menus.enable_online();
+        menus.enable_online();
+        assert_eq!(menus.leaderboard.items.len(), 4);
+
+        let mut state = GameState::GameOver;
+        let key = LeaderboardKey::new(GameMode::Arena, "Classic", Difficulty::Normal);
+        menus.open_leaderboard(&mut state, &key, &Leaderboard::default(), None);
+        assert!(!menus.leaderboard_online());
+
+        // 最后一项是来源，切到在线后先显示加载提示
+        menus.handle_input(MenuInput::Up, &mut state, &mut settings);
+        let action = menus.handle_input(MenuInput::Right, &mut state, &mut settings);
+        assert_eq!(action, Some(UiAction::RefreshLeaderboard));
+        menus.refresh_leaderboard(&Leaderboard::default(), None);
+        assert!(menus.leaderboard_online());
+        assert!(menus.leaderboard.lines[1].starts_with("Loading"));
+
+        let remote = [RemoteEntry {
+            name: "Zed".into(),
+            score: 99,
+            length: 20,
+            duration: 61.0,
+            date: 0,
+            seed: 1,
+            verified: false,
+        }];
+        let other = LeaderboardKey::new(GameMode::Classic, "Classic", Difficulty::Normal);
+        menus.show_online_leaderboard(&other, Ok(&remote));
+        assert!(menus.leaderboard.lines[1].starts_with("Loading"));
+        menus.show_online_leaderboard(&key, Ok(&remote));
+        assert!(menus.leaderboard.lines[1].contains("Zed") && menus.leaderboard.lines[1].contains("1:01"));
+        menus.show_online_leaderboard(&key, Err(&OnlineError::Http(503)));
+        assert!(menus.leaderboard.lines[1].contains("HTTP 503"));
+    }
+
+    #[test]
+    fn test_key_capture_rebinds() {
+        let mut settings = Settings::default();
+        let mut menus = MenuSystem::new(&["Classic"], &settings);
+        let mut state = GameState::Settings;
+
+        menus.settings.selected = 5;
+        menus.handle_input(MenuInput::Confirm, &mut state, &mut settings);
+        assert!(menus.is_capturing());
+
+        // Esc 取消捕获，绑定不变
+        assert_eq!(menus.capture_key(KeyCode::Escape, &mut settings), None);
+        assert_eq!(settings.keys.up, KeyCode::Up);
+
+        menus.handle_input(MenuInput::Confirm, &mut state, &mut settings);
+        assert_eq!(menus.capture_key(KeyCode::W, &mut settings), Some(UiAction::SettingsChanged));
+        assert_eq!(settings.keys.up, KeyCode::W);
+        assert!(!menus.is_capturing());
+    }
+}
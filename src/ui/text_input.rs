@@ -0,0 +1,95 @@
+//! 文本输入控件
+//!
+//! 单行文本输入，用于排行榜输入名字。字符过滤和长度限制是纯逻辑，
+//! [`TextInput::read`] 负责从 macroquad 读取本帧的字符和按键。
+
+use macroquad::prelude::*;
+
+/// 文本输入结束事件
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextEvent {
+    /// Enter 确认
+    Submit,
+    /// Esc 取消
+    Cancel,
+}
+
+/// 单行文本输入
+#[derive(Clone, Debug, Default)]
+pub struct TextInput {
+    value: String,
+    max_len: usize,
+}
+
+impl TextInput {
+    /// 创建最多 `max_len` 个字符的空输入框
+    pub fn new(max_len: usize) -> Self {
+        TextInput { value: String::new(), max_len }
+    }
+
+    /// 当前内容
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// 替换内容（同样受字符过滤和长度限制）
+    pub fn set_value(&mut self, value: &str) {
+        self.value.clear();
+        for c in value.chars() {
+            self.insert(c);
+        }
+    }
+
+    /// 追加一个字符，控制字符和超长部分被忽略，返回是否追加成功
+    pub fn insert(&mut self, c: char) -> bool {
+        if c.is_control() || self.value.chars().count() >= self.max_len {
+            return false;
+        }
+        self.value.push(c);
+        true
+    }
+
+    /// 删除最后一个字符
+    pub fn backspace(&mut self) {
+        self.value.pop();
+    }
+
+    /// 读取本帧输入的字符和按键
+    pub fn read(&mut self) -> Option<TextEvent> {
+        while let Some(c) = get_char_pressed() {
+            self.insert(c);
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.backspace();
+        }
+        if is_key_pressed(KeyCode::Enter) || is_key_pressed(KeyCode::KpEnter) {
+            Some(TextEvent::Submit)
+        } else if is_key_pressed(KeyCode::Escape) {
+            Some(TextEvent::Cancel)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_filters_and_limits() {
+        let mut input = TextInput::new(4);
+        assert!(input.insert('a'));
+        assert!(!input.insert('\t'));
+        assert!(!input.insert('\r'));
+        for c in "bcdef".chars() {
+            input.insert(c);
+        }
+        assert_eq!(input.value(), "abcd");
+
+        input.backspace();
+        assert_eq!(input.value(), "abc");
+        input.set_value("蛇蛇蛇蛇蛇");
+        assert_eq!(input.value(), "蛇蛇蛇蛇");
+    }
+}
@@ -0,0 +1,1508 @@
+//! 游戏世界
+//!
+//! [`GameWorld`] 集中了一局游戏的全部状态和逐帧推进逻辑。它不读键盘、不绘制：
+//! 游戏主程序每帧读输入、调用 [`GameWorld::advance`] 再绘制；
+//! 音效通过 [`SoundEffects`] 播放，无界面重放时不发声。
+
+use std::path::PathBuf;
+use macroquad::prelude::*;
+use ::rand::{thread_rng, Rng, RngCore, SeedableRng};
+use crate::audio::SoundEffects;
+use crate::bots::{Board, BotController, BotDecision, GameInfo, GameRequest};
+use crate::broadcast::{
+    active_buffs, color_array, DroppedSnapshot, FruitSnapshot, PortalSnapshot, SnakeRole, SnakeSnapshot, WorldSnapshot,
+};
+use crate::constants::{CELL, NO_FOOD, TEAM_WIPE_BONUS, VICTORY_BONUS};
+use crate::fruits::{create_fruit_registry, FruitRegistry};
+use crate::game::{
+    builtin_maps, check_fruit_collision, check_hazard_collision, create_default_portal_manager,
+    create_default_spawn_manager, default_leaderboard_path, free_spot, handle_fruit_effect, spawn_blood_particles,
+    spawn_food, spawn_position, start_position, update_blood_stains, update_combo, update_damage_animation,
+    update_fruits_with_callbacks, update_particles, update_sandworm_mode, AIManager, BombManager, BossEvent,
+    BossSchedule, FruitSpawnManager, GameMap, GameRng, HazardManager, Leaderboard, LeaderboardKey, MoveResult,
+    Party, PartyOutcome, Player, PlayerClash, PlayerView, PortalManager, ScoreEntry, Team, TeamEvent,
+    ARENA_BOSS_SCORES, DEFAULT_LEADERBOARD_SIZE, MAX_PLAYERS,
+};
+use crate::net::{MatchConfig, NetError, NetInput, StateHasher, NET_PLAYERS, TICK_RATE};
+use crate::online::{OnlineService, ScoreSubmission};
+use crate::render::{spawn_egg_hatch_particles, spawn_freeze_particles};
+use crate::types::{
+    Afterimage, BloodStain, BoardDims, CombatRules, Fruit, GameMode, GameState, Grid, GridKind, Particle, PartyMode,
+    SandwormPhase, Settings, TeamMode, Topology,
+};
+use super::{replay_dir, Replay, ReplayError, ReplayRecorder};
+
+/// 游戏世界状态
+///
+/// 集中管理所有游戏状态，作为游戏主循环的核心数据结构。
+///
+/// ## 设计说明
+///
+/// 将所有状态集中在一个结构体中的好处：
+/// - 便于序列化/反序列化（存档功能）
+/// - 便于重置游戏状态
+/// - 清晰的所有权边界
+pub struct GameWorld {
+    // -------------------------------------------------------------------------
+    // 核心游戏对象
+    // -------------------------------------------------------------------------
+
+    /// 本地玩家（1-4 名）
+    ///
+    /// 每名玩家有自己的蛇、按键、Buff、受伤动画、Combo 和分数，按自己的速度移动
+    pub players: Vec<Player>,
+
+    /// 本地多人规则
+    ///
+    /// 判定对战胜负、限时对战计时、合作时救人
+    pub party: Party,
+
+    /// 食物位置
+    ///
+    /// 普通食物，吃到后+1分并增长1节
+    pub food: IVec2,
+
+    /// 果实列表
+    ///
+    /// 特殊果实（陷阱、功能、幸运方块等），有生命周期
+    pub fruits: Vec<Fruit>,
+
+    /// 传送门管理器
+    ///
+    /// 成对出现，碰到一个会传送到另一个；包含地图永久传送门和随机临时传送门
+    pub portal_manager: PortalManager,
+
+    /// 动态障碍物
+    ///
+    /// 旋转横杆、轨道滑块、定时墙，碰撞规则与墙相同
+    pub hazards: HazardManager,
+
+    // -------------------------------------------------------------------------
+    // 视觉效果
+    // -------------------------------------------------------------------------
+
+    /// 粒子列表
+    ///
+    /// 用于爆炸、血液飞溅等特效
+    pub particles: Vec<Particle>,
+
+    /// 血迹列表
+    ///
+    /// 受伤时留下的血迹，会逐渐消失
+    pub blood_stains: Vec<BloodStain>,
+
+    /// 残影列表
+    ///
+    /// 速度模式下的蛇身残影
+    pub afterimages: Vec<Afterimage>,
+
+    // -------------------------------------------------------------------------
+    // 游戏控制
+    // -------------------------------------------------------------------------
+
+    /// 游戏状态
+    ///
+    /// Title 等菜单界面: 显示菜单，游戏逻辑不更新
+    /// Playing: 游戏进行中
+    /// Paused: 暂停菜单
+    /// GameOver: 游戏结束
+    pub state: GameState,
+
+    /// 游戏模式（经典 / 竞技场），在模式选择界面选择
+    pub mode: GameMode,
+
+    /// 本地多人规则和玩家数，在模式选择界面选择
+    pub party_mode: PartyMode,
+    pub player_count: usize,
+
+    /// 本地多人的结果（分出胜负时设置，游戏结束画面显示）
+    pub outcome: Option<PartyOutcome>,
+
+    /// 玩家设置，在设置界面修改
+    pub settings: Settings,
+
+    /// 当前榜单（模式 + 地图 + 难度）的最高分，来自排行榜
+    pub high_score: u32,
+
+    /// 游戏时间（秒）
+    ///
+    /// 用于动画和果实生命周期计算
+    pub game_time: f32,
+
+    /// 边界拓扑
+    ///
+    /// 决定越过边界时撞墙、环绕还是镜像环绕；随地图切换，T 键循环
+    pub topology: Topology,
+
+    /// 棋盘几何（方格 / 六边形），G 键循环
+    pub grid: GridKind,
+
+    // -------------------------------------------------------------------------
+    // 地图
+    // -------------------------------------------------------------------------
+
+    /// 所有可选地图
+    pub maps: Vec<GameMap>,
+
+    /// 当前地图索引
+    pub map_index: usize,
+
+    // -------------------------------------------------------------------------
+    // 系统
+    // -------------------------------------------------------------------------
+
+    /// 果实注册表
+    ///
+    /// 管理所有已注册的果实类型
+    pub registry: FruitRegistry,
+    
+    /// 果实生成管理器
+    ///
+    /// 统一管理所有果实类别的生成逻辑，支持声明式配置
+    pub spawn_manager: FruitSpawnManager,
+
+    // -------------------------------------------------------------------------
+    // AI 蛇系统
+    // -------------------------------------------------------------------------
+
+    /// AI 蛇管理器
+    ///
+    /// 管理所有 AI 蛇的生成、移动、碰撞和死亡
+    pub ai_manager: AIManager,
+
+    /// Boss 登场时间表
+    ///
+    /// 地图自己的分数阈值，竞技场模式再加上 `ARENA_BOSS_SCORES`
+    pub boss_schedule: BossSchedule,
+
+    // -------------------------------------------------------------------------
+    // 随机数与排行榜
+    // -------------------------------------------------------------------------
+
+    /// 本局随机种子（每次重置时重新生成，记录到排行榜）
+    pub seed: u64,
+
+    /// 本局随机数生成器，由 `seed` 初始化
+    pub rng: GameRng,
+
+    /// 本地排行榜
+    pub leaderboard: Leaderboard,
+
+    /// 排行榜文件路径
+    pub leaderboard_path: PathBuf,
+
+    /// 上次输入的玩家名字（下次上榜时预填）
+    pub player_name: String,
+
+    /// 在线排行榜服务（设置了 SNAKE2D_ONLINE_URL 时启用）
+    pub online: Option<OnlineService>,
+
+    /// 本局的回放录制（只录单人对局；中途改了玩法设置时放弃）
+    pub recording: Option<ReplayRecorder>,
+
+    /// 刚结束的一局的回放，上榜时保存到回放目录
+    pub finished_replay: Option<Replay>,
+
+    // -------------------------------------------------------------------------
+    // HTTP 机器人
+    // -------------------------------------------------------------------------
+
+    /// 命令行接入的机器人（`--bot`），按顺序坐在最后几个玩家位置上
+    pub bots: Vec<BotController>,
+
+    /// 和机器人进行中的一局（已经发出 `/start`）
+    pub bot_game: Option<GameInfo>,
+}
+
+impl GameWorld {
+    /// 创建新的游戏世界（读取本地排行榜，设置了 SNAKE2D_ONLINE_URL 时启用在线排行榜）
+    pub fn new() -> Self {
+        let leaderboard_path = default_leaderboard_path();
+        // 排行榜文件损坏或无法读取时从空榜开始，保存时会覆盖
+        let leaderboard = Leaderboard::load(&leaderboard_path, DEFAULT_LEADERBOARD_SIZE).unwrap_or_default();
+        Self::with_storage(leaderboard, leaderboard_path, OnlineService::from_env())
+    }
+
+    /// 不读写任何文件、不联网的游戏世界（无界面重放用）
+    pub fn headless() -> Self {
+        Self::with_storage(Leaderboard::default(), PathBuf::new(), None)
+    }
+
+    fn with_storage(leaderboard: Leaderboard, leaderboard_path: PathBuf, online: Option<OnlineService>) -> Self {
+        let seed = thread_rng().next_u64();
+        let mut rng = GameRng::seed_from_u64(seed);
+        let players = vec![Player::new(0, 1, GridKind::Square, BoardDims::DEFAULT, Settings::default().keys)];
+        let maps = builtin_maps();
+        let hazards = maps[0].hazards.clone();
+        let mut portal_manager = create_default_portal_manager();
+        portal_manager.set_map_portals(maps[0].portals.clone());
+        let mut ai_manager = AIManager::new();
+        ai_manager.set_blocked_cells(&hazards.footprint());
+        let food = spawn_food(ai_manager.free_cells(), |p| player_at(&players, p), &mut rng)
+            .expect("new board has free cells");
+
+        let mut world = GameWorld {
+            players,
+            party: Party::new(PartyMode::Solo, 1),
+            food,
+            fruits: vec![],
+            portal_manager,
+            hazards,
+            particles: vec![],
+            blood_stains: vec![],
+            afterimages: vec![],
+            state: GameState::Title,
+            mode: GameMode::default(),
+            party_mode: PartyMode::Solo,
+            player_count: 1,
+            outcome: None,
+            settings: Settings::default(),
+            high_score: 0,
+            game_time: 0.0,
+            topology: maps[0].topology,
+            grid: GridKind::Square,
+            maps,
+            map_index: 0,
+            registry: create_fruit_registry(),
+            spawn_manager: create_default_spawn_manager(),
+            ai_manager,
+            boss_schedule: BossSchedule::default(),
+            seed,
+            rng,
+            leaderboard,
+            leaderboard_path,
+            player_name: String::new(),
+            online,
+            recording: None,
+            finished_replay: None,
+            bots: vec![],
+            bot_game: None,
+        };
+        // 应用默认设置的棋盘，然后停在标题界面
+        world.reset();
+        world.state = GameState::Title;
+        world
+    }
+
+    /// 重置游戏状态
+    ///
+    /// 保留最高分，重置其他所有状态；按当前设置重建棋盘围栏和 AI 上限，按玩家数重新放置所有玩家
+    pub fn reset(&mut self) {
+        self.reset_seeded(thread_rng().next_u64());
+    }
+
+    /// 用指定种子重置（联机时双方用主机下发的种子开始同一局）
+    pub(crate) fn reset_seeded(&mut self, seed: u64) {
+        self.end_bots();
+        // 机器人只会走方格棋盘的四个方向
+        if !self.bots.is_empty() {
+            self.grid = GridKind::Square;
+        }
+        self.seed = seed;
+        self.rng = GameRng::seed_from_u64(self.seed);
+        self.high_score = self.leaderboard.best_score(&self.leaderboard_key());
+        self.apply_board();
+        self.ai_manager.grid = self.grid;
+        self.players = (0..self.player_count)
+            .map(|i| Player::new(i, self.player_count, self.grid, self.board(), self.settings.player_keys(i)))
+            .collect();
+        self.party = Party::new(self.party_mode, self.player_count);
+        self.outcome = None;
+        self.ai_manager.reset();
+        self.fruits.clear();
+        self.food = self.place_food().expect("new board has free cells");
+        self.portal_manager.reset();
+        self.particles.clear();
+        self.blood_stains.clear();
+        self.afterimages.clear();
+        self.state = GameState::Playing;
+        self.game_time = 0.0;
+        self.spawn_manager.reset();
+        let mut boss_scores = self.maps[self.map_index].boss_scores.clone();
+        if self.mode == GameMode::Arena {
+            boss_scores.extend(ARENA_BOSS_SCORES);
+        }
+        self.boss_schedule = BossSchedule::new(boss_scores);
+        // 只有单人对局上榜，也只录单人对局
+        self.finished_replay = None;
+        self.recording = (self.party_mode == PartyMode::Solo)
+            .then(|| ReplayRecorder::new(self.match_config(), self.topology));
+    }
+
+    /// 按地图和棋盘大小设置棋盘范围、障碍物、地图传送门和生成时避开的格子
+    ///
+    /// 缩小的棋盘只在中间一块区域上移动和环绕，区域外的围栏格子不生成任何东西，
+    /// 两端不都在区域内的地图传送门被移除
+    fn apply_board(&mut self) {
+        let map = &self.maps[self.map_index];
+        let board = self.settings.board_size;
+        self.hazards = map.hazards.clone();
+        self.ai_manager.set_board(board.dims());
+        self.portal_manager.set_map_portals(
+            map.portals
+                .iter()
+                .filter(|p| board.contains(p.pos_a) && board.contains(p.pos_b))
+                .cloned()
+                .collect(),
+        );
+        let mut blocked = self.hazards.footprint();
+        blocked.extend(board.fence_cells());
+        self.ai_manager.set_blocked_cells(&blocked);
+        self.ai_manager.max_snakes = self.settings.max_ai_snakes(self.mode);
+        self.ai_manager.set_difficulty(self.settings.difficulty);
+        self.ai_manager.combat = self.settings.combat;
+        self.ai_manager.teams = self.settings.teams;
+        self.ai_manager.friendly_fire = self.settings.friendly_fire;
+    }
+
+    /// 当前模式、地图和难度对应的排行榜
+    pub fn leaderboard_key(&self) -> LeaderboardKey {
+        LeaderboardKey::new(self.mode, self.maps[self.map_index].name, self.settings.difficulty)
+    }
+
+    /// 当前种子、模式、地图、棋盘几何和玩法设置
+    pub fn match_config(&self) -> MatchConfig {
+        MatchConfig::new(self.seed, self.mode, self.map_index, self.grid, &self.settings)
+    }
+
+    /// 把本局成绩写入排行榜并保存（启用在线服务时同时提交），返回本地名次
+    ///
+    /// 有这一局的回放时先把回放保存到排行榜旁边的回放目录，记录里引用回放文件
+    pub fn record_score(&mut self, name: &str) -> Option<usize> {
+        let mut entry = ScoreEntry::new(name, self.score(), self.players[0].snake.len(), self.game_time, self.seed);
+        if let Some(replay) = &self.finished_replay {
+            let dir = replay_dir(&self.leaderboard_path);
+            match replay.save(&dir) {
+                Ok(path) => entry.replay = Some(path.display().to_string()),
+                Err(err) => eprintln!("failed to save replay to {}: {}", dir.display(), err),
+            }
+        }
+        self.player_name = entry.name.clone();
+        let key = self.leaderboard_key();
+        if let Some(online) = &self.online {
+            online.submit(ScoreSubmission::from_entry(&key, &entry));
+        }
+        let rank = self.leaderboard.insert(key, entry);
+        if let Err(err) = self.leaderboard.save(&self.leaderboard_path) {
+            eprintln!("failed to save leaderboard to {}: {}", self.leaderboard_path.display(), err);
+        }
+        rank
+    }
+
+    /// 切换地图并重新开始
+    pub fn load_map(&mut self, index: usize) {
+        self.map_index = index % self.maps.len();
+        self.topology = self.maps[self.map_index].topology;
+        self.reset();
+    }
+
+    /// 按主机下发的设置开始联机对战（双方用同一个种子）
+    pub fn start_online(&mut self, config: &MatchConfig) -> Result<(), NetError> {
+        let (mode, grid) = config.apply(&mut self.settings)?;
+        self.mode = mode;
+        self.grid = grid;
+        self.party_mode = PartyMode::Versus;
+        self.player_count = NET_PLAYERS;
+        self.map_index = config.map % self.maps.len();
+        self.topology = self.maps[self.map_index].topology;
+        self.reset_seeded(config.seed);
+        Ok(())
+    }
+
+    /// 联机对局推进一个 tick：按座位应用到齐的输入，按固定步长推进，返回这个 tick 结束时的状态哈希
+    pub fn advance_tick(&mut self, inputs: &[NetInput], sounds: &dyn SoundEffects) -> u64 {
+        if self.state == GameState::Playing {
+            for (i, input) in inputs.iter().enumerate() {
+                if let Some(dir) = input.dir() {
+                    self.turn(i, dir);
+                }
+            }
+        }
+        self.advance(1.0 / TICK_RATE as f32, sounds);
+        self.state_hash()
+    }
+
+    /// 按回放的对局设置和开局拓扑开始同一局单人游戏
+    pub fn start_replay(&mut self, replay: &Replay) -> Result<(), ReplayError> {
+        let config = &replay.config;
+        let (mode, grid) = config.apply(&mut self.settings).map_err(|err| ReplayError::Setting(err.to_string()))?;
+        if config.map >= self.maps.len() {
+            return Err(ReplayError::Setting(format!("unknown map {}", config.map)));
+        }
+        self.mode = mode;
+        self.grid = grid;
+        self.party_mode = PartyMode::Solo;
+        self.player_count = 1;
+        self.map_index = config.map;
+        self.topology = replay.topology()?;
+        self.reset_seeded(config.seed);
+        Ok(())
+    }
+
+    /// 设置多人规则和本地玩家数；有机器人时座位加上机器人，单人改成对战
+    pub fn seat(&mut self, party: PartyMode, humans: usize) {
+        let bots = self.bots.len();
+        self.party_mode = if bots > 0 && party == PartyMode::Solo { PartyMode::Versus } else { party };
+        self.player_count = humans.clamp(1, MAX_PLAYERS - bots) + bots;
+    }
+
+    /// 接入机器人，回到标题界面
+    pub fn attach_bots(&mut self, bots: Vec<BotController>) {
+        self.bots = bots;
+        self.seat(self.party_mode, 1);
+        self.reset();
+        self.state = GameState::Title;
+    }
+
+    /// 第 `i` 名玩家是第几个机器人（本地玩家为 None）
+    pub fn bot_of(&self, i: usize) -> Option<usize> {
+        i.checked_sub(self.players.len().saturating_sub(self.bots.len()))
+    }
+
+    /// 发给第 `i` 名玩家（机器人）的请求，回合数由控制器填写
+    fn bot_request(&self, i: usize, game: &GameInfo) -> GameRequest {
+        let board = Board::from_snapshot(&self.snapshot(), &self.registry);
+        GameRequest::new(game, 0, board, &self.players[i].name())
+    }
+
+    /// 一局开始：给每个机器人发 `/start` 和第一回合的 `/move`
+    fn start_bots(&mut self) {
+        let timeout = self.bots.first().map_or(0, |bot| bot.timeout().as_millis() as u64);
+        let game = GameInfo::new(&format!("{:016x}", self.seed), self.maps[self.map_index].name, self.topology, timeout);
+        let first = self.players.len() - self.bots.len();
+        for k in 0..self.bots.len() {
+            let request = self.bot_request(first + k, &game);
+            self.bots[k].start(request.clone());
+            self.bots[k].request_move(request);
+        }
+        self.bot_game = Some(game);
+    }
+
+    /// 一局结束：给每个机器人发 `/end`
+    fn end_bots(&mut self) {
+        let Some(game) = self.bot_game.take() else {
+            return;
+        };
+        let first = self.players.len() - self.bots.len();
+        for k in 0..self.bots.len() {
+            let request = self.bot_request(first + k, &game);
+            self.bots[k].end(request);
+        }
+    }
+
+    /// 第 `i` 名玩家是还在场上的机器人时，请求它下一回合的方向
+    fn request_bot_move(&mut self, i: usize) {
+        if let Some(k) = self.bot_of(i)
+            && let Some(game) = &self.bot_game
+            && self.state == GameState::Playing
+            && !self.players[i].downed
+        {
+            let request = self.bot_request(i, game);
+            self.bots[k].request_move(request);
+        }
+    }
+
+    /// 离开联机对局，回到单人标题界面
+    pub fn leave_online(&mut self) {
+        self.party_mode = PartyMode::Solo;
+        self.player_count = 1;
+        self.reset();
+        self.state = GameState::Title;
+    }
+
+    /// 本局的棋盘范围（缩小的棋盘是窗口中间的一块区域）
+    pub fn board(&self) -> BoardDims {
+        self.ai_manager.board()
+    }
+
+    /// 当前分数（单人和合作是全队总分，对战是最高的个人分数）
+    pub fn score(&self) -> u32 {
+        self.party.score(&self.players)
+    }
+
+    /// 没有归属的奖励（消灭敌队、击败 Boss、盟友击杀）
+    ///
+    /// 对战时每名还在场上的玩家都得分，否则记在第 1 名玩家身上（合作时反正计入总分）
+    fn reward(&mut self, points: u32) {
+        if self.party.mode.is_versus() {
+            for player in self.players.iter_mut().filter(|p| !p.downed) {
+                player.score += points;
+            }
+        } else {
+            self.players[0].score += points;
+        }
+    }
+
+    /// 第 `i` 名玩家死亡：单人时游戏结束，多人时倒下，蛇身变成掉落食物
+    fn knock_out(&mut self, i: usize, sounds: &dyn SoundEffects) {
+        if self.party.mode == PartyMode::Solo {
+            self.state = GameState::GameOver;
+            return;
+        }
+        if self.players[i].downed {
+            return;
+        }
+        let body = self.players[i].knock_out();
+        self.ai_manager.drop_food(&body, self.game_time);
+        sounds.play_trap();
+    }
+
+    /// 从空闲格子里放一个新食物（避开玩家和果实），没有空位返回 None
+    fn place_food(&mut self) -> Option<IVec2> {
+        spawn_position(self.ai_manager.free_cells(), |p| player_at(&self.players, p), &self.fruits, &mut self.rng)
+    }
+
+    /// 第 `i` 名玩家的蛇身占满了所有能走的格子（障碍物和围栏不算）
+    fn fills_board(&self, i: usize) -> bool {
+        let mut cells: Vec<[i32; 2]> = self.players[i]
+            .snake
+            .body
+            .iter()
+            .filter(|&&p| !self.ai_manager.is_blocked_cell(p))
+            .map(|p| p.to_array())
+            .collect();
+        cells.sort_unstable();
+        cells.dedup();
+        cells.len() >= self.ai_manager.open_cells()
+    }
+
+    /// 把倒下的第 `i` 名玩家放回棋盘（出生位置被占时换一个空位），没有空位时返回 false
+    fn revive(&mut self, i: usize) -> bool {
+        let free = self.ai_manager.free_cells();
+        let board = self.board();
+        let start = start_position(board, i, self.players.len());
+        match free_spot(board, start, |p| !free.is_free(p) || player_at(&self.players, p)) {
+            Some((head, step)) => {
+                self.players[i].revive(head, step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 第 `i` 名玩家的插值系数（冰冻或不在游戏中时不插值）
+    pub fn blend(&self, i: usize) -> f32 {
+        let player = &self.players[i];
+        if self.state == GameState::Playing && !player.buff_state.frozen {
+            (player.accumulator / player.tick(self.settings.difficulty)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// 第 `i` 名玩家转向 `new_dir`：倒下、冰冻或直接掉头时忽略
+    pub fn turn(&mut self, i: usize, new_dir: IVec2) {
+        if let Some(recording) = &mut self.recording {
+            recording.turn(new_dir);
+        }
+        let player = &mut self.players[i];
+        if player.downed || player.buff_state.frozen {
+            return;
+        }
+
+        // 防止反向移动（不能直接掉头）
+        let grid = player.snake.grid;
+        if grid.is_reverse(new_dir, player.snake.dir) {
+            return;
+        }
+
+        // 眩晕效果：40% 概率方向偏移
+        let mut final_dir = new_dir;
+        if player.buff_state.dizzy_active && self.rng.gen_bool(0.4) {
+            let valid_dirs: Vec<_> = grid
+                .directions()
+                .iter()
+                .filter(|&&d| !grid.is_reverse(d, player.snake.dir))
+                .collect();
+            if !valid_dirs.is_empty() {
+                final_dir = *valid_dirs[self.rng.gen_range(0..valid_dirs.len())];
+            }
+        }
+        player.snake.dir = final_dir;
+    }
+
+    /// 推进 `dt` 秒：玩家移动、各系统更新、粒子和受伤动画
+    ///
+    /// 单机时每帧用帧时间调用；联机时每个 tick 用固定步长调用，双方结果逐位相同
+    pub fn advance(&mut self, dt: f32, sounds: &dyn SoundEffects) {
+        // --- 回放录制：回到标题或中途改了玩法设置（暂停菜单）时放弃 ---
+        if let Some(recording) = &self.recording
+            && (self.state == GameState::Title || *recording.config() != self.match_config())
+        {
+            self.recording = None;
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.frame(dt, self.state != GameState::Playing, self.topology);
+        }
+
+        // --- HTTP 机器人：开局时发 /start，蛇用机器人自选的颜色 ---
+        if !self.bots.is_empty() {
+            if self.state == GameState::Playing && self.bot_game.is_none() {
+                self.start_bots();
+            }
+            let first = self.players.len() - self.bots.len();
+            for k in 0..self.bots.len() {
+                if let Some(color) = self.bots[k].color() {
+                    let snake = &mut self.players[first + k].snake;
+                    snake.head_color = color;
+                    snake.color = Color::new(color.r * 0.7, color.g * 0.7, color.b * 0.7, 1.0);
+                }
+            }
+        }
+
+        for i in 0..self.players.len() {
+            let tick = self.players[i].tick(self.settings.difficulty);
+            self.players[i].accumulator += dt;
+            let mut steps = 0;
+            while self.players[i].accumulator >= tick && steps < 5 {
+                self.players[i].accumulator -= tick;
+
+                // 非游戏中状态和倒下的玩家跳过
+                if self.state != GameState::Playing || self.players[i].downed {
+                    break;
+                }
+
+                // 冰冻状态跳过移动
+                if self.players[i].buff_state.frozen {
+                    continue;
+                }
+
+                // 机器人还没回复时原地等（等到时限为止，之后按原方向走）
+                if let Some(k) = self.bot_of(i) {
+                    match self.bots[k].decide() {
+                        BotDecision::Move(dir) => self.turn(i, dir.to_grid()),
+                        BotDecision::Fallback => {}
+                        BotDecision::Wait => {
+                            self.players[i].accumulator = tick;
+                            break;
+                        }
+                        BotDecision::Idle => {
+                            // 重生后还没要过方向
+                            self.request_bot_move(i);
+                            self.players[i].accumulator = tick;
+                            break;
+                        }
+                    }
+                }
+
+                steps += 1;
+                let alive = self.step_player(i, sounds);
+                self.request_bot_move(i);
+                if !alive {
+                    break;
+                }
+            }
+        }
+
+        // --- 更新游戏系统 ---
+        if self.state == GameState::Playing {
+            self.game_time += dt;
+
+            // --- 收起的食物在空出位置后放回来 ---
+            if self.food == NO_FOOD
+                && let Some(food) = self.place_food()
+            {
+                self.food = food;
+            }
+
+            // --- 每名玩家的沙虫、Buff、炸弹、宠物蛋、掉血和障碍物 ---
+            for i in 0..self.players.len() {
+                if !self.players[i].downed && self.state == GameState::Playing {
+                    self.update_player(i, dt, sounds);
+                }
+            }
+
+            // --- 更新 AI 蛇决策（每名场上的玩家都是目标） ---
+            let active: Vec<usize> = (0..self.players.len()).filter(|&i| !self.players[i].downed).collect();
+            let views: Vec<PlayerView> = active.iter().map(|&i| self.players[i].view()).collect();
+            self.ai_manager.update_thinking(
+                self.food,
+                &self.fruits,
+                &self.registry,
+                &views,
+                &self.hazards,
+                &self.portal_manager.portals,
+                self.topology,
+                self.game_time,
+                dt,
+                &mut self.rng,
+            );
+
+            // --- 更新 AI 蛇移动 ---
+            let ai_result = self.ai_manager.update_movement(
+                &mut self.food,
+                &mut self.fruits,
+                &views,
+                &self.hazards,
+                &self.portal_manager.portals,
+                &mut self.particles,
+                &self.registry,
+                self.topology,
+                self.game_time,
+                dt,
+                &mut self.rng,
+            );
+
+            // 每名玩家的结果：击杀奖励、被切断、被吞掉尾巴、被撞死
+            for (report, &i) in ai_result.players.iter().zip(&active) {
+                let player = &mut self.players[i];
+                player.score += report.kill_bonus;
+                // 切割规则：被 AI 蛇撞断的尾巴变成掉落食物
+                if let Some(keep) = report.cut {
+                    self.ai_manager.sever_player(&mut player.snake, keep, self.game_time);
+                }
+                // AI 沙虫吞掉了玩家的尾巴
+                if let Some(keep) = report.devoured {
+                    player.snake.truncate(keep);
+                    sounds.play_cut();
+                }
+                // AI 蛇撞到玩家导致玩家死亡
+                if report.died {
+                    self.knock_out(i, sounds);
+                }
+            }
+            if ai_result.cuts > 0 {
+                sounds.play_cut();
+            }
+
+            // 宠物蛇吃到的东西算主人的分，没有归属的击杀奖励（盟友、挤死）按规则分给玩家
+            for &(owner, points) in &ai_result.pet_scores {
+                if let Some(player) = self.players.get_mut(owner) {
+                    player.score += points;
+                }
+            }
+            self.reward(ai_result.kill_bonus);
+
+            // 队伍事件：消灭整支敌队有奖励，被包围时警告
+            for event in &ai_result.team_events {
+                match *event {
+                    TeamEvent::Eliminated(team) if team != Team::Player => {
+                        self.reward(TEAM_WIPE_BONUS);
+                        sounds.play_power();
+                    }
+                    TeamEvent::PlayerSurrounded(_) => sounds.play_trap(),
+                    _ => {}
+                }
+            }
+
+            // Boss 事件：登场时警告，击败后有大奖励
+            for event in &ai_result.boss_events {
+                match *event {
+                    BossEvent::Appeared => sounds.play_trap(),
+                    BossEvent::Hit { .. } => sounds.play_cut(),
+                    BossEvent::Defeated { reward } => {
+                        self.reward(reward);
+                        sounds.play_victory();
+                    }
+                    BossEvent::Phase(_) => {}
+                }
+            }
+
+            // 分数达到阈值时 Boss 登场
+            if self.boss_schedule.reached(self.score()) {
+                self.ai_manager.spawn_boss(&human_cells(&self.players), &mut self.rng);
+            }
+
+            // --- 更新掉落的食物 ---
+            self.ai_manager.update_dropped_foods(self.game_time);
+
+            // --- 更新果实（移除过期并调用 on_expire 回调） ---
+            // 所有过期逻辑都在各果实的 on_expire 回调中处理
+            // 例如：蛇蛋过期时会在其 on_expire 中直接调用 ai_manager.spawn_snake()
+            // 回调作用于第一名还在场上的玩家
+            if let Some(player) = self.players.iter_mut().find(|p| !p.downed) {
+                let _expired_fruits = update_fruits_with_callbacks(
+                    &mut self.fruits,
+                    &self.registry,
+                    &mut player.snake.body,
+                    &mut player.snake.dir,
+                    player.snake.grid,
+                    &mut player.buff_state,
+                    &mut player.damage_state,
+                    &mut self.particles,
+                    &mut player.score,
+                    &mut player.combo_state,
+                    &mut self.ai_manager,
+                    &mut self.food,
+                    self.game_time,
+                    &mut self.rng,
+                );
+                // on_expire 回调可能直接修改了蛇身
+                player.snake.sync_occupancy();
+            }
+
+            // --- 果实生成（使用 FruitSpawnManager 统一管理） ---
+            // 所有生成规则都在 create_default_spawn_manager() 中声明式配置
+            let sandworm_active = self.players.iter().any(|p| !p.downed && p.buff_state.sandworm_active);
+            let players = &self.players;
+            self.spawn_manager.update(
+                &self.registry,
+                self.ai_manager.free_cells(),
+                |p| player_at(players, p),
+                players.iter().filter(|p| !p.downed).map(|p| p.snake.len()).sum(),
+                &mut self.fruits,
+                self.game_time,
+                sandworm_active,
+                dt,
+                &mut self.rng,
+            );
+
+            // --- 传送门生成和过期（规则在 create_default_portal_manager() 中配置） ---
+            self.portal_manager.update(
+                self.ai_manager.free_cells(),
+                |p| player_at(&self.players, p),
+                &self.fruits,
+                self.game_time,
+                dt,
+                &mut self.rng,
+            );
+
+            // --- 更新残影 ---
+            self
+                .afterimages
+                .retain(|a| self.game_time - a.spawn_time < 0.3);
+
+            // --- 更新 Combo 显示 ---
+            for player in &mut self.players {
+                player.combo_state.display_timer -= dt;
+            }
+
+            // --- 本地多人：限时对战重生，分出胜负时游戏结束 ---
+            for i in self.party.respawns_due(&self.players) {
+                if self.revive(i) {
+                    sounds.play_power();
+                }
+            }
+            if self.state == GameState::Playing
+                && let Some(outcome) = self.party.update(&mut self.players, dt)
+            {
+                self.outcome = Some(outcome);
+                self.state = GameState::GameOver;
+            }
+        }
+
+        // --- 更新粒子 ---
+        update_particles(&mut self.particles, dt);
+
+        // --- 更新受伤动画 ---
+        for player in self.players.iter_mut().filter(|p| p.damage_state.active) {
+            update_damage_animation(
+                &mut player.damage_state,
+                &mut player.snake,
+                &mut self.particles,
+                &mut self.blood_stains,
+                self.game_time,
+                dt,
+                &mut self.rng,
+            );
+        }
+
+        // --- 更新血迹 ---
+        update_blood_stains(&mut self.blood_stains, self.game_time);
+
+        // --- 一局结束时给机器人发 /end，结束回放录制 ---
+        if matches!(self.state, GameState::GameOver | GameState::Victory) {
+            self.end_bots();
+            if let Some(recording) = self.recording.take() {
+                self.finished_replay = Some(recording.finish());
+            }
+        }
+    }
+
+    /// 观战端看到的一帧世界
+    pub fn snapshot(&self) -> WorldSnapshot {
+        let mut snakes: Vec<SnakeSnapshot> = self
+            .players
+            .iter()
+            .map(|player| SnakeSnapshot {
+                name: player.name(),
+                role: SnakeRole::Player,
+                body: player.snake.body.iter().map(|c| c.to_array()).collect(),
+                color: color_array(player.snake.head_color),
+                score: player.score,
+                level: 0,
+                team: None,
+                downed: player.downed,
+                buffs: active_buffs(&player.buff_state),
+            })
+            .collect();
+        snakes.extend(self.ai_manager.snakes.iter().map(|snake| {
+            let (role, name) = if snake.boss.is_some() {
+                (SnakeRole::Boss, format!("Boss #{}", snake.id))
+            } else if snake.pet {
+                (SnakeRole::Pet, format!("P{} Pet #{}", snake.owner + 1, snake.id))
+            } else {
+                (SnakeRole::Ai, format!("{} #{}", snake.brain.name(), snake.id))
+            };
+            SnakeSnapshot {
+                name,
+                role,
+                body: snake.body.iter().map(|c| c.to_array()).collect(),
+                color: color_array(snake.color),
+                score: snake.progress.xp as u32,
+                level: snake.progress.level,
+                team: snake.team.map(|t| t.name().to_string()),
+                downed: false,
+                buffs: active_buffs(&snake.buff_state),
+            }
+        }));
+        WorldSnapshot {
+            time: self.game_time,
+            state: format!("{:?}", self.state),
+            mode: if self.party.mode == PartyMode::Solo {
+                self.mode.name().to_string()
+            } else {
+                format!("{} {}", self.mode.name(), self.party.mode.name())
+            },
+            map: self.maps[self.map_index].name.to_string(),
+            grid: self.grid.name().to_string(),
+            topology: self.topology.name().to_string(),
+            food: self.food.to_array(),
+            fruits: self
+                .fruits
+                .iter()
+                .map(|f| FruitSnapshot {
+                    pos: f.pos.to_array(),
+                    kind: f.type_id.to_string(),
+                    spawn_time: f.spawn_time,
+                    lifetime: f.lifetime,
+                })
+                .collect(),
+            dropped: self
+                .ai_manager
+                .dropped_foods
+                .iter()
+                .map(|d| DroppedSnapshot { pos: d.pos.to_array(), spawn_time: d.spawn_time, lifetime: d.lifetime })
+                .collect(),
+            walls: self
+                .hazards
+                .solid_cells(self.game_time)
+                .iter()
+                .chain(&self.settings.board_size.fence_cells())
+                .map(|c| c.to_array())
+                .collect(),
+            portals: self
+                .portal_manager
+                .portals
+                .iter()
+                .map(|p| PortalSnapshot { a: p.pos_a.to_array(), b: p.pos_b.to_array(), color: color_array(p.color) })
+                .collect(),
+            snakes,
+            ..Default::default()
+        }
+    }
+
+    /// 会影响后续模拟的状态的哈希（联机时定期和对方比对）
+    pub fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        for player in &self.players {
+            for &cell in &player.snake.body {
+                hasher.write_ivec2(cell);
+            }
+            hasher.write_ivec2(player.snake.dir);
+            hasher.write_u64(u64::from(player.score));
+            hasher.write(&[u8::from(player.downed)]);
+            hasher.write_f32(player.accumulator);
+        }
+        hasher.write_ivec2(self.food);
+        for fruit in &self.fruits {
+            hasher.write_ivec2(fruit.pos);
+            hasher.write(fruit.type_id.as_bytes());
+        }
+        for snake in &self.ai_manager.snakes {
+            for &cell in &snake.body {
+                hasher.write_ivec2(cell);
+            }
+        }
+        for food in &self.ai_manager.dropped_foods {
+            hasher.write_ivec2(food.pos);
+        }
+        hasher.write_f32(self.game_time);
+        hasher.write_u64(self.rng.clone().next_u64());
+        hasher.finish()
+    }
+
+    /// 第 `i` 名玩家移动一格，结算碰撞、食物和果实；玩家死亡或通关时返回 false
+    fn step_player(&mut self, i: usize, sounds: &dyn SoundEffects) -> bool {
+        let player = &mut self.players[i];
+
+        // --- 速度模式残影（减少动态效果时不生成） ---
+        if player.buff_state.speed_active && !self.settings.reduced_motion {
+            self.afterimages.push(Afterimage {
+                positions: player.snake.body.iter().copied().collect(),
+                alpha: 0.4,
+                spawn_time: self.game_time,
+            });
+        }
+
+        // --- 沙虫模式跳过正常移动 ---
+        if player.buff_state.sandworm_phase != SandwormPhase::None {
+            return true;
+        }
+
+        // --- 移动蛇 ---
+        // 护盾/幽灵/沙虫模式可穿过自己
+        let can_pass_self = player.buff_state.can_pass_through();
+        // 八方向模式：斜向挤过自己、其他玩家或 AI 蛇的身体之间同样致命
+        let (before, rest) = self.players.split_at_mut(i);
+        let Some((player, after)) = rest.split_first_mut() else { return false };
+        let players_collide = self.party.players_collide(self.ai_manager.friendly_fire);
+        let others = |p: IVec2| {
+            self.ai_manager.blocks_player(p)
+                || (players_collide && before.iter().chain(after.iter()).any(|o| o.solid_at(p)))
+        };
+        let teleported = player.snake.pending_portal.is_some();
+        let new_head = match player.snake.move_forward_among(self.topology, can_pass_self, others) {
+            MoveResult::Normal(new_head) => new_head,
+            // 碰撞处理
+            MoveResult::WallCollision | MoveResult::SelfCollision => {
+                if player.buff_state.can_pass_through() {
+                    return true;
+                }
+                self.knock_out(i, sounds);
+                return false;
+            }
+        };
+
+        // 检查动态障碍物 - 规则与撞墙相同
+        if check_hazard_collision(new_head, &self.hazards, self.game_time) && !player.buff_state.can_pass_through() {
+            self.knock_out(i, sounds);
+            return false;
+        }
+
+        // 检查传送门 - 蛇头踏入入口，下一步从出口出现，身体依次跟随穿过
+        // （刚从出口出来的这一步不会再被传回去）
+        if !teleported && let Some(exit) = self.portal_manager.find_exit(new_head, player.snake.dir) {
+            player.snake.enter_portal(exit);
+        }
+
+        // 检查食物
+        if new_head == self.food {
+            player.score += 1;
+            player.snake.grow();
+            sounds.play_eat();
+            // 合作：吃到食物救起倒下最久的队友
+            if let Some(partner) = self.party.rescue_target(&self.players)
+                && self.revive(partner)
+            {
+                sounds.play_power();
+            }
+            // 蛇身占满了棋盘才算通关；只是没有空位放食物时（AI 蛇和果实占着）先收起食物，接着玩
+            if self.fills_board(i) {
+                self.players[i].score += VICTORY_BONUS;
+                self.state = GameState::Victory;
+                return false;
+            }
+            self.food = self.place_food().unwrap_or(NO_FOOD);
+        }
+
+        // 检查果实
+        let player = &mut self.players[i];
+        if let Some(idx) = check_fruit_collision(new_head, &self.fruits) {
+            let fruit = self.fruits.remove(idx);
+
+            // 蛇蛋被吃掉 = 阻止孵化，不生成 AI 蛇（盟友编排下孵化一条盟友，对战时没有盟友）
+            // AI 蛇只在蛇蛋过期时自动生成
+            if fruit.type_id == "snake_egg"
+                && self.settings.teams == TeamMode::Allies
+                && !self.party.mode.is_versus()
+                && self.ai_manager.spawn_ally(player.snake.body_slice(), &mut self.rng)
+            {
+                sounds.play_power();
+            }
+
+            update_combo(&mut player.combo_state, self.game_time);
+
+            // 果实效果只结束这名玩家
+            let mut state = GameState::Playing;
+            handle_fruit_effect(
+                &fruit,
+                &self.registry,
+                &mut player.snake,
+                &mut player.buff_state,
+                &mut player.damage_state,
+                &mut self.particles,
+                &mut player.score,
+                &mut player.combo_state,
+                &mut state,
+                &mut self.rng,
+                self.game_time,
+                sounds,
+            );
+            if state == GameState::GameOver {
+                self.knock_out(i, sounds);
+                return false;
+            }
+        }
+
+        // 检查是否吃到 AI 蛇（或倒下的玩家）掉落的食物
+        let player = &mut self.players[i];
+        let eaten = self.ai_manager.check_player_eat_dropped(new_head);
+        if eaten > 0 {
+            player.score += eaten;
+            player.snake.grow();
+            sounds.play_eat();
+        }
+
+        // 撞上 Boss 的蛇头（弱点）：Boss 扣血，玩家不受影响（随 AI 更新报告 Hit 事件）
+        self.ai_manager.hit_boss_head(new_head, &mut self.particles, &mut self.rng);
+
+        // 撞上宠物蛇：宠物蛇死亡，玩家不受影响
+        if self.ai_manager.crush_pet(new_head, &mut self.particles, &mut self.rng) {
+            sounds.play_trap();
+        }
+
+        // 检查是否撞到 AI 蛇身体（包括头对头）
+        // 切割规则下切断 AI 蛇，头对头按长度结算（关闭误伤时穿过盟友）
+        if !player.buff_state.can_pass_through() && self.ai_manager.blocks_player(new_head) {
+            if self.ai_manager.combat == CombatRules::Cutting {
+                let strike = self.ai_manager.player_strike(
+                    new_head,
+                    player.snake.len(),
+                    &mut self.particles,
+                    self.game_time,
+                    &mut self.rng,
+                );
+                player.score += strike.kill_bonus;
+                if strike.severed > 0 || strike.kill_bonus > 0 {
+                    sounds.play_cut();
+                }
+                if strike.player_died {
+                    self.knock_out(i, sounds);
+                    return false;
+                }
+            } else {
+                self.knock_out(i, sounds);
+                return false;
+            }
+        }
+
+        // 检查是否撞到其他玩家（规则同 AI 蛇）
+        let clash = self.party.clash(&self.players, i, new_head, self.ai_manager.combat, self.ai_manager.friendly_fire);
+        match clash {
+            Some(PlayerClash::Lethal) => {
+                self.knock_out(i, sounds);
+                return false;
+            }
+            Some(PlayerClash::Sever { victim, keep }) => {
+                self.ai_manager.sever_player(&mut self.players[victim].snake, keep, self.game_time);
+                sounds.play_cut();
+            }
+            Some(PlayerClash::HeadOn { victim, result }) => {
+                sounds.play_cut();
+                if !result.defender_survives() {
+                    self.knock_out(victim, sounds);
+                }
+                if !result.attacker_survives() {
+                    self.knock_out(i, sounds);
+                    return false;
+                }
+            }
+            None => {}
+        }
+        true
+    }
+
+    /// 第 `i` 名玩家每帧的状态更新：沙虫、Buff、炸弹、宠物蛋、后遗症掉血、障碍物和冰冻粒子
+    fn update_player(&mut self, i: usize, dt: f32, sounds: &dyn SoundEffects) {
+        // --- 更新沙虫模式 ---
+        let player = &mut self.players[i];
+        let sandworm_result = update_sandworm_mode(
+            &mut player.snake,
+            &mut player.buff_state,
+            &mut self.fruits,
+            &mut self.food,
+            &mut self.particles,
+            dt,
+            &mut self.rng,
+        );
+        player.score += sandworm_result.bonus_score;
+        // 沙虫结束时按完整的阻挡格子（含障碍物和围栏）重新放置食物
+        if sandworm_result.need_respawn_food
+            && let Some(food) = self.place_food()
+        {
+            self.food = food;
+        }
+
+        // 沙虫模式吞噬 AI 蛇
+        let player = &mut self.players[i];
+        if player.buff_state.sandworm_phase != SandwormPhase::None {
+            player.score += self.ai_manager.sandworm_devour(
+                player.snake.body_slice(),
+                &mut self.particles,
+                &mut self.rng,
+            );
+        }
+
+        // --- 更新 Buff 计时器 ---
+        player.buff_state.update(dt);
+
+        // --- 更新炸弹状态（使用 BombManager） ---
+        let bomb_result = BombManager::update(
+            &mut player.buff_state,
+            player.snake.body_slice(),
+            dt,
+            &mut self.rng,
+        );
+
+        // 处理炸弹爆炸结果
+        if let Some(truncate_pos) = bomb_result.truncate_to {
+            // 添加爆炸粒子
+            self.particles.extend(bomb_result.particles);
+            // 截断蛇身
+            player.snake.truncate(truncate_pos);
+            // 检查游戏结束
+            if bomb_result.game_over || player.snake.body.len() < 3 {
+                self.knock_out(i, sounds);
+                return;
+            }
+        }
+
+        // --- 宠物蛋到达蛇尾时孵化宠物蛇（宠物蛇的分数记在这名玩家身上） ---
+        if player.buff_state.update_egg(dt, player.snake.len()) {
+            if self.ai_manager.spawn_pet(i, &player.snake, self.topology) {
+                sounds.play_power();
+            }
+            let tail = player.snake.body.back().copied().unwrap_or_default();
+            spawn_egg_hatch_particles(&mut self.particles, tail, &mut self.rng);
+        }
+
+        // --- 炸弹后遗症掉血（使用 BombManager） ---
+        let (need_bleed, bleed_game_over) = BombManager::update_after_effect(
+            &mut player.buff_state,
+            player.snake.body.len(),
+            dt,
+        );
+        if need_bleed {
+            // 获取尾部位置用于生成血迹
+            if let Some(&tail_pos) = player.snake.body.back() {
+                let center = vec2(
+                    tail_pos.x as f32 * CELL + CELL / 2.0,
+                    tail_pos.y as f32 * CELL + CELL / 2.0,
+                );
+                // 生成血液粒子
+                spawn_blood_particles(&mut self.particles, center, &mut self.rng);
+                // 留下血迹
+                self.blood_stains.push(BloodStain {
+                    pos: tail_pos,
+                    spawn_time: self.game_time,
+                    lifetime: 5.0,
+                    size: self.rng.gen_range(0.6..1.0),
+                    alpha: self.rng.gen_range(0.5..0.8),
+                });
+            }
+            player.snake.pop_tail();
+        }
+        if bleed_game_over {
+            self.knock_out(i, sounds);
+            return;
+        }
+
+        // --- 动态障碍物扫到蛇头（例如定时墙在蛇头上关闭） ---
+        if !player.buff_state.can_pass_through()
+            && check_hazard_collision(player.snake.head(), &self.hazards, self.game_time)
+        {
+            self.knock_out(i, sounds);
+            return;
+        }
+
+        // --- 冰冻粒子效果 ---
+        if player.buff_state.frozen && self.rng.gen_bool(0.3) {
+            spawn_freeze_particles(&mut self.particles, player.snake.body_slice(), &mut self.rng);
+        }
+    }
+}
+
+impl Default for GameWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 某格上有没有场上玩家的蛇身（空闲格子索引只记录 AI 蛇和固定阻挡）
+fn player_at(players: &[Player], pos: IVec2) -> bool {
+    players.iter().any(|p| !p.downed && p.snake.occupies(pos))
+}
+
+/// 场上所有玩家的蛇身（倒下的玩家不在棋盘上）
+pub fn human_cells(players: &[Player]) -> Vec<IVec2> {
+    players.iter().filter(|p| !p.downed).flat_map(|p| p.snake.body.iter().copied()).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use crate::audio::Silence;
+    use crate::constants::{GRID_H, GRID_W};
+    use crate::net::{Lobby, NetSession, TransportKind};
+    use crate::types::BoardSize;
+
+    /// 轮询大厅直到握手完成
+    fn connect(lobby: &mut Lobby) -> NetSession {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(session) = lobby.poll().unwrap() {
+                return session;
+            }
+            assert!(Instant::now() < deadline, "handshake timed out");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// 按会话下发的设置开一局，用随机输入跑 `ticks` 个 tick，返回每次比对的哈希
+    fn play_online(mut session: NetSession, ticks: u64) -> Result<Vec<u64>, NetError> {
+        let mut world = GameWorld::headless();
+        world.start_online(session.config())?;
+        let mut rng = GameRng::seed_from_u64(session.local() as u64);
+        let mut hashes = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while session.lockstep().tick() < ticks {
+            assert!(Instant::now() < deadline, "lockstep stalled at {}", session.lockstep().tick());
+            session.poll()?;
+            let dirs = world.grid.directions();
+            let turn = rng.gen_bool(0.1).then(|| dirs[rng.gen_range(0..dirs.len())]);
+            session.push_input(NetInput::turn(turn))?;
+            while let Some((tick, inputs)) = session.next_tick() {
+                let hash = world.advance_tick(&inputs, &Silence);
+                session.record_hash(tick, hash)?;
+                if session.lockstep().hash_due(tick) {
+                    hashes.push(hash);
+                }
+                if tick + 1 == ticks {
+                    break;
+                }
+            }
+            std::thread::sleep(Duration::from_micros(200));
+        }
+        let linger = Instant::now() + Duration::from_secs(2);
+        while session.lockstep().has_unacked() && Instant::now() < linger {
+            let _ = session.poll();
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        Ok(hashes)
+    }
+
+    #[test]
+    fn test_two_worlds_stay_in_lockstep_over_loopback() {
+        let config = MatchConfig::new(1234, GameMode::Arena, 0, GridKind::Square, &Settings::default())
+            .with_input_delay(2)
+            .with_hash_interval(30);
+        let mut host = Lobby::host("127.0.0.1:0", TransportKind::Udp, config).unwrap();
+        let addr = host.local_addr().unwrap().to_string();
+        let guest = std::thread::spawn(move || {
+            let mut lobby = Lobby::join(&addr, TransportKind::Udp, "Guest").unwrap();
+            play_online(connect(&mut lobby), 600)
+        });
+        let host_hashes = play_online(connect(&mut host), 600).unwrap();
+        let guest_hashes = guest.join().unwrap().unwrap();
+        assert_eq!(host_hashes.len(), 20);
+        assert_eq!(host_hashes, guest_hashes);
+        // 世界确实在变
+        assert!(host_hashes.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    /// 单人开一局，去掉 AI 蛇、果实和传送门
+    fn bare_world() -> GameWorld {
+        let mut world = GameWorld::headless();
+        world.reset();
+        world.ai_manager.snakes.clear();
+        world.ai_manager.rebuild_occupancy();
+        world.fruits.clear();
+        world.portal_manager.portals.clear();
+        world
+    }
+
+    #[test]
+    fn test_no_room_for_food_is_not_victory() {
+        let mut world = bare_world();
+        let snake = &world.players[0].snake;
+        world.food = snake.head() + snake.dir;
+        assert!(world.ai_manager.free_cells().is_free(world.food));
+        // 果实占满了其余的空格（包括蛇尾走开后空出的格子），玩家只占棋盘的一小部分
+        let food = world.food;
+        let rest: Vec<IVec2> = world.ai_manager.free_cells().cells().iter().copied().filter(|&p| p != food).collect();
+        world.fruits = rest.into_iter().map(|p| Fruit::new(p, "normal", 0.0, 0.0)).collect();
+
+        assert!(world.step_player(0, &Silence));
+        assert_eq!(world.state, GameState::Playing);
+        assert_eq!(world.players[0].score, 1);
+        assert_eq!(world.food, NO_FOOD);
+
+        // 空出一格后食物放回来
+        let freed = world.fruits.pop().unwrap().pos;
+        world.advance(0.0, &Silence);
+        assert_eq!(world.food, freed);
+    }
+
+    #[test]
+    fn test_filling_the_board_is_victory() {
+        let mut world = bare_world();
+        world.hazards = HazardManager::new();
+        world.ai_manager.set_blocked_cells(&[]);
+        // 蛇形扫过整个棋盘，蛇身占满除最后一格以外的所有格子（尾巴刚长了一节）
+        let path: Vec<IVec2> = (0..GRID_H)
+            .flat_map(|y| (0..GRID_W).map(move |x| ivec2(if y % 2 == 0 { x } else { GRID_W - 1 - x }, y)))
+            .collect();
+        let n = path.len();
+        let snake = &mut world.players[0].snake;
+        snake.set_body((0..n - 1).rev().map(|k| path[k]).chain([path[0]]));
+        snake.dir = path[n - 1] - path[n - 2];
+        world.food = path[n - 1];
+
+        assert!(!world.step_player(0, &Silence));
+        assert_eq!(world.state, GameState::Victory);
+        assert_eq!(world.players[0].score, 1 + VICTORY_BONUS);
+    }
+
+    /// 小棋盘上把蛇头放在右边界前一格，朝右
+    fn small_board_world(topology: Topology) -> GameWorld {
+        let mut world = GameWorld::headless();
+        world.settings.board_size = BoardSize::Small;
+        world.topology = topology;
+        world.reset();
+        world.ai_manager.snakes.clear();
+        world.ai_manager.rebuild_occupancy();
+        world.fruits.clear();
+        world.portal_manager.portals.clear();
+        world.hazards = HazardManager::new();
+        let board = world.board();
+        let head = ivec2(board.end().x - 1, board.center().y);
+        world.players[0].snake.reset_at(head, ivec2(1, 0));
+        world.food = board.origin;
+        world
+    }
+
+    #[test]
+    fn test_torus_wraps_at_the_small_board_edge() {
+        let mut world = small_board_world(Topology::Torus);
+        let board = world.board();
+        assert_eq!(board, BoardSize::Small.dims());
+        let row = board.center().y;
+
+        assert!(world.step_player(0, &Silence));
+        assert_eq!(world.players[0].snake.head(), ivec2(board.origin.x, row));
+
+        // 竖直方向同样在小棋盘的上边界环绕到下边界
+        world.players[0].snake.reset_at(ivec2(board.center().x, board.origin.y), ivec2(0, -1));
+        assert!(world.step_player(0, &Silence));
+        assert_eq!(world.players[0].snake.head(), ivec2(board.center().x, board.end().y - 1));
+    }
+
+    #[test]
+    fn test_shield_cannot_leave_the_small_board() {
+        let mut world = small_board_world(Topology::Bounded);
+        let board = world.board();
+        world.players[0].buff_state.activate_shield();
+        for _ in 0..board.width * 2 {
+            assert!(world.step_player(0, &Silence));
+            assert!(world.players[0].snake.body.iter().all(|&p| board.contains(p)));
+        }
+
+        // 没有护盾时撞上小棋盘的边界
+        let mut world = small_board_world(Topology::Bounded);
+        assert!(!world.step_player(0, &Silence));
+        assert_eq!(world.state, GameState::GameOver);
+    }
+}
@@ -0,0 +1,16 @@
+//! 游戏世界模块
+//!
+//! 一局游戏的全部状态和逐帧推进逻辑，不依赖窗口和键盘：游戏主程序每帧读输入、
+//! 推进世界再绘制；排行榜服务器用同一份代码无界面重放单人对局来复核分数。
+//!
+//! ```text
+//! world/
+//! ├── game_world.rs   # GameWorld：玩家、AI、果实、传送门、排行榜、机器人
+//! └── replay.rs       # 单人对局回放：对局设置 + 逐帧输入，无界面重放
+//! ```
+
+pub mod game_world;
+pub mod replay;
+
+pub use game_world::*;
+pub use replay::*;
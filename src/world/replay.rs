@@ -0,0 +1,276 @@
+//! 单人对局回放
+//!
+//! 一局单人游戏完全由对局设置（[`MatchConfig`]，含随机种子）、开局时的边界拓扑和逐帧输入决定。
+//! 每帧记录：
+//!
+//! - 帧时间 `dt`（单机按帧时间推进，所以要逐帧记下）
+//! - 这一帧玩家按下的方向
+//! - 是否暂停（暂停菜单里世界照样推进粒子和受伤动画，也会用到随机数）
+//! - 中途切换的边界拓扑
+//!
+//! 用同样的设置和输入重新推进 [`GameWorld`] 得到逐位相同的一局，排行榜服务器据此复核分数。
+//! 回放文件是一个 JSON 对象，保存在排行榜文件旁边的 [`REPLAY_DIR`] 目录，文件名是种子。
+
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::audio::Silence;
+use crate::net::MatchConfig;
+use crate::types::{GameState, Topology};
+use super::GameWorld;
+
+/// 回放格式版本
+pub const REPLAY_VERSION: u32 = 1;
+
+/// 回放目录名（在排行榜文件所在的目录下）
+pub const REPLAY_DIR: &str = "snake2d_replays";
+
+/// 回放错误
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ReplayError {
+    /// 无法解析的回放文件
+    Format(String),
+    /// 不支持的回放版本
+    Version(u32),
+    /// 回放里的设置、地图或拓扑不存在
+    Setting(String),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Format(msg) => write!(f, "bad replay: {}", msg),
+            ReplayError::Version(version) => write!(f, "unsupported replay version {}", version),
+            ReplayError::Setting(msg) => write!(f, "bad replay setting: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// 一帧的输入
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// 帧时间（秒）
+    pub dt: f32,
+    /// 这一帧按下的方向
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub turn: Option<[i32; 2]>,
+    /// 这一帧处于暂停（或暂停菜单里）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub paused: bool,
+    /// 这一帧切换到的边界拓扑（按显示名称）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology: Option<String>,
+}
+
+/// 一局单人游戏的回放
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    /// 种子、模式、地图、棋盘几何和玩法设置
+    pub config: MatchConfig,
+    /// 开局时的边界拓扑（按显示名称）
+    pub start_topology: String,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    /// 开始一段空回放
+    pub fn new(config: MatchConfig, topology: Topology) -> Self {
+        Replay { version: REPLAY_VERSION, config, start_topology: topology.name().to_string(), frames: vec![] }
+    }
+
+    /// 开局时的边界拓扑
+    pub fn topology(&self) -> Result<Topology, ReplayError> {
+        parse_topology(&self.start_topology)
+    }
+
+    /// 编码成 JSON（回放文件的内容，回放哈希按这些字节计算）
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("replays always serialize")
+    }
+
+    /// 从 JSON 解码
+    pub fn from_json(text: &str) -> Result<Self, ReplayError> {
+        let replay: Replay = serde_json::from_str(text).map_err(|err| ReplayError::Format(err.to_string()))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(replay.version));
+        }
+        Ok(replay)
+    }
+
+    /// 保存到 `dir`（不存在时创建），文件名是种子，返回文件路径
+    ///
+    /// 和排行榜一样先写临时文件再改名，写到一半崩溃不会留下残缺的回放
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{:016x}.json", self.config.seed));
+        let tmp = path.with_extension("json.tmp");
+        {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(self.to_json().as_bytes())?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, &path)?;
+        Ok(path)
+    }
+
+    /// 无界面重放，返回重放结束时的世界（对局结束后剩下的帧不再推进）
+    pub fn play(&self) -> Result<GameWorld, ReplayError> {
+        let mut world = GameWorld::headless();
+        world.start_replay(self)?;
+        world.recording = None;
+        for frame in &self.frames {
+            if matches!(world.state, GameState::GameOver | GameState::Victory) {
+                break;
+            }
+            if let Some(name) = &frame.topology {
+                world.topology = parse_topology(name)?;
+            }
+            world.state = if frame.paused { GameState::Paused } else { GameState::Playing };
+            if let Some([x, y]) = frame.turn {
+                world.turn(0, ivec2(x, y));
+            }
+            world.advance(frame.dt, &Silence);
+        }
+        Ok(world)
+    }
+}
+
+/// 录制中的回放
+#[derive(Clone, Debug)]
+pub struct ReplayRecorder {
+    replay: Replay,
+    /// 这一帧按下、还没记进帧里的方向
+    turn: Option<IVec2>,
+    /// 上一帧的边界拓扑
+    topology: Topology,
+}
+
+impl ReplayRecorder {
+    /// 按开局的对局设置和边界拓扑开始录制
+    pub fn new(config: MatchConfig, topology: Topology) -> Self {
+        ReplayRecorder { replay: Replay::new(config, topology), turn: None, topology }
+    }
+
+    /// 录制中的对局设置
+    pub fn config(&self) -> &MatchConfig {
+        &self.replay.config
+    }
+
+    /// 已经录下的帧数
+    pub fn frames(&self) -> usize {
+        self.replay.frames.len()
+    }
+
+    /// 记下玩家按下的方向（在这一帧推进世界之前）
+    pub fn turn(&mut self, dir: IVec2) {
+        self.turn = Some(dir);
+    }
+
+    /// 世界推进一帧
+    pub fn frame(&mut self, dt: f32, paused: bool, topology: Topology) {
+        let switched = (topology != self.topology).then(|| topology.name().to_string());
+        self.topology = topology;
+        self.replay.frames.push(ReplayFrame { dt, turn: self.turn.take().map(|d| d.to_array()), paused, topology: switched });
+    }
+
+    /// 结束录制
+    pub fn finish(self) -> Replay {
+        self.replay
+    }
+}
+
+/// 排行榜文件旁边的回放目录
+pub fn replay_dir(leaderboard_path: &Path) -> PathBuf {
+    leaderboard_path.parent().unwrap_or(Path::new("")).join(REPLAY_DIR)
+}
+
+/// 按显示名称找边界拓扑
+fn parse_topology(name: &str) -> Result<Topology, ReplayError> {
+    Topology::ALL
+        .into_iter()
+        .find(|t| t.name() == name)
+        .ok_or_else(|| ReplayError::Setting(format!("unknown topology {:?}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Difficulty, GameMode};
+
+    /// 固定种子（随机种子偶尔一口没吃就撞死，测试时好时坏）
+    const SEED: u64 = 1;
+
+    /// 用固定种子单人开一局，朝食物走 `frames` 帧（帧时间有快有慢，中间暂停一会儿、切换一次拓扑）
+    fn play_live(mode: GameMode, frames: usize) -> GameWorld {
+        let mut world = GameWorld::headless();
+        world.mode = mode;
+        world.settings.difficulty = Difficulty::Hard;
+        world.reset_seeded(SEED);
+        for f in 0..frames {
+            if matches!(world.state, GameState::GameOver | GameState::Victory) {
+                break;
+            }
+            world.state = if (50..80).contains(&f) { GameState::Paused } else { GameState::Playing };
+            if f == 100 {
+                world.topology = world.topology.next();
+            }
+            if world.state == GameState::Playing {
+                let head = world.players[0].snake.head();
+                let delta = world.food - head;
+                let dir = if delta.x != 0 { ivec2(delta.x.signum(), 0) } else { ivec2(0, delta.y.signum()) };
+                world.turn(0, dir);
+            }
+            world.advance(if f % 3 == 0 { 1.0 / 30.0 } else { 1.0 / 60.0 }, &Silence);
+        }
+        world
+    }
+
+    #[test]
+    fn test_replay_reproduces_the_game() {
+        for mode in [GameMode::Classic, GameMode::Arena] {
+            let mut live = play_live(mode, 1500);
+            assert!(live.score() > 0);
+            let replay = match live.finished_replay.take() {
+                Some(replay) => replay,
+                None => live.recording.take().expect("solo games are recorded").finish(),
+            };
+            assert!(replay.frames.iter().any(|f| f.paused));
+            assert!(replay.frames.iter().any(|f| f.topology.is_some()));
+
+            let replayed = Replay::from_json(&replay.to_json()).unwrap().play().unwrap();
+            assert_eq!(replayed.score(), live.score());
+            assert_eq!(replayed.state_hash(), live.state_hash());
+        }
+    }
+
+    #[test]
+    fn test_changing_rules_mid_game_drops_the_recording() {
+        let mut world = GameWorld::headless();
+        world.settings.difficulty = Difficulty::Easy;
+        world.reset();
+        world.advance(1.0 / 60.0, &Silence);
+        assert_eq!(world.recording.as_ref().map(ReplayRecorder::frames), Some(1));
+
+        world.settings.difficulty = Difficulty::Hard;
+        world.advance(1.0 / 60.0, &Silence);
+        assert!(world.recording.is_none());
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions_and_settings() {
+        let world = GameWorld::headless();
+        let mut replay = Replay::new(world.match_config(), Topology::default());
+        replay.version = REPLAY_VERSION + 1;
+        assert_eq!(Replay::from_json(&replay.to_json()), Err(ReplayError::Version(REPLAY_VERSION + 1)));
+
+        replay.version = REPLAY_VERSION;
+        replay.config.map = usize::MAX;
+        assert!(matches!(replay.play(), Err(ReplayError::Setting(_))));
+    }
+}